@@ -0,0 +1,28 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use waysensor_rs_core::procfs;
+
+const FILES: &[&str] = &["/proc/stat", "/proc/meminfo", "/proc/loadavg"];
+
+fn bench_proc_reads(c: &mut Criterion) {
+    for path in FILES {
+        let mut group = c.benchmark_group(*path);
+
+        group.bench_function("read_to_string", |b| {
+            b.iter(|| {
+                let content = std::fs::read_to_string(black_box(path)).unwrap();
+                black_box(content.lines().count())
+            })
+        });
+
+        group.bench_function("procfs_read_with", |b| {
+            b.iter(|| {
+                procfs::read_with(black_box(path), |content| black_box(content.lines().count())).unwrap()
+            })
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_proc_reads);
+criterion_main!(benches);
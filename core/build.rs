@@ -0,0 +1,30 @@
+//! Records the git revision, target and profile for `build_info`.
+
+use std::{env, path::Path, process::Command};
+
+fn main() {
+    let git = |args: &[&str]| Command::new("git")
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+
+    let hash = git(&["rev-parse", "--short=12", "HEAD"]).unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=WAYSENSOR_GIT_HASH={}", hash);
+    println!("cargo:rustc-env=WAYSENSOR_TARGET={}", env::var("TARGET").unwrap_or_default());
+    println!("cargo:rustc-env=WAYSENSOR_PROFILE={}", env::var("PROFILE").unwrap_or_default());
+
+    // Rebuild when HEAD moves; outside a checkout fall back to cargo's default
+    println!("cargo:rerun-if-changed=build.rs");
+    if let Some(git_dir) = git(&["rev-parse", "--absolute-git-dir"]) {
+        let git_dir = Path::new(&git_dir);
+        println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+        if let Some(head_ref) = git(&["symbolic-ref", "-q", "HEAD"]) {
+            let ref_path = git_dir.join(head_ref);
+            if ref_path.exists() {
+                println!("cargo:rerun-if-changed={}", ref_path.display());
+            }
+        }
+    }
+}
@@ -172,12 +172,18 @@ pub struct GlobalConfig {
     /// Update interval in milliseconds
     #[serde(default = "default_update_interval")]
     pub update_interval: u64,
+    /// Measurement units shown by sensors that convert them
+    #[serde(default)]
+    pub units: UnitSystem,
     /// Visual enhancement settings
     #[serde(default)]
     pub visuals: VisualConfig,
     /// Sensor-specific configurations
     #[serde(default)]
     pub sensors: HashMap<String, serde_json::Value>,
+    /// FIFO or file that also receives every output as versioned JSON lines
+    #[serde(default)]
+    pub versioned_output: Option<PathBuf>,
 }
 
 /// Icon configuration for different sensor types
@@ -204,6 +210,15 @@ pub struct IconConfig {
     /// Network Ethernet icon
     #[serde(default = "default_network_ethernet_icon")]
     pub network_ethernet: String,
+    /// Network latency icon
+    #[serde(default = "default_network_latency_icon")]
+    pub network_latency: String,
+    /// Metered connection icon
+    #[serde(default = "default_network_metered_icon")]
+    pub network_metered: String,
+    /// VPN tunnel icon
+    #[serde(default = "default_vpn_icon")]
+    pub vpn: String,
     /// Battery full icon
     #[serde(default = "default_battery_full_icon")]
     pub battery_full: String,
@@ -234,6 +249,87 @@ pub struct IconConfig {
     /// GPU sensor icon
     #[serde(default = "default_gpu_icon")]
     pub gpu: String,
+    /// Fan speed icon
+    #[serde(default = "default_fan_icon")]
+    pub fan: String,
+    /// Sleep readiness icon
+    #[serde(default = "default_sleep_icon")]
+    pub sleep: String,
+    /// Audio volume icon
+    #[serde(default = "default_volume_icon")]
+    pub volume: String,
+    /// Muted audio icon
+    #[serde(default = "default_volume_muted_icon")]
+    pub volume_muted: String,
+    /// Microphone in use icon
+    #[serde(default = "default_microphone_icon")]
+    pub microphone: String,
+    /// Display brightness icon
+    #[serde(default = "default_backlight_icon")]
+    pub backlight: String,
+    /// Bluetooth on icon
+    #[serde(default = "default_bluetooth_icon")]
+    pub bluetooth: String,
+    /// Bluetooth off icon
+    #[serde(default = "default_bluetooth_off_icon")]
+    pub bluetooth_off: String,
+    /// Bluetooth device connected icon
+    #[serde(default = "default_bluetooth_connected_icon")]
+    pub bluetooth_connected: String,
+    /// Media playing icon
+    #[serde(default = "default_media_playing_icon")]
+    pub media_playing: String,
+    /// Media paused icon
+    #[serde(default = "default_media_paused_icon")]
+    pub media_paused: String,
+    /// Pending package updates icon
+    #[serde(default = "default_updates_icon")]
+    pub updates: String,
+    /// Failed systemd units icon
+    #[serde(default = "default_failed_units_icon")]
+    pub failed_units: String,
+    /// Uptime icon
+    #[serde(default = "default_uptime_icon")]
+    pub uptime: String,
+    /// Reboot required icon
+    #[serde(default = "default_reboot_required_icon")]
+    pub reboot_required: String,
+    /// Clear sky weather icon
+    #[serde(default = "default_weather_clear_icon")]
+    pub weather_clear: String,
+    /// Clear night sky weather icon
+    #[serde(default = "default_weather_night_icon")]
+    pub weather_night: String,
+    /// Partly cloudy weather icon
+    #[serde(default = "default_weather_partly_cloudy_icon")]
+    pub weather_partly_cloudy: String,
+    /// Overcast weather icon
+    #[serde(default = "default_weather_cloudy_icon")]
+    pub weather_cloudy: String,
+    /// Fog weather icon
+    #[serde(default = "default_weather_fog_icon")]
+    pub weather_fog: String,
+    /// Rain and drizzle weather icon
+    #[serde(default = "default_weather_rain_icon")]
+    pub weather_rain: String,
+    /// Snow and sleet weather icon
+    #[serde(default = "default_weather_snow_icon")]
+    pub weather_snow: String,
+    /// Thunderstorm weather icon
+    #[serde(default = "default_weather_thunderstorm_icon")]
+    pub weather_thunderstorm: String,
+    /// Keyboard layout icon
+    #[serde(default = "default_keyboard_icon")]
+    pub keyboard: String,
+    /// Focused window icon
+    #[serde(default = "default_window_icon")]
+    pub window: String,
+    /// Focused workspace icon
+    #[serde(default = "default_workspace_icon")]
+    pub workspace: String,
+    /// Containers icon
+    #[serde(default = "default_containers_icon")]
+    pub containers: String,
 }
 
 impl Default for IconConfig {
@@ -246,6 +342,9 @@ impl Default for IconConfig {
             network_upload: default_network_upload_icon(),
             network_wifi: default_network_wifi_icon(),
             network_ethernet: default_network_ethernet_icon(),
+            network_latency: default_network_latency_icon(),
+            network_metered: default_network_metered_icon(),
+            vpn: default_vpn_icon(),
             battery_full: default_battery_full_icon(),
             battery_three_quarters: default_battery_three_quarters_icon(),
             battery_half: default_battery_half_icon(),
@@ -256,6 +355,33 @@ impl Default for IconConfig {
             thermal_medium: default_thermal_medium_icon(),
             thermal_high: default_thermal_high_icon(),
             gpu: default_gpu_icon(),
+            fan: default_fan_icon(),
+            sleep: default_sleep_icon(),
+            volume: default_volume_icon(),
+            volume_muted: default_volume_muted_icon(),
+            microphone: default_microphone_icon(),
+            backlight: default_backlight_icon(),
+            bluetooth: default_bluetooth_icon(),
+            bluetooth_off: default_bluetooth_off_icon(),
+            bluetooth_connected: default_bluetooth_connected_icon(),
+            media_playing: default_media_playing_icon(),
+            media_paused: default_media_paused_icon(),
+            updates: default_updates_icon(),
+            failed_units: default_failed_units_icon(),
+            uptime: default_uptime_icon(),
+            reboot_required: default_reboot_required_icon(),
+            weather_clear: default_weather_clear_icon(),
+            weather_night: default_weather_night_icon(),
+            weather_partly_cloudy: default_weather_partly_cloudy_icon(),
+            weather_cloudy: default_weather_cloudy_icon(),
+            weather_fog: default_weather_fog_icon(),
+            weather_rain: default_weather_rain_icon(),
+            weather_snow: default_weather_snow_icon(),
+            weather_thunderstorm: default_weather_thunderstorm_icon(),
+            keyboard: default_keyboard_icon(),
+            window: default_window_icon(),
+            workspace: default_workspace_icon(),
+            containers: default_containers_icon(),
         }
     }
 }
@@ -282,6 +408,15 @@ fn default_network_wifi_icon() -> String {
 fn default_network_ethernet_icon() -> String {
     "\u{ef44}".to_string()
 } //
+fn default_network_latency_icon() -> String {
+    "\u{f04c5}".to_string()
+} // 󰓅
+fn default_network_metered_icon() -> String {
+    "\u{f0fb8}".to_string()
+} // 󰾸
+fn default_vpn_icon() -> String {
+    "\u{f099d}".to_string()
+} // 󰦝
 fn default_battery_full_icon() -> String {
     "\u{f0079}".to_string()
 } //
@@ -312,6 +447,87 @@ fn default_thermal_high_icon() -> String {
 fn default_gpu_icon() -> String {
     "\u{f08ae}".to_string()
 } //
+fn default_fan_icon() -> String {
+    "\u{f0210}".to_string()
+} //
+fn default_sleep_icon() -> String {
+    "\u{f04b2}".to_string()
+} //
+fn default_volume_icon() -> String {
+    "\u{f057e}".to_string()
+} //
+fn default_volume_muted_icon() -> String {
+    "\u{f0581}".to_string()
+} //
+fn default_microphone_icon() -> String {
+    "\u{f036c}".to_string()
+} //
+fn default_backlight_icon() -> String {
+    "\u{f00df}".to_string()
+} //
+fn default_bluetooth_icon() -> String {
+    "\u{f00af}".to_string()
+} //
+fn default_bluetooth_off_icon() -> String {
+    "\u{f00b2}".to_string()
+} //
+fn default_bluetooth_connected_icon() -> String {
+    "\u{f00b1}".to_string()
+} //
+fn default_media_playing_icon() -> String {
+    "\u{f040a}".to_string()
+} //
+fn default_media_paused_icon() -> String {
+    "\u{f03e4}".to_string()
+} //
+fn default_updates_icon() -> String {
+    "\u{f06b0}".to_string()
+} //
+fn default_failed_units_icon() -> String {
+    "\u{f0026}".to_string()
+} //
+fn default_uptime_icon() -> String {
+    "\u{f0150}".to_string()
+} //
+fn default_reboot_required_icon() -> String {
+    "\u{f0709}".to_string()
+} //
+fn default_weather_clear_icon() -> String {
+    "\u{f0599}".to_string()
+} //
+fn default_weather_night_icon() -> String {
+    "\u{f0594}".to_string()
+} //
+fn default_weather_partly_cloudy_icon() -> String {
+    "\u{f0595}".to_string()
+} //
+fn default_weather_cloudy_icon() -> String {
+    "\u{f0590}".to_string()
+} //
+fn default_weather_fog_icon() -> String {
+    "\u{f0591}".to_string()
+} //
+fn default_weather_rain_icon() -> String {
+    "\u{f0597}".to_string()
+} //
+fn default_weather_snow_icon() -> String {
+    "\u{f0598}".to_string()
+} //
+fn default_weather_thunderstorm_icon() -> String {
+    "\u{f0593}".to_string()
+} //
+fn default_keyboard_icon() -> String {
+    "\u{f030c}".to_string()
+} //
+fn default_window_icon() -> String {
+    "\u{f0614}".to_string()
+} //
+fn default_workspace_icon() -> String {
+    "\u{f0570}".to_string()
+} //
+fn default_containers_icon() -> String {
+    "\u{f0868}".to_string()
+} //
 
 /// Color configuration for waysensor-rs
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -357,6 +573,43 @@ impl Default for StatusColorConfig {
     }
 }
 
+impl StatusColorConfig {
+    /// Colors of `overrides` where set, falling back to `self`.
+    #[must_use]
+    pub fn merged_with(&self, overrides: &Self) -> Self {
+        let pick = |over: &Option<String>, base: &Option<String>| over.clone().or_else(|| base.clone());
+        Self {
+            excellent: pick(&overrides.excellent, &self.excellent),
+            good: pick(&overrides.good, &self.good),
+            warning: pick(&overrides.warning, &self.warning),
+            critical: pick(&overrides.critical, &self.critical),
+            unknown: pick(&overrides.unknown, &self.unknown),
+        }
+    }
+
+    /// Color for an output CSS class, resolved through the theme's class names.
+    ///
+    /// The normal state has no status color and keeps the regular text color.
+    #[must_use]
+    pub fn color_for_class(&self, class: &str, theme: &Theme) -> Option<&str> {
+        let color = if class == theme.critical {
+            &self.critical
+        } else if class == theme.warning {
+            &self.warning
+        } else if class == theme.good {
+            match &self.good {
+                Some(_) => &self.good,
+                None => &self.excellent,
+            }
+        } else if class == theme.unknown {
+            &self.unknown
+        } else {
+            return None;
+        };
+        color.as_deref()
+    }
+}
+
 /// Visual enhancement configuration
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct VisualConfig {
@@ -503,8 +756,10 @@ impl Default for GlobalConfig {
             icon_spacing: default_icon_spacing(),
             icons: IconConfig::default(),
             update_interval: default_update_interval(),
+            units: UnitSystem::default(),
             visuals: VisualConfig::default(),
             sensors: HashMap::new(),
+            versioned_output: None,
         }
     }
 }
@@ -541,8 +796,9 @@ impl GlobalConfig {
     /// Load configuration from the standard config file location.
     ///
     /// Searches for config in:
-    /// 1. ~/.config/waysensor-rs/config.ron
-    /// 2. ~/.waysensor-rs/config.ron (fallback)
+    /// 1. ~/.config/waysensor-rs/config-<seat>.ron (per-seat, see [`session::seat`])
+    /// 2. ~/.config/waysensor-rs/config.ron
+    /// 3. ~/.waysensor-rs/config.ron (fallback)
     ///
     /// Returns default config if no file is found.
     pub fn load() -> Result<Self, SensorError> {
@@ -567,8 +823,14 @@ impl GlobalConfig {
 
     /// Find the config file in standard locations.
     pub fn find_config_file() -> Option<PathBuf> {
-        // Try XDG config directory first
+        // Try XDG config directory first, preferring a file for our seat
         if let Some(config_dir) = dirs::config_dir() {
+            if let Some(seat) = session::seat() {
+                let seat_path = config_dir.join("waysensor-rs").join(format!("config-{}.ron", seat));
+                if seat_path.exists() {
+                    return Some(seat_path);
+                }
+            }
             let xdg_path = config_dir.join("waysensor-rs").join("config.ron");
             if xdg_path.exists() {
                 return Some(xdg_path);
@@ -634,11 +896,44 @@ impl GlobalConfig {
             tooltip_label_color: self.colors.tooltip_label_color.clone(),
             tooltip_value_color: self.colors.tooltip_value_color.clone(),
             sparkline_color: self.colors.sparkline_color.clone(),
+            status_colors: self.colors.status_colors.clone(),
+            units: self.units,
             visuals: self.visuals.clone(),
+            versioned_output: self.versioned_output.clone(),
             custom: HashMap::new(),
         }
     }
 
+    /// Convert to a sensor configuration with that sensor's overrides applied.
+    ///
+    /// A `status_colors` entry in the sensor's section of `sensors` replaces
+    /// the matching global status colors, so a module can restyle a severity
+    /// (e.g. show "critical" network throughput in blue) without every
+    /// sensor handling it. A `theme` entry renames the emitted CSS classes
+    /// (e.g. `mem-warn` instead of `warning`); unset names keep their default.
+    /// All keys of the section are also available through
+    /// [`SensorConfig::get_custom`].
+    pub fn to_sensor_config_for(&self, sensor: &str) -> SensorConfig {
+        let mut config = self.to_sensor_config();
+        let section = self.sensors.get(sensor);
+        if let Some(serde_json::Value::Object(map)) = section {
+            config.custom.extend(map.iter().map(|(key, value)| (key.clone(), value.clone())));
+        }
+        let overrides = section
+            .and_then(|section| section.get("status_colors"))
+            .and_then(|colors| serde_json::from_value::<StatusColorConfig>(colors.clone()).ok());
+        if let Some(overrides) = overrides {
+            config.status_colors = config.status_colors.merged_with(&overrides);
+        }
+        if let Some(theme) = section
+            .and_then(|section| section.get("theme"))
+            .and_then(|theme| serde_json::from_value::<Theme>(theme.clone()).ok())
+        {
+            config.theme = theme;
+        }
+        config
+    }
+
     /// Create an example configuration file with common settings.
     pub fn example_config() -> Self {
         let mut config = Self::default();
@@ -744,6 +1039,16 @@ impl GlobalConfig {
     // This is the internal update rate for persistent processes
     update_interval: 1000,
 
+    // Measurement units for sensors that convert them, such as weather
+    // Options: metric (°C, km/h, mm), imperial (°F, mph, in)
+    units: metric,
+
+    // Also write every output to this FIFO (create it with mkfifo) as one JSON
+    // object per line, with "schema_version" and "sensor" fields added. Lines
+    // are dropped while nothing reads the FIFO; a regular file is rotated at
+    // 1 MiB instead. Waybar's stdout is unchanged.
+    // versioned_output: Some("/run/user/1000/waysensor-rs.fifo"),
+
     // =============================================================================
     // ICON CONFIGURATION
     // =============================================================================
@@ -761,11 +1066,16 @@ impl GlobalConfig {
         // Disk/Storage sensor icon
         disk: "\u{F0A0}",                   //  Hard drive icon
 
-        // Network sensor icons (4 variants)
+        // Network sensor icons (6 variants)
         network_download: "\u{F019}",       //  Download arrow
         network_upload: "\u{F093}",         //  Upload arrow
         network_wifi: "\u{F05A9}",          // 󰖩 WiFi signal
         network_ethernet: "\u{F0200}",      // 󰈀 Ethernet cable
+        network_latency: "\u{F04C5}",       // 󰓅 Speedometer (--ping)
+        network_metered: "\u{F0FB8}",       // 󰾸 Metered connection (hotspot, tethering)
+
+        // VPN tunnel icon (--vpn)
+        vpn: "\u{F099D}",                  // 󰦝 Shield with lock
 
         // Battery sensor icons (6 charge levels)
         battery_full: "\u{F0079}",          // 󰁹 Battery 100%
@@ -782,6 +1092,59 @@ impl GlobalConfig {
 
         // GPU sensor icon
         gpu: "\u{F08AE}",                   // 󰢮 Graphics card icon
+
+        // Fan speed icon
+        fan: "\u{F0210}",                   // 󰈐 Fan icon
+
+        // Sleep readiness icon
+        sleep: "\u{F04B2}",                 // 󰒲 Sleep icon
+
+        // Audio volume icons
+        volume: "\u{F057E}",                // 󰕾 Speaker
+        volume_muted: "\u{F0581}",          // 󰖁 Speaker off
+
+        // Microphone in use icon (--mic)
+        microphone: "\u{F036C}",            // 󰍬 Microphone
+
+        // Display brightness icon
+        backlight: "\u{F00DF}",             // 󰃟 Brightness
+
+        // Bluetooth icons
+        bluetooth: "\u{F00AF}",             // 󰂯 Bluetooth
+        bluetooth_off: "\u{F00B2}",         // 󰂲 Bluetooth off
+        bluetooth_connected: "\u{F00B1}",   // 󰂱 Bluetooth connected
+
+        // Media player icons
+        media_playing: "\u{F040A}",         // 󰐊 Play
+        media_paused: "\u{F03E4}",          // 󰏤 Pause
+
+        // Pending package updates icon
+        updates: "\u{F06B0}",               // 󰚰 Package up
+
+        // Failed systemd units icon
+        failed_units: "\u{F0026}",          // 󰀦 Alert
+
+        // Uptime icons
+        uptime: "\u{F0150}",                // 󰅐 Clock
+        reboot_required: "\u{F0709}",       // 󰜉 Restart
+
+        // Weather condition icons
+        weather_clear: "\u{F0599}",         // 󰖙 Sunny
+        weather_night: "\u{F0594}",         // 󰖔 Night
+        weather_partly_cloudy: "\u{F0595}", // 󰖕 Partly cloudy
+        weather_cloudy: "\u{F0590}",        // 󰖐 Cloudy
+        weather_fog: "\u{F0591}",           // 󰖑 Fog
+        weather_rain: "\u{F0597}",          // 󰖗 Rainy
+        weather_snow: "\u{F0598}",          // 󰖘 Snowy
+        weather_thunderstorm: "\u{F0593}",  // 󰖓 Lightning
+
+        // Keyboard layout icon
+        keyboard: "\u{F030C}",              // 󰌌 Keyboard
+
+        // Compositor icons
+        window: "\u{F0614}",                // 󰘔 Application
+        workspace: "\u{F0570}",             // 󰕰 View grid
+        containers: "\u{F0868}",            // 󰡨 Docker
     ),
 
     // =============================================================================
@@ -897,19 +1260,206 @@ impl GlobalConfig {
         "cpu": {
             "warning_threshold": 75,
             "critical_threshold": 90,
+            // List each core with a gauge in the tooltip
             "show_per_core": true,
+            // Only list the N busiest cores, plus a heat map of all (0 = all cores)
             "max_cores_display": 0,
+            // Append the average core frequency to the bar text (same as --show-frequency)
+            "show_frequency": false,
+            // Append the package temperature (k10temp/coretemp) to the bar text (same as --show-temperature)
+            "show_temperature": false,
+            // Show which core each top process last ran on
+            "show_process_core": false,
+            // Minutes new hardware errors and a kernel warning taint keep the alert class (--kernel-health)
+            "health_alert_mins": 60,
+            // Gauge colors of the tooltip's time breakdown (default: sparkline_color)
+            // "breakdown_colors": {"user": "#7aa2f7", "system": "#f7768e", "iowait": "#e0af68", "irq": "#bb9af7", "steal": "#ff9e64"},
+        },
+        "network": {
+            // Seconds between one-packet gateway reachability checks (expert tooltip, --connectivity)
+            "gateway_check_interval_secs": 30,
+            // URL answering 204 No Content when online; anything else counts as a captive portal
+            "connectivity_url": "http://connectivitycheck.gstatic.com/generate_204",
+            // Look up the public IP with a third-party service in --connectivity mode (same as --public-ip)
+            "public_ip": false,
+            "public_ip_url": "https://api.ipify.org",
+            // How long a --ping probe may take before it counts as lost
+            "ping_timeout_ms": 1000,
+            // Packet loss in percent over the last 60 probes that turns --ping warning / critical
+            "loss_warning": 5,
+            "loss_critical": 20,
+            // Addresses of the interface in the tooltip, per family; link-local IPv6 hidden by default
+            "show_ipv4": true,
+            "show_ipv6": true,
+            "show_link_local": false,
+            // Replace the host part of addresses (192.168.x.x) for screen sharing
+            "mask_addresses": false,
+            // Hotspots, USB tethering and mobile broadband get a badge and the "metered" CSS class
+            "metered_detection": true,
+            // Update immediately on link up/down and address changes via rtnetlink, not just on the timer
+            "link_events": true,
+            // Processes with the most TCP traffic in the tooltip, via `ss` (same as --top-talkers)
+            "top_talkers": false,
+            // Persist daily and monthly transfer totals per interface (same as --usage)
+            "data_usage": false,
+            // Monthly data cap in GB; warning from data_cap_warning percent, critical when used up
+            // "data_cap_gb": 100,
+            "data_cap_warning": 80,
+            // Day of the month the data cap resets
+            "billing_day": 1,
+            // WiFi signal strength in percent below which the module turns warning / critical
+            "wifi_signal_warning": 40,
+            "wifi_signal_critical": 20,
+            // Per-module status colors override the global ones; high throughput isn't bad news
+            "status_colors": {"warning": "#7dcfff", "critical": "#7aa2f7"},
         },
         "memory": {
             "warning_threshold": 80,
             "critical_threshold": 95,
             "include_swap": true,
+            // Split used memory into apps, buffers, cache, shared and slab (same as --show-breakdown)
             "show_breakdown": true,
+            // Count zram/zswap pages once when including swap (same as --fold-compressed-swap)
+            "fold_compressed_swap": false,
+            // Class on memory pressure (full avg10) instead of usage (same as --class-on-pressure)
+            "class_on_pressure": false,
+            // Desktop notification when the OOM killer runs (same as --notify-oom)
+            "notify_oom": false,
+            // Rename the emitted CSS classes to match an existing Waybar stylesheet
+            // "theme": {"warning": "mem-warn", "critical": "mem-crit"},
         },
         "thermal": {
             "warning_threshold": 70,
             "critical_threshold": 85,
             "temperature_unit": "celsius",
+            // Slope (°C per minute) that switches the module to the theme's "rising" CSS class
+            "rapid_rise_per_min": 5.0,
+            // Number of recent samples the trend slope is fitted over
+            "trend_window": 6,
+        },
+        "sleep": {
+            // Warn when hibernation would fail (same as --require-hibernate)
+            "require_hibernate": false,
+        },
+        "audio": {
+            // Percent per --raise/--lower (bind them to on-scroll-up/down)
+            "step": 5,
+            // --raise stops here; above 100 boosts the output
+            "max_volume": 100,
+        },
+        "mic": {
+            // Keep the module visible ("off") while nothing records; hidden by default
+            "show_idle": false,
+        },
+        "backlight": {
+            // Device to show and adjust, e.g. "intel_backlight"; the preferred one when unset (same as --device)
+            // "device": "intel_backlight",
+            // Percent per --raise/--lower (bind them to on-scroll-up/down)
+            "step": 5,
+            // --lower stops here so the panel never turns off
+            "min_percent": 1,
+        },
+        "bluetooth": {
+            // Adapter to show and toggle, e.g. "hci1"; the first one when unset (same as --adapter)
+            // "adapter": "hci0",
+            // Battery level next to a single connected device's name
+            "show_battery": true,
+        },
+        "media": {
+            // Only follow players whose name starts with this, e.g. "spotify"; any when unset (same as --player)
+            // "player": "spotify",
+            // Track text; {artist}, {title} and {album} are filled in (just the title without an artist)
+            "format": "{artist} - {title}",
+            // Longer text is cut with "…", or scrolls one character per update with "scroll"
+            "max_length": 40,
+            "scroll": false,
+        },
+        "updates": {
+            // Backends to check; every installed one when unset (same as --backend)
+            // "backends": ["pacman", "flatpak"],
+            // Seconds between two checks (at least 60); results are shared through ~/.cache/waysensor-rs/updates.json
+            "check_interval": 3600,
+            "cache": true,
+            // Warning class from this many pending updates
+            "warning_threshold": 25,
+            // Keep the module visible ("0") when up to date; hidden by default
+            "show_zero": false,
+        },
+        "systemd": {
+            // Service managers to ask: "system", "user" or "all" (same as --scope)
+            "scope": "all",
+            // Failed units not to count, e.g. ["fwupd-refresh.service"]
+            "ignore": [],
+            // Keep the module visible ("0") while no unit failed; hidden by default
+            "show_zero": false,
+        },
+        "uptime": {
+            // Running kernel release next to the uptime
+            "show_kernel": false,
+        },
+        "weather": {
+            // "open-meteo" (needs a location) or "wttr.in" (locates you by IP
+            // when no location is set); same as --provider
+            "provider": "open-meteo",
+            // Place name, looked up once (same as --location), or exact coordinates
+            "location": "Berlin",
+            // "latitude": 52.52,
+            // "longitude": 13.41,
+            // Seconds between two fetches; the last result is kept while offline
+            "refresh_interval": 900,
+            // Share results between bars and restarts through ~/.cache/waysensor-rs
+            "cache": true,
+        },
+        "keyboard": {
+            // Keyboard to follow: a Sway input identifier or Hyprland device
+            // name (same as --keyboard); the main keyboard by default
+            // "keyboard": "1:1:AT_Translated_Set_2_keyboard",
+            // "code" shows the layout code ("us"), "name" its full name
+            "format": "code",
+            // Bar labels by layout code, e.g. {"us": "EN"}
+            "labels": {},
+        },
+        "compositor": {
+            // "window" shows the focused window, "workspace" the focused workspace (same as --mode)
+            "mode": "window",
+            // Window text; {title}, {app} and {workspace} are replaced
+            "format": "{title}",
+            // Longer text is cut with "…", or scrolls one character per update with "scroll"
+            "max_length": 50,
+            "scroll": false,
+        },
+        "containers": {
+            // Docker or Podman socket (same as --socket); the first one found by default
+            // "socket": "/run/user/1000/podman/podman.sock",
+            // Names or IDs of containers to warn about when not running (same as --watch)
+            "watch": [],
+            // Per-container CPU and memory in the tooltip, one request per running container
+            "stats": true,
+        },
+
+        "disk": {
+            // SMART drive health in the tooltip, critical on failing attributes (same as --smart)
+            "smart": false,
+            // Drive temperature thresholds in °C; the drive's own limits apply when unset
+            // "drive_temp_warning": 60,
+            // "drive_temp_critical": 70,
+            // Mount point globs for --all; empty include means every real mount
+            "mount_include": [],
+            "mount_exclude": ["/snap/**", "/var/lib/docker/**"],
+            // How long NFS, CIFS and FUSE mounts get to answer before showing as unavailable
+            "network_timeout_ms": 2000,
+            // Use `btrfs filesystem usage` instead of statvfs on btrfs (RAID-aware free space)
+            "btrfs": true,
+            // ZFS pool capacity, fragmentation and health; critical unless ONLINE
+            "zfs": true,
+            // Report your own quota instead of the whole filesystem when one is set (same as --quota)
+            "quota": false,
+            // "Full in" prediction with --performance-monitoring: linear or exponential fit
+            "forecast_model": "linear",
+            "full_warning_days": 7,
+            "full_critical_days": 1,
+            // Stop polling while the session is locked (any sensor section accepts this)
+            // "pause_when_locked": true,
         },
         "amd-gpu": {
             "warning_threshold": 80,
@@ -919,10 +1469,15 @@ impl GlobalConfig {
             "show_temperature": true,
             "show_power": true,
             "show_utilization": true,
+            // VRAM in use (mem_info_vram_used)
             "show_memory": false,
             "show_frequency": false,
-            // Custom display order (when all are shown)
+            // Custom display order (when all are shown); also accepts "memory"
             "display_order": ["temperature", "power", "utilization"],
+            // Top GPU processes in the tooltip (scans the fds of every process)
+            "show_processes": false,
+            // Seconds between reads on battery; a suspended dGPU is left asleep (0 = off)
+            "eco_interval_secs": 10,
         },
         "nvidia-gpu": {
             "warning_threshold": 80,
@@ -933,11 +1488,19 @@ impl GlobalConfig {
             "show_utilization": true,
             "show_memory": true,
             "show_clocks": true,
+            // Append NVENC/NVDEC load to the text while they are busy
+            "show_encoder": false,
+            "eco_interval_secs": 10,
         },
         "intel-gpu": {
             "warning_threshold": 80,
             "critical_threshold": 95,
             "show_frequency": true,
+            // Watts from RAPL (needs a readable /sys/class/powercap/intel-rapl:0/energy_uj)
+            "show_power": true,
+            // "frequency" or "power" (watts rated against the package power limit)
+            "display_format": "frequency",
+            "eco_interval_secs": 10,
         },
     },
 )
@@ -1050,6 +1613,59 @@ pub struct IconPositionParseError {
     valid_options: &'static [&'static str],
 }
 
+/// Measurement units shown to the user.
+///
+/// # Examples
+///
+/// ```rust
+/// use waysensor_rs_core::UnitSystem;
+///
+/// let units: UnitSystem = "imperial".parse().unwrap();
+/// assert_eq!(units, UnitSystem::Imperial);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnitSystem {
+    /// °C, km/h, mm
+    #[default]
+    Metric,
+    /// °F, mph, inches
+    Imperial,
+}
+
+impl fmt::Display for UnitSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Metric => "metric",
+            Self::Imperial => "imperial",
+        };
+        f.write_str(name)
+    }
+}
+
+impl std::str::FromStr for UnitSystem {
+    type Err = UnitSystemParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "metric" | "si" => Ok(Self::Metric),
+            "imperial" | "us" => Ok(Self::Imperial),
+            _ => Err(UnitSystemParseError {
+                input: s.to_owned(),
+                valid_options: &["metric", "imperial"],
+            }),
+        }
+    }
+}
+
+/// Error type for parsing [`UnitSystem`] from string.
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid unit system '{input}'. Valid options: {}", valid_options.join(", "))]
+pub struct UnitSystemParseError {
+    input: String,
+    valid_options: &'static [&'static str],
+}
+
 /// Icon style variants for sensor display.
 ///
 /// Simplified icon system with two options:
@@ -1137,6 +1753,7 @@ pub struct IconStyleParseError {
 ///     .with_critical("my-critical");
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Theme {
     /// CSS class for normal/neutral state
     pub normal: String,
@@ -1148,6 +1765,8 @@ pub struct Theme {
     pub good: String,
     /// CSS class for unknown/unavailable state
     pub unknown: String,
+    /// CSS class for a value that is climbing quickly (e.g. a heating CPU)
+    pub rising: String,
 }
 
 impl Theme {
@@ -1192,6 +1811,13 @@ impl Theme {
         self
     }
 
+    /// Set the rising state class name.
+    #[must_use]
+    pub fn with_rising(mut self, class: impl Into<String>) -> Self {
+        self.rising = class.into();
+        self
+    }
+
     /// Get the appropriate class name for a threshold-based value.
     ///
     /// Returns the CSS class name based on comparing `value` against the thresholds:
@@ -1223,6 +1849,7 @@ impl Default for Theme {
             critical: "critical".to_owned(),
             good: "good".to_owned(),
             unknown: "unknown".to_owned(),
+            rising: "rising".to_owned(),
         }
     }
 }
@@ -1279,9 +1906,18 @@ pub struct SensorConfig {
     /// Optional color for sparklines (hex format like "#f7768e")
     #[serde(default)]
     pub sparkline_color: Option<String>,
+    /// Text colors for warning, critical and other non-normal states
+    #[serde(default)]
+    pub status_colors: StatusColorConfig,
+    /// Measurement units shown by sensors that convert them
+    #[serde(default)]
+    pub units: UnitSystem,
     /// Visual enhancement settings
     #[serde(default)]
     pub visuals: VisualConfig,
+    /// FIFO or file that also receives every output as versioned JSON lines, see [`schema`]
+    #[serde(default)]
+    pub versioned_output: Option<PathBuf>,
     /// Sensor-specific custom configuration
     #[serde(flatten)]
     pub custom: HashMap<String, serde_json::Value>,
@@ -1352,6 +1988,13 @@ impl SensorConfig {
         self
     }
 
+    /// Set the measurement units.
+    #[must_use]
+    pub fn with_units(mut self, units: UnitSystem) -> Self {
+        self.units = units;
+        self
+    }
+
     /// Set the icon color (Pango markup format, e.g., "#7aa2f7").
     #[must_use]
     pub fn with_icon_color(mut self, color: impl Into<String>) -> Self {
@@ -1422,6 +2065,26 @@ impl SensorConfig {
     pub fn get_custom(&self, key: &str) -> Option<&serde_json::Value> {
         self.custom.get(key)
     }
+
+    /// Color the output text with the status color of its CSS class.
+    ///
+    /// Replaces the configured text color (or wraps uncolored text); outputs
+    /// in the normal state or without a matching status color are unchanged.
+    #[must_use]
+    pub fn apply_status_color(&self, mut output: WaybarOutput) -> WaybarOutput {
+        let Some(color) = output.class.as_deref()
+            .and_then(|class| self.status_colors.color_for_class(class, &self.theme)) else {
+            return output;
+        };
+        output.text = match &self.text_color {
+            Some(text_color) => output.text.replace(
+                &format!("<span color=\"{}\">", text_color),
+                &format!("<span color=\"{}\">", color),
+            ),
+            None => format!("<span color=\"{}\">{}</span>", color, output.text),
+        };
+        output
+    }
 }
 
 impl Default for SensorConfig {
@@ -1438,7 +2101,10 @@ impl Default for SensorConfig {
             tooltip_label_color: None,
             tooltip_value_color: None,
             sparkline_color: None,
+            status_colors: StatusColorConfig::default(),
+            units: UnitSystem::default(),
             visuals: VisualConfig::default(),
+            versioned_output: None,
             custom: HashMap::new(),
         }
     }
@@ -1460,6 +2126,59 @@ where
     Ok(interval)
 }
 
+/// Raw numeric value of a sensor reading, alongside its rendered output.
+///
+/// Exporters and recorders use this instead of parsing [`WaybarOutput::text`].
+///
+/// # Examples
+///
+/// ```rust
+/// use waysensor_rs_core::Reading;
+///
+/// let reading = Reading::new(63.5, "°C").with_extra("critical", 90.0);
+/// assert_eq!(reading.value, 63.5);
+/// assert_eq!(reading.extra.get("critical"), Some(&90.0));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Reading {
+    /// Primary value, in `unit`
+    pub value: f64,
+    /// Unit of the primary value (e.g. "%", "°C", "RPM", "B/s")
+    pub unit: String,
+    /// Additional named values (per-core usage, secondary metrics, ...)
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub extra: HashMap<String, f64>,
+}
+
+impl Reading {
+    /// Create a reading with a primary value and unit.
+    #[must_use]
+    pub fn new(value: f64, unit: impl Into<String>) -> Self {
+        Self {
+            value,
+            unit: unit.into(),
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Add a named secondary value.
+    #[must_use]
+    pub fn with_extra(mut self, key: impl Into<String>, value: f64) -> Self {
+        self.extra.insert(key.into(), value);
+        self
+    }
+
+    /// Default adapter for sensors without a typed value: uses the output
+    /// percentage, or NaN when the output has none.
+    #[must_use]
+    pub fn from_output(output: &WaybarOutput) -> Self {
+        match output.percentage {
+            Some(percentage) => Self::new(f64::from(percentage), "%"),
+            None => Self::new(f64::NAN, ""),
+        }
+    }
+}
+
 /// Trait for all system sensors providing Waybar-compatible output.
 ///
 /// This trait defines the common interface that all sensors must implement
@@ -1509,6 +2228,55 @@ pub trait Sensor {
     /// Returns an error if the sensor data cannot be read or parsed.
     fn read(&mut self) -> Result<WaybarOutput, Self::Error>;
 
+    /// Read current sensor data together with its raw numeric value.
+    ///
+    /// Default implementation derives the [`Reading`] from the output
+    /// percentage. Sensors with a natural unit (°C, RPM, load) should
+    /// override this.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sensor data cannot be read or parsed.
+    fn read_typed(&mut self) -> Result<(WaybarOutput, Reading), Self::Error> {
+        let output = self.read()?;
+        let reading = Reading::from_output(&output);
+        Ok((output, reading))
+    }
+
+    /// Read current sensor data with the configured status colors applied.
+    ///
+    /// This is what the sensor binaries print; see
+    /// [`SensorConfig::apply_status_color`]. In [`presentation`] mode the
+    /// output is uncolored and only refreshed every [`presentation::INTERVAL`].
+    /// Each call counts as a wake-up for [`wakeups`] accounting, and expert
+    /// tooltips show the achieved update interval. With `versioned_output`
+    /// configured the output is also appended there, see [`schema`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sensor data cannot be read or parsed.
+    fn read_styled(&mut self) -> Result<WaybarOutput, Self::Error> {
+        wakeups::record(self.name());
+        let output = if presentation::is_active() {
+            match presentation::recent(self.name()) {
+                Some(output) => output,
+                None => {
+                    let output = presentation::minimal(&self.read()?, &self.config().theme);
+                    presentation::remember(self.name(), &output);
+                    output
+                }
+            }
+        } else {
+            let output = schedule::with_interval_line(self.read()?, self.name(), self.config());
+            self.config().apply_status_color(output)
+        };
+        if let Some(path) = &self.config().versioned_output {
+            // A full disk or missing directory must not take the bar down
+            let _ = schema::append(path, self.name(), &output);
+        }
+        Ok(output)
+    }
+
     /// Get the unique name/identifier for this sensor.
     ///
     /// This name is used for logging, configuration, and identification
@@ -1555,13 +2323,117 @@ pub trait Sensor {
             tooltip_label_color: None,
             tooltip_value_color: None,
             sparkline_color: None,
+            status_colors: StatusColorConfig::default(),
+            units: UnitSystem::default(),
             visuals: VisualConfig::default(),
+            versioned_output: None,
             custom: HashMap::new(),
         });
         &DEFAULT_CONFIG
     }
 }
 
+/// A monitorable item found on this system, such as a battery, thermal zone
+/// or network interface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveredItem {
+    /// Identifier to pass back to the sensor (e.g. `BAT0`, `wlan0`, `/home`)
+    pub id: String,
+    /// Human readable description
+    pub label: String,
+    /// Additional key/value details, in display order
+    pub details: Vec<(String, String)>,
+}
+
+impl DiscoveredItem {
+    /// Create an item with an ID and label.
+    pub fn new(id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            label: label.into(),
+            details: Vec::new(),
+        }
+    }
+
+    /// Add a detail shown under the item.
+    #[must_use]
+    pub fn with_detail(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.details.push((key.into(), value.into()));
+        self
+    }
+}
+
+/// Sensors that can enumerate the hardware they are able to monitor.
+///
+/// Backs the `--list` flag every sensor binary supports, so listings look the
+/// same everywhere and can be consumed as JSON with `--list --json`.
+pub trait Discoverable {
+    /// Plural noun for the discovered items, e.g. `"batteries"`.
+    const KIND: &'static str;
+
+    /// Enumerate the items available on this system.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the relevant interface can't be queried at all.
+    /// Finding nothing is not an error.
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError>;
+}
+
+/// Rendering of [`Discoverable`] listings.
+pub mod discovery {
+    use super::{DiscoveredItem, Discoverable, SensorError};
+
+    /// Render items as an aligned, human readable list.
+    pub fn format_text(kind: &str, items: &[DiscoveredItem]) -> String {
+        if items.is_empty() {
+            return format!("No {} found", kind);
+        }
+
+        let id_width = items.iter().map(|i| i.id.chars().count()).max().unwrap_or(0);
+        let mut lines = vec![format!("Available {} ({}):", kind, items.len())];
+        for item in items {
+            lines.push(format!("  {:<width$}  {}", item.id, item.label, width = id_width));
+            for (key, value) in &item.details {
+                lines.push(format!("  {:<width$}    {}: {}", "", key, value, width = id_width));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// Render items as a JSON array of `{id, label, details}` objects.
+    pub fn format_json(items: &[DiscoveredItem]) -> String {
+        let values: Vec<serde_json::Value> = items.iter()
+            .map(|item| {
+                let details: serde_json::Map<String, serde_json::Value> = item.details.iter()
+                    .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+                    .collect();
+                serde_json::json!({
+                    "id": item.id,
+                    "label": item.label,
+                    "details": details,
+                })
+            })
+            .collect();
+        serde_json::Value::Array(values).to_string()
+    }
+
+    /// Discover items for `D` and print them as text or JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns the discovery error, if any.
+    pub fn print<D: Discoverable>(json: bool) -> Result<(), SensorError> {
+        let items = D::discover()?;
+        if json {
+            println!("{}", format_json(&items));
+        } else {
+            println!("{}", format_text(D::KIND, &items));
+        }
+        Ok(())
+    }
+}
+
 /// Utility functions for formatting sensor data and creating Waybar output.
 ///
 /// This module provides common formatting utilities that sensors can use
@@ -1576,13 +2448,13 @@ pub mod format {
     /// ```rust
     /// use waysensor_rs_core::{format, IconStyle, IconPosition};
     ///
-    /// let result = format::with_icon("50%", "󰍛", IconStyle::NerdFont, IconPosition::Before);
+    /// let result = format::with_icon("50%", "󰍛", IconStyle::NerdFont, IconPosition::Before, 1);
     /// assert_eq!(result, "󰍛 50%");
     ///
-    /// let result = format::with_icon("50%", "󰍛", IconStyle::NerdFont, IconPosition::After);
+    /// let result = format::with_icon("50%", "󰍛", IconStyle::NerdFont, IconPosition::After, 1);
     /// assert_eq!(result, "50% 󰍛");
     ///
-    /// let result = format::with_icon("50%", "󰍛", IconStyle::None, IconPosition::Before);
+    /// let result = format::with_icon("50%", "󰍛", IconStyle::None, IconPosition::Before, 1);
     /// assert_eq!(result, "50%");
     /// ```
     #[must_use]
@@ -1794,36 +2666,115 @@ pub mod format {
         }
     }
 
-    /// Create a gauge bar visualization based on percentage and configuration.
+    /// Separator between the end and the start of scrolling text.
+    pub const SCROLL_SEPARATOR: &str = " · ";
+
+    /// At most `max` characters of `text`, ending in `…` when cut.
     ///
     /// # Examples
     ///
     /// ```rust
-    /// use waysensor_rs_core::{format, GaugeStyle};
+    /// use waysensor_rs_core::format;
     ///
-    /// // Using blocks style
-    /// assert_eq!(format::create_gauge(50.0, 10, GaugeStyle::Blocks), "█████░░░░░");
+    /// assert_eq!(format::truncate("Massive Attack - Teardrop", 10), "Massive A…");
+    /// assert_eq!(format::truncate("Teardrop", 10), "Teardrop");
+    /// ```
+    #[must_use]
+    pub fn truncate(text: &str, max: usize) -> String {
+        if text.chars().count() <= max {
+            return text.to_string();
+        }
+        let mut cut: String = text.chars().take(max.saturating_sub(1)).collect();
+        cut.push('…');
+        cut
+    }
+
+    /// A `width` characters wide window into `text` scrolled by `offset`
+    /// characters, wrapping around through [`SCROLL_SEPARATOR`]; `text`
+    /// itself when it fits.
     ///
-    /// // Using ASCII style
-    /// assert_eq!(format::create_gauge(30.0, 10, GaugeStyle::Ascii), "[###-------]");
+    /// # Examples
+    ///
+    /// ```rust
+    /// use waysensor_rs_core::format;
+    ///
+    /// assert_eq!(format::scroll_window("Massive Attack", 8, 12), "ck · Mas");
     /// ```
     #[must_use]
-    pub fn create_gauge(percentage: f64, width: usize, style: crate::GaugeStyle) -> String {
-        let filled = ((percentage.clamp(0.0, 100.0) / 100.0) * width as f64).round() as usize;
-        let empty = width.saturating_sub(filled);
+    pub fn scroll_window(text: &str, width: usize, offset: usize) -> String {
+        if text.chars().count() <= width {
+            return text.to_string();
+        }
+        let chars: Vec<char> = text.chars().chain(SCROLL_SEPARATOR.chars()).collect();
+        (0..width).map(|i| chars[(offset + i) % chars.len()]).collect()
+    }
 
-        match style {
-            crate::GaugeStyle::Blocks => {
-                let filled_char = '█';
-                let empty_char = '░';
-                format!(
-                    "{}{}",
-                    filled_char.to_string().repeat(filled),
-                    empty_char.to_string().repeat(empty)
-                )
+    /// Scroll position of text too wide for the bar, advancing one character
+    /// per update and starting over when the text changes.
+    #[derive(Debug, Clone, Default)]
+    pub struct Marquee {
+        offset: usize,
+        text: String,
+    }
+
+    impl Marquee {
+        /// The window of `text` to show now, `width` characters wide. The
+        /// position holds still unless `advance` is set.
+        pub fn next(&mut self, text: &str, width: usize, advance: bool) -> String {
+            if self.text != text {
+                self.offset = 0;
+                self.text = text.to_string();
+            } else if advance {
+                self.offset += 1;
             }
-            crate::GaugeStyle::Ascii => {
-                format!("[{}{}]", "#".repeat(filled), "-".repeat(empty))
+            scroll_window(text, width, self.offset)
+        }
+    }
+
+    /// Escape text for Pango markup.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use waysensor_rs_core::format;
+    ///
+    /// assert_eq!(format::escape_markup("Tom & Jerry <3"), "Tom &amp; Jerry &lt;3");
+    /// ```
+    #[must_use]
+    pub fn escape_markup(text: &str) -> String {
+        text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+    }
+
+    /// Create a gauge bar visualization based on percentage and configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use waysensor_rs_core::{format, GaugeStyle};
+    ///
+    /// // Using blocks style
+    /// assert_eq!(format::create_gauge(50.0, 10, GaugeStyle::Blocks), "█████░░░░░");
+    ///
+    /// // Using ASCII style
+    /// assert_eq!(format::create_gauge(30.0, 10, GaugeStyle::Ascii), "[###-------]");
+    /// ```
+    #[must_use]
+    pub fn create_gauge(percentage: f64, width: usize, style: crate::GaugeStyle) -> String {
+        let filled = ((percentage.clamp(0.0, 100.0) / 100.0) * width as f64).round() as usize;
+        let empty = width.saturating_sub(filled);
+
+        match style {
+            crate::GaugeStyle::Blocks => {
+                let filled_char = '█';
+                let empty_char = '░';
+                format!(
+                    "{}{}",
+                    filled_char.to_string().repeat(filled),
+                    empty_char.to_string().repeat(empty)
+                )
+            }
+            crate::GaugeStyle::Ascii => {
+                format!("[{}{}]", "#".repeat(filled), "-".repeat(empty))
             }
             crate::GaugeStyle::Dots => {
                 let filled_char = '●';
@@ -2206,208 +3157,2188 @@ pub mod format {
     }
 }
 
-/// Common error types for sensor operations.
+/// Change detection between consecutive sensor outputs.
 ///
-/// This enum provides a comprehensive set of error types that cover
-/// the most common failure modes in sensor implementations.
-#[derive(Debug, thiserror::Error)]
-pub enum SensorError {
-    /// I/O error occurred while reading sensor data.
-    #[error("I/O error: {0}")]
-    Io(#[from] std::io::Error),
-
-    /// Error parsing sensor data from text format.
-    #[error("Parse error: {message}")]
-    Parse {
-        /// Description of what failed to parse
-        message: String,
-        /// Optional source error for chaining
-        #[source]
-        source: Option<Box<dyn std::error::Error + Send + Sync>>,
-    },
+/// Lets consumers such as alert sinks or deduplicating emitters react to what
+/// actually changed instead of re-parsing rendered text.
+///
+/// # Examples
+///
+/// ```rust
+/// use waysensor_rs_core::{diff::{ChangeEvent, ChangeTracker}, Theme, WaybarOutput};
+///
+/// let mut tracker = ChangeTracker::new();
+/// tracker.update(&WaybarOutput::from_str("50%").with_class("normal").with_percentage(50));
+///
+/// let events = tracker.update(&WaybarOutput::from_str("95%").with_class("critical").with_percentage(95));
+/// assert!(events.iter().any(|e| e.is_escalation(&Theme::default())));
+/// ```
+pub mod diff {
+    use super::{Theme, WaybarOutput};
+
+    /// A single difference between two consecutive outputs.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum ChangeEvent {
+        /// The bar text changed
+        TextChanged { from: String, to: String },
+        /// The tooltip changed (contents omitted, tooltips are large)
+        TooltipChanged,
+        /// The CSS class changed, including appearing or disappearing
+        ClassChanged {
+            from: Option<String>,
+            to: Option<String>,
+        },
+        /// The percentage changed by at least the tracker's minimum delta
+        ValueChanged {
+            from: Option<u8>,
+            to: Option<u8>,
+            delta: i16,
+        },
+    }
 
-    /// Configuration error (invalid settings, etc.).
-    #[error("Configuration error: {message}")]
-    Config {
-        /// Description of the configuration issue
-        message: String,
-        /// The invalid configuration value if applicable
-        value: Option<String>,
-    },
+    impl ChangeEvent {
+        /// Whether this is a class transition to a more severe state.
+        #[must_use]
+        pub fn is_escalation(&self, theme: &Theme) -> bool {
+            match self {
+                Self::ClassChanged { from, to } => {
+                    severity(to.as_deref(), theme) > severity(from.as_deref(), theme)
+                }
+                _ => false,
+            }
+        }
 
-    /// Sensor is not available on this system.
-    #[error("Sensor unavailable: {reason}")]
-    Unavailable {
-        /// Reason why the sensor is unavailable
-        reason: String,
-        /// Whether this is a temporary or permanent condition
-        is_temporary: bool,
-    },
+        /// Whether this is a class transition to a less severe state.
+        #[must_use]
+        pub fn is_recovery(&self, theme: &Theme) -> bool {
+            match self {
+                Self::ClassChanged { from, to } => {
+                    severity(to.as_deref(), theme) < severity(from.as_deref(), theme)
+                }
+                _ => false,
+            }
+        }
+    }
 
-    /// Permission denied accessing sensor data.
-    #[error("Permission denied: {resource}")]
-    PermissionDenied {
-        /// The resource that couldn't be accessed
-        resource: String,
-    },
+    /// Rank a CSS class by severity using the theme's class names.
+    ///
+    /// Missing, unknown and unrecognized classes rank lowest, `good` and
+    /// `normal` share a rank, followed by `warning` and `critical`.
+    #[must_use]
+    pub fn severity(class: Option<&str>, theme: &Theme) -> u8 {
+        match class {
+            Some(c) if c == theme.critical => 3,
+            Some(c) if c == theme.warning => 2,
+            Some(c) if c == theme.normal || c == theme.good => 1,
+            _ => 0,
+        }
+    }
 
-    /// Timeout occurred while reading sensor data.
-    #[error("Timeout after {duration:?} while {operation}")]
-    Timeout {
-        /// How long the operation took before timing out
-        duration: std::time::Duration,
-        /// Description of what operation timed out
-        operation: String,
-    },
+    /// Compare two outputs and list every change, in field order.
+    ///
+    /// Percentage changes of less than `min_value_delta` points are ignored.
+    #[must_use]
+    pub fn diff(prev: &WaybarOutput, next: &WaybarOutput, min_value_delta: u8) -> Vec<ChangeEvent> {
+        let mut events = Vec::new();
+
+        if prev.text != next.text {
+            events.push(ChangeEvent::TextChanged {
+                from: prev.text.clone(),
+                to: next.text.clone(),
+            });
+        }
 
-    /// Invalid data format or unexpected values.
-    #[error("Invalid data: {message}")]
-    InvalidData {
-        /// Description of what makes the data invalid
-        message: String,
-        /// The invalid data if it can be safely displayed
-        data: Option<String>,
-    },
-}
+        if prev.tooltip != next.tooltip {
+            events.push(ChangeEvent::TooltipChanged);
+        }
 
-impl SensorError {
-    /// Create a new parse error with a simple message.
-    pub fn parse<S: Into<String>>(message: S) -> Self {
-        Self::Parse {
-            message: message.into(),
-            source: None,
+        if prev.class != next.class {
+            events.push(ChangeEvent::ClassChanged {
+                from: prev.class.clone(),
+                to: next.class.clone(),
+            });
         }
+
+        events.extend(value_change(prev.percentage, next.percentage, min_value_delta));
+
+        events
     }
 
-    /// Create a new parse error with a source error.
-    pub fn parse_with_source<S: Into<String>, E>(message: S, source: E) -> Self
-    where
-        E: std::error::Error + Send + Sync + 'static,
-    {
-        Self::Parse {
-            message: message.into(),
-            source: Some(Box::new(source)),
+    /// The percentage change from `from` to `to`, if it's at least `min_value_delta` points.
+    fn value_change(from: Option<u8>, to: Option<u8>, min_value_delta: u8) -> Option<ChangeEvent> {
+        if from == to {
+            return None;
         }
+        let delta = i16::from(to.unwrap_or(0)) - i16::from(from.unwrap_or(0));
+        // Appearing or disappearing values always count
+        let significant = from.is_none() || to.is_none() || delta.unsigned_abs() >= u16::from(min_value_delta);
+        significant.then_some(ChangeEvent::ValueChanged { from, to, delta })
     }
 
-    /// Create a new configuration error.
-    pub fn config<S: Into<String>>(message: S) -> Self {
-        Self::Config {
-            message: message.into(),
-            value: None,
-        }
+    /// Remembers the last output and turns each new one into change events.
+    #[derive(Debug, Clone, Default)]
+    pub struct ChangeTracker {
+        last: Option<WaybarOutput>,
+        /// Percentage of the last reported value change, so a slow drift
+        /// adds up until it crosses the minimum delta
+        last_reported: Option<u8>,
+        min_value_delta: u8,
     }
 
-    /// Create a new configuration error with the invalid value.
-    pub fn config_with_value<S: Into<String>, V: Into<String>>(message: S, value: V) -> Self {
-        Self::Config {
-            message: message.into(),
-            value: Some(value.into()),
+    impl ChangeTracker {
+        /// Create a tracker that reports every percentage change.
+        #[must_use]
+        pub fn new() -> Self {
+            Self::default()
         }
-    }
 
-    /// Create a new unavailable error.
-    pub fn unavailable<S: Into<String>>(reason: S) -> Self {
-        Self::Unavailable {
-            reason: reason.into(),
-            is_temporary: false,
+        /// Ignore percentage changes smaller than `delta` points.
+        #[must_use]
+        pub fn with_min_value_delta(mut self, delta: u8) -> Self {
+            self.min_value_delta = delta;
+            self
         }
-    }
 
-    /// Create a new temporary unavailable error.
-    pub fn temporarily_unavailable<S: Into<String>>(reason: S) -> Self {
-        Self::Unavailable {
-            reason: reason.into(),
-            is_temporary: true,
+        /// Record a new output and return what changed since the previous one.
+        ///
+        /// The first output is compared against an empty output, so an initial
+        /// `critical` reading is reported as a class transition from `None`.
+        /// The percentage is compared against the last *reported* value rather
+        /// than the previous output.
+        pub fn update(&mut self, output: &WaybarOutput) -> Vec<ChangeEvent> {
+            let empty = WaybarOutput::new(String::new());
+            let prev = self.last.as_ref().unwrap_or(&empty);
+            let mut events = diff(prev, output, self.min_value_delta);
+            events.retain(|event| !matches!(event, ChangeEvent::ValueChanged { .. }));
+            if let Some(event) = value_change(self.last_reported, output.percentage, self.min_value_delta) {
+                self.last_reported = output.percentage;
+                events.push(event);
+            }
+            self.last = Some(output.clone());
+            events
         }
-    }
 
-    /// Create a new permission denied error.
-    pub fn permission_denied<S: Into<String>>(resource: S) -> Self {
-        Self::PermissionDenied {
-            resource: resource.into(),
+        /// Whether `output` is identical to the last recorded output.
+        #[must_use]
+        pub fn is_duplicate(&self, output: &WaybarOutput) -> bool {
+            self.last.as_ref() == Some(output)
         }
-    }
 
-    /// Create a new timeout error.
-    pub fn timeout<S: Into<String>>(duration: std::time::Duration, operation: S) -> Self {
-        Self::Timeout {
-            duration,
-            operation: operation.into(),
+        /// The last recorded output, if any.
+        #[must_use]
+        pub fn last(&self) -> Option<&WaybarOutput> {
+            self.last.as_ref()
         }
-    }
 
-    /// Create a new invalid data error.
-    pub fn invalid_data<S: Into<String>>(message: S) -> Self {
-        Self::InvalidData {
-            message: message.into(),
-            data: None,
+        /// Forget the last output, e.g. after the sensor was reconfigured.
+        pub fn reset(&mut self) {
+            self.last = None;
+            self.last_reported = None;
         }
     }
+}
 
-    /// Create a new invalid data error with the problematic data.
-    pub fn invalid_data_with_value<S: Into<String>, D: Into<String>>(message: S, data: D) -> Self {
-        Self::InvalidData {
-            message: message.into(),
-            data: Some(data.into()),
-        }
+/// Presentation mode: minimal output for screen sharing and demos.
+///
+/// A marker file in the user's runtime directory switches every running
+/// sensor at once, without restarting the bar. While it exists
+/// [`Sensor::read_styled`] drops colors, reports the normal class instead of
+/// warning/critical and refreshes each sensor only every [`INTERVAL`].
+pub mod presentation {
+    use super::{SensorError, Theme, WaybarOutput};
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::{LazyLock, Mutex};
+    use std::time::{Duration, Instant};
+
+    /// How often sensors refresh while presentation mode is on.
+    pub const INTERVAL: Duration = Duration::from_secs(30);
+
+    static LAST_OUTPUT: LazyLock<Mutex<HashMap<String, (Instant, WaybarOutput)>>> =
+        LazyLock::new(|| Mutex::new(HashMap::new()));
+
+    /// Location of the marker file in the session's state directory, so the
+    /// mode neither survives a reboot nor leaks into other sessions.
+    pub fn marker_path() -> Option<PathBuf> {
+        super::session::state_dir().map(|dir| dir.join("presentation"))
     }
 
-    /// Check if this error represents a temporary condition.
+    /// Whether presentation mode is on.
     #[must_use]
-    pub fn is_temporary(&self) -> bool {
-        match self {
-            Self::Unavailable { is_temporary, .. } => *is_temporary,
-            Self::Timeout { .. } => true,
-            Self::Io(err) => matches!(
-                err.kind(),
-                std::io::ErrorKind::Interrupted | std::io::ErrorKind::TimedOut
-            ),
-            _ => false,
+    pub fn is_active() -> bool {
+        marker_path().is_some_and(|path| path.exists())
+    }
+
+    /// Turn presentation mode on or off for all sensors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the marker file cannot be created or removed.
+    pub fn set(active: bool) -> Result<(), SensorError> {
+        let path = marker_path()
+            .ok_or_else(|| SensorError::unavailable("no runtime or cache directory"))?;
+        if active {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, "")?;
+        } else if path.exists() {
+            std::fs::remove_file(&path)?;
         }
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::time::Duration;
+    /// Flip presentation mode, returning whether it is now on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the marker file cannot be created or removed.
+    pub fn toggle() -> Result<bool, SensorError> {
+        let active = !is_active();
+        set(active)?;
+        Ok(active)
+    }
 
-    #[test]
-    fn test_waybar_output_builder() {
-        let output = WaybarOutput::from_str("50%")
-            .with_tooltip("CPU Usage: 50%")
-            .with_class("normal")
-            .with_percentage(50);
+    /// Remove Pango markup tags, keeping the text between them.
+    #[must_use]
+    pub fn strip_markup(text: &str) -> String {
+        let mut plain = String::with_capacity(text.len());
+        let mut in_tag = false;
+        for c in text.chars() {
+            match c {
+                '<' => in_tag = true,
+                '>' if in_tag => in_tag = false,
+                _ if !in_tag => plain.push(c),
+                _ => {}
+            }
+        }
+        plain
+    }
 
-        assert_eq!(output.text, "50%");
-        assert_eq!(output.tooltip, Some("CPU Usage: 50%".to_owned()));
-        assert_eq!(output.class, Some("normal".to_owned()));
-        assert_eq!(output.percentage, Some(50));
+    /// Uncolored output in the normal state.
+    #[must_use]
+    pub fn minimal(output: &WaybarOutput, theme: &Theme) -> WaybarOutput {
+        WaybarOutput {
+            text: strip_markup(&output.text),
+            tooltip: output.tooltip.as_deref().map(strip_markup),
+            class: Some(theme.normal.clone()),
+            percentage: output.percentage,
+        }
     }
 
-    #[test]
-    #[should_panic(expected = "Percentage must be <= 100")]
-    fn test_waybar_output_invalid_percentage() {
-        let _ = WaybarOutput::from_str("150%").with_percentage(150);
+    /// Output of `sensor` from less than [`INTERVAL`] ago, if any.
+    pub(crate) fn recent(sensor: &str) -> Option<WaybarOutput> {
+        let last = LAST_OUTPUT.lock().ok()?;
+        let (at, output) = last.get(sensor)?;
+        (at.elapsed() < INTERVAL).then(|| output.clone())
     }
 
-    #[test]
-    fn test_icon_style_parse() {
-        assert_eq!(
-            "nerdfont".parse::<IconStyle>().unwrap(),
-            IconStyle::NerdFont
-        );
-        assert_eq!("nerd".parse::<IconStyle>().unwrap(), IconStyle::NerdFont);
-        assert_eq!("nf".parse::<IconStyle>().unwrap(), IconStyle::NerdFont);
-        assert_eq!("none".parse::<IconStyle>().unwrap(), IconStyle::None);
-        assert_eq!("no".parse::<IconStyle>().unwrap(), IconStyle::None);
-        assert_eq!("".parse::<IconStyle>().unwrap(), IconStyle::None);
+    /// Remember the output of `sensor` for [`recent`].
+    pub(crate) fn remember(sensor: &str, output: &WaybarOutput) {
+        if let Ok(mut last) = LAST_OUTPUT.lock() {
+            last.insert(sensor.to_owned(), (Instant::now(), output.clone()));
+        }
+    }
+}
 
-        assert!("invalid".parse::<IconStyle>().is_err());
+/// Versioned output for consumers other than Waybar.
+///
+/// Waybar only understands the plain [`WaybarOutput`] fields, so the
+/// versioned form is written to a separate stream (`versioned_output` in the
+/// config) while stdout stays unchanged. Each line is one JSON object with
+/// `schema_version` and `sensor` added to the Waybar fields. The minor
+/// version grows when fields are added; the major version changes when
+/// existing fields change meaning or are removed.
+///
+/// The stream is best a FIFO (`mkfifo`): lines go to whoever reads it and
+/// are dropped while nobody does. A regular file is rotated to `<path>.1`
+/// once it reaches [`MAX_FILE_SIZE`], so a file on tmpfs doesn't grow
+/// without bound.
+pub mod schema {
+    use super::WaybarOutput;
+    use serde::Serialize;
+    use std::fs::{self, OpenOptions};
+    use std::io::{self, Write};
+    use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+    use std::path::Path;
+
+    /// Version of the emitted JSON schema.
+    pub const SCHEMA_VERSION: &str = "1.0";
+
+    /// Size at which a regular output file is rotated.
+    pub const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+    /// `O_NONBLOCK`: opening a FIFO without a reader fails instead of blocking
+    const O_NONBLOCK: i32 = 0o4000;
+
+    /// Waybar output tagged with schema version and sensor name.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct VersionedOutput<'a> {
+        pub schema_version: &'static str,
+        pub sensor: &'a str,
+        #[serde(flatten)]
+        pub output: &'a WaybarOutput,
     }
 
-    #[test]
+    impl<'a> VersionedOutput<'a> {
+        /// Tag `output` of `sensor` with the current schema version.
+        #[must_use]
+        pub const fn new(sensor: &'a str, output: &'a WaybarOutput) -> Self {
+            Self { schema_version: SCHEMA_VERSION, sensor, output }
+        }
+    }
+
+    /// Write `output` of `sensor` to `path` as one versioned JSON line.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stream cannot be opened or written, including
+    /// a FIFO that nobody reads.
+    pub fn append(path: &Path, sensor: &str, output: &WaybarOutput) -> io::Result<()> {
+        let mut line = serde_json::to_vec(&VersionedOutput::new(sensor, output)).map_err(io::Error::other)?;
+        line.push(b'\n');
+
+        match fs::metadata(path) {
+            Ok(metadata) if metadata.file_type().is_fifo() => {
+                // A missing reader (ENXIO) or a full pipe (EAGAIN) drops the line
+                return OpenOptions::new().write(true).custom_flags(O_NONBLOCK).open(path)?.write_all(&line);
+            }
+            Ok(metadata) if metadata.len() >= MAX_FILE_SIZE => {
+                let mut rotated = path.as_os_str().to_owned();
+                rotated.push(".1");
+                fs::rename(path, rotated)?;
+            }
+            _ => {}
+        }
+        // One write per line keeps lines from concurrent sensors intact
+        OpenOptions::new().create(true).append(true).open(path)?.write_all(&line)
+    }
+}
+
+/// Deadline-based update scheduling.
+///
+/// Sleeping for the interval after each read lets the period stretch by
+/// however long the read took, so a 1 s sensor under load updates every
+/// 1.2 s. Both schedulers here tick on a fixed grid of the monotonic clock
+/// instead; deadlines missed under load or across a suspend are skipped
+/// rather than made up in a burst of back-to-back reads.
+pub mod schedule {
+    use super::{format, SensorConfig, TooltipDetail, WaybarOutput};
+    use std::time::{Duration, Instant};
+
+    /// Blocking ticker for sensors that do not run inside tokio.
+    #[derive(Debug, Clone)]
+    pub struct Ticker {
+        period: Duration,
+        next: Instant,
+    }
+
+    impl Ticker {
+        /// Ticker whose first tick is due immediately.
+        #[must_use]
+        pub fn new(period: Duration) -> Self {
+            Self { period, next: Instant::now() }
+        }
+
+        /// Sleep until the next deadline.
+        pub fn wait(&mut self) {
+            let now = Instant::now();
+            if self.next > now {
+                std::thread::sleep(self.next - now);
+            }
+            self.next = next_deadline(self.next, self.period, Instant::now());
+        }
+
+        /// Restart the grid so the next deadline is one period from now.
+        pub fn reset(&mut self) {
+            self.next = Instant::now() + self.period;
+        }
+    }
+
+    /// First deadline on the grid `deadline + n * period` that lies after `now`.
+    #[must_use]
+    pub fn next_deadline(deadline: Instant, period: Duration, now: Instant) -> Instant {
+        if period.is_zero() {
+            return now;
+        }
+        let late = now.saturating_duration_since(deadline);
+        let missed = (late.as_nanos() / period.as_nanos()) as u32;
+        deadline + period * (missed + 1)
+    }
+
+    /// Tokio interval that skips missed ticks instead of bursting.
+    #[must_use]
+    pub fn interval(period: Duration) -> tokio::time::Interval {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        interval
+    }
+
+    /// Append configured vs achieved interval to expert tooltips.
+    #[must_use]
+    pub fn with_interval_line(mut output: WaybarOutput, sensor: &str, config: &SensorConfig) -> WaybarOutput {
+        if config.visuals.tooltip_detail != TooltipDetail::Expert {
+            return output;
+        }
+        let Some(achieved) = super::wakeups::achieved_interval(sensor) else {
+            return output;
+        };
+        let line = format::key_value(
+            "Interval",
+            &format!("{}ms configured, {}ms achieved", config.update_interval, achieved.as_millis()),
+            config,
+        );
+        output.tooltip = Some(match output.tooltip {
+            Some(tooltip) => format!("{}\n{}", tooltip, line),
+            None => line,
+        });
+        output
+    }
+}
+
+/// Machine-readable build information.
+///
+/// Every binary prints this with `--build-info` so bug reports and
+/// `waysensor-rs-discover --build-info` can tell whether all modules come
+/// from the same build; a sensor built against a different core version
+/// otherwise fails in confusing ways (unknown config keys, missing fields).
+/// Use the [`build_info!`](crate::build_info!) macro so the package name and
+/// version are those of the calling crate.
+pub mod build_info {
+    use serde::{Deserialize, Serialize};
+
+    /// Version of the core crate.
+    pub const CORE_VERSION: &str = env!("CARGO_PKG_VERSION");
+    /// Abbreviated git commit the workspace was built from, or `unknown`.
+    pub const GIT_HASH: &str = env!("WAYSENSOR_GIT_HASH");
+    /// Target triple.
+    pub const TARGET: &str = env!("WAYSENSOR_TARGET");
+    /// Cargo profile (`debug` or `release`).
+    pub const PROFILE: &str = env!("WAYSENSOR_PROFILE");
+
+    /// Build information of one binary or library.
+    #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct BuildInfo {
+        /// Cargo package name
+        pub package: String,
+        /// Package version
+        pub version: String,
+        /// Version of the core crate it was built against
+        pub core_version: String,
+        /// Git commit of the build
+        pub git_hash: String,
+        /// Target triple
+        pub target: String,
+        /// Cargo profile
+        pub profile: String,
+        /// Enabled cargo features
+        pub features: Vec<String>,
+    }
+
+    impl BuildInfo {
+        /// Build information of `package` at `version` with `features` enabled.
+        #[must_use]
+        pub fn new(package: &str, version: &str, features: &[&str]) -> Self {
+            Self {
+                package: package.to_owned(),
+                version: version.to_owned(),
+                core_version: CORE_VERSION.to_owned(),
+                git_hash: GIT_HASH.to_owned(),
+                target: TARGET.to_owned(),
+                profile: PROFILE.to_owned(),
+                features: features.iter().map(|&f| f.to_owned()).collect(),
+            }
+        }
+
+        /// Serialize as a single JSON line.
+        #[must_use]
+        pub fn to_json(&self) -> String {
+            serde_json::to_string(self).unwrap_or_default()
+        }
+
+        /// Fields that must agree between modules of one installation but
+        /// differ from `other`, as `field: ours != theirs`.
+        #[must_use]
+        pub fn mismatches(&self, other: &Self) -> Vec<String> {
+            [
+                ("core_version", &self.core_version, &other.core_version),
+                ("git_hash", &self.git_hash, &other.git_hash),
+                ("target", &self.target, &other.target),
+            ]
+            .into_iter()
+            .filter(|(_, ours, theirs)| ours != theirs)
+            .map(|(field, ours, theirs)| format!("{}: {} != {}", field, ours, theirs))
+            .collect()
+        }
+    }
+
+    /// [`BuildInfo`] of the calling crate, listing which of the given
+    /// feature names are enabled in it.
+    ///
+    /// ```rust
+    /// let info = waysensor_rs_core::build_info!();
+    /// assert_eq!(info.package, "waysensor-rs-core");
+    /// assert!(info.features.is_empty());
+    /// ```
+    #[macro_export]
+    macro_rules! build_info {
+        ($($feature:literal),* $(,)?) => {{
+            let enabled: &[(bool, &str)] = &[$((cfg!(feature = $feature), $feature)),*];
+            let features: Vec<&str> = enabled.iter().filter(|(on, _)| *on).map(|(_, name)| *name).collect();
+            $crate::build_info::BuildInfo::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), &features)
+        }};
+    }
+}
+
+/// Login session identity for multi-seat and multi-user machines.
+///
+/// Several graphical sessions can run side by side, each with its own bar
+/// and sensor processes. Runtime state (presentation mode, wake-up reports)
+/// lives in a directory namespaced by user and display so they do not see
+/// each other's files, and `config-<seat>.ron` lets each seat have its own
+/// configuration.
+///
+/// The display (`$WAYLAND_DISPLAY`, else `$DISPLAY`) rather than the logind
+/// session ID tells sessions apart: a bar started as a systemd user unit has
+/// no `$XDG_SESSION_ID`, while a terminal of the same session does, but both
+/// see the compositor's display.
+pub mod session {
+    use std::os::unix::fs::MetadataExt;
+    use std::path::PathBuf;
+
+    /// Non-empty environment variable.
+    fn env(key: &str) -> Option<String> {
+        std::env::var(key).ok().filter(|value| !value.is_empty())
+    }
+
+    /// Seat of the session (`$XDG_SEAT`), e.g. `seat0`.
+    #[must_use]
+    pub fn seat() -> Option<String> {
+        env("XDG_SEAT").map(|seat| sanitize(&seat))
+    }
+
+    /// Display of the session, `$WAYLAND_DISPLAY` or else `$DISPLAY`.
+    #[must_use]
+    pub fn display() -> Option<String> {
+        env("WAYLAND_DISPLAY").or_else(|| env("DISPLAY")).map(|display| sanitize(&display))
+    }
+
+    /// User ID of this process.
+    #[must_use]
+    pub fn uid() -> Option<u32> {
+        std::fs::metadata("/proc/self").ok().map(|meta| meta.uid())
+    }
+
+    /// Namespace for runtime state: user ID plus display when known.
+    #[must_use]
+    pub fn namespace() -> String {
+        namespace_for(uid(), display().as_deref())
+    }
+
+    /// Namespace of user `uid` on display `display`.
+    #[must_use]
+    pub fn namespace_for(uid: Option<u32>, display: Option<&str>) -> String {
+        let user = uid.map_or_else(|| "user".to_string(), |uid| format!("uid{}", uid));
+        match display {
+            Some(display) => format!("{}-{}", user, display),
+            None => user,
+        }
+    }
+
+    /// Runtime state directory of this session, preferring
+    /// `$XDG_RUNTIME_DIR` so nothing survives a reboot.
+    #[must_use]
+    pub fn state_dir() -> Option<PathBuf> {
+        dirs::runtime_dir()
+            .or_else(dirs::cache_dir)
+            .map(|dir| dir.join("waysensor-rs").join(namespace()))
+    }
+
+    /// Keep only characters that are safe in file names.
+    fn sanitize(value: &str) -> String {
+        value.chars().filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')).collect()
+    }
+}
+
+/// Pausing sensors while the session is locked.
+///
+/// Waybar is hidden behind the lock screen, so reading sensors there only
+/// costs battery. Sensors with `pause_when_locked` set in their config
+/// section stop polling while logind reports the session as locked and
+/// refresh immediately after unlock. The lock state is asked from `loginctl`
+/// once and then kept current by a `gdbus monitor` of the session's
+/// `Lock`/`Unlock` and `PropertiesChanged` signals; without `gdbus` it falls
+/// back to asking every [`POLL_INTERVAL`].
+pub mod session_lock {
+    use super::SensorConfig;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+    use std::sync::{Condvar, LazyLock, Mutex, OnceLock, PoisonError};
+    use std::time::Duration;
+    use tokio::sync::Notify;
+
+    /// How often the lock state is checked when logind's signals can't be watched.
+    pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+    static LOCKED: Mutex<bool> = Mutex::new(false);
+    static CHANGED: Condvar = Condvar::new();
+    static CHANGED_ASYNC: LazyLock<Notify> = LazyLock::new(Notify::new);
+    static WATCHER: OnceLock<()> = OnceLock::new();
+
+    /// Whether `config` asks to pause the sensor while locked.
+    #[must_use]
+    pub fn enabled(config: &SensorConfig) -> bool {
+        config.get_custom("pause_when_locked").and_then(serde_json::Value::as_bool) == Some(true)
+    }
+
+    /// Parse the value of logind's `LockedHint` session property.
+    #[must_use]
+    pub fn parse_locked_hint(value: &str) -> Option<bool> {
+        match value.trim() {
+            "yes" => Some(true),
+            "no" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Lock state announced by a line of `gdbus monitor` output, if any.
+    #[must_use]
+    pub fn parse_monitor_line(line: &str) -> Option<bool> {
+        if line.contains("'LockedHint': <true>") || line.contains("org.freedesktop.login1.Session.Lock ") {
+            Some(true)
+        } else if line.contains("'LockedHint': <false>") || line.contains("org.freedesktop.login1.Session.Unlock ") {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// D-Bus object path of a logind session, escaped the way sd-bus does
+    /// (`2` becomes `/org/freedesktop/login1/session/_32`).
+    #[must_use]
+    pub fn session_path(id: &str) -> String {
+        let mut path = String::from("/org/freedesktop/login1/session/");
+        if id.is_empty() {
+            path.push('_');
+        }
+        for (i, byte) in id.bytes().enumerate() {
+            if byte.is_ascii_alphabetic() || (i > 0 && byte.is_ascii_digit()) {
+                path.push(char::from(byte));
+            } else {
+                path.push_str(&format!("_{:02x}", byte));
+            }
+        }
+        path
+    }
+
+    fn session_id() -> Option<String> {
+        if let Ok(id) = std::env::var("XDG_SESSION_ID") {
+            return Some(id);
+        }
+        Command::new("loginctl")
+            .args(["show-session", "auto", "--property=Id", "--value"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|id| !id.is_empty())
+    }
+
+    /// Ask logind whether our session is locked; unknown counts as unlocked.
+    #[must_use]
+    pub fn query() -> bool {
+        let session = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| "auto".to_string());
+        Command::new("loginctl")
+            .args(["show-session", &session, "--property=LockedHint", "--value"])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| parse_locked_hint(&String::from_utf8_lossy(&output.stdout)))
+            .unwrap_or(false)
+    }
+
+    fn set_locked(locked: bool) {
+        let mut state = LOCKED.lock().unwrap_or_else(PoisonError::into_inner);
+        if *state != locked {
+            *state = locked;
+            CHANGED.notify_all();
+            CHANGED_ASYNC.notify_waiters();
+        }
+    }
+
+    /// Follow the session's lock signals until `gdbus` exits, then poll.
+    fn watch() {
+        let monitor = session_id().and_then(|id| {
+            Command::new("gdbus")
+                .args(["monitor", "--system", "--dest", "org.freedesktop.login1", "--object-path"])
+                .arg(session_path(&id))
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()
+        });
+
+        if let Some(mut child) = monitor {
+            if let Some(stdout) = child.stdout.take() {
+                let mut subscribed = false;
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    // gdbus reports the name owner once its match is in place;
+                    // re-query to cover a change while it was subscribing
+                    if !std::mem::replace(&mut subscribed, true) {
+                        set_locked(query());
+                    }
+                    if let Some(locked) = parse_monitor_line(&line) {
+                        set_locked(locked);
+                    }
+                }
+            }
+            let _ = child.wait();
+        }
+
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            set_locked(query());
+        }
+    }
+
+    /// Whether the session is locked. The first call starts watching logind.
+    #[must_use]
+    pub fn is_locked() -> bool {
+        WATCHER.get_or_init(|| {
+            set_locked(query());
+            let _ = std::thread::Builder::new().name("session-lock".to_string()).spawn(watch);
+        });
+        *LOCKED.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Block while the session is locked, if enabled for `config`.
+    ///
+    /// Returns `true` if it waited, so the caller can restart its schedule.
+    pub fn wait(config: &SensorConfig) -> bool {
+        if !enabled(config) || !is_locked() {
+            return false;
+        }
+        let mut locked = LOCKED.lock().unwrap_or_else(PoisonError::into_inner);
+        while *locked {
+            locked = CHANGED.wait(locked).unwrap_or_else(PoisonError::into_inner);
+        }
+        true
+    }
+
+    /// Async [`wait`] for sensors running inside tokio.
+    pub async fn wait_async(config: &SensorConfig) -> bool {
+        if !enabled(config) || !is_locked() {
+            return false;
+        }
+        loop {
+            // Registered before checking, so an unlock in between still wakes us
+            let changed = CHANGED_ASYNC.notified();
+            if !is_locked() {
+                return true;
+            }
+            changed.await;
+        }
+    }
+}
+
+/// Desktop notifications.
+///
+/// Sensors that detect one-off events a glance at the bar can miss (an OOM
+/// kill, a failing disk) can raise a notification through the
+/// `org.freedesktop.Notifications` service on the session bus. The call goes
+/// through `busctl --user` on a background thread, so a missing notification
+/// daemon never delays a reading.
+pub mod notify {
+    use std::process::{Command, Stdio};
+
+    /// Notification urgency, as defined by the desktop notifications spec.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Urgency {
+        Low,
+        Normal,
+        Critical,
+    }
+
+    impl Urgency {
+        fn level(self) -> u8 {
+            match self {
+                Self::Low => 0,
+                Self::Normal => 1,
+                Self::Critical => 2,
+            }
+        }
+    }
+
+    /// `busctl` arguments for a `Notify` call.
+    #[must_use]
+    pub fn busctl_args(summary: &str, body: &str, urgency: Urgency) -> Vec<String> {
+        let level = urgency.level().to_string();
+        [
+            // `--` so the -1 timeout isn't taken for an option
+            "--user", "--", "call",
+            "org.freedesktop.Notifications", "/org/freedesktop/Notifications",
+            "org.freedesktop.Notifications", "Notify", "susssasa{sv}i",
+            // app name, replaces id, icon, summary, body
+            "waysensor-rs", "0", "dialog-warning", summary, body,
+            // no actions, one hint, default timeout
+            "0", "1", "urgency", "y", &level, "-1",
+        ]
+        .into_iter()
+        .map(str::to_owned)
+        .collect()
+    }
+
+    /// Show a desktop notification without waiting for it; failures are ignored.
+    pub fn send(summary: &str, body: &str, urgency: Urgency) {
+        let args = busctl_args(summary, body, urgency);
+        std::thread::spawn(move || {
+            let _ = Command::new("busctl")
+                .args(&args)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        });
+    }
+}
+
+/// Wake-up accounting across sensor processes.
+///
+/// Every read is a timer wake-up that keeps the CPU out of deep idle states,
+/// which on laptops costs more battery than the read itself. Each long-running
+/// sensor counts its reads and publishes the rate once a minute to a small
+/// JSON file in the runtime directory, so `waysensor-rs-discover --power-audit`
+/// can show which modules cost the most. Sensors run once per process by
+/// waybar (`--once`) exit before the first report and are not counted.
+pub mod wakeups {
+    use serde::{Deserialize, Serialize};
+    use std::collections::{HashMap, VecDeque};
+    use std::fmt::Write as _;
+    use std::path::{Path, PathBuf};
+    use std::sync::{LazyLock, Mutex};
+    use std::time::{Duration, Instant};
+
+    /// How often each sensor rewrites its report.
+    pub const REPORT_INTERVAL: Duration = Duration::from_secs(60);
+
+    /// Published wake-up statistics of one sensor process.
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    pub struct WakeupReport {
+        /// Sensor name as returned by `Sensor::name`
+        pub sensor: String,
+        /// Process ID of the sensor
+        pub pid: u32,
+        /// Reads since the sensor started
+        pub total: u64,
+        /// Seconds since the first read
+        pub uptime_secs: u64,
+    }
+
+    impl WakeupReport {
+        /// Average wake-ups per minute since the sensor started.
+        #[must_use]
+        pub fn per_minute(&self) -> f64 {
+            if self.uptime_secs == 0 {
+                return 0.0;
+            }
+            self.total as f64 * 60.0 / self.uptime_secs as f64
+        }
+    }
+
+    /// Gaps between reads averaged for [`achieved_interval`].
+    const INTERVAL_SAMPLES: usize = 10;
+
+    struct Counter {
+        started: Instant,
+        total: u64,
+        last_report: Instant,
+        last_read: Instant,
+        gaps: VecDeque<Duration>,
+    }
+
+    static COUNTERS: LazyLock<Mutex<HashMap<String, Counter>>> =
+        LazyLock::new(|| Mutex::new(HashMap::new()));
+
+    /// Directory holding one report per running sensor of this session.
+    pub fn report_dir() -> Option<PathBuf> {
+        super::session::state_dir().map(|dir| dir.join("wakeups"))
+    }
+
+    /// Count one wake-up of `sensor`, publishing its report when due.
+    ///
+    /// Failing to write the report is not worth interrupting the sensor for,
+    /// so errors are ignored.
+    pub fn record(sensor: &str) {
+        let Ok(mut counters) = COUNTERS.lock() else { return };
+        let now = Instant::now();
+        let counter = counters.entry(sensor.to_owned()).or_insert(Counter {
+            started: now,
+            total: 0,
+            last_report: now,
+            last_read: now,
+            gaps: VecDeque::with_capacity(INTERVAL_SAMPLES),
+        });
+        if counter.total > 0 {
+            if counter.gaps.len() == INTERVAL_SAMPLES {
+                counter.gaps.pop_front();
+            }
+            counter.gaps.push_back(now.duration_since(counter.last_read));
+        }
+        counter.last_read = now;
+        counter.total += 1;
+        if now.duration_since(counter.last_report) < REPORT_INTERVAL {
+            return;
+        }
+        counter.last_report = now;
+
+        let report = WakeupReport {
+            sensor: sensor.to_owned(),
+            pid: std::process::id(),
+            total: counter.total,
+            uptime_secs: now.duration_since(counter.started).as_secs(),
+        };
+        if let Some(dir) = report_dir() {
+            let _ = write_report(&dir, &report);
+        }
+    }
+
+    /// Mean time between the recent reads of `sensor` in this process.
+    pub fn achieved_interval(sensor: &str) -> Option<Duration> {
+        let counters = COUNTERS.lock().ok()?;
+        let gaps = &counters.get(sensor)?.gaps;
+        if gaps.is_empty() {
+            return None;
+        }
+        Some(gaps.iter().sum::<Duration>() / gaps.len() as u32)
+    }
+
+    /// Write `report` into `dir`, one file per sensor process.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or file cannot be written.
+    pub fn write_report(dir: &Path, report: &WakeupReport) -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        let name = format!("{}.{}.json", report.sensor.replace('/', "-"), report.pid);
+        let json = serde_json::to_string(report).map_err(std::io::Error::other)?;
+        std::fs::write(dir.join(name), json)
+    }
+
+    /// Reports of sensors that are still running, busiest first.
+    pub fn reports() -> Vec<WakeupReport> {
+        report_dir().map(|dir| reports_in(&dir, is_running)).unwrap_or_default()
+    }
+
+    /// Reports in `dir` whose process `alive` accepts, busiest first.
+    /// Reports of exited processes are removed.
+    pub fn reports_in(dir: &Path, alive: impl Fn(u32) -> bool) -> Vec<WakeupReport> {
+        let Ok(entries) = std::fs::read_dir(dir) else { return Vec::new() };
+        let mut reports: Vec<WakeupReport> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let content = std::fs::read_to_string(entry.path()).ok()?;
+                let report: WakeupReport = serde_json::from_str(&content).ok()?;
+                if alive(report.pid) {
+                    Some(report)
+                } else {
+                    let _ = std::fs::remove_file(entry.path());
+                    None
+                }
+            })
+            .collect();
+        reports.sort_by(|a, b| b.per_minute().total_cmp(&a.per_minute()));
+        reports
+    }
+
+    fn is_running(pid: u32) -> bool {
+        Path::new("/proc").join(pid.to_string()).exists()
+    }
+
+    /// Render reports in the Prometheus text exposition format, e.g. for the
+    /// node_exporter textfile collector.
+    #[must_use]
+    pub fn prometheus(reports: &[WakeupReport]) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP waysensor_wakeups_total Sensor reads since the sensor started.\n");
+        out.push_str("# TYPE waysensor_wakeups_total counter\n");
+        for report in reports {
+            let _ = writeln!(out, "waysensor_wakeups_total{{sensor=\"{}\",pid=\"{}\"}} {}",
+                report.sensor, report.pid, report.total);
+        }
+        out.push_str("# HELP waysensor_wakeups_per_minute Average sensor reads per minute.\n");
+        out.push_str("# TYPE waysensor_wakeups_per_minute gauge\n");
+        for report in reports {
+            let _ = writeln!(out, "waysensor_wakeups_per_minute{{sensor=\"{}\",pid=\"{}\"}} {:.2}",
+                report.sensor, report.pid, report.per_minute());
+        }
+        out
+    }
+}
+
+/// Pressure Stall Information (PSI) from `/proc/pressure/*`.
+///
+/// PSI reports the share of wall time in which tasks were stalled waiting on a
+/// resource, which reflects contention better than utilization does.
+///
+/// # Examples
+///
+/// ```rust
+/// use waysensor_rs_core::psi::PressureStats;
+///
+/// let stats = PressureStats::parse(
+///     "some avg10=1.50 avg60=0.75 avg300=0.20 total=12345\n\
+///      full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n",
+/// ).unwrap();
+/// assert_eq!(stats.some.avg10, 1.5);
+/// ```
+pub mod psi {
+    use super::SensorError;
+    use std::path::Path;
+
+    /// One line of a pressure file (`some` or `full`).
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct PressureLine {
+        /// Percentage of time stalled over the last 10 seconds
+        pub avg10: f64,
+        /// Percentage of time stalled over the last 60 seconds
+        pub avg60: f64,
+        /// Percentage of time stalled over the last 300 seconds
+        pub avg300: f64,
+        /// Total stall time in microseconds
+        pub total: u64,
+    }
+
+    /// Parsed contents of a pressure file.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct PressureStats {
+        /// At least one task stalled
+        pub some: PressureLine,
+        /// All non-idle tasks stalled (not reported for CPU on older kernels)
+        pub full: Option<PressureLine>,
+    }
+
+    impl PressureStats {
+        /// Read pressure stats for a resource (`cpu`, `memory` or `io`).
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the kernel was built without PSI or the file
+        /// cannot be parsed.
+        pub fn read(resource: &str) -> Result<Self, SensorError> {
+            Self::read_from_path(crate::sysroot::path("/proc/pressure").join(resource))
+        }
+
+        /// Read pressure stats from a specific file (useful for testing and cgroups).
+        pub fn read_from_path(path: impl AsRef<Path>) -> Result<Self, SensorError> {
+            let content = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    SensorError::unavailable("Pressure stall information not available (CONFIG_PSI)")
+                } else {
+                    SensorError::Io(e)
+                }
+            })?;
+            Self::parse(&content)
+        }
+
+        /// Parse the contents of a pressure file.
+        pub fn parse(content: &str) -> Result<Self, SensorError> {
+            let mut some = None;
+            let mut full = None;
+
+            for line in content.lines() {
+                let mut fields = line.split_whitespace();
+                let kind = fields.next();
+                let mut parsed = PressureLine::default();
+
+                for field in fields {
+                    let (key, value) = field.split_once('=')
+                        .ok_or_else(|| SensorError::parse(format!("Invalid pressure field: {}", field)))?;
+                    let as_f64 = || value.parse::<f64>()
+                        .map_err(|e| SensorError::parse_with_source(format!("Invalid {} value", key), e));
+                    match key {
+                        "avg10" => parsed.avg10 = as_f64()?,
+                        "avg60" => parsed.avg60 = as_f64()?,
+                        "avg300" => parsed.avg300 = as_f64()?,
+                        "total" => parsed.total = value.parse()
+                            .map_err(|e| SensorError::parse_with_source("Invalid total value", e))?,
+                        _ => {}
+                    }
+                }
+
+                match kind {
+                    Some("some") => some = Some(parsed),
+                    Some("full") => full = Some(parsed),
+                    _ => {}
+                }
+            }
+
+            Ok(Self {
+                some: some.ok_or_else(|| SensorError::parse("Missing 'some' pressure line"))?,
+                full,
+            })
+        }
+    }
+}
+
+/// Alternative root for the system paths sensors read (`/proc`, `/sys`,
+/// `/lib/modules`, ...).
+///
+/// Binaries set it from `--sys-root` so the integration harness can run them
+/// against a fixture tree instead of the host's kernel interfaces. Without a
+/// root every path is used as is.
+///
+/// ```rust
+/// use waysensor_rs_core::sysroot;
+///
+/// // No root set: the path is unchanged
+/// assert_eq!(sysroot::path("/proc/stat"), std::path::Path::new("/proc/stat"));
+/// ```
+pub mod sysroot {
+    use std::path::{Path, PathBuf};
+    use std::sync::OnceLock;
+
+    static ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+    /// Read system paths below `root` for the rest of the process. Only the
+    /// first call has an effect.
+    pub fn set(root: impl Into<PathBuf>) {
+        let _ = ROOT.set(root.into());
+    }
+
+    /// The root set with [`set`], if any.
+    pub fn get() -> Option<&'static Path> {
+        ROOT.get().map(PathBuf::as_path)
+    }
+
+    /// `path` below the root: `/proc/stat` becomes `<root>/proc/stat`.
+    pub fn path(path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        match get() {
+            Some(root) => root.join(path.strip_prefix("/").unwrap_or(path)),
+            None => path.to_path_buf(),
+        }
+    }
+}
+
+/// Allocation-free reads of small `/proc` and `/sys` files.
+///
+/// Sensors re-read the same handful of kernel files on every tick. These
+/// files are generated on demand and are almost always a few kilobytes, so
+/// reading them with a single positioned read into a stack buffer avoids the
+/// `String` allocation and the end-of-file `read` of [`std::fs::read_to_string`].
+/// Files that don't fit fall back to a normal read transparently.
+///
+/// ```rust,no_run
+/// use waysensor_rs_core::procfs;
+///
+/// let first_line = procfs::read_with("/proc/stat", |content| {
+///     content.lines().next().map(str::to_owned)
+/// })?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub mod procfs {
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::fs::FileExt;
+    use std::path::Path;
+
+    /// Size of the stack buffer used for the fast path.
+    ///
+    /// Large enough for `/proc/stat` (including the `intr` line) on typical
+    /// desktops and laptops.
+    pub const STACK_BUFFER_SIZE: usize = 16 * 1024;
+
+    /// Read a file and pass its contents to `f`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or isn't valid UTF-8.
+    pub fn read_with<R>(path: impl AsRef<Path>, f: impl FnOnce(&str) -> R) -> io::Result<R> {
+        let path = path.as_ref();
+        let mut buf = [0u8; STACK_BUFFER_SIZE];
+
+        match read_into(path, &mut buf)? {
+            Some(len) => {
+                let content = std::str::from_utf8(&buf[..len])
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                Ok(f(content))
+            }
+            // Re-read from scratch so the content is one consistent snapshot
+            None => Ok(f(&std::fs::read_to_string(path)?)),
+        }
+    }
+
+    /// Read and parse a file holding a single value, like `rx_bytes` or `temp1_input`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or doesn't parse as `T`.
+    pub fn read_value<T>(path: impl AsRef<Path>) -> io::Result<T>
+    where
+        T: std::str::FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        read_with(path, |content| content.trim().parse::<T>())?
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Pick the bracketed choice out of a sysfs selector such as
+    /// `always [madvise] never` or `s2idle [deep]`.
+    #[must_use]
+    pub fn selected_option(content: &str) -> Option<String> {
+        content.split_whitespace()
+            .find_map(|option| option.strip_prefix('[')?.strip_suffix(']'))
+            .map(str::to_owned)
+    }
+
+    /// Fill `buf` with the file's contents using one positioned read.
+    ///
+    /// procfs and sysfs hand out as much of a file as fits in the first
+    /// read, so a read that doesn't fill the buffer has reached the end.
+    /// Returns `None` if the file may not fit in the buffer.
+    fn read_into(path: &Path, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        let file = File::open(path)?;
+        loop {
+            match file.read_at(buf, 0) {
+                Ok(n) if n < buf.len() => return Ok(Some(n)),
+                Ok(_) => return Ok(None),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Temperature inputs of the hwmon class (`/sys/class/hwmon`).
+///
+/// Shared by the thermal sensor and the CPU sensor's package temperature so
+/// both enumerate devices and labels the same way.
+pub mod hwmon {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// Default location of the hwmon devices.
+    pub const HWMON_PATH: &str = "/sys/class/hwmon";
+
+    /// CPU hwmon drivers with the labels of their package sensor, best first.
+    pub const CPU_DRIVERS: &[(&str, &[&str])] = &[
+        ("zenpower", &["Tdie", "Tctl"]),
+        ("k10temp", &["Tdie", "Tctl"]),
+        ("coretemp", &["Package id 0"]),
+        ("cpu_thermal", &[]),
+    ];
+
+    /// One `tempN_input` file of an hwmon device.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct TempInput {
+        /// Driver name of the device (`name` file), e.g. `k10temp`
+        pub device: String,
+        /// The `N` of `tempN_input`
+        pub index: u32,
+        /// Contents of `tempN_label`, if the driver provides one
+        pub label: Option<String>,
+        /// Path of the `tempN_input` file
+        pub path: PathBuf,
+    }
+
+    /// Every temperature input under `hwmon`, ordered by device directory and index.
+    #[must_use]
+    pub fn temp_inputs(hwmon: &Path) -> Vec<TempInput> {
+        let Ok(entries) = fs::read_dir(hwmon) else { return Vec::new() };
+        let mut dirs: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+        dirs.sort();
+
+        let mut inputs = Vec::new();
+        for dir in dirs {
+            let Ok(name) = fs::read_to_string(dir.join("name")) else { continue };
+            let Ok(files) = fs::read_dir(&dir) else { continue };
+            let mut device_inputs: Vec<TempInput> = files
+                .flatten()
+                .filter_map(|entry| {
+                    let file_name = entry.file_name();
+                    let index = file_name.to_str()?.strip_prefix("temp")?.strip_suffix("_input")?.parse().ok()?;
+                    let label = fs::read_to_string(dir.join(format!("temp{}_label", index)))
+                        .ok()
+                        .map(|label| label.trim().to_owned());
+                    Some(TempInput { device: name.trim().to_owned(), index, label, path: entry.path() })
+                })
+                .collect();
+            device_inputs.sort_unstable_by_key(|input| input.index);
+            inputs.extend(device_inputs);
+        }
+        inputs
+    }
+
+    /// The preferred input of the first driver in `drivers` that is present.
+    ///
+    /// Each driver lists its wanted labels best first; without a matching
+    /// label the driver's first input is used.
+    #[must_use]
+    pub fn find_input(hwmon: &Path, drivers: &[(&str, &[&str])]) -> Option<TempInput> {
+        let inputs = temp_inputs(hwmon);
+        drivers.iter().find_map(|&(driver, labels)| {
+            let mut candidates = inputs.iter().filter(|input| input.device == driver);
+            labels.iter()
+                .find_map(|wanted| candidates.clone().find(|input| input.label.as_deref() == Some(*wanted)))
+                .or_else(|| candidates.next())
+                .cloned()
+        })
+    }
+}
+
+/// Sway and Hyprland IPC sockets.
+///
+/// Sway speaks the i3 protocol on `$SWAYSOCK`: every message is the
+/// `i3-ipc` magic, the payload length and the message type as native-endian
+/// 32-bit integers, then a JSON payload. Events arrive on a connection that
+/// subscribed to them, with the high bit of the type set.
+///
+/// Hyprland answers one plain-text request per connection on `.socket.sock`
+/// and writes events as `name>>data` lines to every client of
+/// `.socket2.sock`.
+pub mod ipc {
+    use serde_json::Value;
+    use std::io::{self, BufRead, BufReader, Read, Write};
+    use std::env;
+    use std::os::unix::net::UnixStream;
+    use std::path::{Path, PathBuf};
+
+    const SWAY_MAGIC: &[u8; 6] = b"i3-ipc";
+    pub const SWAY_RUN_COMMAND: u32 = 0;
+    pub const SWAY_GET_WORKSPACES: u32 = 1;
+    pub const SWAY_SUBSCRIBE: u32 = 2;
+    pub const SWAY_GET_TREE: u32 = 4;
+    pub const SWAY_GET_INPUTS: u32 = 100;
+    /// Event type of workspace changes.
+    pub const SWAY_WORKSPACE_EVENT: u32 = 0x8000_0000;
+    /// Event type of window changes, focus and title included.
+    pub const SWAY_WINDOW_EVENT: u32 = 0x8000_0003;
+    /// Event type of input device changes, layout switches included.
+    pub const SWAY_INPUT_EVENT: u32 = 0x8000_0015;
+
+    /// Sway's socket, from `$SWAYSOCK`.
+    pub fn sway_socket() -> Option<PathBuf> {
+        env::var_os("SWAYSOCK").map(PathBuf::from)
+    }
+
+    /// Socket directory of the Hyprland instance in
+    /// `$HYPRLAND_INSTANCE_SIGNATURE`.
+    pub fn hyprland_dir() -> Option<PathBuf> {
+        let signature = env::var_os("HYPRLAND_INSTANCE_SIGNATURE")?;
+        // Hyprland 0.40 moved its sockets from /tmp to the runtime dir
+        let runtime = env::var_os("XDG_RUNTIME_DIR").map(|dir| PathBuf::from(dir).join("hypr").join(&signature));
+        Some(runtime.filter(|dir| dir.exists()).unwrap_or_else(|| PathBuf::from("/tmp/hypr").join(&signature)))
+    }
+
+    pub(crate) fn sway_write(stream: &mut impl Write, kind: u32, payload: &str) -> io::Result<()> {
+        let length = u32::try_from(payload.len()).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+        let mut message = Vec::with_capacity(14 + payload.len());
+        message.extend_from_slice(SWAY_MAGIC);
+        message.extend_from_slice(&length.to_ne_bytes());
+        message.extend_from_slice(&kind.to_ne_bytes());
+        message.extend_from_slice(payload.as_bytes());
+        stream.write_all(&message)
+    }
+
+    /// Read one message: its type and payload.
+    pub(crate) fn sway_read(stream: &mut impl Read) -> io::Result<(u32, String)> {
+        let mut header = [0u8; 14];
+        stream.read_exact(&mut header)?;
+        if &header[..6] != SWAY_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Not an i3-ipc message"));
+        }
+        let length = u32::from_ne_bytes([header[6], header[7], header[8], header[9]]);
+        let kind = u32::from_ne_bytes([header[10], header[11], header[12], header[13]]);
+        let mut payload = vec![0u8; length as usize];
+        stream.read_exact(&mut payload)?;
+        Ok((kind, String::from_utf8_lossy(&payload).into_owned()))
+    }
+
+    /// Send a message of `kind` to Sway and return the reply's payload.
+    pub fn sway_request(socket: &Path, kind: u32, payload: &str) -> io::Result<String> {
+        let mut stream = UnixStream::connect(socket)?;
+        sway_write(&mut stream, kind, payload)?;
+        // The reply has the type of the request
+        loop {
+            let (reply, payload) = sway_read(&mut stream)?;
+            if reply == kind {
+                return Ok(payload);
+            }
+        }
+    }
+
+    /// Connection to Sway subscribed to `events`, a JSON array of event names.
+    pub fn sway_subscribe(socket: &Path, events: &str) -> io::Result<UnixStream> {
+        let mut stream = UnixStream::connect(socket)?;
+        sway_write(&mut stream, SWAY_SUBSCRIBE, events)?;
+        let (_, reply) = sway_read(&mut stream)?;
+        let reply: Value = serde_json::from_str(&reply).unwrap_or_default();
+        if reply["success"].as_bool() != Some(true) {
+            return Err(io::Error::other(format!("Subscription refused: {}", reply)));
+        }
+        Ok(stream)
+    }
+
+    /// Wait for the next event on a subscribed connection: its type and payload.
+    pub fn sway_event(stream: &mut UnixStream) -> io::Result<(u32, String)> {
+        sway_read(stream)
+    }
+
+    /// Send `command` to Hyprland and return the reply.
+    pub fn hyprland_request(dir: &Path, command: &str) -> io::Result<String> {
+        let mut stream = UnixStream::connect(dir.join(".socket.sock"))?;
+        stream.write_all(command.as_bytes())?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply)?;
+        Ok(reply)
+    }
+
+    /// Hyprland's event stream.
+    pub fn hyprland_events(dir: &Path) -> io::Result<BufReader<UnixStream>> {
+        Ok(BufReader::new(UnixStream::connect(dir.join(".socket2.sock"))?))
+    }
+
+    /// Wait for the next Hyprland event: its name and data.
+    pub fn hyprland_event(events: &mut BufReader<UnixStream>) -> io::Result<(String, String)> {
+        let mut line = String::new();
+        if events.read_line(&mut line)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        let (name, data) = line.trim_end().split_once(">>").unwrap_or((line.trim_end(), ""));
+        Ok((name.to_string(), data.to_string()))
+    }
+}
+
+/// Per-process CPU accounting from `/proc/<pid>/stat`.
+///
+/// Replaces shelling out to `ps`, whose `pcpu` column is the average over the
+/// whole process lifetime. [`ProcessCpuTracker`](process::ProcessCpuTracker)
+/// diffs CPU ticks between two scans, giving the usage over the last interval
+/// the way `top` reports it.
+pub mod process {
+    use super::procfs;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::time::Instant;
+
+    /// Kernel clock ticks per second used by `/proc/<pid>/stat`.
+    ///
+    /// `USER_HZ` is fixed at 100 on every architecture Linux exposes to userspace.
+    const USER_HZ: f64 = 100.0;
+
+    /// Raw accounting for one process at a point in time.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ProcessSample {
+        /// Process ID
+        pub pid: u32,
+        /// Executable name (`comm`), at most 15 bytes
+        pub name: String,
+        /// User plus system CPU time in clock ticks
+        pub cpu_ticks: u64,
+        /// Core the process last ran on
+        pub processor: Option<usize>,
+    }
+
+    impl ProcessSample {
+        /// Parse the contents of `/proc/<pid>/stat`.
+        ///
+        /// The name is enclosed in parentheses and may itself contain spaces
+        /// or parentheses, so fields are located from the last `)`.
+        pub fn parse_stat(content: &str) -> Option<Self> {
+            let open = content.find('(')?;
+            let close = content.rfind(')')?;
+            let pid = content[..open].trim().parse().ok()?;
+            let name = content.get(open + 1..close)?.to_string();
+
+            // Fields after the name, starting at field 3 (state)
+            let fields: Vec<&str> = content[close + 1..].split_whitespace().collect();
+            let utime: u64 = fields.get(11)?.parse().ok()?;
+            let stime: u64 = fields.get(12)?.parse().ok()?;
+            let processor = fields.get(36).and_then(|p| p.parse().ok());
+
+            Some(Self {
+                pid,
+                name,
+                cpu_ticks: utime + stime,
+                processor,
+            })
+        }
+    }
+
+    /// CPU usage of a process over the last sampling interval.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct ProcessCpu {
+        /// Process ID
+        pub pid: u32,
+        /// Executable name
+        pub name: String,
+        /// CPU usage in percent of one core (like `top`, can exceed 100)
+        pub cpu_percent: f64,
+        /// Core the process last ran on
+        pub processor: Option<usize>,
+    }
+
+    /// Read a sample of every process currently visible in `/proc`.
+    ///
+    /// Processes that exit while scanning are skipped.
+    pub fn scan() -> Vec<ProcessSample> {
+        let Ok(entries) = fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+
+        entries
+            .flatten()
+            .filter(|entry| {
+                entry.file_name().to_str().is_some_and(|n| n.bytes().all(|b| b.is_ascii_digit()))
+            })
+            .filter_map(|entry| {
+                procfs::read_with(entry.path().join("stat"), ProcessSample::parse_stat).ok().flatten()
+            })
+            .collect()
+    }
+
+    /// Tracks per-process CPU ticks between scans.
+    #[derive(Debug, Default)]
+    pub struct ProcessCpuTracker {
+        prev: HashMap<u32, u64>,
+        prev_time: Option<Instant>,
+    }
+
+    impl ProcessCpuTracker {
+        /// Create a tracker with no baseline.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Whether a baseline scan has been taken.
+        pub fn is_primed(&self) -> bool {
+            self.prev_time.is_some()
+        }
+
+        /// Scan `/proc` and return usage since the previous call, busiest first.
+        ///
+        /// The first call only records a baseline and returns an empty list.
+        pub fn update(&mut self) -> Vec<ProcessCpu> {
+            self.update_with(scan(), Instant::now())
+        }
+
+        /// Like [`update`](Self::update), with the samples and time supplied by the caller.
+        pub fn update_with(&mut self, samples: Vec<ProcessSample>, now: Instant) -> Vec<ProcessCpu> {
+            let elapsed = self.prev_time.map(|t| now.duration_since(t).as_secs_f64());
+
+            let mut usage = Vec::new();
+            let mut ticks = HashMap::with_capacity(samples.len());
+            for sample in samples {
+                if let (Some(elapsed), Some(&prev)) = (elapsed, self.prev.get(&sample.pid)) {
+                    if elapsed > 0.0 {
+                        let delta = sample.cpu_ticks.saturating_sub(prev) as f64;
+                        usage.push(ProcessCpu {
+                            pid: sample.pid,
+                            name: sample.name.clone(),
+                            cpu_percent: delta / USER_HZ / elapsed * 100.0,
+                            processor: sample.processor,
+                        });
+                    }
+                }
+                ticks.insert(sample.pid, sample.cpu_ticks);
+            }
+
+            self.prev = ticks;
+            self.prev_time = Some(now);
+
+            usage.sort_by(|a, b| b.cpu_percent.total_cmp(&a.cpu_percent));
+            usage
+        }
+    }
+}
+
+/// cgroup v2 accounting files.
+///
+/// Lets sensors scope their readings to a slice, service or container
+/// instead of the whole system. Paths are given relative to the unified
+/// hierarchy (`user.slice/user-1000.slice`) or as absolute paths under it.
+pub mod cgroup {
+    use super::{procfs, SensorError};
+    use std::path::{Path, PathBuf};
+
+    /// Mount point of the cgroup v2 unified hierarchy.
+    pub const ROOT: &str = "/sys/fs/cgroup";
+
+    /// Resolve a cgroup path to its directory in the unified hierarchy.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cgroup does not exist or is not a cgroup v2 group.
+    pub fn resolve(path: &str) -> Result<PathBuf, SensorError> {
+        resolve_in(&root(), path)
+    }
+
+    /// Root of the unified hierarchy, including systemd's hybrid layout where
+    /// it is mounted at `/sys/fs/cgroup/unified` next to the v1 controllers.
+    pub fn root() -> PathBuf {
+        let root = Path::new(ROOT);
+        let hybrid = root.join("unified");
+        if !root.join("cgroup.controllers").is_file() && hybrid.join("cgroup.controllers").is_file() {
+            hybrid
+        } else {
+            root.to_path_buf()
+        }
+    }
+
+    /// Resolve a cgroup path against a specific hierarchy root (useful for testing).
+    pub fn resolve_in(root: &Path, path: &str) -> Result<PathBuf, SensorError> {
+        let relative = Path::new(path).strip_prefix(root).unwrap_or(Path::new(path));
+        let dir = root.join(relative.strip_prefix("/").unwrap_or(relative));
+
+        if !dir.is_dir() {
+            return Err(SensorError::config_with_value("cgroup not found", dir.display().to_string()));
+        }
+        if !dir.join("cgroup.controllers").is_file() {
+            return Err(SensorError::unavailable(format!(
+                "{} is not a cgroup v2 group (is the unified hierarchy mounted?)",
+                dir.display()
+            )));
+        }
+        Ok(dir)
+    }
+
+    /// Cumulative CPU accounting from `cpu.stat`, in microseconds.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct CpuStat {
+        /// Total CPU time consumed by the group
+        pub usage_usec: u64,
+        /// User mode CPU time
+        pub user_usec: u64,
+        /// Kernel mode CPU time
+        pub system_usec: u64,
+        /// Periods in which the group hit its `cpu.max` quota
+        pub nr_throttled: u64,
+        /// Time the group spent throttled
+        pub throttled_usec: u64,
+    }
+
+    impl CpuStat {
+        /// Read `cpu.stat` of a cgroup directory.
+        pub fn read(dir: &Path) -> Result<Self, SensorError> {
+            Ok(procfs::read_with(dir.join("cpu.stat"), Self::parse)?)
+        }
+
+        /// Parse the contents of a `cpu.stat` file. Unknown keys are ignored.
+        pub fn parse(content: &str) -> Self {
+            let mut stat = Self::default();
+            for line in content.lines() {
+                let Some((key, value)) = line.split_once(' ') else { continue };
+                let Ok(value) = value.trim().parse() else { continue };
+                match key {
+                    "usage_usec" => stat.usage_usec = value,
+                    "user_usec" => stat.user_usec = value,
+                    "system_usec" => stat.system_usec = value,
+                    "nr_throttled" => stat.nr_throttled = value,
+                    "throttled_usec" => stat.throttled_usec = value,
+                    _ => {}
+                }
+            }
+            stat
+        }
+    }
+
+    /// CPU quota from `cpu.max` as a number of CPUs, or `None` when unlimited.
+    pub fn cpu_limit(dir: &Path) -> Option<f64> {
+        procfs::read_with(dir.join("cpu.max"), parse_cpu_max).ok().flatten()
+    }
+
+    /// Parse a `cpu.max` line (`"<quota|max> <period>"`) into a number of CPUs.
+    pub fn parse_cpu_max(content: &str) -> Option<f64> {
+        let mut fields = content.split_whitespace();
+        let quota: f64 = fields.next()?.parse().ok()?;
+        let period: f64 = fields.next()?.parse().ok()?;
+        (period > 0.0).then(|| quota / period)
+    }
+
+    /// Memory accounting from the `memory.*` interface files, in bytes.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub struct MemoryStat {
+        /// Memory currently charged to the group (`memory.current`)
+        pub current: u64,
+        /// Hard limit (`memory.max`), `None` when unlimited
+        pub max: Option<u64>,
+        /// Throttling threshold (`memory.high`), `None` when unlimited
+        pub high: Option<u64>,
+        /// Swap currently used by the group (`memory.swap.current`)
+        pub swap_current: Option<u64>,
+        /// Anonymous memory (`anon` in `memory.stat`)
+        pub anon: u64,
+        /// Page cache (`file` in `memory.stat`)
+        pub file: u64,
+        /// Kernel memory (`kernel` in `memory.stat`, 5.18+)
+        pub kernel: u64,
+    }
+
+    impl MemoryStat {
+        /// Read the memory interface files of a cgroup directory.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if `memory.current` is missing, i.e. the memory
+        /// controller is not enabled for the group.
+        pub fn read(dir: &Path) -> Result<Self, SensorError> {
+            let limit = |name: &str| procfs::read_with(dir.join(name), parse_memory_max).ok().flatten();
+            let mut stat = procfs::read_with(dir.join("memory.stat"), Self::parse).unwrap_or_default();
+            stat.current = procfs::read_value(dir.join("memory.current")).map_err(|_| {
+                SensorError::unavailable(format!("memory controller not enabled for {}", dir.display()))
+            })?;
+            stat.max = limit("memory.max");
+            stat.high = limit("memory.high");
+            stat.swap_current = procfs::read_value(dir.join("memory.swap.current")).ok();
+            Ok(stat)
+        }
+
+        /// Parse the breakdown of a `memory.stat` file. Unknown keys are ignored.
+        pub fn parse(content: &str) -> Self {
+            let mut stat = Self::default();
+            for line in content.lines() {
+                let Some((key, value)) = line.split_once(' ') else { continue };
+                let Ok(value) = value.trim().parse() else { continue };
+                match key {
+                    "anon" => stat.anon = value,
+                    "file" => stat.file = value,
+                    "kernel" => stat.kernel = value,
+                    _ => {}
+                }
+            }
+            stat
+        }
+    }
+
+    /// Parse a `memory.max`/`memory.high` value, `"max"` meaning unlimited.
+    pub fn parse_memory_max(content: &str) -> Option<u64> {
+        content.trim().parse().ok()
+    }
+}
+
+/// Trend tracking for predictive monitoring.
+///
+/// Shared by sensors that estimate when a resource runs out, such as disk
+/// space filling up or available memory running dry.
+pub mod trend {
+    use std::time::Instant;
+
+    /// Usage trend over a bounded window of samples.
+    ///
+    /// Samples are usually usage percentages, but any monotonic quantity (such as
+    /// a directory size in bytes) works; the trend is reported in units per day
+    /// or per second.
+    #[derive(Debug, Clone)]
+    pub struct UsageTrend {
+        /// Historical usage samples with timestamps
+        history: Vec<(Instant, f64)>,
+        /// Maximum history entries to keep
+        max_history: usize,
+    }
+
+    impl UsageTrend {
+        pub fn new(max_history: usize) -> Self {
+            Self {
+                history: Vec::with_capacity(max_history),
+                max_history,
+            }
+        }
+
+        pub fn add_sample(&mut self, timestamp: Instant, usage_percentage: f64) {
+            self.history.push((timestamp, usage_percentage));
+
+            // Keep only recent history
+            if self.history.len() > self.max_history {
+                self.history.remove(0);
+            }
+        }
+
+        /// Calculate usage trend in units per second.
+        pub fn trend_per_sec(&self) -> Option<f64> {
+            if self.history.len() < 2 {
+                return None;
+            }
+
+            let (first_time, first_usage) = self.history.first()?;
+            let (last_time, last_usage) = self.history.last()?;
+
+            let duration = last_time.duration_since(*first_time);
+            let usage_change = last_usage - first_usage;
+
+            if duration.as_secs() > 0 {
+                Some(usage_change / duration.as_secs_f64())
+            } else {
+                None
+            }
+        }
+
+        /// Calculate usage trend in percentage points per day.
+        pub fn trend_per_day(&self) -> Option<f64> {
+            self.trend_per_sec().map(|per_sec| per_sec * 24.0 * 3600.0)
+        }
+    }
+}
+
+/// Power-source aware sampling.
+///
+/// Some reads are expensive or wake hardware that would otherwise stay in a
+/// low-power state (a runtime-suspended discrete GPU, `nvidia-smi`). On battery
+/// [`EcoPolling`] stretches the interval of such sensors and skips reads that
+/// would wake a suspended device.
+pub mod power {
+    use super::{format, SensorConfig, TooltipDetail, WaybarOutput};
+    use std::fs;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    /// How long an AC/battery check stays valid.
+    const SOURCE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+    /// Whether the system is running on battery.
+    #[must_use]
+    pub fn on_battery() -> bool {
+        on_battery_in(Path::new("/sys/class/power_supply"))
+    }
+
+    /// Whether the supplies below a sysfs `power_supply` directory say the
+    /// system is running on battery (useful for testing).
+    ///
+    /// Peripheral batteries (mice, headsets) have `scope` `Device` and are ignored.
+    pub fn on_battery_in(dir: &Path) -> bool {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return false;
+        };
+        let read = |path: &Path, name: &str| fs::read_to_string(path.join(name)).map(|s| s.trim().to_owned()).ok();
+
+        let (mut adapters, mut ac_online, mut discharging) = (0, false, false);
+        let mut system_battery = false;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            match read(&path, "type").as_deref() {
+                Some("Mains" | "USB" | "USB_C" | "USB_PD") => {
+                    adapters += 1;
+                    ac_online |= read(&path, "online").as_deref() == Some("1");
+                }
+                Some("Battery") if read(&path, "scope").as_deref() != Some("Device") => {
+                    system_battery = true;
+                    discharging |= read(&path, "status").as_deref() == Some("Discharging");
+                }
+                _ => {}
+            }
+        }
+
+        // Without an adapter entry the battery status is all there is to go on
+        system_battery && if adapters > 0 { !ac_online } else { discharging }
+    }
+
+    /// Whether runtime power management has suspended a device, e.g.
+    /// `/sys/class/drm/card1/device`. Reading its sensors would wake it up.
+    #[must_use]
+    pub fn is_runtime_suspended(device: &Path) -> bool {
+        fs::read_to_string(device.join("power").join("runtime_status"))
+            .is_ok_and(|status| status.trim() == "suspended")
+    }
+
+    /// Sampling policy for expensive sensors.
+    ///
+    /// Call [`EcoPolling::before_read`] at the start of `read()` and return its
+    /// output if there is one, then pass the fresh output through
+    /// [`EcoPolling::after_read`].
+    #[derive(Debug)]
+    pub struct EcoPolling {
+        /// Interval on battery; zero disables the policy
+        interval: Duration,
+        /// Last AC/battery check
+        on_battery: Option<(Instant, bool)>,
+        /// Last full reading
+        last: Option<(Instant, WaybarOutput)>,
+    }
+
+    impl EcoPolling {
+        /// Interval used on battery unless configured otherwise.
+        pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(10);
+
+        pub fn new(interval: Duration) -> Self {
+            Self { interval, on_battery: None, last: None }
+        }
+
+        /// Policy with the interval from the `eco_interval_secs` config key
+        /// (0 disables it).
+        #[must_use]
+        pub fn from_config(config: &SensorConfig) -> Self {
+            let interval = config.get_custom("eco_interval_secs")
+                .and_then(|v| v.as_f64())
+                .map_or(Self::DEFAULT_INTERVAL, |secs| {
+                    // Out of range values are a config typo, not a reason to crash
+                    Duration::try_from_secs_f64(secs.max(0.0)).unwrap_or(Self::DEFAULT_INTERVAL)
+                });
+            Self::new(interval)
+        }
+
+        /// Whether the system is on battery and the policy applies.
+        pub fn is_active(&mut self) -> bool {
+            if self.interval.is_zero() {
+                return false;
+            }
+            match self.on_battery {
+                Some((checked, on_battery)) if checked.elapsed() < SOURCE_CHECK_INTERVAL => on_battery,
+                _ => {
+                    let on_battery = on_battery();
+                    self.on_battery = Some((Instant::now(), on_battery));
+                    on_battery
+                }
+            }
+        }
+
+        /// Output to use instead of a fresh read, if any.
+        ///
+        /// On battery this is the last reading while it is younger than the
+        /// eco interval, or a placeholder when `device` is runtime-suspended.
+        pub fn before_read(&mut self, device: Option<&Path>, config: &SensorConfig) -> Option<WaybarOutput> {
+            if !self.is_active() {
+                return None;
+            }
+            if let Some((at, output)) = &self.last {
+                if at.elapsed() < self.interval {
+                    return Some(output.clone());
+                }
+            }
+            let device = device.filter(|device| is_runtime_suspended(device))?;
+            let mut tooltip = format::key_value("Status", "suspended (not polled on battery)", config);
+            if let Some(line) = self.status_line(config) {
+                tooltip.push_str(&format!("\n{}", line));
+            }
+            Some(WaybarOutput {
+                text: format::with_icon_and_colors("off", &config.icons.gpu, config),
+                tooltip: Some(format!("{}\n{}", format::key_value("Device", &device.display().to_string(), config), tooltip)),
+                class: Some(config.theme.normal.clone()),
+                percentage: None,
+            })
+        }
+
+        /// Note the policy in the expert tooltip and remember the output.
+        pub fn after_read(&mut self, mut output: WaybarOutput, config: &SensorConfig) -> WaybarOutput {
+            if config.visuals.tooltip_detail == TooltipDetail::Expert {
+                if let (Some(line), Some(tooltip)) = (self.status_line(config), output.tooltip.as_mut()) {
+                    tooltip.push_str(&format!("\n{}", line));
+                }
+            }
+            self.last = Some((Instant::now(), output.clone()));
+            output
+        }
+
+        /// Tooltip line describing the active policy.
+        fn status_line(&mut self, config: &SensorConfig) -> Option<String> {
+            self.is_active().then(|| format::key_value(
+                "Power Policy",
+                &format!("eco polling: {}s (on battery)", self.interval.as_secs_f64()),
+                config,
+            ))
+        }
+    }
+}
+
+/// Common error types for sensor operations.
+///
+/// This enum provides a comprehensive set of error types that cover
+/// the most common failure modes in sensor implementations.
+#[derive(Debug, thiserror::Error)]
+pub enum SensorError {
+    /// I/O error occurred while reading sensor data.
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Error parsing sensor data from text format.
+    #[error("Parse error: {message}")]
+    Parse {
+        /// Description of what failed to parse
+        message: String,
+        /// Optional source error for chaining
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+
+    /// Configuration error (invalid settings, etc.).
+    #[error("Configuration error: {message}")]
+    Config {
+        /// Description of the configuration issue
+        message: String,
+        /// The invalid configuration value if applicable
+        value: Option<String>,
+    },
+
+    /// Sensor is not available on this system.
+    #[error("Sensor unavailable: {reason}")]
+    Unavailable {
+        /// Reason why the sensor is unavailable
+        reason: String,
+        /// Whether this is a temporary or permanent condition
+        is_temporary: bool,
+    },
+
+    /// Permission denied accessing sensor data.
+    #[error("Permission denied: {resource}")]
+    PermissionDenied {
+        /// The resource that couldn't be accessed
+        resource: String,
+    },
+
+    /// Timeout occurred while reading sensor data.
+    #[error("Timeout after {duration:?} while {operation}")]
+    Timeout {
+        /// How long the operation took before timing out
+        duration: std::time::Duration,
+        /// Description of what operation timed out
+        operation: String,
+    },
+
+    /// Invalid data format or unexpected values.
+    #[error("Invalid data: {message}")]
+    InvalidData {
+        /// Description of what makes the data invalid
+        message: String,
+        /// The invalid data if it can be safely displayed
+        data: Option<String>,
+    },
+}
+
+impl SensorError {
+    /// Create a new parse error with a simple message.
+    pub fn parse<S: Into<String>>(message: S) -> Self {
+        Self::Parse {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Create a new parse error with a source error.
+    pub fn parse_with_source<S: Into<String>, E>(message: S, source: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Self::Parse {
+            message: message.into(),
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// Create a new configuration error.
+    pub fn config<S: Into<String>>(message: S) -> Self {
+        Self::Config {
+            message: message.into(),
+            value: None,
+        }
+    }
+
+    /// Create a new configuration error with the invalid value.
+    pub fn config_with_value<S: Into<String>, V: Into<String>>(message: S, value: V) -> Self {
+        Self::Config {
+            message: message.into(),
+            value: Some(value.into()),
+        }
+    }
+
+    /// Create a new unavailable error.
+    pub fn unavailable<S: Into<String>>(reason: S) -> Self {
+        Self::Unavailable {
+            reason: reason.into(),
+            is_temporary: false,
+        }
+    }
+
+    /// Create a new temporary unavailable error.
+    pub fn temporarily_unavailable<S: Into<String>>(reason: S) -> Self {
+        Self::Unavailable {
+            reason: reason.into(),
+            is_temporary: true,
+        }
+    }
+
+    /// Create a new permission denied error.
+    pub fn permission_denied<S: Into<String>>(resource: S) -> Self {
+        Self::PermissionDenied {
+            resource: resource.into(),
+        }
+    }
+
+    /// Create a new timeout error.
+    pub fn timeout<S: Into<String>>(duration: std::time::Duration, operation: S) -> Self {
+        Self::Timeout {
+            duration,
+            operation: operation.into(),
+        }
+    }
+
+    /// Create a new invalid data error.
+    pub fn invalid_data<S: Into<String>>(message: S) -> Self {
+        Self::InvalidData {
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// Create a new invalid data error with the problematic data.
+    pub fn invalid_data_with_value<S: Into<String>, D: Into<String>>(message: S, data: D) -> Self {
+        Self::InvalidData {
+            message: message.into(),
+            data: Some(data.into()),
+        }
+    }
+
+    /// Check if this error represents a temporary condition.
+    #[must_use]
+    pub fn is_temporary(&self) -> bool {
+        match self {
+            Self::Unavailable { is_temporary, .. } => *is_temporary,
+            Self::Timeout { .. } => true,
+            Self::Io(err) => matches!(
+                err.kind(),
+                std::io::ErrorKind::Interrupted | std::io::ErrorKind::TimedOut
+            ),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_waybar_output_builder() {
+        let output = WaybarOutput::from_str("50%")
+            .with_tooltip("CPU Usage: 50%")
+            .with_class("normal")
+            .with_percentage(50);
+
+        assert_eq!(output.text, "50%");
+        assert_eq!(output.tooltip, Some("CPU Usage: 50%".to_owned()));
+        assert_eq!(output.class, Some("normal".to_owned()));
+        assert_eq!(output.percentage, Some(50));
+    }
+
+    #[test]
+    #[should_panic(expected = "Percentage must be <= 100")]
+    fn test_waybar_output_invalid_percentage() {
+        let _ = WaybarOutput::from_str("150%").with_percentage(150);
+    }
+
+    #[test]
+    fn test_icon_style_parse() {
+        assert_eq!(
+            "nerdfont".parse::<IconStyle>().unwrap(),
+            IconStyle::NerdFont
+        );
+        assert_eq!("nerd".parse::<IconStyle>().unwrap(), IconStyle::NerdFont);
+        assert_eq!("nf".parse::<IconStyle>().unwrap(), IconStyle::NerdFont);
+        assert_eq!("none".parse::<IconStyle>().unwrap(), IconStyle::None);
+        assert_eq!("no".parse::<IconStyle>().unwrap(), IconStyle::None);
+        assert_eq!("".parse::<IconStyle>().unwrap(), IconStyle::None);
+
+        assert!("invalid".parse::<IconStyle>().is_err());
+    }
+
+    #[test]
     fn test_theme_builder() {
         let theme = Theme::new()
             .with_normal("my-normal")
@@ -2451,6 +5382,247 @@ mod tests {
         let _ = SensorConfig::new().with_update_interval_ms(50);
     }
 
+    #[test]
+    fn test_build_info() {
+        let info = crate::build_info!();
+        assert_eq!(info.package, "waysensor-rs-core");
+        assert_eq!(info.core_version, info.version);
+        assert!(!info.git_hash.is_empty());
+
+        let parsed: build_info::BuildInfo = serde_json::from_str(&info.to_json()).unwrap();
+        assert_eq!(parsed, info);
+        assert!(info.mismatches(&parsed).is_empty());
+
+        let other = build_info::BuildInfo {
+            core_version: "0.0.1".to_string(),
+            git_hash: "0123456789ab".to_string(),
+            ..info.clone()
+        };
+        assert_eq!(info.mismatches(&other), vec![
+            format!("core_version: {} != 0.0.1", info.core_version),
+            format!("git_hash: {} != 0123456789ab", info.git_hash),
+        ]);
+    }
+
+    #[test]
+    fn test_session_namespace() {
+        assert_eq!(session::namespace_for(Some(1000), Some("wayland-1")), "uid1000-wayland-1");
+        assert_eq!(session::namespace_for(Some(1001), None), "uid1001");
+        assert_eq!(session::namespace_for(None, Some("0")), "user-0");
+
+        if let Some(dir) = session::state_dir() {
+            assert!(dir.ends_with(session::namespace()));
+            assert_eq!(wakeups::report_dir(), Some(dir.join("wakeups")));
+        }
+    }
+
+    #[test]
+    fn test_notify_args() {
+        let args = notify::busctl_args("Out of memory", "firefox was killed", notify::Urgency::Critical);
+        assert_eq!(args[..8], ["--user", "--", "call", "org.freedesktop.Notifications", "/org/freedesktop/Notifications",
+            "org.freedesktop.Notifications", "Notify", "susssasa{sv}i"]);
+        assert_eq!(args[11], "Out of memory");
+        assert_eq!(args[12], "firefox was killed");
+        assert_eq!(args[13..], ["0", "1", "urgency", "y", "2", "-1"]);
+    }
+
+    #[test]
+    fn test_session_lock() {
+        assert_eq!(session_lock::parse_locked_hint("yes\n"), Some(true));
+        assert_eq!(session_lock::parse_locked_hint("no"), Some(false));
+        assert_eq!(session_lock::parse_locked_hint(""), None);
+
+        assert_eq!(session_lock::session_path("2"), "/org/freedesktop/login1/session/_32");
+        assert_eq!(session_lock::session_path("c12"), "/org/freedesktop/login1/session/c12");
+        assert_eq!(
+            session_lock::parse_monitor_line("/org/freedesktop/login1/session/_32: org.freedesktop.DBus.Properties.PropertiesChanged ('org.freedesktop.login1.Session', {'LockedHint': <true>}, @as [])"),
+            Some(true)
+        );
+        assert_eq!(session_lock::parse_monitor_line("/org/freedesktop/login1/session/_32: org.freedesktop.login1.Session.Unlock ()"), Some(false));
+        assert_eq!(session_lock::parse_monitor_line("/org/freedesktop/login1/session/_32: org.freedesktop.login1.Session.Lock ()"), Some(true));
+        assert_eq!(session_lock::parse_monitor_line("The name org.freedesktop.login1 is owned by :1.4"), None);
+
+        let config = SensorConfig::default();
+        assert!(!session_lock::enabled(&config));
+        assert!(!session_lock::wait(&config));
+        assert!(session_lock::enabled(&config.with_custom("pause_when_locked", serde_json::Value::Bool(true))));
+    }
+
+    #[test]
+    fn test_versioned_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("versioned.jsonl");
+        let output = WaybarOutput::from_str("42%").with_class("normal");
+        schema::append(&path, "cpu", &output).unwrap();
+        schema::append(&path, "memory", &output.clone().with_percentage(42)).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+
+        let lines: Vec<serde_json::Value> = content.lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], serde_json::json!({
+            "schema_version": schema::SCHEMA_VERSION,
+            "sensor": "cpu",
+            "text": "42%",
+            "class": "normal",
+        }));
+        assert_eq!(lines[1]["sensor"], "memory");
+        assert_eq!(lines[1]["percentage"], 42);
+
+        // Waybar's own output is untouched
+        assert_eq!(serde_json::to_string(&output).unwrap(), r#"{"text":"42%","class":"normal"}"#);
+
+        // A full file is rotated before the next line
+        std::fs::write(&path, vec![b'x'; schema::MAX_FILE_SIZE as usize]).unwrap();
+        schema::append(&path, "cpu", &output).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert_eq!(std::fs::metadata(dir.path().join("versioned.jsonl.1")).unwrap().len(), schema::MAX_FILE_SIZE);
+
+        // A FIFO without a reader drops the line instead of blocking
+        let fifo = dir.path().join("versioned.fifo");
+        if std::process::Command::new("mkfifo").arg(&fifo).status().is_ok_and(|status| status.success()) {
+            assert!(schema::append(&fifo, "cpu", &output).is_err());
+        }
+    }
+
+    #[test]
+    fn test_deadline_scheduling() {
+        use schedule::next_deadline;
+
+        let start = Instant::now();
+        let second = Duration::from_secs(1);
+        // On time: next slot on the grid
+        assert_eq!(next_deadline(start, second, start + Duration::from_millis(200)), start + second);
+        // A slow read does not shift the grid
+        assert_eq!(next_deadline(start, second, start + Duration::from_millis(999)), start + second);
+        // Missed slots are skipped, not made up
+        assert_eq!(next_deadline(start, second, start + Duration::from_millis(3500)), start + second * 4);
+        assert_eq!(next_deadline(start, Duration::ZERO, start + second), start + second);
+    }
+
+    #[test]
+    fn test_wakeup_reports() {
+        use wakeups::{prometheus, reports_in, write_report, WakeupReport};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let report = |sensor: &str, pid, total| WakeupReport {
+            sensor: sensor.to_owned(),
+            pid,
+            total,
+            uptime_secs: 600,
+        };
+        write_report(dir, &report("memory", 10, 300)).unwrap();
+        write_report(dir, &report("cpu", 11, 600)).unwrap();
+        write_report(dir, &report("disk", 12, 10)).unwrap();
+
+        // pid 12 has exited
+        let reports = reports_in(dir, |pid| pid != 12);
+        let remaining = std::fs::read_dir(dir).unwrap().count();
+
+        assert_eq!(remaining, 2);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].sensor, "cpu");
+        assert_eq!(reports[0].per_minute(), 60.0);
+        assert_eq!(reports[1].per_minute(), 30.0);
+
+        let metrics = prometheus(&reports);
+        assert!(metrics.contains("waysensor_wakeups_total{sensor=\"cpu\",pid=\"11\"} 600\n"));
+        assert!(metrics.contains("waysensor_wakeups_per_minute{sensor=\"memory\",pid=\"10\"} 30.00\n"));
+    }
+
+    #[test]
+    fn test_presentation_output() {
+        assert_eq!(
+            presentation::strip_markup("<span color=\"#7aa2f7\">\u{f035b}</span> <span color=\"#f7768e\">95%</span>"),
+            "\u{f035b} 95%"
+        );
+
+        let output = WaybarOutput::from_str("<span color=\"#f7768e\">95%</span>")
+            .with_tooltip("<span color=\"#bb9af7\">Used:</span> 15GB")
+            .with_class("critical")
+            .with_percentage(95);
+        let minimal = presentation::minimal(&output, &Theme::default());
+        assert_eq!(minimal.text, "95%");
+        assert_eq!(minimal.tooltip.as_deref(), Some("Used: 15GB"));
+        assert_eq!(minimal.class.as_deref(), Some("normal"));
+        assert_eq!(minimal.percentage, Some(95));
+    }
+
+    #[test]
+    fn test_on_battery_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let supply = |name: &str, files: &[(&str, &str)]| {
+            let dir = root.join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            for (file, content) in files {
+                std::fs::write(dir.join(file), format!("{}\n", content)).unwrap();
+            }
+        };
+        supply("BAT0", &[("type", "Battery"), ("scope", "System"), ("status", "Discharging")]);
+        supply("hidpp_battery_0", &[("type", "Battery"), ("scope", "Device"), ("status", "Discharging")]);
+        supply("AC", &[("type", "Mains"), ("online", "0")]);
+        let unplugged = power::on_battery_in(root);
+        std::fs::write(root.join("AC").join("online"), "1\n").unwrap();
+        let plugged = power::on_battery_in(root);
+        std::fs::remove_dir_all(root.join("BAT0")).unwrap();
+        std::fs::write(root.join("AC").join("online"), "0\n").unwrap();
+        let desktop = power::on_battery_in(root);
+
+        assert!(unplugged);
+        assert!(!plugged);
+        // A discharging mouse does not make a desktop battery powered
+        assert!(!desktop);
+    }
+
+    #[test]
+    fn test_per_sensor_theme() {
+        let mut global = GlobalConfig::default();
+        global.sensors.insert(
+            "memory".to_string(),
+            serde_json::json!({"theme": {"warning": "mem-warn", "critical": "mem-crit"}}),
+        );
+
+        let memory = global.to_sensor_config_for("memory");
+        assert_eq!(memory.theme.warning, "mem-warn");
+        assert_eq!(memory.theme.critical, "mem-crit");
+        assert_eq!(memory.theme.normal, "normal");
+        assert_eq!(global.to_sensor_config_for("cpu").theme, Theme::default());
+        assert_eq!(memory.theme.class_for_thresholds(95.0, 70.0, 90.0), "mem-crit");
+    }
+
+    #[test]
+    fn test_per_sensor_status_colors() {
+        let mut global = GlobalConfig::default();
+        global.colors.text_color = Some("#c0caf5".to_string());
+        global.colors.status_colors.warning = Some("#e0af68".to_string());
+        global.colors.status_colors.critical = Some("#f7768e".to_string());
+        global.sensors.insert(
+            "network".to_string(),
+            serde_json::json!({"status_colors": {"critical": "#7aa2f7"}}),
+        );
+
+        let network = global.to_sensor_config_for("network");
+        let disk = global.to_sensor_config_for("disk");
+        assert_eq!(network.status_colors.critical.as_deref(), Some("#7aa2f7"));
+        assert_eq!(network.status_colors.warning.as_deref(), Some("#e0af68"));
+        assert_eq!(disk.status_colors.critical.as_deref(), Some("#f7768e"));
+
+        let output = || WaybarOutput::from_str("<span color=\"#c0caf5\">9 MB/s</span>");
+        let styled = network.apply_status_color(output().with_class("critical"));
+        assert_eq!(styled.text, "<span color=\"#7aa2f7\">9 MB/s</span>");
+        let styled = disk.apply_status_color(output().with_class("critical"));
+        assert_eq!(styled.text, "<span color=\"#f7768e\">9 MB/s</span>");
+        // Normal state keeps the text color
+        assert_eq!(disk.apply_status_color(output().with_class("normal")), output().with_class("normal"));
+
+        let plain = SensorConfig { status_colors: disk.status_colors.clone(), ..SensorConfig::default() };
+        let styled = plain.apply_status_color(WaybarOutput::from_str("95%").with_class("warning"));
+        assert_eq!(styled.text, "<span color=\"#e0af68\">95%</span>");
+    }
+
     #[test]
     fn test_bytes_to_human() {
         assert_eq!(format::bytes_to_human(0), "0B");
@@ -2474,6 +5646,19 @@ mod tests {
         assert_eq!(format::frequency_to_human(2400000000), "2.4GHz");
     }
 
+    #[test]
+    fn test_text_fitting() {
+        assert_eq!(format::truncate("Teardrop", 8), "Teardrop");
+        assert_eq!(format::truncate("Teardrop", 1), "…");
+        assert_eq!(format::scroll_window("Massive Attack", 8, 0), "Massive ");
+
+        let mut marquee = format::Marquee::default();
+        assert_eq!(marquee.next("Massive Attack", 8, true), "Massive ");
+        assert_eq!(marquee.next("Massive Attack", 8, true), "assive A");
+        assert_eq!(marquee.next("Massive Attack", 8, false), "assive A");
+        assert_eq!(marquee.next("Teardrop", 8, true), "Teardrop");
+    }
+
     #[test]
     fn test_with_icon() {
         assert_eq!(format::with_icon("50%", "󰍛", IconStyle::NerdFont, IconPosition::Before, 1), "󰍛 50%");
@@ -2517,4 +5702,312 @@ mod tests {
         let err = SensorError::unavailable("Not supported");
         assert!(!err.is_temporary());
     }
+
+    #[test]
+    fn test_procfs_read_with_fallback() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+
+        let small = dir.join("small");
+        std::fs::write(&small, "12345\n").unwrap();
+        assert_eq!(procfs::read_value::<u64>(&small).unwrap(), 12345);
+
+        // Larger than the stack buffer takes the fallback path
+        let large = dir.join("large");
+        let content = "x".repeat(procfs::STACK_BUFFER_SIZE * 2);
+        std::fs::write(&large, &content).unwrap();
+        assert_eq!(procfs::read_with(&large, str::len).unwrap(), content.len());
+
+        let exact = dir.join("exact");
+        std::fs::write(&exact, "y".repeat(procfs::STACK_BUFFER_SIZE)).unwrap();
+        assert_eq!(procfs::read_with(&exact, str::len).unwrap(), procfs::STACK_BUFFER_SIZE);
+
+        assert!(procfs::read_value::<u64>(dir.join("missing")).is_err());
+        std::fs::write(&small, "not a number").unwrap();
+        assert!(procfs::read_value::<u64>(&small).is_err());
+
+        assert_eq!(procfs::selected_option("always [madvise] never\n").as_deref(), Some("madvise"));
+        assert_eq!(procfs::selected_option("[none] integrity confidentiality").as_deref(), Some("none"));
+        assert_eq!(procfs::selected_option("always madvise never\n"), None);
+
+    }
+
+    #[test]
+    fn test_sysroot_path() {
+        // The only test that sets the process-wide root
+        sysroot::set("/tmp/fixture");
+        sysroot::set("/ignored");
+        assert_eq!(sysroot::get(), Some(std::path::Path::new("/tmp/fixture")));
+        assert_eq!(sysroot::path("/proc/stat"), std::path::Path::new("/tmp/fixture/proc/stat"));
+        assert_eq!(sysroot::path("sys/class"), std::path::Path::new("/tmp/fixture/sys/class"));
+    }
+
+    #[test]
+    fn test_hwmon_temp_inputs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for (dir, name, temps) in [
+            ("hwmon0", "nvme", &[(1, Some("Composite"))][..]),
+            ("hwmon1", "k10temp", &[(3, Some("Tccd1")), (1, Some("Tctl"))][..]),
+            ("hwmon2", "cpu_thermal", &[(1, None)][..]),
+        ] {
+            let path = root.join(dir);
+            std::fs::create_dir_all(&path).unwrap();
+            std::fs::write(path.join("name"), format!("{}\n", name)).unwrap();
+            for &(index, label) in temps {
+                std::fs::write(path.join(format!("temp{}_input", index)), "40000\n").unwrap();
+                if let Some(label) = label {
+                    std::fs::write(path.join(format!("temp{}_label", index)), format!("{}\n", label)).unwrap();
+                }
+            }
+        }
+
+        let inputs = hwmon::temp_inputs(root);
+        let found: Vec<(&str, u32)> = inputs.iter().map(|input| (input.device.as_str(), input.index)).collect();
+        assert_eq!(found, vec![("nvme", 1), ("k10temp", 1), ("k10temp", 3), ("cpu_thermal", 1)]);
+
+        let package = hwmon::find_input(root, hwmon::CPU_DRIVERS).unwrap();
+        assert_eq!(package.path, root.join("hwmon1/temp1_input"));
+        assert_eq!(package.label.as_deref(), Some("Tctl"));
+        assert!(hwmon::find_input(root, &[("coretemp", &[])]).is_none());
+    }
+
+    #[test]
+    fn test_sway_ipc_framing() {
+        use ipc::{sway_read, sway_write, SWAY_GET_INPUTS, SWAY_SUBSCRIBE};
+        use std::io::{self, Write};
+        use std::os::unix::net::UnixStream;
+
+        let (mut client, mut server) = UnixStream::pair().unwrap();
+        sway_write(&mut client, SWAY_GET_INPUTS, "").unwrap();
+        sway_write(&mut client, SWAY_SUBSCRIBE, r#"["input"]"#).unwrap();
+        assert_eq!(sway_read(&mut server).unwrap(), (SWAY_GET_INPUTS, String::new()));
+        assert_eq!(sway_read(&mut server).unwrap(), (SWAY_SUBSCRIBE, r#"["input"]"#.to_string()));
+
+        client.write_all(b"i3-xyz00000000").unwrap();
+        assert_eq!(sway_read(&mut server).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_process_stat_parsing_and_tracking() {
+        use process::{ProcessCpuTracker, ProcessSample};
+
+        let stat = "4242 (Web Content (x)) S 1 4242 4242 0 -1 4194560 100 0 0 0 \
+                    250 50 0 0 20 0 30 0 12345 0 0 18446744073709551615 0 0 0 0 0 0 0 0 0 0 0 0 17 3 0 0";
+        let sample = ProcessSample::parse_stat(stat).unwrap();
+        assert_eq!(sample.pid, 4242);
+        assert_eq!(sample.name, "Web Content (x)");
+        assert_eq!(sample.cpu_ticks, 300);
+        assert_eq!(sample.processor, Some(3));
+        assert!(ProcessSample::parse_stat("garbage").is_none());
+
+        let mut tracker = ProcessCpuTracker::new();
+        let start = std::time::Instant::now();
+        assert!(tracker.update_with(vec![sample.clone()], start).is_empty());
+        assert!(tracker.is_primed());
+
+        // 50 ticks over half a second is one full core
+        let later = ProcessSample { cpu_ticks: 350, ..sample };
+        let usage = tracker.update_with(vec![later], start + Duration::from_millis(500));
+        assert_eq!(usage.len(), 1);
+        assert!((usage[0].cpu_percent - 100.0).abs() < 0.01);
+        assert_eq!(usage[0].processor, Some(3));
+    }
+
+    #[test]
+    fn test_discovery_formatting() {
+        let items = vec![
+            DiscoveredItem::new("BAT0", "Li-ion").with_detail("capacity", "87%"),
+            DiscoveredItem::new("BAT10", "Li-poly"),
+        ];
+
+        let text = discovery::format_text("batteries", &items);
+        assert_eq!(
+            text,
+            "Available batteries (2):\n  BAT0   Li-ion\n           capacity: 87%\n  BAT10  Li-poly"
+        );
+        assert_eq!(discovery::format_text("batteries", &[]), "No batteries found");
+
+        let json: serde_json::Value = serde_json::from_str(&discovery::format_json(&items)).unwrap();
+        assert_eq!(json[0]["id"], "BAT0");
+        assert_eq!(json[0]["details"]["capacity"], "87%");
+        assert_eq!(json[1]["details"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_cgroup_cpu_accounting() {
+        let stat = cgroup::CpuStat::parse(
+            "usage_usec 5000000\nuser_usec 3000000\nsystem_usec 2000000\ncore_sched.force_idle_usec 0\nnr_periods 10\nnr_throttled 3\nthrottled_usec 120000\n",
+        );
+        assert_eq!(stat.usage_usec, 5_000_000);
+        assert_eq!(stat.system_usec, 2_000_000);
+        assert_eq!(stat.nr_throttled, 3);
+        assert_eq!(stat.throttled_usec, 120_000);
+
+        assert_eq!(cgroup::parse_cpu_max("max 100000\n"), None);
+        assert_eq!(cgroup::parse_cpu_max("150000 100000\n"), Some(1.5));
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let slice = root.join("user.slice");
+        std::fs::create_dir_all(&slice).unwrap();
+        std::fs::write(slice.join("cgroup.controllers"), "cpu memory\n").unwrap();
+        let relative = cgroup::resolve_in(root, "user.slice");
+        let leading_slash = cgroup::resolve_in(root, "/user.slice");
+        let absolute = cgroup::resolve_in(root, &slice.display().to_string());
+        let missing = cgroup::resolve_in(root, "system.slice");
+
+        assert_eq!(relative.unwrap(), slice);
+        assert_eq!(leading_slash.unwrap(), slice);
+        assert_eq!(absolute.unwrap(), slice);
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_cgroup_memory_accounting() {
+        assert_eq!(cgroup::parse_memory_max("max\n"), None);
+        assert_eq!(cgroup::parse_memory_max("2147483648\n"), Some(2_147_483_648));
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        std::fs::write(dir.join("memory.current"), "734003200\n").unwrap();
+        std::fs::write(dir.join("memory.max"), "1073741824\n").unwrap();
+        std::fs::write(dir.join("memory.high"), "max\n").unwrap();
+        std::fs::write(dir.join("memory.stat"), "anon 524288000\nfile 157286400\nkernel 52428800\nsock 0\n").unwrap();
+        let stat = cgroup::MemoryStat::read(dir);
+
+        let stat = stat.unwrap();
+        assert_eq!(stat.current, 734_003_200);
+        assert_eq!(stat.max, Some(1_073_741_824));
+        assert_eq!(stat.high, None);
+        assert_eq!(stat.swap_current, None);
+        assert_eq!(stat.anon, 524_288_000);
+        assert_eq!(stat.file, 157_286_400);
+        assert_eq!(stat.kernel, 52_428_800);
+    }
+
+    #[test]
+    fn test_psi_parsing() {
+        let stats = psi::PressureStats::parse(
+            "some avg10=8.45 avg60=6.98 avg300=6.09 total=63022221\nfull avg10=0.10 avg60=0.00 avg300=0.00 total=42\n",
+        ).unwrap();
+        assert_eq!(stats.some.avg10, 8.45);
+        assert_eq!(stats.some.avg60, 6.98);
+        assert_eq!(stats.some.total, 63022221);
+        assert_eq!(stats.full.unwrap().avg10, 0.10);
+
+        // Older kernels only report "some" for CPU
+        let stats = psi::PressureStats::parse("some avg10=1.00 avg60=0.50 avg300=0.25 total=1\n").unwrap();
+        assert!(stats.full.is_none());
+
+        assert!(psi::PressureStats::parse("").is_err());
+        assert!(psi::PressureStats::parse("some avg10=abc").is_err());
+    }
+
+    #[test]
+    fn test_reading_default_adapter() {
+        let output = WaybarOutput::from_str("42%").with_percentage(42);
+        let reading = Reading::from_output(&output);
+        assert_eq!(reading.value, 42.0);
+        assert_eq!(reading.unit, "%");
+        assert!(reading.extra.is_empty());
+
+        let reading = Reading::from_output(&WaybarOutput::from_str("up"));
+        assert!(reading.value.is_nan());
+    }
+
+    #[test]
+    fn test_diff_identical_outputs() {
+        let output = WaybarOutput::from_str("50%").with_class("normal").with_percentage(50);
+        assert!(diff::diff(&output, &output, 0).is_empty());
+
+        let mut tracker = diff::ChangeTracker::new();
+        tracker.update(&output);
+        assert!(tracker.is_duplicate(&output));
+        assert!(tracker.update(&output).is_empty());
+    }
+
+    #[test]
+    fn test_diff_class_transitions() {
+        use diff::ChangeEvent;
+
+        let theme = Theme::default();
+        let normal = WaybarOutput::from_str("50%").with_class("normal");
+        let warning = WaybarOutput::from_str("50%").with_class("warning");
+        let critical = WaybarOutput::from_str("50%").with_class("critical");
+        let unclassed = WaybarOutput::from_str("50%");
+
+        let events = diff::diff(&normal, &critical, 0);
+        assert_eq!(events, vec![ChangeEvent::ClassChanged {
+            from: Some("normal".to_owned()),
+            to: Some("critical".to_owned()),
+        }]);
+        assert!(events[0].is_escalation(&theme));
+        assert!(!events[0].is_recovery(&theme));
+
+        let events = diff::diff(&critical, &warning, 0);
+        assert!(events[0].is_recovery(&theme));
+
+        // Class appearing or disappearing is still a transition
+        let events = diff::diff(&unclassed, &warning, 0);
+        assert!(events[0].is_escalation(&theme));
+        let events = diff::diff(&critical, &unclassed, 0);
+        assert!(events[0].is_recovery(&theme));
+
+        // good and normal share a severity, so neither direction escalates
+        let good = WaybarOutput::from_str("50%").with_class("good");
+        let events = diff::diff(&good, &normal, 0);
+        assert_eq!(events.len(), 1);
+        assert!(!events[0].is_escalation(&theme));
+        assert!(!events[0].is_recovery(&theme));
+
+        // Custom theme class names are respected, unknown names rank lowest
+        let custom = Theme::new().with_critical("hot");
+        let hot = WaybarOutput::from_str("50%").with_class("hot");
+        assert!(diff::diff(&normal, &hot, 0)[0].is_escalation(&custom));
+        assert!(!diff::diff(&normal, &hot, 0)[0].is_escalation(&theme));
+    }
+
+    #[test]
+    fn test_change_tracker_first_update_and_value_delta() {
+        use diff::ChangeEvent;
+
+        let mut tracker = diff::ChangeTracker::new().with_min_value_delta(5);
+
+        let first = WaybarOutput::from_str("90%").with_class("critical").with_percentage(90);
+        let events = tracker.update(&first);
+        assert!(events.contains(&ChangeEvent::ClassChanged {
+            from: None,
+            to: Some("critical".to_owned()),
+        }));
+        assert!(events.contains(&ChangeEvent::ValueChanged { from: None, to: Some(90), delta: 90 }));
+
+        // Small value changes are filtered, text changes are not
+        let events = tracker.update(&WaybarOutput::from_str("92%").with_class("critical").with_percentage(92));
+        assert_eq!(events, vec![ChangeEvent::TextChanged { from: "90%".to_owned(), to: "92%".to_owned() }]);
+
+        let events = tracker.update(&WaybarOutput::from_str("80%").with_class("critical").with_percentage(80));
+        assert!(events.contains(&ChangeEvent::ValueChanged { from: Some(90), to: Some(80), delta: -10 }));
+
+        // A slow drift is measured from the last reported value
+        assert_eq!(tracker.update(&WaybarOutput::from_str("82%").with_class("critical").with_percentage(82)).len(), 1);
+        assert_eq!(tracker.update(&WaybarOutput::from_str("84%").with_class("critical").with_percentage(84)).len(), 1);
+        let events = tracker.update(&WaybarOutput::from_str("86%").with_class("critical").with_percentage(86));
+        assert!(events.contains(&ChangeEvent::ValueChanged { from: Some(80), to: Some(86), delta: 6 }));
+
+        tracker.reset();
+        assert!(tracker.last().is_none());
+    }
+
+    #[test]
+    fn test_eco_polling_interval_from_config() {
+        let config = SensorConfig::default();
+        let interval = |secs: f64| {
+            let policy = power::EcoPolling::from_config(&config.clone().with_custom("eco_interval_secs", serde_json::json!(secs)));
+            format!("{:?}", policy)
+        };
+        assert!(interval(2.5).contains("interval: 2.5s"));
+        assert!(interval(-1.0).contains("interval: 0ns"));
+        assert!(interval(1e30).contains("interval: 10s"));
+    }
 }
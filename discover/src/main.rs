@@ -2,14 +2,15 @@ use clap::Parser;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use waysensor_rs_core::{build_info::BuildInfo, presentation, wakeups};
 
 #[derive(Parser)]
 #[command(name = "waysensor-rs-discover")]
 #[command(about = "Hardware discovery tool for waysensor sensors")]
 #[command(version)]
 struct Args {
-    /// Output format: json, ron, waybar-config
+    /// Output format: json, ron, waybar-config (prometheus with --power-audit)
     #[arg(short, long, default_value = "json")]
     format: String,
 
@@ -40,6 +41,18 @@ struct Args {
     /// Verbose output
     #[arg(short, long)]
     verbose: bool,
+
+    /// Switch all running sensors to minimal output for screen sharing (on, off, toggle)
+    #[arg(long, value_name = "MODE", value_parser = ["on", "off", "toggle"])]
+    presentation: Option<String>,
+
+    /// Summarize timer wake-ups of running sensors and which cost the most battery
+    #[arg(long)]
+    power_audit: bool,
+
+    /// Print build information of this tool and every installed sensor, flagging mismatched builds
+    #[arg(long)]
+    build_info: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,7 +96,8 @@ struct GpuInfo {
     vendor: String,
     model: String,
     driver: String,
-    metrics_path: Option<String>,
+    /// DRM card number, passed to waysensor-rs-gpu as --card
+    card: Option<u32>,
     available: bool,
 }
 
@@ -121,6 +135,27 @@ struct WaybarConfig {
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     
+    // Handled before the banner so keybindings get a one-line answer
+    if let Some(mode) = &args.presentation {
+        let active = match mode.as_str() {
+            "toggle" => presentation::toggle()?,
+            mode => {
+                presentation::set(mode == "on")?;
+                mode == "on"
+            }
+        };
+        println!("presentation mode {}", if active { "on" } else { "off" });
+        return Ok(());
+    }
+    
+    if args.power_audit {
+        return run_power_audit(&args);
+    }
+    
+    if args.build_info {
+        return run_build_info();
+    }
+    
     println!("🔍 waysensor-rs Hardware Discovery & Configuration");
     println!("=============================================");
     
@@ -327,53 +362,28 @@ fn discover_disks() -> Result<Vec<DiskInfo>, Box<dyn std::error::Error>> {
 }
 
 fn discover_gpus() -> Result<Vec<GpuInfo>, Box<dyn std::error::Error>> {
-    let mut gpus = Vec::new();
-    
-    // Check for AMD GPUs
-    if let Ok(entries) = fs::read_dir("/sys/class/drm") {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    if name.starts_with("card") && !name.contains("-") {
-                        let device_path = path.join("device");
-                        let gpu_metrics_path = device_path.join("gpu_metrics");
-                        
-                        if gpu_metrics_path.exists() {
-                            // Try to read vendor information
-                            let vendor_path = device_path.join("vendor");
-                            let device_id_path = device_path.join("device");
-                            
-                            let vendor = fs::read_to_string(&vendor_path)
-                                .unwrap_or_default()
-                                .trim()
-                                .to_string();
-                            
-                            let device_id = fs::read_to_string(&device_id_path)
-                                .unwrap_or_default()
-                                .trim()
-                                .to_string();
-                            
-                            let vendor_name = match vendor.as_str() {
-                                "0x1002" => "AMD",
-                                "0x10de" => "NVIDIA",
-                                "0x8086" => "Intel",
-                                _ => "Unknown",
-                            };
-                            
-                            gpus.push(GpuInfo {
-                                vendor: vendor_name.to_string(),
-                                model: format!("GPU {} ({})", name, device_id),
-                                driver: "amdgpu".to_string(), // Detected from gpu_metrics presence
-                                metrics_path: Some(gpu_metrics_path.to_string_lossy().to_string()),
-                                available: true,
-                            });
-                        }
-                    }
-                }
+    let gpus = waysensor_rs_gpu::detect_cards()
+        .into_iter()
+        .map(|gpu| {
+            let device_path = Path::new("/sys/class/drm").join(format!("card{}", gpu.card)).join("device");
+            let device_id = fs::read_to_string(device_path.join("device"))
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            let driver = fs::read_link(device_path.join("driver"))
+                .ok()
+                .and_then(|link| link.file_name().map(|name| name.to_string_lossy().to_string()))
+                .unwrap_or_default();
+            
+            GpuInfo {
+                vendor: gpu.vendor.name().to_string(),
+                model: format!("GPU card{} ({})", gpu.card, device_id),
+                driver,
+                card: Some(gpu.card),
+                available: true,
             }
-        }
-    }
+        })
+        .collect();
     
     Ok(gpus)
 }
@@ -531,10 +541,10 @@ fn generate_waybar_config(hardware: &HardwareInfo) -> Result<WaybarConfig, Box<d
                 format!("custom/waysensor-rs-gpu-{}", i)
             };
             
-            let mut exec_args = vec!["waysensor-rs-amd-gpu", "--once"];
-            if let Some(path) = &gpu.metrics_path {
-                exec_args.push("--file");
-                exec_args.push(path);
+            let mut exec_args = vec!["waysensor-rs-gpu".to_string(), "--once".to_string()];
+            if let Some(card) = gpu.card {
+                exec_args.push("--card".to_string());
+                exec_args.push(card.to_string());
             }
             
             modules.insert(module_name, serde_json::json!({
@@ -628,11 +638,11 @@ fn discover_hardware_smart(verbose: bool) -> Result<HardwareInfo, Box<dyn std::e
     
     // Test GPU sensors
     for gpu in &mut hardware.gpus {
-        if let Some(path) = &gpu.metrics_path {
-            if let Ok(output) = std::process::Command::new("waysensor-rs-amd-gpu")
+        if let Some(card) = gpu.card {
+            if let Ok(output) = std::process::Command::new("waysensor-rs-gpu")
                 .arg("--once")
-                .arg("--file")
-                .arg(path)
+                .arg("--card")
+                .arg(card.to_string())
                 .output() {
                 if output.status.success() {
                     if verbose {
@@ -689,7 +699,7 @@ fn check_required_binaries(hardware: &HardwareInfo) -> Vec<(String, bool)> {
     
     for gpu in &hardware.gpus {
         if gpu.available {
-            binaries.push(("waysensor-rs-amd-gpu".to_string(), check_binary("waysensor-rs-amd-gpu")));
+            binaries.push(("waysensor-rs-gpu".to_string(), check_binary("waysensor-rs-gpu")));
             break;
         }
     }
@@ -846,7 +856,7 @@ fn run_benchmark(_args: &Args) -> Result<(), Box<dyn std::error::Error>> {
     let sensors = [
         ("CPU", "waysensor-rs-cpu"),
         ("Memory", "waysensor-rs-memory"),
-        ("AMD GPU", "waysensor-rs-amd-gpu"),
+        ("GPU", "waysensor-rs-gpu"),
         ("Disk", "waysensor-rs-disk"),
     ];
     
@@ -890,6 +900,118 @@ fn run_benchmark(_args: &Args) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Wake-ups per minute above which a sensor is worth slowing down (faster than every 5s).
+const BUSY_WAKEUPS_PER_MIN: f64 = 12.0;
+
+fn run_power_audit(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    let reports = wakeups::reports();
+    
+    if args.format == "prometheus" {
+        print!("{}", wakeups::prometheus(&reports));
+        return Ok(());
+    }
+    
+    println!("⚡ waysensor-rs Power Audit");
+    println!("==========================");
+    
+    if reports.is_empty() {
+        println!("No running sensors have reported yet.");
+        println!("Sensors report once a minute; sensors run with --once are not counted.");
+        return Ok(());
+    }
+    
+    let total: f64 = reports.iter().map(|r| r.per_minute()).sum();
+    println!("{:<24} {:>12} {:>7} {:>9}", "Sensor", "Wake-ups/min", "Share", "Total");
+    for report in &reports {
+        println!("{:<24} {:>12.1} {:>6.0}% {:>9}",
+            report.sensor,
+            report.per_minute(),
+            report.per_minute() / total * 100.0,
+            report.total);
+    }
+    println!("{:<24} {:>12.1}", "All sensors", total);
+    
+    let busy: Vec<_> = reports.iter().filter(|r| r.per_minute() > BUSY_WAKEUPS_PER_MIN).collect();
+    if !busy.is_empty() {
+        println!();
+        println!("💡 Recommendations:");
+        for report in busy {
+            let saved = (1.0 - BUSY_WAKEUPS_PER_MIN / report.per_minute()) * 100.0;
+            println!("  • {}: wakes every {:.1}s; --interval 5000 would cut its wake-ups by {:.0}%",
+                report.sensor, 60.0 / report.per_minute(), saved);
+            if report.sensor.contains("gpu") {
+                println!("    GPU sensors also poll less on battery with \"eco_interval_secs\"");
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// Sensor binaries checked by `--build-info`.
+const SENSOR_BINARIES: &[&str] = &[
+    "waysensor-rs-cpu",
+    "waysensor-rs-memory",
+    "waysensor-rs-disk",
+    "waysensor-rs-network",
+    "waysensor-rs-battery",
+    "waysensor-rs-thermal",
+    "waysensor-rs-amd-gpu",
+    "waysensor-rs-intel-gpu",
+    "waysensor-rs-nvidia-gpu",
+    "waysensor-rs-gpu",
+    "waysensor-rs-audio",
+    "waysensor-rs-backlight",
+    "waysensor-rs-bluetooth",
+    "waysensor-rs-media",
+    "waysensor-rs-updates",
+    "waysensor-rs-systemd",
+    "waysensor-rs-uptime",
+    "waysensor-rs-weather",
+    "waysensor-rs-keyboard",
+    "waysensor-rs-compositor",
+    "waysensor-rs-containers",
+];
+
+/// Ask `binary` for its build information, preferring the copy installed
+/// next to this tool over the one on `PATH`.
+fn query_build_info(binary: &str) -> Option<BuildInfo> {
+    let program = std::env::current_exe().ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(binary)))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from(binary));
+    let output = std::process::Command::new(program).arg("--build-info").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    serde_json::from_slice(&output.stdout).ok()
+}
+
+fn run_build_info() -> Result<(), Box<dyn std::error::Error>> {
+    let own = waysensor_rs_core::build_info!();
+    let mut modules = serde_json::Map::new();
+    let mut mismatches = serde_json::Map::new();
+    for &binary in SENSOR_BINARIES {
+        // Not installed, or too old to know --build-info
+        let Some(info) = query_build_info(binary) else {
+            modules.insert(binary.to_string(), serde_json::Value::Null);
+            continue;
+        };
+        let differences = own.mismatches(&info);
+        if !differences.is_empty() {
+            mismatches.insert(binary.to_string(), serde_json::json!(differences));
+        }
+        modules.insert(binary.to_string(), serde_json::to_value(info)?);
+    }
+    
+    println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+        "discover": own,
+        "modules": modules,
+        "mismatches": mismatches,
+    }))?);
+    Ok(())
+}
+
 // Generate complete waybar setup
 fn generate_complete_waybar_setup(hardware: &HardwareInfo, args: &Args) -> Result<(), Box<dyn std::error::Error>> {
     println!("🎯 Generating Complete Waybar Setup");
@@ -975,10 +1097,10 @@ fn generate_complete_waybar_config(hardware: &HardwareInfo) -> Result<serde_json
             
             modules.push(module_name.clone());
             
-            let mut exec_args = vec!["waysensor-rs-amd-gpu", "--once"];
-            if let Some(path) = &gpu.metrics_path {
-                exec_args.push("--file");
-                exec_args.push(path);
+            let mut exec_args = vec!["waysensor-rs-gpu".to_string(), "--once".to_string()];
+            if let Some(card) = gpu.card {
+                exec_args.push("--card".to_string());
+                exec_args.push(card.to_string());
             }
             
             config.insert(module_name, serde_json::json!({
@@ -1217,7 +1339,7 @@ echo ""
     }
     for gpu in &hardware.gpus {
         if gpu.available {
-            binaries_to_check.push("waysensor-rs-amd-gpu");
+            binaries_to_check.push("waysensor-rs-gpu");
             break;
         }
     }
@@ -1326,9 +1448,9 @@ echo "📦 Installing binaries to ~/.local/bin..."
     
     for gpu in &hardware.gpus {
         if gpu.available {
-            script.push_str("if [ -f \"target/release/waysensor-rs-amd-gpu\" ]; then\n");
-            script.push_str("    cp target/release/waysensor-rs-amd-gpu ~/.local/bin/\n");
-            script.push_str("    echo \"  ✅ Installed waysensor-rs-amd-gpu\"\n");
+            script.push_str("if [ -f \"target/release/waysensor-rs-gpu\" ]; then\n");
+            script.push_str("    cp target/release/waysensor-rs-gpu ~/.local/bin/\n");
+            script.push_str("    echo \"  ✅ Installed waysensor-rs-gpu\"\n");
             script.push_str("fi\n");
             break;
         }
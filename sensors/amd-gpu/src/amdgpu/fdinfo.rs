@@ -0,0 +1,262 @@
+//! Per-process GPU usage from DRM fdinfo.
+//!
+//! Every open render node has an fdinfo entry in which amdgpu reports the
+//! nanoseconds the client kept each engine busy (`drm-engine-gfx: 1234 ns`)
+//! and its memory. Diffing two scans gives each process' engine load. A
+//! client shared between processes (fd passing, fork) has a single
+//! `drm-client-id` and is counted once.
+//!
+//! A scan looks at every fd of every process, so the sensor only does it
+//! when `show_processes` is enabled for the GPU.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    time::Instant,
+};
+
+/// One DRM client of the monitored GPU.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DrmClient {
+    pub client_id: u64,
+    /// PCI slot of the GPU, e.g. `0000:03:00.0`
+    pub pdev: Option<String>,
+    /// Busy time per engine (`gfx`, `compute`, `dec`, ...) in nanoseconds
+    pub engines: Vec<(String, u64)>,
+    /// VRAM in KiB
+    pub vram_kib: u64,
+}
+
+impl DrmClient {
+    /// Parse an fdinfo file; `None` unless it belongs to an amdgpu client.
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut driver = None;
+        let mut client = Self { client_id: 0, pdev: None, engines: Vec::new(), vram_kib: 0 };
+        let mut has_id = false;
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(':') else { continue };
+            let value = value.trim();
+            let number = || value.split_whitespace().next()?.parse::<u64>().ok();
+            match key {
+                "drm-driver" => driver = Some(value),
+                "drm-client-id" => {
+                    client.client_id = number()?;
+                    has_id = true;
+                }
+                "drm-pdev" => client.pdev = Some(value.to_string()),
+                "drm-memory-vram" => client.vram_kib = parse_memory_kib(value).unwrap_or(0),
+                _ => {
+                    if let (Some(engine), Some(ns)) = (key.strip_prefix("drm-engine-"), number()) {
+                        client.engines.push((engine.to_string(), ns));
+                    }
+                }
+            }
+        }
+        (driver == Some("amdgpu") && has_id).then_some(client)
+    }
+}
+
+/// A `drm-memory-<region>` value in KiB. The kernel prints plain bytes or
+/// scales the value to KiB or MiB (and GiB on newer kernels).
+fn parse_memory_kib(value: &str) -> Option<u64> {
+    let mut parts = value.split_whitespace();
+    let amount = parts.next()?.parse::<u64>().ok()?;
+    match parts.next() {
+        None => Some(amount / 1024),
+        Some("KiB") => Some(amount),
+        Some("MiB") => Some(amount * 1024),
+        Some("GiB") => Some(amount * 1024 * 1024),
+        Some(_) => None,
+    }
+}
+
+/// A DRM client with the process holding it.
+#[derive(Debug, Clone)]
+pub struct ClientSample {
+    pub pid: u32,
+    pub name: String,
+    pub client: DrmClient,
+}
+
+/// Every amdgpu client of the GPU at `pdev` that is visible to this user.
+pub fn scan(pdev: Option<&str>) -> Vec<ClientSample> {
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    let mut seen = HashSet::new();
+    let mut samples = Vec::new();
+    for process in processes.flatten() {
+        let Some(pid) = process.file_name().to_str().and_then(|pid| pid.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Ok(fds) = fs::read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        let mut name = None;
+        for fd in fds.flatten() {
+            // Only render nodes have DRM fdinfo; skip everything else cheaply
+            let is_drm = fs::read_link(fd.path()).is_ok_and(|target| target.starts_with("/dev/dri"));
+            if !is_drm {
+                continue;
+            }
+            let fdinfo = process.path().join("fdinfo").join(fd.file_name());
+            let Some(client) = fs::read_to_string(fdinfo).ok().and_then(|content| DrmClient::parse(&content)) else {
+                continue;
+            };
+            let other_gpu = matches!((pdev, client.pdev.as_deref()), (Some(wanted), Some(found)) if wanted != found);
+            if other_gpu || !seen.insert(client.client_id) {
+                continue;
+            }
+            let name = name.get_or_insert_with(|| process_name(&process.path())).clone();
+            samples.push(ClientSample { pid, name, client });
+        }
+    }
+    samples
+}
+
+fn process_name(proc_dir: &Path) -> String {
+    fs::read_to_string(proc_dir.join("comm"))
+        .map(|comm| comm.trim().to_string())
+        .unwrap_or_else(|_| "?".to_string())
+}
+
+/// GPU load of a process over the last sampling interval.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessGpu {
+    pub pid: u32,
+    pub name: String,
+    /// Load of the process' busiest engine in percent
+    pub busy_percent: f64,
+    /// That engine, e.g. `gfx`
+    pub engine: String,
+    pub vram_kib: u64,
+}
+
+/// Tracks engine busy time per client between scans.
+#[derive(Debug, Default)]
+pub struct ProcessTracker {
+    prev: HashMap<u64, HashMap<String, u64>>,
+    prev_time: Option<Instant>,
+}
+
+impl ProcessTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scan the clients of the GPU at `pdev` and return the load per
+    /// process since the previous call, busiest first.
+    ///
+    /// The first call only records a baseline and returns an empty list.
+    pub fn update(&mut self, pdev: Option<&str>) -> Vec<ProcessGpu> {
+        self.update_with(scan(pdev), Instant::now())
+    }
+
+    /// Like [`update`](Self::update), with the samples and time supplied by the caller.
+    pub fn update_with(&mut self, samples: Vec<ClientSample>, now: Instant) -> Vec<ProcessGpu> {
+        let elapsed_ns = self.prev_time.map(|t| now.duration_since(t).as_nanos() as f64);
+
+        let mut processes: HashMap<u32, ProcessGpu> = HashMap::new();
+        let mut counters = HashMap::with_capacity(samples.len());
+        for sample in samples {
+            let engines: HashMap<String, u64> = sample.client.engines.into_iter().collect();
+            if let Some(elapsed_ns) = elapsed_ns.filter(|&elapsed| elapsed > 0.0) {
+                let prev = self.prev.get(&sample.client.client_id);
+                let entry = processes.entry(sample.pid).or_insert_with(|| ProcessGpu {
+                    pid: sample.pid,
+                    name: sample.name.clone(),
+                    busy_percent: 0.0,
+                    engine: String::new(),
+                    vram_kib: 0,
+                });
+                entry.vram_kib += sample.client.vram_kib;
+                // Clients opened since the last scan have no baseline yet
+                if let Some(prev) = prev {
+                    for (engine, &busy) in &engines {
+                        let before = prev.get(engine).copied().unwrap_or(busy);
+                        let percent = (busy.saturating_sub(before) as f64 / elapsed_ns * 100.0).min(100.0);
+                        if percent > entry.busy_percent {
+                            entry.busy_percent = percent;
+                            entry.engine = engine.clone();
+                        }
+                    }
+                }
+            }
+            counters.insert(sample.client.client_id, engines);
+        }
+
+        self.prev = counters;
+        self.prev_time = Some(now);
+
+        let mut processes: Vec<ProcessGpu> = processes.into_values()
+            .filter(|process| process.busy_percent > 0.0)
+            .collect();
+        processes.sort_by(|a, b| b.busy_percent.total_cmp(&a.busy_percent));
+        processes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn fdinfo(client_id: u64, gfx_ns: u64, vram: &str) -> String {
+        format!(
+            "pos:\t0\nflags:\t02100002\ndrm-driver:\tamdgpu\ndrm-pdev:\t0000:03:00.0\n\
+             drm-client-id:\t{}\ndrm-memory-vram:\t{}\ndrm-engine-gfx:\t{} ns\ndrm-engine-dec:\t0 ns\n",
+            client_id, vram, gfx_ns
+        )
+    }
+
+    fn sample(pid: u32, name: &str, content: &str) -> ClientSample {
+        ClientSample { pid, name: name.to_string(), client: DrmClient::parse(content).unwrap() }
+    }
+
+    #[test]
+    fn test_drm_client_parsing() {
+        let client = DrmClient::parse(&fdinfo(42, 1_000_000, "2048 KiB")).unwrap();
+        assert_eq!(client.client_id, 42);
+        assert_eq!(client.pdev.as_deref(), Some("0000:03:00.0"));
+        assert_eq!(client.engines, vec![("gfx".to_string(), 1_000_000), ("dec".to_string(), 0)]);
+        assert_eq!(client.vram_kib, 2048);
+
+        // Scaled and unscaled memory values
+        assert_eq!(DrmClient::parse(&fdinfo(1, 0, "300 MiB")).unwrap().vram_kib, 300 * 1024);
+        assert_eq!(DrmClient::parse(&fdinfo(1, 0, "2 GiB")).unwrap().vram_kib, 2 * 1024 * 1024);
+        assert_eq!(DrmClient::parse(&fdinfo(1, 0, "8192")).unwrap().vram_kib, 8);
+
+        // Other drivers and clients without an id are skipped
+        assert!(DrmClient::parse(&fdinfo(1, 0, "0 KiB").replace("amdgpu", "i915")).is_none());
+        assert!(DrmClient::parse("drm-driver:\tamdgpu\ndrm-engine-gfx:\t5 ns\n").is_none());
+    }
+
+    #[test]
+    fn test_process_tracker() {
+        let mut tracker = ProcessTracker::new();
+        let start = Instant::now();
+
+        // The first scan is only a baseline
+        let first = vec![sample(100, "game", &fdinfo(1, 0, "1024 KiB")), sample(200, "mpv", &fdinfo(2, 0, "0 KiB"))];
+        assert!(tracker.update_with(first, start).is_empty());
+
+        // 500 ms of gfx time in one second is 50%; a second client of the
+        // same process adds its VRAM; idle processes are left out
+        let second = vec![
+            sample(100, "game", &fdinfo(1, 500_000_000, "1024 KiB")),
+            sample(100, "game", &fdinfo(3, 0, "512 KiB")),
+            sample(200, "mpv", &fdinfo(2, 0, "0 KiB")),
+        ];
+        let processes = tracker.update_with(second, start + Duration::from_secs(1));
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].pid, 100);
+        assert_eq!(processes[0].engine, "gfx");
+        assert!((processes[0].busy_percent - 50.0).abs() < 1e-9);
+        assert_eq!(processes[0].vram_kib, 1536);
+
+        // Counters going backwards (a reused client id) don't underflow
+        let third = vec![sample(100, "game", &fdinfo(1, 0, "1024 KiB"))];
+        assert!(tracker.update_with(third, start + Duration::from_secs(2)).is_empty());
+    }
+}
@@ -1,32 +1,52 @@
 mod types;
 mod reader;
 mod formats;
+mod multi;
+mod fdinfo;
 
 pub use types::*;
 pub use reader::*;
+pub use multi::{GpuDisplayMode, MultiAmdgpuSensor};
+pub use fdinfo::{ClientSample, DrmClient, ProcessGpu, ProcessTracker};
 // pub use formats::*;
 
-use waysensor_rs_core::{Sensor, SensorConfig, SensorError, WaybarOutput, format};
-use std::path::PathBuf;
+use waysensor_rs_core::{diff, power::EcoPolling, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput, format};
+use std::path::{Path, PathBuf};
+
+/// CSS class of a GPU whose clocks are held back by a thermal or power limit.
+pub const THROTTLED_CLASS: &str = "throttled";
 
 #[derive(Debug)]
 pub struct AmdgpuSensor {
     name: String,
     drm_path: PathBuf,
+    /// `gpu_metrics` file given on the command line instead of the device's
+    /// own `gpu_metrics` under `drm_path`
+    metrics_file: Option<PathBuf>,
+    backend: MetricsBackend,
+    apu_mode: ApuMode,
     temp_warning: u16,
     temp_critical: u16,
     format: OutputFormat,
     config: SensorConfig,
+    eco: EcoPolling,
+    /// Engine time per DRM client for the top-process list
+    processes: ProcessTracker,
 }
 
-fn find_amd_gpu_drm_path() -> Result<PathBuf, SensorError> {
+/// Device directories (`/sys/class/drm/cardN/device`) of all AMD GPUs with
+/// sysfs metrics support, sorted by card.
+fn list_amd_gpu_devices() -> Result<Vec<(String, PathBuf)>, SensorError> {
+    list_amd_gpu_devices_in(Path::new("/sys/class/drm"))
+}
+
+fn list_amd_gpu_devices_in(drm_path: &Path) -> Result<Vec<(String, PathBuf)>, SensorError> {
     // Look for AMD GPU in DRM class
-    let drm_path = std::path::Path::new("/sys/class/drm");
     if !drm_path.exists() {
         return Err(SensorError::unavailable("DRM subsystem not available"));
     }
     
-    // Check each card
+    let mut devices = Vec::new();
     for entry in std::fs::read_dir(drm_path)? {
         let entry = entry?;
         if let Some(name) = entry.file_name().to_str() {
@@ -34,25 +54,101 @@ fn find_amd_gpu_drm_path() -> Result<PathBuf, SensorError> {
                 let device_path = entry.path().join("device");
                 let vendor_path = device_path.join("vendor");
                 
-                // Check if it's an AMD GPU (vendor ID 0x1002)
+                // Check if it's an AMD GPU (vendor ID 0x1002) with gpu_busy_percent support
                 if let Ok(vendor) = std::fs::read_to_string(&vendor_path) {
-                    if vendor.trim() == "0x1002" {
-                        // Check if gpu_busy_percent exists (confirms AMD GPU support)
-                        if device_path.join("gpu_busy_percent").exists() {
-                            return Ok(device_path);
-                        }
+                    if vendor.trim() == "0x1002" && device_path.join("gpu_busy_percent").exists() {
+                        devices.push((name.to_string(), device_path));
                     }
                 }
             }
         }
     }
     
-    Err(SensorError::unavailable("No AMD GPU found with sysfs support"))
+    devices.sort();
+    Ok(devices)
+}
+
+fn find_amd_gpu_drm_path() -> Result<PathBuf, SensorError> {
+    list_amd_gpu_devices()?
+        .into_iter()
+        .next()
+        .map(|(_, path)| path)
+        .ok_or_else(|| SensorError::unavailable("No AMD GPU found with sysfs support"))
+}
+
+/// Whether the device directory matches a PCI id given on the command line:
+/// the slot with or without domain (`0000:03:00.0`, `03:00.0`) or the
+/// vendor and device id (`1002:73bf`).
+fn matches_pci_id(device_path: &Path, pci_id: &str) -> bool {
+    let pci_id = pci_id.trim().to_ascii_lowercase();
+    let slot = std::fs::canonicalize(device_path).ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_ascii_lowercase()));
+    if let Some(slot) = slot {
+        if slot == pci_id || slot.split_once(':').is_some_and(|(_, bus)| bus == pci_id) {
+            return true;
+        }
+    }
+    let read = |name: &str| std::fs::read_to_string(device_path.join(name)).ok()
+        .map(|s| s.trim().trim_start_matches("0x").to_ascii_lowercase());
+    matches!((read("vendor"), read("device")), (Some(vendor), Some(device)) if format!("{}:{}", vendor, device) == pci_id)
+}
+
+/// Device directory of the AMD GPU chosen with `--card` and/or `--pci-id`,
+/// the first one when neither is given.
+fn select_amd_gpu_drm_path(card: Option<u32>, pci_id: Option<&str>) -> Result<PathBuf, SensorError> {
+    select_amd_gpu_drm_path_in(Path::new("/sys/class/drm"), card, pci_id)
+}
+
+fn select_amd_gpu_drm_path_in(drm_path: &Path, card: Option<u32>, pci_id: Option<&str>) -> Result<PathBuf, SensorError> {
+    let devices = list_amd_gpu_devices_in(drm_path)?;
+    let found = devices.into_iter().find(|(name, path)| {
+        card.is_none_or(|card| *name == format!("card{}", card))
+            && pci_id.is_none_or(|pci_id| matches_pci_id(path, pci_id))
+    });
+    found.map(|(_, path)| path).ok_or_else(|| {
+        let wanted = match (card, pci_id) {
+            (Some(card), Some(pci_id)) => format!("card{} at {}", card, pci_id),
+            (Some(card), None) => format!("card{}", card),
+            (None, Some(pci_id)) => pci_id.to_string(),
+            (None, None) => "any".to_string(),
+        };
+        SensorError::unavailable(format!("No AMD GPU found with sysfs support ({})", wanted))
+    })
+}
+
+impl Discoverable for AmdgpuSensor {
+    const KIND: &'static str = "AMD GPUs";
+    
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        if !std::path::Path::new("/sys/class/drm").exists() {
+            return Ok(Vec::new());
+        }
+        let read = |path: PathBuf| std::fs::read_to_string(path).ok().map(|s| s.trim().to_string());
+        
+        Ok(list_amd_gpu_devices()?
+            .into_iter()
+            .map(|(card, device_path)| {
+                let device_id = read(device_path.join("device")).unwrap_or_default();
+                let mut item = DiscoveredItem::new(card, format!("AMD GPU {}", device_id).trim_end().to_string());
+                if let Some(slot) = std::fs::canonicalize(&device_path).ok()
+                    .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string())) {
+                    item = item.with_detail("pci slot", slot);
+                }
+                if let Some(busy) = read(device_path.join("gpu_busy_percent")) {
+                    item = item.with_detail("busy", format!("{}%", busy));
+                }
+                if let Some(vram) = read(device_path.join("mem_info_vram_total")).and_then(|v| v.parse::<u64>().ok()) {
+                    item = item.with_detail("vram", format::bytes_to_human(vram));
+                }
+                item
+            })
+            .collect())
+    }
 }
 
 impl AmdgpuSensor {
     pub fn new(
-        _file: Option<String>, // Ignore file parameter, auto-detect instead
+        file: Option<String>,
         temp_warning: u16,
         temp_critical: u16,
         format_str: String,
@@ -72,13 +168,49 @@ impl AmdgpuSensor {
         Ok(Self {
             name: "amd-gpu".to_string(),
             drm_path,
+            metrics_file: file.map(PathBuf::from),
+            backend: MetricsBackend::default(),
+            apu_mode: ApuMode::default(),
             temp_warning,
             temp_critical,
             format,
             config: SensorConfig::default(),
+            eco: EcoPolling::new(EcoPolling::DEFAULT_INTERVAL),
+            processes: ProcessTracker::new(),
         })
     }
     
+    /// Choose where readings come from.
+    #[must_use]
+    pub fn with_backend(mut self, backend: MetricsBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+    
+    /// Choose whether the GPU is treated as an integrated APU.
+    #[must_use]
+    pub fn with_apu_mode(mut self, apu_mode: ApuMode) -> Self {
+        self.apu_mode = apu_mode;
+        self
+    }
+    
+    fn metrics_path(&self) -> PathBuf {
+        self.metrics_file.clone().unwrap_or_else(|| self.drm_path.join("gpu_metrics"))
+    }
+    
+    /// Monitor the GPU with the given card number and/or PCI id instead of
+    /// the first one found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no AMD GPU matches.
+    pub fn select_device(mut self, card: Option<u32>, pci_id: Option<&str>) -> Result<Self, SensorError> {
+        if card.is_some() || pci_id.is_some() {
+            self.drm_path = select_amd_gpu_drm_path(card, pci_id)?;
+        }
+        Ok(self)
+    }
+    
     /// Create a visual bar gauge for a percentage value.
     /// Returns a string with filled and empty blocks to represent the percentage.
     fn create_gauge(percentage: f64, width: usize) -> String {
@@ -128,6 +260,37 @@ impl AmdgpuSensor {
     }
     
     fn read_sysfs_metrics(&self) -> Result<SimplifiedGpuMetrics, SensorError> {
+        let table = match self.backend {
+            MetricsBackend::Sysfs => None,
+            MetricsBackend::Auto => MetricsReader::new().read_file(self.metrics_path()).ok(),
+            MetricsBackend::GpuMetrics => Some(MetricsReader::new().read_file(self.metrics_path())?),
+        };
+        let details = table.as_deref().map(MetricsDetails::from_table);
+        // Only APUs report the CPU/GPU power split (v2.x metrics)
+        let apu_power = table.as_ref().and_then(|table| table.get_apu_power());
+        let apu = match self.apu_mode {
+            // APU firmware reports v2.x tables, dGPU firmware v1.x
+            ApuMode::Auto => table.as_ref().is_some_and(|table| table.get_header().format_revision == 2),
+            ApuMode::On => true,
+            ApuMode::Off => false,
+        };
+        
+        if let (MetricsBackend::GpuMetrics, Some(table)) = (self.backend, &table) {
+            return Ok(SimplifiedGpuMetrics {
+                temperature_edge: table.get_temperature().0,
+                gpu_activity: table.get_activity(),
+                socket_power: table.get_power(),
+                frequency: table.get_frequency(),
+                fan_speed: table.get_fan_speed().0,
+                fan: FanReading::read(&self.drm_path),
+                vram: VramUsage::read(&self.drm_path),
+                power_settings: PowerSettings::read(&self.drm_path),
+                apu,
+                apu_power,
+                details,
+            });
+        }
+        
         // Read temperature from hwmon
         let temp = self.read_temperature()?;
         
@@ -150,6 +313,12 @@ impl AmdgpuSensor {
             socket_power: power_watts,
             frequency,
             fan_speed,
+            fan: FanReading::read(&self.drm_path),
+            vram: VramUsage::read(&self.drm_path),
+            power_settings: PowerSettings::read(&self.drm_path),
+            apu,
+            apu_power,
+            details,
         })
     }
     
@@ -251,28 +420,93 @@ impl AmdgpuSensor {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct SimplifiedGpuMetrics {
     temperature_edge: u16,
     gpu_activity: u16,
     socket_power: u16, // in watts
     frequency: u16,
     fan_speed: u16,
+    fan: FanReading,
+    vram: Option<VramUsage>,
+    power_settings: PowerSettings,
+    /// Integrated GPU sharing its socket power with the CPU
+    apu: bool,
+    apu_power: Option<ApuPowerSplit>,
+    /// Throttling and all temperatures, from the `gpu_metrics` table
+    details: Option<MetricsDetails>,
+}
+
+impl SimplifiedGpuMetrics {
+    /// Power the gauges are scaled to: the board's power cap, or 300W if
+    /// the driver does not report one. An APU's GFX power is scaled to the
+    /// socket power it is part of.
+    fn power_max(&self) -> f64 {
+        match (self.power_settings.cap, self.gfx_power_split()) {
+            (Some(cap), _) => f64::from(cap),
+            (None, Some(split)) => (f64::from(split.socket) / 1000.0).max(1.0),
+            (None, None) => 300.0,
+        }
+    }
+    
+    /// Power split of an APU whose table reports the GFX share.
+    fn gfx_power_split(&self) -> Option<ApuPowerSplit> {
+        self.apu_power.filter(|split| self.apu && split.gfx > 0)
+    }
+    
+    /// Watts drawn by the GPU: the GFX share on an APU that reports it,
+    /// socket power otherwise.
+    fn gpu_power(&self) -> u16 {
+        match self.gfx_power_split() {
+            Some(split) => ((u32::from(split.gfx) + 500) / 1000) as u16,
+            None => self.socket_power,
+        }
+    }
+    
+    /// Tooltip label of [`gpu_power`](Self::gpu_power); an APU's socket
+    /// power includes the CPU.
+    fn power_label(&self) -> &'static str {
+        match (self.apu, self.gfx_power_split()) {
+            (true, Some(_)) => "GFX Power",
+            (true, None) => "Socket Power",
+            (false, _) => "Power",
+        }
+    }
 }
 
 impl Sensor for AmdgpuSensor {
     type Error = SensorError;
 
     fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        // On battery: poll less often and leave a runtime-suspended dGPU asleep
+        if let Some(output) = self.eco.before_read(Some(&self.drm_path), &self.config) {
+            return Ok(output);
+        }
+        
         let metrics = self.read_sysfs_metrics()?;
         
-        match self.format {
+        let output = match self.format {
             OutputFormat::Compact => self.format_compact(&metrics),
             OutputFormat::Detailed => self.format_detailed(&metrics),
             OutputFormat::Minimal => self.format_minimal(&metrics),
             OutputFormat::Power => self.format_power(&metrics),
             OutputFormat::Activity => self.format_activity(&metrics),
+        }?;
+        let mut output = self.mark_throttling(output, &metrics);
+        
+        // Add top processes by GPU if enabled; scanning every fd in /proc is
+        // too costly to do by default
+        let show_processes = self.config.get_custom("show_processes").and_then(|v| v.as_bool()).unwrap_or(false);
+        if self.config.visuals.show_top_processes && show_processes {
+            let pdev = std::fs::canonicalize(&self.drm_path).ok()
+                .and_then(|device| device.file_name().map(|slot| slot.to_string_lossy().into_owned()));
+            let processes = self.processes.update(pdev.as_deref());
+            let section = self.format_top_processes_section(&processes);
+            if let Some(tooltip) = output.tooltip.as_mut() {
+                tooltip.push_str(&section);
+            }
         }
+        Ok(self.eco.after_read(output, &self.config))
     }
 
     fn name(&self) -> &str {
@@ -280,9 +514,14 @@ impl Sensor for AmdgpuSensor {
     }
 
     fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.eco = EcoPolling::from_config(&config);
         self.config = config;
         Ok(())
     }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
 }
 
 impl AmdgpuSensor {
@@ -320,8 +559,9 @@ impl AmdgpuSensor {
                 if let Some(field) = item.as_str() {
                     match field {
                         "temperature" => parts.push(format!("{}°C", metrics.temperature_edge)),
-                        "power" => parts.push(format!("{}W", metrics.socket_power)),
+                        "power" => parts.push(format!("{}W", metrics.gpu_power())),
                         "utilization" => parts.push(format!("{}%", metrics.gpu_activity)),
+                        "memory" => parts.extend(metrics.vram.map(|vram| format::bytes_to_human(vram.used))),
                         _ => {} // Ignore unknown fields
                     }
                 }
@@ -334,16 +574,21 @@ impl AmdgpuSensor {
                 .and_then(|v| v.as_bool()).unwrap_or(true);
             let show_utilization = self.config.custom.get("show_utilization")
                 .and_then(|v| v.as_bool()).unwrap_or(true);
+            let show_memory = self.config.custom.get("show_memory")
+                .and_then(|v| v.as_bool()).unwrap_or(false);
             
             if show_temperature {
                 parts.push(format!("{}°C", metrics.temperature_edge));
             }
             if show_power {
-                parts.push(format!("{}W", metrics.socket_power));
+                parts.push(format!("{}W", metrics.gpu_power()));
             }
             if show_utilization {
                 parts.push(format!("{}%", metrics.gpu_activity));
             }
+            if let Some(vram) = metrics.vram.filter(|_| show_memory) {
+                parts.push(format::bytes_to_human(vram.used));
+            }
         }
         
         // If no parts were configured, default to activity percentage
@@ -357,12 +602,14 @@ impl AmdgpuSensor {
     fn format_detailed(&self, metrics: &SimplifiedGpuMetrics) -> Result<WaybarOutput, SensorError> {
         let mut text_parts = vec![
             format!("{}°C", metrics.temperature_edge),
-            format!("{}W", metrics.socket_power),
+            format!("{}W", metrics.gpu_power()),
             format!("{}%", metrics.gpu_activity),
             format!("{}MHz", metrics.frequency),
         ];
         
-        if metrics.fan_speed > 0 {
+        if metrics.fan.passive {
+            text_parts.push("passive".to_string());
+        } else if metrics.fan_speed > 0 {
             text_parts.push(format!("{}%", metrics.fan_speed));
         }
         
@@ -403,21 +650,29 @@ impl AmdgpuSensor {
     }
     
     fn format_power(&self, metrics: &SimplifiedGpuMetrics) -> Result<WaybarOutput, SensorError> {
-        let power = metrics.socket_power;
+        let power = metrics.gpu_power();
         let icon = &self.config.icons.gpu;
         let text = format::with_icon_and_colors(&format!("{}W", power), icon, &self.config);
         let tooltip = self.build_tooltip(metrics);
         
-        // Use power as percentage (assuming 300W max for percentage calculation)
-        let power_percentage = ((power as f64 / 300.0) * 100.0).min(100.0) as u8;
+        // Power as percentage of the board's limit
+        let power_max = metrics.power_max();
+        let power_percentage = ((power as f64 / power_max) * 100.0).min(100.0) as u8;
+        // An APU's power follows the CPU's load too, so its draw says
+        // nothing about the GPU running hot
+        let (warning, critical) = if metrics.apu {
+            (f64::INFINITY, f64::INFINITY)
+        } else {
+            (power_max * 2.0 / 3.0, power_max * 5.0 / 6.0) // 200W/250W at the default 300W
+        };
         
         Ok(format::themed_output(
             text,
             Some(tooltip),
             Some(power_percentage),
             power as f64,
-            200.0, // 200W warning
-            250.0, // 250W critical
+            warning,
+            critical,
             &self.config.theme,
         ))
     }
@@ -444,7 +699,7 @@ impl AmdgpuSensor {
         
         // Calculate percentages for gauges
         let temp_percentage = ((metrics.temperature_edge as f64 / 100.0) * 100.0).min(100.0);
-        let power_percentage = ((metrics.socket_power as f64 / 300.0) * 100.0).min(100.0); // Assume 300W max
+        let power_percentage = ((metrics.gpu_power() as f64 / metrics.power_max()) * 100.0).min(100.0);
         let activity_percentage = metrics.gpu_activity as f64;
         let freq_percentage = ((metrics.frequency as f64 / 3000.0) * 100.0).min(100.0); // Assume 3GHz max
         
@@ -464,8 +719,8 @@ impl AmdgpuSensor {
         let header = format::key_only("AMD GPU", &self.config);
         let temp_line = format::key_value("Temperature", &format!("{} {}°C {}", 
             temp_gauge, metrics.temperature_edge, temp_indicator), &self.config);
-        let power_line = format::key_value("Power", &format!("{} {}W {}", 
-            power_gauge, metrics.socket_power, power_indicator), &self.config);
+        let power_line = format::key_value(metrics.power_label(), &format!("{} {}W {}", 
+            power_gauge, metrics.gpu_power(), power_indicator), &self.config);
         let activity_line = format::key_value("Activity", &format!("{} {}% {}", 
             activity_gauge, metrics.gpu_activity, activity_indicator), &self.config);
         let freq_line = format::key_value("Frequency", &format!("{} {}MHz {}", 
@@ -474,17 +729,224 @@ impl AmdgpuSensor {
         let mut tooltip = format!("{}\n{}\n{}\n{}\n{}", 
             header, temp_line, power_line, activity_line, freq_line);
         
-        if metrics.fan_speed > 0 {
+        tooltip.push_str(&self.format_power_settings(&metrics.power_settings));
+        
+        if let Some(vram) = &metrics.vram {
+            let vram_percentage = vram.percent();
+            let vram_gauge = Self::create_gauge(vram_percentage, 12);
+            let vram_indicator = Self::get_usage_indicator(vram_percentage, "memory");
+            let vram_line = format::key_value("VRAM", &format!("{} {:.1}% ({} / {}) {}", 
+                vram_gauge, vram_percentage, format::bytes_to_human(vram.used),
+                format::bytes_to_human(vram.total), vram_indicator), &self.config);
+            tooltip.push_str(&format!("\n{}", vram_line));
+        }
+        
+        if metrics.fan.passive {
+            // Zero-RPM mode: the fan is stopped on purpose, not broken
+            let fan_line = format::key_value("Fan Speed", "passive (0 RPM)", &self.config);
+            tooltip.push_str(&format!("\n{}", fan_line));
+        } else if metrics.fan_speed > 0 || metrics.fan.rpm.is_some_and(|rpm| rpm > 0) {
             let fan_percentage = ((metrics.fan_speed as f64 / 100.0) * 100.0).min(100.0);
             let fan_gauge = Self::create_gauge(fan_percentage, 12);
             let fan_indicator = Self::get_usage_indicator(fan_percentage, "fan");
-            let fan_line = format::key_value("Fan Speed", &format!("{} {}% {}", 
-                fan_gauge, metrics.fan_speed, fan_indicator), &self.config);
+            let rpm = metrics.fan.rpm.map(|rpm| format!(" ({} RPM)", rpm)).unwrap_or_default();
+            let fan_line = format::key_value("Fan Speed", &format!("{} {}%{} {}", 
+                fan_gauge, metrics.fan_speed, rpm, fan_indicator), &self.config);
             tooltip.push_str(&format!("\n{}", fan_line));
         }
         
+        if let Some(details) = &metrics.details {
+            tooltip.push_str(&self.format_metrics_details(details));
+        }
+        
+        if let Some(split) = &metrics.apu_power {
+            tooltip.push_str(&self.format_apu_power(split));
+        }
+        
         tooltip
     }
+    
+    /// Tooltip lines with the power limit, performance level and profile.
+    fn format_power_settings(&self, settings: &PowerSettings) -> String {
+        let mut lines = String::new();
+        if let Some(cap) = settings.cap {
+            let value = match settings.cap_max {
+                Some(max) => format!("{}W (max {}W)", cap, max),
+                None => format!("{}W", cap),
+            };
+            lines.push_str(&format!("\n{}", format::key_value("Power Cap", &value, &self.config)));
+        }
+        if let Some(level) = &settings.performance_level {
+            lines.push_str(&format!("\n{}", format::key_value("Performance Level", level, &self.config)));
+        }
+        if let Some(profile) = &settings.profile {
+            lines.push_str(&format!("\n{}", format::key_value("Power Profile", profile, &self.config)));
+        }
+        lines
+    }
+    
+    /// Tooltip section with throttle reasons and every temperature sensor.
+    fn format_metrics_details(&self, details: &MetricsDetails) -> String {
+        let throttle = details.throttle;
+        let mut section = format!(
+            "\n\n{}\n{}",
+            format::key_only(&format!("GPU Metrics {}", details.version), &self.config),
+            format::key_value("Throttling", match throttle.kind() {
+                Some(kind) => kind,
+                None => "none",
+            }, &self.config),
+        );
+        for flag in throttle.active_flags() {
+            section.push_str(&format!("\n{}", format::key_value(
+                &format!("  {}", flag),
+                ThrottleStatus::describe(&flag),
+                &self.config,
+            )));
+        }
+        for (label, temperature) in &details.temperatures {
+            section.push_str(&format!("\n{}", format::key_value(label, &format!("{}°C", temperature), &self.config)));
+        }
+        section
+    }
+    
+    /// Format the processes keeping the GPU busiest; non-graphics engines
+    /// are named, e.g. `mpv (dec)`.
+    fn format_top_processes_section(&self, processes: &[ProcessGpu]) -> String {
+        let max_name_length = self.config.visuals.process_name_max_length as usize;
+        
+        let top: Vec<(String, f64)> = processes.iter()
+            .take(self.config.visuals.top_processes_count as usize)
+            .map(|process| {
+                let mut name = process.name.clone();
+                if name.chars().count() > max_name_length {
+                    name = name.chars().take(max_name_length.saturating_sub(3)).collect();
+                    name.push_str("...");
+                }
+                if process.engine != "gfx" {
+                    name.push_str(&format!(" ({})", process.engine));
+                }
+                (name, process.busy_percent)
+            })
+            .collect();
+        
+        format::format_top_processes(
+            &top,
+            "Top Processes by GPU",
+            self.config.tooltip_label_color.as_deref(),
+            self.config.tooltip_value_color.as_deref(),
+        )
+    }
+    
+    /// Mark a throttling GPU with [`THROTTLED_CLASS`], unless the output
+    /// already shows a warning or worse.
+    fn mark_throttling(&self, mut output: WaybarOutput, metrics: &SimplifiedGpuMetrics) -> WaybarOutput {
+        let throttling = metrics.details.as_ref().is_some_and(|details| details.throttle.is_throttling());
+        if throttling && diff::severity(output.class.as_deref(), &self.config.theme) <= 1 {
+            output.class = Some(THROTTLED_CLASS.to_string());
+        }
+        output
+    }
+    
+    /// Tooltip section showing how the shared socket power budget is split.
+    fn format_apu_power(&self, split: &ApuPowerSplit) -> String {
+        const WIDTH: usize = 12;
+        let socket = split.socket as f64;
+        let parts = [
+            ("CPU", split.cpu, '█'),
+            ("GFX", split.gfx, '▓'),
+            ("SoC", split.soc, '▒'),
+        ];
+        
+        // Stacked gauge: one segment per consumer, the remainder shaded as other
+        let mut gauge = String::new();
+        let mut used = 0;
+        for (_, milliwatts, fill) in &parts {
+            let cells = ((*milliwatts as f64 / socket) * WIDTH as f64).round() as usize;
+            let cells = cells.min(WIDTH - used);
+            gauge.extend(std::iter::repeat_n(*fill, cells));
+            used += cells;
+        }
+        gauge.extend(std::iter::repeat_n('░', WIDTH - used));
+        
+        let mut section = format!(
+            "\n\n{}\n{}",
+            format::key_only("APU Power", &self.config),
+            format::key_value("Socket", &format!("{} {:.1}W", gauge, socket / 1000.0), &self.config),
+        );
+        for (label, milliwatts, fill) in parts {
+            let share = milliwatts as f64 / socket * 100.0;
+            section.push_str(&format!("\n{}", format::key_value(
+                &format!("{} {}", fill, label),
+                &format!("{:.1}W ({:.0}%)", milliwatts as f64 / 1000.0, share),
+                &self.config,
+            )));
+        }
+        if split.other() > 0 {
+            section.push_str(&format!("\n{}", format::key_value(
+                "░ Other",
+                &format!("{:.1}W", split.other() as f64 / 1000.0),
+                &self.config,
+            )));
+        }
+        section
+    }
+}
+
+/// Whether the GPU is an integrated APU sharing the socket power budget
+/// with the CPU. APUs show GFX power where the `gpu_metrics` table splits it
+/// out, and get no power thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ApuMode {
+    /// Detect from the `gpu_metrics` table version
+    #[default]
+    Auto,
+    On,
+    Off,
+}
+
+impl std::str::FromStr for ApuMode {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "on" | "apu" => Ok(Self::On),
+            "off" | "dgpu" => Ok(Self::Off),
+            _ => Err(SensorError::config_with_value(
+                "Invalid APU mode. Valid options: auto, on, off",
+                s,
+            )),
+        }
+    }
+}
+
+/// Where readings come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MetricsBackend {
+    /// Individual sysfs and hwmon files, plus the `gpu_metrics` table for
+    /// throttling and extra temperatures when the kernel provides it
+    #[default]
+    Auto,
+    /// Individual sysfs and hwmon files only
+    Sysfs,
+    /// Everything from the `gpu_metrics` table (one read per update)
+    GpuMetrics,
+}
+
+impl std::str::FromStr for MetricsBackend {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "sysfs" => Ok(Self::Sysfs),
+            "gpu-metrics" | "gpu_metrics" | "metrics" => Ok(Self::GpuMetrics),
+            _ => Err(SensorError::config_with_value(
+                "Invalid backend. Valid options: auto, sysfs, gpu-metrics",
+                s,
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -496,4 +958,151 @@ pub enum OutputFormat {
     Activity,
 }
 
-// ThrottleStatus and find_gpu_metrics_file are imported from types.rs
\ No newline at end of file
+// ThrottleStatus and find_gpu_metrics_file are imported from types.rs
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A `/sys/class/drm` tree whose `cardN/device` links point at PCI
+    /// device directories, like sysfs does.
+    fn drm_tree(root: &Path, cards: &[(&str, &str, &str)]) -> PathBuf {
+        let drm = root.join("class/drm");
+        for (card, slot, device) in cards {
+            let pci = root.join("devices").join(slot);
+            fs::create_dir_all(&pci).unwrap();
+            fs::write(pci.join("vendor"), "0x1002\n").unwrap();
+            fs::write(pci.join("device"), format!("0x{}\n", device)).unwrap();
+            fs::write(pci.join("gpu_busy_percent"), "0\n").unwrap();
+            fs::create_dir_all(drm.join(card)).unwrap();
+            std::os::unix::fs::symlink(&pci, drm.join(card).join("device")).unwrap();
+        }
+        // Connectors and other vendors are skipped
+        fs::create_dir_all(drm.join("card1-DP-1")).unwrap();
+        drm
+    }
+
+    #[test]
+    fn test_matches_pci_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let drm = drm_tree(dir.path(), &[("card1", "0000:03:00.0", "73BF")]);
+        let device = drm.join("card1/device");
+
+        assert!(matches_pci_id(&device, "0000:03:00.0"));
+        assert!(matches_pci_id(&device, "03:00.0"));
+        assert!(matches_pci_id(&device, " 0000:03:00.0\n"));
+        assert!(matches_pci_id(&device, "1002:73bf"));
+        assert!(matches_pci_id(&device, "1002:73BF"));
+        assert!(!matches_pci_id(&device, "0000:04:00.0"));
+        assert!(!matches_pci_id(&device, "00.0"));
+        assert!(!matches_pci_id(&device, "10de:2684"));
+    }
+
+    #[test]
+    fn test_select_amd_gpu_drm_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let drm = drm_tree(dir.path(), &[("card2", "0000:0c:00.0", "164e"), ("card1", "0000:03:00.0", "73bf")]);
+        let other = dir.path().join("devices/0000:01:00.0");
+        fs::create_dir_all(&other).unwrap();
+        fs::write(other.join("vendor"), "0x10de\n").unwrap();
+        fs::write(other.join("gpu_busy_percent"), "0\n").unwrap();
+        fs::create_dir_all(drm.join("card0")).unwrap();
+        std::os::unix::fs::symlink(&other, drm.join("card0/device")).unwrap();
+
+        let devices = list_amd_gpu_devices_in(&drm).unwrap();
+        let cards: Vec<&str> = devices.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(cards, vec!["card1", "card2"]);
+
+        assert_eq!(select_amd_gpu_drm_path_in(&drm, None, None).unwrap(), drm.join("card1/device"));
+        assert_eq!(select_amd_gpu_drm_path_in(&drm, Some(2), None).unwrap(), drm.join("card2/device"));
+        assert_eq!(select_amd_gpu_drm_path_in(&drm, None, Some("0c:00.0")).unwrap(), drm.join("card2/device"));
+        assert_eq!(select_amd_gpu_drm_path_in(&drm, Some(1), Some("1002:73bf")).unwrap(), drm.join("card1/device"));
+
+        // Both must match, and the error names what was asked for
+        let err = select_amd_gpu_drm_path_in(&drm, Some(1), Some("1002:164e")).unwrap_err();
+        assert!(err.to_string().contains("card1 at 1002:164e"));
+        assert!(select_amd_gpu_drm_path_in(&drm, Some(0), None).is_err());
+        assert!(select_amd_gpu_drm_path_in(&dir.path().join("missing"), None, None).is_err());
+    }
+
+    fn apu_metrics(apu: bool, apu_power: Option<ApuPowerSplit>) -> SimplifiedGpuMetrics {
+        SimplifiedGpuMetrics {
+            temperature_edge: 55,
+            gpu_activity: 40,
+            socket_power: 28,
+            frequency: 2200,
+            fan_speed: 0,
+            fan: FanReading::default(),
+            vram: None,
+            power_settings: PowerSettings::default(),
+            apu,
+            apu_power,
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_apu_power() {
+        assert_eq!("APU".parse::<ApuMode>().unwrap(), ApuMode::On);
+        assert_eq!("dgpu".parse::<ApuMode>().unwrap(), ApuMode::Off);
+        assert_eq!("auto".parse::<ApuMode>().unwrap(), ApuMode::Auto);
+        assert!("maybe".parse::<ApuMode>().is_err());
+
+        // An APU shows its GFX share, scaled to the socket power
+        let split = ApuPowerSplit::from_raw(28_000, 9_000, 12_600, 4_000);
+        let apu = apu_metrics(true, split);
+        assert_eq!(apu.gpu_power(), 13);
+        assert_eq!(apu.power_max(), 28.0);
+        assert_eq!(apu.power_label(), "GFX Power");
+
+        // Without a GFX share the socket power is shown, and a board cap wins
+        let apu = apu_metrics(true, ApuPowerSplit::from_raw(28_000, 9_000, 0, 4_000));
+        assert_eq!(apu.gpu_power(), 28);
+        assert_eq!(apu.power_label(), "Socket Power");
+        let capped = SimplifiedGpuMetrics {
+            power_settings: PowerSettings { cap: Some(54), ..PowerSettings::default() },
+            ..apu_metrics(true, split)
+        };
+        assert_eq!(capped.power_max(), 54.0);
+
+        // A dGPU ignores any split
+        let dgpu = apu_metrics(false, split);
+        assert_eq!(dgpu.gpu_power(), 28);
+        assert_eq!(dgpu.power_max(), 300.0);
+        assert_eq!(dgpu.power_label(), "Power");
+    }
+
+    #[test]
+    fn test_format_apu_power() {
+        let dir = tempfile::tempdir().unwrap();
+        let sensor = AmdgpuSensor {
+            name: "amd-gpu".to_string(),
+            drm_path: dir.path().to_path_buf(),
+            metrics_file: None,
+            backend: MetricsBackend::default(),
+            apu_mode: ApuMode::On,
+            temp_warning: 80,
+            temp_critical: 90,
+            format: OutputFormat::Compact,
+            config: SensorConfig::default(),
+            eco: EcoPolling::new(EcoPolling::DEFAULT_INTERVAL),
+            processes: ProcessTracker::new(),
+        };
+
+        // 6 + 3 + 2 of 12 cells, one left for the rest
+        let split = ApuPowerSplit::from_raw(24_000, 12_000, 6_000, 4_000).unwrap();
+        let section = sensor.format_apu_power(&split);
+        assert!(section.contains("██████▓▓▓▒▒░ 24.0W"), "{}", section);
+        assert!(section.contains("12.0W (50%)"), "{}", section);
+        assert!(section.contains("6.0W (25%)"), "{}", section);
+        assert!(section.contains("4.0W (17%)"), "{}", section);
+        assert!(section.contains("2.0W"), "{}", section);
+
+        // Nothing left over, no "Other" row
+        let split = ApuPowerSplit::from_raw(10_000, 5_000, 5_000, 0).unwrap();
+        let section = sensor.format_apu_power(&split);
+        assert!(section.contains("██████▓▓▓▓▓▓ 10.0W"), "{}", section);
+        assert!(!section.contains("Other"), "{}", section);
+    }
+}
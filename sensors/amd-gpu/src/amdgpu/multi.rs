@@ -0,0 +1,273 @@
+//! All AMD GPUs at once, for dual-GPU laptops and multi-GPU workstations.
+
+use super::{list_amd_gpu_devices, AmdgpuSensor, FanReading, PowerSettings, SimplifiedGpuMetrics, VramUsage};
+use waysensor_rs_core::{power::{self, EcoPolling}, format, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+/// What the bar shows when monitoring several GPUs.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum GpuDisplayMode {
+    /// The GPU with the highest temperature
+    #[default]
+    Hottest,
+    /// Each GPU in turn
+    Cycle { current: usize },
+    /// Power and VRAM summed, the highest temperature and the mean load
+    Combined,
+}
+
+impl std::str::FromStr for GpuDisplayMode {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "hottest" => Ok(Self::Hottest),
+            "cycle" => Ok(Self::Cycle { current: 0 }),
+            "combined" | "sum" => Ok(Self::Combined),
+            _ => Err(SensorError::config_with_value(
+                "Invalid display mode. Valid options: hottest, cycle, combined",
+                s,
+            )),
+        }
+    }
+}
+
+/// Metrics of all GPUs folded into one reading.
+fn combine(metrics: &[SimplifiedGpuMetrics]) -> SimplifiedGpuMetrics {
+    let vram: Vec<VramUsage> = metrics.iter().filter_map(|m| m.vram).collect();
+    let count = metrics.len().max(1) as u32;
+    SimplifiedGpuMetrics {
+        temperature_edge: metrics.iter().map(|m| m.temperature_edge).max().unwrap_or(0),
+        gpu_activity: (metrics.iter().map(|m| m.gpu_activity as u32).sum::<u32>() / count) as u16,
+        socket_power: metrics.iter().map(|m| m.socket_power).sum(),
+        frequency: metrics.iter().map(|m| m.frequency).max().unwrap_or(0),
+        fan_speed: metrics.iter().map(|m| m.fan_speed).max().unwrap_or(0),
+        fan: FanReading {
+            rpm: metrics.iter().filter_map(|m| m.fan.rpm).max(),
+            passive: !metrics.is_empty() && metrics.iter().all(|m| m.fan.passive),
+        },
+        vram: (!vram.is_empty()).then(|| VramUsage {
+            used: vram.iter().map(|v| v.used).sum(),
+            total: vram.iter().map(|v| v.total).sum(),
+        }),
+        power_settings: PowerSettings {
+            cap: metrics.iter().map(|m| m.power_settings.cap).sum(),
+            cap_max: metrics.iter().map(|m| m.power_settings.cap_max).sum(),
+            ..PowerSettings::default()
+        },
+        apu: false,
+        apu_power: None,
+        details: None,
+    }
+}
+
+/// Temperature, power and load of every AMD GPU.
+#[derive(Debug)]
+pub struct MultiAmdgpuSensor {
+    name: String,
+    /// One sensor per card, e.g. `("card1", sensor)`
+    gpus: Vec<(String, AmdgpuSensor)>,
+    temp_warning: u16,
+    temp_critical: u16,
+    display_mode: GpuDisplayMode,
+    config: SensorConfig,
+    eco: EcoPolling,
+}
+
+impl MultiAmdgpuSensor {
+    pub fn new(temp_warning: u16, temp_critical: u16, display_mode: GpuDisplayMode) -> Result<Self, SensorError> {
+        let gpus = list_amd_gpu_devices()?
+            .into_iter()
+            .map(|(card, drm_path)| {
+                let mut sensor = AmdgpuSensor::new(None, temp_warning, temp_critical, "compact".to_string(), false)?;
+                sensor.drm_path = drm_path;
+                Ok((card, sensor))
+            })
+            .collect::<Result<Vec<_>, SensorError>>()?;
+        if gpus.is_empty() {
+            return Err(SensorError::unavailable("No AMD GPU found with sysfs support"));
+        }
+
+        Ok(Self {
+            name: "amd-gpu-multi".to_string(),
+            gpus,
+            temp_warning,
+            temp_critical,
+            display_mode,
+            config: SensorConfig::default(),
+            eco: EcoPolling::new(EcoPolling::DEFAULT_INTERVAL),
+        })
+    }
+
+    /// Metrics of every GPU that could be read, `None` for GPUs left asleep.
+    fn read_all(&mut self) -> Result<Vec<(String, Option<SimplifiedGpuMetrics>)>, SensorError> {
+        // On battery a runtime-suspended dGPU stays asleep
+        let skip_suspended = self.eco.is_active();
+        let mut readings = Vec::new();
+        for (card, sensor) in &self.gpus {
+            if skip_suspended && power::is_runtime_suspended(&sensor.drm_path) {
+                readings.push((card.clone(), None));
+                continue;
+            }
+            match sensor.read_sysfs_metrics() {
+                Ok(metrics) => readings.push((card.clone(), Some(metrics))),
+                Err(e) => eprintln!("Warning: Failed to read {}: {}", card, e),
+            }
+        }
+        if readings.is_empty() {
+            return Err(SensorError::temporarily_unavailable("No AMD GPU could be read"));
+        }
+        Ok(readings)
+    }
+
+    /// One line per GPU.
+    fn build_tooltip(&self, readings: &[(String, Option<SimplifiedGpuMetrics>)]) -> String {
+        let mut lines = vec![format::key_only(&format!("AMD GPUs: {}", readings.len()), &self.config)];
+        for (card, metrics) in readings {
+            let value = match metrics {
+                Some(m) => {
+                    let mut value = format!("{}°C {}W {}% {}MHz", m.temperature_edge, m.gpu_power(), m.gpu_activity, m.frequency);
+                    if let Some(vram) = m.vram {
+                        value.push_str(&format!(" VRAM {} / {}", format::bytes_to_human(vram.used), format::bytes_to_human(vram.total)));
+                    }
+                    if let Some(kind) = m.details.as_ref().and_then(|details| details.throttle.kind()) {
+                        value.push_str(&format!(" throttled ({})", kind));
+                    }
+                    value
+                }
+                None => "suspended".to_string(),
+            };
+            lines.push(format::key_value(card, &value, &self.config));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Sensor for MultiAmdgpuSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        if let Some(output) = self.eco.before_read(None, &self.config) {
+            return Ok(output);
+        }
+
+        let readings = self.read_all()?;
+        let awake: Vec<(&String, &SimplifiedGpuMetrics)> = readings.iter()
+            .filter_map(|(card, metrics)| metrics.as_ref().map(|m| (card, m)))
+            .collect();
+
+        let (shown, prefix) = match &mut self.display_mode {
+            GpuDisplayMode::Combined => {
+                let all: Vec<SimplifiedGpuMetrics> = readings.iter().filter_map(|(_, m)| m.clone()).collect();
+                (Some(combine(&all)), None)
+            }
+            GpuDisplayMode::Hottest => match awake.iter().max_by_key(|(_, m)| m.temperature_edge) {
+                Some((card, metrics)) => (Some((*metrics).clone()), Some((*card).clone())),
+                None => (None, None),
+            },
+            GpuDisplayMode::Cycle { current } => {
+                let (card, metrics) = &readings[*current % readings.len()];
+                *current = (*current + 1) % readings.len();
+                (metrics.clone(), Some(card.clone()))
+            }
+        };
+
+        // Formatting is shared with the single-GPU sensor
+        let display = &self.gpus[0].1;
+        let text = match &shown {
+            Some(metrics) => display.build_display_text(metrics),
+            None => "off".to_string(),
+        };
+        let text = match prefix {
+            Some(card) => format!("{}: {}", card, text),
+            None => text,
+        };
+        let text = format::with_icon_and_colors(&text, &self.config.icons.gpu, &self.config);
+        let temperature = shown.as_ref().map_or(0, |m| m.temperature_edge);
+
+        let output = format::themed_output(
+            text,
+            Some(self.build_tooltip(&readings)),
+            Some(temperature.min(100) as u8),
+            temperature as f64,
+            self.temp_warning as f64,
+            self.temp_critical as f64,
+            &self.config.theme,
+        );
+        let output = match &shown {
+            Some(metrics) => display.mark_throttling(output, metrics),
+            None => output,
+        };
+        Ok(self.eco.after_read(output, &self.config))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        for (_, sensor) in &mut self.gpus {
+            sensor.configure(config.clone())?;
+        }
+        self.eco = EcoPolling::from_config(&config);
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(temperature: u16, activity: u16, power: u16, rpm: Option<u32>, vram: Option<(u64, u64)>, cap: Option<u16>) -> SimplifiedGpuMetrics {
+        SimplifiedGpuMetrics {
+            temperature_edge: temperature,
+            gpu_activity: activity,
+            socket_power: power,
+            frequency: temperature * 30,
+            fan_speed: activity / 2,
+            fan: FanReading { rpm, passive: rpm == Some(0) },
+            vram: vram.map(|(used, total)| VramUsage { used, total }),
+            power_settings: PowerSettings { cap, cap_max: cap.map(|cap| cap + 20), ..PowerSettings::default() },
+            apu: false,
+            apu_power: None,
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_combine() {
+        let combined = combine(&[
+            metrics(60, 90, 200, Some(1500), Some((4, 16)), Some(250)),
+            metrics(45, 10, 30, Some(0), None, Some(100)),
+        ]);
+        // Hottest temperature and highest clock, mean load, summed power
+        assert_eq!(combined.temperature_edge, 60);
+        assert_eq!(combined.frequency, 1800);
+        assert_eq!(combined.gpu_activity, 50);
+        assert_eq!(combined.fan_speed, 45);
+        assert_eq!(combined.socket_power, 230);
+        assert_eq!(combined.fan, FanReading { rpm: Some(1500), passive: false });
+        assert_eq!(combined.vram, Some(VramUsage { used: 4, total: 16 }));
+        assert_eq!(combined.power_settings.cap, Some(350));
+        assert_eq!(combined.power_settings.cap_max, Some(390));
+
+        // A cap is only summed when every card reports one; fans are only
+        // passive when all of them are
+        let combined = combine(&[
+            metrics(50, 0, 10, Some(0), None, None),
+            metrics(50, 0, 10, Some(0), None, Some(100)),
+        ]);
+        assert_eq!(combined.power_settings.cap, None);
+        assert!(combined.fan.passive);
+        assert_eq!(combined.vram, None);
+
+        let empty = combine(&[]);
+        assert_eq!(empty.temperature_edge, 0);
+        assert_eq!(empty.gpu_activity, 0);
+        assert!(!empty.fan.passive);
+    }
+}
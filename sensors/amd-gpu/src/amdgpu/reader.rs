@@ -110,10 +110,11 @@ impl MetricsReader {
 
     fn parse_v2_metrics(&self, header: Header, data: &[u8]) -> Result<Box<dyn GpuMetrics>, SensorError> {
         match header.content_revision {
-            0 => {
-                if data.len() < 96 { // Minimum size for v2.0
+            // v2.1+ only append fields, so the v2.0 layout covers everything read here
+            0..=4 => {
+                if data.len() < 114 { // Minimum size for v2.0
                     return Err(SensorError::Parse {
-                        message: "Insufficient data for v2.0".to_string(),
+                        message: format!("Insufficient data for {}", header.version()),
                         source: None,
                     });
                 }
@@ -199,4 +200,56 @@ fn read_u64_le(data: &[u8], offset: usize) -> u64 {
         data[offset], data[offset + 1], data[offset + 2], data[offset + 3],
         data[offset + 4], data[offset + 5], data[offset + 6], data[offset + 7],
     ])
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A `gpu_metrics` v2.x file with the given socket, CPU, GFX and SoC
+    /// power in milliwatts and `data_size` bytes after the header.
+    fn v2_file(content_revision: u8, data_size: usize, power: [u16; 4]) -> tempfile::NamedTempFile {
+        let mut bytes = vec![0u8; 4 + data_size];
+        bytes[0..2].copy_from_slice(&((4 + data_size) as u16).to_le_bytes());
+        bytes[2] = 2;
+        bytes[3] = content_revision;
+        // average_socket_power, average_cpu_power, average_soc_power, average_gfx_power
+        let [socket, cpu, gfx, soc] = power;
+        for (offset, value) in [(40, socket), (42, cpu), (44, soc), (46, gfx)] {
+            bytes[4 + offset..4 + offset + 2].copy_from_slice(&value.to_le_bytes());
+        }
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), bytes).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_v2_apu_power_every_revision() {
+        for revision in 0..=4 {
+            let file = v2_file(revision, 114, [15_400, 6_200, 5_100, 2_300]);
+            let table = MetricsReader::new().read_file(file.path()).unwrap();
+            assert_eq!(table.get_header().version(), format!("v2.{}", revision));
+            let split = table.get_apu_power().unwrap();
+            assert_eq!(split, ApuPowerSplit { socket: 15_400, cpu: 6_200, gfx: 5_100, soc: 2_300 });
+            assert_eq!(split.other(), 1_800);
+        }
+
+        // Fields the firmware leaves unsupported count as zero
+        let file = v2_file(1, 114, [9_000, u16::MAX, 4_000, u16::MAX]);
+        let split = MetricsReader::new().read_file(file.path()).unwrap().get_apu_power().unwrap();
+        assert_eq!((split.cpu, split.gfx, split.soc, split.other()), (0, 4_000, 0, 5_000));
+
+        // No socket power, no split
+        for socket in [0, u16::MAX] {
+            let file = v2_file(2, 114, [socket, 1_000, 1_000, 1_000]);
+            assert_eq!(MetricsReader::new().read_file(file.path()).unwrap().get_apu_power(), None);
+        }
+    }
+
+    #[test]
+    fn test_v2_table_size() {
+        assert!(MetricsReader::new().read_file(v2_file(0, 113, [1; 4]).path()).is_err());
+        assert!(MetricsReader::new().read_file(v2_file(4, 300, [1; 4]).path()).is_ok());
+        assert!(MetricsReader::new().read_file(v2_file(5, 114, [1; 4]).path()).is_err());
+    }
+}
@@ -17,13 +17,193 @@ impl Header {
 
 /// Trait for all GPU metrics versions
 pub trait GpuMetrics: fmt::Debug {
+    /// Primary temperature in °C and its sensor label.
     fn get_temperature(&self) -> (u16, String);
+    /// Socket power in watts.
     fn get_power(&self) -> u16;
     fn get_activity(&self) -> u16;
     fn get_frequency(&self) -> u16;
     fn get_throttle_status(&self) -> u64;
     fn get_fan_speed(&self) -> (u16, bool);
     fn get_header(&self) -> Header;
+
+    /// Every temperature sensor the firmware reports, in °C. Sensors the
+    /// firmware does not fill in are left out.
+    fn get_all_temperatures(&self) -> Vec<(String, u16)> {
+        let (temperature, label) = self.get_temperature();
+        vec![(label, temperature)]
+    }
+
+    /// Split of socket power between CPU, GFX and SoC, only reported by APUs.
+    fn get_apu_power(&self) -> Option<ApuPowerSplit> {
+        None
+    }
+}
+
+/// Shared socket power budget of an APU, in milliwatts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApuPowerSplit {
+    pub socket: u16,
+    pub cpu: u16,
+    pub gfx: u16,
+    pub soc: u16,
+}
+
+/// Whether a raw temperature field holds a reading.
+fn reported(value: u16) -> bool {
+    value != 0 && value != u16::MAX
+}
+
+impl ApuPowerSplit {
+    /// Value the SMU reports for fields the firmware does not fill in.
+    const UNSUPPORTED: u16 = u16::MAX;
+
+    /// Build a split from raw metrics fields, treating unsupported fields as
+    /// zero. Returns `None` when the socket power itself is not reported.
+    pub fn from_raw(socket: u16, cpu: u16, gfx: u16, soc: u16) -> Option<Self> {
+        let known = |v: u16| if v == Self::UNSUPPORTED { 0 } else { v };
+        if socket == 0 || socket == Self::UNSUPPORTED {
+            return None;
+        }
+        Some(Self {
+            socket,
+            cpu: known(cpu),
+            gfx: known(gfx),
+            soc: known(soc),
+        })
+    }
+
+    /// Power not attributed to CPU, GFX or SoC (memory, I/O, rounding).
+    pub fn other(&self) -> u16 {
+        self.socket.saturating_sub(self.cpu.saturating_add(self.gfx).saturating_add(self.soc))
+    }
+}
+
+/// Dedicated video memory in use, in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VramUsage {
+    pub used: u64,
+    pub total: u64,
+}
+
+impl VramUsage {
+    /// Read `mem_info_vram_used` and `mem_info_vram_total` of the device
+    /// directory. `None` if the driver does not expose them or reports no
+    /// VRAM (APUs without a carve-out).
+    pub fn read(device: &std::path::Path) -> Option<Self> {
+        let read = |name: &str| std::fs::read_to_string(device.join(name)).ok()?.trim().parse::<u64>().ok();
+        let total = read("mem_info_vram_total").filter(|&total| total > 0)?;
+        Some(Self { used: read("mem_info_vram_used")?, total })
+    }
+
+    pub fn percent(&self) -> f64 {
+        (self.used as f64 / self.total as f64 * 100.0).min(100.0)
+    }
+}
+
+/// The `amdgpu` hwmon directory of a device.
+pub fn find_hwmon(device: &std::path::Path) -> Option<std::path::PathBuf> {
+    std::fs::read_dir(device.join("hwmon")).ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| std::fs::read_to_string(path.join("name")).is_ok_and(|name| name.trim() == "amdgpu"))
+}
+
+/// Fan tachometer of a device.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FanReading {
+    /// Speed in RPM (`fan1_input`), `None` without a tachometer
+    pub rpm: Option<u32>,
+    /// The fan stands still under automatic control (zero-RPM mode), as it
+    /// does on most cards while idle and cool
+    pub passive: bool,
+}
+
+impl FanReading {
+    pub fn read(device: &std::path::Path) -> Self {
+        let Some(hwmon) = find_hwmon(device) else {
+            return Self::default();
+        };
+        let read = |name: &str| std::fs::read_to_string(hwmon.join(name)).ok();
+        let rpm = read("fan1_input").and_then(|rpm| rpm.trim().parse::<u32>().ok());
+        // pwm1_enable 2 is automatic control; RDNA3 and later also have an
+        // explicit switch in the overdrive interface
+        let automatic = read("pwm1_enable").is_some_and(|mode| mode.trim() == "2");
+        let zero_rpm_enabled = std::fs::read_to_string(device.join("gpu_od/fan_ctrl/fan_zero_rpm_enable")).ok()
+            .is_some_and(|table| Self::parse_zero_rpm_enable(&table));
+        Self {
+            rpm,
+            passive: rpm == Some(0) && (automatic || zero_rpm_enabled),
+        }
+    }
+
+    /// Whether `fan_zero_rpm_enable` (`FAN_ZERO_RPM_ENABLE:` then the value)
+    /// has zero-RPM mode switched on.
+    pub fn parse_zero_rpm_enable(table: &str) -> bool {
+        table.lines().nth(1).is_some_and(|value| value.trim() == "1")
+    }
+}
+
+/// Power limit and performance tuning of a device.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PowerSettings {
+    /// Board power limit in watts (`power1_cap`)
+    pub cap: Option<u16>,
+    /// Highest limit the board allows in watts (`power1_cap_max`)
+    pub cap_max: Option<u16>,
+    /// `power_dpm_force_performance_level`, e.g. `auto` or `manual`
+    pub performance_level: Option<String>,
+    /// Active entry of `pp_power_profile_mode`, e.g. `3D_FULL_SCREEN`
+    pub profile: Option<String>,
+}
+
+impl PowerSettings {
+    /// Read the settings of the device directory; files the driver does not
+    /// expose are left `None`.
+    pub fn read(device: &std::path::Path) -> Self {
+        let hwmon = find_hwmon(device);
+        let watts = |name: &str| {
+            let microwatts = std::fs::read_to_string(hwmon.as_ref()?.join(name)).ok()?.trim().parse::<u64>().ok()?;
+            Some((microwatts / 1_000_000) as u16).filter(|&watts| watts > 0)
+        };
+        Self {
+            cap: watts("power1_cap"),
+            cap_max: watts("power1_cap_max"),
+            performance_level: std::fs::read_to_string(device.join("power_dpm_force_performance_level")).ok()
+                .map(|level| level.trim().to_string())
+                .filter(|level| !level.is_empty()),
+            profile: std::fs::read_to_string(device.join("pp_power_profile_mode")).ok()
+                .and_then(|table| Self::parse_profile(&table)),
+        }
+    }
+
+    /// Name of the active profile in `pp_power_profile_mode`, the row marked
+    /// with `*` (`  1 3D_FULL_SCREEN*:` or `  1 3D_FULL_SCREEN *:`).
+    pub fn parse_profile(table: &str) -> Option<String> {
+        let (before, _) = table.lines().find_map(|line| line.split_once('*'))?;
+        before.split_whitespace().last()
+            .filter(|name| name.parse::<u32>().is_err())
+            .map(str::to_string)
+    }
+}
+
+/// Readings only the `gpu_metrics` table provides.
+#[derive(Debug, Clone)]
+pub struct MetricsDetails {
+    /// Table version, e.g. `v2.1`
+    pub version: String,
+    pub temperatures: Vec<(String, u16)>,
+    pub throttle: ThrottleStatus,
+}
+
+impl MetricsDetails {
+    pub fn from_table(table: &dyn GpuMetrics) -> Self {
+        Self {
+            version: table.get_header().version(),
+            temperatures: table.get_all_temperatures(),
+            throttle: ThrottleStatus(table.get_throttle_status()),
+        }
+    }
 }
 
 /// Throttle status with bit flags and helper methods
@@ -51,6 +231,11 @@ impl ThrottleStatus {
         self.0 != 0
     }
 
+    /// PROCHOT and the THM_* temperature limits
+    const THERMAL: u64 = (1 << 0) | (1 << 1) | (1 << 32) | (1 << 33) | (1 << 34);
+    /// PPT0-3, SPL, FPPT, SPPT and SPPT_APU
+    const POWER: u64 = 0xFF << 16;
+
     pub fn active_flags(&self) -> Vec<String> {
         let mut flags = Vec::new();
         for (name, flag) in Self::THROTTLE_FLAGS {
@@ -60,6 +245,43 @@ impl ThrottleStatus {
         }
         flags
     }
+
+    pub fn is_thermal_throttling(&self) -> bool {
+        self.0 & Self::THERMAL != 0
+    }
+
+    pub fn is_power_throttling(&self) -> bool {
+        self.0 & Self::POWER != 0
+    }
+
+    /// What limits the clocks: `thermal`, `power`, `thermal and power`, or
+    /// `other` for bits without a known meaning.
+    pub fn kind(&self) -> Option<&'static str> {
+        match (self.is_thermal_throttling(), self.is_power_throttling()) {
+            _ if !self.is_throttling() => None,
+            (true, true) => Some("thermal and power"),
+            (true, false) => Some("thermal"),
+            (false, true) => Some("power"),
+            (false, false) => Some("other"),
+        }
+    }
+
+    /// Plain description of a flag from [`active_flags`](Self::active_flags).
+    pub fn describe(flag: &str) -> &'static str {
+        match flag {
+            "PROCHOT_CPU" => "CPU asserted PROCHOT (overheating)",
+            "PROCHOT_GFX" => "GPU asserted PROCHOT (overheating)",
+            "PPT0" | "PPT1" | "PPT2" | "PPT3" => "package power tracking limit",
+            "SPL" => "sustained power limit",
+            "FPPT" => "fast power limit",
+            "SPPT" => "slow power limit",
+            "SPPT_APU" => "APU slow power limit",
+            "THM_CORE" => "CPU core temperature limit",
+            "THM_GFX" => "GPU temperature limit",
+            "THM_SOC" => "SoC temperature limit",
+            _ => "unknown reason",
+        }
+    }
 }
 
 // GPU Metrics v1.0
@@ -119,6 +341,21 @@ impl GpuMetrics for GpuMetricsV1_0 {
         self.throttle_status
     }
 
+    fn get_all_temperatures(&self) -> Vec<(String, u16)> {
+        [
+            ("Edge", self.temperature_edge),
+            ("Hotspot", self.temperature_hotspot),
+            ("Memory", self.temperature_mem),
+            ("VR GFX", self.temperature_vrgfx),
+            ("VR SoC", self.temperature_vrsoc),
+            ("VR Memory", self.temperature_vrmem),
+        ]
+        .into_iter()
+        .filter(|&(_, value)| reported(value))
+        .map(|(label, value)| (label.to_string(), value))
+        .collect()
+    }
+
     fn get_fan_speed(&self) -> (u16, bool) {
         // Convert PWM to percentage if needed
         let speed = if self.current_fan_speed > 100 {
@@ -173,11 +410,13 @@ pub struct GpuMetricsV2_0 {
 
 impl GpuMetrics for GpuMetricsV2_0 {
     fn get_temperature(&self) -> (u16, String) {
-        (self.temperature_gfx, "GFX".to_string())
+        // APU firmware reports centidegrees
+        (self.temperature_gfx / 100, "GFX".to_string())
     }
 
     fn get_power(&self) -> u16 {
-        self.average_socket_power
+        // APU firmware reports milliwatts
+        self.average_socket_power / 1000
     }
 
     fn get_activity(&self) -> u16 {
@@ -192,6 +431,24 @@ impl GpuMetrics for GpuMetricsV2_0 {
         self.throttle_status
     }
 
+    fn get_all_temperatures(&self) -> Vec<(String, u16)> {
+        // APU firmware reports centidegrees
+        let mut temperatures: Vec<(String, u16)> = [("GFX", self.temperature_gfx), ("SoC", self.temperature_soc)]
+            .into_iter()
+            .filter(|&(_, value)| reported(value))
+            .map(|(label, value)| (label.to_string(), value / 100))
+            .collect();
+        temperatures.extend(self.temperature_core.iter()
+            .enumerate()
+            .filter(|&(_, &value)| reported(value))
+            .map(|(core, &value)| (format!("Core {}", core), value / 100)));
+        temperatures.extend(self.temperature_l3.iter()
+            .enumerate()
+            .filter(|&(_, &value)| reported(value))
+            .map(|(cache, &value)| (format!("L3 {}", cache), value / 100)));
+        temperatures
+    }
+
     fn get_fan_speed(&self) -> (u16, bool) {
         // fan_pwm is PWM value, convert to percentage
         let speed = ((self.fan_pwm as f64 / 255.0) * 100.0) as u16;
@@ -201,6 +458,15 @@ impl GpuMetrics for GpuMetricsV2_0 {
     fn get_header(&self) -> Header {
         self.header.clone()
     }
+
+    fn get_apu_power(&self) -> Option<ApuPowerSplit> {
+        ApuPowerSplit::from_raw(
+            self.average_socket_power,
+            self.average_cpu_power,
+            self.average_gfx_power,
+            self.average_soc_power,
+        )
+    }
 }
 
 /// Find GPU metrics file automatically
@@ -228,4 +494,254 @@ pub fn find_gpu_metrics_file() -> Result<Option<std::path::PathBuf>, SensorError
         reason: "No AMD GPU found".to_string(),
         is_temporary: false,
     })
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(format_revision: u8, content_revision: u8) -> Header {
+        Header { structure_size: 120, format_revision, content_revision }
+    }
+
+    fn v1_table() -> GpuMetricsV1_0 {
+        GpuMetricsV1_0 {
+            header: header(1, 3),
+            system_clock_counter: 0,
+            temperature_edge: 0,
+            temperature_hotspot: 0,
+            temperature_mem: 0,
+            temperature_vrgfx: 0,
+            temperature_vrsoc: 0,
+            temperature_vrmem: 0,
+            average_gfx_activity: 0,
+            average_umc_activity: 0,
+            average_mm_activity: 0,
+            average_socket_power: 0,
+            energy_accumulator: 0,
+            average_gfxclk_frequency: 0,
+            average_socclk_frequency: 0,
+            average_uclk_frequency: 0,
+            average_vclk0_frequency: 0,
+            average_dclk0_frequency: 0,
+            average_vclk1_frequency: 0,
+            average_dclk1_frequency: 0,
+            current_gfxclk: 0,
+            current_socclk: 0,
+            current_uclk: 0,
+            current_vclk0: 0,
+            current_dclk0: 0,
+            current_vclk1: 0,
+            current_dclk1: 0,
+            throttle_status: 0,
+            current_fan_speed: 0,
+            pcie_link_width: 0,
+            pcie_link_speed: 0,
+        }
+    }
+
+    fn v2_table() -> GpuMetricsV2_0 {
+        GpuMetricsV2_0 {
+            header: header(2, 2),
+            system_clock_counter: 0,
+            temperature_gfx: 0,
+            temperature_soc: 0,
+            temperature_core: [0; 8],
+            temperature_l3: [0; 2],
+            average_gfx_activity: 0,
+            average_mm_activity: 0,
+            average_socket_power: 0,
+            average_cpu_power: 0,
+            average_soc_power: 0,
+            average_gfx_power: 0,
+            average_core_power: [0; 8],
+            average_gfxclk_frequency: 0,
+            average_socclk_frequency: 0,
+            average_uclk_frequency: 0,
+            average_fclk_frequency: 0,
+            average_vclk_frequency: 0,
+            average_dclk_frequency: 0,
+            current_gfxclk: 0,
+            current_socclk: 0,
+            current_uclk: 0,
+            current_fclk: 0,
+            current_vclk: 0,
+            current_dclk: 0,
+            current_coreclk: [0; 8],
+            current_l3clk: [0; 2],
+            throttle_status: 0,
+            fan_pwm: 0,
+            padding: [0; 3],
+        }
+    }
+
+    #[test]
+    fn test_v1_table() {
+        let table = GpuMetricsV1_0 {
+            temperature_edge: 52,
+            temperature_hotspot: 68,
+            temperature_mem: u16::MAX,
+            temperature_vrgfx: 0,
+            temperature_vrsoc: 47,
+            average_socket_power: 180,
+            average_gfx_activity: 97,
+            average_gfxclk_frequency: 2450,
+            current_fan_speed: 40,
+            ..v1_table()
+        };
+        assert_eq!(table.get_header().version(), "v1.3");
+        assert_eq!(table.get_temperature(), (52, "Edge".to_string()));
+        assert_eq!(table.get_power(), 180);
+        assert_eq!(table.get_activity(), 97);
+        assert_eq!(table.get_frequency(), 2450);
+        assert_eq!(table.get_fan_speed(), (40, true));
+        assert_eq!(table.get_apu_power(), None);
+
+        // Unsupported (0xFFFF) and empty sensors are left out
+        assert_eq!(
+            table.get_all_temperatures(),
+            vec![("Edge".to_string(), 52), ("Hotspot".to_string(), 68), ("VR SoC".to_string(), 47)]
+        );
+
+        // Raw PWM is scaled to a percentage
+        assert_eq!(GpuMetricsV1_0 { current_fan_speed: 255, ..v1_table() }.get_fan_speed(), (100, true));
+        assert_eq!(v1_table().get_fan_speed(), (0, false));
+    }
+
+    #[test]
+    fn test_v2_table_units() {
+        let mut table = GpuMetricsV2_0 {
+            temperature_gfx: 4550,
+            temperature_soc: 4120,
+            average_socket_power: 15_400,
+            average_gfx_activity: 35,
+            average_gfxclk_frequency: 1800,
+            fan_pwm: 128,
+            ..v2_table()
+        };
+        table.temperature_core = [5000, 5100, 0, 0, u16::MAX, u16::MAX, u16::MAX, u16::MAX];
+        table.temperature_l3 = [4800, u16::MAX];
+
+        // Centidegrees and milliwatts are converted to °C and W
+        assert_eq!(table.get_header().version(), "v2.2");
+        assert_eq!(table.get_temperature(), (45, "GFX".to_string()));
+        assert_eq!(table.get_power(), 15);
+        assert_eq!(table.get_activity(), 35);
+        assert_eq!(table.get_frequency(), 1800);
+        assert_eq!(table.get_fan_speed(), (50, true));
+
+        assert_eq!(
+            table.get_all_temperatures(),
+            vec![
+                ("GFX".to_string(), 45),
+                ("SoC".to_string(), 41),
+                ("Core 0".to_string(), 50),
+                ("Core 1".to_string(), 51),
+                ("L3 0".to_string(), 48),
+            ]
+        );
+
+        // A table without any reported sensor has no temperatures
+        let empty = GpuMetricsV2_0 { temperature_soc: u16::MAX, ..v2_table() };
+        assert!(empty.get_all_temperatures().is_empty());
+    }
+
+    #[test]
+    fn test_reported() {
+        assert!(reported(1));
+        assert!(reported(4550));
+        assert!(!reported(0));
+        assert!(!reported(u16::MAX));
+    }
+
+    #[test]
+    fn test_throttle_status() {
+        let idle = ThrottleStatus(0);
+        assert!(!idle.is_throttling());
+        assert_eq!(idle.kind(), None);
+        assert!(idle.active_flags().is_empty());
+
+        let thermal = ThrottleStatus((1 << 1) | (1 << 33));
+        assert_eq!(thermal.active_flags(), vec!["PROCHOT_GFX", "THM_GFX"]);
+        assert_eq!(thermal.kind(), Some("thermal"));
+
+        let power = ThrottleStatus(1 << 20);
+        assert!(power.is_power_throttling());
+        assert_eq!(power.kind(), Some("power"));
+        assert_eq!(ThrottleStatus::describe(&power.active_flags()[0]), "sustained power limit");
+
+        assert_eq!(ThrottleStatus((1 << 34) | (1 << 23)).kind(), Some("thermal and power"));
+
+        // Bits without a known meaning still count as throttling
+        let unknown = ThrottleStatus(1 << 8);
+        assert!(unknown.active_flags().is_empty());
+        assert_eq!(unknown.kind(), Some("other"));
+        assert_eq!(ThrottleStatus::describe("BIT8"), "unknown reason");
+    }
+
+    /// A device directory with an `amdgpu` hwmon holding the given files.
+    fn device(hwmon_files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let hwmon = dir.path().join("hwmon/hwmon4");
+        std::fs::create_dir_all(&hwmon).unwrap();
+        std::fs::write(hwmon.join("name"), "amdgpu\n").unwrap();
+        for (name, value) in hwmon_files {
+            std::fs::write(hwmon.join(name), value).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_power_settings() {
+        let table = "PROFILE_INDEX(NAME) CLOCK_TYPE(NAME) FPS MinActiveFreqType\n\
+                       0 BOOTUP_DEFAULT :\n\
+                       1 3D_FULL_SCREEN*:\n\
+                       2 POWER_SAVING  :\n";
+        assert_eq!(PowerSettings::parse_profile(table).as_deref(), Some("3D_FULL_SCREEN"));
+        assert_eq!(PowerSettings::parse_profile("  1 3D_FULL_SCREEN *:\n").as_deref(), Some("3D_FULL_SCREEN"));
+        assert_eq!(PowerSettings::parse_profile("  0 BOOTUP_DEFAULT :\n"), None);
+        assert_eq!(PowerSettings::parse_profile("  1*:\n"), None);
+
+        let dir = device(&[("power1_cap", "250000000\n"), ("power1_cap_max", "0\n")]);
+        std::fs::write(dir.path().join("power_dpm_force_performance_level"), "manual\n").unwrap();
+        std::fs::write(dir.path().join("pp_power_profile_mode"), table).unwrap();
+        assert_eq!(PowerSettings::read(dir.path()), PowerSettings {
+            cap: Some(250),
+            cap_max: None,
+            performance_level: Some("manual".to_string()),
+            profile: Some("3D_FULL_SCREEN".to_string()),
+        });
+
+        // Nothing exposed
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(PowerSettings::read(empty.path()), PowerSettings::default());
+    }
+
+    #[test]
+    fn test_fan_reading() {
+        assert!(FanReading::parse_zero_rpm_enable("FAN_ZERO_RPM_ENABLE:\n1\nOD_RANGE:\nZERO_RPM_ENABLE: 0 1\n"));
+        assert!(!FanReading::parse_zero_rpm_enable("FAN_ZERO_RPM_ENABLE:\n0\n"));
+        assert!(!FanReading::parse_zero_rpm_enable(""));
+
+        // Spinning fan
+        let dir = device(&[("fan1_input", "1450\n"), ("pwm1_enable", "2\n")]);
+        assert_eq!(FanReading::read(dir.path()), FanReading { rpm: Some(1450), passive: false });
+
+        // Stopped under automatic control
+        let dir = device(&[("fan1_input", "0\n"), ("pwm1_enable", "2\n")]);
+        assert_eq!(FanReading::read(dir.path()), FanReading { rpm: Some(0), passive: true });
+
+        // Stopped under manual control, unless the overdrive switch is on
+        let dir = device(&[("fan1_input", "0\n"), ("pwm1_enable", "1\n")]);
+        assert_eq!(FanReading::read(dir.path()), FanReading { rpm: Some(0), passive: false });
+        let fan_ctrl = dir.path().join("gpu_od/fan_ctrl");
+        std::fs::create_dir_all(&fan_ctrl).unwrap();
+        std::fs::write(fan_ctrl.join("fan_zero_rpm_enable"), "FAN_ZERO_RPM_ENABLE:\n1\n").unwrap();
+        assert_eq!(FanReading::read(dir.path()), FanReading { rpm: Some(0), passive: true });
+
+        // No tachometer, or no hwmon at all
+        let dir = device(&[("pwm1_enable", "2\n")]);
+        assert_eq!(FanReading::read(dir.path()), FanReading::default());
+        let empty = tempfile::tempdir().unwrap();
+        assert_eq!(FanReading::read(empty.path()), FanReading::default());
+    }
+}
@@ -1,3 +1,3 @@
 pub mod amdgpu;
 
-pub use amdgpu::{AmdgpuSensor, OutputFormat, MetricsReader, GpuMetrics};
\ No newline at end of file
+pub use amdgpu::{AmdgpuSensor, MultiAmdgpuSensor, GpuDisplayMode, MetricsBackend, ApuMode, ApuPowerSplit, OutputFormat, MetricsReader, GpuMetrics};
\ No newline at end of file
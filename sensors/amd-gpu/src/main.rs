@@ -1,10 +1,9 @@
 use clap::Parser;
-use waysensor_rs_core::{GlobalConfig, Sensor, IconStyle};
+use waysensor_rs_core::{discovery, schedule, session_lock, GlobalConfig, Sensor, SensorError, IconStyle};
 use std::io::{self, Write};
 use std::time::Duration;
-use tokio::time;
 
-use waysensor_rs_amd_gpu::AmdgpuSensor;
+use waysensor_rs_amd_gpu::{AmdgpuSensor, ApuMode, GpuDisplayMode, MetricsBackend, MultiAmdgpuSensor};
 
 #[derive(Parser)]
 #[command(name = "waysensor-rs-amd-gpu")]
@@ -15,6 +14,30 @@ struct Args {
     #[arg(short, long)]
     file: Option<String>,
 
+    /// Where readings come from: auto, sysfs, gpu-metrics
+    #[arg(long, default_value = "auto")]
+    backend: MetricsBackend,
+
+    /// Treat the GPU as an integrated APU: auto, on, off
+    #[arg(long, default_value = "auto")]
+    apu_mode: ApuMode,
+
+    /// Monitor this card, e.g. 1 for /sys/class/drm/card1 (see --list)
+    #[arg(long)]
+    card: Option<u32>,
+
+    /// Monitor the GPU with this PCI slot (0000:03:00.0) or vendor:device id (1002:73bf)
+    #[arg(long)]
+    pci_id: Option<String>,
+
+    /// Monitor all AMD GPUs at once
+    #[arg(long, conflicts_with_all = ["card", "pci_id"])]
+    all: bool,
+
+    /// What to show with --all (hottest, cycle, combined)
+    #[arg(short, long, default_value = "hottest")]
+    display_mode: GpuDisplayMode,
+
     /// Update interval in milliseconds
     #[arg(short, long, default_value = "1000")]
     interval: u64,
@@ -35,6 +58,18 @@ struct Args {
     #[arg(short, long)]
     once: bool,
 
+    /// List available AMD GPUs and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
     /// Verbose output
     #[arg(short, long)]
     verbose: bool,
@@ -72,17 +107,37 @@ struct Args {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<AmdgpuSensor>(args.json) {
+            eprintln!("Error listing AMD GPUs: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    
     if args.verbose {
         eprintln!("Starting waysensor-rs-amd-gpu...");
     }
     
-    let mut amdgpu_sensor = AmdgpuSensor::new(
-        args.file,
-        args.temp_warning,
-        args.temp_critical,
-        args.format,
-        args.verbose,
-    )?;
+    let mut amdgpu_sensor: Box<dyn Sensor<Error = SensorError>> = if args.all {
+        Box::new(MultiAmdgpuSensor::new(args.temp_warning, args.temp_critical, args.display_mode)?)
+    } else {
+        Box::new(AmdgpuSensor::new(
+            args.file,
+            args.temp_warning,
+            args.temp_critical,
+            args.format,
+            args.verbose,
+        )?
+        .select_device(args.card, args.pci_id.as_deref())?
+        .with_backend(args.backend)
+        .with_apu_mode(args.apu_mode))
+    };
     
     // Handle config generation
     if args.generate_config {
@@ -113,7 +168,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Load global configuration and apply command line overrides
     let global_config = GlobalConfig::load().unwrap_or_default();
-    let mut config = global_config.to_sensor_config()
+    let mut config = global_config.to_sensor_config_for("amd-gpu")
         .with_update_interval(Duration::from_millis(args.interval))
         .apply_color_overrides(
             args.icon_color,
@@ -139,15 +194,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     amdgpu_sensor.configure(config)?;
     
     if args.once {
-        let output = amdgpu_sensor.read()?;
+        let output = amdgpu_sensor.read_styled()?;
         println!("{}", serde_json::to_string(&output)?);
     } else {
-        let mut interval = time::interval(Duration::from_millis(args.interval));
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
         
         loop {
             interval.tick().await;
+            if session_lock::wait_async(amdgpu_sensor.config()).await {
+                interval.reset();
+            }
             
-            match amdgpu_sensor.read() {
+            match amdgpu_sensor.read_styled() {
                 Ok(output) => {
                     println!("{}", serde_json::to_string(&output)?);
                     io::stdout().flush()?;
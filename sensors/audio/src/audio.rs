@@ -0,0 +1,413 @@
+//! Volume, mute state and device of the default audio output.
+//!
+//! PipeWire is queried through WirePlumber's `wpctl`, PulseAudio through
+//! `pactl`. pipewire-pulse answers `pactl` too, so `pactl` is the fallback
+//! on any system without WirePlumber. Both tools are run with `LC_ALL=C`
+//! because `pactl` translates its output.
+
+use std::process::Command;
+use waysensor_rs_core::{format, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+/// CSS class of a muted output, in place of the theme's normal class.
+pub const MUTED_CLASS: &str = "muted";
+
+/// `wpctl` name of the default output
+const WPCTL_SINK: &str = "@DEFAULT_AUDIO_SINK@";
+/// `pactl` name of the default output
+const PACTL_SINK: &str = "@DEFAULT_SINK@";
+
+/// Run a sound server tool and return its output.
+pub(crate) fn run(program: &str, args: &[&str]) -> Result<String, SensorError> {
+    let output = Command::new(program)
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => SensorError::unavailable(format!("{} not installed", program)),
+            _ => SensorError::Io(e),
+        })?;
+    if !output.status.success() {
+        return Err(SensorError::unavailable(format!(
+            "{} {} failed: {}",
+            program,
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Where the volume is read and changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AudioBackend {
+    /// `wpctl` if WirePlumber answers, `pactl` otherwise
+    #[default]
+    Auto,
+    PipeWire,
+    Pulse,
+}
+
+impl std::str::FromStr for AudioBackend {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "pipewire" | "wpctl" => Ok(Self::PipeWire),
+            "pulseaudio" | "pulse" | "pactl" => Ok(Self::Pulse),
+            _ => Err(SensorError::config_with_value(
+                "Invalid audio backend. Valid options: auto, pipewire, pulseaudio",
+                s,
+            )),
+        }
+    }
+}
+
+/// State of the default output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SinkState {
+    /// Volume in percent, above 100 when boosted
+    pub volume: u32,
+    pub muted: bool,
+    /// Name of the output device, e.g. `Built-in Audio Analog Stereo`
+    pub device: Option<String>,
+    /// Active port of the device, e.g. `Headphones`
+    pub port: Option<String>,
+}
+
+/// A volume change, bound to scrolling and clicking on the module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeAction {
+    /// Raise by this many percent, up to the maximum volume
+    Raise(u32),
+    /// Lower by this many percent
+    Lower(u32),
+    ToggleMute,
+}
+
+/// An output as `pactl list sinks` describes it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Sink {
+    name: String,
+    description: Option<String>,
+    /// Description of the active port
+    port: Option<String>,
+}
+
+/// Parse `pactl list sinks`.
+fn parse_sinks(output: &str) -> Vec<Sink> {
+    let mut sinks: Vec<Sink> = Vec::new();
+    // Port id -> description of the sink being parsed
+    let mut ports: Vec<(String, String)> = Vec::new();
+    let mut in_ports = false;
+    for line in output.lines() {
+        if line.starts_with("Sink #") {
+            sinks.push(Sink::default());
+            ports.clear();
+            in_ports = false;
+            continue;
+        }
+        let Some(sink) = sinks.last_mut() else { continue };
+        let depth = line.len() - line.trim_start_matches('\t').len();
+        let line = line.trim();
+        if in_ports && depth >= 2 {
+            // analog-output-headphones: Headphones (type: Headphones, priority: 9900, available)
+            if let Some((id, description)) = line.split_once(": ") {
+                let description = description.rsplit_once(" (").map_or(description, |(name, _)| name);
+                ports.push((id.to_string(), description.to_string()));
+            }
+            continue;
+        }
+        in_ports = line == "Ports:";
+        if let Some(name) = line.strip_prefix("Name: ") {
+            sink.name = name.to_string();
+        } else if let Some(description) = line.strip_prefix("Description: ") {
+            sink.description = Some(description.to_string());
+        } else if let Some(active) = line.strip_prefix("Active Port: ") {
+            sink.port = ports.iter().find(|(id, _)| id == active).map(|(_, description)| description.clone());
+        }
+    }
+    sinks
+}
+
+/// Volume from `pactl get-sink-volume`: the mean of the channels.
+fn parse_pactl_volume(output: &str) -> Option<u32> {
+    // Volume: front-left: 29491 /  45% / -20.81 dB,   front-right: 29491 /  45% / -20.81 dB
+    let channels: Vec<u32> = output.lines().next()?
+        .split_whitespace()
+        .filter_map(|word| word.strip_suffix('%')?.parse().ok())
+        .collect();
+    if channels.is_empty() {
+        return None;
+    }
+    Some((channels.iter().sum::<u32>() as f64 / channels.len() as f64).round() as u32)
+}
+
+/// Volume and mute state from `wpctl get-volume`, e.g. `Volume: 0.45 [MUTED]`.
+fn parse_wpctl_volume(output: &str) -> Option<(u32, bool)> {
+    let rest = output.trim().strip_prefix("Volume:")?;
+    let volume: f64 = rest.split_whitespace().next()?.parse().ok()?;
+    Some(((volume * 100.0).round() as u32, rest.contains("[MUTED]")))
+}
+
+/// Device name from `wpctl inspect`: `node.description`, else `node.nick`.
+fn parse_wpctl_description(output: &str) -> Option<String> {
+    let property = |key: &str| {
+        output.lines().find_map(|line| {
+            // "  * node.description = "Built-in Audio Analog Stereo""
+            let (name, value) = line.trim().trim_start_matches('*').split_once('=')?;
+            (name.trim() == key).then(|| value.trim().trim_matches('"').to_string())
+        })
+    };
+    property("node.description").or_else(|| property("node.nick"))
+}
+
+impl AudioBackend {
+    /// The backend that answers, for `Auto`.
+    pub(crate) fn resolve(self) -> Result<Self, SensorError> {
+        if self != Self::Auto {
+            return Ok(self);
+        }
+        if run("wpctl", &["get-volume", WPCTL_SINK]).is_ok() {
+            return Ok(Self::PipeWire);
+        }
+        run("pactl", &["get-default-sink"])
+            .map(|_| Self::Pulse)
+            .map_err(|_| SensorError::unavailable("Neither wpctl (PipeWire) nor pactl (PulseAudio) reaches a sound server"))
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::PipeWire => "PipeWire",
+            Self::Pulse => "PulseAudio",
+        }
+    }
+
+    fn query(self) -> Result<SinkState, SensorError> {
+        match self {
+            Self::PipeWire | Self::Auto => {
+                let (volume, muted) = parse_wpctl_volume(&run("wpctl", &["get-volume", WPCTL_SINK])?)
+                    .ok_or_else(|| SensorError::parse("Unexpected wpctl get-volume output"))?;
+                let device = run("wpctl", &["inspect", WPCTL_SINK]).ok()
+                    .and_then(|inspect| parse_wpctl_description(&inspect));
+                Ok(SinkState { volume, muted, device, port: None })
+            }
+            Self::Pulse => {
+                let volume = parse_pactl_volume(&run("pactl", &["get-sink-volume", PACTL_SINK])?)
+                    .ok_or_else(|| SensorError::parse("Unexpected pactl get-sink-volume output"))?;
+                let muted = run("pactl", &["get-sink-mute", PACTL_SINK])?.contains("yes");
+                let default = run("pactl", &["get-default-sink"])?;
+                let sink = run("pactl", &["list", "sinks"]).ok()
+                    .and_then(|list| parse_sinks(&list).into_iter().find(|sink| sink.name == default.trim()));
+                Ok(SinkState {
+                    volume,
+                    muted,
+                    device: sink.as_ref().and_then(|sink| sink.description.clone()),
+                    port: sink.and_then(|sink| sink.port),
+                })
+            }
+        }
+    }
+
+    fn apply(self, action: VolumeAction, max_volume: u32) -> Result<(), SensorError> {
+        match (self, action) {
+            (Self::Pulse, VolumeAction::ToggleMute) => run("pactl", &["set-sink-mute", PACTL_SINK, "toggle"]),
+            (Self::Pulse, VolumeAction::Raise(step) | VolumeAction::Lower(step)) => {
+                // pactl has no limit of its own; never lower a volume that
+                // is already boosted past the maximum when raising
+                let current = self.query()?.volume;
+                let volume = match action {
+                    VolumeAction::Raise(_) => current.max((current + step).min(max_volume)),
+                    _ => current.saturating_sub(step),
+                };
+                run("pactl", &["set-sink-volume", PACTL_SINK, &format!("{}%", volume)])
+            }
+            (_, VolumeAction::ToggleMute) => run("wpctl", &["set-mute", WPCTL_SINK, "toggle"]),
+            (_, VolumeAction::Raise(step)) => {
+                let limit = format!("{:.2}", f64::from(max_volume) / 100.0);
+                run("wpctl", &["set-volume", "-l", &limit, WPCTL_SINK, &format!("{}%+", step)])
+            }
+            (_, VolumeAction::Lower(step)) => run("wpctl", &["set-volume", WPCTL_SINK, &format!("{}%-", step)]),
+        }
+        .map(|_| ())
+    }
+}
+
+/// Sensor for the default audio output.
+#[derive(Debug)]
+pub struct AudioSensor {
+    name: String,
+    config: SensorConfig,
+    backend: AudioBackend,
+    /// Percent per scroll step
+    step: u32,
+    /// Highest volume scrolling raises to, in percent
+    max_volume: u32,
+}
+
+impl AudioSensor {
+    /// Create an audio sensor, finding the sound server for `Auto`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no sound server answers.
+    pub fn new(backend: AudioBackend) -> Result<Self, SensorError> {
+        Ok(Self {
+            name: "audio".to_string(),
+            config: SensorConfig::default(),
+            backend: backend.resolve()?,
+            step: 5,
+            max_volume: 100,
+        })
+    }
+
+    /// Percent per scroll step, from the `step` setting.
+    pub fn step(&self) -> u32 {
+        self.step
+    }
+
+    /// Change the volume or mute state of the default output.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sound server rejects the change.
+    pub fn apply(&self, action: VolumeAction) -> Result<(), SensorError> {
+        self.backend.apply(action, self.max_volume)
+    }
+
+    fn build_tooltip(&self, state: &SinkState) -> String {
+        let mut lines = Vec::new();
+        if let Some(device) = &state.device {
+            lines.push(format::key_value("Output", device, &self.config));
+        }
+        if let Some(port) = &state.port {
+            lines.push(format::key_value("Port", port, &self.config));
+        }
+        let volume = if state.muted {
+            format!("{}% (muted)", state.volume)
+        } else {
+            format!("{}%", state.volume)
+        };
+        lines.push(format::key_value("Volume", &volume, &self.config));
+        lines.push(format::key_value("Sound Server", self.backend.name(), &self.config));
+        lines.join("\n")
+    }
+}
+
+impl Discoverable for AudioSensor {
+    const KIND: &'static str = "audio outputs";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        // No pactl means nothing to list, not a failure
+        let Ok(list) = run("pactl", &["list", "sinks"]) else {
+            return Ok(Vec::new());
+        };
+        let default = run("pactl", &["get-default-sink"]).unwrap_or_default();
+        Ok(parse_sinks(&list)
+            .into_iter()
+            .map(|sink| {
+                let is_default = sink.name == default.trim();
+                let mut item = DiscoveredItem::new(sink.name, sink.description.unwrap_or_default());
+                if let Some(port) = sink.port {
+                    item = item.with_detail("port", port);
+                }
+                if is_default {
+                    item = item.with_detail("default", "yes");
+                }
+                item
+            })
+            .collect())
+    }
+}
+
+impl Sensor for AudioSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let state = self.backend.query()?;
+
+        let (status, icon) = if state.muted {
+            ("muted".to_string(), &self.config.icons.volume_muted)
+        } else {
+            (format!("{}%", state.volume), &self.config.icons.volume)
+        };
+        let class = if state.muted { MUTED_CLASS } else { &self.config.theme.normal };
+
+        Ok(WaybarOutput {
+            text: format::with_icon_and_colors(&status, icon, &self.config),
+            tooltip: Some(self.build_tooltip(&state)),
+            class: Some(class.to_string()),
+            percentage: Some(state.volume.min(100) as u8),
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        if let Some(step) = config.get_custom("step").and_then(|v| v.as_u64()) {
+            self.step = step as u32;
+        }
+        if let Some(max_volume) = config.get_custom("max_volume").and_then(|v| v.as_u64()) {
+            self.max_volume = max_volume as u32;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        self.backend.query().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SINKS: &str = "\
+Sink #47
+\tState: RUNNING
+\tName: alsa_output.pci-0000_00_1f.3.analog-stereo
+\tDescription: Built-in Audio Analog Stereo
+\tDriver: PipeWire
+\tPorts:
+\t\tanalog-output-speaker: Speakers (type: Speaker, priority: 10000, availability unknown)
+\t\tanalog-output-headphones: Headphones (type: Headphones, priority: 9900, available)
+\tActive Port: analog-output-headphones
+\tFormats:
+\t\tpcm
+
+Sink #62
+\tState: SUSPENDED
+\tName: bluez_output.00_1B_66_AA_BB_CC.1
+\tDescription: WH-1000XM4
+\tDriver: PipeWire
+";
+
+    #[test]
+    fn test_sound_server_output_parsing() {
+        let sinks = parse_sinks(SINKS);
+        assert_eq!(sinks.len(), 2);
+        assert_eq!(sinks[0].name, "alsa_output.pci-0000_00_1f.3.analog-stereo");
+        assert_eq!(sinks[0].description.as_deref(), Some("Built-in Audio Analog Stereo"));
+        assert_eq!(sinks[0].port.as_deref(), Some("Headphones"));
+        assert_eq!(sinks[1].description.as_deref(), Some("WH-1000XM4"));
+        assert_eq!(sinks[1].port, None);
+
+        let volume = "Volume: front-left: 29491 /  45% / -20.81 dB,   front-right: 32768 /  50% / -18.06 dB\n        balance 0.10\n";
+        assert_eq!(parse_pactl_volume(volume), Some(48));
+
+        assert_eq!(parse_wpctl_volume("Volume: 0.45\n"), Some((45, false)));
+        assert_eq!(parse_wpctl_volume("Volume: 1.20 [MUTED]\n"), Some((120, true)));
+        assert_eq!(parse_wpctl_volume("Translate ID: 0"), None);
+
+        let inspect = "id 52, type PipeWire:Interface:Node\n    media.class = \"Audio/Sink\"\n  * node.description = \"Built-in Audio Analog Stereo\"\n  * node.name = \"alsa_output.pci\"\n    node.nick = \"ALC257 Analog\"\n";
+        assert_eq!(parse_wpctl_description(inspect).as_deref(), Some("Built-in Audio Analog Stereo"));
+    }
+}
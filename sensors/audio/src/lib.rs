@@ -0,0 +1,12 @@
+//! Audio output sensor for waysensor-rs.
+//!
+//! Reports the volume, mute state and device of the default output through
+//! PipeWire's `wpctl`, or `pactl` where PulseAudio (or pipewire-pulse) runs
+//! without WirePlumber, and changes the volume on scroll and click. A second
+//! sensor shows which applications are recording from the microphone.
+
+pub mod audio;
+pub mod mic;
+
+pub use audio::{AudioBackend, AudioSensor, SinkState, VolumeAction, MUTED_CLASS};
+pub use mic::{MicClient, MicSensor, RECORDING_CLASS};
@@ -0,0 +1,245 @@
+//! waysensor-rs-audio: audio volume binary for Waybar.
+//!
+//! Bind the actions to the module's mouse events, e.g.
+//! `"on-scroll-up": "waysensor-rs-audio --raise"`,
+//! `"on-scroll-down": "waysensor-rs-audio --lower"` and
+//! `"on-click": "waysensor-rs-audio --toggle-mute"`. With `--mic` it shows
+//! which applications are recording instead.
+
+use clap::Parser;
+use waysensor_rs_core::{discovery, schedule, session_lock, GlobalConfig, IconStyle, Sensor, SensorConfig, SensorError};
+use waysensor_rs_audio::{AudioBackend, AudioSensor, MicSensor, VolumeAction};
+use std::io::{self, Write};
+use std::process;
+use std::time::Duration;
+
+/// Command-line arguments for the audio sensor.
+#[derive(Parser)]
+#[command(name = "waysensor-rs-audio")]
+#[command(about = "Audio volume sensor for waysensor-rs")]
+#[command(version)]
+#[command(author)]
+struct Args {
+    /// Update interval in milliseconds (minimum 100ms)
+    #[arg(short, long, default_value = "1000", value_parser = validate_interval)]
+    interval: u64,
+
+    /// Sound server tool to use: auto, pipewire (wpctl), pulseaudio (pactl)
+    #[arg(long, default_value = "auto")]
+    backend: AudioBackend,
+
+    /// Show which applications are recording from the microphone instead
+    #[arg(long, conflicts_with_all = ["raise", "lower", "toggle_mute"])]
+    mic: bool,
+
+    /// Raise the volume by one step and exit
+    #[arg(long, conflicts_with_all = ["lower", "toggle_mute"])]
+    raise: bool,
+
+    /// Lower the volume by one step and exit
+    #[arg(long, conflicts_with = "toggle_mute")]
+    lower: bool,
+
+    /// Mute or unmute the output and exit
+    #[arg(long)]
+    toggle_mute: bool,
+
+    /// Percent per step for --raise and --lower (default: the "step" setting, 5)
+    #[arg(long)]
+    step: Option<u32>,
+
+    /// One-shot mode (output once and exit)
+    #[arg(short, long)]
+    once: bool,
+
+    /// List audio outputs and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Icon style (nerdfont, fontawesome, ascii, none)
+    #[arg(long)]
+    icon_style: Option<IconStyle>,
+
+    /// Icon color (hex format like "#7aa2f7")
+    #[arg(long)]
+    icon_color: Option<String>,
+
+    /// Text color (hex format like "#c0caf5")
+    #[arg(long)]
+    text_color: Option<String>,
+
+    /// Tooltip label color (hex format like "#bb9af7")
+    #[arg(long)]
+    tooltip_label_color: Option<String>,
+
+    /// Tooltip value color (hex format like "#9ece6a")
+    #[arg(long)]
+    tooltip_value_color: Option<String>,
+
+    /// Verify a sound server answers and exit
+    #[arg(long)]
+    check: bool,
+
+    /// Generate example config file and exit
+    #[arg(long)]
+    generate_config: bool,
+}
+
+/// Validate that the interval is at least 100ms.
+fn validate_interval(s: &str) -> Result<u64, String> {
+    let interval = s.parse::<u64>()
+        .map_err(|_| "Interval must be a positive integer".to_owned())?;
+    
+    if interval < SensorConfig::MIN_UPDATE_INTERVAL {
+        return Err(format!(
+            "Interval must be at least {}ms", 
+            SensorConfig::MIN_UPDATE_INTERVAL
+        ));
+    }
+    
+    Ok(interval)
+}
+
+/// Main entry point for the audio sensor.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<AudioSensor>(args.json) {
+            eprintln!("Error listing audio outputs: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Handle config generation
+    if args.generate_config {
+        if let Some(config_path) = GlobalConfig::default_config_path() {
+            GlobalConfig::save_example_config_to_file(&config_path)?;
+            println!("Generated example config at: {}", config_path.display());
+            println!("\nYou can now edit this file to customize your default colors and settings.");
+        } else {
+            eprintln!("Could not determine config directory");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Load global configuration and apply command line overrides
+    let global_config = GlobalConfig::load().unwrap_or_default();
+    let mut config = global_config.to_sensor_config_for(if args.mic { "mic" } else { "audio" })
+        .with_update_interval(Duration::from_millis(args.interval))
+        .apply_color_overrides(
+            args.icon_color,
+            args.text_color,
+            args.tooltip_label_color,
+            args.tooltip_value_color,
+        );
+    
+    // Override icon style only if explicitly provided
+    if let Some(icon_style) = args.icon_style {
+        config = config.with_icon_style(icon_style);
+    }
+    
+    // Volume changes from scroll and click bindings
+    if args.raise || args.lower || args.toggle_mute {
+        let result = AudioSensor::new(args.backend).and_then(|mut sensor| {
+            sensor.configure(config)?;
+            let step = args.step.unwrap_or_else(|| sensor.step());
+            let action = if args.raise {
+                VolumeAction::Raise(step)
+            } else if args.lower {
+                VolumeAction::Lower(step)
+            } else {
+                VolumeAction::ToggleMute
+            };
+            sensor.apply(action)
+        });
+        if let Err(e) = result {
+            eprintln!("Error changing the volume: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Create the audio or microphone sensor
+    let label = if args.mic { "Microphone" } else { "Audio" };
+    let sensor: Result<Box<dyn Sensor<Error = SensorError>>, SensorError> = if args.mic {
+        MicSensor::new(args.backend).map(|sensor| Box::new(sensor) as _)
+    } else {
+        AudioSensor::new(args.backend).map(|sensor| Box::new(sensor) as _)
+    };
+    let mut audio_sensor = match sensor {
+        Ok(sensor) => sensor,
+        Err(e) => {
+            eprintln!("Failed to create {} sensor: {}", label.to_lowercase(), e);
+            process::exit(1);
+        }
+    };
+    
+    // Check availability if requested
+    if args.check {
+        match audio_sensor.check_availability() {
+            Ok(()) => {
+                println!("{} sensor is available", label);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("{} sensor is not available: {}", label, e);
+                process::exit(1);
+            }
+        }
+    }
+    
+    audio_sensor.configure(config)?;
+    
+    if args.once {
+        // One-shot mode: read once and exit
+        match audio_sensor.read_styled() {
+            Ok(output) => {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+            Err(e) => {
+                eprintln!("Error reading audio state: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        // Continuous mode: loop and output readings
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
+        
+        loop {
+            interval.tick().await;
+            if session_lock::wait_async(audio_sensor.config()).await {
+                interval.reset();
+            }
+            
+            match audio_sensor.read_styled() {
+                Ok(output) => {
+                    println!("{}", serde_json::to_string(&output)?);
+                    io::stdout().flush()?;
+                }
+                Err(e) => {
+                    eprintln!("Error reading audio state: {}", e);
+                    // Continue running on errors, just log them
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,241 @@
+//! Microphone privacy indicator: which applications are recording.
+//!
+//! Every application recording audio owns a capture stream: a PipeWire node
+//! of class `Stream/Input/Audio` (listed by `pw-dump`), or a PulseAudio
+//! source output. Streams recording a sink monitor (screen recorders, peak
+//! meters such as pavucontrol's) capture the desktop's sound rather than a
+//! microphone and are left out. A stream that is open but paused (corked,
+//! or idle in PipeWire terms) does not count as recording.
+
+use crate::audio::{run, AudioBackend};
+use serde_json::Value;
+use waysensor_rs_core::{format, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+/// CSS class while an application is recording.
+pub const RECORDING_CLASS: &str = "recording";
+
+/// An application with a capture stream open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MicClient {
+    /// Application name, e.g. `Firefox`
+    pub name: String,
+    pub pid: Option<u32>,
+    /// Recording right now rather than paused
+    pub active: bool,
+}
+
+/// Capture streams from `pw-dump` output.
+fn parse_pw_dump(output: &str) -> Result<Vec<MicClient>, SensorError> {
+    let objects: Vec<Value> = serde_json::from_str(output)
+        .map_err(|e| SensorError::parse_with_source("Invalid pw-dump output", e))?;
+    Ok(objects
+        .iter()
+        .filter(|object| object["type"] == "PipeWire:Interface:Node")
+        .filter_map(|object| {
+            let props = &object["info"]["props"];
+            let flag = |key: &str| props[key] == true || props[key] == "true";
+            if props["media.class"] != "Stream/Input/Audio" || flag("stream.monitor") || flag("stream.capture.sink") {
+                return None;
+            }
+            let name = ["application.name", "application.process.binary", "node.name"]
+                .iter()
+                .find_map(|key| props[*key].as_str())
+                .unwrap_or("unknown");
+            // Numbers in newer PipeWire versions, strings in older ones
+            let pid = props["application.process.id"].as_u64()
+                .or_else(|| props["application.process.id"].as_str()?.parse().ok())
+                .map(|pid| pid as u32);
+            Some(MicClient { name: name.to_string(), pid, active: object["info"]["state"] == "running" })
+        })
+        .collect())
+}
+
+/// Capture streams from `pactl list source-outputs`, leaving out those
+/// recording a source in `monitors` (`pactl list short sources` indices of
+/// sink monitors).
+fn parse_source_outputs(output: &str, monitors: &[u32]) -> Vec<MicClient> {
+    let mut clients = Vec::new();
+    // Client being parsed and whether it records a monitor
+    let mut current: Option<(MicClient, bool)> = None;
+    for line in output.lines() {
+        if line.starts_with("Source Output #") {
+            clients.extend(current.take().filter(|(_, monitor)| !monitor).map(|(client, _)| client));
+            current = Some((MicClient { name: "unknown".to_string(), pid: None, active: true }, false));
+            continue;
+        }
+        let Some((client, monitor)) = current.as_mut() else { continue };
+        let line = line.trim();
+        if let Some(source) = line.strip_prefix("Source: ") {
+            *monitor = source.parse().is_ok_and(|source: u32| monitors.contains(&source));
+        } else if let Some(corked) = line.strip_prefix("Corked: ") {
+            client.active = corked == "no";
+        } else if let Some((key, value)) = line.split_once(" = ") {
+            let value = value.trim_matches('"');
+            match key {
+                "application.name" => client.name = value.to_string(),
+                "application.process.id" => client.pid = value.parse().ok(),
+                _ => {}
+            }
+        }
+    }
+    clients.extend(current.filter(|(_, monitor)| !monitor).map(|(client, _)| client));
+    clients
+}
+
+/// Indices of sink monitor sources in `pactl list short sources`.
+fn parse_monitor_sources(output: &str) -> Vec<u32> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let index = fields.next()?.parse().ok()?;
+            fields.next()?.ends_with(".monitor").then_some(index)
+        })
+        .collect()
+}
+
+/// Sensor showing which applications have the microphone open.
+#[derive(Debug)]
+pub struct MicSensor {
+    name: String,
+    config: SensorConfig,
+    backend: AudioBackend,
+}
+
+impl MicSensor {
+    /// Create a microphone sensor, finding the sound server for `Auto`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no sound server answers.
+    pub fn new(backend: AudioBackend) -> Result<Self, SensorError> {
+        Ok(Self {
+            name: "mic".to_string(),
+            config: SensorConfig::default(),
+            backend: backend.resolve()?,
+        })
+    }
+
+    /// Applications with a capture stream open, recording ones first.
+    pub fn clients(&self) -> Result<Vec<MicClient>, SensorError> {
+        let mut clients = match self.backend {
+            AudioBackend::Pulse => {
+                let monitors = parse_monitor_sources(&run("pactl", &["list", "short", "sources"])?);
+                parse_source_outputs(&run("pactl", &["list", "source-outputs"])?, &monitors)
+            }
+            _ => parse_pw_dump(&run("pw-dump", &[])?)?,
+        };
+        clients.sort_by_key(|client| !client.active);
+        Ok(clients)
+    }
+}
+
+impl Sensor for MicSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let clients = self.clients()?;
+        let recording: Vec<&MicClient> = clients.iter().filter(|client| client.active).collect();
+
+        // Nothing to show while no one records, unless configured otherwise
+        let show_idle = self.config.get_custom("show_idle").and_then(|v| v.as_bool()).unwrap_or(false);
+        let text = match recording.as_slice() {
+            [] if !show_idle => String::new(),
+            [] => format::with_icon_and_colors("off", &self.config.icons.microphone, &self.config),
+            [client] => format::with_icon_and_colors(&client.name, &self.config.icons.microphone, &self.config),
+            clients => format::with_icon_and_colors(
+                &format!("{} apps", clients.len()),
+                &self.config.icons.microphone,
+                &self.config,
+            ),
+        };
+
+        let tooltip = if clients.is_empty() {
+            format::key_value("Microphone", "not in use", &self.config)
+        } else {
+            let mut lines = vec![format::key_only("Microphone", &self.config)];
+            for client in &clients {
+                let mut value = if client.active { "recording".to_string() } else { "open, paused".to_string() };
+                if let Some(pid) = client.pid {
+                    value.push_str(&format!(" (pid {})", pid));
+                }
+                lines.push(format::key_value(&client.name, &value, &self.config));
+            }
+            lines.join("\n")
+        };
+
+        let class = if recording.is_empty() { &self.config.theme.normal } else { RECORDING_CLASS };
+        Ok(WaybarOutput {
+            text,
+            tooltip: Some(tooltip),
+            class: Some(class.to_string()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        self.clients().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_stream_parsing() {
+        let dump = r#"[
+            {"id": 30, "type": "PipeWire:Interface:Client", "info": {"props": {"application.name": "wireplumber"}}},
+            {"id": 75, "type": "PipeWire:Interface:Node", "info": {"state": "running", "props": {
+                "media.class": "Stream/Input/Audio", "application.name": "Firefox", "application.process.id": 4242}}},
+            {"id": 81, "type": "PipeWire:Interface:Node", "info": {"state": "idle", "props": {
+                "media.class": "Stream/Input/Audio", "application.process.binary": "zoom", "application.process.id": "777"}}},
+            {"id": 90, "type": "PipeWire:Interface:Node", "info": {"state": "running", "props": {
+                "media.class": "Stream/Input/Audio", "application.name": "OBS", "stream.capture.sink": true}}},
+            {"id": 95, "type": "PipeWire:Interface:Node", "info": {"state": "running", "props": {
+                "media.class": "Stream/Output/Audio", "application.name": "mpv"}}}
+        ]"#;
+        let clients = parse_pw_dump(dump).unwrap();
+        assert_eq!(clients, [
+            MicClient { name: "Firefox".to_string(), pid: Some(4242), active: true },
+            MicClient { name: "zoom".to_string(), pid: Some(777), active: false },
+        ]);
+
+        let sources = "57\talsa_output.pci-0000_00_1f.3.analog-stereo.monitor\tPipeWire\ts32le 2ch 48000Hz\tRUNNING\n\
+                       58\talsa_input.pci-0000_00_1f.3.analog-stereo\tPipeWire\ts32le 2ch 48000Hz\tRUNNING\n";
+        let monitors = parse_monitor_sources(sources);
+        assert_eq!(monitors, [57]);
+
+        let outputs = "\
+Source Output #73
+\tDriver: PipeWire
+\tSource: 58
+\tCorked: no
+\tProperties:
+\t\tapplication.name = \"Firefox\"
+\t\tapplication.process.id = \"4242\"
+
+Source Output #74
+\tSource: 57
+\tCorked: no
+\tProperties:
+\t\tapplication.name = \"pavucontrol\"
+";
+        assert_eq!(parse_source_outputs(outputs, &monitors), [
+            MicClient { name: "Firefox".to_string(), pid: Some(4242), active: true },
+        ]);
+    }
+}
@@ -0,0 +1,309 @@
+//! Brightness of the displays' backlights.
+//!
+//! Each backlight is a directory in `/sys/class/backlight` holding its
+//! current and maximum brightness in raw hardware units. Writing the
+//! `brightness` file needs root (or a udev rule), so changes go through
+//! logind's `SetBrightness`, which lets the user owning the active session
+//! change it; a direct write remains the fallback for systems without
+//! logind.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use waysensor_rs_core::{format, sysroot, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+const BACKLIGHT_PATH: &str = "/sys/class/backlight";
+
+fn read_u32(path: &Path) -> Result<u32, SensorError> {
+    let content = fs::read_to_string(path)?;
+    content.trim().parse().map_err(|_| {
+        SensorError::parse(format!("Invalid value in {}: {}", path.display(), content.trim()))
+    })
+}
+
+/// A brightness change, bound to scrolling on the module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrightnessAction {
+    /// Raise by this many percent
+    Raise(u32),
+    /// Lower by this many percent, down to the minimum brightness
+    Lower(u32),
+}
+
+/// A backlight device such as `intel_backlight` or `amdgpu_bl1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backlight {
+    pub name: String,
+    /// `firmware`, `platform` or `raw`
+    pub kind: String,
+    path: PathBuf,
+    max_brightness: u32,
+}
+
+/// Preference of a backlight type, as systemd-backlight ranks them: the
+/// firmware and platform interfaces know the panel better than the GPU
+/// driver's raw register.
+fn kind_rank(kind: &str) -> u8 {
+    match kind {
+        "firmware" => 0,
+        "platform" => 1,
+        "raw" => 2,
+        _ => 3,
+    }
+}
+
+impl Backlight {
+    /// Every backlight device, most preferred first.
+    pub fn list() -> Vec<Self> {
+        Self::list_in(&sysroot::path(BACKLIGHT_PATH))
+    }
+
+    fn list_in(root: &Path) -> Vec<Self> {
+        let Ok(entries) = fs::read_dir(root) else {
+            return Vec::new();
+        };
+        let mut backlights: Vec<Self> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let max_brightness = read_u32(&path.join("max_brightness")).ok().filter(|&max| max > 0)?;
+                let kind = fs::read_to_string(path.join("type")).map(|kind| kind.trim().to_string()).unwrap_or_default();
+                Some(Self { name: entry.file_name().to_string_lossy().into_owned(), kind, path, max_brightness })
+            })
+            .collect();
+        backlights.sort_by(|a, b| kind_rank(&a.kind).cmp(&kind_rank(&b.kind)).then_with(|| a.name.cmp(&b.name)));
+        backlights
+    }
+
+    /// The backlight named `name`, or the preferred one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no backlight, or none of that name.
+    pub fn find(name: Option<&str>) -> Result<Self, SensorError> {
+        let mut backlights = Self::list().into_iter();
+        match name {
+            Some(name) => backlights.find(|backlight| backlight.name == name)
+                .ok_or_else(|| SensorError::unavailable(format!("No backlight named {}", name))),
+            None => backlights.next().ok_or_else(|| SensorError::unavailable("No backlight found")),
+        }
+    }
+
+    /// Brightness in raw units. `actual_brightness` is what the hardware
+    /// reports, which may lag behind or differ from the requested value.
+    pub fn brightness(&self) -> Result<u32, SensorError> {
+        read_u32(&self.path.join("actual_brightness")).or_else(|_| read_u32(&self.path.join("brightness")))
+    }
+
+    pub fn max_brightness(&self) -> u32 {
+        self.max_brightness
+    }
+
+    /// Brightness in percent of the maximum.
+    pub fn percent(&self) -> Result<u32, SensorError> {
+        Ok(to_percent(self.brightness()?, self.max_brightness))
+    }
+
+    /// Set the brightness in raw units through logind, or by writing sysfs
+    /// if logind refuses.
+    ///
+    /// # Errors
+    ///
+    /// Returns logind's error if both ways fail.
+    pub fn set_brightness(&self, value: u32) -> Result<(), SensorError> {
+        let value = value.min(self.max_brightness);
+        let logind = self.set_through_logind(value);
+        if logind.is_ok() || fs::write(self.path.join("brightness"), value.to_string()).is_ok() {
+            return Ok(());
+        }
+        logind
+    }
+
+    fn set_through_logind(&self, value: u32) -> Result<(), SensorError> {
+        let output = Command::new("busctl")
+            .args([
+                "--system",
+                "call",
+                "org.freedesktop.login1",
+                "/org/freedesktop/login1/session/auto",
+                "org.freedesktop.login1.Session",
+                "SetBrightness",
+                "ssu",
+                "backlight",
+                &self.name,
+                &value.to_string(),
+            ])
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => SensorError::unavailable("busctl not installed"),
+                _ => SensorError::Io(e),
+            })?;
+        if !output.status.success() {
+            return Err(SensorError::unavailable(format!(
+                "logind SetBrightness failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn to_percent(value: u32, max: u32) -> u32 {
+    (f64::from(value) / f64::from(max) * 100.0).round() as u32
+}
+
+/// Raw brightness after `action`, never below `min_percent` so scrolling
+/// down cannot turn the panel off. Each step moves by at least one raw
+/// unit, which matters on devices with only a few levels.
+fn target_brightness(current: u32, max: u32, action: BrightnessAction, min_percent: u32) -> u32 {
+    let units = |percent: u32| ((f64::from(max) * f64::from(percent) / 100.0).round() as u32).max(1);
+    match action {
+        BrightnessAction::Raise(step) => current.saturating_add(units(step)).min(max),
+        BrightnessAction::Lower(step) => {
+            let floor = (f64::from(max) * f64::from(min_percent) / 100.0).round() as u32;
+            // Already under the floor (set by another tool): stay there
+            current.saturating_sub(units(step)).max(floor.min(current))
+        }
+    }
+}
+
+/// Sensor for one backlight device.
+#[derive(Debug)]
+pub struct BacklightSensor {
+    name: String,
+    config: SensorConfig,
+    backlight: Backlight,
+    /// Percent per scroll step
+    step: u32,
+    /// Lowest brightness scrolling lowers to, in percent
+    min_percent: u32,
+}
+
+impl BacklightSensor {
+    /// Create a sensor for the backlight named `device`, or the preferred
+    /// one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backlight does not exist.
+    pub fn new(device: Option<&str>) -> Result<Self, SensorError> {
+        Ok(Self {
+            name: "backlight".to_string(),
+            config: SensorConfig::default(),
+            backlight: Backlight::find(device)?,
+            step: 5,
+            min_percent: 1,
+        })
+    }
+
+    /// Percent per scroll step, from the `step` setting.
+    pub fn step(&self) -> u32 {
+        self.step
+    }
+
+    /// Change the brightness of the backlight.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the brightness cannot be read or set.
+    pub fn apply(&self, action: BrightnessAction) -> Result<(), SensorError> {
+        let current = self.backlight.brightness()?;
+        let max = self.backlight.max_brightness();
+        self.backlight.set_brightness(target_brightness(current, max, action, self.min_percent))
+    }
+}
+
+impl Discoverable for BacklightSensor {
+    const KIND: &'static str = "backlights";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        Ok(Backlight::list()
+            .into_iter()
+            .map(|backlight| {
+                let mut item = DiscoveredItem::new(backlight.name.clone(), backlight.kind.clone())
+                    .with_detail("max", backlight.max_brightness.to_string());
+                if let Ok(percent) = backlight.percent() {
+                    item = item.with_detail("brightness", format!("{}%", percent));
+                }
+                item
+            })
+            .collect())
+    }
+}
+
+impl Sensor for BacklightSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let brightness = self.backlight.brightness()?;
+        let max = self.backlight.max_brightness();
+        let percent = to_percent(brightness, max);
+
+        let tooltip = [
+            format::key_value("Device", &self.backlight.name, &self.config),
+            format::key_value("Type", &self.backlight.kind, &self.config),
+            format::key_value("Brightness", &format!("{}% ({}/{})", percent, brightness, max), &self.config),
+        ]
+        .join("\n");
+
+        Ok(WaybarOutput {
+            text: format::with_icon_and_colors(&format!("{}%", percent), &self.config.icons.backlight, &self.config),
+            tooltip: Some(tooltip),
+            class: Some(self.config.theme.normal.clone()),
+            percentage: Some(percent.min(100) as u8),
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        if let Some(step) = config.get_custom("step").and_then(|v| v.as_u64()) {
+            self.step = step as u32;
+        }
+        if let Some(min_percent) = config.get_custom("min_percent").and_then(|v| v.as_u64()) {
+            self.min_percent = (min_percent as u32).min(100);
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        self.backlight.brightness().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backlight_listing_and_steps() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let device = |name: &str, kind: &str, max: &str| {
+            let path = root.join(name);
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("type"), format!("{}\n", kind)).unwrap();
+            fs::write(path.join("max_brightness"), format!("{}\n", max)).unwrap();
+        };
+        device("intel_backlight", "raw", "19393");
+        device("acpi_video0", "firmware", "100");
+        device("broken", "raw", "0");
+
+        let names: Vec<String> = Backlight::list_in(root).into_iter().map(|backlight| backlight.name).collect();
+        assert_eq!(names, ["acpi_video0", "intel_backlight"]);
+
+        assert_eq!(target_brightness(9697, 19393, BrightnessAction::Raise(5), 1), 10667);
+        assert_eq!(target_brightness(19000, 19393, BrightnessAction::Raise(5), 1), 19393);
+        assert_eq!(target_brightness(500, 19393, BrightnessAction::Lower(5), 1), 194);
+        // Few levels: a step still moves, the floor still holds
+        assert_eq!(target_brightness(3, 7, BrightnessAction::Lower(5), 0), 2);
+        assert_eq!(target_brightness(1, 7, BrightnessAction::Lower(5), 20), 1);
+    }
+}
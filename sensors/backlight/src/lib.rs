@@ -0,0 +1,9 @@
+//! Backlight sensor for waysensor-rs.
+//!
+//! Reports the brightness of a `/sys/class/backlight` device and changes it
+//! on scroll through logind, which lets the session owner do so without
+//! root.
+
+pub mod backlight;
+
+pub use backlight::{Backlight, BacklightSensor, BrightnessAction};
@@ -0,0 +1,231 @@
+//! waysensor-rs-backlight: display brightness binary for Waybar.
+//!
+//! Bind the actions to the module's scroll events, e.g.
+//! `"on-scroll-up": "waysensor-rs-backlight --raise"` and
+//! `"on-scroll-down": "waysensor-rs-backlight --lower"`.
+
+use clap::Parser;
+use waysensor_rs_core::{discovery, schedule, session_lock, sysroot, GlobalConfig, IconStyle, Sensor, SensorConfig};
+use waysensor_rs_backlight::{BacklightSensor, BrightnessAction};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+/// Command-line arguments for the backlight sensor.
+#[derive(Parser)]
+#[command(name = "waysensor-rs-backlight")]
+#[command(about = "Backlight brightness sensor for waysensor-rs")]
+#[command(version)]
+#[command(author)]
+struct Args {
+    /// Update interval in milliseconds (minimum 100ms)
+    #[arg(short, long, default_value = "1000", value_parser = validate_interval)]
+    interval: u64,
+
+    /// Backlight device, e.g. intel_backlight (default: the "device" setting, else the preferred one)
+    #[arg(short, long)]
+    device: Option<String>,
+
+    /// Raise the brightness by one step and exit
+    #[arg(long, conflicts_with = "lower")]
+    raise: bool,
+
+    /// Lower the brightness by one step and exit
+    #[arg(long)]
+    lower: bool,
+
+    /// Percent per step for --raise and --lower (default: the "step" setting, 5)
+    #[arg(long)]
+    step: Option<u32>,
+
+    /// One-shot mode (output once and exit)
+    #[arg(short, long)]
+    once: bool,
+
+    /// List backlight devices and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Icon style (nerdfont, fontawesome, ascii, none)
+    #[arg(long)]
+    icon_style: Option<IconStyle>,
+
+    /// Icon color (hex format like "#7aa2f7")
+    #[arg(long)]
+    icon_color: Option<String>,
+
+    /// Text color (hex format like "#c0caf5")
+    #[arg(long)]
+    text_color: Option<String>,
+
+    /// Tooltip label color (hex format like "#bb9af7")
+    #[arg(long)]
+    tooltip_label_color: Option<String>,
+
+    /// Tooltip value color (hex format like "#9ece6a")
+    #[arg(long)]
+    tooltip_value_color: Option<String>,
+
+    /// Verify the backlight is readable and exit
+    #[arg(long)]
+    check: bool,
+
+    /// Read /proc, /sys and other system paths below this directory (for testing)
+    #[arg(long, value_name = "DIR")]
+    sys_root: Option<PathBuf>,
+
+    /// Generate example config file and exit
+    #[arg(long)]
+    generate_config: bool,
+}
+
+/// Validate that the interval is at least 100ms.
+fn validate_interval(s: &str) -> Result<u64, String> {
+    let interval = s.parse::<u64>()
+        .map_err(|_| "Interval must be a positive integer".to_owned())?;
+    
+    if interval < SensorConfig::MIN_UPDATE_INTERVAL {
+        return Err(format!(
+            "Interval must be at least {}ms", 
+            SensorConfig::MIN_UPDATE_INTERVAL
+        ));
+    }
+    
+    Ok(interval)
+}
+
+/// Main entry point for the backlight sensor.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    if let Some(root) = &args.sys_root {
+        sysroot::set(root);
+    }
+    
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<BacklightSensor>(args.json) {
+            eprintln!("Error listing backlights: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Handle config generation
+    if args.generate_config {
+        if let Some(config_path) = GlobalConfig::default_config_path() {
+            GlobalConfig::save_example_config_to_file(&config_path)?;
+            println!("Generated example config at: {}", config_path.display());
+            println!("\nYou can now edit this file to customize your default colors and settings.");
+        } else {
+            eprintln!("Could not determine config directory");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Load global configuration and apply command line overrides
+    let global_config = GlobalConfig::load().unwrap_or_default();
+    let mut config = global_config.to_sensor_config_for("backlight")
+        .with_update_interval(Duration::from_millis(args.interval))
+        .apply_color_overrides(
+            args.icon_color,
+            args.text_color,
+            args.tooltip_label_color,
+            args.tooltip_value_color,
+        );
+    
+    // Override icon style only if explicitly provided
+    if let Some(icon_style) = args.icon_style {
+        config = config.with_icon_style(icon_style);
+    }
+    
+    // The device from the command line, else from the config
+    let device = args.device.or_else(|| {
+        config.get_custom("device").and_then(|v| v.as_str()).map(str::to_string)
+    });
+    let mut backlight_sensor = match BacklightSensor::new(device.as_deref()) {
+        Ok(sensor) => sensor,
+        Err(e) => {
+            eprintln!("Failed to create backlight sensor: {}", e);
+            process::exit(1);
+        }
+    };
+    
+    // Brightness changes from scroll bindings
+    if args.raise || args.lower {
+        backlight_sensor.configure(config)?;
+        let step = args.step.unwrap_or_else(|| backlight_sensor.step());
+        let action = if args.raise { BrightnessAction::Raise(step) } else { BrightnessAction::Lower(step) };
+        if let Err(e) = backlight_sensor.apply(action) {
+            eprintln!("Error changing the brightness: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Check availability if requested
+    if args.check {
+        match backlight_sensor.check_availability() {
+            Ok(()) => {
+                println!("Backlight sensor is available");
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Backlight sensor is not available: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+    
+    backlight_sensor.configure(config)?;
+    
+    if args.once {
+        // One-shot mode: read once and exit
+        match backlight_sensor.read_styled() {
+            Ok(output) => {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+            Err(e) => {
+                eprintln!("Error reading backlight: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        // Continuous mode: loop and output readings
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
+        
+        loop {
+            interval.tick().await;
+            if session_lock::wait_async(backlight_sensor.config()).await {
+                interval.reset();
+            }
+            
+            match backlight_sensor.read_styled() {
+                Ok(output) => {
+                    println!("{}", serde_json::to_string(&output)?);
+                    io::stdout().flush()?;
+                }
+                Err(e) => {
+                    eprintln!("Error reading backlight: {}", e);
+                    // Continue running on errors, just log them
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
\ No newline at end of file
@@ -1,6 +1,6 @@
-use waysensor_rs_core::{Sensor, SensorConfig, SensorError, WaybarOutput};
+use waysensor_rs_core::{sysroot, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 
 #[derive(Debug)]
 pub struct BatterySensor {
@@ -129,7 +129,7 @@ impl BatterySensor {
         critical_threshold: u8
     ) -> Result<Self, SensorError> {
         let battery_path = if let Some(name) = battery_name {
-            PathBuf::from("/sys/class/power_supply").join(&name)
+            sysroot::path("/sys/class/power_supply").join(&name)
         } else {
             Self::find_battery()?
         };
@@ -176,7 +176,7 @@ impl BatterySensor {
     }
 
     fn find_battery() -> Result<PathBuf, SensorError> {
-        let power_supply_dir = Path::new("/sys/class/power_supply");
+        let power_supply_dir = sysroot::path("/sys/class/power_supply");
         
         if !power_supply_dir.exists() {
             return Err(SensorError::Unavailable {
@@ -412,7 +412,7 @@ impl BatterySensor {
     }
 
     pub fn list_available_batteries() -> Result<Vec<String>, SensorError> {
-        let power_supply_dir = Path::new("/sys/class/power_supply");
+        let power_supply_dir = sysroot::path("/sys/class/power_supply");
         
         if !power_supply_dir.exists() {
             return Ok(Vec::new());
@@ -443,6 +443,45 @@ impl BatterySensor {
     }
 }
 
+impl Discoverable for BatterySensor {
+    const KIND: &'static str = "batteries";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        let power_supply_dir = sysroot::path("/sys/class/power_supply");
+        let read = |name: &str, file: &str| {
+            fs::read_to_string(power_supply_dir.join(name).join(file))
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+
+        Ok(Self::list_available_batteries()?
+            .into_iter()
+            .map(|name| {
+                let label = [read(&name, "manufacturer"), read(&name, "model_name")]
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                let mut item = DiscoveredItem::new(
+                    name.clone(),
+                    if label.is_empty() { "Battery".to_string() } else { label },
+                );
+                if let Some(capacity) = read(&name, "capacity") {
+                    item = item.with_detail("capacity", format!("{}%", capacity));
+                }
+                if let Some(status) = read(&name, "status") {
+                    item = item.with_detail("status", status);
+                }
+                if let Some(technology) = read(&name, "technology") {
+                    item = item.with_detail("technology", technology);
+                }
+                item
+            })
+            .collect())
+    }
+}
+
 impl Sensor for BatterySensor {
     type Error = SensorError;
 
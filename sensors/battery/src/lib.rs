@@ -1,7 +1,9 @@
 pub mod battery;
 pub mod error;
+pub mod suspend;
 pub mod types;
 
 pub use battery::BatterySensor;
 pub use error::BatteryError;
+pub use suspend::{SleepReadiness, SleepSensor, SleepSupport};
 pub use types::{BatteryInfo, BatteryState};
\ No newline at end of file
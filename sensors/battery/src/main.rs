@@ -1,10 +1,10 @@
 use clap::Parser;
-use waysensor_rs_core::{GlobalConfig, Sensor, IconStyle};
+use waysensor_rs_core::{discovery, schedule, session_lock, sysroot, GlobalConfig, Sensor, SensorError, IconStyle};
 use std::io::{self, Write};
+use std::path::PathBuf;
 use std::time::Duration;
-use tokio::time;
 
-use waysensor_rs_battery::BatterySensor;
+use waysensor_rs_battery::{BatterySensor, SleepSensor};
 
 #[derive(Parser)]
 #[command(name = "waysensor-rs-battery")]
@@ -35,6 +35,22 @@ struct Args {
     #[arg(short, long)]
     list: bool,
 
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Report whether suspend and hibernate would currently succeed instead of battery charge
+    #[arg(long)]
+    sleep: bool,
+
+    /// With --sleep, warn when the machine cannot hibernate
+    #[arg(long, requires = "sleep")]
+    require_hibernate: bool,
+
     /// Icon style (nerdfont, fontawesome, ascii, none)
     #[arg(long)]
     icon_style: Option<IconStyle>,
@@ -59,6 +75,10 @@ struct Args {
     #[arg(long)]
     check: bool,
 
+    /// Read /proc, /sys and other system paths below this directory (for testing)
+    #[arg(long, value_name = "DIR")]
+    sys_root: Option<PathBuf>,
+
     /// Generate example config file and exit
     #[arg(long)]
     generate_config: bool,
@@ -67,6 +87,14 @@ struct Args {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    if let Some(root) = &args.sys_root {
+        sysroot::set(root);
+    }
+    
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
     
     // Handle config generation
     if args.generate_config {
@@ -83,55 +111,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Handle list command
     if args.list {
-        match BatterySensor::list_available_batteries() {
-            Ok(batteries) => {
-                if batteries.is_empty() {
-                    println!("No batteries found");
-                } else {
-                    println!("Available batteries:");
-                    for battery in batteries {
-                        println!("  {}", battery);
-                    }
-                }
-            }
-            Err(e) => {
-                eprintln!("Error listing batteries: {}", e);
-                std::process::exit(1);
-            }
+        if let Err(e) = discovery::print::<BatterySensor>(args.json) {
+            eprintln!("Error listing batteries: {}", e);
+            std::process::exit(1);
         }
         return Ok(());
     }
 
     // Validate thresholds
-    if args.warning <= args.critical {
+    if !args.sleep && args.warning <= args.critical {
         eprintln!("Warning threshold must be greater than critical threshold");
         std::process::exit(1);
     }
 
-    if args.critical == 0 || args.warning >= 100 {
+    if !args.sleep && (args.critical == 0 || args.warning >= 100) {
         eprintln!("Thresholds must be between 1-99%, with warning > critical");
         std::process::exit(1);
     }
 
     // Create battery sensor
-    let mut battery_sensor = match BatterySensor::new(args.battery.clone(), args.warning, args.critical) {
-        Ok(sensor) => sensor,
-        Err(e) => {
-            eprintln!("Error initializing battery sensor: {}", e);
+    let mut battery_sensor: Box<dyn Sensor<Error = SensorError>> = if args.sleep {
+        Box::new(SleepSensor::new(args.require_hibernate))
+    } else {
+        match BatterySensor::new(args.battery.clone(), args.warning, args.critical) {
+            Ok(sensor) => Box::new(sensor),
+            Err(e) => {
+                eprintln!("Error initializing battery sensor: {}", e);
             
-            // If no specific battery was requested, show available options
-            if args.battery.is_none() {
-                if let Ok(batteries) = BatterySensor::list_available_batteries() {
-                    if !batteries.is_empty() {
-                        eprintln!("Available batteries:");
-                        for battery in batteries {
-                            eprintln!("  {}", battery);
+                // If no specific battery was requested, show available options
+                if args.battery.is_none() {
+                    if let Ok(batteries) = BatterySensor::list_available_batteries() {
+                        if !batteries.is_empty() {
+                            eprintln!("Available batteries:");
+                            for battery in batteries {
+                                eprintln!("  {}", battery);
+                            }
+                            eprintln!("Try specifying a battery with --battery <name>");
                         }
-                        eprintln!("Try specifying a battery with --battery <name>");
                     }
                 }
+                std::process::exit(1);
             }
-            std::process::exit(1);
         }
     };
     
@@ -151,7 +171,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Load global configuration and apply command line overrides
     let global_config = GlobalConfig::load().unwrap_or_default();
-    let mut config = global_config.to_sensor_config()
+    let mut config = global_config.to_sensor_config_for("battery")
         .with_update_interval(Duration::from_millis(args.interval))
         .apply_color_overrides(
             args.icon_color,
@@ -165,10 +185,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config = config.with_icon_style(icon_style);
     }
     
+    // Load sensor-specific configuration from global config
+    if args.sleep {
+        if let Some(serde_json::Value::Object(map)) = global_config.sensors.get("sleep") {
+            for (key, value) in map {
+                config = config.with_custom(key.clone(), value.clone());
+            }
+        }
+    }
+    
     battery_sensor.configure(config)?;
     
     if args.once {
-        match battery_sensor.read() {
+        match battery_sensor.read_styled() {
             Ok(output) => {
                 println!("{}", serde_json::to_string(&output)?);
             }
@@ -178,12 +207,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
     } else {
-        let mut interval = time::interval(Duration::from_millis(args.interval));
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
         
         loop {
             interval.tick().await;
+            if session_lock::wait_async(battery_sensor.config()).await {
+                interval.reset();
+            }
             
-            match battery_sensor.read() {
+            match battery_sensor.read_styled() {
                 Ok(output) => {
                     println!("{}", serde_json::to_string(&output)?);
                     io::stdout().flush()?;
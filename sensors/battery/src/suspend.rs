@@ -0,0 +1,424 @@
+//! Suspend and hibernate readiness.
+//!
+//! Checks what the kernel supports (`/sys/power/state`), whether a hibernation
+//! image would fit in disk-backed swap and can be resumed from, and which
+//! logind inhibitors currently block sleep.
+
+use waysensor_rs_core::{format, procfs, sysroot, Sensor, SensorConfig, SensorError, WaybarOutput};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// How long the inhibitor list from logind is reused before asking again.
+const INHIBITOR_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Sleep states and hibernation prerequisites reported by the kernel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SleepSupport {
+    /// Supported states from `/sys/power/state` (`freeze`, `mem`, `disk`)
+    pub states: Vec<String>,
+    /// Selected suspend variant from `/sys/power/mem_sleep` (`s2idle`, `deep`)
+    pub mem_sleep: Option<String>,
+    /// Configured resume device (`major:minor`), if any
+    pub resume_device: Option<String>,
+    /// Active kernel lockdown mode, which disables hibernation
+    pub lockdown: Option<String>,
+}
+
+impl SleepSupport {
+    /// Read the running kernel's sleep support.
+    pub fn read() -> Self {
+        Self::read_from(&sysroot::path("/sys/power"), &sysroot::path("/sys/kernel/security/lockdown"))
+    }
+
+    /// Read sleep support from an explicit sysfs power directory (useful for testing).
+    pub fn read_from(power_dir: &Path, lockdown_path: &Path) -> Self {
+        let read = |path: &Path| fs::read_to_string(path).ok();
+
+        Self {
+            states: read(&power_dir.join("state"))
+                .map(|s| s.split_whitespace().map(str::to_owned).collect())
+                .unwrap_or_default(),
+            mem_sleep: read(&power_dir.join("mem_sleep")).and_then(|s| procfs::selected_option(&s)),
+            resume_device: read(&power_dir.join("resume"))
+                .map(|s| s.trim().to_owned())
+                .filter(|dev| !dev.is_empty() && dev != "0:0"),
+            lockdown: read(lockdown_path)
+                .and_then(|s| procfs::selected_option(&s))
+                .filter(|mode| mode != "none"),
+        }
+    }
+
+    /// Whether the kernel can suspend to RAM or idle.
+    pub fn can_suspend(&self) -> bool {
+        self.states.iter().any(|s| s == "mem" || s == "freeze")
+    }
+
+    /// Whether the kernel was built with hibernation support.
+    pub fn can_hibernate(&self) -> bool {
+        self.states.iter().any(|s| s == "disk")
+    }
+}
+
+/// A logind inhibitor lock.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inhibitor {
+    /// Inhibited operations (`sleep`, `handle-lid-switch`, `idle`, ...)
+    pub what: Vec<String>,
+    /// Application holding the lock
+    pub who: String,
+    /// Reason given by the application
+    pub why: String,
+    /// `block` or `delay`
+    pub mode: String,
+}
+
+impl Inhibitor {
+    /// Whether this lock stops the machine from sleeping.
+    ///
+    /// `handle-lid-switch` locks don't count: desktops hold them to handle
+    /// the lid themselves (GNOME Shell with an external monitor, KDE
+    /// powerdevil always), and an explicit suspend still works.
+    pub fn blocks_sleep(&self) -> bool {
+        self.mode == "block" && self.what.iter().any(|w| w == "sleep")
+    }
+}
+
+/// Current logind inhibitors, or `None` if logind cannot be queried.
+pub fn list_inhibitors() -> Option<Vec<Inhibitor>> {
+    let output = Command::new("busctl")
+        .args([
+            "--system", "--json=short", "call",
+            "org.freedesktop.login1", "/org/freedesktop/login1",
+            "org.freedesktop.login1.Manager", "ListInhibitors",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    parse_inhibitors(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parse the `busctl --json` reply of `ListInhibitors` (`a(ssssuu)`).
+pub fn parse_inhibitors(json: &str) -> Option<Vec<Inhibitor>> {
+    let reply: serde_json::Value = serde_json::from_str(json).ok()?;
+    let entries = reply.get("data")?.get(0)?.as_array()?;
+
+    Some(entries.iter()
+        .filter_map(|entry| {
+            let field = |i: usize| entry.get(i).and_then(|v| v.as_str()).map(str::to_owned);
+            Some(Inhibitor {
+                what: field(0)?.split(':').map(str::to_owned).collect(),
+                who: field(1)?,
+                why: field(2)?,
+                mode: field(3)?,
+            })
+        })
+        .collect())
+}
+
+/// Size and usage, in bytes, of swap that survives power-off (everything but zram).
+///
+/// Takes the contents of `/proc/swaps`, whose sizes are in KiB.
+pub fn disk_swap(swaps: &str) -> (u64, u64) {
+    swaps.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [name, _kind, size, used, ..] = fields[..] else {
+                return None;
+            };
+            if name.starts_with("/dev/zram") {
+                return None;
+            }
+            Some((size.parse::<u64>().ok()? * 1024, used.parse::<u64>().ok()? * 1024))
+        })
+        .fold((0, 0), |(size, used), (s, u)| (size + s, used + u))
+}
+
+/// Memory that would have to be written to the hibernation image, in bytes
+/// (`MemTotal - MemAvailable` from `/proc/meminfo`).
+pub fn memory_in_use(meminfo: &str) -> Option<u64> {
+    let field = |key: &str| {
+        meminfo.lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix(':'))
+            .and_then(|rest| rest.split_whitespace().next()?.parse::<u64>().ok())
+            .map(|kib| kib * 1024)
+    };
+    Some(field("MemTotal")?.saturating_sub(field("MemAvailable")?))
+}
+
+/// Whether suspend and hibernate would currently succeed, with the reasons
+/// they would not.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SleepReadiness {
+    /// Reasons suspend would fail or not happen; empty when ready
+    pub suspend_blockers: Vec<String>,
+    /// Reasons hibernation would fail or not happen; empty when ready
+    pub hibernate_blockers: Vec<String>,
+}
+
+impl SleepReadiness {
+    /// Combine kernel support, swap capacity and inhibitors into a verdict.
+    pub fn assess(
+        support: &SleepSupport,
+        inhibitors: &[Inhibitor],
+        swap_free: u64,
+        memory_in_use: u64,
+    ) -> Self {
+        let inhibited: Vec<String> = inhibitors.iter()
+            .filter(|i| i.blocks_sleep())
+            .map(|i| format!("inhibited by {}", i.who))
+            .collect();
+
+        let mut suspend_blockers = Vec::new();
+        if !support.can_suspend() {
+            suspend_blockers.push("not supported by the kernel".to_owned());
+        }
+        suspend_blockers.extend(inhibited.iter().cloned());
+
+        let mut hibernate_blockers = Vec::new();
+        if !support.can_hibernate() {
+            hibernate_blockers.push("not supported by the kernel".to_owned());
+        } else {
+            if let Some(mode) = &support.lockdown {
+                hibernate_blockers.push(format!("disabled by kernel lockdown ({})", mode));
+            }
+            if support.resume_device.is_none() {
+                hibernate_blockers.push("no resume device configured".to_owned());
+            }
+            if swap_free < memory_in_use {
+                hibernate_blockers.push(format!(
+                    "swap too small ({} free, {} needed)",
+                    format::bytes_to_human(swap_free),
+                    format::bytes_to_human(memory_in_use)
+                ));
+            }
+        }
+        hibernate_blockers.extend(inhibited);
+
+        Self { suspend_blockers, hibernate_blockers }
+    }
+
+    pub fn suspend_ready(&self) -> bool {
+        self.suspend_blockers.is_empty()
+    }
+
+    pub fn hibernate_ready(&self) -> bool {
+        self.hibernate_blockers.is_empty()
+    }
+}
+
+/// Sensor reporting whether the machine would currently suspend and hibernate.
+#[derive(Debug)]
+pub struct SleepSensor {
+    name: String,
+    config: SensorConfig,
+    require_hibernate: bool,
+    /// Last inhibitor list with the time it was fetched
+    inhibitors: Option<(Instant, Option<Vec<Inhibitor>>)>,
+}
+
+impl SleepSensor {
+    /// Create a sleep readiness sensor. With `require_hibernate`, a machine
+    /// that cannot hibernate is reported as a warning instead of normal.
+    pub fn new(require_hibernate: bool) -> Self {
+        Self {
+            name: "sleep".to_string(),
+            config: SensorConfig::default(),
+            require_hibernate,
+            inhibitors: None,
+        }
+    }
+
+    /// Current inhibitors, asking logind at most every [`INHIBITOR_INTERVAL`].
+    fn inhibitors(&mut self) -> Option<Vec<Inhibitor>> {
+        match &self.inhibitors {
+            Some((fetched, inhibitors)) if fetched.elapsed() < INHIBITOR_INTERVAL => inhibitors.clone(),
+            _ => {
+                let inhibitors = list_inhibitors();
+                self.inhibitors = Some((Instant::now(), inhibitors.clone()));
+                inhibitors
+            }
+        }
+    }
+
+    fn status_line(&self, label: &str, blockers: &[String], detail: Option<&str>) -> String {
+        let value = if blockers.is_empty() {
+            match detail {
+                Some(detail) => format!("✓ ready ({})", detail),
+                None => "✓ ready".to_string(),
+            }
+        } else {
+            format!("✗ {}", blockers.join(", "))
+        };
+        format::key_value(label, &value, &self.config)
+    }
+}
+
+impl Sensor for SleepSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let support = SleepSupport::read();
+        let inhibitors = self.inhibitors();
+        let (swap_total, swap_used) = procfs::read_with(sysroot::path("/proc/swaps"), disk_swap).unwrap_or_default();
+        let swap_free = swap_total.saturating_sub(swap_used);
+        let memory_in_use = procfs::read_with(sysroot::path("/proc/meminfo"), memory_in_use)?.unwrap_or(0);
+
+        let readiness = SleepReadiness::assess(
+            &support,
+            inhibitors.as_deref().unwrap_or_default(),
+            swap_free,
+            memory_in_use,
+        );
+
+        let (status, class) = match (readiness.suspend_ready(), readiness.hibernate_ready()) {
+            (false, _) => ("blocked", &self.config.theme.critical),
+            (true, false) if self.require_hibernate => ("no hibernate", &self.config.theme.warning),
+            (true, false) => ("suspend only", &self.config.theme.normal),
+            (true, true) => ("ready", &self.config.theme.good),
+        };
+        let text = format::with_icon_and_colors(status, &self.config.icons.sleep, &self.config);
+
+        let mut lines = vec![
+            format::key_only("Sleep Readiness", &self.config),
+            self.status_line("Suspend", &readiness.suspend_blockers, support.mem_sleep.as_deref()),
+            self.status_line("Hibernate", &readiness.hibernate_blockers, None),
+            format::key_value("Swap (disk)", &format!("{} free of {}, {} in use",
+                format::bytes_to_human(swap_free),
+                format::bytes_to_human(swap_total),
+                format::bytes_to_human(memory_in_use)), &self.config),
+        ];
+        if let Some(device) = &support.resume_device {
+            lines.push(format::key_value("Resume Device", device, &self.config));
+        }
+
+        match &inhibitors {
+            Some(inhibitors) => {
+                let blocking: Vec<&Inhibitor> = inhibitors.iter().filter(|i| i.blocks_sleep()).collect();
+                if !blocking.is_empty() {
+                    lines.push(String::new());
+                    lines.push(format::key_only("Inhibitors", &self.config));
+                    for inhibitor in blocking {
+                        lines.push(format::key_value(
+                            &inhibitor.who,
+                            &format!("{} ({})", inhibitor.why, inhibitor.what.join(", ")),
+                            &self.config,
+                        ));
+                    }
+                }
+            }
+            None => lines.push(format::key_value("Inhibitors", "unknown (logind unavailable)", &self.config)),
+        }
+
+        Ok(WaybarOutput {
+            text,
+            tooltip: Some(lines.join("\n")),
+            class: Some(class.clone()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        if let Some(require) = config.get_custom("require_hibernate").and_then(|v| v.as_bool()) {
+            self.require_hibernate = require;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        if !sysroot::path("/sys/power/state").exists() {
+            return Err(SensorError::unavailable("/sys/power/state does not exist"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sleep_support_parsing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        fs::write(dir.join("state"), "freeze mem disk\n").unwrap();
+        fs::write(dir.join("mem_sleep"), "s2idle [deep]\n").unwrap();
+        fs::write(dir.join("resume"), "0:0\n").unwrap();
+        fs::write(dir.join("lockdown"), "none [integrity] confidentiality\n").unwrap();
+
+        let support = SleepSupport::read_from(dir, &dir.join("lockdown"));
+
+        assert!(support.can_suspend());
+        assert!(support.can_hibernate());
+        assert_eq!(support.mem_sleep.as_deref(), Some("deep"));
+        assert_eq!(support.resume_device, None);
+        assert_eq!(support.lockdown.as_deref(), Some("integrity"));
+    }
+
+    #[test]
+    fn test_inhibitor_parsing() {
+        let json = r#"{"type":"a(ssssuu)","data":[[["sleep","NetworkManager","NetworkManager needs to turn off networks","delay",0,812],["handle-power-key:handle-suspend-key:handle-lid-switch","GNOME Shell","Lid switch handled by desktop","block",1000,1702],["sleep:idle","Firefox","Playing video","block",1000,4242],["idle","mpv","Playing video","block",1000,5000]]]}"#;
+        let inhibitors = parse_inhibitors(json).unwrap();
+        assert_eq!(inhibitors.len(), 4);
+        let blocking: Vec<&str> = inhibitors.iter()
+            .filter(|i| i.blocks_sleep())
+            .map(|i| i.who.as_str())
+            .collect();
+        assert_eq!(blocking, vec!["Firefox"]);
+        assert!(parse_inhibitors("not json").is_none());
+    }
+
+    #[test]
+    fn test_readiness_assessment() {
+        let swaps = "Filename\tType\tSize\tUsed\tPriority\n\
+            /dev/zram0 partition 8388604 1048576 100\n\
+            /dev/nvme0n1p3 partition 4194300 524288 -2\n";
+        assert_eq!(disk_swap(swaps), (4_194_300 * 1024, 524_288 * 1024));
+        assert_eq!(
+            memory_in_use("MemTotal: 16000000 kB\nMemFree: 1000 kB\nMemAvailable: 10000000 kB\n"),
+            Some(6_000_000 * 1024)
+        );
+
+        let support = SleepSupport {
+            states: vec!["freeze".into(), "mem".into(), "disk".into()],
+            mem_sleep: Some("s2idle".into()),
+            resume_device: Some("259:3".into()),
+            lockdown: None,
+        };
+        let gib = 1024 * 1024 * 1024;
+        let ready = SleepReadiness::assess(&support, &[], 8 * gib, 6 * gib);
+        assert!(ready.suspend_ready() && ready.hibernate_ready());
+
+        let small_swap = SleepReadiness::assess(&support, &[], 2 * gib, 6 * gib);
+        assert!(small_swap.suspend_ready());
+        assert_eq!(small_swap.hibernate_blockers.len(), 1);
+        assert!(small_swap.hibernate_blockers[0].starts_with("swap too small"));
+
+        let lid = Inhibitor {
+            what: vec!["handle-lid-switch".into()],
+            who: "GNOME Shell".into(),
+            why: "".into(),
+            mode: "block".into(),
+        };
+        let sleep = Inhibitor {
+            what: vec!["sleep".into()],
+            who: "Firefox".into(),
+            why: "Playing video".into(),
+            mode: "block".into(),
+        };
+        let inhibited = SleepReadiness::assess(&support, &[lid, sleep], 8 * gib, 6 * gib);
+        assert_eq!(inhibited.suspend_blockers, vec!["inhibited by Firefox".to_owned()]);
+        assert!(!inhibited.hibernate_ready());
+    }
+}
@@ -0,0 +1,308 @@
+//! Bluetooth adapter power and connected devices from BlueZ.
+//!
+//! BlueZ publishes every adapter (`org.bluez.Adapter1`) and every known
+//! device (`org.bluez.Device1`) as a D-Bus object, all returned at once by
+//! its object manager. Devices that report a charge level carry an
+//! `org.bluez.Battery1` interface as well. The bus is reached through
+//! `busctl`, as the battery sensor does for logind.
+
+use serde_json::Value;
+use std::process::Command;
+use waysensor_rs_core::{format, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+/// CSS class of a powered-off adapter.
+pub const OFF_CLASS: &str = "off";
+/// CSS class while at least one device is connected.
+pub const CONNECTED_CLASS: &str = "connected";
+
+/// Run `busctl` on the system bus against BlueZ and return its output.
+fn busctl(args: &[&str]) -> Result<String, SensorError> {
+    let output = Command::new("busctl")
+        .arg("--system")
+        .args(args)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => SensorError::unavailable("busctl not installed"),
+            _ => SensorError::Io(e),
+        })?;
+    if !output.status.success() {
+        return Err(SensorError::unavailable(format!(
+            "BlueZ did not answer: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// A Bluetooth controller such as `hci0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Adapter {
+    /// Object path, e.g. `/org/bluez/hci0`
+    pub path: String,
+    /// Name other devices see, e.g. `thinkpad`
+    pub alias: String,
+    pub address: String,
+    pub powered: bool,
+}
+
+impl Adapter {
+    /// Kernel name of the adapter, e.g. `hci0`.
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+/// A device paired with or seen by an adapter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Device {
+    /// Object path of the adapter the device belongs to
+    pub adapter: String,
+    pub name: String,
+    pub address: String,
+    /// freedesktop icon name BlueZ derives from the device class, e.g. `audio-headset`
+    pub icon: Option<String>,
+    pub connected: bool,
+    /// Charge level in percent
+    pub battery: Option<u8>,
+}
+
+/// Parse the `busctl --json` reply of `GetManagedObjects`
+/// (`a{oa{sa{sv}}}`), adapters and devices sorted by path.
+fn parse_managed_objects(json: &str) -> Result<(Vec<Adapter>, Vec<Device>), SensorError> {
+    let reply: Value = serde_json::from_str(json)
+        .map_err(|e| SensorError::parse_with_source("Invalid GetManagedObjects reply", e))?;
+    let objects = reply["data"][0].as_object()
+        .ok_or_else(|| SensorError::parse("Unexpected GetManagedObjects reply"))?;
+
+    let mut adapters = Vec::new();
+    let mut devices = Vec::new();
+    for (path, interfaces) in objects {
+        // Variants come as {"type": "s", "data": ...}
+        let property = |interface: &str, name: &str| &interfaces[interface][name]["data"];
+        let string = |interface: &str, name: &str| property(interface, name).as_str().map(str::to_string);
+
+        if interfaces.get("org.bluez.Adapter1").is_some() {
+            adapters.push(Adapter {
+                path: path.clone(),
+                alias: string("org.bluez.Adapter1", "Alias").unwrap_or_default(),
+                address: string("org.bluez.Adapter1", "Address").unwrap_or_default(),
+                powered: property("org.bluez.Adapter1", "Powered") == true,
+            });
+        } else if interfaces.get("org.bluez.Device1").is_some() {
+            let address = string("org.bluez.Device1", "Address").unwrap_or_default();
+            devices.push(Device {
+                adapter: string("org.bluez.Device1", "Adapter").unwrap_or_default(),
+                name: string("org.bluez.Device1", "Alias")
+                    .or_else(|| string("org.bluez.Device1", "Name"))
+                    .unwrap_or_else(|| address.clone()),
+                address,
+                icon: string("org.bluez.Device1", "Icon"),
+                connected: property("org.bluez.Device1", "Connected") == true,
+                battery: property("org.bluez.Battery1", "Percentage").as_u64().map(|percent| percent.min(100) as u8),
+            });
+        }
+    }
+    adapters.sort_by(|a, b| a.path.cmp(&b.path));
+    devices.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok((adapters, devices))
+}
+
+/// Every adapter and device BlueZ knows.
+fn managed_objects() -> Result<(Vec<Adapter>, Vec<Device>), SensorError> {
+    parse_managed_objects(&busctl(&[
+        "--json=short", "call",
+        "org.bluez", "/",
+        "org.freedesktop.DBus.ObjectManager", "GetManagedObjects",
+    ])?)
+}
+
+/// Sensor for one Bluetooth adapter and its connected devices.
+#[derive(Debug)]
+pub struct BluetoothSensor {
+    name: String,
+    config: SensorConfig,
+    /// Object path of the adapter
+    adapter: String,
+}
+
+impl BluetoothSensor {
+    /// Create a sensor for the adapter named `adapter` (`hci0`), or the
+    /// first one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if BlueZ does not answer or has no such adapter.
+    pub fn new(adapter: Option<&str>) -> Result<Self, SensorError> {
+        let (adapters, _) = managed_objects()?;
+        let found = match adapter {
+            Some(name) => adapters.into_iter().find(|adapter| adapter.name() == name)
+                .ok_or_else(|| SensorError::unavailable(format!("No Bluetooth adapter named {}", name)))?,
+            None => adapters.into_iter().next()
+                .ok_or_else(|| SensorError::unavailable("No Bluetooth adapter found"))?,
+        };
+        Ok(Self {
+            name: "bluetooth".to_string(),
+            config: SensorConfig::default(),
+            adapter: found.path,
+        })
+    }
+
+    /// The adapter and its connected devices.
+    fn state(&self) -> Result<(Adapter, Vec<Device>), SensorError> {
+        let (adapters, devices) = managed_objects()?;
+        let adapter = adapters.into_iter().find(|adapter| adapter.path == self.adapter)
+            .ok_or_else(|| SensorError::unavailable(format!("Bluetooth adapter {} is gone", self.adapter)))?;
+        let connected = devices.into_iter()
+            .filter(|device| device.adapter == self.adapter && device.connected)
+            .collect();
+        Ok((adapter, connected))
+    }
+
+    /// Turn the adapter on if it is off, off if it is on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if BlueZ refuses, e.g. while rfkill blocks the
+    /// adapter.
+    pub fn toggle_power(&self) -> Result<(), SensorError> {
+        let (adapter, _) = self.state()?;
+        let powered = if adapter.powered { "false" } else { "true" };
+        busctl(&["set-property", "org.bluez", &self.adapter, "org.bluez.Adapter1", "Powered", "b", powered])
+            .map(|_| ())
+    }
+
+    fn build_tooltip(&self, adapter: &Adapter, devices: &[Device]) -> String {
+        let mut lines = vec![
+            format::key_value("Adapter", &format!("{} ({})", adapter.alias, adapter.name()), &self.config),
+            format::key_value("Address", &adapter.address, &self.config),
+            format::key_value("Power", if adapter.powered { "on" } else { "off" }, &self.config),
+        ];
+        if !devices.is_empty() {
+            lines.push(format::key_only("Connected", &self.config));
+            for device in devices {
+                let value = match device.battery {
+                    Some(battery) => format!("{}% battery", battery),
+                    None => device.icon.clone().unwrap_or_else(|| device.address.clone()),
+                };
+                lines.push(format::key_value(&device.name, &value, &self.config));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl Discoverable for BluetoothSensor {
+    const KIND: &'static str = "Bluetooth adapters";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        // No BlueZ means nothing to list, not a failure
+        let Ok((adapters, devices)) = managed_objects() else {
+            return Ok(Vec::new());
+        };
+        Ok(adapters
+            .into_iter()
+            .map(|adapter| {
+                let connected = devices.iter()
+                    .filter(|device| device.adapter == adapter.path && device.connected)
+                    .count();
+                DiscoveredItem::new(adapter.name().to_string(), adapter.alias.clone())
+                    .with_detail("address", adapter.address.clone())
+                    .with_detail("powered", if adapter.powered { "yes" } else { "no" })
+                    .with_detail("connected", connected.to_string())
+            })
+            .collect())
+    }
+}
+
+impl Sensor for BluetoothSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let (adapter, devices) = self.state()?;
+        let show_battery = self.config.get_custom("show_battery").and_then(|v| v.as_bool()).unwrap_or(true);
+
+        let (status, icon, class) = match devices.as_slice() {
+            _ if !adapter.powered => ("off".to_string(), &self.config.icons.bluetooth_off, OFF_CLASS),
+            [] => ("on".to_string(), &self.config.icons.bluetooth, self.config.theme.normal.as_str()),
+            [device] => {
+                let status = match device.battery {
+                    Some(battery) if show_battery => format!("{} {}%", device.name, battery),
+                    _ => device.name.clone(),
+                };
+                (status, &self.config.icons.bluetooth_connected, CONNECTED_CLASS)
+            }
+            devices => (format!("{} devices", devices.len()), &self.config.icons.bluetooth_connected, CONNECTED_CLASS),
+        };
+
+        Ok(WaybarOutput {
+            text: format::with_icon_and_colors(&status, icon, &self.config),
+            tooltip: Some(self.build_tooltip(&adapter, &devices)),
+            class: Some(class.to_string()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        self.state().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_managed_objects_parsing() {
+        let reply = r#"{"type":"a{oa{sa{sv}}}","data":[{
+            "/org/bluez":{"org.bluez.AgentManager1":{}},
+            "/org/bluez/hci0":{"org.bluez.Adapter1":{
+                "Address":{"type":"s","data":"DC:21:48:AA:BB:CC"},
+                "Alias":{"type":"s","data":"thinkpad"},
+                "Powered":{"type":"b","data":true}}},
+            "/org/bluez/hci0/dev_00_1B_66_AA_BB_CC":{
+                "org.bluez.Device1":{
+                    "Address":{"type":"s","data":"00:1B:66:AA:BB:CC"},
+                    "Alias":{"type":"s","data":"WH-1000XM4"},
+                    "Icon":{"type":"s","data":"audio-headset"},
+                    "Adapter":{"type":"o","data":"/org/bluez/hci0"},
+                    "Connected":{"type":"b","data":true}},
+                "org.bluez.Battery1":{"Percentage":{"type":"y","data":70}}},
+            "/org/bluez/hci0/dev_F4_73_35_11_22_33":{"org.bluez.Device1":{
+                "Address":{"type":"s","data":"F4:73:35:11:22:33"},
+                "Adapter":{"type":"o","data":"/org/bluez/hci0"},
+                "Connected":{"type":"b","data":false}}}
+        }]}"#;
+        let (adapters, devices) = parse_managed_objects(reply).unwrap();
+        assert_eq!(adapters, [Adapter {
+            path: "/org/bluez/hci0".to_string(),
+            alias: "thinkpad".to_string(),
+            address: "DC:21:48:AA:BB:CC".to_string(),
+            powered: true,
+        }]);
+        assert_eq!(adapters[0].name(), "hci0");
+
+        assert_eq!(devices.len(), 2);
+        // Sorted by name; a device without one goes by its address
+        assert_eq!(devices[0].name, "F4:73:35:11:22:33");
+        assert!(!devices[0].connected);
+        assert_eq!(devices[1].name, "WH-1000XM4");
+        assert_eq!(devices[1].icon.as_deref(), Some("audio-headset"));
+        assert_eq!(devices[1].battery, Some(70));
+        assert!(devices[1].connected);
+
+        assert!(parse_managed_objects("{}").is_err());
+    }
+}
@@ -0,0 +1,9 @@
+//! Bluetooth sensor for waysensor-rs.
+//!
+//! Reports whether the adapter is powered and which devices are connected,
+//! with their battery levels, from BlueZ over D-Bus, and turns the adapter
+//! on or off on click.
+
+pub mod bluetooth;
+
+pub use bluetooth::{Adapter, BluetoothSensor, Device, CONNECTED_CLASS, OFF_CLASS};
@@ -0,0 +1,211 @@
+//! waysensor-rs-bluetooth: Bluetooth status binary for Waybar.
+//!
+//! Bind the power toggle to the module's click event, e.g.
+//! `"on-click": "waysensor-rs-bluetooth --toggle-power"`.
+
+use clap::Parser;
+use waysensor_rs_core::{discovery, schedule, session_lock, GlobalConfig, IconStyle, Sensor, SensorConfig};
+use waysensor_rs_bluetooth::BluetoothSensor;
+use std::io::{self, Write};
+use std::process;
+use std::time::Duration;
+
+/// Command-line arguments for the Bluetooth sensor.
+#[derive(Parser)]
+#[command(name = "waysensor-rs-bluetooth")]
+#[command(about = "Bluetooth sensor for waysensor-rs")]
+#[command(version)]
+#[command(author)]
+struct Args {
+    /// Update interval in milliseconds (minimum 100ms)
+    #[arg(short, long, default_value = "1000", value_parser = validate_interval)]
+    interval: u64,
+
+    /// Bluetooth adapter, e.g. hci1 (default: the "adapter" setting, else the first one)
+    #[arg(short, long)]
+    adapter: Option<String>,
+
+    /// Turn the adapter on or off and exit
+    #[arg(long)]
+    toggle_power: bool,
+
+    /// One-shot mode (output once and exit)
+    #[arg(short, long)]
+    once: bool,
+
+    /// List Bluetooth adapters and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Icon style (nerdfont, fontawesome, ascii, none)
+    #[arg(long)]
+    icon_style: Option<IconStyle>,
+
+    /// Icon color (hex format like "#7aa2f7")
+    #[arg(long)]
+    icon_color: Option<String>,
+
+    /// Text color (hex format like "#c0caf5")
+    #[arg(long)]
+    text_color: Option<String>,
+
+    /// Tooltip label color (hex format like "#bb9af7")
+    #[arg(long)]
+    tooltip_label_color: Option<String>,
+
+    /// Tooltip value color (hex format like "#9ece6a")
+    #[arg(long)]
+    tooltip_value_color: Option<String>,
+
+    /// Verify BlueZ answers and exit
+    #[arg(long)]
+    check: bool,
+
+    /// Generate example config file and exit
+    #[arg(long)]
+    generate_config: bool,
+}
+
+/// Validate that the interval is at least 100ms.
+fn validate_interval(s: &str) -> Result<u64, String> {
+    let interval = s.parse::<u64>()
+        .map_err(|_| "Interval must be a positive integer".to_owned())?;
+    
+    if interval < SensorConfig::MIN_UPDATE_INTERVAL {
+        return Err(format!(
+            "Interval must be at least {}ms", 
+            SensorConfig::MIN_UPDATE_INTERVAL
+        ));
+    }
+    
+    Ok(interval)
+}
+
+/// Main entry point for the Bluetooth sensor.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<BluetoothSensor>(args.json) {
+            eprintln!("Error listing Bluetooth adapters: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Handle config generation
+    if args.generate_config {
+        if let Some(config_path) = GlobalConfig::default_config_path() {
+            GlobalConfig::save_example_config_to_file(&config_path)?;
+            println!("Generated example config at: {}", config_path.display());
+            println!("\nYou can now edit this file to customize your default colors and settings.");
+        } else {
+            eprintln!("Could not determine config directory");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Load global configuration and apply command line overrides
+    let global_config = GlobalConfig::load().unwrap_or_default();
+    let mut config = global_config.to_sensor_config_for("bluetooth")
+        .with_update_interval(Duration::from_millis(args.interval))
+        .apply_color_overrides(
+            args.icon_color,
+            args.text_color,
+            args.tooltip_label_color,
+            args.tooltip_value_color,
+        );
+    
+    // Override icon style only if explicitly provided
+    if let Some(icon_style) = args.icon_style {
+        config = config.with_icon_style(icon_style);
+    }
+    
+    // The adapter from the command line, else from the config
+    let adapter = args.adapter.or_else(|| {
+        config.get_custom("adapter").and_then(|v| v.as_str()).map(str::to_string)
+    });
+    let mut bluetooth_sensor = match BluetoothSensor::new(adapter.as_deref()) {
+        Ok(sensor) => sensor,
+        Err(e) => {
+            eprintln!("Failed to create Bluetooth sensor: {}", e);
+            process::exit(1);
+        }
+    };
+    
+    // Power toggle from the click binding
+    if args.toggle_power {
+        if let Err(e) = bluetooth_sensor.toggle_power() {
+            eprintln!("Error toggling Bluetooth power: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Check availability if requested
+    if args.check {
+        match bluetooth_sensor.check_availability() {
+            Ok(()) => {
+                println!("Bluetooth sensor is available");
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Bluetooth sensor is not available: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+    
+    bluetooth_sensor.configure(config)?;
+    
+    if args.once {
+        // One-shot mode: read once and exit
+        match bluetooth_sensor.read_styled() {
+            Ok(output) => {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+            Err(e) => {
+                eprintln!("Error reading Bluetooth state: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        // Continuous mode: loop and output readings
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
+        
+        loop {
+            interval.tick().await;
+            if session_lock::wait_async(bluetooth_sensor.config()).await {
+                interval.reset();
+            }
+            
+            match bluetooth_sensor.read_styled() {
+                Ok(output) => {
+                    println!("{}", serde_json::to_string(&output)?);
+                    io::stdout().flush()?;
+                }
+                Err(e) => {
+                    eprintln!("Error reading Bluetooth state: {}", e);
+                    // Continue running on errors, just log them
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,379 @@
+//! Focused window and workspaces of Sway or Hyprland.
+//!
+//! Both compositors are asked over their IPC sockets (see
+//! [`waysensor_rs_core::ipc`]): Sway for its workspaces and the focused node
+//! of its layout tree, Hyprland for its active window and workspaces.
+
+use serde_json::Value;
+use std::path::PathBuf;
+use waysensor_rs_core::{format, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput};
+use waysensor_rs_core::ipc;
+
+/// What the module shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    /// Title of the focused window, hidden on an empty workspace
+    #[default]
+    Window,
+    /// Name of the focused workspace
+    Workspace,
+}
+
+impl std::str::FromStr for DisplayMode {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "window" | "title" => Ok(Self::Window),
+            "workspace" => Ok(Self::Workspace),
+            _ => Err(SensorError::config_with_value(
+                "Invalid display mode. Valid options: window, workspace",
+                s,
+            )),
+        }
+    }
+}
+
+/// The running compositor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compositor {
+    /// Sway's IPC socket
+    Sway(PathBuf),
+    /// Hyprland's instance socket directory
+    Hyprland(PathBuf),
+}
+
+impl Compositor {
+    /// The compositor of the running session.
+    pub fn detect() -> Option<Self> {
+        ipc::sway_socket().map(Self::Sway).or_else(|| ipc::hyprland_dir().map(Self::Hyprland))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sway(_) => "sway",
+            Self::Hyprland(_) => "hyprland",
+        }
+    }
+
+    /// The focused window and every workspace.
+    pub fn focus(&self) -> Result<Focus, SensorError> {
+        let no_answer = |e: std::io::Error| SensorError::unavailable(format!("No answer from {}: {}", self.name(), e));
+        match self {
+            Self::Sway(socket) => {
+                let workspaces = ipc::sway_request(socket, ipc::SWAY_GET_WORKSPACES, "").map_err(no_answer)?;
+                let tree = ipc::sway_request(socket, ipc::SWAY_GET_TREE, "").map_err(no_answer)?;
+                Ok(Focus { window: parse_sway_tree(&tree)?, workspaces: parse_sway_workspaces(&workspaces)? })
+            }
+            Self::Hyprland(dir) => {
+                let window = ipc::hyprland_request(dir, "j/activewindow").map_err(no_answer)?;
+                let active = ipc::hyprland_request(dir, "j/activeworkspace").map_err(no_answer)?;
+                let workspaces = ipc::hyprland_request(dir, "j/workspaces").map_err(no_answer)?;
+                Ok(Focus {
+                    window: parse_hyprland_window(&window)?,
+                    workspaces: parse_hyprland_workspaces(&workspaces, &active)?,
+                })
+            }
+        }
+    }
+}
+
+/// A window.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Window {
+    pub title: String,
+    /// Wayland app id, or X11 class of XWayland windows
+    pub app: String,
+}
+
+/// A workspace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub name: String,
+    /// Output (monitor) showing it
+    pub output: String,
+    pub focused: bool,
+    /// A window on it asks for attention (Sway only)
+    pub urgent: bool,
+}
+
+/// Focused window and workspaces, in the compositor's order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Focus {
+    pub window: Option<Window>,
+    pub workspaces: Vec<Workspace>,
+}
+
+impl Focus {
+    pub fn workspace(&self) -> Option<&Workspace> {
+        self.workspaces.iter().find(|workspace| workspace.focused)
+    }
+}
+
+fn parse_json(json: &str, what: &str) -> Result<Value, SensorError> {
+    serde_json::from_str(json).map_err(|e| SensorError::parse_with_source(format!("Invalid {} reply", what), e))
+}
+
+/// Parse Sway's `GET_WORKSPACES` reply.
+fn parse_sway_workspaces(json: &str) -> Result<Vec<Workspace>, SensorError> {
+    let reply = parse_json(json, "GET_WORKSPACES")?;
+    let workspaces = reply.as_array().ok_or_else(|| SensorError::parse("Unexpected GET_WORKSPACES reply"))?;
+    Ok(workspaces
+        .iter()
+        .filter_map(|workspace| {
+            Some(Workspace {
+                name: workspace["name"].as_str()?.to_string(),
+                output: workspace["output"].as_str().unwrap_or_default().to_string(),
+                focused: workspace["focused"].as_bool().unwrap_or(false),
+                urgent: workspace["urgent"].as_bool().unwrap_or(false),
+            })
+        })
+        .collect())
+}
+
+/// The focused node under `node`.
+fn find_focused(node: &Value) -> Option<&Value> {
+    if node["focused"].as_bool() == Some(true) {
+        return Some(node);
+    }
+    let children = node["nodes"].as_array().into_iter().chain(node["floating_nodes"].as_array());
+    children.flatten().find_map(find_focused)
+}
+
+/// Parse Sway's `GET_TREE` reply into its focused window. The focused node
+/// is a workspace, not a window, when the workspace is empty.
+fn parse_sway_tree(json: &str) -> Result<Option<Window>, SensorError> {
+    let tree = parse_json(json, "GET_TREE")?;
+    Ok(find_focused(&tree)
+        .filter(|node| matches!(node["type"].as_str(), Some("con" | "floating_con")))
+        .map(|node| Window {
+            title: node["name"].as_str().unwrap_or_default().to_string(),
+            app: node["app_id"].as_str()
+                .or_else(|| node["window_properties"]["class"].as_str())
+                .unwrap_or_default()
+                .to_string(),
+        }))
+}
+
+/// Parse Hyprland's `j/activewindow` reply, an empty object when no window
+/// has focus.
+fn parse_hyprland_window(json: &str) -> Result<Option<Window>, SensorError> {
+    let window = parse_json(json, "activewindow")?;
+    if window["address"].as_str().is_none() {
+        return Ok(None);
+    }
+    Ok(Some(Window {
+        title: window["title"].as_str().unwrap_or_default().to_string(),
+        app: window["class"].as_str().unwrap_or_default().to_string(),
+    }))
+}
+
+/// Parse Hyprland's `j/workspaces` reply, marking the one of the
+/// `j/activeworkspace` reply as focused. Special workspaces (negative ids)
+/// are left out.
+fn parse_hyprland_workspaces(json: &str, active: &str) -> Result<Vec<Workspace>, SensorError> {
+    let reply = parse_json(json, "workspaces")?;
+    let active = parse_json(active, "activeworkspace")?;
+    let workspaces = reply.as_array().ok_or_else(|| SensorError::parse("Unexpected workspaces reply"))?;
+    let mut workspaces: Vec<(i64, Workspace)> = workspaces
+        .iter()
+        .filter_map(|workspace| {
+            let id = workspace["id"].as_i64()?;
+            Some((id, Workspace {
+                name: workspace["name"].as_str()?.to_string(),
+                output: workspace["monitor"].as_str().unwrap_or_default().to_string(),
+                focused: active["id"].as_i64() == Some(id),
+                urgent: false,
+            }))
+        })
+        .filter(|(id, _)| *id > 0)
+        .collect();
+    workspaces.sort_by_key(|(id, _)| *id);
+    Ok(workspaces.into_iter().map(|(_, workspace)| workspace).collect())
+}
+
+/// Sensor for the focused window or workspace.
+#[derive(Debug)]
+pub struct CompositorSensor {
+    name: String,
+    config: SensorConfig,
+    compositor: Compositor,
+    mode: DisplayMode,
+    marquee: format::Marquee,
+}
+
+impl CompositorSensor {
+    /// Create a sensor showing `mode` of the running compositor.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error outside of a Sway or Hyprland session.
+    pub fn new(mode: DisplayMode) -> Result<Self, SensorError> {
+        let compositor = Compositor::detect()
+            .ok_or_else(|| SensorError::unavailable("No Sway or Hyprland session found"))?;
+        Ok(Self {
+            name: "compositor".to_string(),
+            config: SensorConfig::default(),
+            compositor,
+            mode,
+            marquee: format::Marquee::default(),
+        })
+    }
+
+    pub fn compositor(&self) -> &Compositor {
+        &self.compositor
+    }
+
+    /// The window text of the `format` setting: `{title}`, `{app}` and
+    /// `{workspace}` are replaced.
+    fn window_text(&self, window: &Window, workspace: &str) -> String {
+        self.config.get_custom("format").and_then(|v| v.as_str()).unwrap_or("{title}")
+            .replace("{title}", &window.title)
+            .replace("{app}", &window.app)
+            .replace("{workspace}", workspace)
+    }
+
+    /// `text` truncated or scrolled to the `max_length` setting.
+    fn fit(&mut self, text: &str) -> String {
+        let max_length = self.config.get_custom("max_length").and_then(|v| v.as_u64()).unwrap_or(50) as usize;
+        if self.config.get_custom("scroll").and_then(|v| v.as_bool()).unwrap_or(false) {
+            self.marquee.next(text, max_length, true)
+        } else {
+            format::truncate(text, max_length)
+        }
+    }
+
+    fn build_tooltip(&self, focus: &Focus) -> String {
+        let mut lines = Vec::new();
+        if let Some(window) = &focus.window {
+            lines.push(format::key_value("Window", &format::escape_markup(&window.title), &self.config));
+            if !window.app.is_empty() {
+                lines.push(format::key_value("Application", &format::escape_markup(&window.app), &self.config));
+            }
+        }
+        if let Some(workspace) = focus.workspace() {
+            let place = format!("{} on {}", workspace.name, workspace.output);
+            lines.push(format::key_value("Workspace", &format::escape_markup(&place), &self.config));
+        }
+        let urgent: Vec<&str> = focus.workspaces.iter()
+            .filter(|workspace| workspace.urgent)
+            .map(|workspace| workspace.name.as_str())
+            .collect();
+        if !urgent.is_empty() {
+            lines.push(format::key_value("Urgent", &format::escape_markup(&urgent.join(", ")), &self.config));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Discoverable for CompositorSensor {
+    const KIND: &'static str = "workspaces";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        let Some(compositor) = Compositor::detect() else {
+            return Ok(Vec::new());
+        };
+        Ok(compositor
+            .focus()?
+            .workspaces
+            .into_iter()
+            .map(|workspace| {
+                let item = DiscoveredItem::new(workspace.name.clone(), format!("Workspace {}", workspace.name))
+                    .with_detail("output", workspace.output);
+                if workspace.focused { item.with_detail("focused", "yes") } else { item }
+            })
+            .collect())
+    }
+}
+
+impl Sensor for CompositorSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let focus = self.compositor.focus()?;
+        let workspace = focus.workspace().map(|workspace| workspace.name.clone()).unwrap_or_default();
+
+        let text = match self.mode {
+            DisplayMode::Window => match &focus.window {
+                Some(window) => {
+                    let text = self.fit(&self.window_text(window, &workspace));
+                    format::with_icon_and_colors(&format::escape_markup(&text), &self.config.icons.window, &self.config)
+                }
+                None => String::new(),
+            },
+            DisplayMode::Workspace => {
+                let text = self.fit(&workspace);
+                format::with_icon_and_colors(&format::escape_markup(&text), &self.config.icons.workspace, &self.config)
+            }
+        };
+        let urgent = focus.workspaces.iter().any(|workspace| workspace.urgent);
+        let class = if urgent { &self.config.theme.warning } else { &self.config.theme.normal };
+
+        Ok(WaybarOutput {
+            text,
+            tooltip: Some(self.build_tooltip(&focus)),
+            class: Some(class.clone()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        self.compositor.focus().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_focus_parsing() {
+        let workspaces = r#"[
+            {"num":1,"name":"1: web","focused":false,"urgent":true,"output":"DP-1"},
+            {"num":2,"name":"2","focused":true,"urgent":false,"output":"eDP-1"}
+        ]"#;
+        let workspaces = parse_sway_workspaces(workspaces).unwrap();
+        assert_eq!(workspaces[0], Workspace { name: "1: web".to_string(), output: "DP-1".to_string(), focused: false, urgent: true });
+        assert!(workspaces[1].focused);
+
+        let tree = r#"{"type":"root","focused":false,"nodes":[{"type":"output","focused":false,"nodes":[
+            {"type":"workspace","name":"2","focused":false,"nodes":[
+                {"type":"con","name":"vim","app_id":"foot","focused":false,"nodes":[]}
+            ],"floating_nodes":[
+                {"type":"floating_con","name":"Firefox","app_id":null,"window_properties":{"class":"firefox"},"focused":true,"nodes":[]}
+            ]}
+        ]}]}"#;
+        assert_eq!(
+            parse_sway_tree(tree).unwrap(),
+            Some(Window { title: "Firefox".to_string(), app: "firefox".to_string() })
+        );
+        let empty = r#"{"type":"root","nodes":[{"type":"workspace","name":"3","focused":true,"nodes":[]}]}"#;
+        assert_eq!(parse_sway_tree(empty).unwrap(), None);
+
+        let window = r#"{"address":"0x5581","title":"~/src","class":"kitty","workspace":{"id":2,"name":"2"}}"#;
+        assert_eq!(
+            parse_hyprland_window(window).unwrap(),
+            Some(Window { title: "~/src".to_string(), app: "kitty".to_string() })
+        );
+        assert_eq!(parse_hyprland_window("{}").unwrap(), None);
+        let workspaces = r#"[{"id":3,"name":"3","monitor":"DP-1"},{"id":-98,"name":"special:scratch","monitor":"DP-1"},
+            {"id":1,"name":"1","monitor":"eDP-1"}]"#;
+        let workspaces = parse_hyprland_workspaces(workspaces, r#"{"id":3,"name":"3","monitor":"DP-1"}"#).unwrap();
+        let names: Vec<&str> = workspaces.iter().map(|workspace| workspace.name.as_str()).collect();
+        assert_eq!(names, ["1", "3"]);
+        assert!(workspaces[1].focused);
+    }
+}
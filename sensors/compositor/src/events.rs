@@ -0,0 +1,123 @@
+//! Immediate updates on focus and workspace changes.
+//!
+//! The focused window changes with every click or keypress, far more often
+//! than any reasonable polling interval. Sway sends `window` and `workspace`
+//! events to a subscribed connection and Hyprland writes every change to its
+//! event socket, so a thread blocks on that connection and wakes the update
+//! loop for the changes the module shows.
+
+use crate::compositor::Compositor;
+use std::io::BufReader;
+use std::os::unix::net::UnixStream;
+use std::thread;
+use tokio::sync::mpsc;
+use waysensor_rs_core::SensorError;
+use waysensor_rs_core::ipc;
+
+/// Hyprland events changing the focused window or workspaces.
+const HYPRLAND_EVENTS: &[&str] = &[
+    "activewindow", "windowtitle", "closewindow", "workspace", "focusedmon",
+    "createworkspace", "destroyworkspace", "renameworkspace", "moveworkspace",
+];
+
+/// Whether a Sway event of `kind` with `change` concerns the module.
+fn sway_wakes(kind: u32, change: &str) -> bool {
+    match kind {
+        ipc::SWAY_WORKSPACE_EVENT => true,
+        ipc::SWAY_WINDOW_EVENT => matches!(change, "focus" | "title" | "close" | "move"),
+        _ => false,
+    }
+}
+
+/// Whether Hyprland event `name` concerns the module. Newer Hyprland sends
+/// a `v2` twin of most events, which is skipped.
+fn hyprland_wakes(name: &str) -> bool {
+    HYPRLAND_EVENTS.contains(&name)
+}
+
+enum Subscription {
+    Sway(UnixStream),
+    Hyprland(BufReader<UnixStream>),
+}
+
+impl Subscription {
+    fn open(compositor: &Compositor) -> std::io::Result<Self> {
+        match compositor {
+            Compositor::Sway(socket) => ipc::sway_subscribe(socket, r#"["window","workspace"]"#).map(Self::Sway),
+            Compositor::Hyprland(dir) => ipc::hyprland_events(dir).map(Self::Hyprland),
+        }
+    }
+
+    /// Wait for the next change the module shows.
+    fn wait(&mut self) -> std::io::Result<()> {
+        loop {
+            let wakes = match self {
+                Self::Sway(stream) => {
+                    let (kind, payload) = ipc::sway_event(stream)?;
+                    let event: serde_json::Value = serde_json::from_str(&payload).unwrap_or_default();
+                    sway_wakes(kind, event["change"].as_str().unwrap_or_default())
+                }
+                Self::Hyprland(events) => hyprland_wakes(&ipc::hyprland_event(events)?.0),
+            };
+            if wakes {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Wakeups for focus and workspace changes.
+#[derive(Debug)]
+pub struct FocusEvents {
+    receiver: mpsc::Receiver<()>,
+}
+
+impl FocusEvents {
+    /// Start watching `compositor`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the compositor refuses the subscription.
+    pub fn spawn(compositor: &Compositor) -> Result<Self, SensorError> {
+        let mut subscription = Subscription::open(compositor)
+            .map_err(|e| SensorError::unavailable(format!("No events from {}: {}", compositor.name(), e)))?;
+        // A single slot: changes arriving while an update is pending are
+        // covered by that update
+        let (sender, receiver) = mpsc::channel(1);
+        thread::Builder::new()
+            .name("focus-events".to_string())
+            .spawn(move || {
+                while subscription.wait().is_ok() {
+                    if sender.try_send(()).is_err() && sender.is_closed() {
+                        return;
+                    }
+                }
+            })
+            .map_err(SensorError::Io)?;
+        Ok(Self { receiver })
+    }
+
+    /// Wait for the next change. Never returns once the watcher has stopped.
+    pub async fn changed(&mut self) {
+        if self.receiver.recv().await.is_none() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_filtering() {
+        assert!(sway_wakes(ipc::SWAY_WORKSPACE_EVENT, "focus"));
+        assert!(sway_wakes(ipc::SWAY_WINDOW_EVENT, "title"));
+        assert!(!sway_wakes(ipc::SWAY_WINDOW_EVENT, "mark"));
+        assert!(!sway_wakes(ipc::SWAY_INPUT_EVENT, "xkb_layout"));
+
+        assert!(hyprland_wakes("activewindow"));
+        assert!(!hyprland_wakes("activewindowv2"));
+        assert!(!hyprland_wakes("activelayout"));
+    }
+}
@@ -0,0 +1,10 @@
+//! Compositor sensor for waysensor-rs.
+//!
+//! Shows the focused window's title or the focused workspace of Sway or
+//! Hyprland, updating on every focus change.
+
+pub mod compositor;
+pub mod events;
+
+pub use compositor::{Compositor, CompositorSensor, DisplayMode, Focus, Window, Workspace};
+pub use events::FocusEvents;
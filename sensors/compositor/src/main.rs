@@ -0,0 +1,214 @@
+//! waysensor-rs-compositor: focused window and workspace binary for Waybar.
+//!
+//! Focus and workspace changes are printed as they happen; the interval
+//! refreshes the output in case the compositor stops sending events, and
+//! sets the pace of long titles with the "scroll" setting.
+
+use clap::Parser;
+use waysensor_rs_core::{discovery, schedule, session_lock, GlobalConfig, IconStyle, Sensor, SensorConfig};
+use waysensor_rs_compositor::{CompositorSensor, DisplayMode, FocusEvents};
+use std::io::{self, Write};
+use std::process;
+use std::time::Duration;
+
+/// Command-line arguments for the compositor sensor.
+#[derive(Parser)]
+#[command(name = "waysensor-rs-compositor")]
+#[command(about = "Compositor window and workspace sensor for waysensor-rs")]
+#[command(version)]
+#[command(author)]
+struct Args {
+    /// Update interval in milliseconds (minimum 100ms)
+    #[arg(short, long, default_value = "5000", value_parser = validate_interval)]
+    interval: u64,
+
+    /// What to show: window or workspace (default: the "mode" setting, else window)
+    #[arg(short, long)]
+    mode: Option<DisplayMode>,
+
+    /// One-shot mode (output once and exit)
+    #[arg(short, long)]
+    once: bool,
+
+    /// List workspaces and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Icon style (nerdfont, fontawesome, ascii, none)
+    #[arg(long)]
+    icon_style: Option<IconStyle>,
+
+    /// Icon color (hex format like "#7aa2f7")
+    #[arg(long)]
+    icon_color: Option<String>,
+
+    /// Text color (hex format like "#c0caf5")
+    #[arg(long)]
+    text_color: Option<String>,
+
+    /// Tooltip label color (hex format like "#bb9af7")
+    #[arg(long)]
+    tooltip_label_color: Option<String>,
+
+    /// Tooltip value color (hex format like "#9ece6a")
+    #[arg(long)]
+    tooltip_value_color: Option<String>,
+
+    /// Verify the compositor answers and exit
+    #[arg(long)]
+    check: bool,
+
+    /// Generate example config file and exit
+    #[arg(long)]
+    generate_config: bool,
+}
+
+/// Validate that the interval is at least 100ms.
+fn validate_interval(s: &str) -> Result<u64, String> {
+    let interval = s.parse::<u64>()
+        .map_err(|_| "Interval must be a positive integer".to_owned())?;
+    
+    if interval < SensorConfig::MIN_UPDATE_INTERVAL {
+        return Err(format!(
+            "Interval must be at least {}ms", 
+            SensorConfig::MIN_UPDATE_INTERVAL
+        ));
+    }
+    
+    Ok(interval)
+}
+
+/// Main entry point for the compositor sensor.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<CompositorSensor>(args.json) {
+            eprintln!("Error listing workspaces: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Handle config generation
+    if args.generate_config {
+        if let Some(config_path) = GlobalConfig::default_config_path() {
+            GlobalConfig::save_example_config_to_file(&config_path)?;
+            println!("Generated example config at: {}", config_path.display());
+            println!("\nYou can now edit this file to customize your default colors and settings.");
+        } else {
+            eprintln!("Could not determine config directory");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Load global configuration and apply command line overrides
+    let global_config = GlobalConfig::load().unwrap_or_default();
+    let mut config = global_config.to_sensor_config_for("compositor")
+        .with_update_interval(Duration::from_millis(args.interval))
+        .apply_color_overrides(
+            args.icon_color,
+            args.text_color,
+            args.tooltip_label_color,
+            args.tooltip_value_color,
+        );
+    
+    // Override icon style only if explicitly provided
+    if let Some(icon_style) = args.icon_style {
+        config = config.with_icon_style(icon_style);
+    }
+    
+    let mode = match args.mode {
+        Some(mode) => mode,
+        None => match config.get_custom("mode").and_then(|v| v.as_str()) {
+            Some(mode) => mode.parse()?,
+            None => DisplayMode::default(),
+        },
+    };
+    let mut compositor_sensor = match CompositorSensor::new(mode) {
+        Ok(sensor) => sensor,
+        Err(e) => {
+            eprintln!("Failed to create compositor sensor: {}", e);
+            process::exit(1);
+        }
+    };
+    
+    // Check availability if requested
+    if args.check {
+        match compositor_sensor.check_availability() {
+            Ok(()) => {
+                println!("Compositor sensor is available");
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Compositor sensor is not available: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+    
+    compositor_sensor.configure(config)?;
+    
+    if args.once {
+        // One-shot mode: read once and exit
+        match compositor_sensor.read_styled() {
+            Ok(output) => {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+            Err(e) => {
+                eprintln!("Error reading compositor state: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        // Continuous mode: loop and output readings
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
+        // Focus changes update the bar right away, the timer is a fallback
+        let mut events = FocusEvents::spawn(compositor_sensor.compositor())
+            .map_err(|e| eprintln!("Focus events unavailable, polling only: {}", e))
+            .ok();
+        
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = async {
+                    match &mut events {
+                        Some(events) => events.changed().await,
+                        None => std::future::pending().await,
+                    }
+                } => {}
+            }
+            if session_lock::wait_async(compositor_sensor.config()).await {
+                interval.reset();
+            }
+            
+            match compositor_sensor.read_styled() {
+                Ok(output) => {
+                    println!("{}", serde_json::to_string(&output)?);
+                    io::stdout().flush()?;
+                }
+                Err(e) => {
+                    eprintln!("Error reading compositor state: {}", e);
+                    // Continue running on errors, just log them
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
\ No newline at end of file
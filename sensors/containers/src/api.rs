@@ -0,0 +1,141 @@
+//! Docker Engine API over a Unix socket.
+//!
+//! Docker and Podman both serve the Docker Engine API on a Unix socket. An
+//! HTTP/1.0 request is all it takes: the engine sends the whole response
+//! and closes the connection, so there is no keep-alive or client library
+//! to deal with. Chunked answers, which some proxies send regardless, are
+//! decoded.
+
+use serde_json::Value;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use waysensor_rs_core::SensorError;
+
+/// Longest wait for an answer; stats of a busy container take the longest.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Engine sockets in the order they are tried: `$DOCKER_HOST` and
+/// `$CONTAINER_HOST` (Podman's equivalent) when they name a Unix socket,
+/// then the rootful and rootless default locations of Docker and Podman.
+fn candidates() -> Vec<PathBuf> {
+    let mut sockets: Vec<PathBuf> = ["DOCKER_HOST", "CONTAINER_HOST"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .filter_map(|host| host.strip_prefix("unix://").map(PathBuf::from))
+        .collect();
+    sockets.push(PathBuf::from("/var/run/docker.sock"));
+    if let Ok(runtime) = std::env::var("XDG_RUNTIME_DIR") {
+        sockets.push(Path::new(&runtime).join("docker.sock"));
+        sockets.push(Path::new(&runtime).join("podman/podman.sock"));
+    }
+    sockets.push(PathBuf::from("/run/podman/podman.sock"));
+    sockets
+}
+
+/// The first engine socket that exists.
+pub fn detect() -> Option<PathBuf> {
+    candidates().into_iter().find(|socket| socket.exists())
+}
+
+/// Name of the engine behind `socket`, guessed from its path.
+pub fn engine(socket: &Path) -> &'static str {
+    if socket.to_string_lossy().contains("podman") { "Podman" } else { "Docker" }
+}
+
+/// Status code and body of a raw HTTP response.
+fn parse_response(raw: &[u8]) -> Result<(u16, Vec<u8>), SensorError> {
+    let split = raw.windows(4).position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| SensorError::parse("Truncated response from the container engine"))?;
+    let head = String::from_utf8_lossy(&raw[..split]);
+    let body = &raw[split + 4..];
+
+    let mut lines = head.lines();
+    let status = lines.next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| SensorError::parse("Malformed response from the container engine"))?;
+    let chunked = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("transfer-encoding") && value.trim().eq_ignore_ascii_case("chunked")
+        })
+    });
+    if chunked {
+        let body = decode_chunked(body).ok_or_else(|| SensorError::parse("Malformed chunked response"))?;
+        Ok((status, body))
+    } else {
+        Ok((status, body.to_vec()))
+    }
+}
+
+/// Join the chunks of a chunked transfer encoding body.
+fn decode_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|window| window == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..line_end]).ok()?;
+        // Chunk extensions after a semicolon are allowed and meaningless here
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Some(decoded);
+        }
+        decoded.extend_from_slice(body.get(..size)?);
+        body = body.get(size + 2..)?;
+    }
+}
+
+fn io_error(socket: &Path, e: io::Error) -> SensorError {
+    match e.kind() {
+        io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused => SensorError::unavailable(format!(
+            "No container engine listening on {}", socket.display()
+        )),
+        io::ErrorKind::PermissionDenied => SensorError::permission_denied(socket.display().to_string()),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+            SensorError::timeout(TIMEOUT, "waiting for the container engine")
+        }
+        _ => SensorError::Io(e),
+    }
+}
+
+/// `GET` `path` from the engine on `socket` and parse the JSON answer.
+///
+/// # Errors
+///
+/// Returns an error if the engine is unreachable, answers with an error
+/// status or sends something other than JSON.
+pub fn get(socket: &Path, path: &str) -> Result<Value, SensorError> {
+    let mut stream = UnixStream::connect(socket).map_err(|e| io_error(socket, e))?;
+    stream.set_read_timeout(Some(TIMEOUT)).map_err(SensorError::Io)?;
+    let request = format!("GET {} HTTP/1.0\r\nHost: localhost\r\n\r\n", path);
+    stream.write_all(request.as_bytes()).map_err(|e| io_error(socket, e))?;
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).map_err(|e| io_error(socket, e))?;
+
+    let (status, body) = parse_response(&raw)?;
+    let value: Value = serde_json::from_slice(&body)
+        .map_err(|e| SensorError::parse_with_source("Invalid JSON from the container engine", e))?;
+    if status != 200 {
+        let message = value["message"].as_str().map_or_else(|| format!("HTTP {}", status), str::to_string);
+        return Err(SensorError::unavailable(format!("Container engine error: {}", message)));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_parsing() {
+        let raw = b"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n[{\"Id\":\"abc\"}]";
+        assert_eq!(parse_response(raw).unwrap(), (200, b"[{\"Id\":\"abc\"}]".to_vec()));
+
+        let raw = b"HTTP/1.1 404 Not Found\r\nTransfer-Encoding: Chunked\r\n\r\n7\r\n{\"messa\r\n11;x=1\r\nge\":\"no such id\"}\r\n0\r\n\r\n";
+        assert_eq!(parse_response(raw).unwrap(), (404, b"{\"message\":\"no such id\"}".to_vec()));
+
+        assert!(parse_response(b"HTTP/1.0 200 OK\r\nContent-Type: appl").is_err());
+        assert!(decode_chunked(b"ff\r\nshort").is_none());
+    }
+}
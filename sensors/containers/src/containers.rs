@@ -0,0 +1,298 @@
+//! Container counts and resource usage from Docker or Podman.
+//!
+//! `/containers/json?all=true` lists every container with its state, and
+//! `/containers/{id}/stats` gives the CPU time and memory of a running one.
+//! Stats are taken with `one-shot`, which answers at once instead of after
+//! a second sample, so CPU usage is the CPU time spent between two reads of
+//! the module (or the engine's previous sample where it still sends one).
+
+use crate::api;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use waysensor_rs_core::{format, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+/// A container known to the engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Container {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    /// `running`, `exited`, `paused`, `created`, ...
+    pub state: String,
+    /// Human-readable status such as "Up 2 hours"
+    pub status: String,
+}
+
+impl Container {
+    pub fn is_running(&self) -> bool {
+        self.state == "running"
+    }
+
+    /// Whether `name` designates this container, by name or ID prefix.
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || (name.len() >= 4 && self.id.starts_with(name))
+    }
+}
+
+/// Parse a `/containers/json` answer, sorted by name.
+fn parse_containers(value: &Value) -> Result<Vec<Container>, SensorError> {
+    let list = value.as_array().ok_or_else(|| SensorError::parse("Container list is not an array"))?;
+    let mut containers: Vec<Container> = list
+        .iter()
+        .map(|container| {
+            let field = |key: &str| container[key].as_str().unwrap_or_default().to_string();
+            let id = field("Id");
+            // Names carry a leading slash; unnamed containers go by short ID
+            let name = container["Names"][0].as_str()
+                .map(|name| name.trim_start_matches('/').to_string())
+                .unwrap_or_else(|| id.chars().take(12).collect());
+            Container { id, name, image: field("Image"), state: field("State"), status: field("Status") }
+        })
+        .collect();
+    containers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(containers)
+}
+
+/// Cumulative CPU time of a container and of the whole system, in ns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CpuSample {
+    container: u64,
+    system: u64,
+}
+
+/// Resource usage of a running container.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Usage {
+    /// Percent of one CPU, so up to 100 times the CPU count; `None` until
+    /// two samples are known
+    pub cpu_percent: Option<f64>,
+    /// Memory in use, page cache excluded
+    pub memory: Option<u64>,
+}
+
+/// Parse a `/containers/{id}/stats` answer into the current CPU sample, the
+/// engine's previous one if it sent any, the CPU count and memory in use.
+fn parse_stats(value: &Value) -> (Option<CpuSample>, Option<CpuSample>, u64, Option<u64>) {
+    let sample = |stats: &Value| {
+        let container = stats["cpu_usage"]["total_usage"].as_u64()?;
+        let system = stats["system_cpu_usage"].as_u64().filter(|&system| system > 0)?;
+        Some(CpuSample { container, system })
+    };
+    let cpus = value["cpu_stats"]["online_cpus"].as_u64()
+        .or_else(|| value["cpu_stats"]["cpu_usage"]["percpu_usage"].as_array().map(|cpus| cpus.len() as u64))
+        .filter(|&cpus| cpus > 0)
+        .unwrap_or(1);
+    // Like `docker stats`, leave out reclaimable page cache: inactive_file
+    // on cgroup v2, total_inactive_file or cache on v1
+    let memory = &value["memory_stats"];
+    let cache = ["inactive_file", "total_inactive_file", "cache"]
+        .iter()
+        .find_map(|key| memory["stats"][key].as_u64())
+        .unwrap_or(0);
+    let used = memory["usage"].as_u64().map(|usage| usage.saturating_sub(cache));
+    (sample(&value["cpu_stats"]), sample(&value["precpu_stats"]), cpus, used)
+}
+
+/// CPU usage between two samples, in percent of one CPU.
+fn cpu_percent(current: CpuSample, previous: CpuSample, cpus: u64) -> Option<f64> {
+    let system = current.system.checked_sub(previous.system).filter(|&delta| delta > 0)?;
+    let container = current.container.checked_sub(previous.container)?;
+    Some(container as f64 / system as f64 * cpus as f64 * 100.0)
+}
+
+/// Containers sensor reporting running and total containers.
+#[derive(Debug)]
+pub struct ContainersSensor {
+    name: String,
+    config: SensorConfig,
+    socket: PathBuf,
+    /// Containers to warn about when they are not running
+    watched: Vec<String>,
+    /// Last CPU sample of each running container, by ID
+    samples: HashMap<String, CpuSample>,
+}
+
+impl ContainersSensor {
+    /// Create a sensor talking to the engine on `socket`, or the first
+    /// Docker or Podman socket found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no `socket` is given and none is found.
+    pub fn new(socket: Option<PathBuf>, watched: Vec<String>) -> Result<Self, SensorError> {
+        let socket = socket.or_else(api::detect)
+            .ok_or_else(|| SensorError::unavailable("No Docker or Podman socket found"))?;
+        Ok(Self {
+            name: "containers".to_string(),
+            config: SensorConfig::default(),
+            socket,
+            watched,
+            samples: HashMap::new(),
+        })
+    }
+
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+
+    /// Every container, running or not.
+    pub fn containers(&self) -> Result<Vec<Container>, SensorError> {
+        parse_containers(&api::get(&self.socket, "/containers/json?all=true")?)
+    }
+
+    /// Resource usage of the running container `id`.
+    fn usage(&mut self, id: &str) -> Result<Usage, SensorError> {
+        let stats = api::get(&self.socket, &format!("/containers/{}/stats?stream=false&one-shot=true", id))?;
+        let (current, previous, cpus, memory) = parse_stats(&stats);
+        let previous = previous.or_else(|| self.samples.get(id).copied());
+        let cpu_percent = current.zip(previous).and_then(|(current, previous)| cpu_percent(current, previous, cpus));
+        if let Some(current) = current {
+            self.samples.insert(id.to_string(), current);
+        }
+        Ok(Usage { cpu_percent, memory })
+    }
+
+    /// Watched containers that are missing or not running.
+    fn down<'a>(&'a self, containers: &[Container]) -> Vec<&'a str> {
+        self.watched
+            .iter()
+            .filter(|name| !containers.iter().any(|container| container.matches(name) && container.is_running()))
+            .map(String::as_str)
+            .collect()
+    }
+
+    fn build_tooltip(&self, containers: &[Container], usage: &HashMap<String, Usage>, down: &[&str]) -> String {
+        let running = containers.iter().filter(|container| container.is_running()).count();
+        let mut lines = vec![format::key_value(
+            api::engine(&self.socket),
+            &format!("{} of {} running", running, containers.len()),
+            &self.config,
+        )];
+        for container in containers {
+            let value = match usage.get(&container.id) {
+                Some(usage) => {
+                    let cpu = usage.cpu_percent.map_or_else(|| "-".to_string(), |cpu| format!("{:.1}%", cpu));
+                    let memory = usage.memory.map_or_else(|| "-".to_string(), format::bytes_to_human);
+                    format!("{} CPU, {}", cpu, memory)
+                }
+                None => container.status.clone(),
+            };
+            lines.push(format::key_value(&format::escape_markup(&container.name), &format::escape_markup(&value), &self.config));
+        }
+        if !down.is_empty() {
+            lines.push(format::key_value("Down", &format::escape_markup(&down.join(", ")), &self.config));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Discoverable for ContainersSensor {
+    const KIND: &'static str = "containers";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        let Some(socket) = api::detect() else {
+            return Ok(Vec::new());
+        };
+        Ok(Self::new(Some(socket), Vec::new())?
+            .containers()?
+            .into_iter()
+            .map(|container| {
+                DiscoveredItem::new(container.name.clone(), container.image.clone())
+                    .with_detail("state", container.state)
+                    .with_detail("id", container.id.chars().take(12).collect::<String>())
+            })
+            .collect())
+    }
+}
+
+impl Sensor for ContainersSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let containers = self.containers()?;
+
+        let mut usage = HashMap::new();
+        if self.config.get_custom("stats").and_then(|v| v.as_bool()).unwrap_or(true) {
+            for container in containers.iter().filter(|container| container.is_running()) {
+                // A container stopping between the two requests is no error
+                if let Ok(container_usage) = self.usage(&container.id) {
+                    usage.insert(container.id.clone(), container_usage);
+                }
+            }
+        }
+        self.samples.retain(|id, _| usage.contains_key(id));
+
+        let running = containers.iter().filter(|container| container.is_running()).count();
+        let down = self.down(&containers);
+        let text = format::with_icon_and_colors(
+            &format!("{}/{}", running, containers.len()),
+            &self.config.icons.containers,
+            &self.config,
+        );
+        let class = if down.is_empty() { &self.config.theme.normal } else { &self.config.theme.warning };
+
+        Ok(WaybarOutput {
+            text,
+            tooltip: Some(self.build_tooltip(&containers, &usage, &down)),
+            class: Some(class.clone()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        api::get(&self.socket, "/version").map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_parsing() {
+        let list = serde_json::json!([
+            {"Id": "9f2c41d0aa7b3e", "Names": ["/web"], "Image": "nginx:latest", "State": "running", "Status": "Up 2 hours"},
+            {"Id": "0b1c2d3e4f5a6b7c", "Names": [], "Image": "alpine", "State": "exited", "Status": "Exited (0) 3 days ago"},
+            {"Id": "5e6f", "Names": ["/db"], "Image": "postgres:16", "State": "exited", "Status": "Exited (1) 1 minute ago"}
+        ]);
+        let containers = parse_containers(&list).unwrap();
+        let names: Vec<&str> = containers.iter().map(|container| container.name.as_str()).collect();
+        assert_eq!(names, ["0b1c2d3e4f5a", "db", "web"]);
+        assert!(containers[2].is_running());
+        assert!(containers[2].matches("web") && containers[2].matches("9f2c41"));
+        assert!(!containers[2].matches("9f"));
+
+        let sensor = ContainersSensor::new(Some(PathBuf::from("/nonexistent")), vec!["web".to_string(), "db".to_string(), "cache".to_string()]).unwrap();
+        assert_eq!(sensor.down(&containers), ["db", "cache"]);
+
+        let stats = serde_json::json!({
+            "cpu_stats": {"cpu_usage": {"total_usage": 3_000_000_000u64}, "system_cpu_usage": 120_000_000_000u64, "online_cpus": 4},
+            "precpu_stats": {"cpu_usage": {"total_usage": 0}},
+            "memory_stats": {"usage": 157_286_400, "limit": 8_000_000_000u64, "stats": {"inactive_file": 52_428_800}}
+        });
+        let (current, previous, cpus, memory) = parse_stats(&stats);
+        assert_eq!(current, Some(CpuSample { container: 3_000_000_000, system: 120_000_000_000 }));
+        assert_eq!(previous, None);
+        assert_eq!((cpus, memory), (4, Some(104_857_600)));
+
+        // 1s of CPU time out of 40s across 4 CPUs is a tenth of one CPU
+        let earlier = CpuSample { container: 2_000_000_000, system: 80_000_000_000 };
+        let percent = cpu_percent(current.unwrap(), earlier, cpus).unwrap();
+        assert!((percent - 10.0).abs() < 1e-9);
+        assert_eq!(cpu_percent(earlier, earlier, cpus), None);
+    }
+}
@@ -0,0 +1,9 @@
+//! Containers sensor for waysensor-rs.
+//!
+//! Counts running and total Docker or Podman containers through the
+//! engine's socket API, with the CPU and memory use of each running one.
+
+pub mod api;
+pub mod containers;
+
+pub use containers::{Container, ContainersSensor, Usage};
@@ -4,13 +4,58 @@
 //! and calculating the percentage of CPU time spent in active (non-idle) states.
 
 use waysensor_rs_core::{
-    format, Sensor, SensorConfig, SensorError, WaybarOutput,
+    cgroup, diff, format, hwmon, process::{ProcessCpu, ProcessCpuTracker}, procfs, psi::PressureStats, sysroot,
+    DiscoveredItem, Discoverable, Reading, Sensor, SensorConfig, SensorError, WaybarOutput,
 };
+use std::fmt;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::{Duration, Instant};
 
+/// Primary metric shown in the bar text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CpuMetric {
+    /// Busy percentage from `/proc/stat` deltas
+    #[default]
+    Usage,
+    /// 1/5/15-minute load averages from `/proc/loadavg`, normalized per core
+    LoadAvg,
+    /// CPU pressure stall percentage from `/proc/pressure/cpu`
+    Psi,
+    /// Share of CPU time spent waiting for I/O
+    IoWait,
+}
+
+impl fmt::Display for CpuMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Usage => "usage",
+            Self::LoadAvg => "loadavg",
+            Self::Psi => "psi",
+            Self::IoWait => "iowait",
+        };
+        f.write_str(name)
+    }
+}
+
+impl std::str::FromStr for CpuMetric {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "usage" | "busy" => Ok(Self::Usage),
+            "loadavg" | "load" => Ok(Self::LoadAvg),
+            "psi" | "pressure" => Ok(Self::Psi),
+            "iowait" => Ok(Self::IoWait),
+            _ => Err(SensorError::config_with_value(
+                "Invalid CPU metric. Valid options: usage, loadavg, psi, iowait",
+                s,
+            )),
+        }
+    }
+}
+
 /// CPU usage sensor that monitors system CPU utilization.
 ///
 /// Reads CPU statistics from `/proc/stat` and calculates usage percentages
@@ -20,7 +65,7 @@ use std::time::{Duration, Instant};
 /// # Examples
 ///
 /// ```rust
-/// use waysensor_cpu::CpuSensor;
+/// use waysensor_rs_cpu::CpuSensor;
 /// use waysensor_rs_core::Sensor;
 ///
 /// let mut sensor = CpuSensor::new(70, 90)?;
@@ -38,6 +83,31 @@ pub struct CpuSensor {
     prev_core_stats: Option<Vec<PerCoreCpuStats>>,
     min_sample_interval: Duration,
     usage_history: Vec<f64>,
+    metric: CpuMetric,
+    load_warning: f64,
+    load_critical: f64,
+    psi_warning: f64,
+    psi_critical: f64,
+    iowait_warning: f64,
+    iowait_critical: f64,
+    last_breakdown: Option<CpuTimeBreakdown>,
+    process_tracker: ProcessCpuTracker,
+    topology: Option<CoreTopology>,
+    pcore_thresholds: Option<(f64, f64)>,
+    ecore_thresholds: Option<(f64, f64)>,
+    /// Package temperature sensor, `None` until looked up
+    temperature: Option<Option<CpuTemperature>>,
+    cgroup: Option<CgroupScope>,
+    throttle: ThrottleDetector,
+    last_reading: Option<Reading>,
+}
+
+/// cgroup the sensor is scoped to, with the previous `cpu.stat` sample.
+#[derive(Debug)]
+struct CgroupScope {
+    path: String,
+    dir: PathBuf,
+    prev: Option<(cgroup::CpuStat, Instant)>,
 }
 
 /// CPU statistics from `/proc/stat`.
@@ -64,6 +134,23 @@ pub struct CpuStats {
     pub steal: u64,
 }
 
+/// Share of CPU time spent in each state between two `/proc/stat` samples.
+///
+/// All values are percentages of total elapsed CPU time.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CpuTimeBreakdown {
+    /// User mode, including niced processes
+    pub user: f64,
+    /// Kernel mode
+    pub system: f64,
+    /// Idle while waiting for I/O to complete
+    pub iowait: f64,
+    /// Hardware and software interrupt handling
+    pub irq: f64,
+    /// Time the hypervisor gave to another guest
+    pub steal: f64,
+}
+
 /// Per-core CPU statistics.
 ///
 /// Holds statistics for an individual CPU core, including its core number
@@ -141,6 +228,29 @@ impl CpuStats {
         }
     }
     
+    /// Break the time elapsed since a previous reading down by CPU state.
+    ///
+    /// Returns all zeros if no time has elapsed between readings.
+    #[must_use]
+    pub fn breakdown(&self, prev: &CpuStats) -> CpuTimeBreakdown {
+        let total_diff = self.total().saturating_sub(prev.total());
+        if total_diff == 0 {
+            return CpuTimeBreakdown::default();
+        }
+        
+        let share = |now: u64, before: u64| {
+            (now.saturating_sub(before) as f64 / total_diff as f64 * 100.0).clamp(0.0, 100.0)
+        };
+        
+        CpuTimeBreakdown {
+            user: share(self.user + self.nice, prev.user + prev.nice),
+            system: share(self.system, prev.system),
+            iowait: share(self.iowait, prev.iowait),
+            irq: share(self.irq + self.softirq, prev.irq + prev.softirq),
+            steal: share(self.steal, prev.steal),
+        }
+    }
+    
     /// Parse CPU statistics from a `/proc/stat` line.
     ///
     /// # Errors
@@ -197,7 +307,7 @@ pub struct CpuInfo {
 impl CpuInfo {
     /// Parse CPU information from `/proc/cpuinfo`.
     pub fn from_proc_cpuinfo() -> Result<Self, SensorError> {
-        Self::from_proc_cpuinfo_path(Path::new("/proc/cpuinfo"))
+        Self::from_proc_cpuinfo_path(&sysroot::path("/proc/cpuinfo"))
     }
     
     /// Parse CPU information from a cpuinfo file path (useful for testing).
@@ -273,6 +383,468 @@ impl CpuInfo {
     }
 }
 
+/// System load averages from `/proc/loadavg`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoadAverage {
+    /// 1-minute load average
+    pub one: f64,
+    /// 5-minute load average
+    pub five: f64,
+    /// 15-minute load average
+    pub fifteen: f64,
+    /// Currently runnable scheduling entities
+    pub running: u32,
+    /// Total scheduling entities
+    pub total: u32,
+}
+
+impl LoadAverage {
+    /// Read load averages from `/proc/loadavg`.
+    pub fn read() -> Result<Self, SensorError> {
+        procfs::read_with(sysroot::path("/proc/loadavg"), Self::parse_loadavg_content)?
+    }
+    
+    /// Parse the contents of `/proc/loadavg`, e.g. `0.52 0.48 0.40 2/345 12345`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SensorError::Parse`] if any of the fields are missing or invalid.
+    pub fn parse_loadavg_content(content: &str) -> Result<Self, SensorError> {
+        let mut fields = content.split_whitespace();
+        let mut next_load = |name: &str| -> Result<f64, SensorError> {
+            fields.next()
+                .ok_or_else(|| SensorError::parse(format!("Missing {} load average", name)))?
+                .parse::<f64>()
+                .map_err(|e| SensorError::parse_with_source(format!("Failed to parse {} load average", name), e))
+        };
+        
+        let one = next_load("1-minute")?;
+        let five = next_load("5-minute")?;
+        let fifteen = next_load("15-minute")?;
+        
+        let (running, total) = fields.next()
+            .and_then(|entities| entities.split_once('/'))
+            .and_then(|(r, t)| Some((r.parse().ok()?, t.parse().ok()?)))
+            .ok_or_else(|| SensorError::parse("Failed to parse runnable entities"))?;
+        
+        Ok(Self { one, five, fifteen, running, total })
+    }
+    
+    /// Load averages divided by the number of cores.
+    #[must_use]
+    pub fn per_core(&self, cores: usize) -> (f64, f64, f64) {
+        let cores = cores.max(1) as f64;
+        (self.one / cores, self.five / cores, self.fifteen / cores)
+    }
+}
+
+/// Core type on a hybrid CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreType {
+    /// Performance core (Intel P-core, AMD full Zen core)
+    Performance,
+    /// Efficiency core (Intel E-core, AMD compact "c" core)
+    Efficiency,
+}
+
+impl fmt::Display for CoreType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Performance => "P",
+            Self::Efficiency => "E",
+        })
+    }
+}
+
+/// Split of logical CPUs into performance and efficiency cores.
+///
+/// Only exists on hybrid CPUs; [`CoreTopology::detect`] returns `None` when
+/// all cores are the same kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreTopology {
+    /// Logical CPU IDs of performance cores
+    pub performance: Vec<usize>,
+    /// Logical CPU IDs of efficiency cores
+    pub efficiency: Vec<usize>,
+}
+
+impl CoreTopology {
+    /// Efficiency cores top out below this fraction of the fastest core's
+    /// maximum frequency. Boost-binning differences between identical cores
+    /// stay well above it.
+    const EFFICIENCY_FREQ_RATIO: f64 = 0.85;
+    
+    /// Detect the hybrid topology of this system.
+    pub fn detect() -> Option<Self> {
+        Self::detect_from(&sysroot::path("/sys/devices"))
+    }
+    
+    /// Detect the hybrid topology from a sysfs `devices` directory.
+    ///
+    /// Intel hybrid CPUs expose separate `cpu_core` and `cpu_atom` PMUs listing
+    /// their CPUs. Elsewhere (AMD Zen + Zen c, ARM big.LITTLE) the cores are
+    /// told apart by `cpu_capacity` or, failing that, their maximum frequency.
+    pub fn detect_from(devices: &Path) -> Option<Self> {
+        let read_list = |pmu: &str| {
+            fs::read_to_string(devices.join(pmu).join("cpus"))
+                .ok()
+                .map(|list| Self::parse_cpu_list(&list))
+                .filter(|cpus| !cpus.is_empty())
+        };
+        if let (Some(performance), Some(efficiency)) = (read_list("cpu_core"), read_list("cpu_atom")) {
+            return Some(Self { performance, efficiency });
+        }
+        
+        let cpu_dir = devices.join("system").join("cpu");
+        let mut metrics: Vec<(usize, f64)> = Vec::new();
+        for file in ["cpu_capacity", "cpufreq/cpuinfo_max_freq"] {
+            metrics = fs::read_dir(&cpu_dir).ok()?
+                .flatten()
+                .filter_map(|entry| {
+                    let id = entry.file_name().to_str()?.strip_prefix("cpu")?.parse().ok()?;
+                    let value = procfs::read_value::<f64>(entry.path().join(file)).ok()?;
+                    Some((id, value))
+                })
+                .collect();
+            if !metrics.is_empty() {
+                break;
+            }
+        }
+        
+        let max = metrics.iter().map(|&(_, v)| v).fold(0.0, f64::max);
+        if max <= 0.0 {
+            return None;
+        }
+        
+        let (mut performance, mut efficiency): (Vec<_>, Vec<_>) = metrics.into_iter()
+            .partition(|&(_, value)| value >= max * Self::EFFICIENCY_FREQ_RATIO);
+        if efficiency.is_empty() {
+            return None;
+        }
+        performance.sort_unstable_by_key(|&(id, _)| id);
+        efficiency.sort_unstable_by_key(|&(id, _)| id);
+        
+        Some(Self {
+            performance: performance.into_iter().map(|(id, _)| id).collect(),
+            efficiency: efficiency.into_iter().map(|(id, _)| id).collect(),
+        })
+    }
+    
+    /// Parse a kernel CPU list such as `0-7,16,18-19`.
+    pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+        list.trim()
+            .split(',')
+            .filter(|part| !part.is_empty())
+            .flat_map(|part| {
+                let (start, end) = part.split_once('-').unwrap_or((part, part));
+                match (start.trim().parse::<usize>(), end.trim().parse::<usize>()) {
+                    (Ok(start), Ok(end)) if start <= end => (start..=end).collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect()
+    }
+    
+    /// Type of a logical CPU, if it is part of the topology.
+    pub fn core_type(&self, core_id: usize) -> Option<CoreType> {
+        if self.performance.contains(&core_id) {
+            Some(CoreType::Performance)
+        } else if self.efficiency.contains(&core_id) {
+            Some(CoreType::Efficiency)
+        } else {
+            None
+        }
+    }
+    
+    /// Average usage over the cores of one type.
+    pub fn average_usage(&self, core_type: CoreType, core_usages: &[(usize, f64)]) -> Option<f64> {
+        let usages: Vec<f64> = core_usages.iter()
+            .filter(|&&(id, _)| self.core_type(id) == Some(core_type))
+            .map(|&(_, usage)| usage)
+            .collect();
+        (!usages.is_empty()).then(|| usages.iter().sum::<f64>() / usages.len() as f64)
+    }
+}
+
+/// CPU frequency scaling state from `/sys/devices/system/cpu/cpufreq`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuFrequency {
+    /// Average current frequency across all policies in MHz
+    pub average_mhz: f64,
+    /// Highest current frequency across all policies in MHz
+    pub max_mhz: f64,
+    /// Active scaling governor(s), e.g. "schedutil" or "performance, powersave"
+    pub governor: Option<String>,
+    /// Highest frequency the hardware supports (`cpuinfo_max_freq`) in MHz
+    pub hardware_max_mhz: Option<f64>,
+    /// Highest frequency the kernel currently allows (`scaling_max_freq`) in MHz
+    pub scaling_max_mhz: Option<f64>,
+}
+
+impl CpuFrequency {
+    /// Default location of the cpufreq policies.
+    const CPUFREQ_PATH: &'static str = "/sys/devices/system/cpu/cpufreq";
+    
+    /// Read frequency scaling information for all policies.
+    pub fn read() -> Result<Self, SensorError> {
+        Self::read_from_path(&sysroot::path(Self::CPUFREQ_PATH))
+    }
+    
+    /// Read frequency scaling information from a cpufreq directory (useful for testing).
+    pub fn read_from_path(path: &Path) -> Result<Self, SensorError> {
+        let mut frequencies = Vec::new();
+        let mut governors: Vec<String> = Vec::new();
+        let mut hardware_max: Option<f64> = None;
+        let mut scaling_max: Option<f64> = None;
+        let read_mhz = |path: PathBuf| procfs::read_value::<u64>(path).ok().map(|khz| khz as f64 / 1000.0);
+        
+        for entry in fs::read_dir(path)?.flatten() {
+            let name = entry.file_name();
+            if !name.to_string_lossy().starts_with("policy") {
+                continue;
+            }
+            
+            let policy = entry.path();
+            if let Some(khz) = fs::read_to_string(policy.join("scaling_cur_freq"))
+                .ok()
+                .and_then(|s| s.trim().parse::<u64>().ok())
+            {
+                frequencies.push(khz as f64 / 1000.0);
+            }
+            if let Some(mhz) = read_mhz(policy.join("cpuinfo_max_freq")) {
+                hardware_max = Some(hardware_max.map_or(mhz, |max| max.max(mhz)));
+            }
+            if let Some(mhz) = read_mhz(policy.join("scaling_max_freq")) {
+                scaling_max = Some(scaling_max.map_or(mhz, |max| max.max(mhz)));
+            }
+            if let Ok(governor) = fs::read_to_string(policy.join("scaling_governor")) {
+                let governor = governor.trim().to_owned();
+                if !governor.is_empty() && !governors.contains(&governor) {
+                    governors.push(governor);
+                }
+            }
+        }
+        
+        if frequencies.is_empty() {
+            return Err(SensorError::unavailable("No cpufreq policies found"));
+        }
+        
+        Ok(Self {
+            average_mhz: frequencies.iter().sum::<f64>() / frequencies.len() as f64,
+            max_mhz: frequencies.iter().copied().fold(0.0, f64::max),
+            governor: (!governors.is_empty()).then(|| governors.join(", ")),
+            hardware_max_mhz: hardware_max,
+            scaling_max_mhz: scaling_max,
+        })
+    }
+    
+    /// Whether the clocks are held back by a policy limit below what the
+    /// hardware supports (`cpupower frequency-set`, platform limits).
+    ///
+    /// A lowered `scaling_max_freq` alone is a deliberate setting, e.g. of a
+    /// power-saver profile; it only counts while the CPU actually runs at
+    /// the policy limit.
+    pub fn is_capped(&self) -> bool {
+        match (self.scaling_max_mhz, self.hardware_max_mhz) {
+            (Some(limit), Some(hardware)) => limit < hardware * 0.95 && self.max_mhz >= limit * 0.95,
+            _ => false,
+        }
+    }
+    
+    /// Format frequency and governor information with optional coloring for tooltips.
+    #[must_use]
+    pub fn format_info_colored(&self, config: &SensorConfig) -> String {
+        let to_human = |mhz: f64| format::frequency_to_human((mhz * 1_000_000.0) as u64);
+        
+        let mut lines = vec![format::key_value(
+            "Core Clock",
+            &format!("avg {} / max {}", to_human(self.average_mhz), to_human(self.max_mhz)),
+            config,
+        )];
+        if let Some(governor) = &self.governor {
+            lines.push(format::key_value("Governor", governor, config));
+        }
+        
+        lines.join("\n")
+    }
+}
+
+/// Cumulative thermal throttling events from `cpuN/thermal_throttle` (Intel).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ThrottleCounters {
+    /// Core throttle events summed over all CPUs
+    pub core: u64,
+    /// Package throttle events, summed over packages
+    pub package: u64,
+}
+
+impl ThrottleCounters {
+    /// Default location of the per-CPU sysfs directories.
+    const CPU_PATH: &'static str = "/sys/devices/system/cpu";
+    
+    /// Read the throttle counters, or `None` if the platform has none.
+    pub fn read() -> Option<Self> {
+        Self::read_from_path(&sysroot::path(Self::CPU_PATH))
+    }
+    
+    /// Read the throttle counters from a cpu sysfs directory (useful for testing).
+    pub fn read_from_path(path: &Path) -> Option<Self> {
+        let mut counters = Self::default();
+        let mut found = false;
+        // Every CPU of a package reports the same package count
+        let mut packages: Vec<(String, u64)> = Vec::new();
+        
+        for entry in fs::read_dir(path).ok()?.flatten() {
+            let name = entry.file_name();
+            let Some(id) = name.to_str().and_then(|n| n.strip_prefix("cpu")) else { continue };
+            if id.parse::<usize>().is_err() {
+                continue;
+            }
+            
+            let throttle = entry.path().join("thermal_throttle");
+            if let Ok(count) = procfs::read_value::<u64>(throttle.join("core_throttle_count")) {
+                counters.core += count;
+                found = true;
+            }
+            if let Ok(count) = procfs::read_value::<u64>(throttle.join("package_throttle_count")) {
+                let package = fs::read_to_string(entry.path().join("topology/physical_package_id"))
+                    .map(|p| p.trim().to_owned())
+                    .unwrap_or_default();
+                if !packages.iter().any(|(p, _)| *p == package) {
+                    packages.push((package, count));
+                }
+                found = true;
+            }
+        }
+        
+        counters.package = packages.iter().map(|&(_, count)| count).sum();
+        found.then_some(counters)
+    }
+    
+    /// Total number of events.
+    pub fn total(&self) -> u64 {
+        self.core + self.package
+    }
+}
+
+/// Why the CPU is running below its rated frequency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleReason {
+    /// Hardware reported thermal throttling, or the package is hot while clocks sag under load
+    Thermal,
+    /// Clocks sag under load without heat: power or current limits (PL1/PL2, PPT)
+    PowerLimit,
+    /// The kernel caps the maximum frequency
+    FrequencyCap,
+    /// Clocks sag under load but no temperature is available to tell heat from power limits
+    Unknown,
+}
+
+impl fmt::Display for ThrottleReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Thermal => "thermal",
+            Self::PowerLimit => "power limit",
+            Self::FrequencyCap => "frequency cap",
+            Self::Unknown => "cause unknown",
+        })
+    }
+}
+
+/// Tracks throttle counters between reads and classifies throttling.
+#[derive(Debug, Default)]
+pub struct ThrottleDetector {
+    prev_counters: Option<ThrottleCounters>,
+    /// Reads in which throttling was detected since the sensor started
+    pub throttled_reads: u64,
+}
+
+impl ThrottleDetector {
+    /// Usage above which clocks are expected to be near the rated maximum.
+    const LOADED_USAGE: f64 = 80.0;
+    
+    /// Fraction of the rated maximum below which a loaded CPU counts as held back.
+    const SAGGING_RATIO: f64 = 0.6;
+    
+    /// Package temperature treated as "hot" when no hardware counters exist.
+    const HOT_CELSIUS: f64 = 90.0;
+    
+    /// Classify the current sample and return the reason with the number of
+    /// new hardware throttle events since the previous sample.
+    pub fn update(
+        &mut self,
+        counters: Option<ThrottleCounters>,
+        frequency: Option<&CpuFrequency>,
+        usage: f64,
+        temperature: Option<f64>,
+    ) -> Option<(ThrottleReason, u64)> {
+        let new_events = match (self.prev_counters, counters) {
+            (Some(prev), Some(current)) => current.total().saturating_sub(prev.total()),
+            _ => 0,
+        };
+        if counters.is_some() {
+            self.prev_counters = counters;
+        }
+        
+        let sagging = frequency.is_some_and(|freq| {
+            let rated = freq.scaling_max_mhz.or(freq.hardware_max_mhz).unwrap_or(0.0);
+            usage >= Self::LOADED_USAGE && rated > 0.0 && freq.max_mhz < rated * Self::SAGGING_RATIO
+        });
+        
+        let reason = if new_events > 0 {
+            Some(ThrottleReason::Thermal)
+        } else if frequency.is_some_and(CpuFrequency::is_capped) {
+            Some(ThrottleReason::FrequencyCap)
+        } else if sagging {
+            match temperature {
+                Some(celsius) if celsius >= Self::HOT_CELSIUS => Some(ThrottleReason::Thermal),
+                Some(_) => Some(ThrottleReason::PowerLimit),
+                None => Some(ThrottleReason::Unknown),
+            }
+        } else {
+            None
+        };
+        
+        if reason.is_some() {
+            self.throttled_reads += 1;
+        }
+        reason.map(|reason| (reason, new_events))
+    }
+}
+
+/// CPU package temperature from the hwmon driver of the processor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuTemperature {
+    /// Path of the `tempN_input` file holding the package temperature
+    pub input_path: PathBuf,
+    /// hwmon driver name (`k10temp`, `coretemp`, ...)
+    pub driver: String,
+}
+
+impl CpuTemperature {
+    /// Locate the CPU package temperature sensor.
+    pub fn detect() -> Option<Self> {
+        Self::detect_in(&sysroot::path(hwmon::HWMON_PATH))
+    }
+    
+    /// Locate the CPU package temperature sensor under a hwmon class directory.
+    ///
+    /// Prefers the labelled package sensor of the best known driver and falls
+    /// back to the driver's first temperature input.
+    pub fn detect_in(hwmon_dir: &Path) -> Option<Self> {
+        hwmon::find_input(hwmon_dir, hwmon::CPU_DRIVERS).map(|input| Self {
+            input_path: input.path,
+            driver: input.device,
+        })
+    }
+    
+    /// Current package temperature in °C.
+    pub fn read_celsius(&self) -> Result<f64, SensorError> {
+        let millidegrees: i64 = procfs::read_value(&self.input_path)?;
+        Ok(millidegrees as f64 / 1000.0)
+    }
+}
+
 impl CpuSensor {
     /// Path to the proc stat file (customizable for testing).
     const PROC_STAT_PATH: &'static str = "/proc/stat";
@@ -307,6 +879,81 @@ impl CpuSensor {
         }
     }
     
+    /// Render per-core usage as a heat map with one block per core.
+    fn create_heat_map(core_usages: &[(usize, f64)]) -> String {
+        const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        core_usages.iter()
+            .map(|&(_, usage)| {
+                let index = ((usage.clamp(0.0, 100.0) / 100.0) * (LEVELS.len() - 1) as f64).round() as usize;
+                LEVELS[index]
+            })
+            .collect()
+    }
+    
+    /// Pick the cores to list individually in the tooltip.
+    ///
+    /// With `max_cores == 0` or few enough cores every core is shown. Otherwise
+    /// the busiest `max_cores` cores are kept. The result is sorted by core ID.
+    fn select_cores_for_display(core_usages: &[(usize, f64)], max_cores: usize) -> Vec<(usize, f64)> {
+        let mut selected = core_usages.to_vec();
+        if max_cores > 0 && selected.len() > max_cores {
+            selected.sort_by(|a, b| b.1.total_cmp(&a.1));
+            selected.truncate(max_cores);
+        }
+        selected.sort_by_key(|&(id, _)| id);
+        selected
+    }
+    
+    /// Build the per-core tooltip section, honoring the `show_per_core` and
+    /// `max_cores_display` config keys.
+    fn format_per_core_section(&self, core_usages: &[(usize, f64)]) -> String {
+        let show_per_core = self.config.get_custom("show_per_core")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        if !show_per_core || core_usages.is_empty() {
+            return String::new();
+        }
+        
+        let max_cores = self.config.get_custom("max_cores_display")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0) as usize;
+        
+        let mut all_cores = core_usages.to_vec();
+        all_cores.sort_by_key(|&(id, _)| id);
+        let shown = Self::select_cores_for_display(&all_cores, max_cores);
+        
+        let section_header = format::key_only("Per-Core Usage", &self.config);
+        let mut section = format!("\n\n{}", section_header);
+        
+        // Display each core with a gauge
+        for &(core_id, core_usage) in &shown {
+            let gauge = Self::create_gauge(core_usage, 10);
+            let indicator = Self::get_usage_indicator(core_usage);
+            let core_type = self.topology.as_ref()
+                .and_then(|t| t.core_type(core_id))
+                .map(|t| format!(" {}", t))
+                .unwrap_or_default();
+            let core_label = format::key_only(&format!("Core {:2}{}", core_id, core_type), &self.config);
+            let core_value = format::value_only(&format!("{} {:5.1}% {}", gauge, core_usage, indicator), &self.config);
+            section.push_str(&format!("\n  {} {}", core_label, core_value));
+        }
+        
+        // Summarize the cores that didn't make the cut
+        let hidden = all_cores.len() - shown.len();
+        if hidden > 0 {
+            let hidden_line = format::value_only(
+                &format!("… {} more cores (busiest {} shown)", hidden, shown.len()),
+                &self.config,
+            );
+            section.push_str(&format!("\n  {}", hidden_line));
+            
+            let heat_map = Self::create_heat_map(&all_cores);
+            section.push_str(&format!("\n  {}", format::key_value("All cores", &heat_map, &self.config)));
+        }
+        
+        section
+    }
+    
     /// Create a new CPU sensor with the specified thresholds.
     ///
     /// # Arguments
@@ -334,9 +981,450 @@ impl CpuSensor {
             prev_core_stats: None,
             min_sample_interval: Self::MIN_SAMPLE_INTERVAL,
             usage_history: Vec::new(),
+            metric: CpuMetric::default(),
+            load_warning: 1.0,
+            load_critical: 2.0,
+            psi_warning: 10.0,
+            psi_critical: 25.0,
+            iowait_warning: 10.0,
+            iowait_critical: 25.0,
+            last_breakdown: None,
+            process_tracker: ProcessCpuTracker::new(),
+            topology: CoreTopology::detect(),
+            pcore_thresholds: None,
+            ecore_thresholds: None,
+            temperature: None,
+            cgroup: None,
+            throttle: ThrottleDetector::default(),
+            last_reading: None,
         })
     }
     
+    /// Select the metric shown in the bar text.
+    #[must_use]
+    pub fn with_metric(mut self, metric: CpuMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+    
+    /// Scope usage and pressure to a cgroup v2 group such as `user.slice` or
+    /// `system.slice/docker-<id>.scope`.
+    ///
+    /// Usage is relative to the group's `cpu.max` quota, or to all CPUs when
+    /// it has none. Load average and I/O wait stay system-wide.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cgroup does not exist.
+    pub fn with_cgroup(mut self, path: &str) -> Result<Self, SensorError> {
+        let dir = cgroup::resolve(path)?;
+        let scope_name = path.trim_matches('/').replace('/', "-");
+        if !scope_name.is_empty() {
+            self.name = format!("cpu-{}", scope_name);
+        }
+        self.cgroup = Some(CgroupScope { path: path.to_owned(), dir, prev: None });
+        Ok(self)
+    }
+    
+    /// Set load-per-core thresholds used by [`CpuMetric::LoadAvg`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thresholds are negative or critical <= warning.
+    pub fn with_load_thresholds(mut self, warning: f64, critical: f64) -> Result<Self, SensorError> {
+        if warning < 0.0 || critical <= warning {
+            return Err(SensorError::config(format!(
+                "Critical load per core ({}) must be greater than warning load per core ({})",
+                critical, warning
+            )));
+        }
+        self.load_warning = warning;
+        self.load_critical = critical;
+        Ok(self)
+    }
+    
+    /// Set stall percentage thresholds used by [`CpuMetric::Psi`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thresholds are outside 0-100 or critical <= warning.
+    pub fn with_psi_thresholds(mut self, warning: f64, critical: f64) -> Result<Self, SensorError> {
+        if warning < 0.0 || critical > 100.0 || critical <= warning {
+            return Err(SensorError::config(format!(
+                "Critical pressure ({}%) must be greater than warning pressure ({}%) and at most 100%",
+                critical, warning
+            )));
+        }
+        self.psi_warning = warning;
+        self.psi_critical = critical;
+        Ok(self)
+    }
+    
+    /// Set separate usage thresholds for the average of one core type.
+    ///
+    /// On hybrid CPUs the sensor class escalates when either core type
+    /// crosses its own thresholds. Ignored on CPUs without a hybrid topology.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if critical <= warning.
+    pub fn with_core_type_thresholds(mut self, core_type: CoreType, warning: u8, critical: u8) -> Result<Self, SensorError> {
+        if critical <= warning {
+            return Err(SensorError::config(format!(
+                "Critical {}-core threshold ({}) must be greater than warning threshold ({})",
+                core_type, critical, warning
+            )));
+        }
+        let thresholds = Some((f64::from(warning), f64::from(critical)));
+        match core_type {
+            CoreType::Performance => self.pcore_thresholds = thresholds,
+            CoreType::Efficiency => self.ecore_thresholds = thresholds,
+        }
+        Ok(self)
+    }
+    
+    /// Tooltip lines with the average usage of each core type.
+    fn format_core_type_lines(&self, core_usages: &[(usize, f64)]) -> String {
+        let Some(topology) = &self.topology else {
+            return String::new();
+        };
+        
+        [(CoreType::Performance, topology.performance.len()), (CoreType::Efficiency, topology.efficiency.len())]
+            .into_iter()
+            .filter_map(|(core_type, count)| {
+                let usage = topology.average_usage(core_type, core_usages)?;
+                Some(format!("\n{}", format::key_value(
+                    &format!("{}-cores", core_type),
+                    &format!("{:.1}% ({} threads)", usage, count),
+                    &self.config,
+                )))
+            })
+            .collect()
+    }
+    
+    /// Most severe class across the per-core-type thresholds, if any are set.
+    fn core_type_class(&self, core_usages: &[(usize, f64)]) -> Option<String> {
+        let topology = self.topology.as_ref()?;
+        let theme = &self.config.theme;
+        
+        [(CoreType::Performance, self.pcore_thresholds), (CoreType::Efficiency, self.ecore_thresholds)]
+            .into_iter()
+            .filter_map(|(core_type, thresholds)| {
+                let (warning, critical) = thresholds?;
+                let usage = topology.average_usage(core_type, core_usages)?;
+                Some(theme.class_for_thresholds(usage, warning, critical).to_owned())
+            })
+            .max_by_key(|class| diff::severity(Some(class), theme))
+    }
+    
+    /// Tooltip line with the throttle badge, shown while throttled.
+    fn format_throttle_line(&self, throttled: Option<(ThrottleReason, u64)>) -> Option<String> {
+        let (reason, events) = throttled?;
+        let mut value = format!("🔥 {}", reason);
+        if events > 0 {
+            value.push_str(&format!(" ({} new events)", events));
+        }
+        value.push_str(&format!(", throttled in {} samples", self.throttle.throttled_reads));
+        Some(format::key_value("Throttling", &value, &self.config))
+    }
+    
+    /// Read the package temperature, locating the hwmon sensor on first use.
+    ///
+    /// The lookup result is kept, including finding no sensor, so machines
+    /// without one don't rescan hwmon every tick. hwmon numbering can change
+    /// when drivers are reloaded, so a vanished input triggers one fresh
+    /// lookup on the next read.
+    fn read_temperature(&mut self) -> Option<f64> {
+        let temperature = self.temperature.get_or_insert_with(CpuTemperature::detect).as_ref()?;
+        let celsius = temperature.read_celsius();
+        if celsius.is_err() {
+            self.temperature = None;
+        }
+        celsius.ok()
+    }
+    
+    /// Set I/O wait percentage thresholds used by [`CpuMetric::IoWait`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thresholds are outside 0-100 or critical <= warning.
+    pub fn with_iowait_thresholds(mut self, warning: f64, critical: f64) -> Result<Self, SensorError> {
+        if warning < 0.0 || critical > 100.0 || critical <= warning {
+            return Err(SensorError::config(format!(
+                "Critical I/O wait ({}%) must be greater than warning I/O wait ({}%) and at most 100%",
+                critical, warning
+            )));
+        }
+        self.iowait_warning = warning;
+        self.iowait_critical = critical;
+        Ok(self)
+    }
+    
+    /// Build the tooltip section splitting busy time into its components.
+    fn format_breakdown_section(&self, breakdown: &CpuTimeBreakdown) -> String {
+        // Per-component colors from the `breakdown_colors` config key, so each
+        // is recognizable at a glance; otherwise the sparkline color
+        let colors = self.config.get_custom("breakdown_colors");
+        let color = |key: &str| {
+            colors.and_then(|colors| colors.get(key)).and_then(|v| v.as_str())
+                .or(self.config.sparkline_color.as_deref())
+        };
+        let components = [
+            ("User", breakdown.user, color("user")),
+            ("System", breakdown.system, color("system")),
+            ("I/O Wait", breakdown.iowait, color("iowait")),
+            ("IRQ", breakdown.irq, color("irq")),
+            ("Steal", breakdown.steal, color("steal")),
+        ];
+        
+        let section_header = format::key_only("Time Breakdown", &self.config);
+        let mut section = format!("\n\n{}", section_header);
+        
+        for (label, value, color) in components {
+            let gauge = format::colored_sparkline(&Self::create_gauge(value, 10), color);
+            let label = format::key_only(&format!("{:<8}", label), &self.config);
+            let value = format::value_only(&format!("{:5.1}%", value), &self.config);
+            section.push_str(&format!("\n  {} {} {}", label, gauge, value));
+        }
+        
+        section
+    }
+    
+    /// Build the top processes section from interval CPU usage.
+    ///
+    /// With the `show_process_core` config key each process is tagged with
+    /// the core it last ran on.
+    fn format_top_processes_section(&self, processes: &[ProcessCpu]) -> String {
+        let show_core = self.config.get_custom("show_process_core")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let max_name_length = self.config.visuals.process_name_max_length as usize;
+        
+        let top: Vec<(String, f64)> = processes.iter()
+            .take(self.config.visuals.top_processes_count as usize)
+            .map(|process| {
+                let mut name = process.name.clone();
+                if name.chars().count() > max_name_length {
+                    name = name.chars().take(max_name_length.saturating_sub(3)).collect();
+                    name.push_str("...");
+                }
+                if let Some(core) = process.processor.filter(|_| show_core) {
+                    name.push_str(&format!(" (core {})", core));
+                }
+                (name, process.cpu_percent)
+            })
+            .collect();
+        
+        format::format_top_processes(
+            &top,
+            "Top Processes by CPU",
+            self.config.tooltip_label_color.as_deref(),
+            self.config.tooltip_value_color.as_deref(),
+        )
+    }
+    
+    /// Format CPU pressure as a tooltip line.
+    fn format_pressure_line(&self, pressure: &PressureStats) -> String {
+        format::key_value(
+            "Pressure",
+            &format!("{:.1}% (10s) / {:.1}% (60s) stalled", pressure.some.avg10, pressure.some.avg60),
+            &self.config,
+        )
+    }
+    
+    /// Read the CPU pressure stall metric.
+    fn read_psi(&mut self) -> Result<WaybarOutput, SensorError> {
+        let pressure = match &self.cgroup {
+            Some(scope) => PressureStats::read_from_path(scope.dir.join("cpu.pressure"))?,
+            None => PressureStats::read("cpu")?,
+        };
+        let stalled = pressure.some.avg10;
+        
+        self.usage_history.push(stalled);
+        if self.usage_history.len() > self.config.visuals.sparkline_length {
+            self.usage_history.remove(0);
+        }
+        
+        self.last_reading = Some(
+            Reading::new(stalled, "%")
+                .with_extra("some_avg60", pressure.some.avg60)
+                .with_extra("some_avg300", pressure.some.avg300),
+        );
+        
+        let text = format::with_icon_and_colors(&format!("{:4.1}%", stalled), &self.config.icons.cpu, &self.config);
+        
+        let mut tooltip_lines = vec![
+            format::key_value("CPU Pressure", &format!("{:.2}% / {:.2}% / {:.2}% (10s/60s/300s)",
+                pressure.some.avg10, pressure.some.avg60, pressure.some.avg300), &self.config),
+        ];
+        if let Some(full) = pressure.full.filter(|f| f.total > 0) {
+            tooltip_lines.push(format::key_value("Fully Stalled", &format!("{:.2}% / {:.2}% / {:.2}%",
+                full.avg10, full.avg60, full.avg300), &self.config));
+        }
+        tooltip_lines.push(format::key_value("Thresholds", &format!("⚠️ {:.0}% / 🔴 {:.0}%",
+            self.psi_warning, self.psi_critical), &self.config));
+        
+        if self.config.visuals.sparklines && self.usage_history.len() > 1 {
+            let sparkline = format::create_sparkline(&self.usage_history, self.config.visuals.sparkline_style);
+            if !sparkline.is_empty() {
+                let colored_sparkline = format::colored_sparkline(&sparkline, self.config.sparkline_color.as_deref());
+                tooltip_lines.push(format::key_value("Pressure History", &colored_sparkline, &self.config));
+            }
+        }
+        
+        let percentage = ((stalled / self.psi_critical) * 100.0).round().clamp(0.0, 100.0) as u8;
+        
+        Ok(format::themed_output(
+            text,
+            Some(tooltip_lines.join("\n")),
+            Some(percentage),
+            stalled,
+            self.psi_warning,
+            self.psi_critical,
+            &self.config.theme,
+        ))
+    }
+    
+    /// Read the usage of the configured cgroup.
+    fn read_cgroup(&mut self) -> Result<WaybarOutput, SensorError> {
+        let system_cpus = Self::read_all_cpu_stats()?.1.len().max(1) as f64;
+        let Some(scope) = self.cgroup.as_mut() else {
+            return Err(SensorError::config("No cgroup configured"));
+        };
+        
+        let mut stat = cgroup::CpuStat::read(&scope.dir)?;
+        let mut now = Instant::now();
+        let (prev, prev_time) = match scope.prev {
+            Some(prev) => prev,
+            None => {
+                // First read - sleep and read again to get a delta
+                let first = (stat, now);
+                thread::sleep(self.min_sample_interval);
+                stat = cgroup::CpuStat::read(&scope.dir)?;
+                now = Instant::now();
+                first
+            }
+        };
+        scope.prev = Some((stat, now));
+        
+        let limit = cgroup::cpu_limit(&scope.dir);
+        let capacity = limit.unwrap_or(system_cpus).min(system_cpus);
+        let elapsed_usec = now.duration_since(prev_time).as_secs_f64() * 1_000_000.0;
+        let share = |usec: u64| if elapsed_usec > 0.0 {
+            (usec as f64 / (elapsed_usec * capacity) * 100.0).clamp(0.0, 100.0)
+        } else {
+            0.0
+        };
+        
+        let usage = share(stat.usage_usec.saturating_sub(prev.usage_usec));
+        let user = share(stat.user_usec.saturating_sub(prev.user_usec));
+        let system = share(stat.system_usec.saturating_sub(prev.system_usec));
+        let throttled = stat.nr_throttled.saturating_sub(prev.nr_throttled);
+        let cgroup_path = scope.path.clone();
+        
+        self.usage_history.push(usage);
+        if self.usage_history.len() > self.config.visuals.sparkline_length {
+            self.usage_history.remove(0);
+        }
+        
+        self.last_reading = Some(
+            Reading::new(usage, "%")
+                .with_extra("user", user)
+                .with_extra("system", system)
+                .with_extra("throttled_periods", throttled as f64),
+        );
+        
+        let text = format::with_icon_and_colors(&format!("{:3.0}%", usage), &self.config.icons.cpu, &self.config);
+        
+        let capacity_label = match limit {
+            Some(cpus) => format!("{:.2} CPUs (cpu.max)", cpus),
+            None => format!("{:.0} CPUs", system_cpus),
+        };
+        let mut tooltip_lines = vec![
+            format::key_value("cgroup", &cgroup_path, &self.config),
+            format::key_value("Usage", &format!("{:.1}% of {}", usage, capacity_label), &self.config),
+            format::key_value("User / System", &format!("{:.1}% / {:.1}%", user, system), &self.config),
+        ];
+        if limit.is_some() {
+            tooltip_lines.push(format::key_value(
+                "Throttled",
+                &format!("{} periods this interval ({:.1}s total)", throttled, stat.throttled_usec as f64 / 1_000_000.0),
+                &self.config,
+            ));
+        }
+        
+        if self.config.visuals.sparklines && self.usage_history.len() > 1 {
+            let sparkline = format::create_sparkline(&self.usage_history, self.config.visuals.sparkline_style);
+            if !sparkline.is_empty() {
+                let colored_sparkline = format::colored_sparkline(&sparkline, self.config.sparkline_color.as_deref());
+                tooltip_lines.push(format::key_value("Usage History", &colored_sparkline, &self.config));
+            }
+        }
+        
+        Ok(format::themed_output(
+            text,
+            Some(tooltip_lines.join("\n")),
+            Some(usage.round() as u8),
+            usage,
+            self.warning_threshold,
+            self.critical_threshold,
+            &self.config.theme,
+        ))
+    }
+    
+    /// Read the load average metric.
+    fn read_loadavg(&mut self) -> Result<WaybarOutput, SensorError> {
+        let load = LoadAverage::read()?;
+        let cores = Self::read_all_cpu_stats()
+            .map(|(_, cores)| cores.len())
+            .unwrap_or(1);
+        let (one, five, fifteen) = load.per_core(cores);
+        
+        self.usage_history.push(one);
+        if self.usage_history.len() > self.config.visuals.sparkline_length {
+            self.usage_history.remove(0);
+        }
+        
+        self.last_reading = Some(
+            Reading::new(one, "load/core")
+                .with_extra("load1", load.one)
+                .with_extra("load5", load.five)
+                .with_extra("load15", load.fifteen)
+                .with_extra("cores", cores as f64),
+        );
+        
+        let display_text = format!("{:.2} {:.2} {:.2}", one, five, fifteen);
+        let text = format::with_icon_and_colors(&display_text, &self.config.icons.cpu, &self.config);
+        
+        let mut tooltip_lines = vec![
+            format::key_value("Load (1/5/15 min)", &format!("{:.2} {:.2} {:.2}", load.one, load.five, load.fifteen), &self.config),
+            format::key_value("Per Core", &format!("{:.2} {:.2} {:.2}", one, five, fifteen), &self.config),
+            format::key_value("Cores", &cores.to_string(), &self.config),
+            format::key_value("Runnable", &format!("{} / {}", load.running, load.total), &self.config),
+            format::key_value("Thresholds", &format!("⚠️ {:.2} / 🔴 {:.2} per core", self.load_warning, self.load_critical), &self.config),
+        ];
+        
+        if self.config.visuals.sparklines && self.usage_history.len() > 1 {
+            let sparkline = format::create_sparkline(&self.usage_history, self.config.visuals.sparkline_style);
+            if !sparkline.is_empty() {
+                let colored_sparkline = format::colored_sparkline(&sparkline, self.config.sparkline_color.as_deref());
+                tooltip_lines.push(format::key_value("Load History", &colored_sparkline, &self.config));
+            }
+        }
+        
+        let percentage = ((one / self.load_critical) * 100.0).round().clamp(0.0, 100.0) as u8;
+        
+        Ok(format::themed_output(
+            text,
+            Some(tooltip_lines.join("\n")),
+            Some(percentage),
+            one,
+            self.load_warning,
+            self.load_critical,
+            &self.config.theme,
+        ))
+    }
+    
     /// Create a new CPU sensor with default thresholds (70% warning, 90% critical).
     pub fn with_defaults() -> Result<Self, SensorError> {
         Self::new(70, 90)
@@ -344,28 +1432,32 @@ impl CpuSensor {
     
     /// Read CPU statistics from `/proc/stat`.
     fn read_proc_stat() -> Result<CpuStats, SensorError> {
-        Self::read_proc_stat_from_path(Path::new(Self::PROC_STAT_PATH))
+        Self::read_proc_stat_from_path(&sysroot::path(Self::PROC_STAT_PATH))
     }
     
     /// Read CPU statistics from a specific path (useful for testing).
     fn read_proc_stat_from_path(path: &Path) -> Result<CpuStats, SensorError> {
-        let content = fs::read_to_string(path)?;
-        
-        let first_line = content.lines().next().ok_or_else(|| {
-            SensorError::invalid_data("Empty /proc/stat file")
-        })?;
-        
-        CpuStats::parse_from_proc_stat_line(first_line)
+        procfs::read_with(path, |content| {
+            let first_line = content.lines().next().ok_or_else(|| {
+                SensorError::invalid_data("Empty /proc/stat file")
+            })?;
+            
+            CpuStats::parse_from_proc_stat_line(first_line)
+        })?
     }
     
     /// Read all CPU statistics including per-core stats from `/proc/stat`.
     fn read_all_cpu_stats() -> Result<(CpuStats, Vec<PerCoreCpuStats>), SensorError> {
-        Self::read_all_cpu_stats_from_path(Path::new(Self::PROC_STAT_PATH))
+        Self::read_all_cpu_stats_from_path(&sysroot::path(Self::PROC_STAT_PATH))
     }
     
     /// Read all CPU statistics from a specific path (useful for testing).
     fn read_all_cpu_stats_from_path(path: &Path) -> Result<(CpuStats, Vec<PerCoreCpuStats>), SensorError> {
-        let content = fs::read_to_string(path)?;
+        procfs::read_with(path, Self::parse_all_cpu_stats)?
+    }
+    
+    /// Parse the overall and per-core CPU lines of `/proc/stat` content.
+    fn parse_all_cpu_stats(content: &str) -> Result<(CpuStats, Vec<PerCoreCpuStats>), SensorError> {
         let mut lines = content.lines();
         
         // First line should be the overall CPU stats
@@ -411,6 +1503,7 @@ impl CpuSensor {
                     // Read again after sleeping
                     let (current_stats, current_core_stats) = Self::read_all_cpu_stats()?;
                     let overall_usage = current_stats.usage_percent(prev_stats);
+                    self.last_breakdown = Some(current_stats.breakdown(prev_stats));
                     
                     // Calculate per-core usage
                     let mut core_usages = Vec::new();
@@ -425,6 +1518,7 @@ impl CpuSensor {
                     (overall_usage, core_usages)
                 } else {
                     let overall_usage = current_stats.usage_percent(prev_stats);
+                    self.last_breakdown = Some(current_stats.breakdown(prev_stats));
                     
                     // Calculate per-core usage
                     let mut core_usages = Vec::new();
@@ -444,6 +1538,7 @@ impl CpuSensor {
                 thread::sleep(self.min_sample_interval);
                 let (second_stats, second_core_stats) = Self::read_all_cpu_stats()?;
                 let overall_usage = second_stats.usage_percent(&current_stats);
+                self.last_breakdown = Some(second_stats.breakdown(&current_stats));
                 
                 // Calculate per-core usage
                 let mut core_usages = Vec::new();
@@ -467,74 +1562,149 @@ impl CpuSensor {
     }
 }
 
+impl Discoverable for CpuSensor {
+    const KIND: &'static str = "CPU cores";
+    
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        let (_, cores) = Self::read_all_cpu_stats()?;
+        let model = CpuInfo::from_proc_cpuinfo()
+            .map(|info| info.model_name)
+            .unwrap_or_else(|_| "CPU".to_string());
+        
+        Ok(cores.iter()
+            .map(|core| {
+                let id = format!("cpu{}", core.core_id);
+                let mut item = DiscoveredItem::new(id.clone(), model.clone());
+                let cpufreq = sysroot::path("/sys/devices/system/cpu").join(&id).join("cpufreq");
+                if let Ok(khz) = procfs::read_value::<u64>(cpufreq.join("scaling_cur_freq")) {
+                    item = item.with_detail("frequency", format::frequency_to_human(khz * 1000));
+                }
+                if let Ok(khz) = procfs::read_value::<u64>(cpufreq.join("cpuinfo_max_freq")) {
+                    item = item.with_detail("max frequency", format::frequency_to_human(khz * 1000));
+                }
+                item
+            })
+            .collect())
+    }
+}
+
 impl Sensor for CpuSensor {
     type Error = SensorError;
     
     fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        match self.metric {
+            CpuMetric::LoadAvg => return self.read_loadavg(),
+            CpuMetric::Psi => return self.read_psi(),
+            CpuMetric::Usage if self.cgroup.is_some() => return self.read_cgroup(),
+            CpuMetric::Usage | CpuMetric::IoWait => {}
+        }
+        
+        // Take the process baseline before the first usage sample so both
+        // cover the same interval
+        let show_processes = self.config.visuals.show_top_processes;
+        if show_processes && !self.process_tracker.is_primed() {
+            self.process_tracker.update();
+        }
+        
         let (usage, core_usages) = self.calculate_usage()?;
+        let breakdown = self.last_breakdown.unwrap_or_default();
+        let processes = if show_processes { self.process_tracker.update() } else { Vec::new() };
+        
+        // In iowait mode the bar, history and theming follow I/O wait instead of usage
+        let (value, warning, critical) = if self.metric == CpuMetric::IoWait {
+            (breakdown.iowait, self.iowait_warning, self.iowait_critical)
+        } else {
+            (usage, self.warning_threshold, self.critical_threshold)
+        };
         
         // Update usage history
-        self.usage_history.push(usage);
+        self.usage_history.push(value);
         if self.usage_history.len() > self.config.visuals.sparkline_length {
             self.usage_history.remove(0);
         }
         
+        let show_temperature = self.config.get_custom("show_temperature")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let frequency = CpuFrequency::read().ok();
+        // The throttle detector needs the temperature even when it isn't shown
+        let temperature = self.read_temperature();
+        let throttled = self.throttle.update(ThrottleCounters::read(), frequency.as_ref(), usage, temperature);
+        
+        let mut reading = Reading::new(value, "%")
+            .with_extra("throttled", if throttled.is_some() { 1.0 } else { 0.0 });
+        if let Some(celsius) = temperature {
+            reading = reading.with_extra("temperature", celsius);
+        }
+        self.last_reading = Some(core_usages.iter().fold(
+            reading
+                .with_extra("usage", usage)
+                .with_extra("user", breakdown.user)
+                .with_extra("system", breakdown.system)
+                .with_extra("iowait", breakdown.iowait)
+                .with_extra("irq", breakdown.irq)
+                .with_extra("steal", breakdown.steal),
+            |reading, &(core_id, core_usage)| reading.with_extra(format!("core{}", core_id), core_usage),
+        ));
+        
+        let show_frequency = self.config.get_custom("show_frequency")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        
         // Build the main text - just the percentage like other sensors
         let icon = &self.config.icons.cpu;
-        let display_text = format!("{:3.0}%", usage);
+        let mut display_text = match &frequency {
+            Some(freq) if show_frequency => format!(
+                "{:3.0}% {}",
+                value,
+                format::frequency_to_human((freq.average_mhz * 1_000_000.0) as u64)
+            ),
+            _ => format!("{:3.0}%", value),
+        };
+        if let Some(celsius) = temperature.filter(|_| show_temperature) {
+            display_text.push_str(&format!(" {:.0}°C", celsius));
+        }
         let text = format::with_icon_and_colors(&display_text, icon, &self.config);
         
+        let history_label = if self.metric == CpuMetric::IoWait { "I/O Wait History" } else { "Usage History" };
+        
         let tooltip = match Self::get_cpu_info() {
             Ok(info) => {
                 use waysensor_rs_core::format;
                 
-                let info_str = info.format_info_colored(&self.config);
+                let mut info_str = info.format_info_colored(&self.config);
+                if let Some(freq) = &frequency {
+                    info_str.push_str(&format!("\n{}", freq.format_info_colored(&self.config)));
+                }
+                if let Some(celsius) = temperature {
+                    info_str.push_str(&format!("\n{}", format::key_value("Package Temp", &format!("{:.1}°C", celsius), &self.config)));
+                }
+                if let Some(line) = self.format_throttle_line(throttled) {
+                    info_str.push_str(&format!("\n{}", line));
+                }
                 let overall_usage_line = format::key_value("Overall Usage", &format!("{:.1}%", usage), &self.config);
                 let mut tooltip_text = format!("{}\n{}", info_str, overall_usage_line);
+                tooltip_text.push_str(&self.format_core_type_lines(&core_usages));
+                if let Ok(pressure) = PressureStats::read("cpu") {
+                    tooltip_text.push_str(&format!("\n{}", self.format_pressure_line(&pressure)));
+                }
+                tooltip_text.push_str(&self.format_breakdown_section(&breakdown));
                 
                 // Add sparkline to tooltip if enabled and we have history
                 if self.config.visuals.sparklines && self.usage_history.len() > 1 {
                     let sparkline = format::create_sparkline(&self.usage_history, self.config.visuals.sparkline_style);
                     if !sparkline.is_empty() {
                         let colored_sparkline = format::colored_sparkline(&sparkline, self.config.sparkline_color.as_deref());
-                        let sparkline_line = format::key_value("Usage History", &colored_sparkline, &self.config);
+                        let sparkline_line = format::key_value(history_label, &colored_sparkline, &self.config);
                         tooltip_text.push_str(&format!("\n{}", sparkline_line));
                     }
                 }
                 
                 // Add per-core usage information with gauges
-                if !core_usages.is_empty() {
-                    let section_header = format::key_only("Per-Core Usage", &self.config);
-                    tooltip_text.push_str(&format!("\n\n{}", section_header));
-                    
-                    // Sort cores by ID for consistent display
-                    let mut sorted_cores = core_usages;
-                    sorted_cores.sort_by_key(|&(id, _)| id);
-                    
-                    // Display each core with a gauge
-                    for &(core_id, core_usage) in &sorted_cores {
-                        let gauge = Self::create_gauge(core_usage, 10);
-                        let indicator = Self::get_usage_indicator(core_usage);
-                        let core_label = format::key_only(&format!("Core {:2}", core_id), &self.config);
-                        let core_value = format::value_only(&format!("{} {:5.1}% {}", gauge, core_usage, indicator), &self.config);
-                        tooltip_text.push_str(&format!("\n  {} {}", core_label, core_value));
-                    }
-                }
+                tooltip_text.push_str(&self.format_per_core_section(&core_usages));
                 
                 // Add top processes by CPU if enabled
-                if self.config.visuals.show_top_processes {
-                    let top_processes = format::get_top_processes_by_cpu(
-                        self.config.visuals.top_processes_count as usize,
-                        self.config.visuals.process_name_max_length as usize
-                    );
-                    let processes_section = format::format_top_processes(
-                        &top_processes,
-                        "Top Processes by CPU",
-                        self.config.tooltip_label_color.as_deref(),
-                        self.config.tooltip_value_color.as_deref()
-                    );
-                    tooltip_text.push_str(&processes_section);
-                }
+                tooltip_text.push_str(&self.format_top_processes_section(&processes));
                 
                 Some(tooltip_text)
             }
@@ -543,65 +1713,70 @@ impl Sensor for CpuSensor {
                 
                 let usage_line = format::key_value("CPU Usage", &format!("{:.1}%", usage), &self.config);
                 let mut tooltip_text = usage_line;
+                tooltip_text.push_str(&self.format_core_type_lines(&core_usages));
+                if let Ok(pressure) = PressureStats::read("cpu") {
+                    tooltip_text.push_str(&format!("\n{}", self.format_pressure_line(&pressure)));
+                }
+                tooltip_text.push_str(&self.format_breakdown_section(&breakdown));
+                if let Some(freq) = &frequency {
+                    tooltip_text.push_str(&format!("\n{}", freq.format_info_colored(&self.config)));
+                }
+                if let Some(celsius) = temperature {
+                    tooltip_text.push_str(&format!("\n{}", format::key_value("Package Temp", &format!("{:.1}°C", celsius), &self.config)));
+                }
+                if let Some(line) = self.format_throttle_line(throttled) {
+                    tooltip_text.push_str(&format!("\n{}", line));
+                }
                 
                 // Add sparkline to tooltip if enabled and we have history
                 if self.config.visuals.sparklines && self.usage_history.len() > 1 {
                     let sparkline = format::create_sparkline(&self.usage_history, self.config.visuals.sparkline_style);
                     if !sparkline.is_empty() {
                         let colored_sparkline = format::colored_sparkline(&sparkline, self.config.sparkline_color.as_deref());
-                        let sparkline_line = format::key_value("Usage History", &colored_sparkline, &self.config);
+                        let sparkline_line = format::key_value(history_label, &colored_sparkline, &self.config);
                         tooltip_text.push_str(&format!("\n{}", sparkline_line));
                     }
                 }
                 
                 // Still try to show per-core usage even if cpuinfo fails
-                if !core_usages.is_empty() {
-                    let section_header = format::key_only("Per-Core Usage", &self.config);
-                    tooltip_text.push_str(&format!("\n\n{}", section_header));
-                    
-                    let mut sorted_cores = core_usages;
-                    sorted_cores.sort_by_key(|&(id, _)| id);
-                    
-                    // Display each core with a gauge
-                    for &(core_id, core_usage) in &sorted_cores {
-                        let gauge = Self::create_gauge(core_usage, 10);
-                        let indicator = Self::get_usage_indicator(core_usage);
-                        let core_label = format::key_only(&format!("Core {:2}", core_id), &self.config);
-                        let core_value = format::value_only(&format!("{} {:5.1}% {}", gauge, core_usage, indicator), &self.config);
-                        tooltip_text.push_str(&format!("\n  {} {}", core_label, core_value));
-                    }
-                }
+                tooltip_text.push_str(&self.format_per_core_section(&core_usages));
                 
                 // Add top processes by CPU if enabled
-                if self.config.visuals.show_top_processes {
-                    let top_processes = format::get_top_processes_by_cpu(
-                        self.config.visuals.top_processes_count as usize,
-                        self.config.visuals.process_name_max_length as usize
-                    );
-                    let processes_section = format::format_top_processes(
-                        &top_processes,
-                        "Top Processes by CPU",
-                        self.config.tooltip_label_color.as_deref(),
-                        self.config.tooltip_value_color.as_deref()
-                    );
-                    tooltip_text.push_str(&processes_section);
-                }
+                tooltip_text.push_str(&self.format_top_processes_section(&processes));
                 
                 Some(tooltip_text)
             }
         };
         
-        let percentage = usage.round().clamp(0.0, 100.0) as u8;
+        let percentage = value.round().clamp(0.0, 100.0) as u8;
         
-        Ok(format::themed_output(
+        let mut output = format::themed_output(
             text,
             tooltip,
             Some(percentage),
-            usage,
-            self.warning_threshold,
-            self.critical_threshold,
+            value,
+            warning,
+            critical,
             &self.config.theme,
-        ))
+        );
+        
+        // A saturated core type escalates the class even when the overall average looks fine
+        if self.metric == CpuMetric::Usage {
+            if let Some(class) = self.core_type_class(&core_usages)
+                .filter(|c| diff::severity(Some(c), &self.config.theme) > diff::severity(output.class.as_deref(), &self.config.theme))
+            {
+                output.class = Some(class);
+            }
+        }
+        
+        Ok(output)
+    }
+    
+    fn read_typed(&mut self) -> Result<(WaybarOutput, Reading), Self::Error> {
+        let output = self.read()?;
+        let reading = self.last_reading.clone()
+            .unwrap_or_else(|| Reading::from_output(&output));
+        Ok((output, reading))
     }
     
     fn name(&self) -> &str {
@@ -627,7 +1802,7 @@ impl Sensor for CpuSensor {
     
     fn check_availability(&self) -> Result<(), Self::Error> {
         // Check if /proc/stat exists and is readable
-        if !Path::new(Self::PROC_STAT_PATH).exists() {
+        if !sysroot::path(Self::PROC_STAT_PATH).exists() {
             return Err(SensorError::unavailable(format!(
                 "{} does not exist (not a Linux system?)", 
                 Self::PROC_STAT_PATH
@@ -752,4 +1927,208 @@ cpu MHz         : 3700.000
         assert!(PerCoreCpuStats::parse_from_proc_stat_line("cpu  1 2 3 4").is_err());
         assert!(PerCoreCpuStats::parse_from_proc_stat_line("notcpu0 1 2 3 4").is_err());
     }
+
+    #[test]
+    fn test_cpu_frequency_from_sysfs() {
+        let dir = tempfile::tempdir().unwrap();
+        for (policy, khz, governor) in [("policy0", "1000000", "schedutil"), ("policy1", "3000000", "schedutil")] {
+            let policy_dir = dir.path().join(policy);
+            fs::create_dir(&policy_dir).unwrap();
+            fs::write(policy_dir.join("scaling_cur_freq"), khz).unwrap();
+            fs::write(policy_dir.join("scaling_governor"), governor).unwrap();
+        }
+        
+        let freq = CpuFrequency::read_from_path(dir.path()).unwrap();
+        assert_eq!(freq.average_mhz, 2000.0);
+        assert_eq!(freq.max_mhz, 3000.0);
+        assert_eq!(freq.governor.as_deref(), Some("schedutil"));
+        
+        let empty = tempfile::tempdir().unwrap();
+        assert!(CpuFrequency::read_from_path(empty.path()).is_err());
+    }
+
+    #[test]
+    fn test_throttle_detection() {
+        let cpu = tempfile::tempdir().unwrap();
+        for id in 0..2 {
+            let throttle = cpu.path().join(format!("cpu{}/thermal_throttle", id));
+            fs::create_dir_all(&throttle).unwrap();
+            fs::write(throttle.join("core_throttle_count"), "3\n").unwrap();
+            fs::write(throttle.join("package_throttle_count"), "5\n").unwrap();
+        }
+        fs::create_dir(cpu.path().join("cpufreq")).unwrap();
+        let counters = ThrottleCounters::read_from_path(cpu.path()).unwrap();
+        assert_eq!(counters, ThrottleCounters { core: 6, package: 5 });
+        assert!(ThrottleCounters::read_from_path(tempfile::tempdir().unwrap().path()).is_none());
+        
+        let freq = |current: f64, scaling_max: f64| CpuFrequency {
+            average_mhz: current,
+            max_mhz: current,
+            governor: None,
+            hardware_max_mhz: Some(5000.0),
+            scaling_max_mhz: Some(scaling_max),
+        };
+        let mut detector = ThrottleDetector::default();
+        
+        // Baseline sample, full clocks
+        assert_eq!(detector.update(Some(counters), Some(&freq(4800.0, 5000.0)), 95.0, Some(70.0)), None);
+        // New hardware events
+        let more = ThrottleCounters { core: 8, package: 5 };
+        assert_eq!(detector.update(Some(more), Some(&freq(4800.0, 5000.0)), 95.0, Some(70.0)), Some((ThrottleReason::Thermal, 2)));
+        // A lowered limit only counts while the clocks run into it
+        assert_eq!(detector.update(Some(more), Some(&freq(1200.0, 2500.0)), 10.0, None), None);
+        assert_eq!(detector.update(Some(more), Some(&freq(2500.0, 2500.0)), 95.0, None), Some((ThrottleReason::FrequencyCap, 0)));
+        // Loaded and slow: hot means thermal, cool means power limit
+        assert_eq!(detector.update(None, Some(&freq(2000.0, 5000.0)), 95.0, Some(97.0)), Some((ThrottleReason::Thermal, 0)));
+        assert_eq!(detector.update(None, Some(&freq(2000.0, 5000.0)), 95.0, Some(60.0)), Some((ThrottleReason::PowerLimit, 0)));
+        // Without a temperature the cause can't be told apart
+        assert_eq!(detector.update(None, Some(&freq(2000.0, 5000.0)), 95.0, None), Some((ThrottleReason::Unknown, 0)));
+        // Idle at low clocks is normal
+        assert_eq!(detector.update(None, Some(&freq(800.0, 5000.0)), 5.0, Some(40.0)), None);
+        assert_eq!(detector.throttled_reads, 5);
+    }
+    
+    #[test]
+    fn test_missing_cgroup_is_rejected() {
+        let sensor = CpuSensor::with_defaults().unwrap();
+        assert!(sensor.with_cgroup("waysensor-test-missing.slice").is_err());
+    }
+    
+    #[test]
+    fn test_cpu_temperature_detection() {
+        let hwmon = tempfile::tempdir().unwrap();
+        let add_device = |dir: &str, name: &str, temps: &[(u32, Option<&str>, &str)]| {
+            let path = hwmon.path().join(dir);
+            fs::create_dir(&path).unwrap();
+            fs::write(path.join("name"), format!("{}\n", name)).unwrap();
+            for &(index, label, value) in temps {
+                fs::write(path.join(format!("temp{}_input", index)), value).unwrap();
+                if let Some(label) = label {
+                    fs::write(path.join(format!("temp{}_label", index)), label).unwrap();
+                }
+            }
+        };
+        add_device("hwmon0", "nvme", &[(1, Some("Composite"), "38850")]);
+        add_device("hwmon1", "coretemp", &[(2, Some("Core 0"), "55000"), (1, Some("Package id 0"), "71000")]);
+        
+        let temp = CpuTemperature::detect_in(hwmon.path()).unwrap();
+        assert_eq!(temp.driver, "coretemp");
+        assert_eq!(temp.input_path, hwmon.path().join("hwmon1/temp1_input"));
+        assert!((temp.read_celsius().unwrap() - 71.0).abs() < f64::EPSILON);
+        
+        // k10temp wins over other drivers and picks Tctl over CCD sensors
+        add_device("hwmon2", "k10temp", &[(3, Some("Tccd1"), "60000"), (1, Some("Tctl"), "65125")]);
+        let temp = CpuTemperature::detect_in(hwmon.path()).unwrap();
+        assert_eq!(temp.input_path, hwmon.path().join("hwmon2/temp1_input"));
+        
+        let empty = tempfile::tempdir().unwrap();
+        assert!(CpuTemperature::detect_in(empty.path()).is_none());
+    }
+    
+    #[test]
+    fn test_core_topology_detection() {
+        assert_eq!(CoreTopology::parse_cpu_list("0-3,8,10-11\n"), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert!(CoreTopology::parse_cpu_list("").is_empty());
+        
+        // Intel hybrid: separate PMUs
+        let intel = tempfile::tempdir().unwrap();
+        for (pmu, cpus) in [("cpu_core", "0-3"), ("cpu_atom", "4-7")] {
+            fs::create_dir(intel.path().join(pmu)).unwrap();
+            fs::write(intel.path().join(pmu).join("cpus"), cpus).unwrap();
+        }
+        let topology = CoreTopology::detect_from(intel.path()).unwrap();
+        assert_eq!(topology.performance, vec![0, 1, 2, 3]);
+        assert_eq!(topology.efficiency, vec![4, 5, 6, 7]);
+        assert_eq!(topology.core_type(5), Some(CoreType::Efficiency));
+        assert_eq!(
+            topology.average_usage(CoreType::Performance, &[(0, 80.0), (1, 40.0), (4, 10.0)]),
+            Some(60.0)
+        );
+        
+        // Zen + Zen c: told apart by max frequency, boost binning ignored
+        let amd = tempfile::tempdir().unwrap();
+        for (cpu, khz) in [(0, "5100000"), (1, "4900000"), (2, "3500000"), (3, "3500000")] {
+            let cpufreq = amd.path().join("system/cpu").join(format!("cpu{}", cpu)).join("cpufreq");
+            fs::create_dir_all(&cpufreq).unwrap();
+            fs::write(cpufreq.join("cpuinfo_max_freq"), khz).unwrap();
+        }
+        let topology = CoreTopology::detect_from(amd.path()).unwrap();
+        assert_eq!(topology.performance, vec![0, 1]);
+        assert_eq!(topology.efficiency, vec![2, 3]);
+        
+        // Homogeneous CPU
+        fs::write(amd.path().join("system/cpu/cpu2/cpufreq/cpuinfo_max_freq"), "5000000").unwrap();
+        fs::write(amd.path().join("system/cpu/cpu3/cpufreq/cpuinfo_max_freq"), "5000000").unwrap();
+        assert!(CoreTopology::detect_from(amd.path()).is_none());
+    }
+    
+    #[test]
+    fn test_cpu_time_breakdown() {
+        let prev = CpuStats::parse_from_proc_stat_line("cpu  100 0 100 700 0 0 0 100").unwrap();
+        let curr = CpuStats::parse_from_proc_stat_line("cpu  150 10 120 760 40 5 5 110").unwrap();
+        
+        let breakdown = curr.breakdown(&prev);
+        assert!((breakdown.user - 30.0).abs() < 0.01);
+        assert!((breakdown.system - 10.0).abs() < 0.01);
+        assert!((breakdown.iowait - 20.0).abs() < 0.01);
+        assert!((breakdown.irq - 5.0).abs() < 0.01);
+        assert!((breakdown.steal - 5.0).abs() < 0.01);
+        
+        assert_eq!(prev.breakdown(&prev), CpuTimeBreakdown::default());
+    }
+    
+    #[test]
+    fn test_loadavg_parsing() {
+        let load = LoadAverage::parse_loadavg_content("0.52 1.48 2.40 2/345 12345\n").unwrap();
+        assert_eq!(load.one, 0.52);
+        assert_eq!(load.five, 1.48);
+        assert_eq!(load.fifteen, 2.40);
+        assert_eq!(load.running, 2);
+        assert_eq!(load.total, 345);
+        
+        let (one, five, fifteen) = load.per_core(4);
+        assert!((one - 0.13).abs() < 1e-9);
+        assert!((five - 0.37).abs() < 1e-9);
+        assert!((fifteen - 0.60).abs() < 1e-9);
+        
+        assert!(LoadAverage::parse_loadavg_content("0.52 1.48").is_err());
+        assert!(LoadAverage::parse_loadavg_content("a b c 1/2 3").is_err());
+    }
+
+    #[test]
+    fn test_cpu_metric_and_load_thresholds() {
+        assert_eq!("loadavg".parse::<CpuMetric>().unwrap(), CpuMetric::LoadAvg);
+        assert_eq!("usage".parse::<CpuMetric>().unwrap(), CpuMetric::Usage);
+        assert_eq!("psi".parse::<CpuMetric>().unwrap(), CpuMetric::Psi);
+        assert_eq!("iowait".parse::<CpuMetric>().unwrap(), CpuMetric::IoWait);
+        assert!("bogus".parse::<CpuMetric>().is_err());
+        
+        assert!(CpuSensor::with_defaults().unwrap().with_psi_thresholds(10.0, 25.0).is_ok());
+        assert!(CpuSensor::with_defaults().unwrap().with_psi_thresholds(25.0, 10.0).is_err());
+        assert!(CpuSensor::with_defaults().unwrap().with_psi_thresholds(10.0, 150.0).is_err());
+        
+        let sensor = CpuSensor::with_defaults().unwrap()
+            .with_metric(CpuMetric::LoadAvg)
+            .with_load_thresholds(0.7, 1.5)
+            .unwrap();
+        assert_eq!(sensor.metric, CpuMetric::LoadAvg);
+        assert_eq!(sensor.load_warning, 0.7);
+        
+        assert!(CpuSensor::with_defaults().unwrap().with_load_thresholds(2.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_select_cores_for_display() {
+        let cores = vec![(0, 10.0), (1, 90.0), (2, 50.0), (3, 70.0)];
+        
+        // 0 means show everything
+        assert_eq!(CpuSensor::select_cores_for_display(&cores, 0).len(), 4);
+        assert_eq!(CpuSensor::select_cores_for_display(&cores, 8).len(), 4);
+        
+        // Busiest cores are kept, ordered by core ID
+        let shown = CpuSensor::select_cores_for_display(&cores, 2);
+        assert_eq!(shown, vec![(1, 90.0), (3, 70.0)]);
+        
+        assert_eq!(CpuSensor::create_heat_map(&cores), "▂▇▅▆");
+    }
 }
\ No newline at end of file
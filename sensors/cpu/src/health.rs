@@ -0,0 +1,409 @@
+//! Kernel taint and hardware error monitoring.
+//!
+//! Decodes `/proc/sys/kernel/tainted` into readable reasons, lists the modules
+//! responsible, and counts machine-check exceptions (`/proc/interrupts`) and
+//! EDAC memory controller errors so new hardware errors can be flagged as
+//! they happen rather than discovered in `dmesg` later.
+//!
+//! Taint bits and error counters only ever grow until reboot, so one-off
+//! events (new hardware errors, a kernel warning) raise the class for an
+//! alert window and then settle back, while staying listed in the tooltip.
+
+use waysensor_rs_core::{format, procfs, sysroot, Reading, Sensor, SensorConfig, SensorError, WaybarOutput};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Default time a one-off event keeps the sensor in a warning/critical class.
+const DEFAULT_ALERT_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Taints recording a single event rather than a lasting condition. They
+/// raise the class only for the alert window after they first appear.
+const TRANSIENT_TAINTS: &[char] = &['W'];
+
+/// How much a taint flag should worry the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TaintSeverity {
+    /// Expected on many systems (proprietary or out-of-tree drivers)
+    Info,
+    /// The kernel ran into something unusual
+    Warning,
+    /// The kernel or hardware has misbehaved
+    Critical,
+}
+
+/// One bit of the kernel taint mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaintFlag {
+    /// Bit number in `/proc/sys/kernel/tainted`
+    pub bit: u32,
+    /// Letter used in oops reports and `/sys/module/*/taint`
+    pub letter: char,
+    /// Human-readable reason
+    pub reason: &'static str,
+    pub severity: TaintSeverity,
+}
+
+/// Taint flags as documented in the kernel's `tainted-kernels.rst`.
+pub const TAINT_FLAGS: &[TaintFlag] = &[
+    TaintFlag { bit: 0, letter: 'P', reason: "proprietary module loaded", severity: TaintSeverity::Info },
+    TaintFlag { bit: 1, letter: 'F', reason: "module was force loaded", severity: TaintSeverity::Warning },
+    TaintFlag { bit: 2, letter: 'S', reason: "kernel running on out-of-spec system", severity: TaintSeverity::Warning },
+    TaintFlag { bit: 3, letter: 'R', reason: "module was force unloaded", severity: TaintSeverity::Warning },
+    TaintFlag { bit: 4, letter: 'M', reason: "machine check exception occurred", severity: TaintSeverity::Critical },
+    TaintFlag { bit: 5, letter: 'B', reason: "bad page referenced or unexpected page flags", severity: TaintSeverity::Critical },
+    TaintFlag { bit: 6, letter: 'U', reason: "taint requested by userspace", severity: TaintSeverity::Warning },
+    TaintFlag { bit: 7, letter: 'D', reason: "kernel died recently (OOPS or BUG)", severity: TaintSeverity::Critical },
+    TaintFlag { bit: 8, letter: 'A', reason: "ACPI table overridden", severity: TaintSeverity::Warning },
+    TaintFlag { bit: 9, letter: 'W', reason: "kernel issued a warning", severity: TaintSeverity::Warning },
+    TaintFlag { bit: 10, letter: 'C', reason: "staging driver loaded", severity: TaintSeverity::Warning },
+    TaintFlag { bit: 11, letter: 'I', reason: "working around platform firmware bug", severity: TaintSeverity::Warning },
+    TaintFlag { bit: 12, letter: 'O', reason: "out-of-tree module loaded", severity: TaintSeverity::Info },
+    TaintFlag { bit: 13, letter: 'E', reason: "unsigned module loaded", severity: TaintSeverity::Info },
+    TaintFlag { bit: 14, letter: 'L', reason: "soft lockup occurred", severity: TaintSeverity::Critical },
+    TaintFlag { bit: 15, letter: 'K', reason: "kernel has been live patched", severity: TaintSeverity::Info },
+    TaintFlag { bit: 16, letter: 'X', reason: "auxiliary taint (distribution defined)", severity: TaintSeverity::Info },
+    TaintFlag { bit: 17, letter: 'T', reason: "built with struct randomization plugin", severity: TaintSeverity::Info },
+    TaintFlag { bit: 18, letter: 'N', reason: "in-kernel test has been run", severity: TaintSeverity::Warning },
+];
+
+/// Flags set in a taint mask. Unknown bits are ignored.
+pub fn decode_taint(mask: u64) -> Vec<&'static TaintFlag> {
+    TAINT_FLAGS.iter()
+        .filter(|flag| mask & (1 << flag.bit) != 0)
+        .collect()
+}
+
+/// Modules that tainted the kernel, with their taint letters, sorted by name.
+pub fn tainted_modules() -> Vec<(String, String)> {
+    tainted_modules_in(&sysroot::path("/sys/module"))
+}
+
+/// Tainting modules below a sysfs `module` directory (useful for testing).
+pub fn tainted_modules_in(module_dir: &Path) -> Vec<(String, String)> {
+    let Ok(entries) = fs::read_dir(module_dir) else {
+        return Vec::new();
+    };
+    let mut modules: Vec<(String, String)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let taint = fs::read_to_string(entry.path().join("taint")).ok()?;
+            let taint = taint.trim();
+            (!taint.is_empty()).then(|| (entry.file_name().to_string_lossy().into_owned(), taint.to_owned()))
+        })
+        .collect();
+    modules.sort();
+    modules
+}
+
+/// Total machine-check exceptions across all CPUs from `/proc/interrupts`.
+pub fn parse_mce_count(interrupts: &str) -> Option<u64> {
+    let line = interrupts.lines().find(|line| line.trim_start().starts_with("MCE:"))?;
+    Some(line.split_whitespace()
+        .skip(1)
+        .map_while(|field| field.parse::<u64>().ok())
+        .sum())
+}
+
+/// Hardware error counters at one point in time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HardwareErrors {
+    /// Machine-check exceptions since boot
+    pub mce: u64,
+    /// Corrected memory errors reported by EDAC
+    pub corrected: u64,
+    /// Uncorrected memory errors reported by EDAC
+    pub uncorrected: u64,
+}
+
+impl HardwareErrors {
+    /// Read the running system's counters. Missing sources count as zero.
+    pub fn read() -> Self {
+        let mce = procfs::read_with(sysroot::path("/proc/interrupts"), parse_mce_count)
+            .ok()
+            .flatten()
+            .unwrap_or(0);
+        let (corrected, uncorrected) = edac_counts_in(&sysroot::path("/sys/devices/system/edac/mc"));
+        Self { mce, corrected, uncorrected }
+    }
+
+    /// Errors that occurred since `earlier` was taken.
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            mce: self.mce.saturating_sub(earlier.mce),
+            corrected: self.corrected.saturating_sub(earlier.corrected),
+            uncorrected: self.uncorrected.saturating_sub(earlier.uncorrected),
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.mce + self.corrected + self.uncorrected
+    }
+}
+
+/// Sum of corrected and uncorrected error counts over all EDAC memory controllers.
+pub fn edac_counts_in(edac_dir: &Path) -> (u64, u64) {
+    let Ok(entries) = fs::read_dir(edac_dir) else {
+        return (0, 0);
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("mc"))
+        .map(|entry| {
+            let count = |file: &str| procfs::read_value::<u64>(entry.path().join(file)).unwrap_or(0);
+            (count("ce_count"), count("ue_count"))
+        })
+        .fold((0, 0), |(ce, ue), (c, u)| (ce + c, ue + u))
+}
+
+/// Sensor reporting kernel taint and new hardware errors.
+#[derive(Debug)]
+pub struct KernelHealthSensor {
+    name: String,
+    config: SensorConfig,
+    alert_window: Duration,
+    /// Counters new errors are counted from, moved up once the alert expires
+    baseline: Option<HardwareErrors>,
+    previous: Option<HardwareErrors>,
+    /// When the error counters last grew
+    last_error: Option<Instant>,
+    /// When each transient taint was first seen
+    transient_seen: Vec<(char, Instant)>,
+    last_reading: Option<Reading>,
+}
+
+impl KernelHealthSensor {
+    pub fn new() -> Self {
+        Self {
+            name: "kernel-health".to_string(),
+            config: SensorConfig::default(),
+            alert_window: DEFAULT_ALERT_WINDOW,
+            baseline: None,
+            previous: None,
+            last_error: None,
+            transient_seen: Vec::new(),
+            last_reading: None,
+        }
+    }
+
+    /// Hardware errors within the alert window: counted from startup, and
+    /// from the current counters again once no error occurred for a window.
+    fn track_errors(&mut self, errors: HardwareErrors, now: Instant) -> HardwareErrors {
+        let baseline = *self.baseline.get_or_insert(errors);
+        if self.previous.replace(errors).is_some_and(|previous| errors.since(&previous).total() > 0) {
+            self.last_error = Some(now);
+        }
+        match self.last_error {
+            Some(last) if now.duration_since(last) >= self.alert_window => {
+                self.baseline = Some(errors);
+                self.last_error = None;
+                HardwareErrors::default()
+            }
+            _ => errors.since(&baseline),
+        }
+    }
+
+    /// Effective severity of a taint flag: transient taints count as
+    /// informational once the alert window after their appearance is over.
+    fn severity(&mut self, flag: &TaintFlag, now: Instant) -> TaintSeverity {
+        if !TRANSIENT_TAINTS.contains(&flag.letter) {
+            return flag.severity;
+        }
+        let seen = match self.transient_seen.iter().find(|(letter, _)| *letter == flag.letter) {
+            Some((_, seen)) => *seen,
+            None => {
+                self.transient_seen.push((flag.letter, now));
+                now
+            }
+        };
+        if now.duration_since(seen) < self.alert_window {
+            flag.severity
+        } else {
+            TaintSeverity::Info
+        }
+    }
+}
+
+impl Default for KernelHealthSensor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sensor for KernelHealthSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let mask: u64 = procfs::read_value(sysroot::path("/proc/sys/kernel/tainted"))?;
+        let flags = decode_taint(mask);
+        let errors = HardwareErrors::read();
+        let now = Instant::now();
+        let new_errors = self.track_errors(errors, now);
+        let severities: Vec<TaintSeverity> = flags.iter().map(|flag| self.severity(flag, now)).collect();
+
+        let letters: String = flags.iter().map(|flag| flag.letter).collect();
+        let status = if new_errors.total() > 0 {
+            format!("{} new HW errors", new_errors.total())
+        } else if letters.is_empty() {
+            "clean".to_string()
+        } else {
+            letters.clone()
+        };
+        let text = format::with_icon_and_colors(&status, &self.config.icons.cpu, &self.config);
+
+        let mut lines = vec![
+            format::key_only("Kernel Health", &self.config),
+            format::key_value("Taint", &if mask == 0 {
+                "not tainted".to_string()
+            } else {
+                format!("{} ({})", letters, mask)
+            }, &self.config),
+        ];
+        for (flag, severity) in flags.iter().zip(&severities) {
+            let marker = match severity {
+                TaintSeverity::Info => "ℹ️",
+                TaintSeverity::Warning => "⚠️",
+                TaintSeverity::Critical => "🔴",
+            };
+            lines.push(format::key_value(&format!("  {}", flag.letter), &format!("{} {}", marker, flag.reason), &self.config));
+        }
+
+        let modules = tainted_modules();
+        if !modules.is_empty() {
+            let list: Vec<String> = modules.iter()
+                .map(|(module, taint)| format!("{} ({})", module, taint))
+                .collect();
+            lines.push(format::key_value("Tainting Modules", &list.join(", "), &self.config));
+        }
+
+        lines.push(String::new());
+        lines.push(format::key_only("Hardware Errors", &self.config));
+        lines.push(format::key_value("Machine Checks", &format!("{} ({} new)", errors.mce, new_errors.mce), &self.config));
+        if errors.corrected + errors.uncorrected > 0 || sysroot::path("/sys/devices/system/edac/mc/mc0").exists() {
+            lines.push(format::key_value("Memory (EDAC)", &format!("{} corrected ({} new), {} uncorrected ({} new)",
+                errors.corrected, new_errors.corrected, errors.uncorrected, new_errors.uncorrected), &self.config));
+        }
+
+        let theme = &self.config.theme;
+        let severity = severities.iter().copied().max();
+        let class = if new_errors.uncorrected > 0 || severity == Some(TaintSeverity::Critical) {
+            &theme.critical
+        } else if new_errors.total() > 0 || severity == Some(TaintSeverity::Warning) {
+            &theme.warning
+        } else {
+            &theme.normal
+        };
+
+        self.last_reading = Some(
+            Reading::new(mask as f64, "mask")
+                .with_extra("mce", errors.mce as f64)
+                .with_extra("new_hw_errors", new_errors.total() as f64),
+        );
+
+        Ok(WaybarOutput {
+            text,
+            tooltip: Some(lines.join("\n")),
+            class: Some(class.clone()),
+            percentage: None,
+        })
+    }
+
+    fn read_typed(&mut self) -> Result<(WaybarOutput, Reading), Self::Error> {
+        let output = self.read()?;
+        let reading = self.last_reading.clone()
+            .unwrap_or_else(|| Reading::from_output(&output));
+        Ok((output, reading))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        if let Some(mins) = config.get_custom("health_alert_mins").and_then(|v| v.as_u64()) {
+            self.alert_window = Duration::from_secs(mins * 60);
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        procfs::read_value::<u64>(sysroot::path("/proc/sys/kernel/tainted"))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_taint_decoding() {
+        // P + O + E, typical for the NVIDIA driver
+        let letters: String = decode_taint(0x3001).iter().map(|f| f.letter).collect();
+        assert_eq!(letters, "POE");
+        assert!(decode_taint(0).is_empty());
+
+        let flags = decode_taint((1 << 9) | (1 << 7));
+        assert_eq!(flags.iter().map(|f| f.letter).collect::<String>(), "DW");
+        assert_eq!(flags.iter().map(|f| f.severity).max(), Some(TaintSeverity::Critical));
+    }
+
+    #[test]
+    fn test_hardware_error_counting() {
+        let interrupts = "            CPU0       CPU1\n\
+            NMI:          3          2   Non-maskable interrupts\n\
+            MCE:          1          2   Machine check exceptions\n\
+            MCP:         40         40   Machine check polls\n";
+        assert_eq!(parse_mce_count(interrupts), Some(3));
+        assert_eq!(parse_mce_count("NMI: 0 0\n"), None);
+
+        let before = HardwareErrors { mce: 3, corrected: 10, uncorrected: 0 };
+        let after = HardwareErrors { mce: 3, corrected: 12, uncorrected: 1 };
+        assert_eq!(after.since(&before), HardwareErrors { mce: 0, corrected: 2, uncorrected: 1 });
+        assert_eq!(after.since(&before).total(), 3);
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        for (mc, ce, ue) in [("mc0", "4\n", "0\n"), ("mc1", "1\n", "2\n")] {
+            fs::create_dir_all(dir.join(mc)).unwrap();
+            fs::write(dir.join(mc).join("ce_count"), ce).unwrap();
+            fs::write(dir.join(mc).join("ue_count"), ue).unwrap();
+        }
+        fs::create_dir_all(dir.join("power")).unwrap();
+        let counts = edac_counts_in(dir);
+        assert_eq!(counts, (5, 2));
+    }
+
+    #[test]
+    fn test_alerts_expire_after_window() {
+        let mut sensor = KernelHealthSensor::new();
+        let start = Instant::now();
+        let at = |mins: u64| start + Duration::from_secs(mins * 60);
+
+        let clean = HardwareErrors { mce: 1, corrected: 0, uncorrected: 0 };
+        assert_eq!(sensor.track_errors(clean, at(0)).total(), 0);
+
+        let errored = HardwareErrors { mce: 1, corrected: 2, uncorrected: 0 };
+        assert_eq!(sensor.track_errors(errored, at(10)).corrected, 2);
+        assert_eq!(sensor.track_errors(errored, at(69)).corrected, 2);
+        // An hour without further errors clears the alert
+        assert_eq!(sensor.track_errors(errored, at(70)).total(), 0);
+
+        let again = HardwareErrors { mce: 1, corrected: 3, uncorrected: 0 };
+        assert_eq!(sensor.track_errors(again, at(80)).corrected, 1);
+
+        let warning = decode_taint(1 << 9)[0];
+        let proprietary = decode_taint(1)[0];
+        assert_eq!(sensor.severity(warning, at(0)), TaintSeverity::Warning);
+        assert_eq!(sensor.severity(warning, at(59)), TaintSeverity::Warning);
+        assert_eq!(sensor.severity(warning, at(60)), TaintSeverity::Info);
+        assert_eq!(sensor.severity(proprietary, at(600)), TaintSeverity::Info);
+        let died = decode_taint(1 << 7)[0];
+        assert_eq!(sensor.severity(died, at(600)), TaintSeverity::Critical);
+    }
+}
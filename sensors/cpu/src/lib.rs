@@ -7,7 +7,7 @@
 //! # Examples
 //!
 //! ```rust
-//! use waysensor_cpu::CpuSensor;
+//! use waysensor_rs_cpu::CpuSensor;
 //! use waysensor_rs_core::Sensor;
 //!
 //! // Create a CPU sensor with 70% warning and 90% critical thresholds
@@ -20,5 +20,7 @@
 //! ```
 
 pub mod cpu;
+pub mod health;
 
-pub use cpu::{CpuInfo, CpuSensor, CpuStats};
\ No newline at end of file
+pub use cpu::{CoreTopology, CoreType, CpuFrequency, CpuInfo, CpuMetric, CpuSensor, CpuStats, CpuTemperature, CpuTimeBreakdown, LoadAverage, ThrottleCounters, ThrottleDetector, ThrottleReason};
+pub use health::{HardwareErrors, KernelHealthSensor, TaintFlag, TaintSeverity};
\ No newline at end of file
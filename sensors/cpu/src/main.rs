@@ -4,12 +4,12 @@
 //! It outputs JSON-formatted data compatible with Waybar's custom modules.
 
 use clap::Parser;
-use waysensor_rs_core::{GlobalConfig, IconStyle, Sensor, SensorConfig};
-use waysensor_rs_cpu::CpuSensor;
+use waysensor_rs_core::{discovery, schedule, session_lock, sysroot, GlobalConfig, IconStyle, Sensor, SensorConfig, SensorError};
+use waysensor_rs_cpu::{CoreType, CpuMetric, CpuSensor, KernelHealthSensor};
 use std::io::{self, Write};
+use std::path::PathBuf;
 use std::process;
 use std::time::Duration;
-use tokio::time;
 
 /// Command-line arguments for the CPU sensor.
 #[derive(Parser)]
@@ -30,10 +30,82 @@ struct Args {
     #[arg(short, long, default_value = "90", value_parser = validate_percentage)]
     critical: u8,
 
+    /// Report kernel taint and new machine-check/EDAC errors instead of CPU usage
+    #[arg(long)]
+    kernel_health: bool,
+
+    /// Metric shown in the bar (usage, loadavg, psi, iowait)
+    #[arg(short, long, default_value = "usage")]
+    metric: CpuMetric,
+
+    /// Load-per-core warning threshold (loadavg metric)
+    #[arg(long, default_value = "1.0")]
+    load_warning: f64,
+
+    /// Load-per-core critical threshold (loadavg metric, must be > load warning)
+    #[arg(long, default_value = "2.0")]
+    load_critical: f64,
+
+    /// CPU pressure warning threshold in percent stalled (psi metric)
+    #[arg(long, default_value = "10")]
+    psi_warning: f64,
+
+    /// CPU pressure critical threshold in percent stalled (psi metric, must be > psi warning)
+    #[arg(long, default_value = "25")]
+    psi_critical: f64,
+
+    /// I/O wait warning threshold in percent (iowait metric)
+    #[arg(long, default_value = "10")]
+    iowait_warning: f64,
+
+    /// I/O wait critical threshold in percent (iowait metric, must be > iowait warning)
+    #[arg(long, default_value = "25")]
+    iowait_critical: f64,
+
+    /// Warning threshold for the P-core average on hybrid CPUs (needs --pcore-critical)
+    #[arg(long, requires = "pcore_critical", value_parser = validate_percentage)]
+    pcore_warning: Option<u8>,
+
+    /// Critical threshold for the P-core average on hybrid CPUs
+    #[arg(long, requires = "pcore_warning", value_parser = validate_percentage)]
+    pcore_critical: Option<u8>,
+
+    /// Warning threshold for the E-core average on hybrid CPUs (needs --ecore-critical)
+    #[arg(long, requires = "ecore_critical", value_parser = validate_percentage)]
+    ecore_warning: Option<u8>,
+
+    /// Critical threshold for the E-core average on hybrid CPUs
+    #[arg(long, requires = "ecore_warning", value_parser = validate_percentage)]
+    ecore_critical: Option<u8>,
+
+    /// Report usage and pressure of a cgroup v2 group (e.g. user.slice) instead of the whole system
+    #[arg(long, value_name = "PATH")]
+    cgroup: Option<String>,
+
+    /// Show the average core frequency next to the usage in the bar text
+    #[arg(long)]
+    show_frequency: bool,
+
+    /// Show the CPU package temperature next to the usage in the bar text
+    #[arg(long)]
+    show_temperature: bool,
+
     /// One-shot mode (output once and exit)
     #[arg(short, long)]
     once: bool,
 
+    /// List available CPU cores and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
     /// Icon style (nerdfont, fontawesome, ascii, none)
     #[arg(long)]
     icon_style: Option<IconStyle>,
@@ -58,6 +130,10 @@ struct Args {
     #[arg(long)]
     check: bool,
 
+    /// Read /proc, /sys and other system paths below this directory (for testing)
+    #[arg(long, value_name = "DIR")]
+    sys_root: Option<PathBuf>,
+
     /// Generate example config file and exit
     #[arg(long)]
     generate_config: bool,
@@ -94,6 +170,22 @@ fn validate_percentage(s: &str) -> Result<u8, String> {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    if let Some(root) = &args.sys_root {
+        sysroot::set(root);
+    }
+    
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<CpuSensor>(args.json) {
+            eprintln!("Error listing CPU cores: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
     
     // Handle config generation
     if args.generate_config {
@@ -116,14 +208,36 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     
     // Create the CPU sensor
-    let mut cpu_sensor = match CpuSensor::new(args.warning, args.critical) {
-        Ok(sensor) => sensor,
+    let cpu_sensor = match CpuSensor::new(args.warning, args.critical)
+        .and_then(|sensor| sensor.with_load_thresholds(args.load_warning, args.load_critical))
+        .and_then(|sensor| sensor.with_psi_thresholds(args.psi_warning, args.psi_critical))
+        .and_then(|sensor| sensor.with_iowait_thresholds(args.iowait_warning, args.iowait_critical))
+        .and_then(|sensor| match (args.pcore_warning, args.pcore_critical) {
+            (Some(w), Some(c)) => sensor.with_core_type_thresholds(CoreType::Performance, w, c),
+            _ => Ok(sensor),
+        })
+        .and_then(|sensor| match (args.ecore_warning, args.ecore_critical) {
+            (Some(w), Some(c)) => sensor.with_core_type_thresholds(CoreType::Efficiency, w, c),
+            _ => Ok(sensor),
+        })
+        .and_then(|sensor| match &args.cgroup {
+            Some(path) => sensor.with_cgroup(path),
+            None => Ok(sensor),
+        })
+    {
+        Ok(sensor) => sensor.with_metric(args.metric),
         Err(e) => {
             eprintln!("Failed to create CPU sensor: {}", e);
             process::exit(1);
         }
     };
     
+    let mut cpu_sensor: Box<dyn Sensor<Error = SensorError>> = if args.kernel_health {
+        Box::new(KernelHealthSensor::new())
+    } else {
+        Box::new(cpu_sensor)
+    };
+    
     // Check availability if requested
     if args.check {
         match cpu_sensor.check_availability() {
@@ -140,7 +254,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Load global configuration and apply command line overrides
     let global_config = GlobalConfig::load().unwrap_or_default();
-    let mut config = global_config.to_sensor_config()
+    let mut config = global_config.to_sensor_config_for("cpu")
         .with_update_interval(Duration::from_millis(args.interval))
         .apply_color_overrides(
             args.icon_color.clone(),
@@ -154,11 +268,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config = config.with_icon_style(icon_style);
     }
     
+    // Load sensor-specific configuration from global config
+    if let Some(serde_json::Value::Object(map)) = global_config.sensors.get("cpu") {
+        for (key, value) in map {
+            config = config.with_custom(key.clone(), value.clone());
+        }
+    }
+    
+    if args.show_frequency {
+        config = config.with_custom("show_frequency", serde_json::Value::Bool(true));
+    }
+    if args.show_temperature {
+        config = config.with_custom("show_temperature", serde_json::Value::Bool(true));
+    }
+    
     cpu_sensor.configure(config)?;
     
     if args.once {
         // One-shot mode: read once and exit
-        match cpu_sensor.read() {
+        match cpu_sensor.read_styled() {
             Ok(output) => {
                 println!("{}", serde_json::to_string(&output)?);
             }
@@ -169,13 +297,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     } else {
         // Continuous mode: loop and output readings
-        let mut interval = time::interval(Duration::from_millis(args.interval));
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
         
         loop {
             interval.tick().await;
+            if session_lock::wait_async(cpu_sensor.config()).await {
+                interval.reset();
+            }
             
             // Regular sensor reading
-            match cpu_sensor.read() {
+            match cpu_sensor.read_styled() {
                 Ok(output) => {
                     println!("{}", serde_json::to_string(&output)?);
                     io::stdout().flush()?;
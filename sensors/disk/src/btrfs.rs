@@ -0,0 +1,333 @@
+//! Real space usage of btrfs filesystems from `btrfs filesystem usage`.
+//!
+//! statvfs (and with it `df`) can only guess on btrfs: space is allocated to
+//! data and metadata in chunks, RAID profiles store several copies and free
+//! space depends on which profile the next chunk gets. `btrfs filesystem
+//! usage` accounts for all of that. Without root it cannot read per-device
+//! chunk details, but the overall figures remain correct.
+
+use waysensor_rs_core::{format, SensorConfig, SensorError};
+use std::{
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// How long a `btrfs filesystem usage` result is reused.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Chunks of one type (`Data`, `Metadata`, `System`) and their RAID profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BtrfsProfile {
+    /// Chunk type
+    pub kind: String,
+    /// RAID profile, e.g. `single`, `DUP` or `RAID1`
+    pub profile: String,
+    /// Logical bytes allocated to these chunks
+    pub size: u64,
+    /// Logical bytes used within them
+    pub used: u64,
+}
+
+/// Allocation on one member device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BtrfsDevice {
+    /// Device node
+    pub path: String,
+    /// Raw bytes allocated to chunks
+    pub allocated: u64,
+    /// Raw bytes not yet allocated
+    pub unallocated: u64,
+}
+
+/// Space usage of a btrfs filesystem.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BtrfsUsage {
+    /// Raw size of all member devices
+    pub device_size: u64,
+    /// Raw bytes allocated to chunks
+    pub device_allocated: u64,
+    /// Raw bytes used, counting every RAID copy
+    pub raw_used: u64,
+    /// Logical bytes that can still be written
+    pub free_estimated: u64,
+    /// Free space if all new chunks used the most wasteful profile
+    pub free_min: Option<u64>,
+    /// Raw bytes stored per logical data byte (2.0 for RAID1)
+    pub data_ratio: f64,
+    /// Raw bytes stored per logical metadata byte
+    pub metadata_ratio: f64,
+    /// Chunk types with their profiles
+    pub profiles: Vec<BtrfsProfile>,
+    /// Per-device allocation (only available to root)
+    pub devices: Vec<BtrfsDevice>,
+}
+
+impl BtrfsUsage {
+    /// Parse `btrfs filesystem usage --raw` output.
+    ///
+    /// Returns `None` if the overall section is missing.
+    pub fn parse(output: &str) -> Option<Self> {
+        enum Section { Overall, Allocated, Unallocated, Other }
+
+        let mut usage = Self::default();
+        let mut section = Section::Other;
+        let mut saw_overall = false;
+        for line in output.lines().filter(|line| !line.trim().is_empty()) {
+            if !line.starts_with(char::is_whitespace) {
+                section = match line.trim_end() {
+                    "Overall:" => {
+                        saw_overall = true;
+                        Section::Overall
+                    }
+                    "Unallocated:" => Section::Unallocated,
+                    header => match parse_profile_header(header) {
+                        Some(profile) => {
+                            usage.profiles.push(profile);
+                            Section::Allocated
+                        }
+                        None => Section::Other,
+                    },
+                };
+                continue;
+            }
+
+            match section {
+                Section::Overall => {
+                    let Some((key, value)) = line.trim().split_once(':') else { continue };
+                    let number = value.split_whitespace().next().unwrap_or_default();
+                    match key {
+                        "Device size" => usage.device_size = number.parse().ok()?,
+                        "Device allocated" => usage.device_allocated = number.parse().ok()?,
+                        "Used" => usage.raw_used = number.parse().ok()?,
+                        "Free (estimated)" => {
+                            usage.free_estimated = number.parse().ok()?;
+                            usage.free_min = value.split_once("min:")
+                                .and_then(|(_, min)| min.trim().trim_end_matches(')').parse().ok());
+                        }
+                        "Data ratio" => usage.data_ratio = number.parse().ok()?,
+                        "Metadata ratio" => usage.metadata_ratio = number.parse().ok()?,
+                        _ => {}
+                    }
+                }
+                Section::Allocated | Section::Unallocated => {
+                    let mut parts = line.split_whitespace();
+                    let (Some(path), Some(Ok(bytes))) = (parts.next(), parts.next().map(str::parse::<u64>)) else {
+                        continue;
+                    };
+                    let device = match usage.devices.iter_mut().find(|d| d.path == path) {
+                        Some(device) => device,
+                        None => {
+                            usage.devices.push(BtrfsDevice { path: path.to_owned(), allocated: 0, unallocated: 0 });
+                            usage.devices.last_mut()?
+                        }
+                    };
+                    if matches!(section, Section::Unallocated) {
+                        device.unallocated = bytes;
+                    } else {
+                        device.allocated += bytes;
+                    }
+                }
+                Section::Other => {}
+            }
+        }
+        saw_overall.then_some(usage)
+    }
+
+    /// Query the filesystem mounted at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `btrfs` tool is missing or fails.
+    pub fn query(path: &Path) -> Result<Self, SensorError> {
+        let output = Command::new("btrfs")
+            .args(["filesystem", "usage", "--raw"])
+            .arg(path)
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => SensorError::unavailable("btrfs-progs not installed"),
+                _ => SensorError::Io(e),
+            })?;
+        if !output.status.success() {
+            return Err(SensorError::unavailable(format!(
+                "btrfs filesystem usage failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Self::parse(&String::from_utf8_lossy(&output.stdout))
+            .ok_or_else(|| SensorError::parse("Could not parse btrfs filesystem usage output"))
+    }
+
+    /// Logical bytes in use across data, metadata and system chunks.
+    pub fn logical_used(&self) -> u64 {
+        if self.profiles.is_empty() {
+            return (self.raw_used as f64 / self.data_ratio.max(1.0)) as u64;
+        }
+        self.profiles.iter().map(|p| p.used).sum()
+    }
+
+    /// Extra raw space taken by RAID copies beyond the logical usage.
+    pub fn raid_overhead(&self) -> u64 {
+        self.raw_used.saturating_sub(self.logical_used())
+    }
+
+    /// Tooltip lines: profiles, RAID overhead, unallocated space and devices.
+    pub fn tooltip_lines(&self, config: &SensorConfig) -> Vec<String> {
+        let profiles = self.profiles.iter()
+            .map(|p| format!("{} {}", p.kind, p.profile))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut lines = vec![format::key_value("Btrfs", &profiles, config)];
+        if self.raid_overhead() > 0 {
+            lines.push(format::key_value("RAID Overhead", &format!(
+                "{} (data ×{:.2}, metadata ×{:.2})",
+                format::bytes_to_human(self.raid_overhead()),
+                self.data_ratio,
+                self.metadata_ratio,
+            ), config));
+        }
+        lines.push(format::key_value(
+            "Unallocated",
+            &format::bytes_to_human(self.device_size.saturating_sub(self.device_allocated)),
+            config,
+        ));
+        if let Some(min) = self.free_min.filter(|&min| min != self.free_estimated) {
+            lines.push(format::key_value("Free (min)", &format::bytes_to_human(min), config));
+        }
+        if self.devices.len() > 1 {
+            for device in &self.devices {
+                lines.push(format::key_value(&format!("  {}", device.path), &format!(
+                    "{} allocated, {} free",
+                    format::bytes_to_human(device.allocated),
+                    format::bytes_to_human(device.unallocated),
+                ), config));
+            }
+        }
+        lines
+    }
+}
+
+/// Parse a chunk header such as `Data,RAID1: Size:21474836480, Used:1073741824 (5.00%)`.
+fn parse_profile_header(line: &str) -> Option<BtrfsProfile> {
+    let (name, rest) = line.split_once(": ")?;
+    let (kind, profile) = name.split_once(',')?;
+    let field = |key: &str| rest.split_once(key)?.1
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok();
+    Some(BtrfsProfile {
+        kind: kind.to_owned(),
+        profile: profile.to_owned(),
+        size: field("Size:")?,
+        used: field("Used:")?,
+    })
+}
+
+/// Cached btrfs usage of a mount point.
+#[derive(Debug, Default)]
+pub struct BtrfsMonitor {
+    usage: Option<BtrfsUsage>,
+    last_query: Option<Instant>,
+}
+
+impl BtrfsMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refresh the usage of the btrfs filesystem at `path` if the cached
+    /// result is older than [`REFRESH_INTERVAL`]. Failures leave the
+    /// monitor empty so callers fall back to statvfs numbers.
+    pub fn update(&mut self, path: &Path) {
+        if self.last_query.is_some_and(|at| at.elapsed() < REFRESH_INTERVAL) {
+            return;
+        }
+        self.last_query = Some(Instant::now());
+        self.usage = BtrfsUsage::query(path).ok();
+    }
+
+    /// Last known usage.
+    pub fn usage(&self) -> Option<&BtrfsUsage> {
+        self.usage.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RAID1: &str = "\
+Overall:
+    Device size:\t\t\t2000398934016
+    Device allocated:\t\t 429496729600
+    Device unallocated:\t\t1570902204416
+    Device missing:\t\t\t            0
+    Device slack:\t\t\t            0
+    Used:\t\t\t\t 398000098304
+    Free (estimated):\t\t 800906100736\t(min: 800906100736)
+    Free (statfs, df):\t\t 799000000000
+    Data ratio:\t\t\t         2.00
+    Metadata ratio:\t\t\t         2.00
+    Global reserve:\t\t    536870912\t(used: 0)
+    Multiple profiles:\t\t           no
+
+Data,RAID1: Size:210453397504, Used:195000000000 (92.66%)
+   /dev/sda\t210453397504
+   /dev/sdb\t210453397504
+
+Metadata,RAID1: Size:4294967296, Used:4000000000 (93.13%)
+   /dev/sda\t4294967296
+   /dev/sdb\t4294967296
+
+System,RAID1: Size:33554432, Used:49152 (0.15%)
+   /dev/sda\t  33554432
+   /dev/sdb\t  33554432
+
+Unallocated:
+   /dev/sda\t785451102208
+   /dev/sdb\t785451102208
+";
+
+    #[test]
+    fn test_btrfs_usage_parsing() {
+        let usage = BtrfsUsage::parse(RAID1).unwrap();
+        assert_eq!(usage.device_size, 2_000_398_934_016);
+        assert_eq!(usage.device_allocated, 429_496_729_600);
+        assert_eq!(usage.free_estimated, 800_906_100_736);
+        assert_eq!(usage.free_min, Some(800_906_100_736));
+        assert_eq!(usage.data_ratio, 2.0);
+
+        assert_eq!(usage.profiles.len(), 3);
+        assert_eq!(usage.profiles[0], BtrfsProfile {
+            kind: "Data".to_string(),
+            profile: "RAID1".to_string(),
+            size: 210_453_397_504,
+            used: 195_000_000_000,
+        });
+        assert_eq!(usage.logical_used(), 199_000_049_152);
+        assert_eq!(usage.raid_overhead(), 199_000_049_152);
+
+        assert_eq!(usage.devices, vec![
+            BtrfsDevice { path: "/dev/sda".to_string(), allocated: 214_781_919_232, unallocated: 785_451_102_208 },
+            BtrfsDevice { path: "/dev/sdb".to_string(), allocated: 214_781_919_232, unallocated: 785_451_102_208 },
+        ]);
+
+        // Unprivileged runs leave out the per-device lines
+        let unprivileged: String = RAID1.lines()
+            .filter(|line| !line.starts_with("   /dev"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let usage = BtrfsUsage::parse(&unprivileged).unwrap();
+        assert_eq!(usage.profiles.len(), 3);
+        assert!(usage.devices.is_empty());
+        assert_eq!(usage.logical_used(), 199_000_049_152);
+
+        let overall = RAID1.split("\n\n").next().unwrap();
+        let usage = BtrfsUsage::parse(overall).unwrap();
+        assert!(usage.profiles.is_empty());
+        assert_eq!(usage.logical_used(), 199_000_049_152);
+
+        assert_eq!(BtrfsUsage::parse("ERROR: not a btrfs filesystem"), None);
+    }
+}
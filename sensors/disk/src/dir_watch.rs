@@ -0,0 +1,292 @@
+//! Growth monitoring for per-user scratch directories.
+//!
+//! Walking a directory tree is far more expensive than a `df` call, so sizes are
+//! sampled at a long interval (10 minutes by default) and reused between reads.
+
+use crate::disk::UsageTrend;
+use waysensor_rs_core::{format, Sensor, SensorConfig, SensorError, TooltipDetail, WaybarOutput};
+use std::{
+    fs,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// Number of subdirectories listed per watched directory in the expert tooltip.
+const LARGEST_SUBDIRS: usize = 5;
+
+/// Size sample of a single watched directory.
+#[derive(Debug, Clone)]
+pub struct DirSample {
+    /// Watched directory
+    pub path: PathBuf,
+    /// Allocated size in bytes
+    pub size: u64,
+    /// Largest immediate subdirectories (path, bytes), largest first
+    pub largest: Vec<(PathBuf, u64)>,
+}
+
+#[derive(Debug)]
+struct WatchedDir {
+    path: PathBuf,
+    sample: Option<DirSample>,
+    trend: UsageTrend,
+}
+
+/// Sensor watching the size of `~/.cache`, `/tmp`, the XDG state dir or any
+/// user supplied directories.
+#[derive(Debug)]
+pub struct DirWatchSensor {
+    name: String,
+    config: SensorConfig,
+    dirs: Vec<WatchedDir>,
+    sample_interval: Duration,
+    last_sample: Option<Instant>,
+    warning_bytes: u64,
+    critical_bytes: u64,
+}
+
+impl DirWatchSensor {
+    /// Directories watched when none are given explicitly.
+    pub fn default_dirs() -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        if let Some(cache) = dirs::cache_dir() {
+            paths.push(cache);
+        }
+        paths.push(std::env::temp_dir());
+        if let Some(state) = dirs::state_dir() {
+            paths.push(state);
+        }
+        paths.retain(|d| d.is_dir());
+        paths.dedup();
+        paths
+    }
+
+    /// Create a new directory watcher.
+    ///
+    /// Thresholds apply to the combined size of all watched directories.
+    pub fn new(
+        paths: Vec<PathBuf>,
+        sample_interval: Duration,
+        warning_bytes: u64,
+        critical_bytes: u64,
+    ) -> Result<Self, SensorError> {
+        let paths = if paths.is_empty() { Self::default_dirs() } else { paths };
+
+        if paths.is_empty() {
+            return Err(SensorError::config("No directories to watch"));
+        }
+        if let Some(missing) = paths.iter().find(|p| !p.is_dir()) {
+            return Err(SensorError::config_with_value(
+                "Watched path is not a directory",
+                missing.display().to_string(),
+            ));
+        }
+        if warning_bytes >= critical_bytes {
+            return Err(SensorError::config_with_value(
+                "Warning threshold must be less than critical threshold",
+                format!("warning: {}, critical: {}", warning_bytes, critical_bytes),
+            ));
+        }
+
+        Ok(Self {
+            name: "disk-dirs".to_string(),
+            config: SensorConfig::default(),
+            dirs: paths.into_iter()
+                .map(|path| WatchedDir { path, sample: None, trend: UsageTrend::new(24) })
+                .collect(),
+            sample_interval,
+            last_sample: None,
+            warning_bytes,
+            critical_bytes,
+        })
+    }
+
+    /// Walk all watched directories if the last sample is older than the
+    /// sample interval.
+    fn refresh(&mut self) {
+        let now = Instant::now();
+        if self.last_sample.is_some_and(|t| now.duration_since(t) < self.sample_interval) {
+            return;
+        }
+
+        for dir in &mut self.dirs {
+            let sample = sample_dir(&dir.path);
+            dir.trend.add_sample(now, sample.size as f64);
+            dir.sample = Some(sample);
+        }
+        self.last_sample = Some(now);
+    }
+
+    fn total_size(&self) -> u64 {
+        self.dirs.iter()
+            .filter_map(|d| d.sample.as_ref())
+            .map(|s| s.size)
+            .sum()
+    }
+
+    fn build_tooltip(&self) -> String {
+        let mut lines = Vec::new();
+
+        for dir in &self.dirs {
+            let Some(sample) = &dir.sample else { continue };
+
+            let mut value = format::bytes_to_human(sample.size);
+            if let Some(growth) = dir.trend.trend_per_day() {
+                let sign = if growth < 0.0 { "-" } else { "+" };
+                value.push_str(&format!(" ({}{}/day)", sign, format::bytes_to_human(growth.abs() as u64)));
+            }
+            lines.push(format::key_value(&dir.path.display().to_string(), &value, &self.config));
+
+            if self.config.visuals.tooltip_detail == TooltipDetail::Expert {
+                for (path, size) in &sample.largest {
+                    let name = path.file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_else(|| path.display().to_string());
+                    lines.push(format::value_only(
+                        &format!("  {:>8}  {}", format::bytes_to_human(*size), name),
+                        &self.config,
+                    ));
+                }
+            }
+        }
+
+        lines.push(format::key_value("Total", &format::bytes_to_human(self.total_size()), &self.config));
+
+        if let Some(last) = self.last_sample {
+            let age = last.elapsed().as_secs() / 60;
+            lines.push(format::key_value("Sampled", &format!("{} min ago", age), &self.config));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Measure a directory and its largest immediate subdirectories.
+fn sample_dir(path: &Path) -> DirSample {
+    let mut size = 0;
+    let mut largest = Vec::new();
+
+    // Stay on the filesystem the watched directory lives on
+    let device = fs::symlink_metadata(path).map(|m| m.dev()).ok();
+
+    if let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else { continue };
+            let entry_size = if metadata.is_dir() {
+                dir_size(&entry.path(), device)
+            } else {
+                allocated_size(&metadata)
+            };
+
+            size += entry_size;
+            if metadata.is_dir() {
+                largest.push((entry.path(), entry_size));
+            }
+        }
+    }
+
+    largest.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    largest.truncate(LARGEST_SUBDIRS);
+
+    DirSample { path: path.to_path_buf(), size, largest }
+}
+
+/// Recursive allocated size of a directory, without following symlinks or
+/// crossing into other filesystems. Unreadable entries are skipped.
+fn dir_size(path: &Path, device: Option<u64>) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else { return 0 };
+
+    entries.flatten()
+        .filter_map(|entry| entry.metadata().ok().map(|m| (entry.path(), m)))
+        .filter(|(_, m)| device.is_none_or(|dev| m.dev() == dev))
+        .map(|(path, m)| {
+            if m.is_dir() {
+                allocated_size(&m) + dir_size(&path, device)
+            } else {
+                allocated_size(&m)
+            }
+        })
+        .sum()
+}
+
+fn allocated_size(metadata: &fs::Metadata) -> u64 {
+    // st_blocks is always in 512-byte units
+    metadata.blocks() * 512
+}
+
+impl Sensor for DirWatchSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        self.refresh();
+
+        let total = self.total_size();
+        let text = format::with_icon_and_colors(
+            &format::bytes_to_human(total),
+            &self.config.icons.disk,
+            &self.config,
+        );
+
+        let percentage = ((total as f64 / self.critical_bytes as f64) * 100.0).min(100.0) as u8;
+
+        Ok(format::themed_output(
+            text,
+            Some(self.build_tooltip()),
+            Some(percentage),
+            total as f64,
+            self.warning_bytes as f64,
+            self.critical_bytes as f64,
+            &self.config.theme,
+        ))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        if let Some(secs) = config.get_custom("dir_sample_interval_secs").and_then(|v| v.as_u64()) {
+            self.sample_interval = Duration::from_secs(secs);
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_dir_counts_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let nested = root.join("big").join("deeper");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(root.join("small")).unwrap();
+        fs::write(nested.join("data"), vec![1u8; 64 * 1024]).unwrap();
+        fs::write(root.join("small").join("data"), b"x").unwrap();
+
+        let sample = sample_dir(root);
+
+        assert!(sample.size >= 64 * 1024);
+        assert_eq!(sample.largest.len(), 2);
+        assert_eq!(sample.largest[0].0.file_name().unwrap(), "big");
+    }
+
+    #[test]
+    fn test_invalid_thresholds() {
+        let result = DirWatchSensor::new(
+            vec![std::env::temp_dir()],
+            Duration::from_secs(600),
+            10,
+            5,
+        );
+        assert!(result.is_err());
+    }
+}
@@ -15,7 +15,7 @@
 //! ## Quick Start
 //!
 //! ```rust
-//! use waysensor_disk::{DiskSensor, DiskSensorBuilder};
+//! use waysensor_rs_disk::{DiskSensorBuilder, DisplayMode, MultiDiskSensor};
 //!
 //! // Single disk monitoring
 //! let sensor = DiskSensorBuilder::new("/")
@@ -25,16 +25,30 @@
 //!     .build()?;
 //!
 //! // Multi-disk monitoring
-//! let multi_sensor = DiskSensorBuilder::multi_disk()
-//!     .add_path("/")
-//!     .add_path("/home")
-//!     .display_mode(DisplayMode::HighestUsage)
-//!     .build()?;
+//! let multi_sensor = MultiDiskSensor::new(
+//!     vec!["/".to_string(), "/tmp".to_string()],
+//!     80,
+//!     95,
+//!     false,
+//!     DisplayMode::HighestUsage,
+//! )?;
+//! # Ok::<(), waysensor_rs_core::SensorError>(())
 //! ```
 
+use crate::latency::LatencyTracker;
+use crate::drive_temp::DriveTemp;
+use crate::smart::{self, SmartMonitor};
+use crate::network_fs::NetworkGuard;
+use crate::btrfs::BtrfsMonitor;
+use crate::zfs::ZfsMonitor;
+use crate::quota::QuotaMonitor;
+use crate::forecast::{FitModel, Forecast, Sample, UsageHistory};
+use crate::io::DiskMetric;
+use crate::target::FsTarget;
 use waysensor_rs_core::{
-    Sensor, SensorConfig, SensorError, WaybarOutput, format
+    diff, power, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, TooltipDetail, WaybarOutput, format
 };
+pub use waysensor_rs_core::trend::UsageTrend;
 use std::{
     path::{Path, PathBuf},
     time::{Duration, Instant},
@@ -156,70 +170,6 @@ impl DiskInfo {
             _ => None,
         }
     }
-    
-    
-    /// Estimate time until disk is full based on usage trend.
-    pub fn time_until_full(&self, usage_trend_per_day: f64) -> Option<Duration> {
-        if usage_trend_per_day <= 0.0 {
-            return None; // Not filling up
-        }
-        
-        let remaining_percentage = 100.0 - self.used_percentage();
-        let days_remaining = remaining_percentage / usage_trend_per_day;
-        
-        if days_remaining > 0.0 && days_remaining.is_finite() {
-            Some(Duration::from_secs_f64(days_remaining * 24.0 * 3600.0))
-        } else {
-            None
-        }
-    }
-}
-
-/// Usage trend tracking for predictive monitoring.
-#[derive(Debug, Clone)]
-pub struct UsageTrend {
-    /// Historical usage percentages with timestamps
-    history: Vec<(Instant, f64)>,
-    /// Maximum history entries to keep
-    max_history: usize,
-}
-
-impl UsageTrend {
-    pub fn new(max_history: usize) -> Self {
-        Self {
-            history: Vec::with_capacity(max_history),
-            max_history,
-        }
-    }
-    
-    pub fn add_sample(&mut self, timestamp: Instant, usage_percentage: f64) {
-        self.history.push((timestamp, usage_percentage));
-        
-        // Keep only recent history
-        if self.history.len() > self.max_history {
-            self.history.remove(0);
-        }
-    }
-    
-    /// Calculate usage trend in percentage points per day.
-    pub fn trend_per_day(&self) -> Option<f64> {
-        if self.history.len() < 2 {
-            return None;
-        }
-        
-        let (first_time, first_usage) = self.history.first()?;
-        let (last_time, last_usage) = self.history.last()?;
-        
-        let duration = last_time.duration_since(*first_time);
-        let usage_change = last_usage - first_usage;
-        
-        if duration.as_secs() > 0 {
-            let days = duration.as_secs_f64() / (24.0 * 3600.0);
-            Some(usage_change / days)
-        } else {
-            None
-        }
-    }
 }
 
 /// Configuration for disk monitoring caching.
@@ -249,6 +199,8 @@ pub struct DiskSensor {
     config: SensorConfig,
     /// Disk mount path
     path: PathBuf,
+    /// Filesystem followed by label or UUID; `path` tracks its mount point
+    target: Option<FsTarget>,
     /// Warning threshold percentage (0-100)
     warning_threshold: u8,
     /// Critical threshold percentage (0-100)
@@ -257,6 +209,8 @@ pub struct DiskSensor {
     show_available: bool,
     /// Include inode monitoring
     monitor_inodes: bool,
+    /// Metric shown in the bar (space or inode usage)
+    metric: DiskMetric,
     /// Cache configuration
     cache_config: CacheConfig,
     /// Cached disk information
@@ -265,6 +219,36 @@ pub struct DiskSensor {
     usage_trend: UsageTrend,
     /// Performance monitoring enabled
     performance_monitoring: bool,
+    /// I/O latency tracking for the backing block device
+    latency: Option<LatencyTracker>,
+    /// Sustained median I/O latency that triggers a warning, in milliseconds
+    latency_warning_ms: f64,
+    /// Sustained median I/O latency that is critical, in milliseconds
+    latency_critical_ms: f64,
+    /// SMART health of the drive behind the mount, when enabled
+    smart: Option<SmartMonitor>,
+    /// Last temperature of the drive behind the mount
+    drive_temp: Option<DriveTemp>,
+    /// Drive temperature warning threshold in °C (default: the drive's own)
+    drive_temp_warning: Option<f64>,
+    /// Drive temperature critical threshold in °C (default: the drive's own)
+    drive_temp_critical: Option<f64>,
+    /// Timeout protection for network mounts
+    network: NetworkGuard,
+    /// btrfs' own space accounting, used instead of statvfs on btrfs
+    btrfs: Option<BtrfsMonitor>,
+    /// State of the ZFS pool behind the mount
+    zfs: Option<ZfsMonitor>,
+    /// The user's quota, reported instead of the whole filesystem when set
+    quota: Option<QuotaMonitor>,
+    /// Persisted usage history for the "full in" forecast, loaded on first use
+    history: Option<UsageHistory>,
+    /// Curve fitted to the history
+    forecast_model: FitModel,
+    /// Days until full below which the sensor warns
+    full_warning_days: f64,
+    /// Days until full below which the sensor is critical
+    full_critical_days: f64,
 }
 
 /// Builder for configuring DiskSensor instances.
@@ -272,14 +256,24 @@ pub struct DiskSensor {
 pub struct DiskSensorBuilder {
     path: Option<PathBuf>,
     paths: Vec<PathBuf>,
+    target: Option<FsTarget>,
     warning_threshold: u8,
     critical_threshold: u8,
     show_available: bool,
     monitor_inodes: bool,
+    metric: DiskMetric,
     cache_config: CacheConfig,
     display_mode: DisplayMode,
     performance_monitoring: bool,
     trend_history_size: usize,
+    latency_warning_ms: f64,
+    latency_critical_ms: f64,
+    smart: bool,
+    quota: bool,
+    drive_temp_warning: Option<f64>,
+    drive_temp_critical: Option<f64>,
+    full_warning_days: f64,
+    full_critical_days: f64,
 }
 
 impl DiskSensorBuilder {
@@ -288,14 +282,24 @@ impl DiskSensorBuilder {
         Self {
             path: Some(path.as_ref().to_path_buf()),
             paths: Vec::new(),
+            target: None,
             warning_threshold: 80,
             critical_threshold: 95,
             show_available: false,
             monitor_inodes: false,
+            metric: DiskMetric::default(),
             cache_config: CacheConfig::default(),
             display_mode: DisplayMode::default(),
             performance_monitoring: false,
             trend_history_size: 24, // 24 hours worth of hourly samples
+            latency_warning_ms: 100.0,
+            latency_critical_ms: 500.0,
+            smart: false,
+            quota: false,
+            drive_temp_warning: None,
+            drive_temp_critical: None,
+            full_warning_days: 7.0,
+            full_critical_days: 1.0,
         }
     }
     
@@ -304,14 +308,24 @@ impl DiskSensorBuilder {
         Self {
             path: None,
             paths: Vec::new(),
+            target: None,
             warning_threshold: 80,
             critical_threshold: 95,
             show_available: false,
             monitor_inodes: false,
+            metric: DiskMetric::default(),
             cache_config: CacheConfig::default(),
             display_mode: DisplayMode::default(),
             performance_monitoring: false,
             trend_history_size: 24,
+            latency_warning_ms: 100.0,
+            latency_critical_ms: 500.0,
+            smart: false,
+            quota: false,
+            drive_temp_warning: None,
+            drive_temp_critical: None,
+            full_warning_days: 7.0,
+            full_critical_days: 1.0,
         }
     }
     
@@ -321,6 +335,13 @@ impl DiskSensorBuilder {
         self
     }
     
+    /// Follow the filesystem with this label or UUID wherever it is
+    /// mounted, instead of the path.
+    pub fn target(mut self, target: FsTarget) -> Self {
+        self.target = Some(target);
+        self
+    }
+    
     /// Set warning threshold percentage (0-100).
     pub fn warning_threshold(mut self, threshold: u8) -> Self {
         self.warning_threshold = threshold.min(100);
@@ -345,6 +366,14 @@ impl DiskSensorBuilder {
         self
     }
     
+    /// Set the metric shown in the bar. [`DiskMetric::Inodes`] shows inode
+    /// usage and implies inode monitoring; the I/O metric has its own sensor
+    /// and falls back to space usage here.
+    pub fn metric(mut self, metric: DiskMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+    
     /// Configure caching behavior.
     pub fn cache_config(mut self, config: CacheConfig) -> Self {
         self.cache_config = config;
@@ -369,20 +398,67 @@ impl DiskSensorBuilder {
         self
     }
     
+    /// Set the sustained I/O latency thresholds in milliseconds.
+    ///
+    /// Healthy SSDs complete I/O in well under a millisecond and spinning
+    /// disks in around ten; the defaults (100 / 500 ms) point at a failing
+    /// drive or a saturated USB enclosure.
+    pub fn latency_thresholds(mut self, warning_ms: f64, critical_ms: f64) -> Self {
+        self.latency_warning_ms = warning_ms;
+        self.latency_critical_ms = critical_ms;
+        self
+    }
+    
+    /// Report SMART health of the drive behind the mount (needs smartctl
+    /// and root or `CAP_SYS_RAWIO`).
+    pub fn smart(mut self, enable: bool) -> Self {
+        self.smart = enable;
+        self
+    }
+    
+    /// Report the user's quota consumption instead of whole-filesystem
+    /// usage when the filesystem has a quota for them (needs `quota`).
+    pub fn quota(mut self, enable: bool) -> Self {
+        self.quota = enable;
+        self
+    }
+    
+    /// Set how many days before the predicted "full" date the sensor warns
+    /// and turns critical (needs performance monitoring).
+    pub fn full_thresholds(mut self, warning_days: f64, critical_days: f64) -> Self {
+        self.full_warning_days = warning_days;
+        self.full_critical_days = critical_days;
+        self
+    }
+    
+    /// Set drive temperature thresholds in °C. Unset thresholds fall back to
+    /// the high and critical temperatures the drive reports, if any.
+    pub fn drive_temp_thresholds(mut self, warning: Option<f64>, critical: Option<f64>) -> Self {
+        self.drive_temp_warning = warning;
+        self.drive_temp_critical = critical;
+        self
+    }
+    
     /// Build a single disk sensor.
     pub fn build(self) -> Result<DiskSensor, SensorError> {
-        let path = self.path
-            .ok_or_else(|| SensorError::config("No path specified for single disk sensor"))?;
+        // A labelled filesystem need not be mounted yet
+        let path = match &self.target {
+            Some(target) => target.resolve()
+                .map(|mount| PathBuf::from(mount.mount_point))
+                .unwrap_or_default(),
+            None => self.path
+                .ok_or_else(|| SensorError::config("No path specified for single disk sensor"))?,
+        };
         
-        // Validate path exists and is accessible
-        if !path.exists() {
+        // Validate path exists and is accessible (a target is checked on every read)
+        if self.target.is_none() && !path.exists() {
             return Err(DiskError::InvalidPath {
                 path: path.display().to_string(),
                 reason: "Path does not exist".to_string(),
             }.into());
         }
         
-        if !path.is_dir() {
+        if self.target.is_none() && !path.is_dir() {
             return Err(DiskError::InvalidPath {
                 path: path.display().to_string(),
                 reason: "Path is not a directory".to_string(),
@@ -397,21 +473,48 @@ impl DiskSensorBuilder {
             ));
         }
         
-        let name = format!("disk-{}", 
-            path.to_string_lossy().replace('/', "-").trim_matches('-'));
+        if self.latency_warning_ms <= 0.0 || self.latency_warning_ms >= self.latency_critical_ms {
+            return Err(SensorError::config_with_value(
+                "Latency warning threshold must be positive and less than the critical threshold",
+                format!("warning: {} ms, critical: {} ms", self.latency_warning_ms, self.latency_critical_ms),
+            ));
+        }
+        
+        let name = match &self.target {
+            Some(target) => format!("disk-{}", target.id()),
+            None => format!("disk-{}", 
+                path.to_string_lossy().replace('/', "-").trim_matches('-')),
+        };
         
         Ok(DiskSensor {
             name,
             config: SensorConfig::default(),
             path,
+            target: self.target,
             warning_threshold: self.warning_threshold,
             critical_threshold: self.critical_threshold,
             show_available: self.show_available,
-            monitor_inodes: self.monitor_inodes,
+            monitor_inodes: self.monitor_inodes || self.metric == DiskMetric::Inodes,
+            metric: self.metric,
             cache_config: self.cache_config,
             cached_info: None,
             usage_trend: UsageTrend::new(self.trend_history_size),
             performance_monitoring: self.performance_monitoring,
+            latency: None,
+            latency_warning_ms: self.latency_warning_ms,
+            latency_critical_ms: self.latency_critical_ms,
+            smart: self.smart.then(SmartMonitor::new),
+            drive_temp: None,
+            drive_temp_warning: self.drive_temp_warning,
+            drive_temp_critical: self.drive_temp_critical,
+            network: NetworkGuard::default(),
+            btrfs: Some(BtrfsMonitor::new()),
+            zfs: Some(ZfsMonitor::new()),
+            quota: self.quota.then(QuotaMonitor::new),
+            history: None,
+            forecast_model: FitModel::default(),
+            full_warning_days: self.full_warning_days,
+            full_critical_days: self.full_critical_days,
         })
     }
 }
@@ -449,8 +552,24 @@ impl DiskSensor {
         DiskSensorBuilder::new(path).build()
     }
     
+    /// Point `path` at the current mount point of the label/UUID target.
+    /// A new mount point drops everything tied to the old one.
+    fn follow_target(&mut self) -> Result<(), SensorError> {
+        let Some(target) = &self.target else {
+            return Ok(());
+        };
+        let mount_point = PathBuf::from(target.resolve()?.mount_point);
+        if mount_point != self.path {
+            self.path = mount_point;
+            self.cached_info = None;
+            self.history = None;
+        }
+        Ok(())
+    }
+    
     /// Get current disk information, using cache if available and valid.
     fn get_disk_info(&mut self) -> Result<DiskInfo, SensorError> {
+        self.follow_target()?;
         let now = Instant::now();
         
         // Check if cached data is still valid
@@ -461,11 +580,15 @@ impl DiskSensor {
         }
         
         // Fetch fresh data
-        let info = self.fetch_disk_info()?;
+        let mut info = self.fetch_disk_info()?;
+        self.apply_btrfs(&mut info);
+        self.apply_quota(&mut info);
         
         // Update trend tracking if performance monitoring is enabled
         if self.performance_monitoring {
             self.usage_trend.add_sample(now, info.used_percentage());
+            self.history.get_or_insert_with(|| UsageHistory::load(&self.path))
+                .record(Sample::now(info.used, info.available));
         }
         
         // Cache the result
@@ -479,14 +602,10 @@ impl DiskSensor {
         let path_str = self.path.to_string_lossy();
         
         // Use df command for comprehensive disk information
-        let output = Command::new("df")
-            .args(["-B1", "-T", "-P"]) // Bytes, filesystem type, POSIX format
-            .arg(&*path_str)
-            .output()
-            .map_err(|e| DiskError::CommandFailed {
-                command: "df".to_string(),
-                source: e,
-            })?;
+        let mut df = Command::new("df");
+        df.args(["-B1", "-T", "-P"]) // Bytes, filesystem type, POSIX format
+            .arg(&*path_str);
+        let output = self.network.output(&self.path, df)?;
         
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -526,6 +645,35 @@ impl DiskSensor {
         })
     }
     
+    /// Replace the statvfs numbers of a btrfs filesystem with its own
+    /// accounting, which knows about chunk allocation and RAID copies.
+    fn apply_btrfs(&mut self, info: &mut DiskInfo) {
+        let Some(btrfs) = self.btrfs.as_mut().filter(|_| info.filesystem == "btrfs") else {
+            return;
+        };
+        btrfs.update(&self.path);
+        if let Some(usage) = btrfs.usage() {
+            info.used = usage.logical_used();
+            info.available = usage.free_estimated;
+            info.total = info.used + info.available;
+        }
+    }
+    
+    /// Replace filesystem usage with the user's quota, if they have one.
+    /// Free space is whatever runs out first, the quota or the filesystem.
+    fn apply_quota(&mut self, info: &mut DiskInfo) {
+        let Some(monitor) = self.quota.as_mut() else {
+            return;
+        };
+        monitor.update(&self.path);
+        let Some((used, limit)) = monitor.quota().and_then(|quota| Some((quota.used, quota.limit()?))) else {
+            return;
+        };
+        info.available = limit.saturating_sub(used).min(info.available);
+        info.used = used;
+        info.total = limit;
+    }
+    
     /// Parse df command output to extract disk information.
     fn parse_df_output(&self, output: &str) -> Result<(String, String, u64, u64, u64), SensorError> {
         // Skip header line and find the data line
@@ -556,11 +704,10 @@ impl DiskSensor {
     fn get_inode_info(&self) -> Result<(Option<u64>, Option<u64>), SensorError> {
         let path_str = self.path.to_string_lossy();
         
-        let output = Command::new("df")
-            .args(["-i", "-P"]) // Inodes, POSIX format
-            .arg(&*path_str)
-            .output()
-            .map_err(|e| SensorError::Io(e))?;
+        let mut df = Command::new("df");
+        df.args(["-i", "-P"]) // Inodes, POSIX format
+            .arg(&*path_str);
+        let output = self.network.output(&self.path, df)?;
         
         if !output.status.success() {
             // Inode information might not be available on all filesystems
@@ -652,6 +799,39 @@ impl DiskSensor {
             tooltip.push_str(&format!("\n{}", inode_line));
         }
         
+        if let Some(usage) = self.btrfs.as_ref().and_then(BtrfsMonitor::usage).filter(|_| info.filesystem == "btrfs") {
+            for line in usage.tooltip_lines(&self.config) {
+                tooltip.push_str(&format!("\n{}", line));
+            }
+        }
+        
+        if let Some(zfs) = self.zfs.as_ref().filter(|_| info.filesystem == "zfs") {
+            for line in zfs.tooltip_lines(&self.config) {
+                tooltip.push_str(&format!("\n{}", line));
+            }
+        }
+        
+        if let Some(quota) = &self.quota {
+            for line in quota.tooltip_lines(&self.config) {
+                tooltip.push_str(&format!("\n{}", line));
+            }
+        }
+        
+        for line in self.format_latency_lines() {
+            tooltip.push_str(&format!("\n{}", line));
+        }
+        
+        if let Some(temp) = &self.drive_temp {
+            let temp_line = format::key_value("Drive Temp", &format!("{:.0}°C", temp.celsius), &self.config);
+            tooltip.push_str(&format!("\n{}", temp_line));
+        }
+        
+        if let Some(smart) = &self.smart {
+            for line in smart.tooltip_lines(&self.config) {
+                tooltip.push_str(&format!("\n{}", line));
+            }
+        }
+        
         // Read-only status
         if info.readonly {
             let status_line = format::key_value("Status", "Read-only", &self.config);
@@ -664,12 +844,21 @@ impl DiskSensor {
                 let trend_line = format::key_value("Trend", &format!("{:.2}% per day", trend), &self.config);
                 tooltip.push_str(&format!("\n{}", trend_line));
                 
-                if let Some(time_until_full) = info.time_until_full(trend) {
-                    let days = time_until_full.as_secs_f64() / (24.0 * 3600.0);
-                    if days < 365.0 {
-                        let estimate_line = format::key_value("Est. full in", &format!("{:.1} days", days), &self.config);
-                        tooltip.push_str(&format!("\n{}", estimate_line));
-                    }
+            }
+            
+            if let Some(forecast) = self.forecast() {
+                let growth_line = format::key_value("Growth", &format!("{}/day",
+                    format::bytes_to_human(forecast.growth_per_day as u64)), &self.config);
+                tooltip.push_str(&format!("\n{}", growth_line));
+                if forecast.days_until_full < 365.0 {
+                    let full_in = if forecast.days_until_full < 1.0 {
+                        format!("~{:.0} hours", forecast.days_until_full * 24.0)
+                    } else {
+                        format!("~{:.0} days", forecast.days_until_full)
+                    };
+                    let estimate_line = format::key_value("Full in", &format!("{} ({} fit over {:.1} days)",
+                        full_in, forecast.model, forecast.span_days), &self.config);
+                    tooltip.push_str(&format!("\n{}", estimate_line));
                 }
             }
         }
@@ -677,6 +866,95 @@ impl DiskSensor {
         tooltip
     }
     
+    /// Sample I/O latency of the block device behind the mount.
+    ///
+    /// The tracker is created lazily from the device `df` reports and dropped
+    /// if its statistics vanish, so a re-plugged device is picked up again.
+    fn update_latency(&mut self, device: &str) {
+        if self.latency.is_none() {
+            self.latency = LatencyTracker::for_device(device);
+        }
+        if self.latency.as_mut().is_some_and(|tracker| tracker.update().is_err()) {
+            self.latency = None;
+        }
+    }
+    
+    /// Read the temperature of the drive behind the mount.
+    ///
+    /// A runtime-suspended drive keeps its last reading rather than being
+    /// woken up for a new one.
+    fn update_drive_temp(&mut self, device: &str) {
+        let Some(disk) = smart::whole_disk_name(device) else {
+            self.drive_temp = None;
+            return;
+        };
+        if power::is_runtime_suspended(&Path::new("/sys/class/block").join(&disk).join("device")) {
+            return;
+        }
+        self.drive_temp = DriveTemp::read(&disk);
+    }
+    
+    /// CSS class for the drive temperature, if any threshold applies.
+    fn drive_temp_class(&self) -> Option<&str> {
+        let temp = self.drive_temp.as_ref()?;
+        let theme = &self.config.theme;
+        let warning = self.drive_temp_warning.or(temp.max);
+        let critical = self.drive_temp_critical.or(temp.crit);
+        if critical.is_some_and(|c| temp.celsius >= c) {
+            Some(&theme.critical)
+        } else if warning.is_some_and(|w| temp.celsius >= w) {
+            Some(&theme.warning)
+        } else {
+            None
+        }
+    }
+    
+    /// Tooltip lines describing I/O latency.
+    fn format_latency_lines(&self) -> Vec<String> {
+        let Some(tracker) = &self.latency else {
+            return Vec::new();
+        };
+        let mut lines = Vec::new();
+        
+        if self.config.visuals.tooltip_detail == TooltipDetail::Expert {
+            if let (Some(p50), Some(p99)) = (tracker.percentile(50.0), tracker.percentile(99.0)) {
+                lines.push(format::key_value(
+                    "I/O Latency",
+                    &format!("p50 {:.1} ms / p99 {:.1} ms ({} samples)", p50, p99, tracker.sample_count()),
+                    &self.config,
+                ));
+            }
+        }
+        
+        if let Some(latency) = tracker.sustained_latency().filter(|&l| l >= self.latency_warning_ms) {
+            lines.push(format::key_value(
+                "Warning",
+                &format!("⚠️ sustained I/O latency {:.0} ms", latency),
+                &self.config,
+            ));
+        }
+        
+        lines
+    }
+    
+    /// Predicted time until the mount is full, from the persisted history.
+    pub fn forecast(&self) -> Option<Forecast> {
+        self.history.as_ref()?.forecast(self.forecast_model)
+    }
+    
+    /// CSS class for a mount predicted to fill up soon.
+    fn forecast_class(&self) -> Option<&str> {
+        let days = self.forecast()?.days_until_full;
+        let theme = &self.config.theme;
+        if days < self.full_critical_days {
+            Some(&theme.critical)
+        } else if days < self.full_warning_days {
+            Some(&theme.warning)
+        } else {
+            None
+        }
+    }
+    
     /// Get usage trend information if available.
     pub fn usage_trend_per_day(&self) -> Option<f64> {
         self.usage_trend.trend_per_day()
@@ -688,15 +966,98 @@ impl DiskSensor {
     }
 }
 
+/// A mounted filesystem backed by a real device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mount {
+    /// Backing device, e.g. `/dev/nvme0n1p2`
+    pub device: String,
+    /// Where it is mounted
+    pub mount_point: String,
+    /// Filesystem type, e.g. `ext4`
+    pub fs_type: String,
+    /// Mounted read-only
+    pub read_only: bool,
+}
+
+/// Real mounts of the running system, see [`parse_real_mounts`].
+///
+/// # Errors
+///
+/// Returns an error if `/proc/mounts` cannot be read.
+pub fn real_mounts() -> Result<Vec<Mount>, SensorError> {
+    Ok(parse_real_mounts(&std::fs::read_to_string("/proc/mounts")?))
+}
+
+/// Parse `/proc/mounts`, keeping device-backed filesystems and skipping
+/// pseudo filesystems and anything below `/proc`, `/sys` and `/dev`.
+pub fn parse_real_mounts(content: &str) -> Vec<Mount> {
+    content.lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let [device, mount_point, fs_type, options, ..] = parts[..] else { return None };
+
+            // Skip virtual filesystems and special mounts
+            if !device.starts_with('/') ||
+               matches!(fs_type, "proc" | "sysfs" | "devtmpfs" | "tmpfs" | "devpts" | "cgroup") ||
+               mount_point.starts_with("/proc") || mount_point.starts_with("/sys") ||
+               mount_point.starts_with("/dev") {
+                return None;
+            }
+
+            Some(Mount {
+                device: device.to_owned(),
+                // Spaces in mount points are octal-escaped
+                mount_point: mount_point.replace("\\040", " "),
+                fs_type: fs_type.to_owned(),
+                read_only: options.split(',').any(|o| o == "ro"),
+            })
+        })
+        .collect()
+}
+
+impl Discoverable for DiskSensor {
+    const KIND: &'static str = "mount points";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        let mut items: Vec<DiscoveredItem> = real_mounts()?
+            .into_iter()
+            .map(|mount| DiscoveredItem::new(mount.mount_point, mount.device)
+                .with_detail("filesystem", mount.fs_type)
+                .with_detail("read-only", if mount.read_only { "yes" } else { "no" }))
+            .collect();
+
+        items.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(items)
+    }
+}
+
 impl Sensor for DiskSensor {
     type Error = SensorError;
     
     fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
         let info = self.get_disk_info()?;
+        self.update_latency(&info.device);
+        if let Some(smart) = &mut self.smart {
+            smart.update(&info.device);
+        }
+        self.update_drive_temp(&info.device);
+        if let Some(zfs) = self.zfs.as_mut().filter(|_| info.filesystem == "zfs") {
+            zfs.update(&info.device);
+        }
         
         let icon = &self.config.icons.disk;
         
-        let (text, percentage, value_for_theming) = if self.show_available {
+        let (text, percentage, value_for_theming) = if self.metric == DiskMetric::Inodes {
+            let inode_percent = info.inode_usage_percentage().ok_or_else(|| SensorError::unavailable(
+                format!("{} does not report inode counts", info.filesystem),
+            ))?;
+            let text = if self.show_available {
+                format!("{:3.0}% inodes free", 100.0 - inode_percent)
+            } else {
+                format!("{:3.0}% inodes", inode_percent)
+            };
+            (text, Some(inode_percent.round() as u8), inode_percent)
+        } else if self.show_available {
             let available_percent = info.available_percentage();
             (
                 format!("{:3.0}% free", available_percent),
@@ -715,18 +1076,14 @@ impl Sensor for DiskSensor {
         let formatted_text = format::with_icon_and_colors(&text, icon, &self.config);
         let tooltip = self.build_tooltip(&info);
         
-        // Consider inode usage for criticality if monitoring is enabled
+        // Space or inodes, whichever is closer to running out
         let effective_value = if self.monitor_inodes {
-            if let Some(inode_usage) = info.inode_usage_percentage() {
-                value_for_theming.max(inode_usage)
-            } else {
-                value_for_theming
-            }
+            value_for_theming.max(info.inode_usage_percentage().unwrap_or(0.0)).max(info.used_percentage())
         } else {
             value_for_theming
         };
         
-        Ok(format::themed_output(
+        let mut output = format::themed_output(
             formatted_text,
             Some(tooltip),
             percentage,
@@ -734,7 +1091,29 @@ impl Sensor for DiskSensor {
             self.warning_threshold as f64,
             self.critical_threshold as f64,
             &self.config.theme,
-        ))
+        );
+        
+        // A drive that keeps stalling is a problem no matter how full it is
+        let theme = &self.config.theme;
+        if let Some(latency) = self.latency.as_ref().and_then(LatencyTracker::sustained_latency) {
+            let class = theme.class_for_thresholds(latency, self.latency_warning_ms, self.latency_critical_ms);
+            if diff::severity(Some(class), theme) > diff::severity(output.class.as_deref(), theme) {
+                output.class = Some(class.to_owned());
+            }
+        }
+        
+        // Likewise a drive that reports failing SMART attributes or runs hot,
+        // a ZFS pool that is no longer healthy, or a mount about to fill up
+        let health_class = self.smart.as_ref().and_then(SmartMonitor::health).map(|health| health.class(theme));
+        let pool_class = self.zfs.as_ref().and_then(ZfsMonitor::pool).and_then(|pool| pool.class(theme))
+            .filter(|_| info.filesystem == "zfs");
+        for class in [health_class, self.drive_temp_class(), pool_class, self.forecast_class()].into_iter().flatten() {
+            if diff::severity(Some(class), theme) > diff::severity(output.class.as_deref(), theme) {
+                output.class = Some(class.to_owned());
+            }
+        }
+        
+        Ok(output)
     }
     
     fn name(&self) -> &str {
@@ -757,6 +1136,42 @@ impl Sensor for DiskSensor {
             }
         }
         
+        if let Some(warning) = config.get_custom("drive_temp_warning").and_then(|v| v.as_f64()) {
+            self.drive_temp_warning = Some(warning);
+        }
+        if let Some(critical) = config.get_custom("drive_temp_critical").and_then(|v| v.as_f64()) {
+            self.drive_temp_critical = Some(critical);
+        }
+        
+        if config.get_custom("btrfs").and_then(|v| v.as_bool()) == Some(false) {
+            self.btrfs = None;
+        }
+        if let Some(model) = config.get_custom("forecast_model").and_then(|v| v.as_str()) {
+            self.forecast_model = model.parse()?;
+        }
+        if let Some(days) = config.get_custom("full_warning_days").and_then(|v| v.as_f64()) {
+            self.full_warning_days = days;
+        }
+        if let Some(days) = config.get_custom("full_critical_days").and_then(|v| v.as_f64()) {
+            self.full_critical_days = days;
+        }
+        
+        if config.get_custom("zfs").and_then(|v| v.as_bool()) == Some(false) {
+            self.zfs = None;
+        }
+        
+        if let Some(ms) = config.get_custom("network_timeout_ms").and_then(|v| v.as_u64()) {
+            self.network.set_timeout(Duration::from_millis(ms));
+        }
+        
+        if config.get_custom("smart").and_then(|v| v.as_bool()) == Some(true) && self.smart.is_none() {
+            self.smart = Some(SmartMonitor::new());
+        }
+        
+        if config.get_custom("quota").and_then(|v| v.as_bool()) == Some(true) && self.quota.is_none() {
+            self.quota = Some(QuotaMonitor::new());
+        }
+        
         // Now move the values
         self.config = config;
         
@@ -771,16 +1186,20 @@ impl Sensor for DiskSensor {
     }
     
     fn check_availability(&self) -> Result<(), Self::Error> {
-        if !self.path.exists() {
+        let path = match &self.target {
+            Some(target) => PathBuf::from(target.resolve()?.mount_point),
+            None => self.path.clone(),
+        };
+        if !path.exists() {
             return Err(DiskError::InvalidPath {
-                path: self.path.display().to_string(),
+                path: path.display().to_string(),
                 reason: "Path no longer exists".to_string(),
             }.into());
         }
         
         // Test if we can read disk information
         let output = Command::new("df")
-            .arg(&self.path)
+            .arg(&path)
             .output()
             .map_err(|e| DiskError::CommandFailed {
                 command: "df".to_string(),
@@ -789,7 +1208,7 @@ impl Sensor for DiskSensor {
         
         if !output.status.success() {
             return Err(DiskError::UsageCalculation {
-                path: self.path.display().to_string(),
+                path: path.display().to_string(),
                 reason: "Cannot read disk usage information".to_string(),
             }.into());
         }
@@ -855,6 +1274,13 @@ mod tests {
         assert!(sensor.show_available);
         assert!(sensor.monitor_inodes);
         assert!(sensor.performance_monitoring);
+        
+        let sensor = DiskSensorBuilder::new("/tmp")
+            .metric(DiskMetric::Inodes)
+            .build()
+            .unwrap();
+        assert_eq!(sensor.metric, DiskMetric::Inodes);
+        assert!(sensor.monitor_inodes);
     }
     
     #[test]
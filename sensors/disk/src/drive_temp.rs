@@ -0,0 +1,127 @@
+//! Drive temperatures from hwmon.
+//!
+//! NVMe controllers register an `nvme` hwmon device and SATA drives get a
+//! `drivetemp` one once that module is loaded. Either hangs off the drive's
+//! device in sysfs, which is how a mount's backing disk is matched to its
+//! sensor.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// hwmon drivers that report drive temperatures.
+const DRIVE_HWMON_NAMES: &[&str] = &["nvme", "drivetemp"];
+
+/// Temperature of one drive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DriveTemp {
+    /// hwmon driver (`nvme` or `drivetemp`)
+    pub driver: String,
+    /// Current temperature in °C (NVMe: the composite sensor)
+    pub celsius: f64,
+    /// Temperature the drive considers high, in °C
+    pub max: Option<f64>,
+    /// Temperature the drive considers critical, in °C
+    pub crit: Option<f64>,
+}
+
+impl DriveTemp {
+    /// Temperature of whole disk `disk` (e.g. `nvme0n1`), if a drive hwmon
+    /// device reports it.
+    pub fn read(disk: &str) -> Option<Self> {
+        Self::read_in(Path::new("/sys/class/hwmon"), &Path::new("/sys/class/block").join(disk))
+    }
+
+    /// Temperature of the disk at sysfs `block_dir`, searching hwmon devices
+    /// below `hwmon_root` (useful for testing).
+    pub fn read_in(hwmon_root: &Path, block_dir: &Path) -> Option<Self> {
+        let disk_device = fs::canonicalize(block_dir.join("device")).ok()?;
+
+        fs::read_dir(hwmon_root).ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .find_map(|hwmon| {
+                let driver = read_trimmed(&hwmon.join("name"))?;
+                if !DRIVE_HWMON_NAMES.contains(&driver.as_str()) {
+                    return None;
+                }
+                // NVMe hwmon sits on the controller or its PCI function, both
+                // at or above the namespace's device
+                let hwmon_device = fs::canonicalize(hwmon.join("device")).ok()?;
+                if !disk_device.starts_with(&hwmon_device) {
+                    return None;
+                }
+                Some(Self {
+                    celsius: read_millidegrees(hwmon.join("temp1_input"))?,
+                    max: read_millidegrees(hwmon.join("temp1_max")),
+                    crit: read_millidegrees(hwmon.join("temp1_crit")),
+                    driver,
+                })
+            })
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_owned())
+}
+
+fn read_millidegrees(path: PathBuf) -> Option<f64> {
+    read_trimmed(&path)?.parse::<i64>().ok().map(|m| m as f64 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn test_drive_temp_matching() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let pci = root.join("devices").join("0000:01:00.0");
+        let controller = pci.join("nvme").join("nvme0");
+        let sata = root.join("devices").join("0:0:0:0");
+        fs::create_dir_all(&controller).unwrap();
+        fs::create_dir_all(&sata).unwrap();
+
+        let block = root.join("block");
+        fs::create_dir_all(block.join("nvme0n1")).unwrap();
+        fs::create_dir_all(block.join("sda")).unwrap();
+        fs::create_dir_all(block.join("sdb")).unwrap();
+        symlink(&controller, block.join("nvme0n1").join("device")).unwrap();
+        symlink(&sata, block.join("sda").join("device")).unwrap();
+        symlink(root.join("devices"), block.join("sdb").join("device")).unwrap();
+
+        let hwmon = root.join("hwmon");
+        for (name, driver, device, temp) in [
+            ("hwmon0", "k10temp", &pci, "60000"),
+            ("hwmon1", "nvme", &pci, "41850"),
+            ("hwmon2", "drivetemp", &sata, "35000"),
+        ] {
+            fs::create_dir_all(hwmon.join(name)).unwrap();
+            fs::write(hwmon.join(name).join("name"), format!("{}\n", driver)).unwrap();
+            fs::write(hwmon.join(name).join("temp1_input"), temp).unwrap();
+            symlink(device, hwmon.join(name).join("device")).unwrap();
+        }
+        fs::write(hwmon.join("hwmon1").join("temp1_max"), "81850").unwrap();
+        fs::write(hwmon.join("hwmon1").join("temp1_crit"), "84850").unwrap();
+
+        let nvme = DriveTemp::read_in(&hwmon, &block.join("nvme0n1"));
+        let sda = DriveTemp::read_in(&hwmon, &block.join("sda"));
+        let sdb = DriveTemp::read_in(&hwmon, &block.join("sdb"));
+
+        let nvme = nvme.unwrap();
+        assert_eq!(nvme.driver, "nvme");
+        assert_eq!(nvme.celsius, 41.85);
+        assert_eq!(nvme.max, Some(81.85));
+        assert_eq!(nvme.crit, Some(84.85));
+
+        let sda = sda.unwrap();
+        assert_eq!(sda.driver, "drivetemp");
+        assert_eq!(sda.celsius, 35.0);
+        assert_eq!(sda.max, None);
+
+        assert_eq!(sdb, None);
+    }
+}
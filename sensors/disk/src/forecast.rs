@@ -0,0 +1,249 @@
+//! "Days until full" prediction from persisted usage history.
+//!
+//! A sensor restarts with every login, so an in-memory trend never sees more
+//! than a few hours. Usage is instead sampled every [`SAMPLE_INTERVAL`] into a
+//! small JSON file per mount in the XDG state directory and kept for
+//! [`RETENTION`]. A least-squares fit over that history smooths out temporary
+//! files and package caches coming and going.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use waysensor_rs_core::SensorError;
+
+/// Minimum time between two persisted samples.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// How far back samples are kept.
+pub const RETENTION: Duration = Duration::from_secs(30 * 24 * 3600);
+
+/// History needed before predicting anything.
+const MIN_SPAN: Duration = Duration::from_secs(3600);
+
+const SECS_PER_DAY: f64 = 24.0 * 3600.0;
+
+/// Space usage of a mount at one point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Unix time in seconds
+    pub time: u64,
+    /// Used bytes
+    pub used: u64,
+    /// Available bytes
+    pub available: u64,
+}
+
+impl Sample {
+    /// Sample taken now.
+    pub fn now(used: u64, available: u64) -> Self {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        Self { time, used, available }
+    }
+}
+
+/// Curve fitted to the usage history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FitModel {
+    /// Constant growth in bytes per day
+    #[default]
+    Linear,
+    /// Constant growth in percent per day, for usage that grows with itself
+    Exponential,
+}
+
+impl std::str::FromStr for FitModel {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "linear" => Ok(Self::Linear),
+            "exponential" | "exp" => Ok(Self::Exponential),
+            _ => Err(SensorError::config_with_value(
+                "Invalid forecast model. Valid options: linear, exponential",
+                s,
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for FitModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Linear => "linear",
+            Self::Exponential => "exponential",
+        })
+    }
+}
+
+/// Predicted time until a mount runs out of space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Forecast {
+    /// Days until available space reaches zero
+    pub days_until_full: f64,
+    /// Current growth in bytes per day
+    pub growth_per_day: f64,
+    /// Days of history the fit is based on
+    pub span_days: f64,
+    /// Model used
+    pub model: FitModel,
+}
+
+/// Slope of the least-squares line through `points`.
+fn slope(points: &[(f64, f64)]) -> Option<f64> {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (x, y) in points {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance += (x - mean_x).powi(2);
+    }
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+/// Usage samples of one mount, persisted across restarts.
+#[derive(Debug, Default)]
+pub struct UsageHistory {
+    /// History file; `None` keeps the history in memory only
+    path: Option<PathBuf>,
+    samples: Vec<Sample>,
+}
+
+impl UsageHistory {
+    /// History of `mount`, loaded from its file if there is one.
+    pub fn load(mount: &Path) -> Self {
+        let path = history_path(mount);
+        let samples = path.as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path, samples }
+    }
+
+    /// History that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Samples, oldest first.
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    /// Add `sample` unless the last one is younger than [`SAMPLE_INTERVAL`],
+    /// dropping samples older than [`RETENTION`] and saving the history.
+    ///
+    /// Returns whether the sample was added. A history that cannot be saved
+    /// still works for this run, so write errors are ignored.
+    pub fn record(&mut self, sample: Sample) -> bool {
+        if self.samples.last().is_some_and(|last| sample.time < last.time + SAMPLE_INTERVAL.as_secs()) {
+            return false;
+        }
+        let cutoff = sample.time.saturating_sub(RETENTION.as_secs());
+        self.samples.retain(|s| s.time >= cutoff);
+        self.samples.push(sample);
+
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if let Ok(json) = serde_json::to_string(&self.samples) {
+                let _ = fs::write(path, json);
+            }
+        }
+        true
+    }
+
+    /// Predict when the mount fills up, or `None` while usage is not growing
+    /// or the history is shorter than an hour.
+    pub fn forecast(&self, model: FitModel) -> Option<Forecast> {
+        let (first, last) = (self.samples.first()?, self.samples.last()?);
+        let span = last.time - first.time;
+        if span < MIN_SPAN.as_secs() || self.samples.len() < 3 {
+            return None;
+        }
+        let time = |s: &Sample| (s.time - first.time) as f64;
+
+        let (growth_per_sec, secs_until_full) = match model {
+            FitModel::Linear => {
+                let points: Vec<(f64, f64)> = self.samples.iter().map(|s| (time(s), s.used as f64)).collect();
+                let rate = slope(&points).filter(|&rate| rate > 0.0)?;
+                (rate, last.available as f64 / rate)
+            }
+            FitModel::Exponential => {
+                let points: Vec<(f64, f64)> = self.samples.iter()
+                    .filter(|s| s.used > 0)
+                    .map(|s| (time(s), (s.used as f64).ln()))
+                    .collect();
+                let rate = slope(&points).filter(|&rate| rate > 0.0)?;
+                let capacity = (last.used + last.available) as f64;
+                (last.used as f64 * rate, (capacity / last.used.max(1) as f64).ln() / rate)
+            }
+        };
+
+        Some(Forecast {
+            days_until_full: secs_until_full / SECS_PER_DAY,
+            growth_per_day: growth_per_sec * SECS_PER_DAY,
+            span_days: span as f64 / SECS_PER_DAY,
+            model,
+        })
+    }
+}
+
+/// History file of `mount`, e.g. `~/.local/state/waysensor-rs/disk-history/home.json`.
+pub fn history_path(mount: &Path) -> Option<PathBuf> {
+    let name = match mount.to_string_lossy().trim_matches('/') {
+        "" => "root".to_string(),
+        path => path.replace('/', "-"),
+    };
+    dirs::state_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join("waysensor-rs").join("disk-history").join(format!("{}.json", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_usage_forecast() {
+        let mut history = UsageHistory::in_memory();
+        let start = 1_700_000_000;
+        // 1 GB/day for 4 days with a temporary 2 GB spike on day 2
+        for hour in 0..=96u64 {
+            let spike = if (48..52).contains(&hour) { 2 * GB } else { 0 };
+            let used = 100 * GB + hour * GB / 24 + spike;
+            assert!(history.record(Sample { time: start + hour * 3600, used, available: 200 * GB - used }));
+        }
+        // Too soon after the last sample
+        assert!(!history.record(Sample { time: start + 96 * 3600 + 60, used: 0, available: 0 }));
+
+        let linear = history.forecast(FitModel::Linear).unwrap();
+        assert!((linear.growth_per_day / GB as f64 - 1.0).abs() < 0.05, "{:?}", linear);
+        assert!((linear.days_until_full - 96.0).abs() < 5.0, "{:?}", linear);
+        assert_eq!(linear.span_days, 4.0);
+
+        let exponential = history.forecast(FitModel::Exponential).unwrap();
+        assert!(exponential.days_until_full < linear.days_until_full);
+
+        // Shrinking usage never fills up
+        let mut shrinking = UsageHistory::in_memory();
+        for hour in 0..10u64 {
+            shrinking.record(Sample { time: start + hour * 3600, used: (50 - hour) * GB, available: 50 * GB });
+        }
+        assert_eq!(shrinking.forecast(FitModel::Linear), None);
+
+        // Old samples are dropped
+        history.record(Sample { time: start + RETENTION.as_secs() + 50 * 3600, used: 150 * GB, available: 50 * GB });
+        assert!(history.samples().iter().all(|s| s.time >= start + 50 * 3600));
+
+        assert_eq!("exp".parse::<FitModel>().unwrap(), FitModel::Exponential);
+        assert!(history_path(Path::new("/")).unwrap().ends_with("disk-history/root.json"));
+        assert!(history_path(Path::new("/mnt/data")).unwrap().ends_with("disk-history/mnt-data.json"));
+    }
+}
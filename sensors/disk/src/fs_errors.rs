@@ -0,0 +1,343 @@
+//! Filesystem error counters.
+//!
+//! ext4 counts errors it detected in `/sys/fs/ext4/<dev>/errors_count` and
+//! btrfs keeps per-device I/O and checksum error stats in
+//! `/sys/fs/btrfs/<uuid>/devinfo/<id>/error_stats`. Both persist on disk, so a
+//! non-zero count when a filesystem is first seen means old damage while any
+//! increase after that means the filesystem is hitting corruption right now.
+
+use waysensor_rs_core::{format, Sensor, SensorConfig, SensorError, WaybarOutput};
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+};
+
+/// Error count of one mounted filesystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FsErrorCount {
+    /// `ext4` or `btrfs`
+    pub fs_type: &'static str,
+    /// Device name (ext4) or UUID (btrfs); stable across reads
+    pub id: String,
+    /// Device name (ext4) or label, falling back to the UUID (btrfs)
+    pub name: String,
+    /// Block devices backing the filesystem
+    pub devices: Vec<String>,
+    /// Total errors recorded by the filesystem
+    pub errors: u64,
+    /// Per-kind breakdown where the filesystem reports one
+    pub detail: Vec<(String, u64)>,
+}
+
+impl FsErrorCount {
+    /// Key identifying the filesystem across reads.
+    ///
+    /// btrfs labels are neither unique nor required, so btrfs is keyed by UUID.
+    fn key(&self) -> String {
+        format!("{}:{}", self.fs_type, self.id)
+    }
+}
+
+/// Error counts of all ext4 and btrfs filesystems the kernel knows about.
+pub fn scan() -> Vec<FsErrorCount> {
+    scan_in(Path::new("/sys/fs"))
+}
+
+/// Error counts below a sysfs `fs` directory (useful for testing).
+pub fn scan_in(sys_fs: &Path) -> Vec<FsErrorCount> {
+    let mut counts = scan_ext4(&sys_fs.join("ext4"));
+    counts.extend(scan_btrfs(&sys_fs.join("btrfs")));
+    counts
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_owned())
+}
+
+fn sorted_entries(dir: &Path) -> Vec<fs::DirEntry> {
+    let mut entries: Vec<fs::DirEntry> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().collect())
+        .unwrap_or_default();
+    entries.sort_by_key(|entry| entry.file_name());
+    entries
+}
+
+fn scan_ext4(dir: &Path) -> Vec<FsErrorCount> {
+    sorted_entries(dir)
+        .into_iter()
+        .filter_map(|entry| {
+            // Kernels before 5.x have no errors_count; skip rather than report zero
+            let errors = read_trimmed(&entry.path().join("errors_count"))?.parse().ok()?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            Some(FsErrorCount {
+                fs_type: "ext4",
+                id: name.clone(),
+                devices: vec![name.clone()],
+                name,
+                errors,
+                detail: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+fn scan_btrfs(dir: &Path) -> Vec<FsErrorCount> {
+    sorted_entries(dir)
+        .into_iter()
+        .filter(|entry| entry.path().join("devinfo").is_dir())
+        .map(|entry| {
+            let path = entry.path();
+            let uuid = entry.file_name().to_string_lossy().into_owned();
+            let label = read_trimmed(&path.join("label")).filter(|l| !l.is_empty());
+            let name = label.unwrap_or_else(|| uuid.clone());
+            let devices = sorted_entries(&path.join("devices"))
+                .iter()
+                .map(|dev| dev.file_name().to_string_lossy().into_owned())
+                .collect();
+
+            let mut detail: Vec<(String, u64)> = Vec::new();
+            for dev in sorted_entries(&path.join("devinfo")) {
+                let Some(stats) = read_trimmed(&dev.path().join("error_stats")) else { continue };
+                for (kind, count) in parse_error_stats(&stats) {
+                    match detail.iter_mut().find(|(k, _)| *k == kind) {
+                        Some((_, total)) => *total += count,
+                        None => detail.push((kind, count)),
+                    }
+                }
+            }
+
+            FsErrorCount {
+                fs_type: "btrfs",
+                id: uuid,
+                name,
+                devices,
+                errors: detail.iter().map(|(_, count)| count).sum(),
+                detail,
+            }
+        })
+        .collect()
+}
+
+/// Parse a btrfs `error_stats` file (`write_errs 0` lines).
+pub fn parse_error_stats(content: &str) -> Vec<(String, u64)> {
+    content.lines()
+        .filter_map(|line| {
+            let (kind, count) = line.split_once(char::is_whitespace)?;
+            let kind = kind.strip_suffix("_errs").unwrap_or(kind);
+            Some((kind.to_owned(), count.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Map block device names (`nvme0n1p2`, `dm-0`) to their mount points.
+fn mount_points() -> HashMap<String, String> {
+    let Ok(mounts) = fs::read_to_string("/proc/self/mounts") else {
+        return HashMap::new();
+    };
+    let mut points = HashMap::new();
+    for line in mounts.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(device), Some(mount)) = (fields.next(), fields.next()) else { continue };
+        if !device.starts_with("/dev/") {
+            continue;
+        }
+        let Some(name) = fs::canonicalize(device).ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned())) else { continue };
+        // Keep the first mount; later entries for the device are usually bind mounts
+        points.entry(name).or_insert_with(|| mount.replace("\\040", " "));
+    }
+    points
+}
+
+/// Sensor that turns critical as soon as any filesystem records new errors.
+#[derive(Debug)]
+pub struct FsErrorSensor {
+    name: String,
+    config: SensorConfig,
+    /// Error count of each filesystem when it was first seen
+    baseline: HashMap<String, u64>,
+}
+
+impl FsErrorSensor {
+    pub fn new() -> Self {
+        Self {
+            name: "fs-errors".to_string(),
+            config: SensorConfig::default(),
+            baseline: HashMap::new(),
+        }
+    }
+
+    /// Record the baseline of filesystems seen for the first time.
+    ///
+    /// A disk plugged in after startup brings its historical errors along;
+    /// those are old damage, not something happening now. A count that drops
+    /// (the filesystem was recreated or the counter reset) lowers the baseline.
+    fn observe(&mut self, counts: &[FsErrorCount]) {
+        for count in counts {
+            let baseline = self.baseline.entry(count.key()).or_insert(count.errors);
+            *baseline = (*baseline).min(count.errors);
+        }
+    }
+
+    /// Errors recorded since the filesystem was first seen.
+    fn new_errors(&self, count: &FsErrorCount) -> u64 {
+        let baseline = self.baseline.get(&count.key()).copied().unwrap_or(count.errors);
+        count.errors.saturating_sub(baseline)
+    }
+}
+
+impl Default for FsErrorSensor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sensor for FsErrorSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let counts = scan();
+        self.observe(&counts);
+
+        let total: u64 = counts.iter().map(|c| c.errors).sum();
+        let total_new: u64 = counts.iter().map(|c| self.new_errors(c)).sum();
+
+        let status = match (total, total_new) {
+            (0, _) => "no FS errors".to_string(),
+            (total, 0) => format!("{} FS errors", total),
+            (_, new) => format!("{} new FS errors", new),
+        };
+        let text = format::with_icon_and_colors(&status, &self.config.icons.disk, &self.config);
+
+        let mounts = mount_points();
+        let mut lines = vec![format::key_only("Filesystem Errors", &self.config)];
+        if counts.is_empty() {
+            lines.push(format::key_value("Filesystems", "no ext4 or btrfs error counters found", &self.config));
+        }
+        for count in &counts {
+            let mount = count.devices.iter().find_map(|dev| mounts.get(dev));
+            let label = match mount {
+                Some(mount) => format!("{} ({} {})", mount, count.fs_type, count.name),
+                None => format!("{} {}", count.fs_type, count.name),
+            };
+            let mut value = match self.new_errors(count) {
+                0 if count.errors == 0 => "✓ no errors".to_string(),
+                0 => format!("⚠️ {} errors (before first seen)", count.errors),
+                new => format!("🔴 {} errors, {} new", count.errors, new),
+            };
+            let nonzero: Vec<String> = count.detail.iter()
+                .filter(|(_, n)| *n > 0)
+                .map(|(kind, n)| format!("{} {}", kind, n))
+                .collect();
+            if !nonzero.is_empty() {
+                value.push_str(&format!(" [{}]", nonzero.join(", ")));
+            }
+            lines.push(format::key_value(&label, &value, &self.config));
+        }
+
+        let theme = &self.config.theme;
+        let class = if total_new > 0 {
+            &theme.critical
+        } else if total > 0 {
+            &theme.warning
+        } else {
+            &theme.normal
+        };
+
+        Ok(WaybarOutput {
+            text,
+            tooltip: Some(lines.join("\n")),
+            class: Some(class.clone()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fs_error_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let ext4 = root.join("ext4");
+        fs::create_dir_all(ext4.join("nvme0n1p2")).unwrap();
+        fs::write(ext4.join("nvme0n1p2").join("errors_count"), "3\n").unwrap();
+        // Old kernel without the counter
+        fs::create_dir_all(ext4.join("sda1")).unwrap();
+
+        let btrfs = root.join("btrfs").join("0f3c1e6a-uuid");
+        fs::create_dir_all(btrfs.join("devices").join("dm-1")).unwrap();
+        fs::create_dir_all(btrfs.join("devinfo").join("1")).unwrap();
+        fs::create_dir_all(btrfs.join("devinfo").join("2")).unwrap();
+        fs::write(btrfs.join("label"), "data\n").unwrap();
+        fs::write(btrfs.join("devinfo").join("1").join("error_stats"),
+            "write_errs 0\nread_errs 1\nflush_errs 0\ncorruption_errs 2\ngeneration_errs 0\n").unwrap();
+        fs::write(btrfs.join("devinfo").join("2").join("error_stats"),
+            "write_errs 0\nread_errs 0\nflush_errs 0\ncorruption_errs 4\ngeneration_errs 0\n").unwrap();
+        // Non-filesystem entry in /sys/fs/btrfs
+        fs::create_dir_all(root.join("btrfs").join("features")).unwrap();
+
+        let counts = scan_in(root);
+
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0].fs_type, "ext4");
+        assert_eq!(counts[0].name, "nvme0n1p2");
+        assert_eq!(counts[0].errors, 3);
+
+        assert_eq!(counts[1].name, "data");
+        assert_eq!(counts[1].id, "0f3c1e6a-uuid");
+        assert_eq!(counts[1].devices, vec!["dm-1".to_owned()]);
+        assert_eq!(counts[1].errors, 7);
+        assert!(counts[1].detail.contains(&("corruption".to_owned(), 6)));
+        assert!(counts[1].detail.contains(&("read".to_owned(), 1)));
+    }
+
+    fn count(id: &str, errors: u64) -> FsErrorCount {
+        FsErrorCount {
+            fs_type: "btrfs",
+            id: id.to_owned(),
+            name: "data".to_owned(),
+            devices: Vec::new(),
+            errors,
+            detail: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_baseline_at_first_sight() {
+        let mut sensor = FsErrorSensor::new();
+        sensor.observe(&[count("root-uuid", 3)]);
+        assert_eq!(sensor.new_errors(&count("root-uuid", 3)), 0);
+
+        // USB disk with old errors plugged in later, sharing the label
+        let root = count("root-uuid", 5);
+        let usb = count("usb-uuid", 40);
+        sensor.observe(&[root.clone(), usb.clone()]);
+        assert_eq!(sensor.new_errors(&root), 2);
+        assert_eq!(sensor.new_errors(&usb), 0);
+
+        let usb = count("usb-uuid", 41);
+        sensor.observe(&[root, usb.clone()]);
+        assert_eq!(sensor.new_errors(&usb), 1);
+
+        // Recreated filesystem resets its counter
+        sensor.observe(&[count("usb-uuid", 0)]);
+        assert_eq!(sensor.new_errors(&count("usb-uuid", 2)), 2);
+    }
+}
@@ -0,0 +1,99 @@
+//! Removable drives that come and go.
+//!
+//! The kernel flags a change of the mount table by making
+//! `/proc/self/mountinfo` poll with `POLLPRI`, so checking for plugged or
+//! ejected drives costs one non-blocking `poll` per read instead of parsing
+//! `/proc/mounts` every time. Whether a filesystem sits on removable media is
+//! read from sysfs: the block layer's `removable` flag covers card readers
+//! and USB sticks, while USB hard disks only give themselves away by the USB
+//! controller in their device path.
+
+use std::{
+    fs::{self, File},
+    io,
+    os::fd::AsRawFd,
+    path::Path,
+};
+
+/// Notices changes of the mount table.
+#[derive(Debug)]
+pub struct MountWatcher {
+    mountinfo: File,
+    primed: bool,
+}
+
+impl MountWatcher {
+    /// Watch the mount table of this process' mount namespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `/proc/self/mountinfo` cannot be opened.
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            mountinfo: File::open("/proc/self/mountinfo")?,
+            primed: false,
+        })
+    }
+
+    /// Whether anything was mounted or unmounted since the last call.
+    /// The first call always returns `true`.
+    pub fn changed(&mut self) -> bool {
+        let mut fd = libc::pollfd {
+            fd: self.mountinfo.as_raw_fd(),
+            events: libc::POLLPRI,
+            revents: 0,
+        };
+        // SAFETY: `fd` is a valid pollfd for an open file and we pass a count of 1.
+        let ready = unsafe { libc::poll(&mut fd, 1, 0) };
+        let changed = ready > 0 && fd.revents & (libc::POLLPRI | libc::POLLERR) != 0;
+        changed || !std::mem::replace(&mut self.primed, true)
+    }
+}
+
+/// Whether the filesystem on `device` (e.g. `/dev/sdb1`) is on a removable
+/// drive. Devices without a sysfs entry (network shares, ZFS datasets)
+/// are not.
+pub fn is_removable(device: &str) -> bool {
+    let Some(name) = fs::canonicalize(device).ok()
+        .and_then(|node| node.file_name().map(|name| name.to_owned()))
+    else {
+        return false;
+    };
+    let class = Path::new("/sys/class/block").join(name);
+    let Ok(sys_path) = fs::canonicalize(&class) else {
+        return false;
+    };
+    // The removable flag lives on the whole disk, one level above a partition
+    let disk = if class.join("partition").exists() { sys_path.parent() } else { Some(sys_path.as_path()) };
+    let flag = disk.and_then(|disk| fs::read_to_string(disk.join("removable")).ok());
+    removable_from_sysfs(flag.as_deref(), &sys_path)
+}
+
+/// Decide from a disk's `removable` flag and its sysfs device path.
+pub fn removable_from_sysfs(flag: Option<&str>, sys_path: &Path) -> bool {
+    flag.map(str::trim) == Some("1")
+        || sys_path.components().any(|c| c.as_os_str().to_string_lossy().starts_with("usb"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_removable_detection() {
+        let usb_disk = Path::new("/sys/devices/pci0000:00/0000:00:14.0/usb2/2-1/2-1:1.0/host6/target6:0:0/6:0:0:0/block/sdb/sdb1");
+        let nvme = Path::new("/sys/devices/pci0000:00/0000:00:1d.0/0000:3d:00.0/nvme/nvme0/nvme0n1/nvme0n1p2");
+        let card_reader = Path::new("/sys/devices/pci0000:00/0000:00:1c.0/0000:02:00.0/rtsx_pci_sdmmc.0/mmc_host/mmc0/mmc0:1234/block/mmcblk0");
+
+        assert!(removable_from_sysfs(Some("0\n"), usb_disk));
+        assert!(!removable_from_sysfs(Some("0\n"), nvme));
+        assert!(removable_from_sysfs(Some("1\n"), card_reader));
+        assert!(!removable_from_sysfs(None, nvme));
+
+        assert!(!is_removable("tank/home"));
+        assert!(!is_removable("server:/export"));
+
+        let mut watcher = MountWatcher::new().unwrap();
+        assert!(watcher.changed());
+    }
+}
@@ -0,0 +1,340 @@
+//! Block device throughput, IOPS and utilization from `/proc/diskstats`.
+//!
+//! Every counter in `/proc/diskstats` is cumulative, so rates come from the
+//! difference between two snapshots. Utilization is the share of wall time
+//! the device had at least one request in flight (`%util` in iostat); on
+//! NVMe and RAID devices that serve requests in parallel it can read 100%
+//! well before the device is saturated.
+
+use crate::latency::BlockStat;
+use waysensor_rs_core::{format, procfs, Sensor, SensorConfig, SensorError, WaybarOutput};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::Path,
+    time::Instant,
+};
+
+/// `/proc/diskstats` counts sectors of 512 bytes regardless of the device's block size.
+pub const SECTOR_SIZE: u64 = 512;
+
+/// Primary metric of the disk sensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiskMetric {
+    /// Filesystem space usage from `df`
+    #[default]
+    Usage,
+    /// Inode usage from `df -i`, for filesystems full of small files
+    Inodes,
+    /// Throughput, IOPS and utilization from `/proc/diskstats`
+    Io,
+}
+
+impl fmt::Display for DiskMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Usage => "usage",
+            Self::Inodes => "inodes",
+            Self::Io => "io",
+        };
+        f.write_str(name)
+    }
+}
+
+impl std::str::FromStr for DiskMetric {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "usage" | "space" => Ok(Self::Usage),
+            "inodes" | "inode" => Ok(Self::Inodes),
+            "io" | "throughput" => Ok(Self::Io),
+            _ => Err(SensorError::config_with_value(
+                "Invalid disk metric. Valid options: usage, inodes, io",
+                s,
+            )),
+        }
+    }
+}
+
+/// Parse `/proc/diskstats` into per-device counters, keyed by device name.
+pub fn parse_diskstats(content: &str) -> HashMap<String, BlockStat> {
+    content.lines()
+        .filter_map(|line| {
+            // major minor name, then the same counters as the device's `stat` file
+            let (_major, rest) = line.trim_start().split_once(char::is_whitespace)?;
+            let (_minor, rest) = rest.trim_start().split_once(char::is_whitespace)?;
+            let (name, rest) = rest.trim_start().split_once(char::is_whitespace)?;
+            Some((name.to_owned(), BlockStat::parse(rest)?))
+        })
+        .collect()
+}
+
+/// Rates of one device between two snapshots.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IoRates {
+    /// Bytes read per second
+    pub read_bytes: f64,
+    /// Bytes written per second
+    pub write_bytes: f64,
+    /// Reads completed per second
+    pub read_iops: f64,
+    /// Writes completed per second
+    pub write_iops: f64,
+    /// Percentage of time the device was busy (0-100)
+    pub utilization: f64,
+}
+
+impl IoRates {
+    /// Rates between `prev` and `current`, taken `elapsed_secs` apart.
+    ///
+    /// Counters going backwards (device re-created) count as no activity.
+    pub fn between(prev: &BlockStat, current: &BlockStat, elapsed_secs: f64) -> Self {
+        if elapsed_secs <= 0.0 {
+            return Self::default();
+        }
+        let rate = |now: u64, then: u64| now.saturating_sub(then) as f64 / elapsed_secs;
+        let busy_ms = current.io_ticks_ms.saturating_sub(prev.io_ticks_ms) as f64;
+        Self {
+            read_bytes: rate(current.sectors_read, prev.sectors_read) * SECTOR_SIZE as f64,
+            write_bytes: rate(current.sectors_written, prev.sectors_written) * SECTOR_SIZE as f64,
+            read_iops: rate(current.reads, prev.reads),
+            write_iops: rate(current.writes, prev.writes),
+            utilization: (busy_ms / (elapsed_secs * 1000.0) * 100.0).clamp(0.0, 100.0),
+        }
+    }
+
+    /// Combined read and write operations per second.
+    pub fn iops(&self) -> f64 {
+        self.read_iops + self.write_iops
+    }
+}
+
+/// Whole physical disks: block devices backed by hardware, which excludes
+/// partitions, loop, zram and device-mapper nodes.
+pub fn physical_disks() -> Vec<String> {
+    physical_disks_in(Path::new("/sys/block"))
+}
+
+/// Physical disks below a sysfs `block` directory (useful for testing).
+pub fn physical_disks_in(sys_block: &Path) -> Vec<String> {
+    let mut disks: Vec<String> = fs::read_dir(sys_block)
+        .map(|entries| entries.flatten()
+            .filter(|entry| entry.path().join("device").exists())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .collect())
+        .unwrap_or_default();
+    disks.sort();
+    disks
+}
+
+fn format_rate(bytes_per_sec: f64) -> String {
+    format!("{}/s", format::bytes_to_human(bytes_per_sec as u64))
+}
+
+/// Sensor showing disk throughput, with the CSS class driven by the busiest
+/// device's utilization.
+#[derive(Debug)]
+pub struct IoSensor {
+    name: String,
+    config: SensorConfig,
+    devices: Vec<String>,
+    warning_threshold: f64,
+    critical_threshold: f64,
+    prev: Option<(HashMap<String, BlockStat>, Instant)>,
+    utilization_history: Vec<f64>,
+}
+
+impl IoSensor {
+    /// Monitor `devices` (names as in `/proc/diskstats`, e.g. `nvme0n1`),
+    /// or every physical disk when empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `/proc/diskstats` is unreadable, a requested
+    /// device does not exist, or no physical disk was found.
+    pub fn new(devices: Vec<String>, warning_threshold: f64, critical_threshold: f64) -> Result<Self, SensorError> {
+        let stats = procfs::read_with("/proc/diskstats", parse_diskstats)?;
+
+        let devices: Vec<String> = devices.iter()
+            .map(|device| device.trim_start_matches("/dev/").to_owned())
+            .collect();
+        if let Some(missing) = devices.iter().find(|device| !stats.contains_key(*device)) {
+            return Err(SensorError::unavailable(format!("Block device not found: {}", missing)));
+        }
+        let devices = if devices.is_empty() { physical_disks() } else { devices };
+        if devices.is_empty() {
+            return Err(SensorError::unavailable("No physical disks found in /sys/block"));
+        }
+
+        let name = match devices.as_slice() {
+            [device] => format!("disk-io-{}", device),
+            _ => "disk-io".to_string(),
+        };
+
+        Ok(Self {
+            name,
+            config: SensorConfig::default(),
+            devices,
+            warning_threshold,
+            critical_threshold,
+            prev: None,
+            utilization_history: Vec::new(),
+        })
+    }
+}
+
+impl Sensor for IoSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let stats = procfs::read_with("/proc/diskstats", parse_diskstats)?;
+        let now = Instant::now();
+
+        // First read has nothing to compare against and reports idle devices
+        let rates: Vec<(&String, IoRates)> = self.devices.iter()
+            .map(|device| {
+                let rates = match (&self.prev, stats.get(device)) {
+                    (Some((prev, then)), Some(current)) => prev.get(device)
+                        .map(|prev| IoRates::between(prev, current, now.duration_since(*then).as_secs_f64()))
+                        .unwrap_or_default(),
+                    _ => IoRates::default(),
+                };
+                (device, rates)
+            })
+            .collect();
+
+        let read_bytes: f64 = rates.iter().map(|(_, r)| r.read_bytes).sum();
+        let write_bytes: f64 = rates.iter().map(|(_, r)| r.write_bytes).sum();
+        let busiest = rates.iter().map(|(_, r)| r.utilization).fold(0.0, f64::max);
+
+        let text = format::with_icon_and_colors(
+            &format!("R {} W {}", format_rate(read_bytes), format_rate(write_bytes)),
+            &self.config.icons.disk,
+            &self.config,
+        );
+
+        let mut lines = vec![format::key_only("Disk I/O", &self.config)];
+        for (device, rate) in &rates {
+            lines.push(format::key_value(
+                device,
+                &format!("{:.0}% busy", rate.utilization),
+                &self.config,
+            ));
+            lines.push(format::key_value(
+                "  Read",
+                &format!("{} ({:.0} IOPS)", format_rate(rate.read_bytes), rate.read_iops),
+                &self.config,
+            ));
+            lines.push(format::key_value(
+                "  Write",
+                &format!("{} ({:.0} IOPS)", format_rate(rate.write_bytes), rate.write_iops),
+                &self.config,
+            ));
+        }
+        if rates.len() > 1 {
+            let iops: f64 = rates.iter().map(|(_, r)| r.iops()).sum();
+            lines.push(format::key_value("Total IOPS", &format!("{:.0}", iops), &self.config));
+        }
+
+        self.utilization_history.push(busiest);
+        if self.utilization_history.len() > self.config.visuals.sparkline_length {
+            self.utilization_history.remove(0);
+        }
+        if self.config.visuals.sparklines && self.utilization_history.len() > 1 {
+            let sparkline = format::create_sparkline(&self.utilization_history, self.config.visuals.sparkline_style);
+            if !sparkline.is_empty() {
+                let colored_sparkline = format::colored_sparkline(&sparkline, self.config.sparkline_color.as_deref());
+                lines.push(format::key_value("Utilization History", &colored_sparkline, &self.config));
+            }
+        }
+
+        self.prev = Some((stats, now));
+
+        Ok(format::themed_output(
+            text,
+            Some(lines.join("\n")),
+            Some(busiest.round() as u8),
+            busiest,
+            self.warning_threshold,
+            self.critical_threshold,
+            &self.config.theme,
+        ))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diskstats_rates() {
+        let before = parse_diskstats(
+            " 259       0 nvme0n1 1000 10 20000 500 2000 20 40000 900 0 1500 1400 0 0 0 0 0 0\n\
+              259       1 nvme0n1p1 10 0 80 1 0 0 0 0 0 2 1\n\
+                7       0 loop0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0 0\n\
+              garbage\n",
+        );
+        assert_eq!(before.len(), 3);
+        assert_eq!(before["nvme0n1"], BlockStat {
+            ios: 3000,
+            ticks_ms: 1400,
+            reads: 1000,
+            sectors_read: 20000,
+            writes: 2000,
+            sectors_written: 40000,
+            io_ticks_ms: 1500,
+        });
+
+        let after = BlockStat {
+            reads: 1200,
+            sectors_read: 24096,
+            writes: 2100,
+            sectors_written: 40000,
+            io_ticks_ms: 2500,
+            ..Default::default()
+        };
+        let rates = IoRates::between(&before["nvme0n1"], &after, 2.0);
+        assert_eq!(rates.read_iops, 100.0);
+        assert_eq!(rates.write_iops, 50.0);
+        assert_eq!(rates.iops(), 150.0);
+        assert_eq!(rates.read_bytes, 2048.0 * 512.0);
+        assert_eq!(rates.write_bytes, 0.0);
+        assert_eq!(rates.utilization, 50.0);
+
+        // Counter reset reads as idle rather than wrapping
+        let rates = IoRates::between(&after, &before["nvme0n1"], 1.0);
+        assert_eq!(rates, IoRates::default());
+
+        assert_eq!("io".parse::<DiskMetric>().unwrap(), DiskMetric::Io);
+        assert_eq!("inodes".parse::<DiskMetric>().unwrap(), DiskMetric::Inodes);
+        assert!("iops".parse::<DiskMetric>().is_err());
+    }
+
+    #[test]
+    fn test_physical_disk_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("sda").join("device")).unwrap();
+        fs::create_dir_all(root.join("nvme0n1").join("device")).unwrap();
+        fs::create_dir_all(root.join("loop0")).unwrap();
+        fs::create_dir_all(root.join("dm-0")).unwrap();
+
+        let disks = physical_disks_in(root);
+
+        assert_eq!(disks, vec!["nvme0n1".to_owned(), "sda".to_owned()]);
+    }
+}
@@ -0,0 +1,221 @@
+//! I/O completion latency tracking from block device statistics.
+//!
+//! `/sys/class/block/<dev>/stat` only exposes cumulative I/O counts and the
+//! milliseconds spent completing them, so every sample yields the mean
+//! latency of the I/Os completed since the previous one. Percentiles are taken
+//! over a rolling window of those interval means; a p50 above the warning
+//! threshold means latency has been high for at least half the window rather
+//! than during a single burst.
+
+use std::{
+    collections::VecDeque,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Number of interval samples kept for percentile calculation.
+const DEFAULT_WINDOW: usize = 60;
+
+/// Samples needed before latency is considered sustained.
+const MIN_SUSTAINED_SAMPLES: usize = 5;
+
+/// Cumulative counters of one block device, as found in its `stat` file and
+/// in `/proc/diskstats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BlockStat {
+    /// Completed reads, writes, discards and flushes
+    pub ios: u64,
+    /// Milliseconds spent on those I/Os
+    pub ticks_ms: u64,
+    /// Reads completed
+    pub reads: u64,
+    /// Sectors read
+    pub sectors_read: u64,
+    /// Writes completed
+    pub writes: u64,
+    /// Sectors written
+    pub sectors_written: u64,
+    /// Milliseconds with I/O in flight
+    pub io_ticks_ms: u64,
+}
+
+impl BlockStat {
+    /// Parse the contents of a block device `stat` file.
+    ///
+    /// Discard (kernel 4.18+) and flush (5.5+) columns are included when present.
+    pub fn parse(content: &str) -> Option<Self> {
+        let fields: Vec<u64> = content.split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .ok()?;
+        if fields.len() < 11 {
+            return None;
+        }
+
+        // (ios, ticks) column pairs: read, write, discard, flush
+        let (ios, ticks_ms) = [(0, 3), (4, 7), (11, 14), (15, 16)]
+            .into_iter()
+            .filter_map(|(ios, ticks)| Some((*fields.get(ios)?, *fields.get(ticks)?)))
+            .fold((0, 0), |(ios, ticks), (i, t)| (ios + i, ticks + t));
+
+        Some(Self {
+            ios,
+            ticks_ms,
+            reads: fields[0],
+            sectors_read: fields[2],
+            writes: fields[4],
+            sectors_written: fields[6],
+            io_ticks_ms: fields[9],
+        })
+    }
+
+    /// Read the statistics file at `path`.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed block device stat"))
+    }
+}
+
+/// Rolling I/O latency percentiles for one block device.
+#[derive(Debug)]
+pub struct LatencyTracker {
+    stat_path: PathBuf,
+    prev: Option<BlockStat>,
+    samples: VecDeque<f64>,
+    window: usize,
+}
+
+impl LatencyTracker {
+    /// Track the block device backing a filesystem, e.g. `/dev/nvme0n1p2` or
+    /// `/dev/mapper/root`.
+    ///
+    /// Returns `None` for filesystems without a block device (tmpfs, network
+    /// shares) or when sysfs does not expose statistics for it.
+    pub fn for_device(device: &str) -> Option<Self> {
+        let name = fs::canonicalize(device).ok()?.file_name()?.to_owned();
+        let stat_path = Path::new("/sys/class/block").join(name).join("stat");
+        stat_path.is_file().then(|| Self::with_stat_path(stat_path))
+    }
+
+    /// Track an explicit `stat` file (useful for testing).
+    pub fn with_stat_path(stat_path: PathBuf) -> Self {
+        Self {
+            stat_path,
+            prev: None,
+            samples: VecDeque::with_capacity(DEFAULT_WINDOW),
+            window: DEFAULT_WINDOW,
+        }
+    }
+
+    /// Read the device statistics and record a new sample.
+    ///
+    /// Returns the mean latency in milliseconds of the I/Os completed since
+    /// the previous call, or `None` if nothing completed in between.
+    pub fn update(&mut self) -> io::Result<Option<f64>> {
+        let stat = BlockStat::read(&self.stat_path)?;
+        Ok(self.record(stat))
+    }
+
+    /// Record a statistics snapshot taken after the previous one.
+    pub fn record(&mut self, stat: BlockStat) -> Option<f64> {
+        let prev = self.prev.replace(stat)?;
+
+        // Counters going backwards means the device was re-created
+        let ios = stat.ios.checked_sub(prev.ios).filter(|&ios| ios > 0)?;
+        let ticks = stat.ticks_ms.checked_sub(prev.ticks_ms)?;
+        let latency = ticks as f64 / ios as f64;
+
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+        Some(latency)
+    }
+
+    /// Latency percentile (0-100) in milliseconds over the sample window.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<f64> = self.samples.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * (sorted.len() - 1) as f64).round() as usize;
+        Some(sorted[rank])
+    }
+
+    /// Median latency, once enough samples exist to call it sustained.
+    pub fn sustained_latency(&self) -> Option<f64> {
+        (self.samples.len() >= MIN_SUSTAINED_SAMPLES)
+            .then(|| self.percentile(50.0))
+            .flatten()
+    }
+
+    /// Number of samples in the window.
+    pub fn sample_count(&self) -> usize {
+        self.samples.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_stat_parsing() {
+        // Kernel 5.5+ layout with discard and flush columns
+        let stat = BlockStat::parse(
+            "  100 0 800 50  200 0 1600 150  0 30 200  10 0 80 5  4 2\n",
+        ).unwrap();
+        assert_eq!(stat.ios, 100 + 200 + 10 + 4);
+        assert_eq!(stat.ticks_ms, 50 + 150 + 5 + 2);
+
+        // Pre-4.18 layout
+        let stat = BlockStat::parse("100 0 800 50 200 0 1600 150 0 30 200").unwrap();
+        assert_eq!((stat.ios, stat.ticks_ms), (300, 200));
+        assert_eq!((stat.reads, stat.sectors_read, stat.writes, stat.sectors_written), (100, 800, 200, 1600));
+        assert_eq!(stat.io_ticks_ms, 30);
+
+        assert!(BlockStat::parse("1 2 3").is_none());
+        assert!(BlockStat::parse("").is_none());
+    }
+
+    #[test]
+    fn test_latency_percentiles() {
+        let mut tracker = LatencyTracker::with_stat_path(PathBuf::from("/nonexistent"));
+        assert_eq!(tracker.record(BlockStat { ios: 0, ticks_ms: 0, ..Default::default() }), None);
+
+        let mut ios = 0;
+        let mut ticks = 0;
+        for latency in [1, 1, 2, 1, 40] {
+            ios += 10;
+            ticks += 10 * latency;
+            tracker.record(BlockStat { ios, ticks_ms: ticks, ..Default::default() });
+        }
+        // Idle interval adds no sample
+        assert_eq!(tracker.record(BlockStat { ios, ticks_ms: ticks, ..Default::default() }), None);
+
+        assert_eq!(tracker.sample_count(), 5);
+        assert_eq!(tracker.percentile(50.0), Some(1.0));
+        assert_eq!(tracker.percentile(99.0), Some(40.0));
+        assert_eq!(tracker.sustained_latency(), Some(1.0));
+
+        // Counter reset does not produce a bogus sample
+        assert_eq!(tracker.record(BlockStat { ios: 5, ticks_ms: 5, ..Default::default() }), None);
+    }
+
+    #[test]
+    fn test_update_reads_stat_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stat");
+        fs::write(&path, "10 0 0 20 0 0 0 0 0 0 0").unwrap();
+        let mut tracker = LatencyTracker::with_stat_path(path.clone());
+        assert_eq!(tracker.update().unwrap(), None);
+
+        fs::write(&path, "20 0 0 70 0 0 0 0 0 0 0").unwrap();
+        let latency = tracker.update().unwrap();
+
+        assert_eq!(latency, Some(5.0));
+        assert!(tracker.sustained_latency().is_none());
+    }
+}
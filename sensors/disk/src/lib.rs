@@ -5,6 +5,34 @@
 
 mod disk;
 mod multi_disk;
+mod dir_watch;
+mod latency;
+mod fs_errors;
+mod io;
+mod smart;
+mod drive_temp;
+mod network_fs;
+mod btrfs;
+mod zfs;
+mod raid;
+mod forecast;
+mod target;
+mod hotplug;
+mod quota;
 
-pub use disk::{DiskSensor, DiskSensorBuilder, DiskError, CacheConfig};
-pub use multi_disk::{MultiDiskSensor, DisplayMode};
\ No newline at end of file
+pub use disk::{DiskSensor, DiskSensorBuilder, DiskError, CacheConfig, Mount, parse_real_mounts, real_mounts};
+pub use multi_disk::{MultiDiskSensor, DisplayMode, MountFilter};
+pub use dir_watch::{DirWatchSensor, DirSample};
+pub use latency::{BlockStat, LatencyTracker};
+pub use fs_errors::{FsErrorCount, FsErrorSensor};
+pub use io::{DiskMetric, IoRates, IoSensor};
+pub use smart::{SmartHealth, SmartMonitor};
+pub use drive_temp::DriveTemp;
+pub use btrfs::{BtrfsDevice, BtrfsMonitor, BtrfsProfile, BtrfsUsage};
+pub use zfs::{pool_of, ZfsMonitor, Zpool, ZpoolHealth};
+pub use raid::{parse_mdstat, MdArray, MdMember, MdSync, RaidSensor};
+pub use forecast::{history_path, FitModel, Forecast, Sample, UsageHistory};
+pub use target::{escape_label, mount_of, FsTarget};
+pub use hotplug::{is_removable, removable_from_sysfs, MountWatcher};
+pub use quota::{QuotaMonitor, UserQuota};
+pub use network_fs::{is_network_fs, mount_fs_type, NetworkGuard, DEFAULT_NETWORK_TIMEOUT};
@@ -13,9 +13,9 @@
 //! - **Comprehensive error handling** - Detailed error reporting and recovery
 
 use clap::Parser;
-use waysensor_rs_core::{GlobalConfig, Sensor, IconStyle};
+use waysensor_rs_core::{discovery, schedule::Ticker, session_lock, GlobalConfig, Sensor, IconStyle};
 use waysensor_rs_disk::{
-    DiskSensorBuilder, MultiDiskSensor, DisplayMode, CacheConfig
+    DiskSensor, DiskSensorBuilder, DirWatchSensor, DiskMetric, FsErrorSensor, FsTarget, IoSensor, RaidSensor, MultiDiskSensor, DisplayMode, CacheConfig
 };
 use std::{
     io::{self, Write},
@@ -35,6 +35,14 @@ struct Args {
     #[arg(short, long, default_value = "/")]
     path: String,
 
+    /// Filesystem label to monitor instead of a path
+    #[arg(long, conflicts_with_all = ["uuid", "paths", "all"], help = "Monitor the filesystem with this label wherever it is mounted (see /dev/disk/by-label)")]
+    label: Option<String>,
+
+    /// Filesystem UUID to monitor instead of a path
+    #[arg(long, conflicts_with_all = ["paths", "all"], help = "Monitor the filesystem with this UUID wherever it is mounted (see /dev/disk/by-uuid)")]
+    uuid: Option<String>,
+
     /// Additional paths for multi-disk monitoring
     #[arg(long, help = "Additional disk paths to monitor (enables multi-disk mode)")]
     paths: Vec<String>,
@@ -51,6 +59,14 @@ struct Args {
     #[arg(short, long, help = "Display available space percentage instead of used space")]
     available: bool,
 
+    /// Monitor every real mount point
+    #[arg(long, help = "Monitor all real mounts (filter with mount_include/mount_exclude in config)")]
+    all: bool,
+
+    /// Add removable drives while they are mounted
+    #[arg(long, conflicts_with_all = ["all", "label", "uuid"], help = "Also monitor USB drives and memory cards while they are mounted (enables multi-disk mode)")]
+    removable: bool,
+
     /// Display mode for multi-disk monitoring
     #[arg(short, long, default_value = "highest", 
           help = "Display mode: highest, combined, cycle, average, total")]
@@ -76,6 +92,82 @@ struct Args {
     #[arg(long, default_value = "24", help = "Number of historical data points for trend analysis")]
     trend_history_size: usize,
 
+    /// Sustained I/O latency warning threshold
+    #[arg(long, default_value = "100", help = "Sustained median I/O latency that triggers a warning, in milliseconds")]
+    latency_warning_ms: f64,
+
+    /// Sustained I/O latency critical threshold
+    #[arg(long, default_value = "500", help = "Sustained median I/O latency that is critical, in milliseconds")]
+    latency_critical_ms: f64,
+
+    /// Days-until-full warning threshold
+    #[arg(long, default_value = "7", help = "Predicted days until full that trigger a warning (needs --performance-monitoring)")]
+    full_warning_days: f64,
+
+    /// Days-until-full critical threshold
+    #[arg(long, default_value = "1", help = "Predicted days until full that are critical (needs --performance-monitoring)")]
+    full_critical_days: f64,
+
+    /// Report SMART drive health
+    #[arg(long, help = "Show SMART health of the drive in the tooltip (needs smartctl and root or CAP_SYS_RAWIO)")]
+    smart: bool,
+
+    /// Report the user's quota
+    #[arg(long, help = "Show your quota usage instead of the whole filesystem when a user quota is set (needs quota)")]
+    quota: bool,
+
+    /// Drive temperature warning threshold
+    #[arg(long, help = "Drive temperature that triggers a warning, in °C (default: the drive's own limit)")]
+    drive_temp_warning: Option<f64>,
+
+    /// Drive temperature critical threshold
+    #[arg(long, help = "Drive temperature that is critical, in °C (default: the drive's own limit)")]
+    drive_temp_critical: Option<f64>,
+
+    /// Watch the size of ~/.cache, /tmp and the XDG state directory
+    #[arg(long, help = "Monitor growth of per-user cache and temp directories instead of disk usage")]
+    watch_dirs: bool,
+
+    /// Directories to watch instead of the defaults
+    #[arg(long = "watch-dir", help = "Directory to watch (repeatable, implies --watch-dirs)")]
+    watch_dir: Vec<PathBuf>,
+
+    /// Directory sampling interval in seconds
+    #[arg(long, default_value = "600", help = "How often watched directories are re-measured, in seconds")]
+    watch_sample_interval: u64,
+
+    /// Warning threshold for watched directories in MiB
+    #[arg(long, default_value = "5120", help = "Combined size of watched directories that triggers a warning, in MiB")]
+    watch_warning_mb: u64,
+
+    /// Critical threshold for watched directories in MiB
+    #[arg(long, default_value = "10240", help = "Combined size of watched directories that is critical, in MiB")]
+    watch_critical_mb: u64,
+
+    /// Metric shown in the bar (usage, inodes, io)
+    #[arg(long, default_value = "usage")]
+    metric: DiskMetric,
+
+    /// Block devices for the io metric
+    #[arg(long = "io-device", help = "Block device to monitor with --metric io, e.g. nvme0n1 (repeatable, default: all physical disks)")]
+    io_device: Vec<String>,
+
+    /// Utilization warning threshold for the io metric
+    #[arg(long, default_value = "80", help = "Busiest device utilization that triggers a warning, in percent (io metric)")]
+    io_warning: f64,
+
+    /// Utilization critical threshold for the io metric
+    #[arg(long, default_value = "95", help = "Busiest device utilization that is critical, in percent (io metric)")]
+    io_critical: f64,
+
+    /// Monitor ext4/btrfs error counters
+    #[arg(long, help = "Report filesystem error counters instead of disk usage (critical on new errors)")]
+    fs_errors: bool,
+
+    /// Monitor md software RAID arrays
+    #[arg(long, help = "Report md RAID array state and resync progress from /proc/mdstat (critical when degraded)")]
+    raid: bool,
+
     /// Run once and exit (for testing)
     #[arg(long, help = "Run once and exit, useful for testing")]
     once: bool,
@@ -109,8 +201,12 @@ struct Args {
     generate_config: bool,
 
     /// List available disk mount points and exit
-    #[arg(long, help = "List available disk mount points and exit")]
-    list_disks: bool,
+    #[arg(short, long, alias = "list-disks", help = "List available disk mount points and exit")]
+    list: bool,
+
+    /// Print build information as JSON and exit
+    #[arg(long, help = "Print build information (git hash, target, core version) as JSON and exit")]
+    build_info: bool,
 
     /// Show detailed disk information and exit
     #[arg(long, help = "Show detailed information about monitored disks and exit")]
@@ -124,58 +220,11 @@ struct Args {
     #[arg(short, long, help = "Enable verbose output for debugging")]
     verbose: bool,
 
-    /// JSON output format (always enabled for waybar compatibility)
-    #[arg(long, hide = true)]
+    /// Print the --list output as JSON
+    #[arg(long, help = "Print the --list output as JSON")]
     json: bool,
 }
 
-/// List available disk mount points.
-fn list_available_disks() -> Result<(), Box<dyn std::error::Error>> {
-    println!("Available disk mount points:");
-    println!("=============================");
-    
-    // Read /proc/mounts to find mounted filesystems
-    let mounts = std::fs::read_to_string("/proc/mounts")?;
-    let mut mount_points = Vec::new();
-    
-    for line in mounts.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 4 {
-            let device = parts[0];
-            let mount_point = parts[1];
-            let fs_type = parts[2];
-            let options = parts[3];
-            
-            // Skip virtual filesystems and special mounts
-            if !device.starts_with('/') || 
-               fs_type == "proc" || fs_type == "sysfs" || fs_type == "devtmpfs" ||
-               fs_type == "tmpfs" || fs_type == "devpts" || fs_type == "cgroup" ||
-               mount_point.starts_with("/proc") || mount_point.starts_with("/sys") ||
-               mount_point.starts_with("/dev") {
-                continue;
-            }
-            
-            mount_points.push((device, mount_point, fs_type, options.contains("ro")));
-        }
-    }
-    
-    // Sort by mount point
-    mount_points.sort_by(|a, b| a.1.cmp(b.1));
-    
-    for (device, mount_point, fs_type, readonly) in mount_points {
-        let ro_flag = if readonly { " (RO)" } else { "" };
-        println!("  {} -> {} [{}]{}", device, mount_point, fs_type, ro_flag);
-    }
-    
-    println!();
-    println!("Example usage:");
-    println!("  waysensor-rs-disk --path /");
-    println!("  waysensor-rs-disk --path / --paths /home /var");
-    println!("  waysensor-rs-disk --paths / /home --display-mode combined");
-    
-    Ok(())
-}
-
 /// Show detailed information about specified disks.
 fn show_disk_info(paths: &[String], verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
     println!("Disk Information");
@@ -274,6 +323,7 @@ fn test_configuration(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
             .critical_threshold(args.critical)
             .show_available(args.available)
             .monitor_inodes(args.monitor_inodes)
+            .metric(args.metric)
             .cache_config(cache_config)
             .performance_monitoring(args.performance_monitoring)
             .trend_history_size(args.trend_history_size)
@@ -334,7 +384,18 @@ fn parse_display_mode(mode: &str) -> Result<DisplayMode, Box<dyn std::error::Err
         "combined" | "combine" => Ok(DisplayMode::Combined),
         "cycle" | "cycling" => Ok(DisplayMode::Cycle { current: 0 }),
         "specific" => Ok(DisplayMode::Specific(0)), // Default to first disk
-        _ => Err(format!("Invalid display mode: '{}'. Valid options: highest, combined, cycle, specific", mode).into()),
+        "average" | "avg" => Ok(DisplayMode::Average),
+        "total" => Ok(DisplayMode::Total),
+        _ => Err(format!("Invalid display mode: '{}'. Valid options: highest, combined, cycle, specific, average, total", mode).into()),
+    }
+}
+
+/// Filesystem selected with --label or --uuid.
+fn fs_target(args: &Args) -> Option<FsTarget> {
+    match (&args.label, &args.uuid) {
+        (Some(label), _) => Some(FsTarget::Label(label.clone())),
+        (None, Some(uuid)) => Some(FsTarget::Uuid(uuid.clone())),
+        (None, None) => None,
     }
 }
 
@@ -346,16 +407,51 @@ fn create_sensor(args: &Args) -> Result<Box<dyn Sensor<Error = waysensor_rs_core
         aggressive: args.aggressive_cache,
     };
     
-    let sensor: Box<dyn Sensor<Error = waysensor_rs_core::SensorError>> = if args.paths.is_empty() {
+    let sensor: Box<dyn Sensor<Error = waysensor_rs_core::SensorError>> = if args.metric == DiskMetric::Io {
+        // Throughput, IOPS and utilization from /proc/diskstats
+        Box::new(IoSensor::new(args.io_device.clone(), args.io_warning, args.io_critical)?)
+    } else if args.fs_errors {
+        // ext4/btrfs error counters
+        Box::new(FsErrorSensor::new())
+    } else if args.raid {
+        // md array state from /proc/mdstat
+        Box::new(RaidSensor::new())
+    } else if args.watch_dirs || !args.watch_dir.is_empty() {
+        // Cache/temp directory growth monitoring
+        Box::new(DirWatchSensor::new(
+            args.watch_dir.clone(),
+            Duration::from_secs(args.watch_sample_interval),
+            args.watch_warning_mb * 1024 * 1024,
+            args.watch_critical_mb * 1024 * 1024,
+        )?)
+    } else if args.all {
+        // Every real mount, re-discovered on each read
+        Box::new(MultiDiskSensor::all(
+            args.warning,
+            args.critical,
+            args.available,
+            parse_display_mode(&args.display_mode)?,
+        )?)
+    } else if args.paths.is_empty() && !args.removable {
         // Single disk monitoring
-        Box::new(DiskSensorBuilder::new(&args.path)
+        let mut builder = DiskSensorBuilder::new(&args.path);
+        if let Some(target) = fs_target(args) {
+            builder = builder.target(target);
+        }
+        Box::new(builder
             .warning_threshold(args.warning)
             .critical_threshold(args.critical)
             .show_available(args.available)
             .monitor_inodes(args.monitor_inodes)
+            .metric(args.metric)
             .cache_config(cache_config)
             .performance_monitoring(args.performance_monitoring)
             .trend_history_size(args.trend_history_size)
+            .latency_thresholds(args.latency_warning_ms, args.latency_critical_ms)
+            .smart(args.smart)
+            .quota(args.quota)
+            .drive_temp_thresholds(args.drive_temp_warning, args.drive_temp_critical)
+            .full_thresholds(args.full_warning_days, args.full_critical_days)
             .build()?)
     } else {
         // Multi-disk monitoring
@@ -366,13 +462,18 @@ fn create_sensor(args: &Args) -> Result<Box<dyn Sensor<Error = waysensor_rs_core
             paths.push(path.clone());
         }
         
-        Box::new(MultiDiskSensor::new(
+        let sensor = MultiDiskSensor::new(
             paths,
             args.warning,
             args.critical,
             args.available,
             display_mode,
-        )?)
+        )?;
+        if args.removable {
+            Box::new(sensor.with_removable()?)
+        } else {
+            Box::new(sensor)
+        }
     };
     
     Ok(sensor)
@@ -382,7 +483,7 @@ fn create_sensor(args: &Args) -> Result<Box<dyn Sensor<Error = waysensor_rs_core
 fn run_monitoring_loop(mut sensor: Box<dyn Sensor<Error = waysensor_rs_core::SensorError>>, args: &Args) -> Result<(), Box<dyn std::error::Error>> {
     // Load global configuration and apply command line overrides
     let global_config = GlobalConfig::load().unwrap_or_default();
-    let mut config = global_config.to_sensor_config()
+    let mut config = global_config.to_sensor_config_for("disk")
         .with_update_interval(Duration::from_millis(args.interval))
         .apply_color_overrides(
             args.icon_color.clone(),
@@ -414,7 +515,7 @@ fn run_monitoring_loop(mut sensor: Box<dyn Sensor<Error = waysensor_rs_core::Sen
     
     if args.once {
         // Run once and output result
-        let output = sensor.read()?;
+        let output = sensor.read_styled()?;
         println!("{}", serde_json::to_string(&output)?);
         return Ok(());
     }
@@ -422,16 +523,25 @@ fn run_monitoring_loop(mut sensor: Box<dyn Sensor<Error = waysensor_rs_core::Sen
     // Continuous monitoring loop
     let mut error_count = 0;
     const MAX_CONSECUTIVE_ERRORS: usize = 5;
+    let mut ticker = Ticker::new(Duration::from_millis(args.interval));
     
     loop {
-        match sensor.read() {
+        ticker.wait();
+        if session_lock::wait(sensor.config()) {
+            ticker.reset();
+        }
+        
+        match sensor.read_styled() {
             Ok(output) => {
                 println!("{}", serde_json::to_string(&output)?);
                 io::stdout().flush()?;
                 error_count = 0; // Reset error count on success
             },
             Err(e) => {
-                error_count += 1;
+                // An unplugged drive or unreachable share may come back
+                if !e.is_temporary() {
+                    error_count += 1;
+                }
                 
                 if args.verbose {
                     eprintln!("❌ Error reading sensor (attempt {}): {}", error_count, e);
@@ -452,14 +562,17 @@ fn run_monitoring_loop(mut sensor: Box<dyn Sensor<Error = waysensor_rs_core::Sen
                 }
             }
         }
-        
-        std::thread::sleep(Duration::from_millis(args.interval));
     }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
     if args.verbose {
         eprintln!("🚀 waysensor-rs-disk starting...");
     }
@@ -477,8 +590,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
     
-    if args.list_disks {
-        return list_available_disks();
+    if args.list {
+        discovery::print::<DiskSensor>(args.json)?;
+        return Ok(());
     }
     
     if args.info {
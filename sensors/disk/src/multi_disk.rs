@@ -1,5 +1,19 @@
-use waysensor_rs_core::{Sensor, SensorConfig, SensorError, Theme, WaybarOutput, format};
-use std::path::Path;
+use crate::disk::{self, Mount};
+use crate::hotplug::{self, MountWatcher};
+use crate::network_fs::NetworkGuard;
+use waysensor_rs_core::{trend::UsageTrend, Sensor, SensorConfig, SensorError, WaybarOutput, format};
+use glob::Pattern;
+use std::{
+    collections::HashMap,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+/// Reads kept per path for the tooltip's trend arrows.
+const TREND_SAMPLES: usize = 60;
+
+/// Change in used space per minute that counts as growing or shrinking.
+const TREND_BYTES_PER_MIN: f64 = 1024.0 * 1024.0;
 
 #[derive(Debug)]
 pub struct MultiDiskSensor {
@@ -9,7 +23,87 @@ pub struct MultiDiskSensor {
     critical_threshold: u8,
     show_available: bool,
     display_mode: DisplayMode,
-    theme: Theme,
+    config: SensorConfig,
+    /// Re-discover mounts on every read (`--all`)
+    auto: Option<MountFilter>,
+    /// Add removable drives while mounted (`--removable`); `paths` is then
+    /// `base_paths` plus the drives
+    removable: Option<MountWatcher>,
+    base_paths: Vec<String>,
+    /// Used bytes per path, for the tooltip table
+    trends: HashMap<String, UsageTrend>,
+    /// Timeout protection for network mounts
+    network: NetworkGuard,
+}
+
+/// Mount point glob patterns selecting which real mounts `--all` monitors.
+#[derive(Debug, Clone, Default)]
+pub struct MountFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl MountFilter {
+    /// Keep mount points matching any `include` pattern (all when empty) and
+    /// no `exclude` pattern, e.g. `/run/media/*` or `/var/lib/docker/**`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error for an invalid pattern.
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, SensorError> {
+        let compile = |patterns: &[String]| patterns.iter()
+            .map(|p| Pattern::new(p).map_err(|e| SensorError::config_with_value(
+                format!("Invalid mount pattern: {}", e),
+                p.as_str(),
+            )))
+            .collect::<Result<Vec<_>, _>>();
+        Ok(Self {
+            include: compile(include)?,
+            exclude: compile(exclude)?,
+        })
+    }
+
+    /// Whether `mount_point` passes the filter.
+    pub fn matches(&self, mount_point: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches(mount_point)))
+            && !self.exclude.iter().any(|p| p.matches(mount_point))
+    }
+
+    /// Mount points to monitor, keeping only the first mount of each device
+    /// so bind mounts and btrfs subvolumes are not listed twice.
+    pub fn select(&self, mounts: &[Mount]) -> Vec<String> {
+        let mut seen = Vec::new();
+        mounts.iter()
+            .filter(|mount| self.matches(&mount.mount_point))
+            .filter(|mount| {
+                let first = !seen.contains(&&mount.device);
+                seen.push(&mount.device);
+                first
+            })
+            .map(|mount| mount.mount_point.clone())
+            .collect()
+    }
+}
+
+/// `base` followed by every mounted removable drive not already in it.
+fn with_removable_mounts<F>(base: &[String], mounts: &[Mount], is_removable: F) -> Vec<String>
+where
+    F: Fn(&str) -> bool,
+{
+    let drives: Vec<Mount> = mounts.iter().filter(|mount| is_removable(&mount.device)).cloned().collect();
+    let mut paths = base.to_vec();
+    for path in MountFilter::default().select(&drives) {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// Read a list of strings from a custom config key.
+fn string_list(config: &SensorConfig, key: &str) -> Option<Vec<String>> {
+    let values = config.get_custom(key)?.as_array()?;
+    Some(values.iter().filter_map(|v| v.as_str().map(str::to_owned)).collect())
 }
 
 #[derive(Debug, Clone)]
@@ -22,6 +116,10 @@ pub enum DisplayMode {
     Cycle { current: usize },
     /// Show specific path by index
     Specific(usize),
+    /// Show the mean usage percentage, each disk weighted equally
+    Average,
+    /// Show used space against capacity summed over distinct filesystems
+    Total,
 }
 
 #[derive(Debug, Clone)]
@@ -32,6 +130,8 @@ struct DiskInfo {
     available: u64,
     filesystem: String,
     device: String,
+    /// On a USB drive or memory card
+    removable: bool,
 }
 
 impl DiskInfo {
@@ -52,6 +152,48 @@ impl DiskInfo {
     }
 }
 
+/// Disks with the same backing filesystem reduced to the first path on it,
+/// so paths like `/` and `/var` on one partition are counted once.
+fn distinct_disks(all_info: &[DiskInfo]) -> Vec<&DiskInfo> {
+    let mut seen = Vec::new();
+    all_info.iter()
+        .filter(|info| {
+            let key = (&info.device, info.total);
+            let first = !seen.contains(&key);
+            seen.push(key);
+            first
+        })
+        .collect()
+}
+
+/// Capacity and usage summed over distinct filesystems.
+fn total_usage(all_info: &[DiskInfo]) -> DiskInfo {
+    let distinct = distinct_disks(all_info);
+    DiskInfo {
+        path: "All disks".to_string(),
+        total: distinct.iter().map(|i| i.total).sum(),
+        used: distinct.iter().map(|i| i.used).sum(),
+        available: distinct.iter().map(|i| i.available).sum(),
+        filesystem: "total".to_string(),
+        device: format!("{} filesystems", distinct.len()),
+        removable: false,
+    }
+}
+
+/// Mean used and available percentages across distinct filesystems, and
+/// how many were averaged. Paths on the same filesystem count once, as in
+/// [`total_usage`], and pseudo filesystems without capacity (`/proc`) are
+/// left out rather than averaged in as empty.
+fn average_usage(all_info: &[DiskInfo]) -> (f64, f64, usize) {
+    let sized: Vec<&DiskInfo> = distinct_disks(all_info).into_iter().filter(|i| i.total > 0).collect();
+    let count = sized.len().max(1) as f64;
+    (
+        sized.iter().map(|i| i.used_percentage()).sum::<f64>() / count,
+        sized.iter().map(|i| i.available_percentage()).sum::<f64>() / count,
+        sized.len(),
+    )
+}
+
 impl MultiDiskSensor {
     pub fn new(
         paths: Vec<String>,
@@ -90,17 +232,59 @@ impl MultiDiskSensor {
             critical_threshold,
             show_available,
             display_mode,
-            theme: Theme::default(),
+            config: SensorConfig::default(),
+            auto: None,
+            removable: None,
+            base_paths: Vec::new(),
+            trends: HashMap::new(),
+            network: NetworkGuard::default(),
         })
     }
     
+    /// Monitor every real mount, re-discovered on each read so drives
+    /// plugged in later show up. Narrow the selection with the
+    /// `mount_include` and `mount_exclude` config keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `/proc/mounts` cannot be read or lists no real mounts.
+    pub fn all(
+        warning_threshold: u8,
+        critical_threshold: u8,
+        show_available: bool,
+        display_mode: DisplayMode,
+    ) -> Result<Self, SensorError> {
+        let filter = MountFilter::default();
+        let mut sensor = Self::new(
+            filter.select(&disk::real_mounts()?),
+            warning_threshold,
+            critical_threshold,
+            show_available,
+            display_mode,
+        )?;
+        sensor.name = "disk-all".to_string();
+        sensor.auto = Some(filter);
+        Ok(sensor)
+    }
+    
+    /// Also monitor removable drives (USB sticks, card readers) while they
+    /// are mounted, on top of the given paths.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the mount table cannot be watched.
+    pub fn with_removable(mut self) -> Result<Self, SensorError> {
+        self.removable = Some(MountWatcher::new()?);
+        self.base_paths = self.paths.clone();
+        Ok(self)
+    }
+    
     fn get_disk_usage(&self, path: &str) -> Result<DiskInfo, SensorError> {
-        let output = std::process::Command::new("df")
-            .arg("-B1") // Get output in bytes
+        let mut df = std::process::Command::new("df");
+        df.arg("-B1") // Get output in bytes
             .arg("-T")  // Include filesystem type
-            .arg(path)
-            .output()
-            .map_err(|e| SensorError::Io(e))?;
+            .arg(path);
+        let output = self.network.output(Path::new(path), df)?;
         
         if !output.status.success() {
             return Err(SensorError::Unavailable {
@@ -143,6 +327,7 @@ impl MultiDiskSensor {
                     used,
                     available,
                     filesystem,
+                    removable: hotplug::is_removable(&device),
                     device,
                 });
             }
@@ -179,7 +364,14 @@ impl Sensor for MultiDiskSensor {
     type Error = SensorError;
     
     fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        if let Some(filter) = &self.auto {
+            self.paths = filter.select(&disk::real_mounts()?);
+        }
+        if self.removable.as_mut().is_some_and(MountWatcher::changed) {
+            self.paths = with_removable_mounts(&self.base_paths, &disk::real_mounts()?, hotplug::is_removable);
+        }
         let all_info = self.get_all_disk_info()?;
+        self.record_trends(&all_info);
         
         let (display_info, text_prefix) = match &mut self.display_mode {
             DisplayMode::HighestUsage => {
@@ -201,6 +393,7 @@ impl Sensor for MultiDiskSensor {
                     available,
                     filesystem: "combined".to_string(),
                     device: format!("{} disks", all_info.len()),
+                    removable: false,
                 };
                 (combined, None)
             },
@@ -218,20 +411,45 @@ impl Sensor for MultiDiskSensor {
                     })?;
                 (info.clone(), Some(format!("{}: ", basename(&info.path))))
             },
+            DisplayMode::Average => {
+                let mut average = total_usage(&all_info);
+                average.path = "Average of all disks".to_string();
+                average.filesystem = "average".to_string();
+                (average, Some("avg ".to_string()))
+            },
+            DisplayMode::Total => (total_usage(&all_info), None),
+        };
+        
+        let (used_percent, available_percent) = match self.display_mode {
+            DisplayMode::Average => {
+                let (used, available, _) = average_usage(&all_info);
+                (used, available)
+            },
+            _ => (display_info.used_percentage(), display_info.available_percentage()),
         };
         
         let icon = &self.config().icons.disk;
         let (mut text, percentage, value_for_theming) = if self.show_available {
-            let available_percent = display_info.available_percentage();
+            let amount = match self.display_mode {
+                DisplayMode::Total => format::bytes_to_human(display_info.available),
+                _ => format!("{}%", available_percent.round() as u8),
+            };
             (
-                format!("{}% free", available_percent.round() as u8),
+                format!("{} free", amount),
                 Some((100.0_f64 - available_percent).round() as u8),
                 100.0 - available_percent,
             )
         } else {
-            let used_percent = display_info.used_percentage();
+            let amount = match self.display_mode {
+                DisplayMode::Total => format!(
+                    "{}/{}",
+                    format::bytes_to_human(display_info.used),
+                    format::bytes_to_human(display_info.total)
+                ),
+                _ => format!("{}%", used_percent.round() as u8),
+            };
             (
-                format!("{}%", used_percent.round() as u8),
+                amount,
                 Some(used_percent.round() as u8),
                 used_percent,
             )
@@ -254,7 +472,7 @@ impl Sensor for MultiDiskSensor {
             value_for_theming,
             self.warning_threshold as f64,
             self.critical_threshold as f64,
-            &self.theme,
+            &self.config.theme,
         ))
     }
     
@@ -263,19 +481,62 @@ impl Sensor for MultiDiskSensor {
     }
     
     fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
-        self.theme = config.theme;
+        if self.auto.is_some() {
+            let include = string_list(&config, "mount_include").unwrap_or_default();
+            let exclude = string_list(&config, "mount_exclude").unwrap_or_default();
+            self.auto = Some(MountFilter::new(&include, &exclude)?);
+        }
+        if let Some(ms) = config.get_custom("network_timeout_ms").and_then(|v| v.as_u64()) {
+            self.network.set_timeout(Duration::from_millis(ms));
+        }
+        self.config = config;
         Ok(())
     }
+    
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
 }
 
 impl MultiDiskSensor {
     fn build_tooltip(&self, all_info: &[DiskInfo], display_info: &DiskInfo) -> String {
         let mut tooltip = String::new();
         
+        if let DisplayMode::Average = self.display_mode {
+            let (used_percent, available_percent, count) = average_usage(all_info);
+            let fullest = all_info.iter().filter(|i| i.total > 0)
+                .max_by(|a, b| a.used_percentage().total_cmp(&b.used_percentage()));
+            let emptiest = all_info.iter().filter(|i| i.total > 0)
+                .min_by(|a, b| a.used_percentage().total_cmp(&b.used_percentage()));
+            tooltip.push_str(&format!(
+                "Current: Average of {} disks\nUsed: {:.1}% (mean)\nAvailable: {:.1}% (mean)",
+                count,
+                used_percent,
+                available_percent
+            ));
+            if let (Some(fullest), Some(emptiest)) = (fullest, emptiest) {
+                tooltip.push_str(&format!(
+                    "\nRange: {:.0}% ({}) – {:.0}% ({})",
+                    emptiest.used_percentage(),
+                    basename(&emptiest.path),
+                    fullest.used_percentage(),
+                    basename(&fullest.path)
+                ));
+            }
+            tooltip.push_str(&format!(
+                "\nCombined: {} / {} ({:.1}%)",
+                format::bytes_to_human(display_info.used),
+                format::bytes_to_human(display_info.total),
+                display_info.used_percentage()
+            ));
+            return self.push_disk_table(tooltip, all_info);
+        }
+        
         // Show current disk info first
         tooltip.push_str(&format!(
-            "Current: {}\nDevice: {} ({})\nUsed: {} ({:.1}%)\nAvailable: {} ({:.1}%)\nTotal: {}",
+            "Current: {}{}\nDevice: {} ({})\nUsed: {} ({:.1}%)\nAvailable: {} ({:.1}%)\nTotal: {}",
             display_info.path,
+            if display_info.removable { " ⏏ ejectable" } else { "" },
             display_info.device,
             display_info.filesystem,
             format::bytes_to_human(display_info.used),
@@ -285,28 +546,188 @@ impl MultiDiskSensor {
             format::bytes_to_human(display_info.total)
         ));
         
-        // If monitoring multiple disks, show all
-        if all_info.len() > 1 {
-            tooltip.push_str("\n\nAll monitored disks:");
-            for info in all_info {
-                tooltip.push_str(&format!(
-                    "\n• {}: {} / {} ({:.0}%)",
-                    basename(&info.path),
-                    format::bytes_to_human(info.used),
-                    format::bytes_to_human(info.total),
-                    info.used_percentage()
-                ));
+        if let DisplayMode::Total = self.display_mode {
+            let shared = all_info.len() - distinct_disks(all_info).len();
+            if shared > 0 {
+                tooltip.push_str(&format!("\nShared: {} paths on a filesystem already counted", shared));
             }
         }
         
+        self.push_disk_table(tooltip, all_info)
+    }
+    
+    /// Record used space of every path, forgetting paths that went away.
+    fn record_trends(&mut self, all_info: &[DiskInfo]) {
+        let now = Instant::now();
+        self.trends.retain(|path, _| all_info.iter().any(|info| &info.path == path));
+        for info in all_info {
+            self.trends.entry(info.path.clone())
+                .or_insert_with(|| UsageTrend::new(TREND_SAMPLES))
+                .add_sample(now, info.used as f64);
+        }
+    }
+    
+    fn push_disk_table(&self, mut tooltip: String, all_info: &[DiskInfo]) -> String {
+        tooltip.push_str("\n\nAll monitored disks:\n");
+        tooltip.push_str(&disk_table(all_info, &self.trends, &self.config));
         tooltip
     }
 }
 
+/// Trend arrow for the change of used space, `·` until there are two reads.
+fn trend_arrow(trend: Option<&UsageTrend>) -> &'static str {
+    match trend.and_then(UsageTrend::trend_per_sec).map(|per_sec| per_sec * 60.0) {
+        Some(per_min) if per_min >= TREND_BYTES_PER_MIN => "↑",
+        Some(per_min) if per_min <= -TREND_BYTES_PER_MIN => "↓",
+        Some(_) => "→",
+        None => "·",
+    }
+}
+
+/// Monospaced table with one row per path: mount, device, used / total,
+/// gauge (if enabled), percentage and trend arrow. Removable drives are
+/// marked with ⏏.
+fn disk_table(all_info: &[DiskInfo], trends: &HashMap<String, UsageTrend>, config: &SensorConfig) -> String {
+    let visuals = &config.visuals;
+    let rows: Vec<[String; 3]> = all_info.iter()
+        .map(|info| [
+            format!("{}{}", info.path, if info.removable { " ⏏" } else { "" }),
+            info.device.clone(),
+            format!("{} / {}", format::bytes_to_human(info.used), format::bytes_to_human(info.total)),
+        ])
+        .collect();
+    let width = |column: usize, header: &str| rows.iter()
+        .map(|row| row[column].chars().count())
+        .chain([header.chars().count()])
+        .max()
+        .unwrap_or_default();
+    let (mount_width, device_width, size_width) = (width(0, "Mount"), width(1, "Device"), width(2, "Used / Total"));
+    let gauge_header = if visuals.tooltip_gauges { " ".repeat(visuals.gauge_width + 2) } else { String::new() };
+
+    let mut lines = vec![format!(
+        "{:<mount_width$}  {:<device_width$}  {:>size_width$}  {}Use",
+        "Mount", "Device", "Used / Total", gauge_header,
+    )];
+    for (info, [mount, device, size]) in all_info.iter().zip(&rows) {
+        let gauge = if visuals.tooltip_gauges {
+            format!("{}  ", format::create_gauge(info.used_percentage(), visuals.gauge_width, visuals.gauge_style))
+        } else {
+            String::new()
+        };
+        lines.push(format!(
+            "{:<mount_width$}  {:<device_width$}  {:>size_width$}  {}{:>3.0}% {}",
+            mount, device, size, gauge, info.used_percentage(), trend_arrow(trends.get(&info.path)),
+        ));
+    }
+    format!("<tt>{}</tt>", format::escape_markup(&lines.join("\n")))
+}
+
 fn basename(path: &str) -> &str {
     if path == "/" {
         "root"
     } else {
         path.rsplit('/').next().filter(|s| !s.is_empty()).unwrap_or(path)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disk(path: &str, device: &str, total: u64, used: u64) -> DiskInfo {
+        DiskInfo {
+            path: path.to_string(),
+            total,
+            used,
+            available: total - used,
+            filesystem: "ext4".to_string(),
+            device: device.to_string(),
+            removable: false,
+        }
+    }
+
+    #[test]
+    fn test_mount_discovery_filter() {
+        let mounts = disk::parse_real_mounts(
+            "/dev/nvme0n1p2 / ext4 rw,relatime 0 0\n\
+             proc /proc proc rw 0 0\n\
+             tmpfs /tmp tmpfs rw 0 0\n\
+             /dev/nvme0n1p1 /boot vfat rw 0 0\n\
+             /dev/nvme0n1p2 /var/lib/docker ext4 rw 0 0\n\
+             /dev/sdb1 /run/media/me/USB\\040Stick exfat ro 0 0\n\
+             /dev/loop3 /snap/core/123 squashfs ro 0 0\n",
+        );
+        assert_eq!(mounts.len(), 5);
+        assert_eq!(mounts[3].mount_point, "/run/media/me/USB Stick");
+        assert!(mounts[3].read_only);
+
+        // Bind mount of the root device is listed once
+        let all = MountFilter::default();
+        assert_eq!(all.select(&mounts), vec!["/", "/boot", "/run/media/me/USB Stick", "/snap/core/123"]);
+
+        let filter = MountFilter::new(&[], &["/snap/**".to_owned(), "/boot".to_owned()]).unwrap();
+        assert_eq!(filter.select(&mounts), vec!["/", "/run/media/me/USB Stick"]);
+
+        let filter = MountFilter::new(&["/run/media/**".to_owned()], &[]).unwrap();
+        assert_eq!(filter.select(&mounts), vec!["/run/media/me/USB Stick"]);
+
+        assert!(MountFilter::new(&["[".to_owned()], &[]).is_err());
+
+        // Removable drives join the configured paths
+        let usb = |device: &str| device.starts_with("/dev/sd");
+        let base = vec!["/".to_owned(), "/home".to_owned()];
+        assert_eq!(with_removable_mounts(&base, &mounts, usb), vec!["/", "/home", "/run/media/me/USB Stick"]);
+        assert_eq!(with_removable_mounts(&base, &mounts[..2], usb), base);
+    }
+
+    #[test]
+    fn test_disk_table() {
+        let mut usb = disk("/run/media/me/A&B", "/dev/sdb1", 64_000_000_000, 16_000_000_000);
+        usb.removable = true;
+        let disks = vec![disk("/", "/dev/nvme0n1p2", 500_000_000_000, 400_000_000_000), usb];
+
+        let start = Instant::now();
+        let mut growing = UsageTrend::new(TREND_SAMPLES);
+        growing.add_sample(start, 0.0);
+        growing.add_sample(start + Duration::from_secs(60), 10.0 * TREND_BYTES_PER_MIN);
+        let trends = HashMap::from([("/".to_string(), growing)]);
+
+        let mut config = SensorConfig::default();
+        config.visuals.tooltip_gauges = false;
+        let table = disk_table(&disks, &trends, &config);
+        let lines: Vec<&str> = table.trim_start_matches("<tt>").trim_end_matches("</tt>").lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Mount  "));
+        assert!(lines[1].starts_with("/    ") && lines[1].ends_with(" 80% ↑"), "{}", lines[1]);
+        assert!(lines[2].starts_with("/run/media/me/A&amp;B ⏏") && lines[2].ends_with(" 25% ·"), "{}", lines[2]);
+        // Columns line up
+        assert_eq!(lines[0].find("Device"), lines[1].find("/dev/nvme0n1p2"));
+
+        config.visuals.tooltip_gauges = true;
+        assert!(disk_table(&disks, &trends, &config).contains("█"));
+    }
+
+    #[test]
+    fn test_average_and_total() {
+        let disks = vec![
+            disk("/", "/dev/nvme0n1p2", 100, 90),
+            // Same filesystem as /
+            disk("/var", "/dev/nvme0n1p2", 100, 90),
+            disk("/data", "/dev/sda1", 900, 90),
+            // Pseudo filesystem, ignored by the average
+            disk("/proc", "proc", 0, 0),
+        ];
+
+        let total = total_usage(&disks);
+        assert_eq!(total.total, 1000);
+        assert_eq!(total.used, 180);
+        assert_eq!(total.available, 820);
+        assert_eq!(distinct_disks(&disks).len(), 3);
+
+        // Each filesystem weighs the same regardless of size; /var is / again
+        let (used, available, count) = average_usage(&disks);
+        assert_eq!(count, 2);
+        assert!((used - (90.0 + 10.0) / 2.0).abs() < 1e-9);
+        assert!((available - (10.0 + 90.0) / 2.0).abs() < 1e-9);
+    }
+}
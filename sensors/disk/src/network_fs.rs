@@ -0,0 +1,187 @@
+//! Timeout protection for network filesystems.
+//!
+//! `df` on an NFS, CIFS or FUSE mount whose server went away blocks in the
+//! kernel until the server returns, and with it the whole sensor. Queries for
+//! such mounts run on a worker thread instead; when it does not answer within
+//! the timeout the disk is reported as temporarily unavailable and the thread
+//! is left to finish on its own. No new worker is started for a mount while
+//! the previous one is still stuck, so a dead server costs one thread per
+//! mount, not one per update.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    sync::{mpsc, Arc, Mutex, PoisonError},
+    time::Duration,
+};
+use waysensor_rs_core::SensorError;
+
+/// Default time to wait for a network filesystem to answer.
+pub const DEFAULT_NETWORK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Filesystem types served over the network.
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "sshfs", "9p", "afs", "ceph", "glusterfs", "davfs", "fuse",
+];
+
+/// Whether `fs_type` (as in `/proc/mounts`) may block on a remote server.
+///
+/// All `fuse.*` filesystems count since their daemon can hang just the same;
+/// `fuseblk` (ntfs-3g, exfat-fuse) is backed by a local block device.
+pub fn is_network_fs(fs_type: &str) -> bool {
+    NETWORK_FS_TYPES.contains(&fs_type) || fs_type.starts_with("fuse.")
+}
+
+/// Filesystem type of the mount containing `path`, given the contents of
+/// `/proc/mounts`.
+pub fn mount_fs_type(mounts: &str, path: &Path) -> Option<String> {
+    mounts.lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let [_, mount_point, fs_type, ..] = parts[..] else { return None };
+            let mount_point = mount_point.replace("\\040", " ");
+            path.starts_with(&mount_point).then_some((mount_point.len(), fs_type))
+        })
+        // Later mounts shadow earlier ones on the same mount point
+        .max_by_key(|(len, _)| *len)
+        .map(|(_, fs_type)| fs_type.to_owned())
+}
+
+/// Runs filesystem queries with a timeout when the path is on a network mount.
+#[derive(Debug, Clone)]
+pub struct NetworkGuard {
+    timeout: Duration,
+    /// Paths with a worker still waiting on the filesystem
+    busy: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl Default for NetworkGuard {
+    fn default() -> Self {
+        Self::new(DEFAULT_NETWORK_TIMEOUT)
+    }
+}
+
+impl NetworkGuard {
+    /// Guard giving network filesystems `timeout` to answer.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            busy: Arc::default(),
+        }
+    }
+
+    /// Change the timeout.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Run `command` querying `path`, on a worker thread with a timeout if
+    /// `path` is on a network filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns an I/O error if the command cannot be run, or a temporary
+    /// unavailability error if the filesystem does not answer in time.
+    pub fn output(&self, path: &Path, mut command: Command) -> Result<Output, SensorError> {
+        let network = std::fs::read_to_string("/proc/mounts").ok()
+            .and_then(|mounts| mount_fs_type(&mounts, path))
+            .is_some_and(|fs_type| is_network_fs(&fs_type));
+        if !network {
+            return command.output().map_err(SensorError::Io);
+        }
+        self.run(path, move || command.output())?.map_err(SensorError::Io)
+    }
+
+    /// Run `query` for `path` on a worker thread, waiting at most the timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns a temporary unavailability error if `query` does not finish in
+    /// time or an earlier query for `path` is still stuck.
+    pub fn run<T, F>(&self, path: &Path, query: F) -> Result<T, SensorError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let not_responding = || SensorError::temporarily_unavailable(
+            format!("{} is not responding (network filesystem)", path.display()),
+        );
+        let path = path.to_path_buf();
+        if !lock(&self.busy).insert(path.clone()) {
+            return Err(not_responding());
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let busy = Arc::clone(&self.busy);
+        let worker_path = path.clone();
+        std::thread::Builder::new()
+            .name("disk-network-fs".to_string())
+            .spawn(move || {
+                let result = query();
+                lock(&busy).remove(&worker_path);
+                // The receiver is gone if we already timed out
+                let _ = tx.send(result);
+            })
+            .map_err(|e| {
+                lock(&self.busy).remove(&path);
+                SensorError::Io(e)
+            })?;
+
+        rx.recv_timeout(self.timeout).map_err(|_| not_responding())
+    }
+}
+
+fn lock(busy: &Mutex<HashSet<PathBuf>>) -> std::sync::MutexGuard<'_, HashSet<PathBuf>> {
+    busy.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_fs_detection() {
+        let mounts = "\
+/dev/nvme0n1p2 / ext4 rw,relatime 0 0
+server:/export /mnt/nas nfs4 rw,relatime 0 0
+//host/share /mnt/nas/media cifs rw 0 0
+me@host:/home /mnt/remote\\040home fuse.sshfs rw 0 0
+/dev/sdb1 /run/media/usb fuseblk rw 0 0
+";
+        let fs_type = |path: &str| mount_fs_type(mounts, Path::new(path));
+        assert_eq!(fs_type("/home/me").as_deref(), Some("ext4"));
+        assert_eq!(fs_type("/mnt/nas").as_deref(), Some("nfs4"));
+        assert_eq!(fs_type("/mnt/nas/media/films").as_deref(), Some("cifs"));
+        assert_eq!(fs_type("/mnt/nasty").as_deref(), Some("ext4"));
+        assert_eq!(fs_type("/mnt/remote home").as_deref(), Some("fuse.sshfs"));
+
+        assert!(is_network_fs("nfs4"));
+        assert!(is_network_fs("cifs"));
+        assert!(is_network_fs("fuse.sshfs"));
+        assert!(!is_network_fs("fuseblk"));
+        assert!(!is_network_fs("ext4"));
+    }
+
+    #[test]
+    fn test_network_guard_timeout() {
+        let guard = NetworkGuard::new(Duration::from_millis(20));
+        let path = Path::new("/mnt/nas");
+        assert_eq!(guard.run(path, || 42).unwrap(), 42);
+
+        let (release, hung) = mpsc::channel::<()>();
+        let err = guard.run(path, move || hung.recv()).unwrap_err();
+        assert!(err.is_temporary());
+        assert!(err.to_string().contains("/mnt/nas is not responding"));
+
+        // The stuck worker blocks further queries of that mount until it returns
+        assert!(guard.run(path, || 1).unwrap_err().is_temporary());
+        assert_eq!(guard.run(Path::new("/mnt/backup"), || 3).unwrap(), 3);
+        release.send(()).unwrap();
+        let start = std::time::Instant::now();
+        while guard.run(path, || 2).is_err() {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+}
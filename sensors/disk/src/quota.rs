@@ -0,0 +1,223 @@
+//! Per-user filesystem quotas from `quota`.
+//!
+//! On a shared host the filesystem can be half empty while the user is at
+//! their limit, or full while they still have room. With quotas enabled the
+//! sensor reports the user's consumption against their limit instead. The
+//! soft limit counts as the capacity: crossing it starts the grace period
+//! after which writes fail just as at the hard limit.
+
+use waysensor_rs_core::{format, SensorConfig, SensorError};
+use std::{
+    path::Path,
+    process::Command,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// How long a `quota` result is reused.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// `quota` reports space in 1 KiB blocks.
+const BLOCK_SIZE: u64 = 1024;
+
+/// Quota of the current user on one filesystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserQuota {
+    /// Device or export the quota applies to
+    pub filesystem: String,
+    /// Used bytes
+    pub used: u64,
+    /// Soft limit in bytes
+    pub soft: Option<u64>,
+    /// Hard limit in bytes
+    pub hard: Option<u64>,
+    /// Unix time the block grace period ends, while over the soft limit
+    pub grace_until: Option<u64>,
+    /// Used inodes
+    pub files: u64,
+    /// Soft inode limit
+    pub files_soft: Option<u64>,
+    /// Hard inode limit
+    pub files_hard: Option<u64>,
+}
+
+impl UserQuota {
+    /// Parse `quota -v -w -p` output. Filesystems without a quota for the
+    /// user are listed with zero limits and parse to `None` limits.
+    pub fn parse(output: &str) -> Vec<Self> {
+        let limit = |value: &str| value.parse::<u64>().ok().filter(|&limit| limit > 0);
+        output.lines()
+            .skip_while(|line| !line.trim_start().starts_with("Filesystem"))
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                let [filesystem, blocks, soft, hard, grace, files, files_soft, files_hard, ..] = fields[..] else {
+                    return None;
+                };
+                // Usage above the soft limit is starred
+                Some(Self {
+                    filesystem: filesystem.to_owned(),
+                    used: blocks.trim_end_matches('*').parse::<u64>().ok()? * BLOCK_SIZE,
+                    soft: limit(soft).map(|blocks| blocks * BLOCK_SIZE),
+                    hard: limit(hard).map(|blocks| blocks * BLOCK_SIZE),
+                    grace_until: limit(grace),
+                    files: files.trim_end_matches('*').parse().ok()?,
+                    files_soft: limit(files_soft),
+                    files_hard: limit(files_hard),
+                })
+            })
+            .collect()
+    }
+
+    /// Query the current user's quota on the filesystem holding `path`.
+    /// Returns `Ok(None)` when the user has no quota there.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `quota` is missing or fails.
+    pub fn query(path: &Path) -> Result<Option<Self>, SensorError> {
+        let output = Command::new("quota")
+            .args(["-v", "-w", "-p", "-f"])
+            .arg(path)
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => SensorError::unavailable("quota not installed"),
+                _ => SensorError::Io(e),
+            })?;
+        // quota exits with 1 when the user is over a limit
+        if !output.status.success() && output.stdout.is_empty() {
+            return Err(SensorError::unavailable(format!(
+                "quota failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(Self::parse(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .find(|quota| quota.limit().is_some()))
+    }
+
+    /// Space the user may use: the soft limit, or the hard one without it.
+    pub fn limit(&self) -> Option<u64> {
+        self.soft.or(self.hard)
+    }
+
+    /// Tooltip lines: usage against both limits, grace period and files.
+    pub fn tooltip_lines(&self, config: &SensorConfig) -> Vec<String> {
+        let limits = match (self.soft, self.hard) {
+            (Some(soft), Some(hard)) if soft != hard => format!(
+                "{} soft, {} hard",
+                format::bytes_to_human(soft),
+                format::bytes_to_human(hard),
+            ),
+            (soft, hard) => soft.or(hard).map(format::bytes_to_human).unwrap_or_default(),
+        };
+        let mut lines = vec![format::key_value("Quota", &format!(
+            "{} of {} ({})",
+            format::bytes_to_human(self.used),
+            limits,
+            self.filesystem,
+        ), config)];
+        if let Some(until) = self.grace_until {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+            let grace = match until.checked_sub(now) {
+                Some(left) => format!("🟡 over soft limit, {} left", human_duration(left)),
+                None => "🔴 expired, writes fail".to_string(),
+            };
+            lines.push(format::key_value("Grace", &grace, config));
+        }
+        if let Some(limit) = self.files_soft.or(self.files_hard) {
+            lines.push(format::key_value("Files", &format!("{} of {}", self.files, limit), config));
+        }
+        lines
+    }
+}
+
+/// Rough remaining time, e.g. `3 days` or `5 h`.
+fn human_duration(secs: u64) -> String {
+    match secs {
+        s if s >= 2 * 86_400 => format!("{} days", s / 86_400),
+        s if s >= 3600 => format!("{} h", s / 3600),
+        s => format!("{} min", s / 60),
+    }
+}
+
+/// Cached quota of the current user on a mount.
+#[derive(Debug, Default)]
+pub struct QuotaMonitor {
+    quota: Option<UserQuota>,
+    error: Option<String>,
+    last_query: Option<Instant>,
+}
+
+impl QuotaMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refresh the quota on the filesystem holding `path` if the cached
+    /// result is older than [`REFRESH_INTERVAL`].
+    pub fn update(&mut self, path: &Path) {
+        if self.last_query.is_some_and(|at| at.elapsed() < REFRESH_INTERVAL) {
+            return;
+        }
+        self.last_query = Some(Instant::now());
+        match UserQuota::query(path) {
+            Ok(quota) => {
+                self.quota = quota;
+                self.error = None;
+            }
+            Err(SensorError::Unavailable { reason, .. }) => self.error = Some(reason),
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    /// Last known quota, `None` if the user has none.
+    pub fn quota(&self) -> Option<&UserQuota> {
+        self.quota.as_ref()
+    }
+
+    /// Tooltip lines: the quota, or why the whole filesystem is shown instead.
+    pub fn tooltip_lines(&self, config: &SensorConfig) -> Vec<String> {
+        match (&self.quota, &self.error) {
+            (Some(quota), _) => quota.tooltip_lines(config),
+            (None, Some(error)) => vec![format::key_value("Quota", &format!("unavailable ({})", error), config)],
+            (None, None) => vec![format::key_value("Quota", "none, showing the whole filesystem", config)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_parsing() {
+        let output = "\
+Disk quotas for user alice (uid 1000):
+     Filesystem  blocks   quota   limit   grace   files   quota   limit   grace
+      /dev/sdb1 4200000* 4000000 5000000 1700000000   1200   10000   12000       0
+  nas:/export/home   1024       0       0       0      12       0       0       0
+";
+        let quotas = UserQuota::parse(output);
+        assert_eq!(quotas.len(), 2);
+        assert_eq!(quotas[0], UserQuota {
+            filesystem: "/dev/sdb1".to_string(),
+            used: 4_200_000 * 1024,
+            soft: Some(4_000_000 * 1024),
+            hard: Some(5_000_000 * 1024),
+            grace_until: Some(1_700_000_000),
+            files: 1200,
+            files_soft: Some(10_000),
+            files_hard: Some(12_000),
+        });
+        assert_eq!(quotas[0].limit(), Some(4_000_000 * 1024));
+        assert_eq!(quotas[1].limit(), None);
+        assert_eq!(quotas[1].grace_until, None);
+
+        let lines = quotas[0].tooltip_lines(&SensorConfig::default());
+        assert!(lines[1].contains("expired"));
+        assert_eq!(human_duration(3 * 86_400 + 5), "3 days");
+        assert_eq!(human_duration(7200), "2 h");
+
+        assert!(UserQuota::parse("Disk quotas for user bob (uid 1001): none\n").is_empty());
+    }
+}
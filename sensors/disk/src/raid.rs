@@ -0,0 +1,299 @@
+//! Linux software RAID (md) status from `/proc/mdstat`.
+//!
+//! An array that lost a member keeps working, which is exactly why the loss
+//! goes unnoticed until the next disk dies. mdstat lists every array with its
+//! members, how many of them are in sync (`[2/1] [U_]`) and the progress of a
+//! running resync, recovery or check.
+
+use waysensor_rs_core::{format, Sensor, SensorConfig, SensorError, WaybarOutput};
+use std::fs;
+
+/// One member device of an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MdMember {
+    /// Device name, e.g. `sda1`
+    pub name: String,
+    /// Marked faulty (`(F)`)
+    pub failed: bool,
+    /// Hot spare (`(S)`)
+    pub spare: bool,
+}
+
+/// A running resync, recovery, reshape or check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MdSync {
+    /// `resync`, `recovery`, `reshape` or `check`
+    pub action: String,
+    /// Progress in percent
+    pub percent: f64,
+    /// Estimated minutes left
+    pub finish_min: Option<f64>,
+    /// Current speed in KiB/s
+    pub speed_kib: Option<u64>,
+}
+
+/// State of one md array.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MdArray {
+    /// Array name, e.g. `md0`
+    pub name: String,
+    /// Whether the array is assembled and running
+    pub active: bool,
+    /// RAID level, e.g. `raid1`
+    pub level: Option<String>,
+    /// Member devices
+    pub members: Vec<MdMember>,
+    /// Devices the array should have
+    pub raid_disks: Option<u32>,
+    /// Devices currently in sync
+    pub in_sync: Option<u32>,
+    /// Running sync operation
+    pub sync: Option<MdSync>,
+}
+
+impl MdArray {
+    /// Whether the array runs with fewer devices than it needs, or has
+    /// a faulty member.
+    pub fn is_degraded(&self) -> bool {
+        let missing = matches!((self.raid_disks, self.in_sync), (Some(total), Some(ok)) if ok < total);
+        missing || self.members.iter().any(|m| m.failed)
+    }
+}
+
+/// Parse the contents of `/proc/mdstat`.
+pub fn parse_mdstat(content: &str) -> Vec<MdArray> {
+    let mut arrays: Vec<MdArray> = Vec::new();
+    for line in content.lines() {
+        if let Some((name, rest)) = line.split_once(" : ").filter(|(name, _)| name.starts_with("md")) {
+            let mut words = rest.split_whitespace().peekable();
+            let active = words.next() == Some("active");
+            // "active (auto-read-only) raid1 ..."
+            while words.peek().is_some_and(|w| w.starts_with('(')) {
+                words.next();
+            }
+            let level = words.next_if(|w| !w.contains('[')).map(str::to_owned);
+            let members = words.map(parse_member).collect();
+            arrays.push(MdArray {
+                name: name.trim().to_owned(),
+                active,
+                level,
+                members,
+                raid_disks: None,
+                in_sync: None,
+                sync: None,
+            });
+            continue;
+        }
+
+        let Some(array) = arrays.last_mut().filter(|_| line.starts_with(char::is_whitespace)) else {
+            continue;
+        };
+        if line.contains(" blocks") {
+            // "... [2/1] [U_]"
+            if let Some((total, ok)) = line.split_whitespace()
+                .filter_map(|w| w.strip_prefix('[')?.strip_suffix(']')?.split_once('/'))
+                .next()
+            {
+                array.raid_disks = total.parse().ok();
+                array.in_sync = ok.parse().ok();
+            }
+        } else if let Some(sync) = parse_sync(line) {
+            array.sync = Some(sync);
+        }
+    }
+    arrays
+}
+
+/// Parse a member such as `sdc1[3](F)`.
+fn parse_member(word: &str) -> MdMember {
+    MdMember {
+        name: word.split('[').next().unwrap_or(word).to_owned(),
+        failed: word.ends_with("(F)"),
+        spare: word.ends_with("(S)"),
+    }
+}
+
+/// Parse a progress line such as
+/// `[==>....]  recovery = 12.6% (123/976) finish=80.3min speed=100000K/sec`.
+fn parse_sync(line: &str) -> Option<MdSync> {
+    let (before, after) = line.split_once(" = ")?;
+    let action = before.split_whitespace().last()?.to_owned();
+    let percent = after.split('%').next()?.trim().parse().ok()?;
+    let value = |key: &str| after.split_whitespace().find_map(|w| w.strip_prefix(key));
+    Some(MdSync {
+        action,
+        percent,
+        finish_min: value("finish=").and_then(|v| v.trim_end_matches("min").parse().ok()),
+        speed_kib: value("speed=").and_then(|v| v.trim_end_matches("K/sec").parse().ok()),
+    })
+}
+
+/// Status of all md arrays; critical while any is degraded or inactive.
+#[derive(Debug)]
+pub struct RaidSensor {
+    name: String,
+    config: SensorConfig,
+}
+
+impl RaidSensor {
+    pub fn new() -> Self {
+        Self {
+            name: "raid".to_string(),
+            config: SensorConfig::default(),
+        }
+    }
+}
+
+impl Default for RaidSensor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sensor for RaidSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let content = fs::read_to_string("/proc/mdstat")
+            .map_err(|_| SensorError::unavailable("/proc/mdstat not found (md driver not loaded)"))?;
+        let arrays = parse_mdstat(&content);
+
+        let broken: Vec<&MdArray> = arrays.iter().filter(|a| a.is_degraded() || !a.active).collect();
+        let syncing = arrays.iter().find_map(|a| a.sync.as_ref().map(|sync| (a, sync)));
+        let status = match (broken.first(), syncing) {
+            _ if arrays.is_empty() => "no RAID".to_string(),
+            (Some(array), Some((_, sync))) => format!("{} degraded, {} {:.1}%", array.name, sync.action, sync.percent),
+            (Some(array), None) if array.active => format!("{} degraded", array.name),
+            (Some(array), None) => format!("{} inactive", array.name),
+            (None, Some((array, sync))) => format!("{} {} {:.1}%", array.name, sync.action, sync.percent),
+            (None, None) => "RAID OK".to_string(),
+        };
+        let text = format::with_icon_and_colors(&status, &self.config.icons.disk, &self.config);
+
+        let mut lines = vec![format::key_only("Software RAID", &self.config)];
+        if arrays.is_empty() {
+            lines.push(format::key_value("Arrays", "none", &self.config));
+        }
+        for array in &arrays {
+            let label = match &array.level {
+                Some(level) => format!("{} ({})", array.name, level),
+                None => array.name.clone(),
+            };
+            let state = if !array.active {
+                "🔴 inactive"
+            } else if array.is_degraded() {
+                "🔴 degraded"
+            } else {
+                "✓ clean"
+            };
+            let counts = match (array.in_sync, array.raid_disks) {
+                (Some(ok), Some(total)) => format!(" {}/{}", ok, total),
+                _ => String::new(),
+            };
+            let members = array.members.iter()
+                .map(|m| match (m.failed, m.spare) {
+                    (true, _) => format!("{} failed", m.name),
+                    (_, true) => format!("{} spare", m.name),
+                    _ => m.name.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            lines.push(format::key_value(&label, &format!("{}{} [{}]", state, counts, members), &self.config));
+            if let Some(sync) = &array.sync {
+                let mut value = format!("{:.1}%", sync.percent);
+                if let Some(minutes) = sync.finish_min {
+                    value.push_str(&format!(", {:.0} min left", minutes));
+                }
+                if let Some(speed) = sync.speed_kib {
+                    value.push_str(&format!(" at {}/s", format::bytes_to_human(speed * 1024)));
+                }
+                let mut action = sync.action.clone();
+                action[..1].make_ascii_uppercase();
+                lines.push(format::key_value(&format!("  {}", action), &value, &self.config));
+            }
+        }
+
+        let theme = &self.config.theme;
+        let class = if !broken.is_empty() {
+            &theme.critical
+        } else if syncing.is_some_and(|(_, sync)| sync.action != "check") {
+            &theme.warning
+        } else {
+            &theme.normal
+        };
+
+        Ok(WaybarOutput {
+            text,
+            tooltip: Some(lines.join("\n")),
+            class: Some(class.clone()),
+            percentage: syncing.map(|(_, sync)| sync.percent.round() as u8),
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mdstat_parsing() {
+        let mdstat = "\
+Personalities : [raid1] [raid6] [raid5] [raid4]
+md0 : active raid1 sdb1[1] sda1[0]
+      976630464 blocks super 1.2 [2/2] [UU]
+      bitmap: 0/8 pages [0KB], 65536KB chunk
+
+md1 : active raid5 sdc1[3](F) sdd1[1] sde1[0] sdg1[4](S)
+      1953260544 blocks super 1.2 level 5, 512k chunk, algorithm 2 [3/2] [UU_]
+      [==>..................]  recovery = 12.6% (123456/976630272) finish=80.3min speed=100000K/sec
+
+md2 : inactive sdf1[0](S)
+      976630464 blocks super 1.2
+
+md3 : active (auto-read-only) raid1 sdh1[0] sdi1[1]
+      104320 blocks [2/2] [UU]
+      [=>...................]  check =  5.0% (5216/104320) finish=1.0min speed=5216K/sec
+
+unused devices: <none>
+";
+        let arrays = parse_mdstat(mdstat);
+        assert_eq!(arrays.len(), 4);
+
+        assert_eq!(arrays[0].name, "md0");
+        assert_eq!(arrays[0].level.as_deref(), Some("raid1"));
+        assert_eq!((arrays[0].raid_disks, arrays[0].in_sync), (Some(2), Some(2)));
+        assert!(!arrays[0].is_degraded());
+        assert_eq!(arrays[0].sync, None);
+
+        assert!(arrays[1].is_degraded());
+        assert_eq!(arrays[1].members[0], MdMember { name: "sdc1".to_string(), failed: true, spare: false });
+        assert!(arrays[1].members[3].spare);
+        assert_eq!(arrays[1].sync, Some(MdSync {
+            action: "recovery".to_string(),
+            percent: 12.6,
+            finish_min: Some(80.3),
+            speed_kib: Some(100_000),
+        }));
+
+        assert!(!arrays[2].active);
+        assert_eq!(arrays[2].level, None);
+        assert_eq!(arrays[2].members.len(), 1);
+
+        assert_eq!(arrays[3].level.as_deref(), Some("raid1"));
+        assert_eq!(arrays[3].sync.as_ref().map(|s| (s.action.as_str(), s.percent)), Some(("check", 5.0)));
+        assert!(!arrays[3].is_degraded());
+    }
+}
@@ -0,0 +1,321 @@
+//! Drive health from SMART, read through `smartctl --json`.
+//!
+//! smartctl knows the quirks of thousands of ATA drives and speaks NVMe, so
+//! it is used as the backend instead of issuing ioctls directly. Querying a
+//! drive needs root or `CAP_SYS_RAWIO`, takes tens of milliseconds and SMART
+//! data changes slowly, so results are cached for [`REFRESH_INTERVAL`].
+//! Drives in standby are not spun up for it.
+
+use waysensor_rs_core::{format, SensorConfig, SensorError, Theme};
+use serde_json::Value;
+use std::{
+    fs,
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// How long a SMART query result is reused.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(600);
+
+/// ATA attributes whose raw value counts damaged sectors.
+const REALLOCATED_SECTORS: u64 = 5;
+const PENDING_SECTORS: u64 = 197;
+
+/// Health summary of one drive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SmartHealth {
+    /// Drive model
+    pub model: Option<String>,
+    /// Overall self-assessment; `Some(false)` means the drive predicts its own failure
+    pub passed: Option<bool>,
+    /// Remapped sectors (ATA attribute 5)
+    pub reallocated_sectors: Option<u64>,
+    /// Sectors waiting to be remapped (ATA attribute 197)
+    pub pending_sectors: Option<u64>,
+    /// Share of rated endurance consumed (NVMe, may exceed 100)
+    pub percentage_used: Option<u64>,
+    /// Unrecovered data integrity errors (NVMe)
+    pub media_errors: Option<u64>,
+    /// NVMe critical warning bit field; any bit set needs attention
+    pub critical_warning: u64,
+    /// ATA attributes below their failure threshold right now
+    pub failing: Vec<String>,
+}
+
+impl SmartHealth {
+    /// Extract the health summary from `smartctl --json -a` output.
+    ///
+    /// Returns `None` if the output carries no SMART data, e.g. for a drive
+    /// left in standby or a device without SMART.
+    pub fn parse(json: &Value) -> Option<Self> {
+        let nvme = json.get("nvme_smart_health_information_log");
+        let attributes = json.pointer("/ata_smart_attributes/table").and_then(Value::as_array);
+        let passed = json.pointer("/smart_status/passed").and_then(Value::as_bool);
+        if nvme.is_none() && attributes.is_none() && passed.is_none() {
+            return None;
+        }
+
+        let raw = |id: u64| attributes?.iter()
+            .find(|attr| attr.get("id").and_then(Value::as_u64) == Some(id))?
+            .pointer("/raw/value")?
+            .as_u64();
+        let nvme_value = |key: &str| nvme?.get(key)?.as_u64();
+
+        let failing = attributes.into_iter()
+            .flatten()
+            .filter(|attr| attr.get("when_failed").and_then(Value::as_str) == Some("now"))
+            .filter_map(|attr| attr.get("name").and_then(Value::as_str).map(str::to_owned))
+            .collect();
+
+        Some(Self {
+            model: json.get("model_name").and_then(Value::as_str).map(str::to_owned),
+            passed,
+            reallocated_sectors: raw(REALLOCATED_SECTORS),
+            pending_sectors: raw(PENDING_SECTORS),
+            percentage_used: nvme_value("percentage_used"),
+            media_errors: nvme_value("media_errors"),
+            critical_warning: nvme_value("critical_warning").unwrap_or(0),
+            failing,
+        })
+    }
+
+    /// Query `device` (e.g. `/dev/sda`) with smartctl.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if smartctl is not installed, cannot open the device
+    /// (usually missing privileges) or reports no SMART data.
+    pub fn query(device: &str) -> Result<Option<Self>, SensorError> {
+        let output = Command::new("smartctl")
+            .args(["--json", "-a", "-n", "standby,0", device])
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => SensorError::unavailable("smartctl not installed"),
+                _ => SensorError::unavailable(format!("cannot run smartctl: {}", e)),
+            })?;
+
+        let json: Value = serde_json::from_slice(&output.stdout)
+            .map_err(|e| SensorError::parse_with_source("Invalid smartctl JSON output", e))?;
+
+        // Bits 0 and 1 of the exit status: bad command line or device open failed
+        let exit_status = json.pointer("/smartctl/exit_status").and_then(Value::as_u64).unwrap_or(0);
+        if exit_status & 0b11 != 0 {
+            let message = json.pointer("/smartctl/messages/0/string")
+                .and_then(Value::as_str)
+                .unwrap_or("smartctl failed");
+            return Err(SensorError::unavailable(format!("{}: {}", device, message)));
+        }
+
+        Ok(Self::parse(&json))
+    }
+
+    /// CSS class reflecting drive health.
+    pub fn class<'a>(&self, theme: &'a Theme) -> &'a str {
+        let worn_out = self.percentage_used.is_some_and(|used| used >= 100);
+        let damaged = self.reallocated_sectors.unwrap_or(0) > 0
+            || self.pending_sectors.unwrap_or(0) > 0
+            || self.media_errors.unwrap_or(0) > 0
+            || self.percentage_used.is_some_and(|used| used >= 90);
+
+        if self.passed == Some(false) || !self.failing.is_empty() || self.critical_warning != 0 || worn_out {
+            &theme.critical
+        } else if damaged {
+            &theme.warning
+        } else {
+            &theme.normal
+        }
+    }
+
+    /// Tooltip lines describing drive health.
+    pub fn tooltip_lines(&self, config: &SensorConfig) -> Vec<String> {
+        let status = match self.passed {
+            Some(true) if self.failing.is_empty() => "✓ passed".to_string(),
+            Some(false) => "🔴 FAILING".to_string(),
+            _ if !self.failing.is_empty() => format!("🔴 failing: {}", self.failing.join(", ")),
+            _ => "unknown".to_string(),
+        };
+        let mut lines = vec![format::key_value("SMART", &status, config)];
+
+        if let Some(model) = &self.model {
+            lines.push(format::key_value("Drive", model, config));
+        }
+        if let Some(sectors) = self.reallocated_sectors {
+            lines.push(format::key_value("Reallocated Sectors", &sectors.to_string(), config));
+        }
+        if let Some(sectors) = self.pending_sectors.filter(|&n| n > 0) {
+            lines.push(format::key_value("Pending Sectors", &sectors.to_string(), config));
+        }
+        if let Some(used) = self.percentage_used {
+            lines.push(format::key_value("Endurance Used", &format!("{}%", used), config));
+        }
+        if let Some(errors) = self.media_errors {
+            lines.push(format::key_value("Media Errors", &errors.to_string(), config));
+        }
+        if self.critical_warning != 0 {
+            lines.push(format::key_value(
+                "Critical Warning",
+                &format!("🔴 0x{:02x}", self.critical_warning),
+                config,
+            ));
+        }
+        lines
+    }
+}
+
+/// Whole-disk device node for a partition or device-mapper node, e.g.
+/// `/dev/nvme0n1p2` → `/dev/nvme0n1`. SMART only exists for whole disks.
+pub fn whole_disk(device: &str) -> Option<String> {
+    whole_disk_name(device).map(|disk| format!("/dev/{}", disk))
+}
+
+/// Kernel name of the whole disk behind `device`, e.g. `nvme0n1`.
+pub fn whole_disk_name(device: &str) -> Option<String> {
+    let name = fs::canonicalize(device).ok()?.file_name()?.to_string_lossy().into_owned();
+    whole_disk_in(Path::new("/sys/class/block"), &name)
+}
+
+/// Whole-disk name of block device `name` below a sysfs `class/block`
+/// directory (useful for testing).
+pub fn whole_disk_in(sys_class_block: &Path, name: &str) -> Option<String> {
+    let dir = sys_class_block.join(name);
+    if !dir.exists() {
+        return None;
+    }
+    // Partitions live below their disk's directory
+    if dir.join("partition").exists() {
+        let disk = fs::canonicalize(&dir).ok()?.parent()?.file_name()?.to_string_lossy().into_owned();
+        return Some(disk);
+    }
+    // dm-crypt and LVM: follow the first underlying device
+    let slave = fs::read_dir(dir.join("slaves")).ok()
+        .and_then(|mut entries| entries.next())
+        .and_then(Result::ok);
+    match slave {
+        Some(slave) => whole_disk_in(sys_class_block, &slave.file_name().to_string_lossy()),
+        None => Some(name.to_owned()),
+    }
+}
+
+/// Cached SMART state of the drive behind a filesystem.
+#[derive(Debug, Default)]
+pub struct SmartMonitor {
+    device: Option<String>,
+    health: Option<SmartHealth>,
+    error: Option<String>,
+    last_query: Option<Instant>,
+}
+
+impl SmartMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refresh the health of the drive behind `device` (as reported by `df`)
+    /// if the cached result is older than [`REFRESH_INTERVAL`].
+    pub fn update(&mut self, device: &str) {
+        if self.last_query.is_some_and(|at| at.elapsed() < REFRESH_INTERVAL) {
+            return;
+        }
+        self.last_query = Some(Instant::now());
+
+        let Some(disk) = whole_disk(device) else {
+            self.error = Some(format!("no block device behind {}", device));
+            return;
+        };
+        match SmartHealth::query(&disk) {
+            Ok(Some(health)) => {
+                self.health = Some(health);
+                self.error = None;
+            }
+            // Standby: keep the last known health
+            Ok(None) => {}
+            Err(SensorError::Unavailable { reason, .. }) => self.error = Some(reason),
+            Err(e) => self.error = Some(e.to_string()),
+        }
+        self.device = Some(disk);
+    }
+
+    /// Last known health.
+    pub fn health(&self) -> Option<&SmartHealth> {
+        self.health.as_ref()
+    }
+
+    /// Tooltip lines: health, or why it is unavailable.
+    pub fn tooltip_lines(&self, config: &SensorConfig) -> Vec<String> {
+        match (&self.health, &self.error) {
+            (Some(health), _) => health.tooltip_lines(config),
+            (None, Some(error)) => vec![format::key_value("SMART", &format!("unavailable ({})", error), config)],
+            (None, None) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smart_parsing() {
+        let ata: Value = serde_json::from_str(r#"{
+            "smartctl": {"exit_status": 8},
+            "model_name": "WDC WD40EFRX",
+            "smart_status": {"passed": false},
+            "ata_smart_attributes": {"table": [
+                {"id": 5, "name": "Reallocated_Sector_Ct", "when_failed": "now", "raw": {"value": 1200}},
+                {"id": 9, "name": "Power_On_Hours", "when_failed": "", "raw": {"value": 40000}},
+                {"id": 197, "name": "Current_Pending_Sector", "when_failed": "", "raw": {"value": 8}}
+            ]}
+        }"#).unwrap();
+        let health = SmartHealth::parse(&ata).unwrap();
+        let theme = Theme::default();
+        assert_eq!(health.passed, Some(false));
+        assert_eq!(health.reallocated_sectors, Some(1200));
+        assert_eq!(health.pending_sectors, Some(8));
+        assert_eq!(health.failing, vec!["Reallocated_Sector_Ct".to_owned()]);
+        assert_eq!(health.class(&theme), theme.critical);
+
+        let nvme: Value = serde_json::from_str(r#"{
+            "smartctl": {"exit_status": 0},
+            "smart_status": {"passed": true},
+            "nvme_smart_health_information_log": {
+                "critical_warning": 0, "percentage_used": 92, "media_errors": 0
+            }
+        }"#).unwrap();
+        let health = SmartHealth::parse(&nvme).unwrap();
+        assert_eq!(health.percentage_used, Some(92));
+        assert_eq!(health.media_errors, Some(0));
+        assert_eq!(health.reallocated_sectors, None);
+        assert_eq!(health.class(&theme), theme.warning);
+
+        // Drive in standby: no SMART data at all
+        let standby: Value = serde_json::from_str(r#"{"smartctl": {"exit_status": 0}}"#).unwrap();
+        assert_eq!(SmartHealth::parse(&standby), None);
+    }
+
+    #[test]
+    fn test_whole_disk_resolution() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let devices = root.join("devices");
+        let class = root.join("class");
+        fs::create_dir_all(devices.join("nvme0n1").join("nvme0n1p2")).unwrap();
+        fs::write(devices.join("nvme0n1").join("nvme0n1p2").join("partition"), "2\n").unwrap();
+        fs::create_dir_all(devices.join("dm-0").join("slaves")).unwrap();
+        fs::create_dir_all(&class).unwrap();
+        std::os::unix::fs::symlink(devices.join("nvme0n1"), class.join("nvme0n1")).unwrap();
+        std::os::unix::fs::symlink(devices.join("nvme0n1").join("nvme0n1p2"), class.join("nvme0n1p2")).unwrap();
+        std::os::unix::fs::symlink(devices.join("dm-0"), class.join("dm-0")).unwrap();
+        std::os::unix::fs::symlink(class.join("nvme0n1p2"), devices.join("dm-0").join("slaves").join("nvme0n1p2")).unwrap();
+
+        let partition = whole_disk_in(&class, "nvme0n1p2");
+        let mapped = whole_disk_in(&class, "dm-0");
+        let disk = whole_disk_in(&class, "nvme0n1");
+        let missing = whole_disk_in(&class, "sdz");
+
+        assert_eq!(partition.as_deref(), Some("nvme0n1"));
+        assert_eq!(mapped.as_deref(), Some("nvme0n1"));
+        assert_eq!(disk.as_deref(), Some("nvme0n1"));
+        assert_eq!(missing, None);
+    }
+}
@@ -0,0 +1,147 @@
+//! Filesystems selected by label or UUID instead of mount point.
+//!
+//! Mount points change: a drive gets renamed in fstab, or a desktop mounts a
+//! USB stick under `/run/media/$USER/<label>` only while it is plugged in.
+//! The label and UUID stay with the filesystem, and udev keeps symlinks for
+//! both in `/dev/disk/by-label` and `/dev/disk/by-uuid`. The mount point is
+//! looked up from the device on every read.
+
+use crate::disk::{parse_real_mounts, Mount};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+use waysensor_rs_core::SensorError;
+
+/// Filesystem identified independently of where it is mounted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FsTarget {
+    /// Filesystem label, e.g. `DATA`
+    Label(String),
+    /// Filesystem UUID as shown by `blkid`
+    Uuid(String),
+}
+
+impl fmt::Display for FsTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Label(label) => write!(f, "label {}", label),
+            Self::Uuid(uuid) => write!(f, "UUID {}", uuid),
+        }
+    }
+}
+
+impl FsTarget {
+    /// Short identifier for sensor names, e.g. `label-DATA`.
+    pub fn id(&self) -> String {
+        match self {
+            Self::Label(label) => format!("label-{}", label),
+            Self::Uuid(uuid) => format!("uuid-{}", uuid),
+        }
+    }
+
+    /// udev symlinks that may point at the device, most likely first.
+    pub fn links(&self) -> Vec<PathBuf> {
+        match self {
+            Self::Label(label) => vec![Path::new("/dev/disk/by-label").join(escape_label(label))],
+            // ext4 and btrfs UUIDs are lowercase, FAT serials uppercase
+            Self::Uuid(uuid) => [uuid.clone(), uuid.to_lowercase(), uuid.to_uppercase()]
+                .iter()
+                .map(|uuid| Path::new("/dev/disk/by-uuid").join(uuid))
+                .collect(),
+        }
+    }
+
+    /// Block device carrying the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns a temporary unavailability error if no such filesystem is
+    /// connected.
+    pub fn device(&self) -> Result<PathBuf, SensorError> {
+        self.links()
+            .iter()
+            .find_map(|link| fs::canonicalize(link).ok())
+            .ok_or_else(|| SensorError::temporarily_unavailable(format!("No filesystem with {} is connected", self)))
+    }
+
+    /// Current mount of the filesystem.
+    ///
+    /// # Errors
+    ///
+    /// Returns a temporary unavailability error if the filesystem is not
+    /// connected or not mounted.
+    pub fn resolve(&self) -> Result<Mount, SensorError> {
+        let device = self.device()?;
+        let mounts = parse_real_mounts(&fs::read_to_string("/proc/mounts")?);
+        mount_of(&mounts, &device, |path| fs::canonicalize(path).ok())
+            .cloned()
+            .ok_or_else(|| SensorError::temporarily_unavailable(format!(
+                "Filesystem with {} ({}) is not mounted",
+                self,
+                device.display(),
+            )))
+    }
+}
+
+/// First mount of `device`. Mount sources are compared after `canonicalize`,
+/// so `/dev/mapper/*` and `/dev/disk/*` symlinks match the device node.
+pub fn mount_of<'a, F>(mounts: &'a [Mount], device: &Path, canonicalize: F) -> Option<&'a Mount>
+where
+    F: Fn(&Path) -> Option<PathBuf>,
+{
+    mounts.iter().find(|mount| {
+        let source = Path::new(&mount.device);
+        source == device || canonicalize(source).as_deref() == Some(device)
+    })
+}
+
+/// Encode a label the way udev names its `/dev/disk/by-label` links: bytes
+/// outside `[A-Za-z0-9#+-.:=@_]` and non-ASCII characters become `\xNN`.
+pub fn escape_label(label: &str) -> String {
+    let mut escaped = String::with_capacity(label.len());
+    for c in label.chars() {
+        if c.is_ascii_alphanumeric() || "#+-.:=@_".contains(c) || !c.is_ascii() {
+            escaped.push(c);
+        } else {
+            escaped.push_str(&format!("\\x{:02x}", c as u8));
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fs_target_resolution() {
+        assert_eq!(escape_label("DATA"), "DATA");
+        assert_eq!(escape_label("My Files/2"), "My\\x20Files\\x2f2");
+        assert_eq!(escape_label("Música"), "Música");
+
+        let label = FsTarget::Label("My Disk".to_string());
+        assert_eq!(label.links(), vec![PathBuf::from("/dev/disk/by-label/My\\x20Disk")]);
+        assert_eq!(label.id(), "label-My Disk");
+        assert_eq!(label.to_string(), "label My Disk");
+        let uuid = FsTarget::Uuid("AbCd-1234".to_string());
+        assert_eq!(uuid.links()[1], PathBuf::from("/dev/disk/by-uuid/abcd-1234"));
+        assert_eq!(uuid.links()[2], PathBuf::from("/dev/disk/by-uuid/ABCD-1234"));
+
+        let mounts = parse_real_mounts("\
+/dev/nvme0n1p2 / ext4 rw 0 0
+/dev/mapper/data /mnt/data btrfs rw 0 0
+/dev/sdb1 /run/media/me/USB\\040STICK vfat rw 0 0
+/dev/sdb1 /srv/bind vfat rw 0 0
+");
+        let canonicalize = |path: &Path| (path == Path::new("/dev/mapper/data")).then(|| PathBuf::from("/dev/dm-0"));
+        let mount_point = |device: &str| mount_of(&mounts, Path::new(device), canonicalize)
+            .map(|mount| mount.mount_point.as_str());
+        assert_eq!(mount_point("/dev/sdb1"), Some("/run/media/me/USB STICK"));
+        assert_eq!(mount_point("/dev/dm-0"), Some("/mnt/data"));
+        assert_eq!(mount_point("/dev/sdc1"), None);
+
+        let missing = FsTarget::Label("waysensor-test-missing".to_string());
+        assert!(missing.resolve().unwrap_err().is_temporary());
+    }
+}
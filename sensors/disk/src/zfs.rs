@@ -0,0 +1,240 @@
+//! ZFS pool state from `zpool list`.
+//!
+//! A ZFS dataset's `df` numbers say nothing about the pool below it: every
+//! dataset shares the pool's free space, and a pool that lost a disk keeps
+//! serving data while DEGRADED. Pool capacity, fragmentation and health are
+//! read with `zpool list -Hp`, which needs no privileges.
+
+use waysensor_rs_core::{format, SensorConfig, SensorError, Theme};
+use std::{
+    fmt,
+    process::Command,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// How long a `zpool list` result is reused.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Health state of a pool as reported by `zpool`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZpoolHealth {
+    Online,
+    Degraded,
+    Faulted,
+    Offline,
+    Unavail,
+    Removed,
+    Suspended,
+}
+
+impl FromStr for ZpoolHealth {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ONLINE" => Ok(Self::Online),
+            "DEGRADED" => Ok(Self::Degraded),
+            "FAULTED" => Ok(Self::Faulted),
+            "OFFLINE" => Ok(Self::Offline),
+            "UNAVAIL" => Ok(Self::Unavail),
+            "REMOVED" => Ok(Self::Removed),
+            "SUSPENDED" => Ok(Self::Suspended),
+            _ => Err(SensorError::parse(format!("Unknown pool health: {}", s))),
+        }
+    }
+}
+
+impl fmt::Display for ZpoolHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Online => "ONLINE",
+            Self::Degraded => "DEGRADED",
+            Self::Faulted => "FAULTED",
+            Self::Offline => "OFFLINE",
+            Self::Unavail => "UNAVAIL",
+            Self::Removed => "REMOVED",
+            Self::Suspended => "SUSPENDED",
+        })
+    }
+}
+
+/// Capacity and health of one pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Zpool {
+    /// Pool name
+    pub name: String,
+    /// Pool size in bytes
+    pub size: u64,
+    /// Allocated bytes
+    pub allocated: u64,
+    /// Free bytes
+    pub free: u64,
+    /// Free space fragmentation in percent (unknown for some pool layouts)
+    pub fragmentation: Option<u8>,
+    /// Allocated share of the pool in percent
+    pub capacity: u8,
+    /// Health state
+    pub health: ZpoolHealth,
+}
+
+impl Zpool {
+    /// Parse `zpool list -Hp -o name,size,alloc,free,frag,cap,health` output.
+    pub fn parse_list(output: &str) -> Vec<Self> {
+        output.lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split('\t').collect();
+                let [name, size, allocated, free, fragmentation, capacity, health] = fields[..] else {
+                    return None;
+                };
+                Some(Self {
+                    name: name.to_owned(),
+                    size: size.parse().ok()?,
+                    allocated: allocated.parse().ok()?,
+                    free: free.parse().ok()?,
+                    // "-" when not applicable
+                    fragmentation: fragmentation.trim_end_matches('%').parse().ok(),
+                    capacity: capacity.trim_end_matches('%').parse().ok()?,
+                    health: health.parse().ok()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Query pool `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `zpool` is missing, fails or does not know the pool.
+    pub fn query(name: &str) -> Result<Self, SensorError> {
+        let output = Command::new("zpool")
+            .args(["list", "-Hp", "-o", "name,size,alloc,free,frag,cap,health", name])
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => SensorError::unavailable("zpool not installed"),
+                _ => SensorError::Io(e),
+            })?;
+        if !output.status.success() {
+            return Err(SensorError::unavailable(format!(
+                "zpool list failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Self::parse_list(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .find(|pool| pool.name == name)
+            .ok_or_else(|| SensorError::parse("Could not parse zpool list output"))
+    }
+
+    /// CSS class for the pool's health; anything but ONLINE is critical.
+    pub fn class<'a>(&self, theme: &'a Theme) -> Option<&'a str> {
+        (self.health != ZpoolHealth::Online).then_some(theme.critical.as_str())
+    }
+
+    /// Tooltip lines describing the pool.
+    pub fn tooltip_lines(&self, config: &SensorConfig) -> Vec<String> {
+        let health = match self.health {
+            ZpoolHealth::Online => self.health.to_string(),
+            _ => format!("🔴 {}", self.health),
+        };
+        let mut lines = vec![
+            format::key_value("ZFS Pool", &format!("{} ({})", self.name, health), config),
+            format::key_value("Pool Capacity", &format!(
+                "{}% of {} ({} free)",
+                self.capacity,
+                format::bytes_to_human(self.size),
+                format::bytes_to_human(self.free),
+            ), config),
+        ];
+        if let Some(fragmentation) = self.fragmentation {
+            lines.push(format::key_value("Fragmentation", &format!("{}%", fragmentation), config));
+        }
+        lines
+    }
+}
+
+/// Pool of a ZFS dataset as `df` reports it, e.g. `tank/home` → `tank`.
+pub fn pool_of(dataset: &str) -> &str {
+    dataset.split('/').next().unwrap_or(dataset)
+}
+
+/// Cached state of the pool behind a ZFS mount.
+#[derive(Debug, Default)]
+pub struct ZfsMonitor {
+    pool: Option<Zpool>,
+    error: Option<String>,
+    last_query: Option<Instant>,
+}
+
+impl ZfsMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refresh the pool behind `dataset` if the cached result is older than
+    /// [`REFRESH_INTERVAL`].
+    pub fn update(&mut self, dataset: &str) {
+        if self.last_query.is_some_and(|at| at.elapsed() < REFRESH_INTERVAL) {
+            return;
+        }
+        self.last_query = Some(Instant::now());
+        match Zpool::query(pool_of(dataset)) {
+            Ok(pool) => {
+                self.pool = Some(pool);
+                self.error = None;
+            }
+            Err(SensorError::Unavailable { reason, .. }) => self.error = Some(reason),
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    /// Last known pool state.
+    pub fn pool(&self) -> Option<&Zpool> {
+        self.pool.as_ref()
+    }
+
+    /// Tooltip lines: pool state, or why it is unavailable.
+    pub fn tooltip_lines(&self, config: &SensorConfig) -> Vec<String> {
+        match (&self.pool, &self.error) {
+            (Some(pool), _) => pool.tooltip_lines(config),
+            (None, Some(error)) => vec![format::key_value("ZFS Pool", &format!("unavailable ({})", error), config)],
+            (None, None) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zpool_list_parsing() {
+        let output = "\
+tank\t3985729650688\t1793578221568\t2192151429120\t12\t45\tONLINE
+backup\t1992864825344\t1594291860275\t398572965069\t-\t80\tDEGRADED
+broken\tnot a number\t0\t0\t0\t0\tONLINE
+";
+        let pools = Zpool::parse_list(output);
+        assert_eq!(pools.len(), 2);
+        assert_eq!(pools[0], Zpool {
+            name: "tank".to_string(),
+            size: 3_985_729_650_688,
+            allocated: 1_793_578_221_568,
+            free: 2_192_151_429_120,
+            fragmentation: Some(12),
+            capacity: 45,
+            health: ZpoolHealth::Online,
+        });
+        assert_eq!(pools[1].fragmentation, None);
+        assert_eq!(pools[1].health, ZpoolHealth::Degraded);
+
+        let theme = Theme::default();
+        assert_eq!(pools[0].class(&theme), None);
+        assert_eq!(pools[1].class(&theme), Some(theme.critical.as_str()));
+
+        assert_eq!(pool_of("tank/home/me"), "tank");
+        assert_eq!(pool_of("tank"), "tank");
+        assert!("FAULTED".parse::<ZpoolHealth>().is_ok());
+        assert!("online".parse::<ZpoolHealth>().is_err());
+    }
+}
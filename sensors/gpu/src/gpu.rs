@@ -0,0 +1,269 @@
+//! GPU vendor detection and delegation to the vendor sensors.
+
+use std::fs;
+use std::path::Path;
+use waysensor_rs_amd_gpu::AmdgpuSensor;
+use waysensor_rs_core::{DiscoveredItem, Discoverable, Reading, Sensor, SensorConfig, SensorError, WaybarOutput};
+use waysensor_rs_intel_gpu::IntelGpuSensor;
+use waysensor_rs_nvidia_gpu::NvidiaGpuSensor;
+
+const DRM_PATH: &str = "/sys/class/drm";
+
+/// GPU vendor, each handled by its own sensor crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GpuVendor {
+    // Declared in order of preference: discrete GPUs before integrated ones
+    Nvidia,
+    Amd,
+    Intel,
+}
+
+impl GpuVendor {
+    /// Vendor of a PCI vendor id as sysfs prints it (`0x1002`).
+    pub fn from_pci_id(id: &str) -> Option<Self> {
+        match id.trim() {
+            "0x1002" => Some(Self::Amd),
+            "0x10de" => Some(Self::Nvidia),
+            "0x8086" => Some(Self::Intel),
+            _ => None,
+        }
+    }
+
+    /// Section of the global config holding this vendor's sensor settings.
+    pub fn config_section(self) -> &'static str {
+        match self {
+            Self::Amd => "amd-gpu",
+            Self::Nvidia => "nvidia-gpu",
+            Self::Intel => "intel-gpu",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Amd => "AMD",
+            Self::Nvidia => "NVIDIA",
+            Self::Intel => "Intel",
+        }
+    }
+}
+
+impl std::str::FromStr for GpuVendor {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "amd" | "amdgpu" => Ok(Self::Amd),
+            "nvidia" => Ok(Self::Nvidia),
+            "intel" => Ok(Self::Intel),
+            _ => Err(SensorError::config_with_value(
+                "Invalid GPU vendor. Valid options: amd, nvidia, intel",
+                s,
+            )),
+        }
+    }
+}
+
+/// A DRM card of a known vendor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuCard {
+    /// Card number, 1 for `/sys/class/drm/card1`
+    pub card: u32,
+    pub vendor: GpuVendor,
+    /// PCI slot, e.g. `0000:03:00.0`
+    pub pci_slot: Option<String>,
+}
+
+/// Every DRM card of a supported vendor, by card number.
+pub fn detect_cards() -> Vec<GpuCard> {
+    detect_cards_in(Path::new(DRM_PATH))
+}
+
+fn detect_cards_in(drm_path: &Path) -> Vec<GpuCard> {
+    let Ok(entries) = fs::read_dir(drm_path) else {
+        return Vec::new();
+    };
+    let mut cards: Vec<GpuCard> = entries
+        .flatten()
+        .filter_map(|entry| {
+            // card1, but not connectors such as card1-DP-1
+            let card = entry.file_name().to_str()?.strip_prefix("card")?.parse().ok()?;
+            let device = entry.path().join("device");
+            let vendor = GpuVendor::from_pci_id(&fs::read_to_string(device.join("vendor")).ok()?)?;
+            let pci_slot = fs::canonicalize(&device).ok()
+                .and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()));
+            Some(GpuCard { card, vendor, pci_slot })
+        })
+        .collect();
+    cards.sort_by_key(|card| card.card);
+    cards
+}
+
+/// Vendors present, most preferred first. NVIDIA's driver may run without
+/// DRM nodes (no `nvidia-drm` modeset), so it is also found through procfs.
+fn detect_vendors() -> Vec<GpuVendor> {
+    let mut vendors: Vec<GpuVendor> = detect_cards().into_iter().map(|card| card.vendor).collect();
+    if fs::read_dir("/proc/driver/nvidia/gpus").is_ok_and(|mut gpus| gpus.next().is_some()) {
+        vendors.push(GpuVendor::Nvidia);
+    }
+    vendors.sort();
+    vendors.dedup();
+    vendors
+}
+
+/// nvidia-smi index of an NVIDIA card: its position in PCI bus order.
+fn nvidia_index(card: &GpuCard) -> Option<u32> {
+    let mut slots: Vec<String> = detect_cards().into_iter()
+        .filter(|other| other.vendor == GpuVendor::Nvidia)
+        .filter_map(|other| other.pci_slot)
+        .collect();
+    slots.sort();
+    let slot = card.pci_slot.as_ref()?;
+    slots.iter().position(|other| other == slot).map(|index| index as u32)
+}
+
+/// The GPU sensor of whichever vendor the system has, with each vendor's
+/// default thresholds.
+pub struct GpuSensor {
+    vendor: GpuVendor,
+    inner: Box<dyn Sensor<Error = SensorError>>,
+}
+
+impl std::fmt::Debug for GpuSensor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuSensor").field("vendor", &self.vendor).finish_non_exhaustive()
+    }
+}
+
+impl GpuSensor {
+    /// Monitor the given card, or the first GPU of the given vendor, or the
+    /// preferred GPU found: NVIDIA, then AMD, then Intel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no supported GPU is found, the card does not
+    /// exist or belongs to another vendor than the one asked for.
+    pub fn new(vendor: Option<GpuVendor>, card: Option<u32>) -> Result<Self, SensorError> {
+        if let Some(card) = card {
+            let found = detect_cards().into_iter().find(|found| found.card == card)
+                .ok_or_else(|| SensorError::unavailable(format!("No supported GPU at card{}", card)))?;
+            if vendor.is_some_and(|vendor| vendor != found.vendor) {
+                return Err(SensorError::config(format!("card{} is an {} GPU", card, found.vendor.name())));
+            }
+            return Self::open(found.vendor, Some(&found));
+        }
+        if let Some(vendor) = vendor {
+            return Self::open(vendor, None);
+        }
+
+        let mut last_error = None;
+        for vendor in detect_vendors() {
+            match Self::open(vendor, None) {
+                Ok(sensor) => return Ok(sensor),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| SensorError::unavailable("No AMD, NVIDIA or Intel GPU found")))
+    }
+
+    fn open(vendor: GpuVendor, card: Option<&GpuCard>) -> Result<Self, SensorError> {
+        let inner: Box<dyn Sensor<Error = SensorError>> = match vendor {
+            GpuVendor::Amd => Box::new(
+                AmdgpuSensor::new(None, 80, 90, "compact".to_string(), false)?
+                    .select_device(card.map(|card| card.card), None)?,
+            ),
+            GpuVendor::Intel => Box::new(
+                IntelGpuSensor::with_defaults()?.select_device(card.map(|card| card.card), None)?,
+            ),
+            GpuVendor::Nvidia => match card {
+                Some(card) => {
+                    let index = nvidia_index(card).ok_or_else(|| {
+                        SensorError::unavailable(format!("card{} has no NVIDIA GPU index", card.card))
+                    })?;
+                    Box::new(NvidiaGpuSensor::new_with_gpu_id(80, 95, index)?)
+                }
+                None => Box::new(NvidiaGpuSensor::with_defaults()?),
+            },
+        };
+        Ok(Self { vendor, inner })
+    }
+
+    /// Vendor of the monitored GPU.
+    pub fn vendor(&self) -> GpuVendor {
+        self.vendor
+    }
+}
+
+impl Discoverable for GpuSensor {
+    const KIND: &'static str = "GPUs";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        // A vendor whose tools are missing simply contributes nothing
+        let vendors = [
+            (GpuVendor::Amd, AmdgpuSensor::discover()),
+            (GpuVendor::Nvidia, NvidiaGpuSensor::discover()),
+            (GpuVendor::Intel, IntelGpuSensor::discover()),
+        ];
+        Ok(vendors
+            .into_iter()
+            .flat_map(|(vendor, items)| {
+                items.unwrap_or_default().into_iter().map(move |item| item.with_detail("vendor", vendor.name()))
+            })
+            .collect())
+    }
+}
+
+impl Sensor for GpuSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        self.inner.read()
+    }
+
+    fn read_typed(&mut self) -> Result<(WaybarOutput, Reading), Self::Error> {
+        self.inner.read_typed()
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.inner.configure(config)
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        self.inner.check_availability()
+    }
+
+    fn config(&self) -> &SensorConfig {
+        self.inner.config()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_cards_by_pci_vendor() {
+        let dir = tempfile::tempdir().unwrap();
+        let drm = dir.path();
+        let card = |name: &str, vendor: &str| {
+            let device = drm.join(name).join("device");
+            fs::create_dir_all(&device).unwrap();
+            fs::write(device.join("vendor"), format!("{}\n", vendor)).unwrap();
+            device
+        };
+        card("card0", "0x8086");
+        card("card1", "0x10de");
+        card("card1-DP-1", "0x10de");
+        card("card2", "0x1af4");
+
+        let cards = detect_cards_in(drm);
+        let found: Vec<(u32, GpuVendor)> = cards.iter().map(|card| (card.card, card.vendor)).collect();
+        assert_eq!(found, [(0, GpuVendor::Intel), (1, GpuVendor::Nvidia)]);
+        assert!(GpuVendor::Nvidia < GpuVendor::Intel);
+        assert_eq!("AMDGPU".parse::<GpuVendor>().unwrap(), GpuVendor::Amd);
+
+    }
+}
@@ -0,0 +1,9 @@
+//! Vendor-neutral GPU sensor for waysensor-rs.
+//!
+//! Detects which GPUs the system has from their PCI vendor ids and runs the
+//! AMD, NVIDIA or Intel sensor for the right one, so a bar configuration
+//! does not have to name the vendor.
+
+pub mod gpu;
+
+pub use gpu::{detect_cards, GpuCard, GpuSensor, GpuVendor};
@@ -0,0 +1,198 @@
+//! waysensor-rs-gpu: GPU monitoring binary for Waybar that picks the
+//! vendor sensor at runtime.
+
+use clap::Parser;
+use waysensor_rs_core::{discovery, schedule, session_lock, GlobalConfig, IconStyle, Sensor, SensorConfig};
+use waysensor_rs_gpu::{GpuSensor, GpuVendor};
+use std::io::{self, Write};
+use std::process;
+use std::time::Duration;
+
+/// Command-line arguments for the GPU sensor.
+#[derive(Parser)]
+#[command(name = "waysensor-rs-gpu")]
+#[command(about = "GPU monitoring sensor for waysensor-rs (AMD, NVIDIA or Intel, detected at runtime)")]
+#[command(version)]
+#[command(author)]
+struct Args {
+    /// Update interval in milliseconds (minimum 100ms)
+    #[arg(short, long, default_value = "2000", value_parser = validate_interval)]
+    interval: u64,
+
+    /// Monitor a GPU of this vendor (amd, nvidia, intel) instead of detecting it
+    #[arg(long)]
+    vendor: Option<GpuVendor>,
+
+    /// Monitor this card, e.g. 1 for /sys/class/drm/card1
+    #[arg(long)]
+    card: Option<u32>,
+
+    /// One-shot mode (output once and exit)
+    #[arg(short, long)]
+    once: bool,
+
+    /// List the GPUs of every vendor and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Icon style (nerdfont, fontawesome, ascii, none)
+    #[arg(long)]
+    icon_style: Option<IconStyle>,
+
+    /// Icon color (hex format like "#7aa2f7")
+    #[arg(long)]
+    icon_color: Option<String>,
+
+    /// Text color (hex format like "#c0caf5")
+    #[arg(long)]
+    text_color: Option<String>,
+
+    /// Tooltip label color (hex format like "#bb9af7")
+    #[arg(long)]
+    tooltip_label_color: Option<String>,
+
+    /// Tooltip value color (hex format like "#9ece6a")
+    #[arg(long)]
+    tooltip_value_color: Option<String>,
+
+    /// Verify a supported GPU is available and exit
+    #[arg(long)]
+    check: bool,
+
+    /// Generate example config file and exit
+    #[arg(long)]
+    generate_config: bool,
+}
+
+/// Validate that the interval is at least 100ms.
+fn validate_interval(s: &str) -> Result<u64, String> {
+    let interval = s.parse::<u64>()
+        .map_err(|_| "Interval must be a positive integer".to_owned())?;
+    
+    if interval < SensorConfig::MIN_UPDATE_INTERVAL {
+        return Err(format!(
+            "Interval must be at least {}ms", 
+            SensorConfig::MIN_UPDATE_INTERVAL
+        ));
+    }
+    
+    Ok(interval)
+}
+
+/// Main entry point for the GPU sensor.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<GpuSensor>(args.json) {
+            eprintln!("Error listing GPUs: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Handle config generation
+    if args.generate_config {
+        if let Some(config_path) = GlobalConfig::default_config_path() {
+            GlobalConfig::save_example_config_to_file(&config_path)?;
+            println!("Generated example config at: {}", config_path.display());
+            println!("\nYou can now edit this file to customize your default colors and settings.");
+        } else {
+            eprintln!("Could not determine config directory");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Detect the GPU and create its vendor's sensor
+    let mut gpu_sensor = match GpuSensor::new(args.vendor, args.card) {
+        Ok(sensor) => sensor,
+        Err(e) => {
+            eprintln!("Failed to create GPU sensor: {}", e);
+            process::exit(1);
+        }
+    };
+    
+    // Check availability if requested
+    if args.check {
+        match gpu_sensor.check_availability() {
+            Ok(()) => {
+                println!("{} GPU sensor is available", gpu_sensor.vendor().name());
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("{} GPU sensor is not available: {}", gpu_sensor.vendor().name(), e);
+                process::exit(1);
+            }
+        }
+    }
+    
+    // Load global configuration, using the detected vendor's section, and
+    // apply command line overrides
+    let global_config = GlobalConfig::load().unwrap_or_default();
+    let mut config = global_config.to_sensor_config_for(gpu_sensor.vendor().config_section())
+        .with_update_interval(Duration::from_millis(args.interval))
+        .apply_color_overrides(
+            args.icon_color,
+            args.text_color,
+            args.tooltip_label_color,
+            args.tooltip_value_color,
+        );
+    
+    // Override icon style only if explicitly provided
+    if let Some(icon_style) = args.icon_style {
+        config = config.with_icon_style(icon_style);
+    }
+    
+    gpu_sensor.configure(config)?;
+    
+    if args.once {
+        // One-shot mode: read once and exit
+        match gpu_sensor.read_styled() {
+            Ok(output) => {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+            Err(e) => {
+                eprintln!("Error reading GPU stats: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        // Continuous mode: loop and output readings
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
+        
+        loop {
+            interval.tick().await;
+            if session_lock::wait_async(gpu_sensor.config()).await {
+                interval.reset();
+            }
+            
+            match gpu_sensor.read_styled() {
+                Ok(output) => {
+                    println!("{}", serde_json::to_string(&output)?);
+                    io::stdout().flush()?;
+                }
+                Err(e) => {
+                    eprintln!("Error reading GPU stats: {}", e);
+                    // Continue running on errors, just log them
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,146 @@
+//! Board power and temperature of discrete Intel GPUs.
+//!
+//! Arc cards register a hwmon device named after their driver (`i915` or
+//! `xe`). Power comes from a cumulative energy counter in microjoules, like
+//! RAPL: both drivers report the card as channel 1 (on xe only for
+//! Battlemage), xe the package as channel 2. The limit of the same channel
+//! is the sustained power limit in microwatts. xe adds package and VRAM
+//! temperatures; i915 reports none. Integrated GPUs have no such device.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Power channels in order of preference: card, then package
+const CHANNELS: [u32; 2] = [1, 2];
+
+fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// The hwmon directory of a discrete Intel GPU's `device` directory.
+pub fn find_hwmon(device: &Path) -> Option<PathBuf> {
+    fs::read_dir(device.join("hwmon")).ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| fs::read_to_string(path.join("name")).is_ok_and(|name| matches!(name.trim(), "i915" | "xe")))
+}
+
+/// One reading of the card's hwmon sensors.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HwmonReading {
+    /// Board (or package) power over the last interval in Watts
+    pub power_watts: Option<f64>,
+    /// Sustained power limit (PL1) in Watts
+    pub power_limit_watts: Option<f64>,
+    /// GPU temperature in °C
+    pub temperature: Option<f64>,
+}
+
+/// Turns a discrete card's hwmon energy counter into power.
+#[derive(Debug)]
+pub struct CardHwmon {
+    path: PathBuf,
+    /// Power channel with a readable energy counter
+    channel: Option<u32>,
+    /// Time and counter of the previous sample
+    previous: Option<(Instant, u64)>,
+}
+
+impl CardHwmon {
+    /// Open the hwmon device of the GPU at `device`, `None` if it has none.
+    pub fn open(device: &Path) -> Option<Self> {
+        let path = find_hwmon(device)?;
+        let channel = CHANNELS.into_iter()
+            .find(|channel| read_u64(&path.join(format!("energy{}_input", channel))).is_some());
+        Some(Self { path, channel, previous: None })
+    }
+
+    /// Power since the previous call (`None` on the first), the power limit
+    /// and the temperature.
+    pub fn sample(&mut self) -> HwmonReading {
+        let mut reading = HwmonReading {
+            temperature: self.temperature(),
+            ..HwmonReading::default()
+        };
+        let Some(channel) = self.channel else {
+            return reading;
+        };
+        reading.power_limit_watts = read_u64(&self.path.join(format!("power{}_max", channel)))
+            .filter(|&uw| uw > 0)
+            .map(|uw| uw as f64 / 1_000_000.0);
+
+        let now = Instant::now();
+        let Some(energy) = read_u64(&self.path.join(format!("energy{}_input", channel))) else {
+            return reading;
+        };
+        if let Some((then, before)) = self.previous.replace((now, energy)) {
+            let seconds = now.duration_since(then).as_secs_f64();
+            // The counter is 64 bits wide in sysfs; a drop means a reset
+            if seconds > 0.0 && energy >= before {
+                reading.power_watts = Some((energy - before) as f64 / 1_000_000.0 / seconds);
+            }
+        }
+        reading
+    }
+
+    /// First temperature channel the driver reports (xe: package, then VRAM).
+    fn temperature(&self) -> Option<f64> {
+        (1..=3)
+            .find_map(|channel| read_u64(&self.path.join(format!("temp{}_input", channel))))
+            .map(|millidegrees| millidegrees as f64 / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(driver: &str, files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let hwmon = dir.path().join("hwmon/hwmon5");
+        fs::create_dir_all(&hwmon).unwrap();
+        fs::write(hwmon.join("name"), format!("{}\n", driver)).unwrap();
+        for (name, value) in files {
+            fs::write(hwmon.join(name), value).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_xe_card() {
+        // Package channel only (Alchemist on xe), with temperatures
+        let dir = device("xe", &[
+            ("energy2_input", "5000000\n"),
+            ("power2_max", "150000000\n"),
+            ("temp2_input", "61000\n"),
+        ]);
+        let mut card = CardHwmon::open(dir.path()).unwrap();
+        assert_eq!(card.channel, Some(2));
+
+        let first = card.sample();
+        assert_eq!(first.power_watts, None);
+        assert_eq!(first.power_limit_watts, Some(150.0));
+        assert_eq!(first.temperature, Some(61.0));
+
+        fs::write(dir.path().join("hwmon/hwmon5/energy2_input"), "9000000\n").unwrap();
+        assert!(card.sample().power_watts.is_some_and(|watts| watts > 0.0));
+
+        // A counter reset is not reported as power
+        fs::write(dir.path().join("hwmon/hwmon5/energy2_input"), "10\n").unwrap();
+        assert_eq!(card.sample().power_watts, None);
+    }
+
+    #[test]
+    fn test_i915_card() {
+        // The card channel wins; i915 has no temperatures
+        let dir = device("i915", &[("energy1_input", "1\n"), ("energy2_input", "1\n"), ("power1_max", "0\n")]);
+        let mut card = CardHwmon::open(dir.path()).unwrap();
+        assert_eq!(card.channel, Some(1));
+        assert_eq!(card.sample(), HwmonReading::default());
+
+        // Other hwmon devices and integrated GPUs
+        assert!(CardHwmon::open(device("amdgpu", &[]).path()).is_none());
+        assert!(CardHwmon::open(tempfile::tempdir().unwrap().path()).is_none());
+    }
+}
@@ -1,7 +1,10 @@
 //! Intel GPU monitoring using sysfs and DRM interfaces.
 
+use crate::hwmon::CardHwmon;
+use crate::pmu::{self, EngineBusy, EnginePmu};
+use crate::rapl::RaplMeter;
 use waysensor_rs_core::{
-    format, Sensor, SensorConfig, SensorError, WaybarOutput,
+    format, power::EcoPolling, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput,
 };
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -17,6 +20,37 @@ pub struct IntelGpuSensor {
     gt_path: Option<PathBuf>,
     frequency_history: Vec<f64>,
     utilization_history: Vec<f64>,
+    eco: EcoPolling,
+    /// RAPL energy counters, `None` if missing or not readable
+    rapl: Option<RaplMeter>,
+    /// Engine busyness counters, `None` without perf access
+    pmu: Option<EnginePmu>,
+    /// Board power and temperature of a discrete card
+    hwmon: Option<CardHwmon>,
+    discrete: bool,
+}
+
+/// Integrated or discrete (Arc) GPU, for picking a card when a system has
+/// both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuKind {
+    Integrated,
+    Discrete,
+}
+
+impl std::str::FromStr for GpuKind {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "integrated" | "igpu" => Ok(Self::Integrated),
+            "discrete" | "dgpu" => Ok(Self::Discrete),
+            _ => Err(SensorError::config_with_value(
+                "Invalid GPU kind. Valid options: integrated, discrete",
+                s,
+            )),
+        }
+    }
 }
 
 /// Intel GPU metrics from sysfs.
@@ -30,8 +64,22 @@ pub struct IntelGpuMetrics {
     pub min_freq_mhz: Option<u32>,
     /// GPU frequency as percentage of max
     pub frequency_percent: f64,
-    /// Power consumption (if available)
+    /// Busy percentage per engine from the perf PMU
+    pub engines: Vec<EngineBusy>,
+    /// Busy percentage of the busiest engine, as intel_gpu_top reports it
+    pub utilization_percent: Option<f64>,
+    /// GPU power in Watts (RAPL `uncore` domain, if available)
     pub power_watts: Option<f64>,
+    /// Package power in Watts, CPU included (if available)
+    pub package_power_watts: Option<f64>,
+    /// Long-term package power limit (PL1) in Watts
+    pub package_power_limit_watts: Option<f64>,
+    /// Sustained power limit of a discrete card in Watts
+    pub power_limit_watts: Option<f64>,
+    /// GPU temperature in °C (discrete cards on xe)
+    pub temperature: Option<f64>,
+    /// A discrete card rather than the integrated GPU
+    pub discrete: bool,
     /// GPU name/model
     pub name: String,
     /// Driver name
@@ -75,18 +123,69 @@ impl IntelGpuSensor {
 
         // Find Intel GPU card
         let card_path = Self::find_intel_gpu_card()?;
-        let gt_path = Self::find_gt_path(&card_path);
 
-        Ok(Self {
+        let mut sensor = Self {
             name: "intel-gpu".to_owned(),
             config: SensorConfig::default(),
             warning_threshold: f64::from(warning_threshold),
             critical_threshold: f64::from(critical_threshold),
-            card_path,
-            gt_path,
+            card_path: PathBuf::new(),
+            gt_path: None,
             frequency_history: Vec::new(),
             utilization_history: Vec::new(),
-        })
+            eco: EcoPolling::new(EcoPolling::DEFAULT_INTERVAL),
+            rapl: None,
+            pmu: None,
+            hwmon: None,
+            discrete: false,
+        };
+        sensor.open_card(card_path);
+        Ok(sensor)
+    }
+
+    /// Monitor the given card number and/or kind of GPU instead of the
+    /// first one found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no Intel GPU matches.
+    pub fn select_device(mut self, card: Option<u32>, kind: Option<GpuKind>) -> Result<Self, SensorError> {
+        if card.is_none() && kind.is_none() {
+            return Ok(self);
+        }
+        let found = Self::list_intel_gpu_cards()?.into_iter().find(|path| {
+            card.is_none_or(|card| path.file_name().is_some_and(|name| *name == *format!("card{}", card)))
+                && kind.is_none_or(|kind| Self::is_discrete(path) == (kind == GpuKind::Discrete))
+        });
+        let Some(card_path) = found else {
+            let wanted = match (card, kind) {
+                (Some(card), Some(kind)) => format!("{:?} card{}", kind, card),
+                (Some(card), None) => format!("card{}", card),
+                (None, Some(kind)) => format!("{:?}", kind),
+                (None, None) => unreachable!(),
+            };
+            return Err(SensorError::unavailable(format!("No Intel GPU found ({})", wanted)));
+        };
+        self.open_card(card_path);
+        Ok(self)
+    }
+
+    /// Point the sensor at a card and open its counters. RAPL measures the
+    /// CPU package, so it only applies to the integrated GPU.
+    fn open_card(&mut self, card_path: PathBuf) {
+        self.discrete = Self::is_discrete(&card_path);
+        self.gt_path = Self::find_gt_path(&card_path);
+        self.pmu = pmu::pci_slot(&card_path)
+            .and_then(|slot| EnginePmu::open(&Self::read_driver_name(&card_path), &slot).ok());
+        self.hwmon = CardHwmon::open(&card_path.join("device"));
+        self.rapl = if self.discrete { None } else { RaplMeter::open() };
+        self.card_path = card_path;
+    }
+
+    /// Integrated GPUs always sit at PCI address 00:02.0 on the first bus;
+    /// discrete cards are behind a bridge.
+    fn is_discrete(card_path: &Path) -> bool {
+        pmu::pci_slot(card_path).is_some_and(|slot| !slot.ends_with(":00:02.0"))
     }
 
     /// Create a new Intel GPU sensor with default thresholds (80% warning, 95% critical).
@@ -96,6 +195,14 @@ impl IntelGpuSensor {
 
     /// Find Intel GPU card in /sys/class/drm/.
     fn find_intel_gpu_card() -> Result<PathBuf, SensorError> {
+        Self::list_intel_gpu_cards()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| SensorError::unavailable("No Intel GPU found"))
+    }
+
+    /// List all Intel GPU cards in /sys/class/drm/, sorted by card.
+    fn list_intel_gpu_cards() -> Result<Vec<PathBuf>, SensorError> {
         let drm_path = Path::new("/sys/class/drm");
         
         if !drm_path.exists() {
@@ -108,6 +215,7 @@ impl IntelGpuSensor {
         let entries = fs::read_dir(drm_path)
             .map_err(|e| SensorError::Io(e))?;
 
+        let mut cards = Vec::new();
         for entry in entries {
             let entry = entry.map_err(|e| SensorError::Io(e))?;
             let path = entry.path();
@@ -117,31 +225,33 @@ impl IntelGpuSensor {
                     // Check if this is an Intel GPU
                     if let Ok(driver) = fs::read_to_string(path.join("device/driver/module/srcversion")) {
                         if driver.contains("i915") || driver.contains("xe") {
-                            return Ok(path);
+                            cards.push(path);
+                            continue;
                         }
                     }
                     
                     // Alternative: check uevent file
                     if let Ok(uevent) = fs::read_to_string(path.join("device/uevent")) {
                         if uevent.contains("PCI_ID=8086:") {  // Intel PCI vendor ID
-                            return Ok(path);
+                            cards.push(path);
                         }
                     }
                 }
             }
         }
 
-        Err(SensorError::unavailable("No Intel GPU found"))
+        cards.sort();
+        Ok(cards)
     }
 
     /// Find GT (Graphics Technology) path for frequency monitoring.
     fn find_gt_path(card_path: &Path) -> Option<PathBuf> {
-        // Try common GT paths
-        let gt_candidates = ["gt", "gt0", "gt/gt0"];
+        // Try common GT paths: i915 first, then the xe layout
+        let gt_candidates = ["gt/gt0", "gt0", "gt", "device/tile0/gt0/freq0"];
         
         for candidate in &gt_candidates {
             let gt_path = card_path.join(candidate);
-            if gt_path.exists() {
+            if Self::freq_path(&gt_path, "cur").exists() {
                 return Some(gt_path);
             }
         }
@@ -149,6 +259,17 @@ impl IntelGpuSensor {
         None
     }
 
+    /// Frequency file for `which` (`cur`, `max` or `min`) in a GT directory:
+    /// i915 calls it `rps_cur_freq_mhz`, xe `cur_freq` in its `freq0`
+    /// directory.
+    fn freq_path(gt_path: &Path, which: &str) -> PathBuf {
+        if gt_path.ends_with("freq0") {
+            gt_path.join(format!("{}_freq", which))
+        } else {
+            gt_path.join(format!("rps_{}_freq_mhz", which))
+        }
+    }
+
     /// Read frequency from sysfs file.
     fn read_frequency_mhz(path: &Path) -> Result<u32, SensorError> {
         let content = fs::read_to_string(path)
@@ -203,9 +324,9 @@ impl IntelGpuSensor {
         let (current_freq_mhz, max_freq_mhz, min_freq_mhz, frequency_percent) = 
             if let Some(ref gt_path) = self.gt_path {
                 // Try to read frequencies from GT path
-                let current_freq = Self::read_frequency_mhz(&gt_path.join("rps_cur_freq_mhz")).ok();
-                let max_freq = Self::read_frequency_mhz(&gt_path.join("rps_max_freq_mhz")).ok();
-                let min_freq = Self::read_frequency_mhz(&gt_path.join("rps_min_freq_mhz")).ok();
+                let current_freq = Self::read_frequency_mhz(&Self::freq_path(gt_path, "cur")).ok();
+                let max_freq = Self::read_frequency_mhz(&Self::freq_path(gt_path, "max")).ok();
+                let min_freq = Self::read_frequency_mhz(&Self::freq_path(gt_path, "min")).ok();
 
                 let frequency_percent = if let (Some(current), Some(max), Some(min)) = (current_freq, max_freq, min_freq) {
                     if max > min {
@@ -222,15 +343,20 @@ impl IntelGpuSensor {
                 (None, None, None, 0.0)
             };
 
-        // Power consumption is harder to get on Intel - would need PMT or other interfaces
-        let power_watts = None;
-
+        // Power and engine busyness are sampled in read()
         Ok(IntelGpuMetrics {
             current_freq_mhz,
             max_freq_mhz,
             min_freq_mhz,
             frequency_percent,
-            power_watts,
+            engines: Vec::new(),
+            utilization_percent: None,
+            power_watts: None,
+            package_power_watts: None,
+            package_power_limit_watts: None,
+            power_limit_watts: None,
+            temperature: None,
+            discrete: self.discrete,
             name,
             driver,
         })
@@ -246,8 +372,8 @@ impl IntelGpuSensor {
             self.frequency_history.remove(0);
         }
 
-        // For now, use frequency as utilization proxy
-        self.utilization_history.push(metrics.frequency_percent);
+        // Frequency stands in for utilization without the PMU
+        self.utilization_history.push(metrics.usage_percent());
         if self.utilization_history.len() > max_len {
             self.utilization_history.remove(0);
         }
@@ -262,6 +388,15 @@ impl IntelGpuSensor {
         // Basic GPU info
         lines.push(format::key_value("GPU", &metrics.name, &self.config));
         lines.push(format::key_value("Driver", &metrics.driver, &self.config));
+        lines.push(format::key_value(
+            "Type",
+            if metrics.discrete { "Discrete" } else { "Integrated" },
+            &self.config,
+        ));
+
+        if let Some(temperature) = metrics.temperature {
+            lines.push(format::key_value("Temperature", &format!("{:.0}°C", temperature), &self.config));
+        }
 
         // Frequency information
         if let Some(current_freq) = metrics.current_freq_mhz {
@@ -297,16 +432,40 @@ impl IntelGpuSensor {
             &self.config,
         ));
 
-        // Optional power information with gauge
-        if let Some(power) = metrics.power_watts {
-            let power_percentage = ((power / 150.0) * 100.0).min(100.0); // Assume 150W max for Intel GPU
-            let power_gauge = Self::create_gauge(power_percentage, 12);
-            let power_indicator = Self::get_usage_indicator(power_percentage);
+        // Engine busyness from the PMU
+        if let Some(utilization) = metrics.utilization_percent {
             lines.push(format::key_value(
-                "Power",
-                &format!("{} {:.1}W {}", power_gauge, power, power_indicator),
+                "GPU Usage",
+                &format!("{} {:.1}% {}", Self::create_gauge(utilization, 12), utilization, Self::get_usage_indicator(utilization)),
                 &self.config,
             ));
+            for engine in &metrics.engines {
+                lines.push(format::key_value(
+                    &format!("  {}", engine.label),
+                    &format!("{} {:.1}%", Self::create_gauge(engine.percent, 12), engine.percent),
+                    &self.config,
+                ));
+            }
+        }
+
+        // Optional power information with gauges, scaled to the package
+        // power limit
+        let power_max = metrics.power_max();
+        for (label, power) in [("GPU Power", metrics.power_watts), ("Package Power", metrics.package_power_watts)] {
+            if let Some(power) = power {
+                let power_percentage = ((power / power_max) * 100.0).min(100.0);
+                let power_gauge = Self::create_gauge(power_percentage, 12);
+                let power_indicator = Self::get_usage_indicator(power_percentage);
+                lines.push(format::key_value(
+                    label,
+                    &format!("{} {:.1}W {}", power_gauge, power, power_indicator),
+                    &self.config,
+                ));
+            }
+        }
+
+        if let Some(limit) = metrics.power_limit_watts.or(metrics.package_power_limit_watts) {
+            lines.push(format::key_value("Power Limit", &format!("{:.0}W", limit), &self.config));
         }
 
         // Add sparklines if enabled and we have history
@@ -328,11 +487,80 @@ impl IntelGpuSensor {
     }
 }
 
+impl IntelGpuMetrics {
+    /// Utilization shown in the text: the busiest engine's if the PMU is
+    /// readable, the frequency as a proxy otherwise.
+    pub fn usage_percent(&self) -> f64 {
+        self.utilization_percent.unwrap_or(self.frequency_percent)
+    }
+
+    /// Power shown in the text: the GPU's own if RAPL reports it, the
+    /// package's otherwise.
+    pub fn shown_power(&self) -> Option<f64> {
+        self.power_watts.or(self.package_power_watts)
+    }
+
+    /// Power the gauges and power thresholds are scaled to: the card's or
+    /// the package's power limit, or 150W where neither is known.
+    pub fn power_max(&self) -> f64 {
+        self.power_limit_watts.or(self.package_power_limit_watts).unwrap_or(150.0)
+    }
+}
+
+impl Discoverable for IntelGpuSensor {
+    const KIND: &'static str = "Intel GPUs";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        if !Path::new("/sys/class/drm").exists() {
+            return Ok(Vec::new());
+        }
+        Ok(Self::list_intel_gpu_cards()?
+            .into_iter()
+            .map(|card_path| {
+                let card = card_path.file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let kind = if Self::is_discrete(&card_path) { "discrete" } else { "integrated" };
+                let mut item = DiscoveredItem::new(card, Self::read_gpu_name(&card_path))
+                    .with_detail("driver", Self::read_driver_name(&card_path))
+                    .with_detail("type", kind);
+                if let Some(gt_path) = Self::find_gt_path(&card_path) {
+                    if let Ok(max) = Self::read_frequency_mhz(&Self::freq_path(&gt_path, "max")) {
+                        item = item.with_detail("max frequency", format!("{} MHz", max));
+                    }
+                }
+                item
+            })
+            .collect())
+    }
+}
+
 impl Sensor for IntelGpuSensor {
     type Error = SensorError;
 
     fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
-        let metrics = self.query_gpu_metrics()?;
+        // On battery: poll less often and leave a runtime-suspended GPU asleep
+        let device = self.card_path.join("device");
+        if let Some(output) = self.eco.before_read(Some(&device), &self.config) {
+            return Ok(output);
+        }
+        
+        let mut metrics = self.query_gpu_metrics()?;
+        if let Some(power) = self.rapl.as_mut().and_then(RaplMeter::sample) {
+            metrics.power_watts = power.gpu_watts;
+            metrics.package_power_watts = Some(power.package_watts);
+            metrics.package_power_limit_watts = power.package_limit_watts;
+        }
+        if let Some(hwmon) = self.hwmon.as_mut() {
+            let reading = hwmon.sample();
+            metrics.power_watts = reading.power_watts;
+            metrics.power_limit_watts = reading.power_limit_watts;
+            metrics.temperature = reading.temperature;
+        }
+        if let Some(pmu) = self.pmu.as_mut() {
+            metrics.engines = pmu.sample();
+            metrics.utilization_percent = metrics.engines.iter().map(|engine| engine.percent).reduce(f64::max);
+        }
         
         // Update history for sparklines
         self.update_history(&metrics);
@@ -342,22 +570,40 @@ impl Sensor for IntelGpuSensor {
         let mut text_parts = Vec::new();
 
         // Add sparkline if enabled and we have history and should show in text
-        if self.config.visuals.sparklines && self.config.visuals.sparklines_in_text && self.frequency_history.len() > 1 {
-            let sparkline = format::create_sparkline(&self.frequency_history, self.config.visuals.sparkline_style);
+        if self.config.visuals.sparklines && self.config.visuals.sparklines_in_text && self.utilization_history.len() > 1 {
+            let sparkline = format::create_sparkline(&self.utilization_history, self.config.visuals.sparkline_style);
             if !sparkline.is_empty() {
                 let colored_sparkline = format::colored_sparkline(&sparkline, self.config.sparkline_color.as_deref());
                 text_parts.push(colored_sparkline);
             }
         }
 
-        // Add main frequency percentage (as utilization proxy)
-        text_parts.push(format!("{:3.0}%", metrics.frequency_percent));
+        // "power" shows watts first and rates them against the power limit,
+        // like the AMD sensor's power format
+        let power_format = self.config.get_custom("display_format")
+            .and_then(|v| v.as_str())
+            .is_some_and(|format| format == "power");
+        let show_power = self.config.get_custom("show_power")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+        let power = metrics.shown_power();
+
+        match power.filter(|_| power_format) {
+            Some(watts) => text_parts.push(format!("{:.1}W", watts)),
+            None => {
+                // Add main utilization percentage (frequency without the PMU)
+                text_parts.push(format!("{:3.0}%", metrics.usage_percent()));
+                if let Some(watts) = power.filter(|_| show_power) {
+                    text_parts.push(format!("{:.1}W", watts));
+                }
+            }
+        }
 
 
-        // Add status indicator if enabled (based on frequency usage)
+        // Add status indicator if enabled (based on utilization)
         if self.config.visuals.status_indicators {
             let status = format::status_indicator(
-                metrics.frequency_percent,
+                metrics.usage_percent(),
                 self.warning_threshold,
                 self.critical_threshold,
                 self.config.visuals.status_indicators,
@@ -371,17 +617,32 @@ impl Sensor for IntelGpuSensor {
         let text = format::with_icon_and_colors(&combined_text, icon, &self.config);
 
         let tooltip = self.create_tooltip(&metrics);
-        let percentage = metrics.frequency_percent.round().clamp(0.0, 100.0) as u8;
-
-        Ok(format::themed_output(
-            text,
-            Some(tooltip),
-            Some(percentage),
-            metrics.frequency_percent,
-            self.warning_threshold,
-            self.critical_threshold,
-            &self.config.theme,
-        ))
+        let percentage = metrics.usage_percent().round().clamp(0.0, 100.0) as u8;
+
+        let output = match power.filter(|_| power_format) {
+            Some(watts) => {
+                let power_max = metrics.power_max();
+                format::themed_output(
+                    text,
+                    Some(tooltip),
+                    Some(((watts / power_max) * 100.0).round().clamp(0.0, 100.0) as u8),
+                    watts,
+                    power_max * 2.0 / 3.0,
+                    power_max * 5.0 / 6.0,
+                    &self.config.theme,
+                )
+            }
+            None => format::themed_output(
+                text,
+                Some(tooltip),
+                Some(percentage),
+                metrics.usage_percent(),
+                self.warning_threshold,
+                self.critical_threshold,
+                &self.config.theme,
+            ),
+        };
+        Ok(self.eco.after_read(output, &self.config))
     }
 
     fn name(&self) -> &str {
@@ -389,6 +650,7 @@ impl Sensor for IntelGpuSensor {
     }
 
     fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.eco = EcoPolling::from_config(&config);
         self.config = config;
         Ok(())
     }
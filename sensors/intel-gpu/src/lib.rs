@@ -1,8 +1,13 @@
 //! Intel GPU monitoring sensor for waysensor-rs.
 //!
 //! This module provides Intel GPU monitoring by reading from Linux sysfs
-//! and DRM interfaces to extract GPU frequency, power, and utilization metrics.
+//! and DRM interfaces to extract GPU frequency, engine busyness from the
+//! driver's perf PMU, and power from the RAPL energy counters. Discrete Arc
+//! cards on i915 or xe report board power and temperature through hwmon.
 
+pub mod hwmon;
 pub mod intel_gpu;
+pub mod pmu;
+pub mod rapl;
 
-pub use intel_gpu::IntelGpuSensor;
\ No newline at end of file
+pub use intel_gpu::{GpuKind, IntelGpuSensor};
\ No newline at end of file
@@ -1,12 +1,11 @@
 //! waysensor-rs-intel-gpu: Intel GPU monitoring binary for Waybar.
 
 use clap::Parser;
-use waysensor_rs_core::{GlobalConfig, IconStyle, Sensor, SensorConfig};
-use waysensor_rs_intel_gpu::IntelGpuSensor;
+use waysensor_rs_core::{discovery, schedule, session_lock, GlobalConfig, IconStyle, Sensor, SensorConfig};
+use waysensor_rs_intel_gpu::{GpuKind, IntelGpuSensor};
 use std::io::{self, Write};
 use std::process;
 use std::time::Duration;
-use tokio::time;
 
 /// Command-line arguments for the Intel GPU sensor.
 #[derive(Parser)]
@@ -27,10 +26,30 @@ struct Args {
     #[arg(short, long, default_value = "95", value_parser = validate_percentage)]
     critical: u8,
 
+    /// Monitor this card, e.g. 1 for /sys/class/drm/card1 (see --list)
+    #[arg(long)]
+    card: Option<u32>,
+
+    /// Monitor the integrated or the discrete GPU (integrated, discrete)
+    #[arg(long)]
+    kind: Option<GpuKind>,
+
     /// One-shot mode (output once and exit)
     #[arg(short, long)]
     once: bool,
 
+    /// List available Intel GPUs and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
     /// Icon style (nerdfont, fontawesome, ascii, none)
     #[arg(long)]
     icon_style: Option<IconStyle>,
@@ -92,6 +111,19 @@ fn validate_percentage(s: &str) -> Result<u8, String> {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<IntelGpuSensor>(args.json) {
+            eprintln!("Error listing Intel GPUs: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
     // Handle config generation
     if args.generate_config {
         if let Some(config_path) = GlobalConfig::default_config_path() {
@@ -113,7 +145,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     
     // Create the Intel GPU sensor
-    let mut gpu_sensor = match IntelGpuSensor::new(args.warning, args.critical) {
+    let sensor = IntelGpuSensor::new(args.warning, args.critical)
+        .and_then(|sensor| sensor.select_device(args.card, args.kind));
+    let mut gpu_sensor = match sensor {
         Ok(sensor) => sensor,
         Err(e) => {
             eprintln!("Failed to create Intel GPU sensor: {}", e);
@@ -137,7 +171,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Load global configuration and apply command line overrides
     let global_config = GlobalConfig::load().unwrap_or_default();
-    let mut config = global_config.to_sensor_config()
+    let mut config = global_config.to_sensor_config_for("intel-gpu")
         .with_update_interval(Duration::from_millis(args.interval))
         .apply_color_overrides(
             args.icon_color,
@@ -155,7 +189,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     if args.once {
         // One-shot mode: read once and exit
-        match gpu_sensor.read() {
+        match gpu_sensor.read_styled() {
             Ok(output) => {
                 println!("{}", serde_json::to_string(&output)?);
             }
@@ -166,12 +200,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     } else {
         // Continuous mode: loop and output readings
-        let mut interval = time::interval(Duration::from_millis(args.interval));
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
         
         loop {
             interval.tick().await;
+            if session_lock::wait_async(gpu_sensor.config()).await {
+                interval.reset();
+            }
             
-            match gpu_sensor.read() {
+            match gpu_sensor.read_styled() {
                 Ok(output) => {
                     println!("{}", serde_json::to_string(&output)?);
                     io::stdout().flush()?;
@@ -0,0 +1,295 @@
+//! Engine busyness from the i915 and xe perf PMUs.
+//!
+//! The GPU drivers register a perf PMU under `/sys/bus/event_source/devices`
+//! whose counters tell how long each engine (render, video, blitter, ...)
+//! was busy; intel_gpu_top reads the same counters. i915 counts busy
+//! nanoseconds per engine (`rcs0-busy`); xe counts active and total GPU
+//! ticks per engine class and instance (`engine-active-ticks` with the
+//! engine selected through `format/` fields). Opening the counters needs
+//! `CAP_PERFMON` or `kernel.perf_event_paranoid` <= 0; without it the
+//! sensor keeps using frequency as its utilization proxy.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::os::fd::{FromRawFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const EVENT_SOURCES: &str = "/sys/bus/event_source/devices";
+
+/// `struct perf_event_attr` up to `config1` (`PERF_ATTR_SIZE_VER0`).
+#[repr(C)]
+#[derive(Default)]
+struct PerfEventAttr {
+    kind: u32,
+    size: u32,
+    config: u64,
+    sample_period: u64,
+    sample_type: u64,
+    read_format: u64,
+    flags: u64,
+    wakeup_events: u32,
+    bp_type: u32,
+    config1: u64,
+}
+
+/// Open a counting perf event of PMU `kind` on `cpu`.
+fn open_counter(kind: u32, config: u64, cpu: i32) -> io::Result<File> {
+    let attr = PerfEventAttr {
+        kind,
+        size: std::mem::size_of::<PerfEventAttr>() as u32,
+        config,
+        ..PerfEventAttr::default()
+    };
+    // SAFETY: `attr` is a valid perf_event_attr of the size it declares;
+    // pid -1 with a cpu counts system-wide, without a group or flags.
+    let fd = unsafe { libc::syscall(libc::SYS_perf_event_open, &attr as *const PerfEventAttr, -1, cpu, -1, 0) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: perf_event_open returned a new file descriptor we now own.
+    Ok(unsafe { File::from_raw_fd(fd as RawFd) })
+}
+
+fn read_counter(mut file: &File) -> io::Result<u64> {
+    let mut value = [0u8; 8];
+    file.read_exact(&mut value)?;
+    Ok(u64::from_ne_bytes(value))
+}
+
+/// Encode an event description such as `event=0x02,engine_class=1` into a
+/// `config` value using the PMU's `format/` fields (`config:12-19`).
+fn encode_event(pmu: &Path, event: &str) -> Option<u64> {
+    let mut config = 0u64;
+    for term in event.trim().split(',') {
+        let (field, value) = term.split_once('=')?;
+        let value = match value.trim().strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+            None => value.trim().parse().ok()?,
+        };
+        if field == "config" {
+            config |= value;
+            continue;
+        }
+        let format = fs::read_to_string(pmu.join("format").join(field)).ok()?;
+        let low: u32 = format.trim().strip_prefix("config:")?.split('-').next()?.parse().ok()?;
+        config |= value << low;
+    }
+    Some(config)
+}
+
+/// How an engine's counters turn into a busy percentage.
+#[derive(Debug)]
+enum Counters {
+    /// i915: nanoseconds busy, compared with wall time
+    BusyNs(File),
+    /// xe: ticks active out of ticks elapsed
+    Ticks { active: File, total: File },
+}
+
+/// One GPU engine, e.g. render or the first video engine.
+#[derive(Debug)]
+struct Engine {
+    label: String,
+    counters: Counters,
+    previous: Option<(u64, u64)>,
+}
+
+/// Busy percentage of one engine over the last interval.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EngineBusy {
+    /// intel_gpu_top's name, e.g. `Render/3D` or `Video/1`
+    pub label: String,
+    pub percent: f64,
+}
+
+/// intel_gpu_top name of an engine class.
+fn class_label(class: &str) -> Option<&'static str> {
+    match class {
+        "rcs" | "render" => Some("Render/3D"),
+        "bcs" | "copy" => Some("Blitter"),
+        "vcs" | "video" => Some("Video"),
+        "vecs" | "video-enhance" => Some("VideoEnhance"),
+        "ccs" | "compute" => Some("Compute"),
+        _ => None,
+    }
+}
+
+fn engine_label(class: &str, instance: u32) -> Option<String> {
+    let label = class_label(class)?;
+    Some(if instance == 0 { label.to_string() } else { format!("{}/{}", label, instance) })
+}
+
+/// The engine counters of one GPU.
+#[derive(Debug)]
+pub struct EnginePmu {
+    engines: Vec<Engine>,
+    previous_time: Option<Instant>,
+}
+
+impl EnginePmu {
+    /// Open the engine counters of the GPU in PCI slot `slot`
+    /// (`0000:00:02.0`) driven by `driver` (`i915` or `xe`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the driver registers no PMU for the GPU or the
+    /// counters cannot be opened, typically for lack of permission.
+    pub fn open(driver: &str, slot: &str) -> io::Result<Self> {
+        // Integrated i915 GPUs register a plain "i915" PMU, everything else
+        // is named after its PCI slot
+        let named = Path::new(EVENT_SOURCES).join(format!("{}_{}", driver, slot.replace(':', "_")));
+        let pmu = if named.exists() { named } else { Path::new(EVENT_SOURCES).join(driver) };
+        let read = |name: &str| fs::read_to_string(pmu.join(name));
+        let kind: u32 = read("type")?.trim().parse().map_err(|_| io::ErrorKind::InvalidData)?;
+        // Uncore PMUs count on one designated CPU
+        let cpu: i32 = read("cpumask").ok()
+            .and_then(|mask| mask.trim().split([',', '-']).next()?.parse().ok())
+            .unwrap_or(0);
+
+        let engines = if driver == "xe" {
+            Self::xe_engines(&pmu, kind, cpu)?
+        } else {
+            Self::i915_engines(&pmu, kind, cpu)?
+        };
+        if engines.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no engine busyness events"));
+        }
+        Ok(Self { engines, previous_time: None })
+    }
+
+    /// i915 publishes one `<class><instance>-busy` event per engine.
+    fn i915_engines(pmu: &Path, kind: u32, cpu: i32) -> io::Result<Vec<Engine>> {
+        let mut engines = Vec::new();
+        for entry in fs::read_dir(pmu.join("events"))?.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let Some(engine) = name.strip_suffix("-busy") else { continue };
+            let split = engine.find(|c: char| c.is_ascii_digit()).unwrap_or(engine.len());
+            let (class, instance) = engine.split_at(split);
+            let Some(label) = instance.parse().ok().and_then(|instance| engine_label(class, instance)) else {
+                continue;
+            };
+            let Some(config) = fs::read_to_string(entry.path()).ok().and_then(|event| encode_event(pmu, &event)) else {
+                continue;
+            };
+            engines.push(Engine { label, counters: Counters::BusyNs(open_counter(kind, config, cpu)?), previous: None });
+        }
+        engines.sort_by(|a, b| a.label.cmp(&b.label));
+        Ok(engines)
+    }
+
+    /// xe publishes `engine-active-ticks` and `engine-total-ticks`, with the
+    /// engine given as class and instance on GT 0.
+    fn xe_engines(pmu: &Path, kind: u32, cpu: i32) -> io::Result<Vec<Engine>> {
+        let event = |name: &str| fs::read_to_string(pmu.join("events").join(name));
+        let (active_event, total_event) = (event("engine-active-ticks")?, event("engine-total-ticks")?);
+        const CLASSES: [(&str, u32); 5] = [("render", 0), ("copy", 1), ("video", 2), ("video-enhance", 3), ("compute", 4)];
+        let mut engines = Vec::new();
+        for (class, class_id) in CLASSES {
+            // Engines that do not exist fail to open; stop at the first gap
+            for instance in 0..8 {
+                let select = format!(",gt=0,engine_class={},engine_instance={}", class_id, instance);
+                let config = |event: &str| encode_event(pmu, &format!("{}{}", event.trim(), select));
+                let (Some(active), Some(total)) = (config(&active_event), config(&total_event)) else {
+                    break;
+                };
+                let (Ok(active), Ok(total)) = (open_counter(kind, active, cpu), open_counter(kind, total, cpu)) else {
+                    break;
+                };
+                let Some(label) = engine_label(class, instance) else { break };
+                engines.push(Engine { label, counters: Counters::Ticks { active, total }, previous: None });
+            }
+        }
+        Ok(engines)
+    }
+
+    /// Busy percentage of every engine since the previous call; empty on
+    /// the first call.
+    pub fn sample(&mut self) -> Vec<EngineBusy> {
+        let now = Instant::now();
+        let elapsed_ns = self.previous_time.replace(now).map(|then| now.duration_since(then).as_nanos() as u64);
+
+        let mut busy = Vec::new();
+        for engine in &mut self.engines {
+            // (busy counter, total counter); i915 measures against wall time
+            let current = match &engine.counters {
+                Counters::BusyNs(file) => read_counter(file).ok().map(|busy| (busy, 0)),
+                Counters::Ticks { active, total } => read_counter(active).ok().zip(read_counter(total).ok()),
+            };
+            let Some(current) = current else { continue };
+            let previous = engine.previous.replace(current);
+            let (Some(elapsed_ns), Some((before, total_before))) = (elapsed_ns, previous) else { continue };
+            let (busy_delta, total_delta) = match engine.counters {
+                Counters::BusyNs(_) => (current.0.saturating_sub(before), elapsed_ns),
+                Counters::Ticks { .. } => (current.0.saturating_sub(before), current.1.saturating_sub(total_before)),
+            };
+            if total_delta > 0 {
+                busy.push(EngineBusy {
+                    label: engine.label.clone(),
+                    percent: (busy_delta as f64 / total_delta as f64 * 100.0).min(100.0),
+                });
+            }
+        }
+        busy
+    }
+}
+
+/// PCI slot (`0000:00:02.0`) of a DRM card directory.
+pub fn pci_slot(card_path: &Path) -> Option<String> {
+    let device: PathBuf = fs::canonicalize(card_path.join("device")).ok()?;
+    Some(device.file_name()?.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_event() {
+        let pmu = tempfile::tempdir().unwrap();
+        let format = pmu.path().join("format");
+        fs::create_dir(&format).unwrap();
+        // xe's layout
+        fs::write(format.join("event"), "config:0-11\n").unwrap();
+        fs::write(format.join("gt"), "config:60-63\n").unwrap();
+        fs::write(format.join("engine_class"), "config:20-27\n").unwrap();
+        fs::write(format.join("engine_instance"), "config:12-19\n").unwrap();
+
+        assert_eq!(encode_event(pmu.path(), "event=0x02"), Some(0x02));
+        assert_eq!(
+            encode_event(pmu.path(), "event=0x02,gt=0,engine_class=2,engine_instance=1\n"),
+            Some(0x02 | (2 << 20) | (1 << 12))
+        );
+        assert_eq!(encode_event(pmu.path(), "event=0x03,gt=1"), Some(0x03 | (1 << 60)));
+        // i915 events are raw configs
+        assert_eq!(encode_event(pmu.path(), "config=0x100000"), Some(0x100000));
+
+        // Unknown fields and malformed terms
+        assert_eq!(encode_event(pmu.path(), "event=0x02,umask=1"), None);
+        assert_eq!(encode_event(pmu.path(), "event"), None);
+        assert_eq!(encode_event(pmu.path(), "event=0xzz"), None);
+    }
+
+    #[test]
+    fn test_engine_label() {
+        assert_eq!(engine_label("rcs", 0).as_deref(), Some("Render/3D"));
+        assert_eq!(engine_label("vcs", 1).as_deref(), Some("Video/1"));
+        assert_eq!(engine_label("video-enhance", 0).as_deref(), Some("VideoEnhance"));
+        assert_eq!(engine_label("compute", 2).as_deref(), Some("Compute/2"));
+        assert_eq!(engine_label("copy", 0).as_deref(), Some("Blitter"));
+        assert_eq!(engine_label("gsc", 0), None);
+    }
+
+    #[test]
+    fn test_pci_slot() {
+        let dir = tempfile::tempdir().unwrap();
+        let slot = dir.path().join("devices/0000:00:02.0");
+        let card = dir.path().join("drm/card0");
+        fs::create_dir_all(&slot).unwrap();
+        fs::create_dir_all(&card).unwrap();
+        std::os::unix::fs::symlink(&slot, card.join("device")).unwrap();
+
+        assert_eq!(pci_slot(&card).as_deref(), Some("0000:00:02.0"));
+        assert_eq!(pci_slot(&dir.path().join("drm/card1")), None);
+    }
+}
@@ -0,0 +1,180 @@
+//! Package and integrated GPU power from RAPL energy counters.
+//!
+//! The powercap framework exposes RAPL domains as cumulative energy
+//! counters in microjoules (`energy_uj`); power is the difference between
+//! two reads divided by the time between them. The package domain
+//! (`intel-rapl:0`) covers the whole SoC, its `uncore` subdomain the
+//! integrated GPU. Since 5.10 the counters are only readable by root unless
+//! the permissions are relaxed (e.g. by a udev rule), so power is simply
+//! absent otherwise.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const PACKAGE_ZONE: &str = "/sys/class/powercap/intel-rapl:0";
+
+/// A RAPL domain.
+#[derive(Debug, Clone)]
+struct Zone {
+    path: PathBuf,
+    /// Value at which `energy_uj` wraps around to zero
+    max_energy_uj: u64,
+}
+
+impl Zone {
+    fn open(path: PathBuf) -> Option<Self> {
+        let max_energy_uj = read_u64(&path.join("max_energy_range_uj"))?;
+        // Only usable if the counter itself is readable
+        read_u64(&path.join("energy_uj"))?;
+        Some(Self { path, max_energy_uj })
+    }
+
+    fn energy_uj(&self) -> Option<u64> {
+        read_u64(&self.path.join("energy_uj"))
+    }
+
+    /// Energy used between two counter values, allowing for one wraparound.
+    fn delta_uj(&self, before: u64, after: u64) -> u64 {
+        if after >= before {
+            after - before
+        } else {
+            self.max_energy_uj - before + after
+        }
+    }
+}
+
+fn read_u64(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Power drawn over the last sampling interval, in watts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaplPower {
+    /// Whole package: CPU cores, GPU and the rest of the SoC
+    pub package_watts: f64,
+    /// Integrated GPU (`uncore` domain), if the CPU reports it separately
+    pub gpu_watts: Option<f64>,
+    /// Long-term package power limit (PL1)
+    pub package_limit_watts: Option<f64>,
+}
+
+/// Turns RAPL energy counters into power.
+#[derive(Debug)]
+pub struct RaplMeter {
+    package: Zone,
+    gpu: Option<Zone>,
+    package_limit_watts: Option<f64>,
+    /// Time and counters of the previous sample
+    previous: Option<(Instant, u64, Option<u64>)>,
+}
+
+impl RaplMeter {
+    /// Open the package domain, `None` if RAPL is missing or unreadable.
+    pub fn open() -> Option<Self> {
+        Self::open_in(Path::new(PACKAGE_ZONE))
+    }
+
+    fn open_in(package_path: &Path) -> Option<Self> {
+        let package = Zone::open(package_path.to_path_buf())?;
+        let package_name = package_path.file_name()?.to_string_lossy().into_owned();
+        // Subdomains are intel-rapl:0:N, identified by their name
+        let gpu = fs::read_dir(package_path).ok()?
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(&format!("{}:", package_name)))
+            .find(|entry| fs::read_to_string(entry.path().join("name")).is_ok_and(|name| name.trim() == "uncore"))
+            .and_then(|entry| Zone::open(entry.path()));
+        let package_limit_watts = read_u64(&package_path.join("constraint_0_power_limit_uw"))
+            .filter(|&uw| uw > 0)
+            .map(|uw| uw as f64 / 1_000_000.0);
+        Some(Self { package, gpu, package_limit_watts, previous: None })
+    }
+
+    /// Power since the previous call; `None` on the first call or if a
+    /// counter could not be read.
+    pub fn sample(&mut self) -> Option<RaplPower> {
+        let now = Instant::now();
+        let package = self.package.energy_uj()?;
+        let gpu = self.gpu.as_ref().and_then(Zone::energy_uj);
+        let previous = self.previous.replace((now, package, gpu));
+
+        let (then, package_before, gpu_before) = previous?;
+        let seconds = now.duration_since(then).as_secs_f64();
+        if seconds <= 0.0 {
+            return None;
+        }
+        let watts = |zone: &Zone, before: u64, after: u64| zone.delta_uj(before, after) as f64 / 1_000_000.0 / seconds;
+        let gpu_watts = match (&self.gpu, gpu_before, gpu) {
+            (Some(zone), Some(before), Some(after)) => Some(watts(zone, before, after)),
+            _ => None,
+        };
+        Some(RaplPower {
+            package_watts: watts(&self.package, package_before, package),
+            gpu_watts,
+            package_limit_watts: self.package_limit_watts,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(path: &Path, name: &str, energy_uj: u64) {
+        fs::create_dir_all(path).unwrap();
+        fs::write(path.join("name"), format!("{}\n", name)).unwrap();
+        fs::write(path.join("max_energy_range_uj"), "262143328850\n").unwrap();
+        fs::write(path.join("energy_uj"), format!("{}\n", energy_uj)).unwrap();
+    }
+
+    #[test]
+    fn test_open_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("intel-rapl:0");
+        zone(&package, "package-0", 1_000_000);
+        zone(&package.join("intel-rapl:0:0"), "core", 500_000);
+        zone(&package.join("intel-rapl:0:1"), "uncore", 200_000);
+        fs::write(package.join("constraint_0_power_limit_uw"), "28000000\n").unwrap();
+
+        let mut meter = RaplMeter::open_in(&package).unwrap();
+        assert_eq!(meter.gpu.as_ref().map(|gpu| gpu.path.clone()), Some(package.join("intel-rapl:0:1")));
+        assert_eq!(meter.package_limit_watts, Some(28.0));
+
+        // The first sample is only a baseline
+        assert_eq!(meter.sample(), None);
+        fs::write(package.join("energy_uj"), "3000000\n").unwrap();
+        fs::write(package.join("intel-rapl:0:1/energy_uj"), "700000\n").unwrap();
+        let power = meter.sample().unwrap();
+        assert!(power.package_watts > 0.0);
+        let gpu_watts = power.gpu_watts.unwrap();
+        assert!((gpu_watts / power.package_watts - 0.25).abs() < 1e-9);
+        assert_eq!(power.package_limit_watts, Some(28.0));
+    }
+
+    #[test]
+    fn test_open_in_without_gpu_domain() {
+        let dir = tempfile::tempdir().unwrap();
+        let package = dir.path().join("intel-rapl:0");
+        zone(&package, "package-0", 1_000_000);
+        zone(&package.join("intel-rapl:0:0"), "core", 500_000);
+        // A limit of zero means none is set
+        fs::write(package.join("constraint_0_power_limit_uw"), "0\n").unwrap();
+
+        let meter = RaplMeter::open_in(&package).unwrap();
+        assert!(meter.gpu.is_none());
+        assert_eq!(meter.package_limit_watts, None);
+
+        // Unreadable or missing counters
+        fs::remove_file(package.join("energy_uj")).unwrap();
+        assert!(RaplMeter::open_in(&package).is_none());
+        assert!(RaplMeter::open_in(&dir.path().join("intel-rapl:1")).is_none());
+    }
+
+    #[test]
+    fn test_delta_uj_wraparound() {
+        let zone = Zone { path: PathBuf::new(), max_energy_uj: 1_000 };
+        assert_eq!(zone.delta_uj(100, 400), 300);
+        assert_eq!(zone.delta_uj(400, 400), 0);
+        assert_eq!(zone.delta_uj(900, 50), 150);
+    }
+}
@@ -0,0 +1,74 @@
+//! Immediate updates on layout switches.
+//!
+//! A layout switch is a keypress away, and showing it a polling interval
+//! late defeats the point of the module. Every backend announces switches
+//! (Sway `input` events, Hyprland `activelayout` events, XKB group state
+//! notifications), so a thread blocks on the subscribed connection and wakes
+//! the update loop for each one. The timer only covers a backend that went
+//! away.
+
+use crate::keyboard::Backend;
+use std::thread;
+use tokio::sync::mpsc;
+use waysensor_rs_core::SensorError;
+
+/// Wakeups for layout switches.
+#[derive(Debug)]
+pub struct LayoutEvents {
+    receiver: mpsc::Receiver<()>,
+}
+
+impl LayoutEvents {
+    /// Start watching `backend` for layout switches.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend refuses the subscription.
+    pub fn spawn(backend: &Backend) -> Result<Self, SensorError> {
+        let mut subscription = backend.subscribe()?;
+        // A single slot: switches arriving while an update is pending are
+        // covered by that update
+        let (sender, receiver) = mpsc::channel(1);
+        thread::Builder::new()
+            .name("layout-events".to_string())
+            .spawn(move || {
+                while subscription.wait().is_ok() {
+                    if sender.try_send(()).is_err() && sender.is_closed() {
+                        return;
+                    }
+                }
+            })
+            .map_err(SensorError::Io)?;
+        Ok(Self { receiver })
+    }
+
+    /// Wait for the next switch. Never returns once the watcher has stopped.
+    pub async fn changed(&mut self) {
+        if self.receiver.recv().await.is_none() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn test_hyprland_layout_events() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let listener = UnixListener::bind(dir.join(".socket2.sock")).unwrap();
+
+        let mut subscription = Backend::Hyprland(dir.to_path_buf()).subscribe().unwrap();
+        let (mut compositor, _) = listener.accept().unwrap();
+        compositor.write_all(b"workspace>>2\nactivelayout>>at-translated-set-2-keyboard,German\n").unwrap();
+        drop(compositor);
+
+        // The workspace event is skipped, the layout switch returns
+        assert!(subscription.wait().is_ok());
+        assert!(subscription.wait().is_err());
+    }
+}
@@ -0,0 +1,446 @@
+//! Active keyboard layout of the compositor or X server.
+//!
+//! There is no common Wayland interface for the layout state of the whole
+//! seat, so each compositor is asked over its own IPC: Sway through
+//! `$SWAYSOCK`, Hyprland through the sockets of
+//! `$HYPRLAND_INSTANCE_SIGNATURE`. Outside of Wayland the X server's XKB
+//! extension reports the active group.
+
+use waysensor_rs_core::ipc;
+use crate::rules::XkbRules;
+use crate::x11::X11Keyboard;
+use serde_json::Value;
+use std::env;
+use std::io::BufReader;
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use waysensor_rs_core::{format, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+/// Where the layout comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Backend {
+    /// Sway's IPC socket
+    Sway(PathBuf),
+    /// Hyprland's instance socket directory
+    Hyprland(PathBuf),
+    /// The XKB extension of `$DISPLAY`
+    X11,
+}
+
+impl Backend {
+    /// The backend of the running session.
+    pub fn detect() -> Option<Self> {
+        if let Some(socket) = ipc::sway_socket() {
+            return Some(Self::Sway(socket));
+        }
+        if let Some(dir) = ipc::hyprland_dir() {
+            return Some(Self::Hyprland(dir));
+        }
+        (env::var_os("DISPLAY").is_some() && env::var_os("WAYLAND_DISPLAY").is_none()).then_some(Self::X11)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sway(_) => "sway",
+            Self::Hyprland(_) => "hyprland",
+            Self::X11 => "x11",
+        }
+    }
+
+    /// Every keyboard and its layouts.
+    pub fn keyboards(&self, rules: &XkbRules) -> Result<Vec<KeyboardState>, SensorError> {
+        match self {
+            Self::Sway(socket) => {
+                let inputs = ipc::sway_request(socket, ipc::SWAY_GET_INPUTS, "").map_err(ipc_error(self))?;
+                parse_sway_inputs(&inputs, rules)
+            }
+            Self::Hyprland(dir) => {
+                let devices = ipc::hyprland_request(dir, "j/devices").map_err(ipc_error(self))?;
+                parse_hyprland_devices(&devices, rules)
+            }
+            Self::X11 => {
+                let groups = X11Keyboard::connect()?.groups()?;
+                let layouts = groups.layouts.iter().zip(&groups.variants)
+                    .map(|(code, variant)| Layout {
+                        name: rules.name(code, Some(variant)).unwrap_or(code).to_string(),
+                        code: Some(code.clone()),
+                    })
+                    .collect();
+                Ok(vec![KeyboardState {
+                    id: "core".to_string(),
+                    name: "Core keyboard".to_string(),
+                    main: true,
+                    layouts,
+                    active: groups.active,
+                }])
+            }
+        }
+    }
+
+    /// Switch `keyboard` to its next layout.
+    pub fn next_layout(&self, keyboard: &KeyboardState) -> Result<(), SensorError> {
+        match self {
+            Self::Sway(socket) => {
+                let command = format!("input \"{}\" xkb_switch_layout next", keyboard.id);
+                let reply = ipc::sway_request(socket, ipc::SWAY_RUN_COMMAND, &command).map_err(ipc_error(self))?;
+                let reply: Value = serde_json::from_str(&reply)
+                    .map_err(|e| SensorError::parse_with_source("Invalid Sway reply", e))?;
+                match reply[0]["success"].as_bool() {
+                    Some(true) => Ok(()),
+                    _ => Err(SensorError::unavailable(format!(
+                        "Sway refused to switch layouts: {}",
+                        reply[0]["error"].as_str().unwrap_or("unknown error")
+                    ))),
+                }
+            }
+            Self::Hyprland(dir) => {
+                let reply = ipc::hyprland_request(dir, &format!("switchxkblayout {} next", keyboard.id))
+                    .map_err(ipc_error(self))?;
+                if reply.trim() == "ok" {
+                    Ok(())
+                } else {
+                    Err(SensorError::unavailable(format!("Hyprland refused to switch layouts: {}", reply.trim())))
+                }
+            }
+            Self::X11 => X11Keyboard::connect()?.lock_group((keyboard.active + 1) % keyboard.layouts.len().max(1)),
+        }
+    }
+
+    /// Start listening for layout switches.
+    pub fn subscribe(&self) -> Result<Subscription, SensorError> {
+        match self {
+            Self::Sway(socket) => ipc::sway_subscribe(socket, r#"["input"]"#)
+                .map(Subscription::Sway)
+                .map_err(ipc_error(self)),
+            Self::Hyprland(dir) => ipc::hyprland_events(dir).map(Subscription::Hyprland).map_err(ipc_error(self)),
+            Self::X11 => {
+                let keyboard = X11Keyboard::connect()?;
+                keyboard.subscribe()?;
+                Ok(Subscription::X11(Box::new(keyboard)))
+            }
+        }
+    }
+}
+
+fn ipc_error(backend: &Backend) -> impl Fn(std::io::Error) -> SensorError + '_ {
+    move |e| SensorError::unavailable(format!("No answer from {}: {}", backend.name(), e))
+}
+
+/// A connection receiving layout switches.
+#[derive(Debug)]
+pub enum Subscription {
+    Sway(UnixStream),
+    Hyprland(BufReader<UnixStream>),
+    X11(Box<X11Keyboard>),
+}
+
+impl Subscription {
+    /// Wait for the next layout switch or keymap change.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error once the connection is gone.
+    pub fn wait(&mut self) -> Result<(), SensorError> {
+        let closed = |e: std::io::Error| SensorError::unavailable(format!("Layout events stopped: {}", e));
+        loop {
+            match self {
+                Self::Sway(stream) => {
+                    let (kind, payload) = ipc::sway_event(stream).map_err(closed)?;
+                    let change = serde_json::from_str::<Value>(&payload).ok()
+                        .and_then(|event| event["change"].as_str().map(str::to_string));
+                    if kind == ipc::SWAY_INPUT_EVENT && matches!(change.as_deref(), Some("xkb_layout" | "xkb_keymap")) {
+                        return Ok(());
+                    }
+                }
+                Self::Hyprland(events) => {
+                    let (name, _) = ipc::hyprland_event(events).map_err(closed)?;
+                    if name == "activelayout" {
+                        return Ok(());
+                    }
+                }
+                Self::X11(keyboard) => return keyboard.wait(),
+            }
+        }
+    }
+}
+
+/// A keyboard layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    /// Full name, e.g. `English (US)`
+    pub name: String,
+    /// XKB layout code, e.g. `us`, if known
+    pub code: Option<String>,
+}
+
+impl Layout {
+    /// Short name: the layout code, else the first letters of the name.
+    pub fn short(&self) -> String {
+        self.code.clone().unwrap_or_else(|| self.name.chars().take(2).collect::<String>().to_lowercase())
+    }
+}
+
+/// A keyboard's layouts and the active one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyboardState {
+    /// Sway input identifier or Hyprland device name
+    pub id: String,
+    pub name: String,
+    /// Hyprland's main keyboard
+    pub main: bool,
+    pub layouts: Vec<Layout>,
+    pub active: usize,
+}
+
+impl KeyboardState {
+    pub fn current(&self) -> Option<&Layout> {
+        self.layouts.get(self.active)
+    }
+}
+
+/// The keyboard named `keyboard` (by id or name), else the main keyboard,
+/// else the first one with a layout.
+fn select(keyboards: Vec<KeyboardState>, keyboard: Option<&str>) -> Option<KeyboardState> {
+    match keyboard {
+        Some(wanted) => keyboards.into_iter().find(|state| state.id == wanted || state.name == wanted),
+        None => {
+            let main = keyboards.iter().position(|state| state.main)
+                .or_else(|| keyboards.iter().position(|state| !state.layouts.is_empty()))?;
+            keyboards.into_iter().nth(main)
+        }
+    }
+}
+
+/// Parse Sway's `GET_INPUTS` reply. Sway reports layouts by full name only.
+fn parse_sway_inputs(json: &str, rules: &XkbRules) -> Result<Vec<KeyboardState>, SensorError> {
+    let inputs: Value = serde_json::from_str(json)
+        .map_err(|e| SensorError::parse_with_source("Invalid GET_INPUTS reply", e))?;
+    let inputs = inputs.as_array().ok_or_else(|| SensorError::parse("Unexpected GET_INPUTS reply"))?;
+    Ok(inputs
+        .iter()
+        .filter(|input| input["type"] == "keyboard")
+        .filter_map(|input| {
+            let layouts = input["xkb_layout_names"].as_array()?
+                .iter()
+                .filter_map(|name| name.as_str())
+                .map(|name| Layout { name: name.to_string(), code: rules.code(name).map(str::to_string) })
+                .collect();
+            Some(KeyboardState {
+                id: input["identifier"].as_str()?.to_string(),
+                name: input["name"].as_str().unwrap_or_default().to_string(),
+                main: false,
+                layouts,
+                active: input["xkb_active_layout_index"].as_u64().unwrap_or_default() as usize,
+            })
+        })
+        .collect())
+}
+
+/// Parse Hyprland's `j/devices` reply. Keyboards list their layout codes
+/// and the active one by full name.
+fn parse_hyprland_devices(json: &str, rules: &XkbRules) -> Result<Vec<KeyboardState>, SensorError> {
+    let devices: Value = serde_json::from_str(json)
+        .map_err(|e| SensorError::parse_with_source("Invalid devices reply", e))?;
+    let keyboards = devices["keyboards"].as_array()
+        .ok_or_else(|| SensorError::parse("Devices reply without keyboards"))?;
+    Ok(keyboards
+        .iter()
+        .filter_map(|keyboard| {
+            let name = keyboard["name"].as_str()?.to_string();
+            let variants: Vec<&str> = keyboard["variant"].as_str().unwrap_or_default().split(',').collect();
+            let layouts: Vec<Layout> = keyboard["layout"].as_str().unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|code| !code.is_empty())
+                .enumerate()
+                .map(|(index, code)| Layout {
+                    name: rules.name(code, variants.get(index).map(|variant| variant.trim()))
+                        .unwrap_or(code)
+                        .to_string(),
+                    code: Some(code.to_string()),
+                })
+                .collect();
+            let keymap = keyboard["active_keymap"].as_str().unwrap_or_default();
+            let active = layouts.iter().position(|layout| layout.name == keymap)
+                .or_else(|| {
+                    let code = rules.code(keymap)?;
+                    layouts.iter().position(|layout| layout.code.as_deref() == Some(code))
+                })
+                .unwrap_or_default();
+            Some(KeyboardState {
+                id: name.clone(),
+                name,
+                main: keyboard["main"].as_bool().unwrap_or(false),
+                layouts,
+                active,
+            })
+        })
+        .collect())
+}
+
+/// Sensor for the active keyboard layout.
+#[derive(Debug)]
+pub struct KeyboardSensor {
+    name: String,
+    config: SensorConfig,
+    backend: Backend,
+    keyboard: Option<String>,
+    rules: XkbRules,
+}
+
+impl KeyboardSensor {
+    /// Create a sensor for `keyboard` (a Sway identifier or Hyprland device
+    /// name), or for the main keyboard.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error outside of a Sway, Hyprland or X11 session.
+    pub fn new(keyboard: Option<String>) -> Result<Self, SensorError> {
+        let backend = Backend::detect()
+            .ok_or_else(|| SensorError::unavailable("No Sway, Hyprland or X11 session found"))?;
+        Ok(Self {
+            name: "keyboard".to_string(),
+            config: SensorConfig::default(),
+            backend,
+            keyboard,
+            rules: XkbRules::load(),
+        })
+    }
+
+    pub fn backend(&self) -> &Backend {
+        &self.backend
+    }
+
+    /// The monitored keyboard's layouts.
+    pub fn state(&self) -> Result<KeyboardState, SensorError> {
+        select(self.backend.keyboards(&self.rules)?, self.keyboard.as_deref()).ok_or_else(|| match &self.keyboard {
+            Some(keyboard) => SensorError::unavailable(format!("Keyboard '{}' not found", keyboard)),
+            None => SensorError::unavailable("No keyboard found"),
+        })
+    }
+
+    /// Switch the monitored keyboard to its next layout.
+    pub fn next_layout(&self) -> Result<(), SensorError> {
+        self.backend.next_layout(&self.state()?)
+    }
+
+    /// Bar label of `layout`: its entry in the `labels` setting, else its
+    /// short or full name by the `format` setting.
+    fn label(&self, layout: &Layout) -> String {
+        let short = layout.short();
+        if let Some(label) = self.config.get_custom("labels").and_then(|labels| labels.get(&short)).and_then(|v| v.as_str()) {
+            return label.to_string();
+        }
+        match self.config.get_custom("format").and_then(|v| v.as_str()) {
+            Some("name") => layout.name.clone(),
+            _ => short,
+        }
+    }
+
+    fn build_tooltip(&self, state: &KeyboardState) -> String {
+        let mut lines = Vec::new();
+        if let Some(layout) = state.current() {
+            lines.push(format::key_value("Layout", &layout.name, &self.config));
+        }
+        if !state.name.is_empty() {
+            lines.push(format::key_value("Keyboard", &state.name, &self.config));
+        }
+        if state.layouts.len() > 1 {
+            let names: Vec<&str> = state.layouts.iter().map(|layout| layout.name.as_str()).collect();
+            lines.push(format::key_value("Layouts", &names.join(", "), &self.config));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Discoverable for KeyboardSensor {
+    const KIND: &'static str = "keyboards";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        let Some(backend) = Backend::detect() else {
+            return Ok(Vec::new());
+        };
+        Ok(backend
+            .keyboards(&XkbRules::load())?
+            .into_iter()
+            .map(|state| {
+                let layouts: Vec<String> = state.layouts.iter().map(Layout::short).collect();
+                let item = DiscoveredItem::new(state.id.clone(), state.name.clone())
+                    .with_detail("layouts", layouts.join(", "));
+                if state.main { item.with_detail("main", "yes") } else { item }
+            })
+            .collect())
+    }
+}
+
+impl Sensor for KeyboardSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let state = self.state()?;
+        let text = state.current().map(|layout| self.label(layout)).unwrap_or_default();
+
+        Ok(WaybarOutput {
+            text: format::with_icon_and_colors(&text, &self.config.icons.keyboard, &self.config),
+            tooltip: Some(self.build_tooltip(&state)),
+            class: Some(self.config.theme.normal.clone()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        self.state().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_parsing() {
+        let rules = XkbRules::parse(
+            "! layout\n  us  English (US)\n  de  German\n! variant\n  nodeadkeys  de: German (no dead keys)\n",
+        );
+
+        let sway = r#"[
+            {"identifier":"1:1:AT_Translated_Set_2_keyboard","name":"AT Translated Set 2 keyboard","type":"keyboard",
+             "xkb_layout_names":["English (US)","German (no dead keys)"],"xkb_active_layout_index":1},
+            {"identifier":"1267:12377:ELAN1300:00_04F3:3059_Touchpad","name":"ELAN1300:00 04F3:3059 Touchpad","type":"touchpad"}
+        ]"#;
+        let keyboards = parse_sway_inputs(sway, &rules).unwrap();
+        assert_eq!(keyboards.len(), 1);
+        let state = select(keyboards, None).unwrap();
+        assert_eq!(state.id, "1:1:AT_Translated_Set_2_keyboard");
+        assert_eq!(state.current().unwrap().short(), "de");
+        assert_eq!(state.current().unwrap().name, "German (no dead keys)");
+
+        let hyprland = r#"{"mice":[],"keyboards":[
+            {"name":"power-button","layout":"us","variant":"","active_keymap":"English (US)","main":false},
+            {"name":"at-translated-set-2-keyboard","layout":"us,de","variant":",nodeadkeys",
+             "active_keymap":"German (no dead keys)","main":true}
+        ]}"#;
+        let keyboards = parse_hyprland_devices(hyprland, &rules).unwrap();
+        let state = select(keyboards.clone(), None).unwrap();
+        assert_eq!(state.id, "at-translated-set-2-keyboard");
+        assert_eq!(state.active, 1);
+        assert_eq!(state.layouts[0], Layout { name: "English (US)".to_string(), code: Some("us".to_string()) });
+        assert_eq!(select(keyboards, Some("power-button")).unwrap().current().unwrap().short(), "us");
+
+        let unknown = Layout { name: "Klingon".to_string(), code: None };
+        assert_eq!(unknown.short(), "kl");
+    }
+}
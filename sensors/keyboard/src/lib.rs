@@ -0,0 +1,12 @@
+//! Keyboard layout sensor for waysensor-rs.
+//!
+//! Shows the active keyboard layout from Sway, Hyprland or X11, updating
+//! as soon as the layout is switched.
+
+pub mod keyboard;
+pub mod rules;
+pub mod x11;
+pub mod events;
+
+pub use keyboard::{Backend, KeyboardSensor, KeyboardState, Layout};
+pub use events::LayoutEvents;
@@ -0,0 +1,120 @@
+//! XKB layout codes and names.
+//!
+//! Sway reports layouts by their full name (`English (US)`), Hyprland and
+//! X11 by code (`us`). The layout list shipped with xkeyboard-config maps
+//! between the two, variants included (`dvorak` of `us` is
+//! `English (Dvorak)`).
+
+use std::fs;
+
+const RULES_PATHS: [&str; 2] = [
+    "/usr/share/X11/xkb/rules/evdev.lst",
+    // xkeyboard-config 2.45 and later
+    "/usr/share/xkeyboard-config-2/rules/evdev.lst",
+];
+
+/// Layouts and variants of the XKB rules.
+#[derive(Debug, Clone, Default)]
+pub struct XkbRules {
+    /// Code and name of every layout
+    layouts: Vec<(String, String)>,
+    /// Variant, layout code and name of every variant
+    variants: Vec<(String, String, String)>,
+}
+
+impl XkbRules {
+    /// Rules of the installed xkeyboard-config, empty if it is not installed.
+    pub fn load() -> Self {
+        RULES_PATHS
+            .iter()
+            .find_map(|path| fs::read_to_string(path).ok())
+            .map(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    /// Parse an `evdev.lst` file: `! layout` lines are a code and a name,
+    /// `! variant` lines a variant and `code: name`.
+    pub fn parse(text: &str) -> Self {
+        let mut rules = Self::default();
+        let mut section = "";
+        for line in text.lines() {
+            if let Some(name) = line.strip_prefix('!') {
+                section = name.trim();
+                continue;
+            }
+            let Some((id, description)) = line.trim().split_once(char::is_whitespace) else {
+                continue;
+            };
+            let description = description.trim();
+            match section {
+                "layout" => rules.layouts.push((id.to_string(), description.to_string())),
+                "variant" => {
+                    if let Some((layout, name)) = description.split_once(": ") {
+                        rules.variants.push((id.to_string(), layout.to_string(), name.to_string()));
+                    }
+                }
+                _ => {}
+            }
+        }
+        rules
+    }
+
+    /// Layout code of a full layout or variant name.
+    pub fn code(&self, name: &str) -> Option<&str> {
+        self.layouts
+            .iter()
+            .find(|(_, description)| description == name)
+            .map(|(code, _)| code.as_str())
+            .or_else(|| {
+                self.variants
+                    .iter()
+                    .find(|(_, _, description)| description == name)
+                    .map(|(_, code, _)| code.as_str())
+            })
+    }
+
+    /// Full name of layout `code`, or of its `variant`.
+    pub fn name(&self, code: &str, variant: Option<&str>) -> Option<&str> {
+        let variant = variant.filter(|variant| !variant.is_empty()).and_then(|variant| {
+            self.variants
+                .iter()
+                .find(|(id, layout, _)| id == variant && layout == code)
+                .map(|(_, _, description)| description.as_str())
+        });
+        variant.or_else(|| {
+            self.layouts
+                .iter()
+                .find(|(id, _)| id == code)
+                .map(|(_, description)| description.as_str())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_parsing() {
+        let rules = XkbRules::parse(
+            "! model
+  pc105           Generic 105-key PC
+! layout
+  us              English (US)
+  de              German
+! variant
+  dvorak          us: English (Dvorak)
+  nodeadkeys      de: German (no dead keys)
+! option
+  grp                  Switching to another layout
+",
+        );
+        assert_eq!(rules.code("English (US)"), Some("us"));
+        assert_eq!(rules.code("German (no dead keys)"), Some("de"));
+        assert_eq!(rules.code("Generic 105-key PC"), None);
+        assert_eq!(rules.name("us", Some("dvorak")), Some("English (Dvorak)"));
+        assert_eq!(rules.name("de", Some("")), Some("German"));
+        assert_eq!(rules.name("de", Some("dvorak")), Some("German"));
+        assert_eq!(rules.name("fr", None), None);
+    }
+}
@@ -0,0 +1,136 @@
+//! X11 keyboard groups through the XKB extension.
+//!
+//! XKB calls layouts groups. The configured layout codes are in the root
+//! window's `_XKB_RULES_NAMES` property (rules, model, layouts, variants and
+//! options, NUL-separated), the active group in the core keyboard's state,
+//! and a `StateNotify` event selected for group changes arrives on every
+//! switch.
+
+use waysensor_rs_core::SensorError;
+use x11rb::connection::Connection;
+use x11rb::protocol::xkb::{self, ConnectionExt as _};
+use x11rb::protocol::xproto::{self, AtomEnum, ConnectionExt as _};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+const RULES_NAMES_PROPERTY: &[u8] = b"_XKB_RULES_NAMES";
+
+fn x11_error(e: impl std::fmt::Display) -> SensorError {
+    SensorError::unavailable(format!("X11 keyboard unavailable: {}", e))
+}
+
+/// Layout codes and variants of a `_XKB_RULES_NAMES` value.
+fn parse_rules_names(value: &[u8]) -> (Vec<String>, Vec<String>) {
+    let fields: Vec<String> = value.split(|&byte| byte == 0).map(|field| String::from_utf8_lossy(field).into_owned()).collect();
+    let list = |index: usize| -> Vec<String> {
+        fields.get(index).map_or_else(Vec::new, |field| field.split(',').map(|item| item.trim().to_string()).collect())
+    };
+    let layouts: Vec<String> = list(2).into_iter().filter(|layout| !layout.is_empty()).collect();
+    let mut variants = list(3);
+    variants.resize(layouts.len(), String::new());
+    (layouts, variants)
+}
+
+/// Configured groups and the active one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XkbGroups {
+    pub layouts: Vec<String>,
+    /// Variant of each layout, empty for the default one
+    pub variants: Vec<String>,
+    pub active: usize,
+}
+
+/// Connection to the X server's core keyboard.
+#[derive(Debug)]
+pub struct X11Keyboard {
+    conn: RustConnection,
+    root: xproto::Window,
+}
+
+impl X11Keyboard {
+    /// Connect to `$DISPLAY` and set up XKB.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no X server or it lacks XKB.
+    pub fn connect() -> Result<Self, SensorError> {
+        let (conn, screen) = x11rb::connect(None).map_err(x11_error)?;
+        let supported = conn.xkb_use_extension(1, 0).map_err(x11_error)?.reply().map_err(x11_error)?.supported;
+        if !supported {
+            return Err(SensorError::unavailable("The X server does not support XKB"));
+        }
+        let root = conn.setup().roots[screen].root;
+        Ok(Self { conn, root })
+    }
+
+    /// The configured groups and the active one.
+    pub fn groups(&self) -> Result<XkbGroups, SensorError> {
+        let atom = self.conn.intern_atom(true, RULES_NAMES_PROPERTY)
+            .map_err(x11_error)?.reply().map_err(x11_error)?.atom;
+        let property = self.conn.get_property(false, self.root, atom, AtomEnum::STRING, 0, 1024)
+            .map_err(x11_error)?.reply().map_err(x11_error)?;
+        let (layouts, variants) = parse_rules_names(&property.value);
+        let state = self.conn.xkb_get_state(xkb::ID::USE_CORE_KBD.into())
+            .map_err(x11_error)?.reply().map_err(x11_error)?;
+        Ok(XkbGroups { layouts, variants, active: u8::from(state.group).into() })
+    }
+
+    /// Lock the keyboard to `group`.
+    pub fn lock_group(&self, group: usize) -> Result<(), SensorError> {
+        let group = u8::try_from(group).map_err(|_| SensorError::config("XKB has at most 4 groups"))?;
+        let none = xproto::ModMask::from(0u16);
+        self.conn
+            .xkb_latch_lock_state(xkb::ID::USE_CORE_KBD.into(), none, none, true, group.into(), none, false, 0)
+            .map_err(x11_error)?
+            .check()
+            .map_err(x11_error)
+    }
+
+    /// Ask for an event on every group change, see [`X11Keyboard::wait`].
+    pub fn subscribe(&self) -> Result<(), SensorError> {
+        let details = xkb::SelectEventsAux::new().state_notify(xkb::SelectEventsAuxStateNotify {
+            affect_state: xkb::StatePart::GROUP_STATE,
+            state_details: xkb::StatePart::GROUP_STATE,
+        });
+        self.conn
+            .xkb_select_events(
+                xkb::ID::USE_CORE_KBD.into(),
+                xkb::EventType::from(0u16),
+                xkb::EventType::from(0u16),
+                xkb::MapPart::from(0u16),
+                xkb::MapPart::from(0u16),
+                &details,
+            )
+            .map_err(x11_error)?
+            .check()
+            .map_err(x11_error)
+    }
+
+    /// Wait for the next group change.
+    pub fn wait(&self) -> Result<(), SensorError> {
+        loop {
+            if let Event::XkbStateNotify(event) = self.conn.wait_for_event().map_err(x11_error)? {
+                if event.changed.contains(xkb::StatePart::GROUP_STATE) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_names_parsing() {
+        let (layouts, variants) = parse_rules_names(b"evdev\0pc105\0us,de\0,nodeadkeys\0grp:alt_shift_toggle\0");
+        assert_eq!(layouts, ["us", "de"]);
+        assert_eq!(variants, ["", "nodeadkeys"]);
+
+        let (layouts, variants) = parse_rules_names(b"evdev\0pc105\0fr\0\0\0");
+        assert_eq!(layouts, ["fr"]);
+        assert_eq!(variants, [""]);
+        assert_eq!(parse_rules_names(b"").0, Vec::<String>::new());
+    }
+}
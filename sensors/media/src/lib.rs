@@ -0,0 +1,8 @@
+//! Media player sensor for waysensor-rs.
+//!
+//! Shows what an MPRIS media player is playing, read from the session bus,
+//! and plays or pauses it on click.
+
+pub mod media;
+
+pub use media::{MediaSensor, PlaybackStatus, Player, Track, PAUSED_CLASS, PLAYING_CLASS};
@@ -0,0 +1,352 @@
+//! Now playing, from MPRIS media players.
+//!
+//! Every MPRIS player (Spotify, mpv with mpv-mpris, browsers, ...) owns a
+//! session bus name starting with `org.mpris.MediaPlayer2.` and exposes its
+//! playback status and the current track's metadata on
+//! `/org/mpris/MediaPlayer2`. The bus is reached through `busctl --user`.
+//! With several players, the one playing wins over paused ones.
+
+use serde_json::Value;
+use std::process::Command;
+use std::time::Duration;
+use waysensor_rs_core::{format, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+/// CSS class while a player is playing.
+pub const PLAYING_CLASS: &str = "playing";
+/// CSS class while the shown player is paused.
+pub const PAUSED_CLASS: &str = "paused";
+
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// Run `busctl` on the session bus and return its output.
+fn busctl(args: &[&str]) -> Result<String, SensorError> {
+    let output = Command::new("busctl")
+        .arg("--user")
+        .args(args)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => SensorError::unavailable("busctl not installed"),
+            _ => SensorError::Io(e),
+        })?;
+    if !output.status.success() {
+        return Err(SensorError::unavailable(format!(
+            "Session bus call failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// MPRIS `PlaybackStatus`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PlaybackStatus {
+    // Declared in order of preference when choosing a player
+    Playing,
+    Paused,
+    Stopped,
+}
+
+impl PlaybackStatus {
+    fn parse(status: &str) -> Self {
+        match status {
+            "Playing" => Self::Playing,
+            "Paused" => Self::Paused,
+            _ => Self::Stopped,
+        }
+    }
+}
+
+/// The current track, from MPRIS `Metadata`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Track {
+    pub title: String,
+    /// Artists joined with `, `
+    pub artist: String,
+    pub album: String,
+    pub length: Option<Duration>,
+}
+
+impl Track {
+    /// `format` with `{artist}`, `{title}` and `{album}` filled in; just
+    /// the title when the artist is unknown, as for most web videos.
+    pub fn display(&self, format: &str) -> String {
+        if self.artist.is_empty() {
+            return self.title.clone();
+        }
+        format
+            .replace("{artist}", &self.artist)
+            .replace("{title}", &self.title)
+            .replace("{album}", &self.album)
+    }
+}
+
+/// A media player on the session bus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Player {
+    /// Bus name, e.g. `org.mpris.MediaPlayer2.spotify`
+    pub bus_name: String,
+    pub status: PlaybackStatus,
+    pub track: Track,
+}
+
+impl Player {
+    /// Short name, e.g. `spotify` or `firefox` for
+    /// `org.mpris.MediaPlayer2.firefox.instance_1_42`.
+    pub fn name(&self) -> &str {
+        short_name(&self.bus_name)
+    }
+}
+
+fn short_name(bus_name: &str) -> &str {
+    let name = bus_name.strip_prefix(MPRIS_PREFIX).unwrap_or(bus_name);
+    name.split('.').next().unwrap_or(name)
+}
+
+/// MPRIS bus names in the `busctl --json` reply of `ListNames` (`as`).
+fn parse_player_names(json: &str) -> Result<Vec<String>, SensorError> {
+    let reply: Value = serde_json::from_str(json)
+        .map_err(|e| SensorError::parse_with_source("Invalid ListNames reply", e))?;
+    let names = reply["data"][0].as_array()
+        .ok_or_else(|| SensorError::parse("Unexpected ListNames reply"))?;
+    let mut players: Vec<String> = names.iter()
+        .filter_map(|name| name.as_str())
+        .filter(|name| name.starts_with(MPRIS_PREFIX))
+        .map(str::to_string)
+        .collect();
+    players.sort();
+    Ok(players)
+}
+
+/// Status and track from `busctl --json get-property ... PlaybackStatus
+/// Metadata`, which prints one JSON value per property.
+fn parse_player_properties(output: &str) -> Option<(PlaybackStatus, Track)> {
+    let mut values = serde_json::Deserializer::from_str(output).into_iter::<Value>();
+    let status = PlaybackStatus::parse(values.next()?.ok()?["data"].as_str()?);
+    let metadata = values.next()?.ok()?;
+
+    // Entries are variants: {"type": "s", "data": ...}
+    let entry = |key: &str| &metadata["data"][key]["data"];
+    let string = |key: &str| entry(key).as_str().unwrap_or_default().to_string();
+    let artist = match entry("xesam:artist") {
+        Value::Array(artists) => artists.iter().filter_map(|artist| artist.as_str()).collect::<Vec<_>>().join(", "),
+        artist => artist.as_str().unwrap_or_default().to_string(),
+    };
+    let track = Track {
+        title: string("xesam:title"),
+        artist,
+        album: string("xesam:album"),
+        // Microseconds
+        length: entry("mpris:length").as_u64().filter(|&us| us > 0).map(Duration::from_micros),
+    };
+    Some((status, track))
+}
+
+/// Every MPRIS player on the session bus.
+fn list_players() -> Result<Vec<Player>, SensorError> {
+    let names = parse_player_names(&busctl(&[
+        "--json=short", "call",
+        "org.freedesktop.DBus", "/org/freedesktop/DBus",
+        "org.freedesktop.DBus", "ListNames",
+    ])?)?;
+    // A player quitting between the two calls is simply left out
+    Ok(names
+        .into_iter()
+        .filter_map(|bus_name| {
+            let output = busctl(&[
+                "--json=short", "get-property", &bus_name, MPRIS_PATH, PLAYER_INTERFACE, "PlaybackStatus", "Metadata",
+            ]).ok()?;
+            let (status, track) = parse_player_properties(&output)?;
+            Some(Player { bus_name, status, track })
+        })
+        .collect())
+}
+
+/// Sensor for the most relevant media player.
+#[derive(Debug)]
+pub struct MediaSensor {
+    name: String,
+    config: SensorConfig,
+    /// Only players whose short name starts with this
+    player: Option<String>,
+    scroll: format::Marquee,
+}
+
+impl MediaSensor {
+    /// Create a media sensor following players named `player` (e.g.
+    /// `spotify`), or any player. Players may come and go, so none has to
+    /// be running yet.
+    pub fn new(player: Option<String>) -> Self {
+        Self {
+            name: "media".to_string(),
+            config: SensorConfig::default(),
+            player,
+            scroll: format::Marquee::default(),
+        }
+    }
+
+    /// The player to show: a playing one before a paused one.
+    fn current(&self) -> Result<Option<Player>, SensorError> {
+        let mut players: Vec<Player> = list_players()?
+            .into_iter()
+            .filter(|player| self.player.as_deref().is_none_or(|name| player.name().starts_with(name)))
+            .collect();
+        players.sort_by_key(|player| player.status);
+        Ok(players.into_iter().next())
+    }
+
+    /// Play or pause the shown player.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no player is running or it rejects the call.
+    pub fn toggle(&self) -> Result<(), SensorError> {
+        let player = self.current()?.ok_or_else(|| SensorError::unavailable("No media player running"))?;
+        busctl(&["call", &player.bus_name, MPRIS_PATH, PLAYER_INTERFACE, "PlayPause"]).map(|_| ())
+    }
+
+    /// The track text, truncated or scrolled to `max_length` characters.
+    fn fit(&mut self, text: String, playing: bool) -> String {
+        let max_length = self.config.get_custom("max_length").and_then(|v| v.as_u64()).unwrap_or(40) as usize;
+        let scroll = self.config.get_custom("scroll").and_then(|v| v.as_bool()).unwrap_or(false);
+        if !scroll {
+            return format::truncate(&text, max_length);
+        }
+        // Starts over on a new track; holds still while paused
+        self.scroll.next(&text, max_length, playing)
+    }
+
+    fn build_tooltip(&self, player: &Player) -> String {
+        let status = match player.status {
+            PlaybackStatus::Playing => "playing",
+            PlaybackStatus::Paused => "paused",
+            PlaybackStatus::Stopped => "stopped",
+        };
+        let mut lines = vec![
+            format::key_value("Player", player.name(), &self.config),
+            format::key_value("Status", status, &self.config),
+        ];
+        let track = &player.track;
+        for (key, value) in [("Title", &track.title), ("Artist", &track.artist), ("Album", &track.album)] {
+            if !value.is_empty() {
+                lines.push(format::key_value(key, &format::escape_markup(value), &self.config));
+            }
+        }
+        if let Some(length) = track.length {
+            let seconds = length.as_secs();
+            lines.push(format::key_value("Length", &format!("{}:{:02}", seconds / 60, seconds % 60), &self.config));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Discoverable for MediaSensor {
+    const KIND: &'static str = "media players";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        // No session bus means nothing to list, not a failure
+        let Ok(players) = list_players() else {
+            return Ok(Vec::new());
+        };
+        Ok(players
+            .into_iter()
+            .map(|player| {
+                let status = format!("{:?}", player.status).to_lowercase();
+                let mut item = DiscoveredItem::new(player.name().to_string(), player.bus_name.clone())
+                    .with_detail("status", status);
+                if !player.track.title.is_empty() {
+                    item = item.with_detail("track", player.track.display("{artist} - {title}"));
+                }
+                item
+            })
+            .collect())
+    }
+}
+
+impl Sensor for MediaSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        // Nothing to show without a player or track
+        let Some(player) = self.current()?.filter(|player| player.status != PlaybackStatus::Stopped) else {
+            return Ok(WaybarOutput {
+                text: String::new(),
+                tooltip: Some(format::key_value("Media", "nothing playing", &self.config)),
+                class: Some(self.config.theme.normal.clone()),
+                percentage: None,
+            });
+        };
+
+        let playing = player.status == PlaybackStatus::Playing;
+        let track_format = self.config.get_custom("format").and_then(|v| v.as_str())
+            .unwrap_or("{artist} - {title}")
+            .to_string();
+        let mut status = player.track.display(&track_format);
+        if status.is_empty() {
+            status = player.name().to_string();
+        }
+        let status = format::escape_markup(&self.fit(status, playing));
+
+        let (icon, class) = if playing {
+            (&self.config.icons.media_playing, PLAYING_CLASS)
+        } else {
+            (&self.config.icons.media_paused, PAUSED_CLASS)
+        };
+        Ok(WaybarOutput {
+            text: format::with_icon_and_colors(&status, icon, &self.config),
+            tooltip: Some(self.build_tooltip(&player)),
+            class: Some(class.to_string()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        list_players().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_parsing_and_text_fitting() {
+        let names = r#"{"type":"as","data":[["org.freedesktop.DBus",":1.42","org.mpris.MediaPlayer2.spotify","org.mpris.MediaPlayer2.firefox.instance_1_42"]]}"#;
+        let names = parse_player_names(names).unwrap();
+        assert_eq!(names, ["org.mpris.MediaPlayer2.firefox.instance_1_42", "org.mpris.MediaPlayer2.spotify"]);
+        assert_eq!(short_name(&names[0]), "firefox");
+
+        let properties = concat!(
+            r#"{"type":"s","data":"Playing"}"#, "\n",
+            r#"{"type":"a{sv}","data":{"mpris:trackid":{"type":"o","data":"/com/spotify/track/1"},"#,
+            r#""mpris:length":{"type":"t","data":215000000},"xesam:title":{"type":"s","data":"Teardrop"},"#,
+            r#""xesam:artist":{"type":"as","data":["Massive Attack"]},"xesam:album":{"type":"s","data":"Mezzanine"}}}"#,
+        );
+        let (status, track) = parse_player_properties(properties).unwrap();
+        assert_eq!(status, PlaybackStatus::Playing);
+        assert_eq!(track.display("{artist} - {title}"), "Massive Attack - Teardrop");
+        assert_eq!(track.length, Some(Duration::from_secs(215)));
+        let video = Track { title: "Some video".to_string(), ..Track::default() };
+        assert_eq!(video.display("{artist} - {title}"), "Some video");
+
+        assert_eq!(format::truncate("Massive Attack - Teardrop", 10), "Massive A…");
+        assert_eq!(format::truncate("Teardrop", 10), "Teardrop");
+        assert_eq!(format::scroll_window("Teardrop", 10, 3), "Teardrop");
+        assert_eq!(format::scroll_window("Massive Attack", 8, 0), "Massive ");
+        assert_eq!(format::scroll_window("Massive Attack", 8, 12), "ck · Mas");
+    }
+}
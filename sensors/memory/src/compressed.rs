@@ -0,0 +1,187 @@
+//! Compressed memory: zram devices and the zswap pool.
+//!
+//! Both keep swapped-out pages compressed in RAM, so the kernel reports them
+//! twice: once as swap in use (at their original size) and once as physical
+//! memory in use (at their compressed size). [`MemoryInfo::fold_compressed_swap`]
+//! removes the duplicate so RAM + swap percentages stay meaningful.
+
+use crate::MemoryInfo;
+use std::{fs, path::Path};
+use waysensor_rs_core::sysroot;
+
+/// Statistics of one zram block device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZramDevice {
+    /// Device name, e.g. `zram0`
+    pub name: String,
+    /// Uncompressed size of the data stored on the device, in bytes
+    pub original: u64,
+    /// Compressed size of that data, in bytes
+    pub compressed: u64,
+    /// RAM consumed by the device including allocator overhead, in bytes
+    pub mem_used: u64,
+    /// Configured device size, in bytes
+    pub disksize: u64,
+}
+
+impl ZramDevice {
+    /// Compression ratio (original / compressed), if anything is stored.
+    #[must_use]
+    pub fn ratio(&self) -> Option<f64> {
+        (self.compressed > 0).then(|| self.original as f64 / self.compressed as f64)
+    }
+}
+
+/// All initialized zram devices.
+pub fn zram_devices() -> Vec<ZramDevice> {
+    zram_devices_in(&sysroot::path("/sys/block"))
+}
+
+/// All initialized zram devices below a sysfs `block` directory (useful for testing).
+pub fn zram_devices_in(sys_block: &Path) -> Vec<ZramDevice> {
+    let Ok(entries) = fs::read_dir(sys_block) else {
+        return Vec::new();
+    };
+
+    let mut devices: Vec<ZramDevice> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with("zram") {
+                return None;
+            }
+            let path = entry.path();
+            let disksize = fs::read_to_string(path.join("disksize")).ok()?.trim().parse().ok()?;
+            // Unconfigured devices exist but have no size
+            if disksize == 0 {
+                return None;
+            }
+            let (original, compressed, mem_used) = parse_mm_stat(&fs::read_to_string(path.join("mm_stat")).ok()?)?;
+            Some(ZramDevice { name, original, compressed, mem_used, disksize })
+        })
+        .collect();
+    devices.sort_by(|a, b| a.name.cmp(&b.name));
+    devices
+}
+
+/// Parse `orig_data_size compr_data_size mem_used_total` from a zram `mm_stat` file.
+pub fn parse_mm_stat(content: &str) -> Option<(u64, u64, u64)> {
+    let mut fields = content.split_whitespace().map(str::parse::<u64>);
+    let original = fields.next()?.ok()?;
+    let compressed = fields.next()?.ok()?;
+    let mem_used = fields.next()?.ok()?;
+    Some((original, compressed, mem_used))
+}
+
+/// Size and usage, in bytes, of the swap areas that live on zram devices.
+///
+/// Takes the contents of `/proc/swaps`, whose sizes are in KiB.
+pub fn zram_swap_usage(swaps: &str) -> (u64, u64) {
+    swaps.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [name, _kind, size, used, ..] = fields[..] else {
+                return None;
+            };
+            name.starts_with("/dev/zram")
+                .then(|| Some((size.parse::<u64>().ok()? * 1024, used.parse::<u64>().ok()? * 1024)))
+                .flatten()
+        })
+        .fold((0, 0), |(size, used), (s, u)| (size + s, used + u))
+}
+
+impl MemoryInfo {
+    /// Memory information with swap that is backed by RAM taken out.
+    ///
+    /// zram swap areas are removed from the swap totals, and pages held in
+    /// the zswap pool no longer count as swap in use. Their compressed
+    /// footprint is already part of the RAM in use, so the RAM + swap figures
+    /// of the result count every page exactly once.
+    #[must_use]
+    pub fn fold_compressed_swap(&self, zram_swap_size: u64, zram_swap_used: u64) -> Self {
+        let swap_total = self.swap_total.saturating_sub(zram_swap_size);
+        let swap_used = self.swap_used()
+            .saturating_sub(zram_swap_used)
+            .saturating_sub(self.zswapped)
+            .min(swap_total);
+        Self {
+            swap_total,
+            swap_free: swap_total - swap_used,
+            ..*self
+        }
+    }
+
+    /// zswap compression ratio (original / pool size), if the pool holds anything.
+    #[must_use]
+    pub fn zswap_ratio(&self) -> Option<f64> {
+        (self.zswap_pool > 0).then(|| self.zswapped as f64 / self.zswap_pool as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zram_detection() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        let zram0 = dir.join("zram0");
+        let zram1 = dir.join("zram1");
+        fs::create_dir_all(&zram0).unwrap();
+        fs::create_dir_all(&zram1).unwrap();
+        fs::create_dir_all(dir.join("nvme0n1")).unwrap();
+        fs::write(zram0.join("disksize"), "8589934592\n").unwrap();
+        fs::write(zram0.join("mm_stat"), " 3145728000  1048576000  1100000000        0  1200000000    12345        0      100\n").unwrap();
+        fs::write(zram1.join("disksize"), "0\n").unwrap();
+        fs::write(zram1.join("mm_stat"), "0 0 0 0 0 0 0 0\n").unwrap();
+
+        let devices = zram_devices_in(dir);
+
+        assert_eq!(devices, vec![ZramDevice {
+            name: "zram0".to_owned(),
+            original: 3_145_728_000,
+            compressed: 1_048_576_000,
+            mem_used: 1_100_000_000,
+            disksize: 8_589_934_592,
+        }]);
+        assert_eq!(devices[0].ratio(), Some(3.0));
+        assert!(parse_mm_stat("12 34").is_none());
+    }
+
+    #[test]
+    fn test_fold_compressed_swap() {
+        let swaps = "Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority\n\
+            /dev/zram0                              partition\t8388604\t\t2097152\t\t100\n\
+            /dev/nvme0n1p3                          partition\t4194300\t\t1048576\t\t-2\n";
+        let (zram_size, zram_used) = zram_swap_usage(swaps);
+        assert_eq!(zram_size, 8_388_604 * 1024);
+        assert_eq!(zram_used, 2_097_152 * 1024);
+
+        let gib = 1024 * 1024 * 1024;
+        let info = MemoryInfo {
+            mem_total: 16 * gib,
+            mem_free: 2 * gib,
+            mem_available: 8 * gib,
+            mem_buffers: 0,
+            mem_cached: 0,
+            mem_shmem: 0,
+            mem_slab: 0,
+            mem_slab_reclaimable: 0,
+            swap_total: (8_388_604 + 4_194_300) * 1024,
+            swap_free: (8_388_604 + 4_194_300 - 2_097_152 - 1_048_576) * 1024,
+            zswap_pool: 0,
+            zswapped: 0,
+        };
+        let folded = info.fold_compressed_swap(zram_size, zram_used);
+        assert_eq!(folded.swap_total, 4_194_300 * 1024);
+        assert_eq!(folded.swap_used(), gib);
+        assert_eq!(folded.mem_used(), info.mem_used());
+
+        // zswap: pages in the pool are counted as swap in use by the kernel
+        let zswap = MemoryInfo { zswap_pool: gib / 2, zswapped: 2 * gib, swap_total: 8 * gib, swap_free: 5 * gib, ..info };
+        assert_eq!(zswap.zswap_ratio(), Some(4.0));
+        assert_eq!(zswap.fold_compressed_swap(0, 0).swap_used(), gib);
+    }
+}
@@ -0,0 +1,134 @@
+//! Static hugepages and transparent hugepages (THP).
+//!
+//! Pages in the static hugetlb pool are carved out of RAM at boot or by
+//! `vm.nr_hugepages` and never return to the page allocator, so a pool that
+//! outlives the database or VM that used it silently eats memory. THP usage
+//! shows how much anonymous, shmem and file memory is backed by huge pages.
+
+use std::{fs, path::Path};
+use waysensor_rs_core::{procfs, sysroot, SensorError};
+
+/// Hugepage counters from `/proc/meminfo`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HugePages {
+    /// Pages in the static pool (`HugePages_Total`)
+    pub total: u64,
+    /// Pool pages not allocated to anyone (`HugePages_Free`)
+    pub free: u64,
+    /// Free pages promised to mappings but not yet faulted in (`HugePages_Rsvd`)
+    pub reserved: u64,
+    /// Pages allocated beyond the pool size via overcommit (`HugePages_Surp`)
+    pub surplus: u64,
+    /// Default hugepage size, in bytes
+    pub page_size: u64,
+    /// Memory consumed by hugetlb pages of all sizes, in bytes
+    pub hugetlb: u64,
+    /// Anonymous memory backed by transparent hugepages, in bytes
+    pub anon_thp: u64,
+    /// Shared memory backed by transparent hugepages, in bytes
+    pub shmem_thp: u64,
+    /// Page cache backed by large folios, in bytes
+    pub file_thp: u64,
+}
+
+impl HugePages {
+    /// Read the running system's counters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `/proc/meminfo` cannot be read.
+    pub fn read() -> Result<Self, SensorError> {
+        Ok(procfs::read_with(sysroot::path("/proc/meminfo"), Self::parse)?)
+    }
+
+    /// Parse the hugepage lines of `/proc/meminfo`. Missing fields read as zero.
+    pub fn parse(content: &str) -> Self {
+        let mut pages = Self::default();
+        for line in content.lines() {
+            let mut fields = line.split_whitespace();
+            let (Some(key), Some(value)) = (fields.next(), fields.next()) else { continue };
+            let Ok(value) = value.parse::<u64>() else { continue };
+            // Page counts have no unit; sizes are in kB
+            match key.trim_end_matches(':') {
+                "HugePages_Total" => pages.total = value,
+                "HugePages_Free" => pages.free = value,
+                "HugePages_Rsvd" => pages.reserved = value,
+                "HugePages_Surp" => pages.surplus = value,
+                "Hugepagesize" => pages.page_size = value * 1024,
+                "Hugetlb" => pages.hugetlb = value * 1024,
+                "AnonHugePages" => pages.anon_thp = value * 1024,
+                "ShmemHugePages" => pages.shmem_thp = value * 1024,
+                "FileHugePages" => pages.file_thp = value * 1024,
+                _ => {}
+            }
+        }
+        pages
+    }
+
+    /// Pool pages handed out to mappings.
+    #[must_use]
+    pub const fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+
+    /// Size of the static pool, in bytes.
+    #[must_use]
+    pub const fn pool_bytes(&self) -> u64 {
+        self.total * self.page_size
+    }
+
+    /// Pool memory neither in use nor reserved, in bytes.
+    ///
+    /// This is RAM the rest of the system cannot use; a large value on a host
+    /// whose database or VMs are stopped usually means the pool leaked.
+    #[must_use]
+    pub const fn idle_bytes(&self) -> u64 {
+        self.free.saturating_sub(self.reserved) * self.page_size
+    }
+
+    /// Total memory backed by transparent hugepages, in bytes.
+    #[must_use]
+    pub const fn thp_bytes(&self) -> u64 {
+        self.anon_thp + self.shmem_thp + self.file_thp
+    }
+}
+
+/// Active THP policy (`always`, `madvise` or `never`), if THP is supported.
+pub fn thp_mode() -> Option<String> {
+    thp_mode_in(&sysroot::path("/sys/kernel/mm/transparent_hugepage"))
+}
+
+/// Active THP policy below a sysfs `transparent_hugepage` directory (useful for testing).
+pub fn thp_mode_in(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join("enabled")).ok()?;
+    procfs::selected_option(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hugepage_parsing() {
+        let content = "MemTotal:       65536000 kB\n\
+            AnonHugePages:    409600 kB\n\
+            ShmemHugePages:        0 kB\n\
+            FileHugePages:     20480 kB\n\
+            HugePages_Total:    1024\n\
+            HugePages_Free:      768\n\
+            HugePages_Rsvd:      256\n\
+            HugePages_Surp:        0\n\
+            Hugepagesize:       2048 kB\n\
+            Hugetlb:         2097152 kB\n";
+        let pages = HugePages::parse(content);
+        let mib = 1024 * 1024;
+
+        assert_eq!(pages.total, 1024);
+        assert_eq!(pages.used(), 256);
+        assert_eq!(pages.page_size, 2 * mib);
+        assert_eq!(pages.pool_bytes(), 2048 * mib);
+        assert_eq!(pages.idle_bytes(), 1024 * mib);
+        assert_eq!(pages.hugetlb, 2048 * mib);
+        assert_eq!(pages.thp_bytes(), 420 * mib);
+    }
+}
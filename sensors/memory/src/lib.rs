@@ -7,7 +7,7 @@
 //! # Examples
 //!
 //! ```rust
-//! use waysensor_memory::MemorySensor;
+//! use waysensor_rs_memory::MemorySensor;
 //! use waysensor_rs_core::Sensor;
 //!
 //! // Create a memory sensor with 70% warning and 90% critical thresholds
@@ -19,6 +19,12 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+pub mod compressed;
+pub mod hugepages;
 pub mod memory;
+pub mod vmstat;
 
-pub use memory::{MemoryInfo, MemorySensor};
\ No newline at end of file
+pub use compressed::ZramDevice;
+pub use hugepages::HugePages;
+pub use memory::{MemoryBreakdown, MemoryInfo, MemoryMetric, MemorySensor};
+pub use vmstat::{SwapRates, VmStat};
\ No newline at end of file
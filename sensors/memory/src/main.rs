@@ -4,12 +4,12 @@
 //! It outputs JSON-formatted data compatible with Waybar's custom modules.
 
 use clap::Parser;
-use waysensor_rs_core::{GlobalConfig, IconStyle, Sensor, SensorConfig};
-use waysensor_rs_memory::MemorySensor;
+use waysensor_rs_core::{discovery, schedule, session_lock, sysroot, GlobalConfig, IconStyle, Sensor, SensorConfig};
+use waysensor_rs_memory::{MemoryMetric, MemorySensor};
 use std::io::{self, Write};
+use std::path::PathBuf;
 use std::process;
 use std::time::Duration;
-use tokio::time;
 
 /// Command-line arguments for the memory sensor.
 #[derive(Parser)]
@@ -30,18 +30,62 @@ struct Args {
     #[arg(short, long, default_value = "95", value_parser = validate_percentage)]
     critical: u8,
 
+    /// Metric shown in the bar (usage, psi)
+    #[arg(long, default_value = "usage")]
+    metric: MemoryMetric,
+
+    /// Memory pressure warning threshold in percent fully stalled (psi metric)
+    #[arg(long, default_value = "5")]
+    psi_warning: f64,
+
+    /// Memory pressure critical threshold in percent fully stalled (psi metric, must be > psi warning)
+    #[arg(long, default_value = "20")]
+    psi_critical: f64,
+
+    /// Keep showing usage but set the CSS class from memory pressure (psi thresholds)
+    #[arg(long)]
+    class_on_pressure: bool,
+
+    /// Show a desktop notification when the OOM killer terminates a process
+    #[arg(long)]
+    notify_oom: bool,
+
+    /// Report usage and pressure of a cgroup v2 group (e.g. system.slice/foo.service) instead of the whole system
+    #[arg(long, value_name = "PATH")]
+    cgroup: Option<String>,
+
     /// Include swap usage in calculations
     #[arg(long)]
     include_swap: bool,
 
+    /// Count zram and zswap pages once in --include-swap totals (their RAM footprint is already used memory)
+    #[arg(long, requires = "include_swap")]
+    fold_compressed_swap: bool,
+
     /// Show available memory percentage instead of used
     #[arg(long)]
     show_available: bool,
 
+    /// Break used memory down into apps, buffers, cache, shared and slab in the tooltip
+    #[arg(long)]
+    show_breakdown: bool,
+
     /// One-shot mode (output once and exit)
     #[arg(short, long)]
     once: bool,
 
+    /// List available memory devices and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
     /// Icon style (nerdfont, fontawesome, ascii, none)
     #[arg(long)]
     icon_style: Option<IconStyle>,
@@ -65,6 +109,10 @@ struct Args {
     /// Check sensor availability and exit
     #[arg(long)]
     check: bool,
+
+    /// Read /proc, /sys and other system paths below this directory (for testing)
+    #[arg(long, value_name = "DIR")]
+    sys_root: Option<PathBuf>,
 }
 
 /// Validate that the interval is at least 100ms.
@@ -98,6 +146,22 @@ fn validate_percentage(s: &str) -> Result<u8, String> {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    if let Some(root) = &args.sys_root {
+        sysroot::set(root);
+    }
+    
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<MemorySensor>(args.json) {
+            eprintln!("Error listing memory devices: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
     
     // Validate that critical > warning
     if args.critical <= args.warning {
@@ -112,8 +176,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         args.critical,
         args.include_swap,
         args.show_available,
-    ) {
-        Ok(sensor) => sensor,
+    )
+        .and_then(|sensor| sensor.with_psi_thresholds(args.psi_warning, args.psi_critical))
+        .and_then(|sensor| match &args.cgroup {
+            Some(path) => sensor.with_cgroup(path),
+            None => Ok(sensor),
+        })
+    {
+        Ok(sensor) => sensor.with_metric(args.metric),
         Err(e) => {
             eprintln!("Failed to create memory sensor: {}", e);
             process::exit(1);
@@ -136,7 +206,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Load global configuration and apply command line overrides
     let global_config = GlobalConfig::load().unwrap_or_default();
-    let mut config = global_config.to_sensor_config()
+    let mut config = global_config.to_sensor_config_for("memory")
         .with_update_interval(Duration::from_millis(args.interval))
         .apply_color_overrides(
             args.icon_color,
@@ -150,11 +220,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config = config.with_icon_style(icon_style);
     }
     
+    // Load sensor-specific configuration from global config
+    if let Some(serde_json::Value::Object(map)) = global_config.sensors.get("memory") {
+        for (key, value) in map {
+            config = config.with_custom(key.clone(), value.clone());
+        }
+    }
+    
+    if args.show_breakdown {
+        config = config.with_custom("show_breakdown", serde_json::Value::Bool(true));
+    }
+    
+    if args.class_on_pressure {
+        config = config.with_custom("class_on_pressure", serde_json::Value::Bool(true));
+    }
+    if args.notify_oom {
+        config = config.with_custom("notify_oom", serde_json::Value::Bool(true));
+    }
+    
+    if args.fold_compressed_swap {
+        config = config.with_custom("fold_compressed_swap", serde_json::Value::Bool(true));
+    }
+    
     memory_sensor.configure(config)?;
     
     if args.once {
         // One-shot mode: read once and exit
-        match memory_sensor.read() {
+        match memory_sensor.read_styled() {
             Ok(output) => {
                 println!("{}", serde_json::to_string(&output)?);
             }
@@ -165,12 +257,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     } else {
         // Continuous mode: loop and output readings
-        let mut interval = time::interval(Duration::from_millis(args.interval));
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
         
         loop {
             interval.tick().await;
+            if session_lock::wait_async(memory_sensor.config()).await {
+                interval.reset();
+            }
             
-            match memory_sensor.read() {
+            match memory_sensor.read_styled() {
                 Ok(output) => {
                     println!("{}", serde_json::to_string(&output)?);
                     io::stdout().flush()?;
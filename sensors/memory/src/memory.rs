@@ -3,11 +3,54 @@
 //! This module provides memory usage monitoring by reading from `/proc/meminfo`
 //! and calculating memory usage percentages including RAM and optionally swap.
 
+use crate::compressed::{self, ZramDevice};
+use crate::hugepages::{self, HugePages};
+use crate::vmstat::{SwapRateTracker, SwapRates, VmStat};
 use waysensor_rs_core::{
-    format, Sensor, SensorConfig, SensorError, WaybarOutput,
+    cgroup, DiscoveredItem, Discoverable, format, notify, procfs, psi::PressureStats, sysroot, Sensor, SensorConfig, SensorError,
+    TooltipDetail, trend::UsageTrend, WaybarOutput,
 };
-use std::fs;
-use std::path::Path;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// MemAvailable samples used for the exhaustion estimate (one per reading).
+const AVAILABLE_TREND_SAMPLES: usize = 60;
+
+/// Primary metric shown in the bar text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MemoryMetric {
+    /// Used (or available) percentage from `/proc/meminfo`
+    #[default]
+    Usage,
+    /// Share of time all tasks stalled on memory, from `/proc/pressure/memory`
+    Psi,
+}
+
+impl fmt::Display for MemoryMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Usage => "usage",
+            Self::Psi => "psi",
+        };
+        f.write_str(name)
+    }
+}
+
+impl std::str::FromStr for MemoryMetric {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "usage" | "used" => Ok(Self::Usage),
+            "psi" | "pressure" => Ok(Self::Psi),
+            _ => Err(SensorError::config_with_value(
+                "Invalid memory metric. Valid options: usage, psi",
+                s,
+            )),
+        }
+    }
+}
 
 /// Memory usage sensor that monitors system memory utilization.
 ///
@@ -18,7 +61,7 @@ use std::path::Path;
 /// # Examples
 ///
 /// ```rust
-/// use waysensor_memory::MemorySensor;
+/// use waysensor_rs_memory::MemorySensor;
 /// use waysensor_rs_core::Sensor;
 ///
 /// // Monitor RAM only, show used percentage, 70% warning, 90% critical
@@ -35,7 +78,46 @@ pub struct MemorySensor {
     critical_threshold: f64,
     include_swap: bool,
     show_available: bool,
+    metric: MemoryMetric,
+    psi_warning: f64,
+    psi_critical: f64,
     usage_history: Vec<f64>,
+    swap_tracker: SwapRateTracker,
+    swap_rates: Option<SwapRates>,
+    swap_rate_history: Vec<f64>,
+    oom_kills: OomKills,
+    available_trend: UsageTrend,
+    cgroup: Option<CgroupScope>,
+}
+
+/// OOM killer activity from the `oom_kill` counter.
+#[derive(Debug, Default)]
+struct OomKills {
+    /// Counter value at the first reading
+    baseline: Option<u64>,
+    /// Counter value at the latest reading
+    total: u64,
+    /// Kills since the previous reading
+    new: u64,
+}
+
+impl OomKills {
+    fn update(&mut self, total: u64) {
+        let previous = *self.baseline.get_or_insert(total);
+        self.new = total.saturating_sub(previous.max(self.total));
+        self.total = total;
+    }
+
+    fn since_start(&self) -> u64 {
+        self.total.saturating_sub(self.baseline.unwrap_or(self.total))
+    }
+}
+
+/// cgroup the sensor is scoped to.
+#[derive(Debug)]
+struct CgroupScope {
+    path: String,
+    dir: PathBuf,
 }
 
 /// Memory statistics from `/proc/meminfo`.
@@ -51,12 +133,22 @@ pub struct MemoryInfo {
     pub mem_available: u64,
     /// Memory used for buffers
     pub mem_buffers: u64,
-    /// Memory used for page cache
+    /// Memory used for page cache (includes shared memory)
     pub mem_cached: u64,
+    /// Shared memory (tmpfs, shm segments, GPU buffers)
+    pub mem_shmem: u64,
+    /// Kernel slab allocations
+    pub mem_slab: u64,
+    /// Part of the slab the kernel can reclaim under pressure
+    pub mem_slab_reclaimable: u64,
     /// Total swap space
     pub swap_total: u64,
     /// Free swap space
     pub swap_free: u64,
+    /// RAM used by the zswap compressed pool
+    pub zswap_pool: u64,
+    /// Uncompressed size of the pages held in the zswap pool
+    pub zswapped: u64,
 }
 
 impl MemoryInfo {
@@ -125,19 +217,50 @@ impl MemoryInfo {
         }
     }
     
+    /// Estimate time until available memory runs out at the given trend.
+    ///
+    /// `available_per_sec` is the change of MemAvailable in bytes per second;
+    /// only a negative trend leads to exhaustion.
+    #[must_use]
+    pub fn time_until_exhausted(&self, available_per_sec: f64) -> Option<Duration> {
+        if available_per_sec >= 0.0 {
+            return None; // Not shrinking
+        }
+        
+        let seconds = self.mem_available as f64 / -available_per_sec;
+        seconds.is_finite().then(|| Duration::from_secs_f64(seconds))
+    }
+    
+    /// Split physical memory into what it is being used for.
+    #[must_use]
+    pub fn breakdown(&self) -> MemoryBreakdown {
+        let cache = self.mem_cached.saturating_sub(self.mem_shmem);
+        MemoryBreakdown {
+            apps: self.mem_total
+                .saturating_sub(self.mem_free)
+                .saturating_sub(self.mem_buffers)
+                .saturating_sub(self.mem_cached)
+                .saturating_sub(self.mem_slab),
+            buffers: self.mem_buffers,
+            cache,
+            shared: self.mem_shmem,
+            slab: self.mem_slab,
+            free: self.mem_free,
+        }
+    }
+    
     /// Parse memory information from `/proc/meminfo`.
     ///
     /// # Errors
     ///
     /// Returns [`SensorError::Parse`] if the meminfo format is invalid.
     pub fn from_proc_meminfo() -> Result<Self, SensorError> {
-        Self::from_proc_meminfo_path(Path::new("/proc/meminfo"))
+        Self::from_proc_meminfo_path(&sysroot::path("/proc/meminfo"))
     }
     
     /// Parse memory information from a meminfo file path (useful for testing).
     pub fn from_proc_meminfo_path(path: &Path) -> Result<Self, SensorError> {
-        let content = fs::read_to_string(path)?;
-        Self::parse_meminfo_content(&content)
+        procfs::read_with(path, Self::parse_meminfo_content)?
     }
     
     /// Parse memory information from meminfo content.
@@ -147,8 +270,13 @@ impl MemoryInfo {
         let mut mem_available = 0;
         let mut mem_buffers = 0;
         let mut mem_cached = 0;
+        let mut mem_shmem = 0;
+        let mut mem_slab = 0;
+        let mut mem_slab_reclaimable = 0;
         let mut swap_total = 0;
         let mut swap_free = 0;
+        let mut zswap_pool = 0;
+        let mut zswapped = 0;
         
         for line in content.lines() {
             let parts: Vec<&str> = line.split_whitespace().collect();
@@ -171,8 +299,13 @@ impl MemoryInfo {
                 "MemAvailable" => mem_available = value_bytes,
                 "Buffers" => mem_buffers = value_bytes,
                 "Cached" => mem_cached = value_bytes,
+                "Shmem" => mem_shmem = value_bytes,
+                "Slab" => mem_slab = value_bytes,
+                "SReclaimable" => mem_slab_reclaimable = value_bytes,
                 "SwapTotal" => swap_total = value_bytes,
                 "SwapFree" => swap_free = value_bytes,
+                "Zswap" => zswap_pool = value_bytes,
+                "Zswapped" => zswapped = value_bytes,
                 _ => {} // Ignore other fields
             }
         }
@@ -188,12 +321,34 @@ impl MemoryInfo {
             mem_available,
             mem_buffers,
             mem_cached,
+            mem_shmem,
+            mem_slab,
+            mem_slab_reclaimable,
             swap_total,
             swap_free,
+            zswap_pool,
+            zswapped,
         })
     }
 }
 
+/// Physical memory split by use, in bytes. The parts add up to `MemTotal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryBreakdown {
+    /// Process memory: everything not accounted for by the kernel caches
+    pub apps: u64,
+    /// Block device buffers
+    pub buffers: u64,
+    /// Page cache, excluding shared memory
+    pub cache: u64,
+    /// Shared memory and tmpfs
+    pub shared: u64,
+    /// Kernel slab
+    pub slab: u64,
+    /// Completely unused memory
+    pub free: u64,
+}
+
 impl MemorySensor {
     /// Path to the proc meminfo file.
     const PROC_MEMINFO_PATH: &'static str = "/proc/meminfo";
@@ -242,10 +397,62 @@ impl MemorySensor {
             critical_threshold: f64::from(critical_threshold),
             include_swap,
             show_available,
+            metric: MemoryMetric::default(),
+            psi_warning: 5.0,
+            psi_critical: 20.0,
             usage_history: Vec::new(),
+            swap_tracker: SwapRateTracker::new(),
+            swap_rates: None,
+            swap_rate_history: Vec::new(),
+            oom_kills: OomKills::default(),
+            available_trend: UsageTrend::new(AVAILABLE_TREND_SAMPLES),
+            cgroup: None,
         })
     }
     
+    /// Select the metric shown in the bar text.
+    #[must_use]
+    pub fn with_metric(mut self, metric: MemoryMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+    
+    /// Set stall percentage thresholds used by [`MemoryMetric::Psi`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error unless `0 <= warning < critical <= 100`.
+    pub fn with_psi_thresholds(mut self, warning: f64, critical: f64) -> Result<Self, SensorError> {
+        if !(0.0..critical).contains(&warning) || critical > 100.0 {
+            return Err(SensorError::config(format!(
+                "Critical pressure ({}%) must be greater than warning pressure ({}%) and at most 100%",
+                critical, warning
+            )));
+        }
+        self.psi_warning = warning;
+        self.psi_critical = critical;
+        Ok(self)
+    }
+    
+    /// Scope usage and pressure to a cgroup v2 group such as
+    /// `system.slice/postgresql.service` or `system.slice/docker-<id>.scope`.
+    ///
+    /// Usage is `memory.current` relative to the group's `memory.max`, or to
+    /// total RAM when it has no limit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cgroup does not exist.
+    pub fn with_cgroup(mut self, path: &str) -> Result<Self, SensorError> {
+        let dir = cgroup::resolve(path)?;
+        let scope_name = path.trim_matches('/').replace('/', "-");
+        if !scope_name.is_empty() {
+            self.name = format!("memory-{}", scope_name);
+        }
+        self.cgroup = Some(CgroupScope { path: path.to_owned(), dir });
+        Ok(self)
+    }
+    
     /// Create a new memory sensor with default settings.
     ///
     /// Defaults: 70% warning, 90% critical, no swap, show used percentage.
@@ -269,8 +476,364 @@ impl MemorySensor {
         Self::new(warning_threshold, critical_threshold, false, true)
     }
     
+    /// One gauge line per memory category, replacing the single "Used" line.
+    fn format_breakdown(&self, info: &MemoryInfo) -> String {
+        let breakdown = info.breakdown();
+        let visuals = &self.config.visuals;
+        let slab_note = format!(", {} reclaimable", format::bytes_to_human(info.mem_slab_reclaimable));
+        
+        [
+            ("Apps", breakdown.apps, ""),
+            ("Buffers", breakdown.buffers, ""),
+            ("Cache", breakdown.cache, ""),
+            ("Shared", breakdown.shared, ""),
+            ("Slab", breakdown.slab, slab_note.as_str()),
+            ("Free", breakdown.free, ""),
+        ]
+        .into_iter()
+        .map(|(label, bytes, note)| {
+            let percent = if info.mem_total == 0 { 0.0 } else { bytes as f64 / info.mem_total as f64 * 100.0 };
+            let amount = format!("{} ({:.1}%{})", format::bytes_to_human(bytes), percent, note);
+            let value = if visuals.tooltip_gauges {
+                format!("{} {}", format::create_gauge(percent, visuals.gauge_width, visuals.gauge_style), amount)
+            } else {
+                amount
+            };
+            format::key_value(label, &value, &self.config)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+    }
+    
+    /// Original and compressed size of each zram device and the zswap pool.
+    fn format_compressed(&self, info: &MemoryInfo, zram: &[ZramDevice]) -> Option<String> {
+        let line = |label: &str, original: u64, compressed: u64, ratio: Option<f64>| {
+            let ratio = ratio.map(|r| format!(" ({:.1}x)", r)).unwrap_or_default();
+            format::key_value(label, &format!("{} → {}{}",
+                format::bytes_to_human(original), format::bytes_to_human(compressed), ratio), &self.config)
+        };
+        
+        let mut lines: Vec<String> = zram.iter()
+            .map(|dev| line(&dev.name, dev.original, dev.compressed, dev.ratio()))
+            .collect();
+        if info.zswap_pool > 0 {
+            lines.push(line("zswap", info.zswapped, info.zswap_pool, info.zswap_ratio()));
+        }
+        if lines.is_empty() {
+            return None;
+        }
+        
+        Some(format!("{}\n{}", format::key_only("Compressed Memory", &self.config), lines.join("\n")))
+    }
+    
+    /// Static hugepage pool and transparent hugepage usage.
+    fn format_hugepages(&self) -> Option<String> {
+        let pages = HugePages::read().ok()?;
+        let thp_mode = hugepages::thp_mode();
+        let mut lines = Vec::new();
+        
+        if pages.total > 0 || pages.surplus > 0 {
+            lines.push(format::key_value("Static Pool", &format!("{} of {} pages used ({} × {})",
+                pages.used(), pages.total, pages.total, format::bytes_to_human(pages.page_size)), &self.config));
+            let idle = pages.idle_bytes();
+            let idle_value = if idle > 0 {
+                format!("{} idle, {} reserved, {} surplus",
+                    format::bytes_to_human(idle), pages.reserved, pages.surplus)
+            } else {
+                format!("none idle, {} reserved, {} surplus", pages.reserved, pages.surplus)
+            };
+            lines.push(format::key_value("Pool State", &idle_value, &self.config));
+        }
+        if let Some(mode) = thp_mode {
+            lines.push(format::key_value("Transparent", &format!("{} anon, {} shmem, {} file (THP {})",
+                format::bytes_to_human(pages.anon_thp), format::bytes_to_human(pages.shmem_thp),
+                format::bytes_to_human(pages.file_thp), mode), &self.config));
+        }
+        if lines.is_empty() {
+            return None;
+        }
+        
+        Some(format!("{}\n{}", format::key_only("Huge Pages", &self.config), lines.join("\n")))
+    }
+    
+    /// Sample `/proc/vmstat`: record the paging rate and count OOM kills.
+    fn update_vmstat(&mut self) {
+        let Ok(stat) = VmStat::read() else { return };
+        self.oom_kills.update(stat.oom_kill);
+        self.swap_rates = self.swap_tracker.update(stat, std::time::Instant::now());
+        if let Some(rates) = self.swap_rates {
+            self.swap_rate_history.push(rates.total());
+            if self.swap_rate_history.len() > self.config.visuals.sparkline_length {
+                self.swap_rate_history.remove(0);
+            }
+        }
+    }
+    
+    /// Swap-in/swap-out rates, with a sparkline of recent paging.
+    fn format_swap_activity(&self) -> Option<String> {
+        let rates = self.swap_rates?;
+        let mut lines = vec![format::key_value("Activity", &format!("↓ {}/s in, ↑ {}/s out ({:.0} pages/s)",
+            format::bytes_to_human(rates.bytes_in() as u64),
+            format::bytes_to_human(rates.bytes_out() as u64),
+            rates.total()), &self.config)];
+        
+        if self.config.visuals.sparklines && self.swap_rate_history.len() > 1 {
+            let sparkline = format::create_sparkline(&self.swap_rate_history, self.config.visuals.sparkline_style);
+            if !sparkline.is_empty() {
+                let colored_sparkline = format::colored_sparkline(&sparkline, self.config.sparkline_color.as_deref());
+                lines.push(format::key_value("Paging History", &colored_sparkline, &self.config));
+            }
+        }
+        Some(lines.join("\n"))
+    }
+    
+    /// Format memory pressure as a tooltip line.
+    fn format_pressure_line(&self, pressure: &PressureStats) -> String {
+        let full = pressure.full.map(|f| f.avg10).unwrap_or(0.0);
+        format::key_value(
+            "Pressure",
+            &format!("{:.1}% some / {:.1}% full stalled (10s)", pressure.some.avg10, full),
+            &self.config,
+        )
+    }
+    
+    /// Memory pressure of the configured cgroup, or of the whole system.
+    fn read_pressure(&self) -> Result<PressureStats, SensorError> {
+        match &self.cgroup {
+            Some(scope) => PressureStats::read_from_path(scope.dir.join("memory.pressure")),
+            None => PressureStats::read("memory"),
+        }
+    }
+    
+    /// Read the memory usage of the configured cgroup.
+    fn read_cgroup(&mut self, info: &MemoryInfo) -> Result<WaybarOutput, SensorError> {
+        let Some(scope) = self.cgroup.as_ref() else {
+            return Err(SensorError::config("No cgroup configured"));
+        };
+        let stat = cgroup::MemoryStat::read(&scope.dir)?;
+        let cgroup_path = scope.path.clone();
+        
+        let capacity = stat.max.unwrap_or(info.mem_total).min(info.mem_total);
+        let used_percent = if capacity > 0 {
+            (stat.current as f64 / capacity as f64 * 100.0).clamp(0.0, 100.0)
+        } else {
+            0.0
+        };
+        
+        self.usage_history.push(used_percent);
+        if self.usage_history.len() > self.config.visuals.sparkline_length {
+            self.usage_history.remove(0);
+        }
+        
+        let text = format::with_icon_and_colors(&format!("{:3.0}%", used_percent), &self.config.icons.memory, &self.config);
+        
+        let gauge = if self.config.visuals.tooltip_gauges {
+            format!(" {}", format::create_gauge(used_percent, self.config.visuals.gauge_width, self.config.visuals.gauge_style))
+        } else {
+            String::new()
+        };
+        let capacity_label = match stat.max {
+            Some(max) => format!("{} (memory.max)", format::bytes_to_human(max)),
+            None => format!("{} RAM (no limit)", format::bytes_to_human(info.mem_total)),
+        };
+        let mut tooltip_lines = vec![
+            format::key_value("cgroup", &cgroup_path, &self.config),
+            format::key_value("Used", &format!("{} of {} ({:.1}%){}",
+                format::bytes_to_human(stat.current), capacity_label, used_percent, gauge), &self.config),
+            format::key_value("Anon / File / Kernel", &format!("{} / {} / {}",
+                format::bytes_to_human(stat.anon), format::bytes_to_human(stat.file),
+                format::bytes_to_human(stat.kernel)), &self.config),
+        ];
+        if let Some(high) = stat.high {
+            tooltip_lines.push(format::key_value("Throttled Above", &format::bytes_to_human(high), &self.config));
+        }
+        if let Some(swap) = stat.swap_current.filter(|&swap| swap > 0) {
+            tooltip_lines.push(format::key_value("Swap", &format::bytes_to_human(swap), &self.config));
+        }
+        if let Ok(pressure) = self.read_pressure() {
+            tooltip_lines.push(self.format_pressure_line(&pressure));
+        }
+        tooltip_lines.extend(self.format_oom_line());
+        
+        if self.config.visuals.sparklines && self.usage_history.len() > 1 {
+            let sparkline = format::create_sparkline(&self.usage_history, self.config.visuals.sparkline_style);
+            if !sparkline.is_empty() {
+                let colored_sparkline = format::colored_sparkline(&sparkline, self.config.sparkline_color.as_deref());
+                tooltip_lines.push(format::key_value("Usage History", &colored_sparkline, &self.config));
+            }
+        }
+        
+        Ok(format::themed_output(
+            text,
+            Some(tooltip_lines.join("\n")),
+            Some(used_percent.round() as u8),
+            used_percent,
+            self.warning_threshold,
+            self.critical_threshold,
+            &self.config.theme,
+        ))
+    }
+    
+    /// Read the used (or available) percentage from `/proc/meminfo`.
+    fn read_usage(&mut self, info: &MemoryInfo, zram: &[ZramDevice]) -> WaybarOutput {
+        let icon = &self.config.icons.memory;
+        
+        // Determine what to display and how to theme it
+        let (text, percentage, value_for_theming) = if self.show_available {
+            // Show available memory percentage
+            let available_percent = info.mem_available_percentage();
+            let display_text = format!("{:.0}% free", available_percent);
+            let text_with_icon = format::with_icon_and_colors(&display_text, icon, &self.config);
+            
+            // For theming, we want high *usage* to trigger warnings, so invert available
+            let usage_for_theming = 100.0 - available_percent;
+            let percentage_for_display = usage_for_theming.round().clamp(0.0, 100.0) as u8;
+            
+            (text_with_icon, Some(percentage_for_display), usage_for_theming)
+        } else if self.include_swap {
+            // Show combined RAM + swap usage
+            let used_percent = info.total_used_percentage_with_swap();
+            let display_text = format!("{:3.0}%", used_percent);
+            let text_with_icon = format::with_icon_and_colors(&display_text, icon, &self.config);
+            let percentage_value = used_percent.round().clamp(0.0, 100.0) as u8;
+            
+            (text_with_icon, Some(percentage_value), used_percent)
+        } else {
+            // Show RAM usage only
+            let used_percent = info.mem_used_percentage();
+            let display_text = format!("{:3.0}%", used_percent);
+            let text_with_icon = format::with_icon_and_colors(&display_text, icon, &self.config);
+            let percentage_value = used_percent.round().clamp(0.0, 100.0) as u8;
+            
+            (text_with_icon, Some(percentage_value), used_percent)
+        };
+        
+        // Track usage history for sparklines
+        self.usage_history.push(value_for_theming);
+        if self.usage_history.len() > self.config.visuals.sparkline_length {
+            self.usage_history.remove(0);
+        }
+        
+        let tooltip = self.build_tooltip(info, zram);
+        
+        let mut output = format::themed_output(
+            text,
+            Some(tooltip),
+            percentage,
+            value_for_theming,
+            self.warning_threshold,
+            self.critical_threshold,
+            &self.config.theme,
+        );
+        
+        // Optionally let real memory stalls, not cache-inflated usage, decide the class
+        let class_on_pressure = self.config.get_custom("class_on_pressure")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if class_on_pressure {
+            if let Some(full) = self.read_pressure().ok().and_then(|p| p.full) {
+                let theme = &self.config.theme;
+                output.class = Some(theme.class_for_thresholds(full.avg10, self.psi_warning, self.psi_critical).to_owned());
+            }
+        }
+        
+        output
+    }
+    
+    /// Estimated time until MemAvailable hits zero, while it is shrinking.
+    fn format_exhaustion_line(&self, info: &MemoryInfo) -> Option<String> {
+        let per_sec = self.available_trend.trend_per_sec()?;
+        let remaining = info.time_until_exhausted(per_sec)?.as_secs();
+        // Slow drift over a minute of samples says nothing about the next day
+        if remaining >= 24 * 3600 {
+            return None;
+        }
+        let eta = if remaining >= 3600 {
+            format!("{}h {:02}m", remaining / 3600, remaining % 3600 / 60)
+        } else {
+            format!("{}m {:02}s", remaining / 60, remaining % 60)
+        };
+        Some(format::key_value("Exhausted In", &format!("≈ {} (-{}/s)",
+            eta, format::bytes_to_human(-per_sec as u64)), &self.config))
+    }
+    
+    /// OOM kill counts as a tooltip line, if the OOM killer ever ran.
+    fn format_oom_line(&self) -> Option<String> {
+        let kills = &self.oom_kills;
+        (kills.total > 0).then(|| format::key_value("OOM Kills", &format!("{} since boot, {} since start",
+            kills.total, kills.since_start()), &self.config))
+    }
+    
+    /// Flag the reading after an OOM kill as critical, and notify if enabled.
+    fn apply_oom_alert(&self, output: &mut WaybarOutput) {
+        let kills = &self.oom_kills;
+        if kills.new > 0 {
+            let label = if kills.new == 1 { "OOM".to_string() } else { format!("OOM ×{}", kills.new) };
+            output.text = format!("{} {}", label, output.text);
+            output.class = Some(self.config.theme.critical.clone());
+
+            if self.config.get_custom("notify_oom").and_then(|v| v.as_bool()).unwrap_or(false) {
+                let body = match kills.new {
+                    1 => "The kernel OOM killer terminated a process".to_string(),
+                    n => format!("The kernel OOM killer terminated {} processes", n),
+                };
+                notify::send("Out of memory", &body, notify::Urgency::Critical);
+            }
+        }
+    }
+    
+    /// Read the memory pressure stall metric.
+    ///
+    /// Uses the `full` line: time in which every runnable task waited on
+    /// memory (reclaim, swap-in, refaults), which is when the desktop stutters.
+    fn read_psi(&mut self, info: &MemoryInfo) -> Result<WaybarOutput, SensorError> {
+        let pressure = self.read_pressure()?;
+        let full = pressure.full.unwrap_or_default();
+        let stalled = full.avg10;
+        
+        self.usage_history.push(stalled);
+        if self.usage_history.len() > self.config.visuals.sparkline_length {
+            self.usage_history.remove(0);
+        }
+        
+        let text = format::with_icon_and_colors(&format!("{:4.1}%", stalled), &self.config.icons.memory, &self.config);
+        
+        let mut tooltip_lines = vec![
+            format::key_value("Fully Stalled", &format!("{:.2}% / {:.2}% / {:.2}% (10s/60s/300s)",
+                full.avg10, full.avg60, full.avg300), &self.config),
+            format::key_value("Partly Stalled", &format!("{:.2}% / {:.2}% / {:.2}%",
+                pressure.some.avg10, pressure.some.avg60, pressure.some.avg300), &self.config),
+            format::key_value("Used", &format!("{} of {} ({:.1}%)",
+                format::bytes_to_human(info.mem_used()), format::bytes_to_human(info.mem_total),
+                info.mem_used_percentage()), &self.config),
+            format::key_value("Thresholds", &format!("⚠️ {:.0}% / 🔴 {:.0}%",
+                self.psi_warning, self.psi_critical), &self.config),
+        ];
+        tooltip_lines.extend(self.format_oom_line());
+        
+        if self.config.visuals.sparklines && self.usage_history.len() > 1 {
+            let sparkline = format::create_sparkline(&self.usage_history, self.config.visuals.sparkline_style);
+            if !sparkline.is_empty() {
+                let colored_sparkline = format::colored_sparkline(&sparkline, self.config.sparkline_color.as_deref());
+                tooltip_lines.push(format::key_value("Pressure History", &colored_sparkline, &self.config));
+            }
+        }
+        
+        let percentage = ((stalled / self.psi_critical) * 100.0).round().clamp(0.0, 100.0) as u8;
+        
+        Ok(format::themed_output(
+            text,
+            Some(tooltip_lines.join("\n")),
+            Some(percentage),
+            stalled,
+            self.psi_warning,
+            self.psi_critical,
+            &self.config.theme,
+        ))
+    }
+    
     /// Build a detailed tooltip with memory information.
-    fn build_tooltip(&self, info: &MemoryInfo) -> String {
+    fn build_tooltip(&self, info: &MemoryInfo, zram: &[ZramDevice]) -> String {
         use waysensor_rs_core::format;
         
         let mem_used = info.mem_used();
@@ -303,8 +866,27 @@ impl MemorySensor {
         let available_line = format::key_value("Available", &format!("{} ({:.1}%)", 
             format::bytes_to_human(info.mem_available), mem_available_percent), &self.config);
         let total_line = format::key_value("Total", &format::bytes_to_human(info.mem_total), &self.config);
+        let available_line = match self.format_exhaustion_line(info) {
+            Some(exhaustion) => format!("{}\n{}", available_line, exhaustion),
+            None => available_line,
+        };
+        
+        let show_breakdown = self.config.get_custom("show_breakdown")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let mut tooltip = if show_breakdown {
+            format!("{}\n{}\n{}\n{}", header, self.format_breakdown(info), available_line, total_line)
+        } else {
+            format!("{}\n{}\n{}\n{}", header, used_line, available_line, total_line)
+        };
         
-        let mut tooltip = format!("{}\n{}\n{}\n{}", header, used_line, available_line, total_line);
+        // Usage includes page cache, so stalls are the better sign of real shortage
+        if let Ok(pressure) = PressureStats::read("memory") {
+            tooltip.push_str(&format!("\n{}", self.format_pressure_line(&pressure)));
+        }
+        if let Some(oom) = self.format_oom_line() {
+            tooltip.push_str(&format!("\n{}", oom));
+        }
         
         // Add swap information if swap is available
         if info.swap_total > 0 {
@@ -336,6 +918,9 @@ impl MemorySensor {
             let swap_total_line = format::key_value("Total", &format::bytes_to_human(info.swap_total), &self.config);
             
             tooltip.push_str(&format!("\n\n{}\n{}\n{}\n{}", swap_header, swap_used_line, swap_free_line, swap_total_line));
+            if let Some(activity) = self.format_swap_activity() {
+                tooltip.push_str(&format!("\n{}", activity));
+            }
             
             // Add combined stats if including swap in calculations
             if self.include_swap {
@@ -368,6 +953,16 @@ impl MemorySensor {
             }
         }
         
+        if let Some(section) = self.format_compressed(info, zram) {
+            tooltip.push_str(&format!("\n\n{}", section));
+        }
+        
+        if self.config.visuals.tooltip_detail == TooltipDetail::Expert {
+            if let Some(section) = self.format_hugepages() {
+                tooltip.push_str(&format!("\n\n{}", section));
+            }
+        }
+        
         // Add sparkline to tooltip if enabled and we have history
         if self.config.visuals.sparklines && self.usage_history.len() > 1 {
             let sparkline = format::create_sparkline(&self.usage_history, self.config.visuals.sparkline_style);
@@ -397,61 +992,59 @@ impl MemorySensor {
     }
 }
 
+impl Discoverable for MemorySensor {
+    const KIND: &'static str = "memory devices";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        let info = MemoryInfo::from_proc_meminfo()?;
+        let mut items = vec![
+            DiscoveredItem::new("ram", "Physical memory")
+                .with_detail("total", format::bytes_to_human(info.mem_total))
+                .with_detail("available", format::bytes_to_human(info.mem_available)),
+        ];
+
+        // Filename Type Size Used Priority, sizes in KiB
+        let swaps = procfs::read_with(sysroot::path("/proc/swaps"), str::to_owned).unwrap_or_default();
+        for line in swaps.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [name, kind, size, used, priority, ..] = fields[..] else { continue };
+            let kib = |v: &str| format::bytes_to_human(v.parse::<u64>().unwrap_or(0) * 1024);
+            items.push(DiscoveredItem::new(name, format!("Swap ({})", kind))
+                .with_detail("size", kib(size))
+                .with_detail("used", kib(used))
+                .with_detail("priority", priority));
+        }
+
+        Ok(items)
+    }
+}
+
 impl Sensor for MemorySensor {
     type Error = SensorError;
     
     fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
-        let info = MemoryInfo::from_proc_meminfo()?;
-        
-        let icon = &self.config.icons.memory;
-        
-        // Determine what to display and how to theme it
-        let (text, percentage, value_for_theming) = if self.show_available {
-            // Show available memory percentage
-            let available_percent = info.mem_available_percentage();
-            let display_text = format!("{:.0}% free", available_percent);
-            let text_with_icon = format::with_icon_and_colors(&display_text, icon, &self.config);
-            
-            // For theming, we want high *usage* to trigger warnings, so invert available
-            let usage_for_theming = 100.0 - available_percent;
-            let percentage_for_display = usage_for_theming.round().clamp(0.0, 100.0) as u8;
-            
-            (text_with_icon, Some(percentage_for_display), usage_for_theming)
-        } else if self.include_swap {
-            // Show combined RAM + swap usage
-            let used_percent = info.total_used_percentage_with_swap();
-            let display_text = format!("{:3.0}%", used_percent);
-            let text_with_icon = format::with_icon_and_colors(&display_text, icon, &self.config);
-            let percentage_value = used_percent.round().clamp(0.0, 100.0) as u8;
-            
-            (text_with_icon, Some(percentage_value), used_percent)
-        } else {
-            // Show RAM usage only
-            let used_percent = info.mem_used_percentage();
-            let display_text = format!("{:3.0}%", used_percent);
-            let text_with_icon = format::with_icon_and_colors(&display_text, icon, &self.config);
-            let percentage_value = used_percent.round().clamp(0.0, 100.0) as u8;
-            
-            (text_with_icon, Some(percentage_value), used_percent)
-        };
+        let mut info = MemoryInfo::from_proc_meminfo()?;
+        let zram = compressed::zram_devices();
         
-        // Track usage history for sparklines
-        self.usage_history.push(value_for_theming);
-        if self.usage_history.len() > self.config.visuals.sparkline_length {
-            self.usage_history.remove(0);
+        let fold_compressed_swap = self.config.get_custom("fold_compressed_swap")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if fold_compressed_swap {
+            let (zram_swap_size, zram_swap_used) = procfs::read_with(sysroot::path("/proc/swaps"), compressed::zram_swap_usage)
+                .unwrap_or_default();
+            info = info.fold_compressed_swap(zram_swap_size, zram_swap_used);
         }
         
-        let tooltip = self.build_tooltip(&info);
+        self.update_vmstat();
+        self.available_trend.add_sample(Instant::now(), info.mem_available as f64);
         
-        Ok(format::themed_output(
-            text,
-            Some(tooltip),
-            percentage,
-            value_for_theming,
-            self.warning_threshold,
-            self.critical_threshold,
-            &self.config.theme,
-        ))
+        let mut output = match self.metric {
+            MemoryMetric::Psi => self.read_psi(&info)?,
+            MemoryMetric::Usage if self.cgroup.is_some() => self.read_cgroup(&info)?,
+            MemoryMetric::Usage => self.read_usage(&info, &zram),
+        };
+        self.apply_oom_alert(&mut output);
+        Ok(output)
     }
     
     fn name(&self) -> &str {
@@ -469,7 +1062,7 @@ impl Sensor for MemorySensor {
     
     fn check_availability(&self) -> Result<(), Self::Error> {
         // Check if /proc/meminfo exists and is readable
-        if !Path::new(Self::PROC_MEMINFO_PATH).exists() {
+        if !sysroot::path(Self::PROC_MEMINFO_PATH).exists() {
             return Err(SensorError::unavailable(format!(
                 "{} does not exist (not a Linux system?)", 
                 Self::PROC_MEMINFO_PATH
@@ -524,8 +1117,13 @@ SwapFree:        6144000 kB
             mem_available: 12 * 1024 * 1024 * 1024, // 12 GB
             mem_buffers: 1024 * 1024 * 1024,    // 1 GB
             mem_cached: 2 * 1024 * 1024 * 1024, // 2 GB
+            mem_shmem: 0,
+            mem_slab: 0,
+            mem_slab_reclaimable: 0,
             swap_total: 8 * 1024 * 1024 * 1024, // 8 GB
             swap_free: 6 * 1024 * 1024 * 1024,  // 6 GB
+            zswap_pool: 0,
+            zswapped: 0,
         };
 
         // Memory calculations
@@ -543,6 +1141,32 @@ SwapFree:        6144000 kB
         assert!((info.total_used_percentage_with_swap() - 25.0).abs() < 0.1); // 6/24 = 25%
     }
 
+    #[test]
+    fn test_memory_breakdown() {
+        let content = r#"
+MemTotal:       16000000 kB
+MemFree:         2000000 kB
+MemAvailable:    9000000 kB
+Buffers:          500000 kB
+Cached:          5000000 kB
+Shmem:           1000000 kB
+Slab:             800000 kB
+SReclaimable:     600000 kB
+"#;
+        let info = MemoryInfo::parse_meminfo_content(content).unwrap();
+        assert_eq!(info.mem_shmem, 1_000_000 * 1024);
+        assert_eq!(info.mem_slab_reclaimable, 600_000 * 1024);
+        
+        let breakdown = info.breakdown();
+        assert_eq!(breakdown.apps, 7_700_000 * 1024);
+        assert_eq!(breakdown.cache, 4_000_000 * 1024);
+        assert_eq!(breakdown.shared, 1_000_000 * 1024);
+        assert_eq!(
+            breakdown.apps + breakdown.buffers + breakdown.cache + breakdown.shared + breakdown.slab + breakdown.free,
+            info.mem_total
+        );
+    }
+    
     #[test]
     fn test_memory_info_fallback() {
         // Test content without MemAvailable (older kernels)
@@ -587,4 +1211,65 @@ SwapFree:        6144000 kB
         let sensor = MemorySensor::show_available(60, 80).unwrap();
         assert!(sensor.show_available);
     }
+
+    #[test]
+    fn test_time_until_exhausted() {
+        let info = MemoryInfo {
+            mem_total: 16 * 1024 * 1024 * 1024,
+            mem_free: 0,
+            mem_available: 600 * 1024 * 1024,
+            mem_buffers: 0,
+            mem_cached: 0,
+            mem_shmem: 0,
+            mem_slab: 0,
+            mem_slab_reclaimable: 0,
+            swap_total: 0,
+            swap_free: 0,
+            zswap_pool: 0,
+            zswapped: 0,
+        };
+        
+        let mut trend = UsageTrend::new(AVAILABLE_TREND_SAMPLES);
+        let start = Instant::now();
+        trend.add_sample(start, 900.0 * 1024.0 * 1024.0);
+        trend.add_sample(start + Duration::from_secs(30), 600.0 * 1024.0 * 1024.0);
+        let per_sec = trend.trend_per_sec().unwrap();
+        assert_eq!(per_sec, -10.0 * 1024.0 * 1024.0);
+        
+        assert_eq!(info.time_until_exhausted(per_sec), Some(Duration::from_secs(60)));
+        assert_eq!(info.time_until_exhausted(0.0), None);
+        assert_eq!(info.time_until_exhausted(1024.0), None);
+    }
+    
+    #[test]
+    fn test_oom_kill_tracking() {
+        let mut kills = OomKills::default();
+        kills.update(3);
+        assert_eq!((kills.new, kills.since_start()), (0, 0));
+        kills.update(5);
+        assert_eq!((kills.new, kills.since_start()), (2, 2));
+        kills.update(5);
+        assert_eq!((kills.new, kills.since_start()), (0, 2));
+    }
+    
+    #[test]
+    fn test_missing_cgroup_is_rejected() {
+        let sensor = MemorySensor::with_defaults().unwrap();
+        assert!(sensor.with_cgroup("waysensor-test-missing.slice").is_err());
+    }
+    
+    #[test]
+    fn test_memory_metric_selection() {
+        assert_eq!("pressure".parse::<MemoryMetric>().unwrap(), MemoryMetric::Psi);
+        assert_eq!("usage".parse::<MemoryMetric>().unwrap(), MemoryMetric::Usage);
+        assert!("swap".parse::<MemoryMetric>().is_err());
+
+        let sensor = MemorySensor::with_defaults().unwrap()
+            .with_psi_thresholds(2.0, 10.0).unwrap()
+            .with_metric(MemoryMetric::Psi);
+        assert_eq!(sensor.metric, MemoryMetric::Psi);
+        assert_eq!(sensor.psi_critical, 10.0);
+        assert!(MemorySensor::with_defaults().unwrap().with_psi_thresholds(10.0, 5.0).is_err());
+        assert!(MemorySensor::with_defaults().unwrap().with_psi_thresholds(10.0, 150.0).is_err());
+    }
 }
\ No newline at end of file
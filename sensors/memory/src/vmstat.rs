@@ -0,0 +1,134 @@
+//! Event counters from `/proc/vmstat`.
+//!
+//! Swap occupancy only says how much was pushed out at some point; the
+//! swap-in/swap-out counters show whether the system is paging right now.
+//! `oom_kill` counts processes the kernel OOM killer has terminated.
+
+use std::sync::OnceLock;
+use std::time::Instant;
+use waysensor_rs_core::{procfs, sysroot, SensorError};
+
+/// Bytes per page as counted by `pswpin`/`pswpout`.
+///
+/// 4 KiB on x86, but arm64 kernels can use 16 KiB (Apple silicon) or 64 KiB
+/// pages, so ask the kernel instead of assuming.
+pub fn page_size() -> u64 {
+    static PAGE_SIZE: OnceLock<u64> = OnceLock::new();
+    *PAGE_SIZE.get_or_init(|| {
+        // SAFETY: sysconf has no preconditions; it returns -1 for unknown names.
+        let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        u64::try_from(size).ok().filter(|&size| size > 0).unwrap_or(4096)
+    })
+}
+
+/// Cumulative counters read from `/proc/vmstat`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VmStat {
+    /// Pages swapped in since boot
+    pub pswpin: u64,
+    /// Pages swapped out since boot
+    pub pswpout: u64,
+    /// Processes killed by the OOM killer since boot (4.13+)
+    pub oom_kill: u64,
+}
+
+impl VmStat {
+    /// Read the running system's counters.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `/proc/vmstat` cannot be read.
+    pub fn read() -> Result<Self, SensorError> {
+        Ok(procfs::read_with(sysroot::path("/proc/vmstat"), Self::parse)?)
+    }
+
+    /// Parse the contents of `/proc/vmstat`. Missing counters read as zero.
+    pub fn parse(content: &str) -> Self {
+        let mut stat = Self::default();
+        for line in content.lines() {
+            let Some((key, value)) = line.split_once(' ') else { continue };
+            let Ok(value) = value.trim().parse::<u64>() else { continue };
+            match key {
+                "pswpin" => stat.pswpin = value,
+                "pswpout" => stat.pswpout = value,
+                "oom_kill" => stat.oom_kill = value,
+                _ => {}
+            }
+        }
+        stat
+    }
+}
+
+/// Swap-in and swap-out rates in pages per second.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SwapRates {
+    pub pages_in: f64,
+    pub pages_out: f64,
+}
+
+impl SwapRates {
+    /// Swap-in rate in bytes per second.
+    pub fn bytes_in(&self) -> f64 {
+        self.pages_in * page_size() as f64
+    }
+
+    /// Swap-out rate in bytes per second.
+    pub fn bytes_out(&self) -> f64 {
+        self.pages_out * page_size() as f64
+    }
+
+    /// Combined paging rate in pages per second.
+    pub fn total(&self) -> f64 {
+        self.pages_in + self.pages_out
+    }
+}
+
+/// Turns successive [`VmStat`] snapshots into swap rates.
+#[derive(Debug, Default)]
+pub struct SwapRateTracker {
+    prev: Option<(VmStat, Instant)>,
+}
+
+impl SwapRateTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a snapshot taken at `now`, returning the rates since the
+    /// previous one (`None` for the first snapshot).
+    pub fn update(&mut self, stat: VmStat, now: Instant) -> Option<SwapRates> {
+        let (prev, then) = self.prev.replace((stat, now))?;
+        let elapsed = now.duration_since(then).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+        Some(SwapRates {
+            pages_in: stat.pswpin.saturating_sub(prev.pswpin) as f64 / elapsed,
+            pages_out: stat.pswpout.saturating_sub(prev.pswpout) as f64 / elapsed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_swap_rates() {
+        let content = "nr_free_pages 123456\npswpin 1000\npswpout 5000\npgfault 42\noom_kill 2\n";
+        assert_eq!(VmStat::parse(content), VmStat { pswpin: 1000, pswpout: 5000, oom_kill: 2 });
+
+        let mut tracker = SwapRateTracker::new();
+        let start = Instant::now();
+        assert_eq!(tracker.update(VmStat { pswpin: 1000, pswpout: 5000, oom_kill: 0 }, start), None);
+
+        let rates = tracker
+            .update(VmStat { pswpin: 1200, pswpout: 5000, oom_kill: 0 }, start + Duration::from_secs(2))
+            .unwrap();
+        assert_eq!(rates.pages_in, 100.0);
+        assert_eq!(rates.pages_out, 0.0);
+        assert_eq!(rates.bytes_in(), 100.0 * page_size() as f64);
+        assert_eq!(rates.total(), 100.0);
+    }
+}
@@ -0,0 +1,216 @@
+//! IPv4 and IPv6 addresses of an interface.
+//!
+//! Addresses come from an `RTM_GETADDR` netlink dump rather than parsing
+//! `ip addr`, which would cost a process per read and break with every
+//! iproute2 output tweak. Link-local IPv6 addresses exist on every interface
+//! and are hidden unless asked for. With masking enabled the host part is
+//! replaced, so the tooltip can be shown on screen shares and screenshots.
+
+use crate::netlink;
+use netlink_packet_route::{
+    address::Nla, AddressMessage, RtnlMessage, AF_INET, AF_INET6, IFA_F_DEPRECATED, IFA_F_TEMPORARY,
+    RT_SCOPE_HOST, RT_SCOPE_LINK,
+};
+use std::{
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+use waysensor_rs_core::{format, SensorConfig, SensorError};
+
+/// An address assigned to an interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceAddress {
+    pub address: IpAddr,
+    pub prefix_len: u8,
+    /// Only valid on the link (`fe80::/10`, `169.254.0.0/16`)
+    pub link_local: bool,
+    /// IPv6 privacy extension address
+    pub temporary: bool,
+    /// Still assigned but no longer used for new connections
+    pub deprecated: bool,
+}
+
+impl InterfaceAddress {
+    /// Decode one address of an `RTM_NEWADDR` message.
+    pub fn from_message(message: &AddressMessage) -> Option<Self> {
+        let bytes = |family_len: usize| {
+            // IFA_LOCAL is the own address on point-to-point links, where
+            // IFA_ADDRESS is the peer's
+            message.nlas.iter()
+                .find_map(|nla| match nla {
+                    Nla::Local(bytes) => Some(bytes),
+                    _ => None,
+                })
+                .or_else(|| message.nlas.iter().find_map(|nla| match nla {
+                    Nla::Address(bytes) => Some(bytes),
+                    _ => None,
+                }))
+                .filter(|bytes| bytes.len() == family_len)
+        };
+        let address = match message.header.family as u16 {
+            AF_INET => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(bytes(4)?.as_slice()).ok()?)),
+            AF_INET6 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(bytes(16)?.as_slice()).ok()?)),
+            _ => return None,
+        };
+        // IFA_FLAGS carries the full 32 bit flags, the header only the low 8
+        let flags = message.nlas.iter()
+            .find_map(|nla| match nla {
+                Nla::Flags(flags) => Some(*flags),
+                _ => None,
+            })
+            .unwrap_or(message.header.flags as u32);
+        Some(Self {
+            address,
+            prefix_len: message.header.prefix_len,
+            link_local: matches!(message.header.scope, RT_SCOPE_LINK | RT_SCOPE_HOST),
+            temporary: flags & IFA_F_TEMPORARY != 0,
+            deprecated: flags & IFA_F_DEPRECATED != 0,
+        })
+    }
+
+    /// Addresses of `interface`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the interface does not exist or the netlink dump
+    /// fails.
+    pub fn query(interface: &str) -> Result<Vec<Self>, SensorError> {
+        let index: u32 = fs::read_to_string(format!("/sys/class/net/{}/ifindex", interface))?
+            .trim()
+            .parse()
+            .map_err(|_| SensorError::parse(format!("Invalid ifindex of {}", interface)))?;
+        let replies = netlink::dump(RtnlMessage::GetAddress(AddressMessage::default()))?;
+        Ok(replies.iter()
+            .filter_map(|reply| match reply {
+                RtnlMessage::NewAddress(message) if message.header.index == index => Self::from_message(message),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Address with the host part replaced: the first two octets of IPv4,
+    /// the first two groups of IPv6 remain.
+    pub fn masked(&self) -> String {
+        match self.address {
+            IpAddr::V4(v4) => {
+                let [a, b, ..] = v4.octets();
+                format!("{}.{}.x.x", a, b)
+            }
+            IpAddr::V6(v6) => {
+                let [a, b, ..] = v6.segments();
+                format!("{:x}:{:x}:x:x::", a, b)
+            }
+        }
+    }
+}
+
+/// Which addresses the tooltip shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AddressDisplay {
+    pub ipv4: bool,
+    pub ipv6: bool,
+    pub link_local: bool,
+    pub mask: bool,
+}
+
+impl Default for AddressDisplay {
+    fn default() -> Self {
+        Self {
+            ipv4: true,
+            ipv6: true,
+            link_local: false,
+            mask: false,
+        }
+    }
+}
+
+impl AddressDisplay {
+    /// Read the `show_ipv4`, `show_ipv6`, `show_link_local` and
+    /// `mask_addresses` config keys.
+    pub fn from_config(config: &SensorConfig) -> Self {
+        let flag = |key: &str, default: bool| config.get_custom(key).and_then(|v| v.as_bool()).unwrap_or(default);
+        let defaults = Self::default();
+        Self {
+            ipv4: flag("show_ipv4", defaults.ipv4),
+            ipv6: flag("show_ipv6", defaults.ipv6),
+            link_local: flag("show_link_local", defaults.link_local),
+            mask: flag("mask_addresses", defaults.mask),
+        }
+    }
+
+    /// Whether any address is shown at all.
+    pub fn enabled(&self) -> bool {
+        self.ipv4 || self.ipv6
+    }
+
+    /// Tooltip lines for `addresses`, one per shown address.
+    pub fn tooltip_lines(&self, addresses: &[InterfaceAddress], config: &SensorConfig) -> Vec<String> {
+        addresses.iter()
+            .filter(|a| if a.address.is_ipv4() { self.ipv4 } else { self.ipv6 })
+            .filter(|a| self.link_local || !a.link_local)
+            .map(|a| {
+                let family = if a.address.is_ipv4() { "IPv4" } else { "IPv6" };
+                let address = if self.mask { a.masked() } else { a.address.to_string() };
+                let mut value = format!("{}/{}", address, a.prefix_len);
+                if a.temporary {
+                    value.push_str(" (temporary)");
+                }
+                if a.deprecated {
+                    value.push_str(" (deprecated)");
+                }
+                format::key_value(family, &value, config)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netlink_packet_route::{AddressHeader, RT_SCOPE_UNIVERSE};
+
+    fn message(family: u16, scope: u8, nlas: Vec<Nla>) -> AddressMessage {
+        let mut message = AddressMessage::default();
+        message.header = AddressHeader { family: family as u8, prefix_len: 64, flags: 0, scope, index: 2 };
+        message.nlas = nlas;
+        message
+    }
+
+    #[test]
+    fn test_interface_addresses() {
+        let v4 = message(AF_INET, RT_SCOPE_UNIVERSE, vec![
+            Nla::Address(vec![10, 8, 0, 1]),
+            Nla::Local(vec![192, 168, 1, 5]),
+        ]);
+        let v4 = InterfaceAddress::from_message(&v4).unwrap();
+        assert_eq!(v4.address, IpAddr::V4(Ipv4Addr::new(192, 168, 1, 5)));
+        assert_eq!(v4.masked(), "192.168.x.x");
+
+        let global: Ipv6Addr = "2001:db8:1:2::abcd".parse().unwrap();
+        let v6 = message(AF_INET6, RT_SCOPE_UNIVERSE, vec![
+            Nla::Address(global.octets().to_vec()),
+            Nla::Flags(IFA_F_TEMPORARY | 0x200),
+        ]);
+        let v6 = InterfaceAddress::from_message(&v6).unwrap();
+        assert!(v6.temporary && !v6.deprecated && !v6.link_local);
+        assert_eq!(v6.masked(), "2001:db8:x:x::");
+
+        let local: Ipv6Addr = "fe80::1".parse().unwrap();
+        let link = InterfaceAddress::from_message(&message(AF_INET6, RT_SCOPE_LINK, vec![Nla::Address(local.octets().to_vec())])).unwrap();
+        assert!(link.link_local);
+        assert_eq!(InterfaceAddress::from_message(&message(AF_INET6, 0, vec![Nla::Address(vec![1, 2, 3, 4])])), None);
+
+        let config = SensorConfig::default();
+        let addresses = [v4, v6, link];
+        let lines = AddressDisplay::default().tooltip_lines(&addresses, &config);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].contains("2001:db8:1:2::abcd/64 (temporary)"));
+        let masked = AddressDisplay { ipv4: false, mask: true, link_local: true, ..AddressDisplay::default() };
+        let lines = masked.tooltip_lines(&addresses, &config);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("2001:db8:x:x::/64") && lines[1].contains("fe80:0:x:x::/64"));
+
+        let lo = InterfaceAddress::query("lo").unwrap();
+        assert!(lo.iter().any(|a| a.address == IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    }
+}
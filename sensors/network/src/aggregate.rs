@@ -0,0 +1,228 @@
+//! Bonds, bridges and teams.
+//!
+//! Traffic of an aggregated link flows through its member ports. Bond and
+//! team devices already count everything their members carry, but a
+//! bridge's own counters only see traffic to and from the host, so frames
+//! forwarded between ports would be missing; bridge throughput is therefore
+//! summed over the ports. Members are listed in the tooltip with their link
+//! and their role: active or backup in a bond, the STP port state in a
+//! bridge.
+
+use crate::network::NetworkStats;
+use std::{fs, path::Path};
+use waysensor_rs_core::{format, SensorConfig, SensorError};
+
+const SYS_CLASS_NET: &str = "/sys/class/net";
+
+/// Kind of aggregated link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MasterKind {
+    Bond,
+    Bridge,
+    Team,
+}
+
+impl MasterKind {
+    /// Kind of `interface` below the sysfs class directory `sys`, `None` for
+    /// plain interfaces.
+    pub fn detect_in(sys: &Path, interface: &str) -> Option<Self> {
+        let device = sys.join(interface);
+        if device.join("bonding").is_dir() {
+            Some(Self::Bond)
+        } else if device.join("bridge").is_dir() {
+            Some(Self::Bridge)
+        } else if fs::read_to_string(device.join("uevent")).is_ok_and(|uevent| uevent.lines().any(|l| l == "DEVTYPE=team")) {
+            Some(Self::Team)
+        } else {
+            None
+        }
+    }
+
+    pub fn detect(interface: &str) -> Option<Self> {
+        Self::detect_in(Path::new(SYS_CLASS_NET), interface)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Bond => "bond",
+            Self::Bridge => "bridge",
+            Self::Team => "team",
+        }
+    }
+}
+
+/// Master an interface is enslaved to, e.g. `bond0` for a bond member.
+pub fn master_of_in(sys: &Path, interface: &str) -> Option<String> {
+    let link = fs::read_link(sys.join(interface).join("master")).ok()?;
+    Some(link.file_name()?.to_string_lossy().into_owned())
+}
+
+pub fn master_of(interface: &str) -> Option<String> {
+    master_of_in(Path::new(SYS_CLASS_NET), interface)
+}
+
+/// A member port of an aggregated link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    /// Whether the member has link
+    pub up: bool,
+    /// Role within the master: `active`/`backup` for bonds, the STP port
+    /// state for bridges
+    pub role: Option<String>,
+}
+
+/// Name of a bridge port's STP state in `brport/state`.
+fn stp_state(state: &str) -> Option<&'static str> {
+    match state.trim() {
+        "0" => Some("disabled"),
+        "1" => Some("listening"),
+        "2" => Some("learning"),
+        "3" => Some("forwarding"),
+        "4" => Some("blocking"),
+        _ => None,
+    }
+}
+
+/// Members of `master` below `sys`, sorted by name.
+pub fn members_in(sys: &Path, master: &str) -> Vec<Member> {
+    let Ok(entries) = fs::read_dir(sys.join(master)) else {
+        return Vec::new();
+    };
+    let mut members: Vec<Member> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.strip_prefix("lower_").map(str::to_owned))
+        .map(|name| {
+            let device = sys.join(&name);
+            let read = |file: &str| fs::read_to_string(device.join(file)).ok();
+            let up = match read("bonding_slave/mii_status") {
+                Some(mii) => mii.trim() == "up",
+                None => read("carrier").is_some_and(|carrier| carrier.trim() == "1"),
+            };
+            let role = read("bonding_slave/state")
+                .map(|state| state.trim().to_owned())
+                .or_else(|| read("brport/state").and_then(|state| stp_state(&state)).map(str::to_owned));
+            Member { name, up, role }
+        })
+        .collect();
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+    members
+}
+
+pub fn members(master: &str) -> Vec<Member> {
+    members_in(Path::new(SYS_CLASS_NET), master)
+}
+
+/// Counters summed over several interfaces.
+pub(crate) fn sum_stats<I: IntoIterator<Item = NetworkStats>>(stats: I) -> NetworkStats {
+    stats.into_iter().fold(
+        NetworkStats { rx_bytes: 0, tx_bytes: 0, rx_packets: 0, tx_packets: 0 },
+        |total, stats| NetworkStats {
+            rx_bytes: total.rx_bytes + stats.rx_bytes,
+            tx_bytes: total.tx_bytes + stats.tx_bytes,
+            rx_packets: total.rx_packets + stats.rx_packets,
+            tx_packets: total.tx_packets + stats.tx_packets,
+        },
+    )
+}
+
+/// An aggregated link being monitored.
+#[derive(Debug, Clone)]
+pub struct Aggregate {
+    pub master: String,
+    pub kind: MasterKind,
+}
+
+impl Aggregate {
+    /// `Some` if `interface` is a bond, bridge or team.
+    pub fn detect(interface: &str) -> Option<Self> {
+        MasterKind::detect(interface).map(|kind| Self { master: interface.to_owned(), kind })
+    }
+
+    /// Counters of the link: the master's own for bonds and teams, the sum
+    /// over the ports for bridges.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the counters of the master cannot be read; ports
+    /// that disappear while reading are skipped.
+    pub(crate) fn stats(&self) -> Result<NetworkStats, SensorError> {
+        let master = NetworkStats::read(&self.master)?;
+        if self.kind != MasterKind::Bridge {
+            return Ok(master);
+        }
+        let ports = members(&self.master);
+        if ports.is_empty() {
+            return Ok(master);
+        }
+        Ok(sum_stats(ports.iter().filter_map(|port| NetworkStats::read(&port.name).ok())))
+    }
+
+    /// Tooltip lines: the kind of link and one line per member.
+    pub fn tooltip_lines(&self, config: &SensorConfig) -> Vec<String> {
+        let members = members(&self.master);
+        let mut lines = vec![format::key_value(
+            "Members",
+            &format!("{} ({} ports)", self.kind.label(), members.len()),
+            config,
+        )];
+        lines.extend(members.iter().map(|member| {
+            let link = if member.up { "🟢 up" } else { "🔴 down" };
+            let value = match &member.role {
+                Some(role) => format!("{}, {}", link, role),
+                None => link.to_string(),
+            };
+            format::key_value(&format!("  {}", member.name), &value, config)
+        }));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn test_aggregated_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let sys = dir.path();
+        let write = |path: &str, content: &str| {
+            let path = sys.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        };
+        write("bond0/bonding/mode", "active-backup 1\n");
+        write("eth0/bonding_slave/state", "active\n");
+        write("eth0/bonding_slave/mii_status", "up\n");
+        write("eth1/bonding_slave/state", "backup\n");
+        write("eth1/bonding_slave/mii_status", "down\n");
+        write("br0/bridge/stp_state", "1\n");
+        write("veth1/brport/state", "3\n");
+        write("veth1/carrier", "1\n");
+        write("team0/uevent", "DEVTYPE=team\nINTERFACE=team0\n");
+        for (master, member) in [("bond0", "eth0"), ("bond0", "eth1"), ("br0", "veth1")] {
+            symlink(sys.join(member), sys.join(master).join(format!("lower_{}", member))).unwrap();
+            symlink(sys.join(master), sys.join(member).join("master")).unwrap();
+        }
+
+        let kinds = ["bond0", "br0", "team0", "eth0"].map(|name| MasterKind::detect_in(sys, name));
+        let bond = members_in(sys, "bond0");
+        let bridge = members_in(sys, "br0");
+        let master = master_of_in(sys, "eth1");
+        let unenslaved = master_of_in(sys, "team0");
+
+        assert_eq!(kinds, [Some(MasterKind::Bond), Some(MasterKind::Bridge), Some(MasterKind::Team), None]);
+        assert_eq!(bond, vec![
+            Member { name: "eth0".to_string(), up: true, role: Some("active".to_string()) },
+            Member { name: "eth1".to_string(), up: false, role: Some("backup".to_string()) },
+        ]);
+        assert_eq!(bridge, vec![Member { name: "veth1".to_string(), up: true, role: Some("forwarding".to_string()) }]);
+        assert_eq!(master.as_deref(), Some("bond0"));
+        assert_eq!(unenslaved, None);
+
+        let stats = |bytes: u64| NetworkStats { rx_bytes: bytes, tx_bytes: 2 * bytes, rx_packets: 1, tx_packets: 1 };
+        let total = sum_stats([stats(100), stats(50)]);
+        assert_eq!((total.rx_bytes, total.tx_bytes, total.rx_packets), (150, 300, 2));
+    }
+}
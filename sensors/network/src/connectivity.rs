@@ -0,0 +1,441 @@
+//! Whether the machine is actually online.
+//!
+//! Throughput says little when the network is down: a zero reading looks the
+//! same on an idle link. The connectivity mode walks the path outwards
+//! instead — link carrier, default route, gateway reachability and finally an
+//! HTTP request to a check URL that answers `204 No Content`, the same probe
+//! Android and NetworkManager use. Any other answer means something on the
+//! way rewrote the request, usually a captive portal.
+//!
+//! The public IP address asks a third-party service and is therefore only
+//! fetched when enabled with `--public-ip` or the `public_ip` config key.
+
+use crate::gateway::{self, GatewayProbe, Reachability};
+use crate::network::NetworkSensor;
+use crate::wireless;
+use waysensor_rs_core::{format, Sensor, SensorConfig, SensorError, Theme, WaybarOutput};
+use std::{
+    fs,
+    net::IpAddr,
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// Check URL answering `204 No Content` when the internet is reachable.
+pub const DEFAULT_PROBE_URL: &str = "http://connectivitycheck.gstatic.com/generate_204";
+
+/// Service answering with the caller's address as plain text.
+pub const DEFAULT_PUBLIC_IP_URL: &str = "https://api.ipify.org";
+
+/// Default time between gateway and internet checks.
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long the public IP is reused while the connection stays up.
+pub const PUBLIC_IP_REFRESH: Duration = Duration::from_secs(600);
+
+/// Seconds an HTTP request may take before the check counts as failed.
+const HTTP_TIMEOUT_SECS: u32 = 3;
+
+/// Carrier state of an interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkState {
+    /// Link is up
+    Up,
+    /// Interface is enabled but has no carrier (cable out, not associated)
+    NoCarrier,
+    /// Interface is administratively down or gone
+    Down,
+}
+
+impl LinkState {
+    /// Decide from sysfs `operstate` and `carrier`. The carrier file cannot
+    /// be read while the interface is administratively down.
+    pub fn parse(operstate: &str, carrier: Option<&str>) -> Self {
+        match (operstate.trim(), carrier.map(str::trim)) {
+            // Tunnels and PPP links report "unknown" while working
+            ("up", _) | ("unknown", Some("1")) => Self::Up,
+            (_, None) => Self::Down,
+            _ => Self::NoCarrier,
+        }
+    }
+
+    /// Current state of `interface`.
+    pub fn read(interface: &str) -> Self {
+        let device = Path::new("/sys/class/net").join(interface);
+        let Ok(operstate) = fs::read_to_string(device.join("operstate")) else {
+            return Self::Down;
+        };
+        let carrier = fs::read_to_string(device.join("carrier")).ok();
+        Self::parse(&operstate, carrier.as_deref())
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::NoCarrier => "no carrier",
+            Self::Down => "down",
+        }
+    }
+}
+
+/// Result of the HTTP check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Internet {
+    /// The check URL answered as expected
+    Online,
+    /// Something else answered, most likely a login page
+    CaptivePortal,
+    /// No answer within the timeout
+    Offline,
+    /// The check could not be run (no `curl` binary)
+    Unknown,
+}
+
+impl Internet {
+    /// Classify the HTTP status of the check URL, 0 meaning no response.
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            204 => Self::Online,
+            0 => Self::Offline,
+            _ => Self::CaptivePortal,
+        }
+    }
+}
+
+/// Overall connection state, from the first hop that failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// The internet is reachable
+    Online,
+    /// A captive portal intercepts requests
+    CaptivePortal,
+    /// The gateway answers but the internet does not
+    NoInternet,
+    /// The gateway does not answer
+    GatewayUnreachable,
+    /// The link is up but has no default route
+    NoRoute,
+    /// No link
+    Disconnected,
+}
+
+impl ConnectionState {
+    /// Combine the individual checks. A working HTTP check wins over an
+    /// unanswered ping, as many routers drop ICMP.
+    pub fn from_checks(link: LinkState, gateway: Option<Reachability>, internet: Internet) -> Self {
+        if link != LinkState::Up {
+            return Self::Disconnected;
+        }
+        let Some(gateway) = gateway else {
+            return Self::NoRoute;
+        };
+        match (internet, gateway) {
+            (Internet::Online, _) => Self::Online,
+            (Internet::CaptivePortal, _) => Self::CaptivePortal,
+            (_, Reachability::Unreachable) => Self::GatewayUnreachable,
+            (Internet::Offline, _) => Self::NoInternet,
+            (Internet::Unknown, _) => Self::Online,
+        }
+    }
+
+    /// Short description for the bar.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Online => "online",
+            Self::CaptivePortal => "captive portal",
+            Self::NoInternet => "no internet",
+            Self::GatewayUnreachable => "gateway down",
+            Self::NoRoute => "no route",
+            Self::Disconnected => "disconnected",
+        }
+    }
+
+    /// CSS class: critical when disconnected, warning while only partly
+    /// connected.
+    pub fn class(self, theme: &Theme) -> &str {
+        match self {
+            Self::Online => &theme.normal,
+            Self::Disconnected => &theme.critical,
+            _ => &theme.warning,
+        }
+    }
+}
+
+/// HTTP status of `url` fetched through `interface`, 0 if nothing answered.
+fn http_status(url: &str, interface: Option<&str>) -> Option<u16> {
+    let mut command = Command::new("curl");
+    command.args(["-s", "-o", "/dev/null", "-w", "%{http_code}", "--max-time", &HTTP_TIMEOUT_SECS.to_string()]);
+    if let Some(interface) = interface {
+        command.args(["--interface", interface]);
+    }
+    // curl prints 000 and fails when it gets no response
+    let output = command.arg(url).output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().parse().unwrap_or(0))
+}
+
+/// Parse the plain-text answer of a public IP service.
+pub fn parse_public_ip(body: &str) -> Option<IpAddr> {
+    body.trim().parse().ok()
+}
+
+/// Rate-limited HTTP connectivity check.
+#[derive(Debug)]
+pub struct InternetProbe {
+    url: String,
+    interval: Duration,
+    last: Option<(Internet, Instant)>,
+}
+
+impl InternetProbe {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            url: DEFAULT_PROBE_URL.to_string(),
+            interval,
+            last: None,
+        }
+    }
+
+    /// Internet state through `interface`, checking it if the last result is
+    /// stale.
+    pub fn check(&mut self, interface: &str) -> Internet {
+        if let Some((result, at)) = self.last {
+            if at.elapsed() < self.interval {
+                return result;
+            }
+        }
+        let result = http_status(&self.url, Some(interface)).map_or(Internet::Unknown, Internet::from_status);
+        self.last = Some((result, Instant::now()));
+        result
+    }
+
+    /// Forget the last result so the next read checks again.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Cached public IP address, fetched only while online.
+#[derive(Debug)]
+pub struct PublicIp {
+    url: String,
+    last: Option<(Option<IpAddr>, Instant)>,
+}
+
+impl Default for PublicIp {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_PUBLIC_IP_URL.to_string(),
+            last: None,
+        }
+    }
+}
+
+impl PublicIp {
+    /// Public address, refreshed after [`PUBLIC_IP_REFRESH`].
+    pub fn update(&mut self) -> Option<IpAddr> {
+        if let Some((address, at)) = self.last {
+            if at.elapsed() < PUBLIC_IP_REFRESH {
+                return address;
+            }
+        }
+        let address = Command::new("curl")
+            .args(["-s", "--max-time", &HTTP_TIMEOUT_SECS.to_string(), &self.url])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| parse_public_ip(&String::from_utf8_lossy(&output.stdout)));
+        self.last = Some((address, Instant::now()));
+        address
+    }
+
+    /// Forget the address, e.g. after the connection dropped.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Link, gateway and internet reachability of the primary or a given interface.
+#[derive(Debug)]
+pub struct ConnectivitySensor {
+    name: String,
+    config: SensorConfig,
+    /// Interface to check, the primary one when `None`
+    interface: Option<String>,
+    gateway_probe: GatewayProbe,
+    internet_probe: InternetProbe,
+    /// Set when the public IP may be looked up
+    public_ip: Option<PublicIp>,
+    last_state: Option<ConnectionState>,
+}
+
+impl ConnectivitySensor {
+    pub fn new(interface: Option<String>) -> Result<Self, SensorError> {
+        if let Some(interface) = &interface {
+            if !Path::new("/sys/class/net").join(interface).exists() {
+                return Err(SensorError::Unavailable {
+                    reason: format!("Network interface not found: {}", interface),
+                    is_temporary: false,
+                });
+            }
+        }
+
+        Ok(Self {
+            name: "network-connectivity".to_string(),
+            config: SensorConfig::default(),
+            interface,
+            gateway_probe: GatewayProbe::new(DEFAULT_CHECK_INTERVAL),
+            internet_probe: InternetProbe::new(DEFAULT_CHECK_INTERVAL),
+            public_ip: None,
+            last_state: None,
+        })
+    }
+
+    /// Look up the public IP address while online.
+    #[must_use]
+    pub fn with_public_ip(mut self, enabled: bool) -> Self {
+        if enabled && self.public_ip.is_none() {
+            self.public_ip = Some(PublicIp::default());
+        }
+        self
+    }
+}
+
+impl Sensor for ConnectivitySensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let interface = self.interface.clone().or_else(|| NetworkSensor::find_primary_interface().ok());
+        let link = interface.as_deref().map_or(LinkState::Down, LinkState::read);
+
+        let mut gateway = None;
+        let mut reachability = None;
+        let mut internet = Internet::Unknown;
+        if let (Some(interface), LinkState::Up) = (interface.as_deref(), link) {
+            gateway = gateway::default_gateway(interface);
+            reachability = gateway.map(|gateway| self.gateway_probe.check(gateway, interface));
+            if gateway.is_some() {
+                internet = self.internet_probe.check(interface);
+            }
+        }
+        let state = ConnectionState::from_checks(link, reachability, internet);
+
+        // A new connection may come with a new address and a login page
+        if self.last_state.is_some_and(|last| last != state) {
+            self.internet_probe.reset();
+            if let Some(public_ip) = &mut self.public_ip {
+                public_ip.reset();
+            }
+        }
+        self.last_state = Some(state);
+        let public_ip = match &mut self.public_ip {
+            Some(public_ip) if state == ConnectionState::Online => public_ip.update(),
+            _ => None,
+        };
+
+        let icon = if interface.as_deref().is_some_and(wireless::is_wireless) {
+            &self.config.icons.network_wifi
+        } else {
+            &self.config.icons.network_ethernet
+        };
+        let text = format::with_icon_and_colors(state.label(), icon, &self.config);
+
+        let config = &self.config;
+        let mut lines = vec![
+            format::key_value("Interface", interface.as_deref().unwrap_or("none"), config),
+            format::key_value("Link", link.label(), config),
+        ];
+        if link == LinkState::Up {
+            let gateway = match (gateway, reachability) {
+                (Some(gateway), Some(Reachability::Reachable(rtt))) => format!("{} 🟢 {:.1} ms", gateway, rtt),
+                (Some(gateway), Some(Reachability::Unreachable)) => format!("{} 🔴 unreachable", gateway),
+                (Some(gateway), _) => format!("{} ⚪ not checked", gateway),
+                (None, _) => "none (no default route)".to_string(),
+            };
+            lines.push(format::key_value("Gateway", &gateway, config));
+            lines.push(format::key_value("Internet", match internet {
+                Internet::Online => "🟢 reachable",
+                Internet::CaptivePortal => "🟡 captive portal, log in to continue",
+                Internet::Offline => "🔴 unreachable",
+                Internet::Unknown => "⚪ not checked (curl not installed)",
+            }, config));
+        }
+        if self.public_ip.is_some() {
+            let address = public_ip.map_or_else(|| "unknown".to_string(), |address| address.to_string());
+            lines.push(format::key_value("Public IP", &address, config));
+        }
+
+        Ok(WaybarOutput::new(text)
+            .with_tooltip(lines.join("\n"))
+            .with_class(state.class(&config.theme)))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        if let Some(secs) = config.get_custom("gateway_check_interval_secs").and_then(|v| v.as_u64()) {
+            self.gateway_probe.set_interval(Duration::from_secs(secs));
+            self.internet_probe.interval = Duration::from_secs(secs);
+        }
+        if let Some(url) = config.get_custom("connectivity_url").and_then(|v| v.as_str()) {
+            self.internet_probe.url = url.to_string();
+        }
+        if config.get_custom("public_ip").and_then(|v| v.as_bool()) == Some(true) && self.public_ip.is_none() {
+            self.public_ip = Some(PublicIp::default());
+        }
+        if let (Some(public_ip), Some(url)) = (&mut self.public_ip, config.get_custom("public_ip_url").and_then(|v| v.as_str())) {
+            public_ip.url = url.to_string();
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_state() {
+        assert_eq!(LinkState::parse("up\n", Some("1\n")), LinkState::Up);
+        assert_eq!(LinkState::parse("unknown\n", Some("1\n")), LinkState::Up);
+        assert_eq!(LinkState::parse("down\n", Some("0\n")), LinkState::NoCarrier);
+        assert_eq!(LinkState::parse("dormant\n", Some("1\n")), LinkState::NoCarrier);
+        assert_eq!(LinkState::parse("down\n", None), LinkState::Down);
+
+        assert_eq!(Internet::from_status(204), Internet::Online);
+        assert_eq!(Internet::from_status(302), Internet::CaptivePortal);
+        assert_eq!(Internet::from_status(200), Internet::CaptivePortal);
+        assert_eq!(Internet::from_status(0), Internet::Offline);
+
+        let up = LinkState::Up;
+        let reachable = Some(Reachability::Reachable(1.2));
+        let state = ConnectionState::from_checks;
+        assert_eq!(state(up, reachable, Internet::Online), ConnectionState::Online);
+        assert_eq!(state(up, Some(Reachability::Unreachable), Internet::Online), ConnectionState::Online);
+        assert_eq!(state(up, reachable, Internet::CaptivePortal), ConnectionState::CaptivePortal);
+        assert_eq!(state(up, reachable, Internet::Offline), ConnectionState::NoInternet);
+        assert_eq!(state(up, Some(Reachability::Unreachable), Internet::Offline), ConnectionState::GatewayUnreachable);
+        assert_eq!(state(up, reachable, Internet::Unknown), ConnectionState::Online);
+        assert_eq!(state(up, None, Internet::Unknown), ConnectionState::NoRoute);
+        assert_eq!(state(LinkState::NoCarrier, None, Internet::Unknown), ConnectionState::Disconnected);
+
+        let theme = Theme::default();
+        assert_eq!(ConnectionState::Disconnected.class(&theme), theme.critical);
+        assert_eq!(ConnectionState::CaptivePortal.class(&theme), theme.warning);
+        assert_eq!(ConnectionState::Online.class(&theme), theme.normal);
+
+        assert_eq!(parse_public_ip("203.0.113.7\n"), Some("203.0.113.7".parse().unwrap()));
+        assert_eq!(parse_public_ip("2001:db8::1"), Some("2001:db8::1".parse().unwrap()));
+        assert_eq!(parse_public_ip("<html>Login</html>"), None);
+
+        assert!(ConnectivitySensor::new(Some("waysensor-test0".to_string())).is_err());
+        assert_eq!(LinkState::read("waysensor-test0"), LinkState::Down);
+    }
+}
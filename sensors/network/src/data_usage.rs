@@ -0,0 +1,271 @@
+//! Daily and monthly transfer totals, persisted across restarts.
+//!
+//! The kernel's interface counters start from zero at every boot, and the
+//! sensor restarts with every login. Like vnstat, the totals are kept in a
+//! small JSON file per interface in the XDG state directory instead: each
+//! update adds the counter delta since the last one to the current day.
+//! Traffic from before the first run is not counted. A different boot ID,
+//! or counters that went backwards (driver reload, re-created interface),
+//! means the counters restarted and count in full.
+
+use chrono::{Datelike, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use waysensor_rs_core::{format, SensorConfig, Theme};
+
+/// Minimum time between two writes of the state file.
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Days of history kept, enough for the current and the previous month.
+const RETENTION_DAYS: u64 = 93;
+
+/// Default share of the data cap in percent that turns the module warning.
+pub const DEFAULT_CAP_WARNING: f64 = 80.0;
+
+/// Bytes received and sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub rx: u64,
+    pub tx: u64,
+}
+
+impl Transfer {
+    pub fn total(&self) -> u64 {
+        self.rx + self.tx
+    }
+}
+
+impl std::ops::AddAssign for Transfer {
+    fn add_assign(&mut self, other: Self) {
+        self.rx += other.rx;
+        self.tx += other.tx;
+    }
+}
+
+/// Persisted state of one interface.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct UsageState {
+    /// Boot the counters below were read in
+    boot_id: String,
+    /// Kernel counters at the last update
+    counters: Transfer,
+    /// Transfer per local day, keyed by date
+    days: BTreeMap<NaiveDate, Transfer>,
+}
+
+/// First day of the billing period containing `today`, for periods starting
+/// on `billing_day` of each month (clamped to 1–28).
+pub fn period_start(today: NaiveDate, billing_day: u32) -> NaiveDate {
+    let billing_day = billing_day.clamp(1, 28);
+    let start = today.with_day(billing_day).unwrap_or(today);
+    if today.day() >= billing_day {
+        start
+    } else {
+        start.checked_sub_months(Months::new(1)).unwrap_or(start)
+    }
+}
+
+/// Transfer totals of one interface.
+#[derive(Debug)]
+pub struct DataUsage {
+    /// State file; `None` keeps the totals in memory only
+    path: Option<PathBuf>,
+    state: UsageState,
+    /// Boot ID of the running system
+    boot_id: String,
+    last_save: Option<Instant>,
+    /// Monthly data cap in bytes
+    pub cap: Option<u64>,
+    /// Share of the cap in percent that is a warning
+    pub cap_warning: f64,
+    /// Day of the month the cap resets
+    pub billing_day: u32,
+}
+
+impl DataUsage {
+    /// Totals of `interface`, loaded from its state file if there is one.
+    pub fn load(interface: &str) -> Self {
+        let path = state_path(interface);
+        let state = path.as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let boot_id = fs::read_to_string("/proc/sys/kernel/random/boot_id")
+            .map(|id| id.trim().to_owned())
+            .unwrap_or_default();
+        Self::with_state(path, state, boot_id)
+    }
+
+    /// Totals that are never written to disk.
+    pub fn in_memory(boot_id: &str) -> Self {
+        Self::with_state(None, UsageState::default(), boot_id.to_owned())
+    }
+
+    fn with_state(path: Option<PathBuf>, state: UsageState, boot_id: String) -> Self {
+        Self {
+            path,
+            state,
+            boot_id,
+            last_save: None,
+            cap: None,
+            cap_warning: DEFAULT_CAP_WARNING,
+            billing_day: 1,
+        }
+    }
+
+    /// Account the kernel counters `counters` read on `today`.
+    ///
+    /// The state file is written at most every [`SAVE_INTERVAL`]; a state
+    /// that cannot be saved still counts for this run, so write errors are
+    /// ignored.
+    pub fn update(&mut self, counters: Transfer, today: NaiveDate) {
+        let state = &mut self.state;
+        let restarted = state.boot_id != self.boot_id
+            || counters.rx < state.counters.rx
+            || counters.tx < state.counters.tx;
+        let delta = if state.boot_id.is_empty() {
+            Transfer::default()
+        } else if restarted {
+            counters
+        } else {
+            Transfer {
+                rx: counters.rx - state.counters.rx,
+                tx: counters.tx - state.counters.tx,
+            }
+        };
+        state.boot_id.clone_from(&self.boot_id);
+        state.counters = counters;
+        *state.days.entry(today).or_default() += delta;
+
+        let cutoff = today - chrono::Days::new(RETENTION_DAYS);
+        state.days.retain(|&day, _| day > cutoff);
+
+        if self.last_save.is_some_and(|at| at.elapsed() < SAVE_INTERVAL) {
+            return;
+        }
+        self.last_save = Some(Instant::now());
+        if let Some(path) = &self.path {
+            if let Some(parent) = path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if let Ok(json) = serde_json::to_string(&self.state) {
+                let _ = fs::write(path, json);
+            }
+        }
+    }
+
+    /// Transfer on `day`.
+    pub fn day(&self, day: NaiveDate) -> Transfer {
+        self.state.days.get(&day).copied().unwrap_or_default()
+    }
+
+    /// Transfer in the billing period containing `today`.
+    pub fn period(&self, today: NaiveDate) -> Transfer {
+        let mut total = Transfer::default();
+        for (_, transfer) in self.state.days.range(period_start(today, self.billing_day)..=today) {
+            total += *transfer;
+        }
+        total
+    }
+
+    /// Used share of the data cap in percent.
+    pub fn cap_percent(&self, today: NaiveDate) -> Option<f64> {
+        let cap = self.cap.filter(|&cap| cap > 0)?;
+        Some(self.period(today).total() as f64 / cap as f64 * 100.0)
+    }
+
+    /// CSS class for the data cap: warning from `cap_warning`, critical once
+    /// used up, `None` below or without a cap.
+    pub fn class<'a>(&self, today: NaiveDate, theme: &'a Theme) -> Option<&'a str> {
+        match self.cap_percent(today)? {
+            percent if percent >= 100.0 => Some(theme.critical.as_str()),
+            percent if percent >= self.cap_warning => Some(theme.warning.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Tooltip lines: today, this billing period and the cap.
+    pub fn tooltip_lines(&self, today: NaiveDate, config: &SensorConfig) -> Vec<String> {
+        let transfer = |transfer: Transfer| format!(
+            "↓ {} ↑ {} ({})",
+            format::bytes_to_human(transfer.rx),
+            format::bytes_to_human(transfer.tx),
+            format::bytes_to_human(transfer.total()),
+        );
+        let start = period_start(today, self.billing_day);
+        let mut lines = vec![
+            format::key_only("Data Usage", config),
+            format::key_value("Today", &transfer(self.day(today)), config),
+            format::key_value(&format!("Since {}", start.format("%b %-d")), &transfer(self.period(today)), config),
+        ];
+        if let (Some(cap), Some(percent)) = (self.cap, self.cap_percent(today)) {
+            lines.push(format::key_value("Data Cap", &format!(
+                "{:.0}% of {}",
+                percent,
+                format::bytes_to_human(cap),
+            ), config));
+        }
+        lines
+    }
+}
+
+/// State file of `interface`, e.g. `~/.local/state/waysensor-rs/net-usage/wlan0.json`.
+pub fn state_path(interface: &str) -> Option<PathBuf> {
+    dirs::state_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join("waysensor-rs").join("net-usage").join(format!("{}.json", interface)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1_000_000_000;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_data_usage_accounting() {
+        assert_eq!(period_start(date(2026, 3, 20), 15), date(2026, 3, 15));
+        assert_eq!(period_start(date(2026, 3, 10), 15), date(2026, 2, 15));
+        assert_eq!(period_start(date(2026, 1, 3), 1), date(2026, 1, 1));
+        assert_eq!(period_start(date(2026, 3, 31), 31), date(2026, 3, 28));
+
+        let mut usage = DataUsage::in_memory("boot-a");
+        let (feb, mar) = (date(2026, 2, 27), date(2026, 3, 2));
+        usage.update(Transfer { rx: 5 * GB, tx: GB }, feb);
+        usage.update(Transfer { rx: 7 * GB, tx: GB }, feb);
+        assert_eq!(usage.day(feb), Transfer { rx: 2 * GB, tx: 0 });
+
+        // Counters went backwards: the interface was re-created
+        usage.update(Transfer { rx: GB, tx: 0 }, mar);
+        // Reboot with counters above the old ones
+        usage.boot_id = "boot-b".to_string();
+        usage.update(Transfer { rx: 10 * GB, tx: GB }, mar);
+        assert_eq!(usage.day(mar), Transfer { rx: 11 * GB, tx: GB });
+        assert_eq!(usage.period(mar).total(), 12 * GB);
+
+        usage.billing_day = 25;
+        assert_eq!(usage.period(mar).total(), 14 * GB);
+        usage.cap = Some(35 * GB / 2);
+        assert_eq!(usage.cap_percent(mar), Some(80.0));
+        let theme = Theme::default();
+        assert_eq!(usage.class(mar, &theme), Some(theme.warning.as_str()));
+        usage.update(Transfer { rx: 15 * GB, tx: GB }, mar);
+        assert_eq!(usage.class(mar, &theme), Some(theme.critical.as_str()));
+        assert!(usage.tooltip_lines(mar, &SensorConfig::default())[2].contains("Since Feb 25"));
+
+        // Old days are dropped
+        usage.update(Transfer { rx: 15 * GB, tx: GB }, date(2026, 9, 1));
+        assert_eq!(usage.day(feb), Transfer::default());
+
+        assert!(state_path("wlan0").unwrap().ends_with("net-usage/wlan0.json"));
+    }
+}
@@ -0,0 +1,105 @@
+//! Immediate updates on link and address changes.
+//!
+//! Polling finds a pulled cable or a new DHCP lease only on the next tick,
+//! which for a slow interval can be many seconds late. The kernel announces
+//! these changes on the `RTNLGRP_LINK` and `RTNLGRP_IPV*_IFADDR` multicast
+//! groups, so a thread blocks on a subscribed netlink socket and wakes the
+//! update loop for every change of the monitored interface. Bandwidth keeps
+//! being sampled on the timer; an extra read only shortens one interval.
+
+use crate::netlink;
+use netlink_packet_route::{RtnlMessage, RTNLGRP_IPV4_IFADDR, RTNLGRP_IPV6_IFADDR, RTNLGRP_LINK};
+use std::{fs, io, thread, time::Duration};
+use tokio::sync::mpsc;
+
+/// Quiet time after an event before waking the loop, so the burst of
+/// messages a single change produces (link, carrier, addresses) causes one
+/// update rather than several.
+const SETTLE: Duration = Duration::from_millis(100);
+
+/// Interface index of `interface`, `None` if it does not exist.
+fn ifindex(interface: &str) -> Option<u32> {
+    fs::read_to_string(format!("/sys/class/net/{}/ifindex", interface)).ok()?.trim().parse().ok()
+}
+
+/// Whether `message` announces a change of the interface with `index`, or
+/// of any interface when `index` is `None`.
+pub fn concerns(message: &RtnlMessage, index: Option<u32>) -> bool {
+    let changed = match message {
+        RtnlMessage::NewLink(link) | RtnlMessage::DelLink(link) => link.header.index,
+        RtnlMessage::NewAddress(address) | RtnlMessage::DelAddress(address) => address.header.index,
+        _ => return false,
+    };
+    index.is_none_or(|index| index == changed)
+}
+
+/// Wakeups for link and address changes of an interface.
+#[derive(Debug)]
+pub struct LinkEvents {
+    receiver: mpsc::Receiver<()>,
+}
+
+impl LinkEvents {
+    /// Start watching `interface`, or every interface when `None` (the
+    /// sensor picks its interface itself and may switch later).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the netlink socket cannot be opened.
+    pub fn spawn(interface: Option<&str>) -> io::Result<Self> {
+        let socket = netlink::subscribe(&[RTNLGRP_LINK, RTNLGRP_IPV4_IFADDR, RTNLGRP_IPV6_IFADDR])?;
+        let index = interface.and_then(ifindex);
+        // A single slot: events arriving while an update is pending are
+        // covered by that update
+        let (sender, receiver) = mpsc::channel(1);
+        thread::Builder::new().name("link-events".to_string()).spawn(move || loop {
+            let messages = match netlink::receive(&socket) {
+                Ok(messages) => messages,
+                // The receive buffer overflowed and notifications were lost;
+                // something changed, so update anyway
+                Err(e) if e.raw_os_error() == Some(libc::ENOBUFS) => Vec::new(),
+                Err(_) => return,
+            };
+            let overflowed = messages.is_empty();
+            if !overflowed && !messages.iter().any(|message| concerns(message, index)) {
+                continue;
+            }
+            thread::sleep(SETTLE);
+            if sender.try_send(()).is_err() && sender.is_closed() {
+                return;
+            }
+        })?;
+        Ok(Self { receiver })
+    }
+
+    /// Wait for the next change. Never returns once the watcher has stopped.
+    pub async fn changed(&mut self) {
+        if self.receiver.recv().await.is_none() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netlink_packet_route::{AddressMessage, LinkMessage, RouteMessage};
+
+    #[test]
+    fn test_link_events() {
+        let mut link = LinkMessage::default();
+        link.header.index = 3;
+        let mut address = AddressMessage::default();
+        address.header.index = 4;
+
+        assert!(concerns(&RtnlMessage::NewLink(link.clone()), Some(3)));
+        assert!(concerns(&RtnlMessage::DelLink(link.clone()), None));
+        assert!(!concerns(&RtnlMessage::NewLink(link), Some(4)));
+        assert!(concerns(&RtnlMessage::NewAddress(address.clone()), Some(4)));
+        assert!(!concerns(&RtnlMessage::DelAddress(address), Some(3)));
+        assert!(!concerns(&RtnlMessage::NewRoute(RouteMessage::default()), None));
+
+        assert_eq!(ifindex("lo"), Some(1));
+        assert!(LinkEvents::spawn(Some("lo")).is_ok());
+    }
+}
@@ -0,0 +1,254 @@
+//! Default gateway, DNS servers and gateway reachability.
+//!
+//! Everything here feeds the expert tooltip so "the internet is down" can be
+//! narrowed to the link, the router or name resolution without leaving the bar.
+
+use std::{
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    path::Path,
+    process::Command,
+    sync::mpsc::{self, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Resolver configuration written by systemd-resolved with the real upstream
+/// servers, used when `/etc/resolv.conf` only points at the local stub.
+const RESOLVED_UPSTREAM_CONF: &str = "/run/systemd/resolve/resolv.conf";
+
+/// Default route of an interface.
+///
+/// Reads `/proc/net/route` and falls back to `/proc/net/ipv6_route` for
+/// IPv6-only links.
+pub fn default_gateway(interface: &str) -> Option<IpAddr> {
+    fs::read_to_string("/proc/net/route").ok()
+        .and_then(|routes| parse_ipv4_default_route(&routes, interface))
+        .map(IpAddr::V4)
+        .or_else(|| {
+            fs::read_to_string("/proc/net/ipv6_route").ok()
+                .and_then(|routes| parse_ipv6_default_route(&routes, interface))
+                .map(IpAddr::V6)
+        })
+}
+
+/// Find the gateway of the lowest-metric IPv4 default route in `/proc/net/route`.
+pub fn parse_ipv4_default_route(content: &str, interface: &str) -> Option<Ipv4Addr> {
+    content.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [iface, destination, gateway, _flags, _refcnt, _use, metric, ..] = fields[..] else {
+                return None;
+            };
+            if iface != interface || destination != "00000000" {
+                return None;
+            }
+            // The kernel prints the network-order address as a host-order integer
+            let gateway = u32::from_str_radix(gateway, 16).ok().filter(|&g| g != 0)?;
+            Some((metric.parse::<u32>().unwrap_or(u32::MAX), Ipv4Addr::from(gateway.to_ne_bytes())))
+        })
+        .min_by_key(|&(metric, _)| metric)
+        .map(|(_, gateway)| gateway)
+}
+
+/// Find the gateway of the lowest-metric IPv6 default route in `/proc/net/ipv6_route`.
+pub fn parse_ipv6_default_route(content: &str, interface: &str) -> Option<Ipv6Addr> {
+    content.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [destination, prefix_len, _, _, gateway, metric, .., iface] = fields[..] else {
+                return None;
+            };
+            if iface != interface || prefix_len != "00" || destination.bytes().any(|b| b != b'0') {
+                return None;
+            }
+            let gateway = u128::from_str_radix(gateway, 16).ok().filter(|&g| g != 0)?;
+            Some((u32::from_str_radix(metric, 16).unwrap_or(u32::MAX), Ipv6Addr::from(gateway)))
+        })
+        .min_by_key(|&(metric, _)| metric)
+        .map(|(_, gateway)| gateway)
+}
+
+/// DNS servers the system resolver uses.
+///
+/// When `/etc/resolv.conf` only lists the systemd-resolved stub (127.0.0.53),
+/// the upstream servers it forwards to are reported instead.
+pub fn dns_servers() -> Vec<IpAddr> {
+    let servers = read_nameservers(Path::new("/etc/resolv.conf"));
+    if !servers.is_empty() && servers.iter().all(|s| s.is_loopback()) {
+        let upstream = read_nameservers(Path::new(RESOLVED_UPSTREAM_CONF));
+        if !upstream.is_empty() {
+            return upstream;
+        }
+    }
+    servers
+}
+
+fn read_nameservers(path: &Path) -> Vec<IpAddr> {
+    fs::read_to_string(path)
+        .map(|content| parse_nameservers(&content))
+        .unwrap_or_default()
+}
+
+/// Parse the `nameserver` entries of a resolv.conf file.
+pub fn parse_nameservers(content: &str) -> Vec<IpAddr> {
+    content.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            (fields.next() == Some("nameserver")).then_some(())?;
+            // Link-local servers may carry a zone suffix ("fe80::1%wlan0")
+            fields.next()?.split('%').next()?.parse().ok()
+        })
+        .collect()
+}
+
+/// Result of the last reachability probe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reachability {
+    /// The gateway answered, with the round-trip time in milliseconds
+    Reachable(f64),
+    /// The gateway did not answer within the timeout
+    Unreachable,
+    /// The probe could not be run (no `ping` binary)
+    Unknown,
+}
+
+/// Periodic one-packet reachability check of the default gateway.
+///
+/// Probes shell out to `ping -c 1`, which works unprivileged on every
+/// distribution. They run on a worker thread at most once per interval, so
+/// an unreachable gateway never stalls a read; reads report the last result.
+#[derive(Debug)]
+pub struct GatewayProbe {
+    interval: Duration,
+    timeout_secs: u32,
+    last: Option<(IpAddr, Reachability, Instant)>,
+    /// Probe running on a worker thread, with the gateway it checks
+    pending: Option<(IpAddr, mpsc::Receiver<Reachability>)>,
+}
+
+impl GatewayProbe {
+    /// Create a probe that checks at most once per `interval`.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            timeout_secs: 1,
+            last: None,
+            pending: None,
+        }
+    }
+
+    /// Set how often the gateway is probed.
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Last known reachability of `gateway`, starting a probe in the
+    /// background if that result is stale or was for a different gateway.
+    /// [`Reachability::Unknown`] until the first probe of a gateway is done.
+    pub fn check(&mut self, gateway: IpAddr, interface: &str) -> Reachability {
+        if let Some((probed, rx)) = &self.pending {
+            match rx.try_recv() {
+                Ok(result) => {
+                    self.last = Some((*probed, result, Instant::now()));
+                    self.pending = None;
+                }
+                Err(TryRecvError::Disconnected) => self.pending = None,
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+
+        let last = self.last.filter(|&(last_gateway, _, _)| last_gateway == gateway);
+        let stale = last.is_none_or(|(_, _, at)| at.elapsed() >= self.interval);
+        let probing = self.pending.as_ref().is_some_and(|&(probed, _)| probed == gateway);
+        if stale && !probing {
+            let (tx, rx) = mpsc::channel();
+            let (timeout_secs, interface) = (self.timeout_secs, interface.to_string());
+            let spawned = thread::Builder::new()
+                .name("gateway-probe".to_string())
+                .spawn(move || {
+                    let _ = tx.send(probe(gateway, &interface, timeout_secs));
+                });
+            if spawned.is_ok() {
+                self.pending = Some((gateway, rx));
+            }
+        }
+
+        last.map_or(Reachability::Unknown, |(_, result, _)| result)
+    }
+}
+
+fn probe(gateway: IpAddr, interface: &str, timeout_secs: u32) -> Reachability {
+    let mut command = Command::new("ping");
+    command.args(["-n", "-q", "-c", "1", "-W", &timeout_secs.to_string()]);
+    if let IpAddr::V6(addr) = gateway {
+        command.arg("-6");
+        // Link-local gateways need the interface as scope
+        if addr.segments()[0] & 0xffc0 == 0xfe80 {
+            command.args(["-I", interface]);
+        }
+    }
+    command.arg(gateway.to_string());
+
+    match command.output() {
+        Ok(output) if output.status.success() => {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            Reachability::Reachable(parse_ping_rtt(&stdout).unwrap_or(0.0))
+        }
+        Ok(_) => Reachability::Unreachable,
+        Err(_) => Reachability::Unknown,
+    }
+}
+
+/// Extract the average round-trip time from `ping -q` summary output.
+pub fn parse_ping_rtt(output: &str) -> Option<f64> {
+    // "rtt min/avg/max/mdev = 0.412/0.412/0.412/0.000 ms" (iputils)
+    // "round-trip min/avg/max = 0.412/0.412/0.412 ms" (busybox)
+    let line = output.lines().find(|l| l.contains("min/avg/max"))?;
+    let values = line.split('=').nth(1)?.trim();
+    values.split('/').nth(1)?.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_route_parsing() {
+        let routes = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+            wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0\n\
+            eth0\t00000000\t0100000A\t0003\t0\t0\t100\t00000000\t0\t0\t0\n\
+            eth0\t00000000\t0200000A\t0003\t0\t0\t50\t00000000\t0\t0\t0\n\
+            wlan0\t0001A8C0\t00000000\t0001\t0\t0\t600\t00FFFFFF\t0\t0\t0\n";
+        assert_eq!(parse_ipv4_default_route(routes, "wlan0"), Some(Ipv4Addr::new(192, 168, 1, 1)));
+        assert_eq!(parse_ipv4_default_route(routes, "eth0"), Some(Ipv4Addr::new(10, 0, 0, 2)));
+        assert_eq!(parse_ipv4_default_route(routes, "enp3s0"), None);
+
+        let routes6 = "fe800000000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001 wlan0\n\
+            00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe80000000000000021122fffe334455 00000400 00000001 00000000 00000003 wlan0\n";
+        assert_eq!(
+            parse_ipv6_default_route(routes6, "wlan0"),
+            Some("fe80::211:22ff:fe33:4455".parse().unwrap())
+        );
+        assert_eq!(parse_ipv6_default_route(routes6, "eth0"), None);
+    }
+
+    #[test]
+    fn test_nameserver_parsing() {
+        let conf = "# Generated by NetworkManager\nsearch lan\nnameserver 192.168.1.1\nnameserver fe80::1%wlan0\n#nameserver 8.8.8.8\noptions edns0\n";
+        assert_eq!(
+            parse_nameservers(conf),
+            vec!["192.168.1.1".parse::<IpAddr>().unwrap(), "fe80::1".parse().unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_ping_rtt_parsing() {
+        let iputils = "PING 192.168.1.1 (192.168.1.1) 56(84) bytes of data.\n\n--- 192.168.1.1 ping statistics ---\n1 packets transmitted, 1 received, 0% packet loss, time 0ms\nrtt min/avg/max/mdev = 1.873/1.873/1.873/0.000 ms\n";
+        assert_eq!(parse_ping_rtt(iputils), Some(1.873));
+        let busybox = "round-trip min/avg/max = 0.412/0.500/0.600 ms\n";
+        assert_eq!(parse_ping_rtt(busybox), Some(0.5));
+        assert_eq!(parse_ping_rtt("1 packets transmitted, 0 received"), None);
+    }
+}
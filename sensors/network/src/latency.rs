@@ -0,0 +1,321 @@
+//! Round-trip time, jitter and packet loss to a host.
+//!
+//! ICMP echo goes through `ping`, which works unprivileged on every
+//! distribution. Where ICMP is filtered (corporate networks, some game
+//! servers) the time to complete a TCP handshake serves the same purpose; a
+//! refused connection still measures a full round trip.
+
+use crate::gateway::parse_ping_rtt;
+use waysensor_rs_core::{diff, format, Sensor, SensorConfig, SensorError, WaybarOutput};
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    net::{TcpStream, ToSocketAddrs},
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// Number of probes jitter and packet loss are computed over.
+pub const WINDOW: usize = 60;
+
+/// Default time a probe may take before it counts as lost.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Packet loss in percent that turns the module warning / critical.
+pub const DEFAULT_LOSS_WARNING: f64 = 5.0;
+pub const DEFAULT_LOSS_CRITICAL: f64 = 20.0;
+
+/// How the round-trip time is measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeMethod {
+    /// ICMP echo request
+    Icmp,
+    /// TCP handshake with the given port
+    Tcp(u16),
+}
+
+/// Outcome of the last [`WINDOW`] probes.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyStats {
+    /// Round-trip times in milliseconds, `None` for lost probes
+    samples: VecDeque<Option<f64>>,
+}
+
+impl LatencyStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a probe result.
+    pub fn push(&mut self, rtt: Option<f64>) {
+        if self.samples.len() == WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(rtt);
+    }
+
+    /// Result of the last probe, `None` before the first one.
+    pub fn last(&self) -> Option<Option<f64>> {
+        self.samples.back().copied()
+    }
+
+    /// Round-trip times of the answered probes, oldest first.
+    pub fn rtts(&self) -> Vec<f64> {
+        self.samples.iter().flatten().copied().collect()
+    }
+
+    /// Average, minimum and maximum round-trip time.
+    pub fn summary(&self) -> Option<(f64, f64, f64)> {
+        let rtts = self.rtts();
+        if rtts.is_empty() {
+            return None;
+        }
+        let average = rtts.iter().sum::<f64>() / rtts.len() as f64;
+        let min = rtts.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = rtts.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Some((average, min, max))
+    }
+
+    /// Mean difference between consecutive round-trip times, the way VoIP
+    /// and game clients report jitter.
+    pub fn jitter(&self) -> Option<f64> {
+        let rtts = self.rtts();
+        if rtts.len() < 2 {
+            return None;
+        }
+        let total: f64 = rtts.windows(2).map(|pair| (pair[1] - pair[0]).abs()).sum();
+        Some(total / (rtts.len() - 1) as f64)
+    }
+
+    /// Lost and sent probes.
+    pub fn loss(&self) -> (usize, usize) {
+        let lost = self.samples.iter().filter(|sample| sample.is_none()).count();
+        (lost, self.samples.len())
+    }
+
+    /// Packet loss in percent.
+    pub fn loss_percent(&self) -> f64 {
+        match self.loss() {
+            (_, 0) => 0.0,
+            (lost, sent) => lost as f64 / sent as f64 * 100.0,
+        }
+    }
+}
+
+/// Round-trip time to `host` with `ping`, `None` if no answer came back.
+fn probe_icmp(host: &str, timeout: Duration) -> Result<Option<f64>, SensorError> {
+    let output = Command::new("ping")
+        .args(["-n", "-q", "-c", "1", "-W", &timeout.as_secs().max(1).to_string(), host])
+        .output()
+        .map_err(|e| match e.kind() {
+            ErrorKind::NotFound => SensorError::unavailable("ping not installed"),
+            _ => SensorError::Io(e),
+        })?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(parse_ping_rtt(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Time to complete a TCP handshake with `host`, `None` on timeout.
+fn probe_tcp(host: &str, port: u16, timeout: Duration) -> Result<Option<f64>, SensorError> {
+    // Resolve first so name lookup does not count as latency
+    let address = (host, port).to_socket_addrs()
+        .map_err(|e| SensorError::temporarily_unavailable(format!("Cannot resolve {}: {}", host, e)))?
+        .next()
+        .ok_or_else(|| SensorError::temporarily_unavailable(format!("No address for {}", host)))?;
+    let start = Instant::now();
+    match TcpStream::connect_timeout(&address, timeout) {
+        Ok(_) => Ok(Some(start.elapsed().as_secs_f64() * 1000.0)),
+        Err(e) if e.kind() == ErrorKind::ConnectionRefused => Ok(Some(start.elapsed().as_secs_f64() * 1000.0)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Latency to a host with jitter and packet loss over the last probes.
+#[derive(Debug)]
+pub struct LatencySensor {
+    name: String,
+    config: SensorConfig,
+    host: String,
+    method: ProbeMethod,
+    warning_ms: f64,
+    critical_ms: f64,
+    loss_warning: f64,
+    loss_critical: f64,
+    timeout: Duration,
+    stats: LatencyStats,
+}
+
+impl LatencySensor {
+    pub fn new(host: String, method: ProbeMethod, warning_ms: f64, critical_ms: f64) -> Result<Self, SensorError> {
+        if warning_ms >= critical_ms {
+            return Err(SensorError::config("Warning latency must be lower than critical latency"));
+        }
+
+        Ok(Self {
+            name: format!("ping-{}", host),
+            config: SensorConfig::default(),
+            host,
+            method,
+            warning_ms,
+            critical_ms,
+            loss_warning: DEFAULT_LOSS_WARNING,
+            loss_critical: DEFAULT_LOSS_CRITICAL,
+            timeout: DEFAULT_TIMEOUT,
+            stats: LatencyStats::new(),
+        })
+    }
+
+    fn probe(&self) -> Result<Option<f64>, SensorError> {
+        match self.method {
+            ProbeMethod::Icmp => probe_icmp(&self.host, self.timeout),
+            ProbeMethod::Tcp(port) => probe_tcp(&self.host, port, self.timeout),
+        }
+    }
+
+    /// CSS class for the packet loss, `None` while below the warning level.
+    fn loss_class(&self) -> Option<&str> {
+        let theme = &self.config.theme;
+        match self.stats.loss_percent() {
+            loss if loss >= self.loss_critical => Some(&theme.critical),
+            loss if loss >= self.loss_warning => Some(&theme.warning),
+            _ => None,
+        }
+    }
+
+    fn build_tooltip(&self) -> String {
+        let config = &self.config;
+        let method = match self.method {
+            ProbeMethod::Icmp => "ICMP".to_string(),
+            ProbeMethod::Tcp(port) => format!("TCP port {}", port),
+        };
+        let mut lines = vec![format::key_value("Host", &format!("{} ({})", self.host, method), config)];
+
+        let last = match self.stats.last().flatten() {
+            Some(rtt) => format!("{:.1} ms", rtt),
+            None => "timeout".to_string(),
+        };
+        lines.push(format::key_value("Last", &last, config));
+        if let Some((average, min, max)) = self.stats.summary() {
+            lines.push(format::key_value("Average", &format!("{:.1} ms (min {:.1} / max {:.1})", average, min, max), config));
+        }
+        if let Some(jitter) = self.stats.jitter() {
+            lines.push(format::key_value("Jitter", &format!("{:.1} ms", jitter), config));
+        }
+        let (lost, sent) = self.stats.loss();
+        lines.push(format::key_value("Loss", &format!("{:.0}% ({} of {})", self.stats.loss_percent(), lost, sent), config));
+
+        let rtts = self.stats.rtts();
+        if config.visuals.sparklines && rtts.len() > 1 {
+            let recent = &rtts[rtts.len().saturating_sub(config.visuals.sparkline_length)..];
+            let sparkline = format::create_sparkline(recent, config.visuals.sparkline_style);
+            if !sparkline.is_empty() {
+                let colored_sparkline = format::colored_sparkline(&sparkline, config.sparkline_color.as_deref());
+                lines.push(format::key_value("RTT History", &colored_sparkline, config));
+            }
+        }
+
+        lines.push(format::key_value("Thresholds", &format!("⚠️ {:.0} ms / 🔴 {:.0} ms",
+            self.warning_ms, self.critical_ms), config));
+        lines.join("\n")
+    }
+}
+
+impl Sensor for LatencySensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let rtt = self.probe()?;
+        self.stats.push(rtt);
+
+        let icon = &self.config.icons.network_latency;
+        let mut output = match rtt {
+            Some(rtt) => format::themed_output(
+                format::with_icon_and_colors(&format!("{:.0} ms", rtt), icon, &self.config),
+                Some(self.build_tooltip()),
+                Some(((rtt / self.critical_ms) * 100.0).min(100.0) as u8),
+                rtt,
+                self.warning_ms,
+                self.critical_ms,
+                &self.config.theme,
+            ),
+            None => WaybarOutput::new(format::with_icon_and_colors("timeout", icon, &self.config))
+                .with_tooltip(self.build_tooltip())
+                .with_class(self.config.theme.critical.clone())
+                .with_percentage(100),
+        };
+
+        // Steady loss is as bad for a call as a slow link
+        let theme = &self.config.theme;
+        if let Some(class) = self.loss_class() {
+            if diff::severity(Some(class), theme) > diff::severity(output.class.as_deref(), theme) {
+                output.class = Some(class.to_owned());
+            }
+        }
+
+        Ok(output)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        if let Some(ms) = config.get_custom("ping_timeout_ms").and_then(|v| v.as_u64()) {
+            self.timeout = Duration::from_millis(ms);
+        }
+        if let Some(percent) = config.get_custom("loss_warning").and_then(|v| v.as_f64()) {
+            self.loss_warning = percent;
+        }
+        if let Some(percent) = config.get_custom("loss_critical").and_then(|v| v.as_f64()) {
+            self.loss_critical = percent;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_stats() {
+        let mut stats = LatencyStats::new();
+        assert_eq!(stats.last(), None);
+        assert_eq!(stats.loss_percent(), 0.0);
+
+        for rtt in [Some(20.0), Some(30.0), None, Some(25.0)] {
+            stats.push(rtt);
+        }
+        assert_eq!(stats.last(), Some(Some(25.0)));
+        assert_eq!(stats.rtts(), vec![20.0, 30.0, 25.0]);
+        assert_eq!(stats.summary(), Some((25.0, 20.0, 30.0)));
+        assert_eq!(stats.jitter(), Some(7.5));
+        assert_eq!(stats.loss(), (1, 4));
+        assert_eq!(stats.loss_percent(), 25.0);
+
+        for _ in 0..WINDOW {
+            stats.push(Some(10.0));
+        }
+        assert_eq!(stats.loss(), (0, WINDOW));
+        assert_eq!(stats.jitter(), Some(0.0));
+
+        assert!(LatencySensor::new("localhost".to_string(), ProbeMethod::Icmp, 150.0, 80.0).is_err());
+        let mut sensor = LatencySensor::new("1.1.1.1".to_string(), ProbeMethod::Tcp(443), 80.0, 150.0).unwrap();
+        assert_eq!(sensor.name(), "ping-1.1.1.1");
+        sensor.stats = stats;
+        sensor.stats.push(None);
+        assert_eq!(sensor.loss_class(), None);
+        for _ in 0..3 {
+            sensor.stats.push(None);
+        }
+        assert_eq!(sensor.loss_class(), Some(sensor.config.theme.warning.as_str()));
+        assert!(sensor.build_tooltip().contains("TCP port 443"));
+    }
+}
@@ -1,4 +1,21 @@
 pub mod network;
 pub mod auto_detect;
+pub mod gateway;
+pub mod wireless;
+pub mod multi_network;
+pub mod connectivity;
+pub mod latency;
+pub mod vpn;
+pub mod data_usage;
+pub mod talkers;
+pub mod netlink;
+pub mod addresses;
+pub mod metered;
+pub mod aggregate;
+pub mod events;
 
-pub use network::NetworkSensor;
\ No newline at end of file
+pub use network::NetworkSensor;
+pub use multi_network::{MultiNetworkSensor, NetDisplayMode};
+pub use connectivity::ConnectivitySensor;
+pub use latency::{LatencySensor, ProbeMethod};
+pub use vpn::VpnSensor;
\ No newline at end of file
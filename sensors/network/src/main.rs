@@ -1,10 +1,11 @@
 use clap::Parser;
-use waysensor_rs_core::{GlobalConfig, Sensor, IconStyle};
+use waysensor_rs_core::{discovery, schedule, session_lock, GlobalConfig, Sensor, IconStyle};
 use std::io::{self, Write};
 use std::time::Duration;
-use tokio::time;
 
-use waysensor_rs_network::NetworkSensor;
+use waysensor_rs_core::SensorError;
+use waysensor_rs_network::events::LinkEvents;
+use waysensor_rs_network::{ConnectivitySensor, LatencySensor, MultiNetworkSensor, NetDisplayMode, NetworkSensor, ProbeMethod, VpnSensor};
 
 #[derive(Parser)]
 #[command(name = "waysensor-rs-network")]
@@ -15,6 +16,50 @@ struct Args {
     #[arg(short, long)]
     interface: Option<String>,
 
+    /// Monitor several interfaces at once (comma-separated)
+    #[arg(long, value_delimiter = ',', conflicts_with = "interface")]
+    interfaces: Vec<String>,
+
+    /// What to show with --interfaces (sum, busiest, cycle)
+    #[arg(short, long, default_value = "sum")]
+    display_mode: NetDisplayMode,
+
+    /// Show whether the link, gateway and internet are reachable instead of throughput
+    #[arg(long, conflicts_with = "interfaces")]
+    connectivity: bool,
+
+    /// Show the public IP address in the --connectivity tooltip (asks api.ipify.org)
+    #[arg(long, requires = "connectivity")]
+    public_ip: bool,
+
+    /// Show the round-trip time to a host instead of throughput
+    #[arg(long, value_name = "HOST", conflicts_with_all = ["interfaces", "connectivity"])]
+    ping: Option<String>,
+
+    /// Measure --ping as TCP connect time to this port (for hosts filtering ICMP)
+    #[arg(long, requires = "ping")]
+    ping_port: Option<u16>,
+
+    /// Latency warning threshold (ms)
+    #[arg(long, default_value = "80")]
+    ping_warning: f64,
+
+    /// Latency critical threshold (ms)
+    #[arg(long, default_value = "150")]
+    ping_critical: f64,
+
+    /// Show whether a VPN tunnel is up (the one given with --interface, or any)
+    #[arg(long, conflicts_with_all = ["interfaces", "connectivity", "ping"])]
+    vpn: bool,
+
+    /// Keep daily and monthly transfer totals across restarts (see data_cap_gb in the config)
+    #[arg(long, conflicts_with_all = ["interfaces", "connectivity", "ping", "vpn"])]
+    usage: bool,
+
+    /// List the processes with the most TCP traffic in the tooltip (uses `ss`)
+    #[arg(long, conflicts_with_all = ["interfaces", "connectivity", "ping", "vpn"])]
+    top_talkers: bool,
+
     /// Update interval in milliseconds
     #[arg(short = 't', long, default_value = "1000")]
     interval: u64,
@@ -43,10 +88,22 @@ struct Args {
     #[arg(short, long)]
     once: bool,
     
-    /// Detect and list active network interfaces
+    /// Detect and list active network interfaces, with the auto-selected one
     #[arg(long)]
     detect: bool,
 
+    /// List available network interfaces and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
     /// Icon style (nerdfont, fontawesome, ascii, none)
     #[arg(long)]
     icon_style: Option<IconStyle>,
@@ -80,6 +137,19 @@ struct Args {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<NetworkSensor>(args.json) {
+            eprintln!("Error listing interfaces: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    
     // Handle detection mode
     if args.detect {
         use waysensor_rs_network::auto_detect::{detect_active_interfaces, find_best_interface};
@@ -122,14 +192,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
     
-    let mut network_sensor = NetworkSensor::new(
-        args.interface,
-        args.warning,
-        args.critical,
-        args.total,
-        args.upload_only,
-        args.download_only,
-    )?;
+    // Latency only changes on the probe's own schedule
+    let watched_links = args.ping.is_none().then(|| args.interface.clone());
+    
+    let mut network_sensor: Box<dyn Sensor<Error = SensorError>> = if let Some(host) = args.ping {
+        let method = args.ping_port.map_or(ProbeMethod::Icmp, ProbeMethod::Tcp);
+        Box::new(LatencySensor::new(host, method, args.ping_warning, args.ping_critical)?)
+    } else if args.vpn {
+        Box::new(VpnSensor::new(args.interface))
+    } else if args.connectivity {
+        Box::new(ConnectivitySensor::new(args.interface)?.with_public_ip(args.public_ip))
+    } else if args.interfaces.is_empty() {
+        Box::new(NetworkSensor::new(
+            args.interface,
+            args.warning,
+            args.critical,
+            args.total,
+            args.upload_only,
+            args.download_only,
+        )?
+        .with_data_usage(args.usage)
+        .with_top_talkers(args.top_talkers))
+    } else {
+        Box::new(MultiNetworkSensor::new(
+            args.interfaces,
+            args.warning,
+            args.critical,
+            args.display_mode,
+        )?)
+    };
     
     // Check availability if requested
     if args.check {
@@ -147,7 +238,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Load global configuration and apply command line overrides
     let global_config = GlobalConfig::load().unwrap_or_default();
-    let mut config = global_config.to_sensor_config()
+    let mut config = global_config.to_sensor_config_for("network")
         .with_update_interval(Duration::from_millis(args.interval))
         .apply_color_overrides(
             args.icon_color,
@@ -161,20 +252,45 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config = config.with_icon_style(icon_style);
     }
     
+    // Load sensor-specific configuration from global config
+    if let Some(serde_json::Value::Object(map)) = global_config.sensors.get("network") {
+        for (key, value) in map {
+            config = config.with_custom(key.clone(), value.clone());
+        }
+    }
+    
     network_sensor.configure(config)?;
     
     if args.once {
         // For one-shot mode, we need to wait a bit to calculate bandwidth
         tokio::time::sleep(Duration::from_millis(1000)).await;
-        let output = network_sensor.read()?;
+        let output = network_sensor.read_styled()?;
         println!("{}", serde_json::to_string(&output)?);
     } else {
-        let mut interval = time::interval(Duration::from_millis(args.interval));
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
+        // Link and address changes update the bar right away, bandwidth on the timer
+        let link_events = network_sensor.config().get_custom("link_events").and_then(|v| v.as_bool()) != Some(false);
+        let mut events = watched_links.filter(|_| link_events).and_then(|interface| {
+            LinkEvents::spawn(interface.as_deref())
+                .map_err(|e| eprintln!("Link events unavailable, polling only: {}", e))
+                .ok()
+        });
         
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = async {
+                    match &mut events {
+                        Some(events) => events.changed().await,
+                        None => std::future::pending().await,
+                    }
+                } => {}
+            }
+            if session_lock::wait_async(network_sensor.config()).await {
+                interval.reset();
+            }
             
-            match network_sensor.read() {
+            match network_sensor.read_styled() {
                 Ok(output) => {
                     println!("{}", serde_json::to_string(&output)?);
                     io::stdout().flush()?;
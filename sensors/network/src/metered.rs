@@ -0,0 +1,168 @@
+//! Metered connections: phone hotspots, USB tethering and mobile broadband.
+//!
+//! NetworkManager tracks a metered flag per device, set by the user or
+//! guessed from the Android DHCP vendor option and the device type. Without
+//! NetworkManager the interface's driver gives tethering away: phones share
+//! their connection over RNDIS, CDC Ethernet/NCM or Apple's ipheth, and
+//! modems show up as QMI or MBIM devices.
+
+use waysensor_rs_core::{IconStyle, SensorConfig};
+use std::{
+    fs,
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// How long a detection result is reused.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// CSS class of a metered connection.
+pub const METERED_CLASS: &str = "metered";
+
+/// Drivers of USB tethering and mobile broadband devices.
+const TETHERING_DRIVERS: &[&str] = &[
+    "rndis_host", "cdc_ether", "cdc_ncm", "ipheth", "qmi_wwan", "cdc_mbim", "huawei_cdc_ncm",
+];
+
+/// Whether a connection is billed by volume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metered {
+    /// Metered, set explicitly or guessed
+    Yes { guessed: bool },
+    /// Not metered
+    No { guessed: bool },
+    /// Nothing known
+    Unknown,
+}
+
+impl Metered {
+    pub fn is_metered(self) -> bool {
+        matches!(self, Self::Yes { .. })
+    }
+
+    /// Parse `nmcli -t -f GENERAL.METERED device show <interface>`.
+    pub fn parse_nmcli(output: &str) -> Self {
+        let Some(value) = output.lines().find_map(|line| line.strip_prefix("GENERAL.METERED:")) else {
+            return Self::Unknown;
+        };
+        let guessed = value.contains("guessed");
+        match value.split_whitespace().next() {
+            Some("yes") => Self::Yes { guessed },
+            Some("no") => Self::No { guessed },
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Guess from the interface name and kernel driver.
+    pub fn from_device(interface: &str, driver: Option<&str>) -> Self {
+        let tethering = driver.is_some_and(|driver| TETHERING_DRIVERS.contains(&driver));
+        if tethering || interface.starts_with("wwan") || interface.starts_with("usb") {
+            Self::Yes { guessed: true }
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// Detect whether `interface` is metered, asking NetworkManager first.
+    pub fn detect(interface: &str) -> Self {
+        let from_nm = Command::new("nmcli")
+            .args(["-t", "-f", "GENERAL.METERED", "device", "show", interface])
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map_or(Self::Unknown, |output| Self::parse_nmcli(&String::from_utf8_lossy(&output.stdout)));
+        if from_nm != Self::Unknown {
+            return from_nm;
+        }
+        let driver = fs::read_link(Path::new("/sys/class/net").join(interface).join("device/driver")).ok()
+            .and_then(|link| link.file_name().map(|name| name.to_string_lossy().into_owned()));
+        Self::from_device(interface, driver.as_deref())
+    }
+
+    /// Tooltip value, e.g. `yes (guessed)`.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Yes { guessed: false } => "yes",
+            Self::Yes { guessed: true } => "yes (guessed)",
+            Self::No { guessed: false } => "no",
+            Self::No { guessed: true } => "no (guessed)",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Cached metered state of an interface.
+#[derive(Debug)]
+pub struct MeteredMonitor {
+    state: Metered,
+    last_query: Option<Instant>,
+}
+
+impl Default for MeteredMonitor {
+    fn default() -> Self {
+        Self {
+            state: Metered::Unknown,
+            last_query: None,
+        }
+    }
+}
+
+impl MeteredMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refresh the state of `interface` if the cached result is older than
+    /// [`REFRESH_INTERVAL`].
+    pub fn update(&mut self, interface: &str) -> Metered {
+        let fresh = self.last_query.is_some_and(|at| at.elapsed() < REFRESH_INTERVAL);
+        if !fresh {
+            self.last_query = Some(Instant::now());
+            self.state = Metered::detect(interface);
+        }
+        self.state
+    }
+
+    /// Last known state.
+    pub fn state(&self) -> Metered {
+        self.state
+    }
+}
+
+/// Prefix `text` with the metered icon in the icon color.
+pub fn badge(text: &str, config: &SensorConfig) -> String {
+    let icon = &config.icons.network_metered;
+    if config.icon_style == IconStyle::None || icon.trim().is_empty() {
+        return text.to_owned();
+    }
+    match &config.icon_color {
+        Some(color) => format!("<span color=\"{}\">{}</span> {}", color, icon, text),
+        None => format!("{} {}", icon, text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metered_detection() {
+        assert_eq!(Metered::parse_nmcli("GENERAL.METERED:yes (guessed)\n"), Metered::Yes { guessed: true });
+        assert_eq!(Metered::parse_nmcli("GENERAL.METERED:yes\n"), Metered::Yes { guessed: false });
+        assert_eq!(Metered::parse_nmcli("GENERAL.METERED:no (guessed)\n"), Metered::No { guessed: true });
+        assert_eq!(Metered::parse_nmcli("GENERAL.METERED:unknown\n"), Metered::Unknown);
+        assert_eq!(Metered::parse_nmcli(""), Metered::Unknown);
+
+        assert!(Metered::from_device("enp0s20f0u2", Some("rndis_host")).is_metered());
+        assert!(Metered::from_device("eth1", Some("ipheth")).is_metered());
+        assert!(Metered::from_device("wwan0", None).is_metered());
+        assert_eq!(Metered::from_device("enp3s0", Some("r8169")), Metered::Unknown);
+        assert_eq!(Metered::Yes { guessed: true }.describe(), "yes (guessed)");
+
+        let config = SensorConfig::default();
+        assert_eq!(badge("1 MB/s", &config), "1 MB/s");
+        let config = config.with_icon_style(IconStyle::NerdFont);
+        assert_eq!(badge("1 MB/s", &config), format!("{} 1 MB/s", config.icons.network_metered));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::network::{NetworkSensor, NetworkSpeed, NetworkStats};
+use waysensor_rs_core::{format, Sensor, SensorConfig, SensorError, WaybarOutput};
+use std::{collections::HashMap, time::Instant};
+
+/// Throughput of several interfaces at once, e.g. Ethernet and WiFi on a
+/// laptop or the uplinks of a router.
+#[derive(Debug)]
+pub struct MultiNetworkSensor {
+    name: String,
+    interfaces: Vec<String>,
+    warning_threshold: u64,  // MB/s
+    critical_threshold: u64, // MB/s
+    display_mode: NetDisplayMode,
+    config: SensorConfig,
+    /// Last counters per interface
+    last: HashMap<String, (NetworkStats, Instant)>,
+}
+
+/// What the bar shows when monitoring several interfaces.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum NetDisplayMode {
+    /// Throughput summed over all interfaces
+    #[default]
+    Sum,
+    /// The interface moving the most data
+    Busiest,
+    /// Each interface in turn
+    Cycle { current: usize },
+}
+
+impl std::str::FromStr for NetDisplayMode {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "sum" | "total" => Ok(Self::Sum),
+            "busiest" | "highest" => Ok(Self::Busiest),
+            "cycle" => Ok(Self::Cycle { current: 0 }),
+            _ => Err(SensorError::config_with_value(
+                "Invalid display mode. Valid options: sum, busiest, cycle",
+                s,
+            )),
+        }
+    }
+}
+
+/// Throughput of one interface during the last interval.
+#[derive(Debug, Clone, PartialEq)]
+struct InterfaceSpeed {
+    interface: String,
+    speed: NetworkSpeed,
+}
+
+/// Throughput summed over all interfaces.
+fn sum_speed(speeds: &[InterfaceSpeed]) -> NetworkSpeed {
+    NetworkSpeed {
+        download_mbps: speeds.iter().map(|s| s.speed.download_mbps).sum(),
+        upload_mbps: speeds.iter().map(|s| s.speed.upload_mbps).sum(),
+        total_mbps: speeds.iter().map(|s| s.speed.total_mbps).sum(),
+    }
+}
+
+/// Interface with the highest combined throughput.
+fn busiest(speeds: &[InterfaceSpeed]) -> Option<&InterfaceSpeed> {
+    speeds.iter().max_by(|a, b| a.speed.total_mbps.total_cmp(&b.speed.total_mbps))
+}
+
+impl MultiNetworkSensor {
+    pub fn new(
+        interfaces: Vec<String>,
+        warning_threshold: u64,
+        critical_threshold: u64,
+        display_mode: NetDisplayMode,
+    ) -> Result<Self, SensorError> {
+        if interfaces.is_empty() {
+            return Err(SensorError::Unavailable {
+                reason: "No interfaces specified".to_string(),
+                is_temporary: false,
+            });
+        }
+
+        // Validate all interfaces exist
+        for interface in &interfaces {
+            if !std::path::Path::new(&format!("/sys/class/net/{}/statistics", interface)).exists() {
+                return Err(SensorError::Unavailable {
+                    reason: format!("Network interface not found: {}", interface),
+                    is_temporary: false,
+                });
+            }
+        }
+
+        Ok(Self {
+            name: "network-multi".to_string(),
+            interfaces,
+            warning_threshold,
+            critical_threshold,
+            display_mode,
+            config: SensorConfig::default(),
+            last: HashMap::new(),
+        })
+    }
+
+    /// Throughput of every interface since the previous read. Interfaces
+    /// that went away are skipped; the first read reports zero.
+    fn read_speeds(&mut self) -> Result<Vec<InterfaceSpeed>, SensorError> {
+        let now = Instant::now();
+        let mut speeds = Vec::new();
+        for interface in &self.interfaces {
+            let stats = match NetworkStats::read(interface) {
+                Ok(stats) => stats,
+                Err(e) => {
+                    eprintln!("Warning: Failed to read {}: {}", interface, e);
+                    continue;
+                }
+            };
+            let speed = self.last.get(interface)
+                .map(|(last, at)| NetworkSpeed::between(&stats, last, now.duration_since(*at)))
+                .unwrap_or_default();
+            self.last.insert(interface.clone(), (stats, now));
+            speeds.push(InterfaceSpeed { interface: interface.clone(), speed });
+        }
+
+        if speeds.is_empty() {
+            return Err(SensorError::Unavailable {
+                reason: "No network interface available".to_string(),
+                is_temporary: true,
+            });
+        }
+        Ok(speeds)
+    }
+}
+
+impl Sensor for MultiNetworkSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let speeds = self.read_speeds()?;
+
+        let (speed, prefix) = match &mut self.display_mode {
+            NetDisplayMode::Sum => (sum_speed(&speeds), None),
+            NetDisplayMode::Busiest => {
+                let busiest = busiest(&speeds).unwrap_or(&speeds[0]);
+                (busiest.speed.clone(), Some(busiest.interface.clone()))
+            }
+            NetDisplayMode::Cycle { current } => {
+                let shown = &speeds[*current % speeds.len()];
+                *current = (*current + 1) % speeds.len();
+                (shown.speed.clone(), Some(shown.interface.clone()))
+            }
+        };
+
+        let down_text = format::with_icon_and_colors(
+            &NetworkSensor::format_speed(speed.download_mbps), &self.config.icons.network_download, &self.config);
+        let up_text = format::with_icon_and_colors(
+            &NetworkSensor::format_speed(speed.upload_mbps), &self.config.icons.network_upload, &self.config);
+        let text = match prefix {
+            Some(interface) => format!("{}: {} {}", interface, down_text, up_text),
+            None => format!("{} {}", down_text, up_text),
+        };
+
+        let percentage = ((speed.total_mbps / self.critical_threshold as f64) * 100.0).min(100.0) as u8;
+
+        Ok(format::themed_output(
+            text,
+            Some(self.build_tooltip(&speeds)),
+            Some(percentage),
+            speed.total_mbps,
+            self.warning_threshold as f64,
+            self.critical_threshold as f64,
+            &self.config.theme,
+        ))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+}
+
+impl MultiNetworkSensor {
+    /// Sum over all interfaces followed by one line per interface.
+    fn build_tooltip(&self, speeds: &[InterfaceSpeed]) -> String {
+        let (warning, critical) = (self.warning_threshold as f64, self.critical_threshold as f64);
+        let line = |label: &str, speed: &NetworkSpeed| format::key_value(label, &format!(
+            "{} ↓ {} ↑ {} {}",
+            NetworkSensor::create_speed_gauge(speed.total_mbps, critical, 12),
+            NetworkSensor::format_speed(speed.download_mbps),
+            NetworkSensor::format_speed(speed.upload_mbps),
+            NetworkSensor::get_speed_indicator(speed.total_mbps, warning, critical),
+        ), &self.config);
+
+        let mut lines = vec![
+            format::key_only(&format!("Network: {} interfaces", speeds.len()), &self.config),
+            line("Total", &sum_speed(speeds)),
+            String::new(),
+        ];
+        lines.extend(speeds.iter().map(|s| line(&s.interface, &s.speed)));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn speed(interface: &str, down: f64, up: f64) -> InterfaceSpeed {
+        InterfaceSpeed {
+            interface: interface.to_string(),
+            speed: NetworkSpeed { download_mbps: down, upload_mbps: up, total_mbps: down + up },
+        }
+    }
+
+    #[test]
+    fn test_multi_interface_aggregation() {
+        let speeds = vec![speed("eth0", 1.0, 0.5), speed("wlan0", 4.0, 0.25), speed("wg0", 0.0, 0.0)];
+
+        let sum = sum_speed(&speeds);
+        assert_eq!(sum, NetworkSpeed { download_mbps: 5.0, upload_mbps: 0.75, total_mbps: 5.75 });
+        assert_eq!(busiest(&speeds).unwrap().interface, "wlan0");
+        assert_eq!(busiest(&[]), None);
+
+        assert_eq!("busiest".parse::<NetDisplayMode>().unwrap(), NetDisplayMode::Busiest);
+        assert_eq!("cycle".parse::<NetDisplayMode>().unwrap(), NetDisplayMode::Cycle { current: 0 });
+        assert!("average".parse::<NetDisplayMode>().is_err());
+
+        assert!(MultiNetworkSensor::new(Vec::new(), 50, 100, NetDisplayMode::Sum).is_err());
+        let lo = MultiNetworkSensor::new(vec!["lo".to_string()], 50, 100, NetDisplayMode::Sum).unwrap();
+        assert!(lo.build_tooltip(&speeds).contains("wlan0"));
+    }
+}
@@ -0,0 +1,98 @@
+//! Minimal synchronous rtnetlink client.
+//!
+//! Sensors read once per interval and never need the async machinery of the
+//! `rtnetlink` crate; a blocking `NETLINK_ROUTE` socket that sends one dump
+//! request and collects the replies is all it takes. Notifications are read
+//! the same way, from a socket subscribed to multicast groups.
+
+use netlink_packet_core::{NetlinkHeader, NetlinkMessage, NetlinkPayload, NLM_F_DUMP, NLM_F_REQUEST};
+use netlink_packet_route::RtnlMessage;
+use netlink_sys::{protocols::NETLINK_ROUTE, Socket, SocketAddr};
+use std::io;
+
+/// Receive buffer size; the kernel splits dumps into messages of at most a
+/// few pages.
+const RECV_BUFFER: usize = 32 * 1024;
+
+/// Decode the netlink messages in `bytes`, as received in one datagram.
+pub fn parse_messages(bytes: &[u8]) -> io::Result<Vec<NetlinkMessage<RtnlMessage>>> {
+    let mut messages = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let message: NetlinkMessage<RtnlMessage> = NetlinkMessage::deserialize(&bytes[offset..])
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let length = message.header.length as usize;
+        messages.push(message);
+        if length == 0 {
+            break;
+        }
+        offset += length;
+    }
+    Ok(messages)
+}
+
+/// Send `request` as a dump and return every message of the reply.
+///
+/// # Errors
+///
+/// Returns an error if the socket cannot be opened or the kernel rejects the
+/// request.
+pub fn dump(request: RtnlMessage) -> io::Result<Vec<RtnlMessage>> {
+    let mut socket = Socket::new(NETLINK_ROUTE)?;
+    socket.bind_auto()?;
+    socket.connect(&SocketAddr::new(0, 0))?;
+
+    let mut packet = NetlinkMessage::new(NetlinkHeader::default(), NetlinkPayload::from(request));
+    packet.header.flags = NLM_F_DUMP | NLM_F_REQUEST;
+    packet.header.sequence_number = 1;
+    packet.finalize();
+    let mut buffer = vec![0; packet.buffer_len()];
+    packet.serialize(&mut buffer);
+    socket.send(&buffer, 0)?;
+
+    let mut replies = Vec::new();
+    let mut receive = vec![0; RECV_BUFFER];
+    loop {
+        let size = socket.recv(&mut &mut receive[..], 0)?;
+        for message in parse_messages(&receive[..size])? {
+            match message.payload {
+                NetlinkPayload::Done(_) => return Ok(replies),
+                NetlinkPayload::Error(error) if error.code.is_some() => return Err(error.to_io()),
+                NetlinkPayload::InnerMessage(reply) => replies.push(reply),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Open a socket receiving the notifications of the `RTNLGRP_*` `groups`.
+///
+/// # Errors
+///
+/// Returns an error if the socket cannot be opened or a group joined.
+pub fn subscribe(groups: &[u32]) -> io::Result<Socket> {
+    let mut socket = Socket::new(NETLINK_ROUTE)?;
+    socket.bind_auto()?;
+    for &group in groups {
+        socket.add_membership(group)?;
+    }
+    Ok(socket)
+}
+
+/// Block until the next datagram arrives on `socket` and return its messages.
+///
+/// # Errors
+///
+/// Returns an error if receiving fails, e.g. with `ENOBUFS` after the kernel
+/// dropped notifications that were not read in time.
+pub fn receive(socket: &Socket) -> io::Result<Vec<RtnlMessage>> {
+    let mut receive = vec![0; RECV_BUFFER];
+    let size = socket.recv(&mut &mut receive[..], 0)?;
+    Ok(parse_messages(&receive[..size])?
+        .into_iter()
+        .filter_map(|message| match message.payload {
+            NetlinkPayload::InnerMessage(message) => Some(message),
+            _ => None,
+        })
+        .collect())
+}
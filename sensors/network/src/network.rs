@@ -1,4 +1,12 @@
-use waysensor_rs_core::{Sensor, SensorConfig, SensorError, WaybarOutput, format};
+use crate::addresses::{AddressDisplay, InterfaceAddress};
+use crate::aggregate::{self, Aggregate};
+use crate::connectivity::LinkState;
+use crate::data_usage::{DataUsage, Transfer};
+use crate::gateway::{self, GatewayProbe, Reachability};
+use crate::metered::{self, Metered, MeteredMonitor};
+use crate::talkers::{ProcessTraffic, TalkerTracker};
+use crate::wireless::{self, WifiMonitor};
+use waysensor_rs_core::{diff, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, TooltipDetail, WaybarOutput, format, procfs};
 use std::fs;
 use std::time::{Duration, Instant};
 
@@ -14,27 +22,90 @@ pub struct NetworkSensor {
     download_only: bool,
     last_stats: Option<NetworkStats>,
     last_time: Option<Instant>,
+    gateway_probe: GatewayProbe,
+    /// SSID, signal and bitrate of a wireless interface
+    wifi: Option<WifiMonitor>,
+    /// Persisted daily and monthly totals
+    usage: Option<DataUsage>,
+    /// Per-process TCP throughput for the tooltip
+    talkers: Option<TalkerTracker>,
+    /// Which of the interface's addresses the tooltip lists
+    address_display: AddressDisplay,
+    /// Hotspot, tethering and mobile broadband detection
+    metered: Option<MeteredMonitor>,
+    /// Member ports when the interface is a bond, bridge or team
+    aggregate: Option<Aggregate>,
 }
 
 #[derive(Debug, Clone)]
-struct NetworkStats {
-    rx_bytes: u64,
-    tx_bytes: u64,
-    rx_packets: u64,
-    tx_packets: u64,
+pub(crate) struct NetworkStats {
+    pub(crate) rx_bytes: u64,
+    pub(crate) tx_bytes: u64,
+    pub(crate) rx_packets: u64,
+    pub(crate) tx_packets: u64,
 }
 
-#[derive(Debug, Clone)]
-struct NetworkSpeed {
-    download_mbps: f64,
-    upload_mbps: f64,
-    total_mbps: f64,
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct NetworkSpeed {
+    pub(crate) download_mbps: f64,
+    pub(crate) upload_mbps: f64,
+    pub(crate) total_mbps: f64,
+}
+
+impl NetworkStats {
+    /// Read the counters of `interface` from sysfs.
+    pub(crate) fn read(interface: &str) -> Result<Self, SensorError> {
+        let stats_dir = format!("/sys/class/net/{}/statistics", interface);
+        let read = |name: &str| -> Result<u64, SensorError> {
+            procfs::read_with(format!("{}/{}", stats_dir, name), |content| content.trim().parse::<u64>())
+                .map_err(SensorError::Io)?
+                .map_err(|e| SensorError::Parse {
+                    message: format!("Failed to parse stat: {}", e),
+                    source: None,
+                })
+        };
+        Ok(Self {
+            rx_bytes: read("rx_bytes")?,
+            tx_bytes: read("tx_bytes")?,
+            rx_packets: read("rx_packets")?,
+            tx_packets: read("tx_packets")?,
+        })
+    }
+}
+
+impl NetworkSpeed {
+    /// Throughput between two readings `duration` apart.
+    pub(crate) fn between(current: &NetworkStats, last: &NetworkStats, duration: Duration) -> Self {
+        let duration_secs = duration.as_secs_f64();
+        
+        if duration_secs <= 0.0 {
+            return Self::default();
+        }
+        
+        // Calculate bytes per second, then convert to Mbps
+        let rx_bytes_per_sec = (current.rx_bytes.saturating_sub(last.rx_bytes)) as f64 / duration_secs;
+        let tx_bytes_per_sec = (current.tx_bytes.saturating_sub(last.tx_bytes)) as f64 / duration_secs;
+        
+        // Convert bytes/sec to Mbps (1 MB = 1,000,000 bytes)
+        let download_mbps = rx_bytes_per_sec / 1_000_000.0;
+        let upload_mbps = tx_bytes_per_sec / 1_000_000.0;
+        let total_mbps = download_mbps + upload_mbps;
+        
+        Self {
+            download_mbps,
+            upload_mbps,
+            total_mbps,
+        }
+    }
 }
 
 impl NetworkSensor {
+    /// Default time between gateway reachability probes.
+    const GATEWAY_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+    
     /// Create a visual bar gauge for a speed value relative to maximum.
     /// Returns a string with filled and empty blocks to represent the speed.
-    fn create_speed_gauge(speed_mbps: f64, max_mbps: f64, width: usize) -> String {
+    pub(crate) fn create_speed_gauge(speed_mbps: f64, max_mbps: f64, width: usize) -> String {
         let percentage = if max_mbps > 0.0 {
             ((speed_mbps / max_mbps) * 100.0).min(100.0)
         } else {
@@ -55,7 +126,7 @@ impl NetworkSensor {
     }
     
     /// Get a color indicator based on network speed.
-    fn get_speed_indicator(speed_mbps: f64, warning: f64, critical: f64) -> &'static str {
+    pub(crate) fn get_speed_indicator(speed_mbps: f64, warning: f64, critical: f64) -> &'static str {
         match speed_mbps {
             s if s >= critical => "🔴",     // Critical - very high traffic
             s if s >= warning => "🟠",     // Warning - high traffic
@@ -91,7 +162,6 @@ impl NetworkSensor {
         Ok(Self {
             name: format!("network-{}", interface),
             config: SensorConfig::default(),
-            interface,
             warning_threshold,
             critical_threshold,
             show_total,
@@ -99,10 +169,36 @@ impl NetworkSensor {
             download_only,
             last_stats: None,
             last_time: None,
+            gateway_probe: GatewayProbe::new(Self::GATEWAY_CHECK_INTERVAL),
+            wifi: wireless::is_wireless(&interface).then(WifiMonitor::new),
+            usage: None,
+            talkers: None,
+            address_display: AddressDisplay::default(),
+            metered: Some(MeteredMonitor::new()),
+            aggregate: Aggregate::detect(&interface),
+            interface,
         })
     }
+
+    /// List the processes moving the most data in the tooltip.
+    #[must_use]
+    pub fn with_top_talkers(mut self, enabled: bool) -> Self {
+        if enabled && self.talkers.is_none() {
+            self.talkers = Some(TalkerTracker::new());
+        }
+        self
+    }
+
+    /// Track daily and monthly transfer totals across restarts.
+    #[must_use]
+    pub fn with_data_usage(mut self, enabled: bool) -> Self {
+        if enabled && self.usage.is_none() {
+            self.usage = Some(DataUsage::load(&self.interface));
+        }
+        self
+    }
     
-    fn find_primary_interface() -> Result<String, SensorError> {
+    pub(crate) fn find_primary_interface() -> Result<String, SensorError> {
         // Look for the primary interface (not loopback, virtual, or docker)
         let interfaces = fs::read_dir("/sys/class/net")
             .map_err(|e| SensorError::Io(e))?;
@@ -125,11 +221,13 @@ impl NetworkSensor {
                     let operstate_path = format!("/sys/class/net/{}/operstate", name);
                     if let Ok(state) = fs::read_to_string(&operstate_path) {
                         if state.trim() == "up" {
+                            // Traffic of bond and bridge members is counted on the master
+                            let name = aggregate::master_of(name).unwrap_or_else(|| name.to_string());
                             // Prefer ethernet over wireless
-                            if name.starts_with("eth") || name.starts_with("enp") {
-                                return Ok(name.to_string());
+                            if name.starts_with("eth") || name.starts_with("enp") || name.starts_with("bond") {
+                                return Ok(name);
                             }
-                            candidates.push(name.to_string());
+                            candidates.push(name);
                         }
                     }
                 }
@@ -147,60 +245,13 @@ impl NetworkSensor {
     }
     
     fn read_interface_stats(&self) -> Result<NetworkStats, SensorError> {
-        let stats_dir = format!("/sys/class/net/{}/statistics", self.interface);
-        
-        let rx_bytes = self.read_stat_file(&format!("{}/rx_bytes", stats_dir))?;
-        let tx_bytes = self.read_stat_file(&format!("{}/tx_bytes", stats_dir))?;
-        let rx_packets = self.read_stat_file(&format!("{}/rx_packets", stats_dir))?;
-        let tx_packets = self.read_stat_file(&format!("{}/tx_packets", stats_dir))?;
-        
-        Ok(NetworkStats {
-            rx_bytes,
-            tx_bytes,
-            rx_packets,
-            tx_packets,
-        })
-    }
-    
-    fn read_stat_file(&self, path: &str) -> Result<u64, SensorError> {
-        let content = fs::read_to_string(path)
-            .map_err(|e| SensorError::Io(e))?;
-        
-        content.trim().parse::<u64>()
-            .map_err(|e| SensorError::Parse {
-                message: format!("Failed to parse stat: {}", e),
-                source: None,
-            })
-    }
-    
-    fn calculate_speed(&self, current: &NetworkStats, last: &NetworkStats, duration: Duration) -> NetworkSpeed {
-        let duration_secs = duration.as_secs_f64();
-        
-        if duration_secs <= 0.0 {
-            return NetworkSpeed {
-                download_mbps: 0.0,
-                upload_mbps: 0.0,
-                total_mbps: 0.0,
-            };
-        }
-        
-        // Calculate bytes per second, then convert to Mbps
-        let rx_bytes_per_sec = (current.rx_bytes.saturating_sub(last.rx_bytes)) as f64 / duration_secs;
-        let tx_bytes_per_sec = (current.tx_bytes.saturating_sub(last.tx_bytes)) as f64 / duration_secs;
-        
-        // Convert bytes/sec to Mbps (1 MB = 1,000,000 bytes)
-        let download_mbps = rx_bytes_per_sec / 1_000_000.0;
-        let upload_mbps = tx_bytes_per_sec / 1_000_000.0;
-        let total_mbps = download_mbps + upload_mbps;
-        
-        NetworkSpeed {
-            download_mbps,
-            upload_mbps,
-            total_mbps,
+        match &self.aggregate {
+            Some(aggregate) => aggregate.stats(),
+            None => NetworkStats::read(&self.interface),
         }
     }
     
-    fn format_speed(mbps: f64) -> String {
+    pub(crate) fn format_speed(mbps: f64) -> String {
         if mbps >= 1000.0 {
             format!("{:.1}GB/s", mbps / 1000.0)
         } else if mbps >= 1.0 {
@@ -213,6 +264,27 @@ impl NetworkSensor {
     }
 }
 
+impl Discoverable for NetworkSensor {
+    const KIND: &'static str = "network interfaces";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        let interfaces = crate::auto_detect::detect_active_interfaces()
+            .map_err(|e| SensorError::unavailable(format!("Failed to detect interfaces: {}", e)))?;
+
+        Ok(interfaces
+            .into_iter()
+            .map(|iface| {
+                let yes_no = |b: bool| if b { "yes" } else { "no" };
+                DiscoveredItem::new(iface.name, format!("{:?}", iface.interface_type))
+                    .with_detail("up", yes_no(iface.is_up))
+                    .with_detail("has ip", yes_no(iface.has_ip))
+                    .with_detail("packets", format!("{} rx / {} tx", iface.rx_packets, iface.tx_packets))
+                    .with_detail("activity score", format!("{:.1}", iface.activity_score))
+            })
+            .collect())
+    }
+}
+
 impl Sensor for NetworkSensor {
     type Error = SensorError;
     
@@ -222,22 +294,26 @@ impl Sensor for NetworkSensor {
         
         let speed = if let (Some(last_stats), Some(last_time)) = (&self.last_stats, &self.last_time) {
             let duration = current_time.duration_since(*last_time);
-            self.calculate_speed(&current_stats, last_stats, duration)
+            NetworkSpeed::between(&current_stats, last_stats, duration)
         } else {
             // First read, no speed data available yet
-            NetworkSpeed {
-                download_mbps: 0.0,
-                upload_mbps: 0.0,
-                total_mbps: 0.0,
-            }
+            NetworkSpeed::default()
         };
         
         // Update for next reading
         self.last_stats = Some(current_stats.clone());
         self.last_time = Some(current_time);
+        if let Some(wifi) = &mut self.wifi {
+            wifi.update(&self.interface);
+        }
+        let metered = self.metered.as_mut().map_or(Metered::Unknown, |monitor| monitor.update(&self.interface));
+        let today = chrono::Local::now().date_naive();
+        if let Some(usage) = &mut self.usage {
+            usage.update(Transfer { rx: current_stats.rx_bytes, tx: current_stats.tx_bytes }, today);
+        }
         
         // Determine which icon to use
-        let icon = if self.interface.starts_with("wl") || self.interface.starts_with("wlan") {
+        let icon = if self.wifi.is_some() || self.interface.starts_with("wl") {
             &self.config.icons.network_wifi
         } else {
             &self.config.icons.network_ethernet
@@ -264,12 +340,27 @@ impl Sensor for NetworkSensor {
             (text, speed.total_mbps)
         };
         
-        let tooltip = self.build_tooltip(&current_stats, &speed);
+        let text = if metered.is_metered() { metered::badge(&text, &self.config) } else { text };
+        // Without a link the speeds are meaningless, say why instead
+        let link = LinkState::read(&self.interface);
+        let text = if link == LinkState::Up { text } else { format::with_icon_and_colors(link.label(), icon, &self.config) };
+        
+        let mut tooltip = self.build_tooltip(&current_stats, &speed, link);
+        if let Some(talkers) = &mut self.talkers {
+            let traffic = talkers.update();
+            tooltip.push_str(&self.format_talkers_section(&traffic));
+        }
+        if let Some(usage) = &self.usage {
+            tooltip.push_str(&format!("\n\n{}", usage.tooltip_lines(today, &self.config).join("\n")));
+        }
+        if self.config.visuals.tooltip_detail == TooltipDetail::Expert {
+            tooltip.push_str(&self.format_route_section());
+        }
         
         // Calculate percentage based on total throughput
         let percentage = ((value_for_theming / self.critical_threshold as f64) * 100.0).min(100.0) as u8;
         
-        Ok(format::themed_output(
+        let mut output = format::themed_output(
             text,
             Some(tooltip),
             Some(percentage),
@@ -277,7 +368,29 @@ impl Sensor for NetworkSensor {
             self.warning_threshold as f64,
             self.critical_threshold as f64,
             &self.config.theme,
-        ))
+        );
+        
+        // A weak WiFi signal outranks a busy link
+        let theme = &self.config.theme;
+        if let Some(class) = self.wifi.as_ref().and_then(|wifi| wifi.class(theme)) {
+            if diff::severity(Some(class), theme) > diff::severity(output.class.as_deref(), theme) {
+                output.class = Some(class.to_owned());
+            }
+        }
+        if let Some(class) = self.usage.as_ref().and_then(|usage| usage.class(today, theme)) {
+            if diff::severity(Some(class), theme) > diff::severity(output.class.as_deref(), theme) {
+                output.class = Some(class.to_owned());
+            }
+        }
+        // Flag metered connections, unless there is something worse to show
+        if metered.is_metered() && diff::severity(output.class.as_deref(), theme) <= 1 {
+            output.class = Some(metered::METERED_CLASS.to_string());
+        }
+        if link != LinkState::Up {
+            output.class = Some(theme.critical.clone());
+        }
+        
+        Ok(output)
     }
     
     fn name(&self) -> &str {
@@ -285,6 +398,38 @@ impl Sensor for NetworkSensor {
     }
     
     fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        if let Some(secs) = config.get_custom("gateway_check_interval_secs").and_then(|v| v.as_u64()) {
+            self.gateway_probe.set_interval(Duration::from_secs(secs));
+        }
+        if let Some(wifi) = &mut self.wifi {
+            if let Some(percent) = config.get_custom("wifi_signal_warning").and_then(|v| v.as_f64()) {
+                wifi.signal_warning = percent;
+            }
+            if let Some(percent) = config.get_custom("wifi_signal_critical").and_then(|v| v.as_f64()) {
+                wifi.signal_critical = percent;
+            }
+        }
+        self.address_display = AddressDisplay::from_config(&config);
+        if config.get_custom("metered_detection").and_then(|v| v.as_bool()) == Some(false) {
+            self.metered = None;
+        }
+        if config.get_custom("top_talkers").and_then(|v| v.as_bool()) == Some(true) && self.talkers.is_none() {
+            self.talkers = Some(TalkerTracker::new());
+        }
+        if config.get_custom("data_usage").and_then(|v| v.as_bool()) == Some(true) && self.usage.is_none() {
+            self.usage = Some(DataUsage::load(&self.interface));
+        }
+        if let Some(usage) = &mut self.usage {
+            if let Some(gb) = config.get_custom("data_cap_gb").and_then(|v| v.as_f64()) {
+                usage.cap = Some((gb * 1e9) as u64);
+            }
+            if let Some(percent) = config.get_custom("data_cap_warning").and_then(|v| v.as_f64()) {
+                usage.cap_warning = percent;
+            }
+            if let Some(day) = config.get_custom("billing_day").and_then(|v| v.as_u64()) {
+                usage.billing_day = day as u32;
+            }
+        }
         self.config = config;
         Ok(())
     }
@@ -295,7 +440,58 @@ impl Sensor for NetworkSensor {
 }
 
 impl NetworkSensor {
-    fn build_tooltip(&self, stats: &NetworkStats, speed: &NetworkSpeed) -> String {
+    /// Processes with the highest TCP throughput, following the top process
+    /// settings of the visuals config.
+    fn format_talkers_section(&self, traffic: &[ProcessTraffic]) -> String {
+        if !self.config.visuals.show_top_processes || traffic.is_empty() {
+            return String::new();
+        }
+        let max_name_length = self.config.visuals.process_name_max_length as usize;
+        
+        let mut lines = vec![format::key_only("Top Talkers (TCP)", &self.config)];
+        for process in traffic.iter().take(self.config.visuals.top_processes_count as usize) {
+            let mut name = process.name.clone();
+            if name.chars().count() > max_name_length {
+                name = name.chars().take(max_name_length.saturating_sub(3)).collect();
+                name.push_str("...");
+            }
+            lines.push(format::key_value(&format!("  {}", name), &format!(
+                "↓ {} ↑ {}",
+                Self::format_speed(process.download_mbps),
+                Self::format_speed(process.upload_mbps),
+            ), &self.config));
+        }
+        format!("\n\n{}", lines.join("\n"))
+    }
+    
+    /// Gateway, reachability and DNS lines for the expert tooltip.
+    fn format_route_section(&mut self) -> String {
+        let mut lines = vec![format::key_only("Routing", &self.config)];
+        
+        match gateway::default_gateway(&self.interface) {
+            Some(gateway) => {
+                let status = match self.gateway_probe.check(gateway, &self.interface) {
+                    Reachability::Reachable(rtt) => format!("🟢 {:.1} ms", rtt),
+                    Reachability::Unreachable => "🔴 unreachable".to_string(),
+                    Reachability::Unknown => "⚪ not checked".to_string(),
+                };
+                lines.push(format::key_value("Gateway", &format!("{} {}", gateway, status), &self.config));
+            }
+            None => lines.push(format::key_value("Gateway", "none (no default route)", &self.config)),
+        }
+        
+        let dns = gateway::dns_servers();
+        let dns = if dns.is_empty() {
+            "none".to_string()
+        } else {
+            dns.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+        };
+        lines.push(format::key_value("DNS", &dns, &self.config));
+        
+        format!("\n\n{}", lines.join("\n"))
+    }
+    
+    fn build_tooltip(&self, stats: &NetworkStats, speed: &NetworkSpeed, link: LinkState) -> String {
         use waysensor_rs_core::format;
         
         let max_speed = self.critical_threshold as f64;
@@ -325,8 +521,24 @@ impl NetworkSensor {
         let tx_line = format::key_value("TX", &format!("{} ({} packets)", 
             format::bytes_to_human(stats.tx_bytes), stats.tx_packets), &self.config);
         
-        format!("{}\n{}\n{}\n{}\n\n{}\n{}\n{}", 
-            interface_line, download_line, upload_line, total_line, 
+        let addresses = if self.address_display.enabled() {
+            InterfaceAddress::query(&self.interface).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        let link_lines: String = (link != LinkState::Up).then(|| format::key_value("Link", link.label(), &self.config)).into_iter()
+            .chain(self.address_display.tooltip_lines(&addresses, &self.config))
+            .chain(self.wifi.iter().flat_map(|wifi| wifi.link().tooltip_lines(&self.config)))
+            .chain(self.metered.iter()
+                .map(MeteredMonitor::state)
+                .filter(|&state| state != Metered::Unknown)
+                .map(|state| format::key_value("Metered", state.describe(), &self.config)))
+            .chain(self.aggregate.iter().flat_map(|aggregate| aggregate.tooltip_lines(&self.config)))
+            .map(|line| format!("\n{}", line))
+            .collect();
+        
+        format!("{}{}\n{}\n{}\n{}\n\n{}\n{}\n{}", 
+            interface_line, link_lines, download_line, upload_line, total_line, 
             transfer_header, rx_line, tx_line)
     }
 }
\ No newline at end of file
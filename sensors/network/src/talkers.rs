@@ -0,0 +1,192 @@
+//! Processes moving the most data.
+//!
+//! Per-process accounting normally takes packet capture (nethogs) or eBPF,
+//! both of which need root. TCP sockets however keep byte counters that
+//! `ss -tinp` reports together with the owning process, so diffing two
+//! snapshots gives each process' TCP throughput without privileges. Sockets
+//! of other users are only attributed when running as root, and UDP traffic
+//! (QUIC, games, VoIP) is not counted.
+
+use std::{
+    collections::HashMap,
+    process::Command,
+    time::Instant,
+};
+
+/// Byte counters of one TCP connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketSample {
+    /// Local and peer address, unique while the connection exists
+    pub key: (String, String),
+    /// Owning process
+    pub pid: u32,
+    pub name: String,
+    /// Bytes acknowledged by the peer
+    pub sent: u64,
+    pub received: u64,
+}
+
+impl SocketSample {
+    /// Parse `ss -tinpH state established` output: a line per socket
+    /// followed by an indented line of TCP info.
+    pub fn parse_ss(output: &str) -> Vec<Self> {
+        let mut samples = Vec::new();
+        let mut current: Option<Self> = None;
+        for line in output.lines() {
+            if line.starts_with(char::is_whitespace) {
+                let Some(mut sample) = current.take() else { continue };
+                let counter = |name: &str| line.split_whitespace()
+                    .find_map(|field| field.strip_prefix(name)?.strip_prefix(':')?.parse::<u64>().ok());
+                sample.sent = counter("bytes_acked").or_else(|| counter("bytes_sent")).unwrap_or(0);
+                sample.received = counter("bytes_received").unwrap_or(0);
+                samples.push(sample);
+                continue;
+            }
+            current = Self::parse_socket_line(line);
+        }
+        samples
+    }
+
+    /// `Recv-Q Send-Q Local Peer users:(("name",pid=1,fd=3),...)`
+    fn parse_socket_line(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [_, _, local, peer, ..] = fields[..] else {
+            return None;
+        };
+        let users = line.split_once("users:((\"")?.1;
+        let (name, rest) = users.split_once('"')?;
+        let pid = rest.split_once("pid=")?.1
+            .split(|c: char| !c.is_ascii_digit())
+            .next()?
+            .parse()
+            .ok()?;
+        Some(Self {
+            key: (local.to_owned(), peer.to_owned()),
+            pid,
+            name: name.to_owned(),
+            sent: 0,
+            received: 0,
+        })
+    }
+}
+
+/// Snapshot of every established TCP connection, empty if `ss` is missing.
+pub fn scan() -> Vec<SocketSample> {
+    Command::new("ss")
+        .args(["-tinpH", "state", "established"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| SocketSample::parse_ss(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// TCP throughput of a process over the last sampling interval.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessTraffic {
+    pub pid: u32,
+    pub name: String,
+    /// Download in MB/s
+    pub download_mbps: f64,
+    /// Upload in MB/s
+    pub upload_mbps: f64,
+}
+
+/// Tracks per-connection byte counters between scans.
+#[derive(Debug, Default)]
+pub struct TalkerTracker {
+    prev: HashMap<(String, String), (u64, u64)>,
+    prev_time: Option<Instant>,
+}
+
+impl TalkerTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scan the connections and return throughput per process since the
+    /// previous call, busiest first.
+    ///
+    /// The first call only records a baseline and returns an empty list.
+    pub fn update(&mut self) -> Vec<ProcessTraffic> {
+        self.update_with(scan(), Instant::now())
+    }
+
+    /// Like [`update`](Self::update), with the samples and time supplied by the caller.
+    pub fn update_with(&mut self, samples: Vec<SocketSample>, now: Instant) -> Vec<ProcessTraffic> {
+        let elapsed = self.prev_time.map(|t| now.duration_since(t).as_secs_f64());
+
+        let mut traffic: HashMap<u32, ProcessTraffic> = HashMap::new();
+        let mut counters = HashMap::with_capacity(samples.len());
+        for sample in samples {
+            if let Some(elapsed) = elapsed.filter(|&elapsed| elapsed > 0.0) {
+                // Connections opened since the last scan count in full
+                let (sent, received) = self.prev.get(&sample.key).copied().unwrap_or((0, 0));
+                let entry = traffic.entry(sample.pid).or_insert_with(|| ProcessTraffic {
+                    pid: sample.pid,
+                    name: sample.name.clone(),
+                    download_mbps: 0.0,
+                    upload_mbps: 0.0,
+                });
+                entry.download_mbps += sample.received.saturating_sub(received) as f64 / elapsed / 1_000_000.0;
+                entry.upload_mbps += sample.sent.saturating_sub(sent) as f64 / elapsed / 1_000_000.0;
+            }
+            counters.insert(sample.key, (sample.sent, sample.received));
+        }
+
+        self.prev = counters;
+        self.prev_time = Some(now);
+
+        let mut traffic: Vec<ProcessTraffic> = traffic.into_values()
+            .filter(|process| process.download_mbps + process.upload_mbps > 0.0)
+            .collect();
+        traffic.sort_by(|a, b| (b.download_mbps + b.upload_mbps).total_cmp(&(a.download_mbps + a.upload_mbps)));
+        traffic
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_top_talkers() {
+        let ss = |firefox_rx: u64, curl: bool| {
+            let mut output = format!("\
+0      0      192.168.1.5:54322      140.82.112.21:443     users:((\"firefox\",pid=2345,fd=120))
+\t cubic wscale:7,7 rto:220 rtt:19.5/3.2 mss:1448 bytes_sent:6000 bytes_acked:5000 bytes_received:{} segs_out:40
+0      0      192.168.1.5:54330      151.101.1.69:443      users:((\"firefox\",pid=2345,fd=121))
+\t cubic rto:204 rtt:3.1/1.5 bytes_acked:100 bytes_received:1000 segs_out:4
+0      0      [::1]:631      [::1]:40112
+\t cubic rto:204 bytes_acked:1 bytes_received:1
+", firefox_rx);
+            if curl {
+                output.push_str("0      0      192.168.1.5:41000      93.184.216.34:443     users:((\"curl\",pid=999,fd=5))\n\t cubic bytes_sent:3000000 bytes_received:500\n");
+            }
+            output
+        };
+
+        let samples = SocketSample::parse_ss(&ss(2_000_000, false));
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0], SocketSample {
+            key: ("192.168.1.5:54322".to_string(), "140.82.112.21:443".to_string()),
+            pid: 2345,
+            name: "firefox".to_string(),
+            sent: 5000,
+            received: 2_000_000,
+        });
+
+        let start = Instant::now();
+        let mut tracker = TalkerTracker::new();
+        assert!(tracker.update_with(samples, start).is_empty());
+
+        let later = SocketSample::parse_ss(&ss(6_000_000, true));
+        let traffic = tracker.update_with(later, start + Duration::from_secs(2));
+        assert_eq!(traffic.len(), 2);
+        assert_eq!((traffic[0].name.as_str(), traffic[0].pid), ("firefox", 2345));
+        assert_eq!(traffic[0].download_mbps, 2.0);
+        assert_eq!(traffic[1].name, "curl");
+        assert_eq!(traffic[1].upload_mbps, 1.5);
+    }
+}
@@ -0,0 +1,322 @@
+//! VPN tunnels: WireGuard, Tailscale and tun-based clients like OpenVPN.
+//!
+//! Tunnels are found in sysfs: WireGuard links carry `DEVTYPE=wireguard` in
+//! their uevent whatever they are called (`wg0`, `nordlynx`), tun devices
+//! have `tun_flags`. WireGuard has no connection state of its own, so the
+//! age of the last handshake stands in for it: peers re-handshake every two
+//! minutes while traffic flows, and after three minutes without one the
+//! kernel stops sending on the session. `wg show` needs `CAP_NET_ADMIN`;
+//! without it tunnels are still reported, just without peer details.
+
+use crate::connectivity::LinkState;
+use waysensor_rs_core::{format, Sensor, SensorConfig, SensorError, Theme, WaybarOutput};
+use std::{
+    fs,
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Seconds after the last handshake at which a WireGuard session expires.
+pub const HANDSHAKE_STALE_SECS: u64 = 180;
+
+/// Kind of tunnel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VpnKind {
+    WireGuard,
+    Tailscale,
+    /// tun device of a userspace client (OpenVPN, OpenConnect, ...)
+    Tun,
+}
+
+impl VpnKind {
+    /// Classify an interface from its name, uevent and whether it has
+    /// `tun_flags`.
+    pub fn detect(name: &str, uevent: &str, is_tun: bool) -> Option<Self> {
+        if uevent.lines().any(|line| line == "DEVTYPE=wireguard") || name.starts_with("wg") {
+            Some(Self::WireGuard)
+        } else if name.starts_with("tailscale") {
+            Some(Self::Tailscale)
+        } else if is_tun || name.starts_with("tun") {
+            Some(Self::Tun)
+        } else {
+            None
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::WireGuard => "WireGuard",
+            Self::Tailscale => "Tailscale",
+            Self::Tun => "tun",
+        }
+    }
+}
+
+/// A tunnel interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VpnInterface {
+    pub name: String,
+    pub kind: VpnKind,
+    pub up: bool,
+}
+
+impl VpnInterface {
+    /// Inspect `name`, `None` if it is not a tunnel.
+    pub fn read(name: &str) -> Option<Self> {
+        let device = Path::new("/sys/class/net").join(name);
+        let uevent = fs::read_to_string(device.join("uevent")).unwrap_or_default();
+        let kind = VpnKind::detect(name, &uevent, device.join("tun_flags").exists())?;
+        Some(Self {
+            name: name.to_owned(),
+            kind,
+            up: LinkState::read(name) == LinkState::Up,
+        })
+    }
+}
+
+/// All tunnel interfaces, sorted by name.
+pub fn detect_vpns() -> Vec<VpnInterface> {
+    let Ok(entries) = fs::read_dir("/sys/class/net") else {
+        return Vec::new();
+    };
+    let mut vpns: Vec<VpnInterface> = entries
+        .flatten()
+        .filter_map(|entry| VpnInterface::read(&entry.file_name().to_string_lossy()))
+        .collect();
+    vpns.sort_by(|a, b| a.name.cmp(&b.name));
+    vpns
+}
+
+/// A WireGuard peer from `wg show <interface> dump`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WireGuardPeer {
+    pub public_key: String,
+    pub endpoint: Option<String>,
+    /// Unix time of the last handshake
+    pub latest_handshake: Option<u64>,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+}
+
+impl WireGuardPeer {
+    /// Parse `wg show <interface> dump`. The first line describes the
+    /// interface itself and is skipped.
+    pub fn parse_dump(output: &str) -> Vec<Self> {
+        output.lines()
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split('\t').collect();
+                let [public_key, _preshared, endpoint, _allowed_ips, handshake, rx, tx, ..] = fields[..] else {
+                    return None;
+                };
+                Some(Self {
+                    public_key: public_key.to_owned(),
+                    endpoint: (endpoint != "(none)").then(|| endpoint.to_owned()),
+                    latest_handshake: handshake.parse().ok().filter(|&at| at > 0),
+                    rx_bytes: rx.parse().unwrap_or(0),
+                    tx_bytes: tx.parse().unwrap_or(0),
+                })
+            })
+            .collect()
+    }
+
+    /// Peers of `interface`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `wg` is missing or not allowed to read the interface.
+    pub fn query(interface: &str) -> Result<Vec<Self>, SensorError> {
+        let output = Command::new("wg")
+            .args(["show", interface, "dump"])
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => SensorError::unavailable("wg not installed"),
+                _ => SensorError::Io(e),
+            })?;
+        if !output.status.success() {
+            return Err(SensorError::PermissionDenied {
+                resource: format!("WireGuard interface {}", interface),
+            });
+        }
+        Ok(Self::parse_dump(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Seconds since the last handshake at Unix time `now`.
+    pub fn handshake_age(&self, now: u64) -> Option<u64> {
+        self.latest_handshake.map(|at| now.saturating_sub(at))
+    }
+}
+
+/// Rough age, e.g. `42 s` or `3 min`.
+fn human_age(secs: u64) -> String {
+    match secs {
+        s if s >= 3600 => format!("{} h", s / 3600),
+        s if s >= 60 => format!("{} min", s / 60),
+        s => format!("{} s", s),
+    }
+}
+
+/// Tunnel state for the bar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VpnState {
+    /// A tunnel is up and, for WireGuard, handshaking
+    Up,
+    /// A WireGuard tunnel is up but no peer handshook recently
+    Stale,
+    /// No tunnel is up
+    Down,
+}
+
+impl VpnState {
+    /// CSS class: good while connected, warning when stale, critical when down.
+    pub fn class(self, theme: &Theme) -> &str {
+        match self {
+            Self::Up => &theme.good,
+            Self::Stale => &theme.warning,
+            Self::Down => &theme.critical,
+        }
+    }
+}
+
+/// VPN up/down indicator with WireGuard peer details.
+#[derive(Debug)]
+pub struct VpnSensor {
+    name: String,
+    config: SensorConfig,
+    /// Tunnel to watch, any tunnel when `None`
+    interface: Option<String>,
+}
+
+impl VpnSensor {
+    pub fn new(interface: Option<String>) -> Self {
+        let name = match &interface {
+            Some(interface) => format!("vpn-{}", interface),
+            None => "vpn".to_string(),
+        };
+        Self {
+            name,
+            config: SensorConfig::default(),
+            interface,
+        }
+    }
+
+    /// Tunnels to report: the configured one, or every one found.
+    fn tunnels(&self) -> Vec<VpnInterface> {
+        match &self.interface {
+            Some(interface) => VpnInterface::read(interface).into_iter().collect(),
+            None => detect_vpns(),
+        }
+    }
+}
+
+impl Sensor for VpnSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let config = &self.config;
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let tunnels = self.tunnels();
+
+        let mut state = VpnState::Down;
+        let mut shown = None;
+        let mut lines = Vec::new();
+        for tunnel in &tunnels {
+            let status = if tunnel.up { "🟢 up" } else { "🔴 down" };
+            lines.push(format::key_value(&tunnel.name, &format!("{} ({})", status, tunnel.kind.label()), config));
+            if !tunnel.up {
+                continue;
+            }
+
+            let mut tunnel_state = VpnState::Up;
+            if tunnel.kind == VpnKind::WireGuard {
+                match WireGuardPeer::query(&tunnel.name) {
+                    Ok(peers) => {
+                        let fresh = peers.iter()
+                            .filter_map(|peer| peer.handshake_age(now))
+                            .any(|age| age < HANDSHAKE_STALE_SECS);
+                        if !fresh {
+                            tunnel_state = VpnState::Stale;
+                        }
+                        for peer in &peers {
+                            let handshake = peer.handshake_age(now)
+                                .map_or_else(|| "never".to_string(), |age| format!("{} ago", human_age(age)));
+                            lines.push(format::key_value("  Endpoint", peer.endpoint.as_deref().unwrap_or("(none)"), config));
+                            lines.push(format::key_value("  Handshake", &handshake, config));
+                            lines.push(format::key_value("  Transfer", &format!(
+                                "↓ {} ↑ {}",
+                                format::bytes_to_human(peer.rx_bytes),
+                                format::bytes_to_human(peer.tx_bytes),
+                            ), config));
+                        }
+                    }
+                    Err(e) => lines.push(format::key_value("  Peers", &format!("unknown ({})", e), config)),
+                }
+            }
+            // Show the healthiest tunnel in the bar
+            if shown.is_none() || (state == VpnState::Stale && tunnel_state == VpnState::Up) {
+                shown = Some(tunnel.name.as_str());
+                state = tunnel_state;
+            }
+        }
+        if tunnels.is_empty() {
+            lines.push(format::key_value("VPN", "no tunnel interfaces", config));
+        }
+
+        let text = match (state, shown) {
+            (VpnState::Down, _) | (_, None) => "off".to_string(),
+            (_, Some(name)) => name.to_string(),
+        };
+        Ok(WaybarOutput::new(format::with_icon_and_colors(&text, &config.icons.vpn, config))
+            .with_tooltip(lines.join("\n"))
+            .with_class(state.class(&config.theme)))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vpn_detection() {
+        assert_eq!(VpnKind::detect("nordlynx", "DEVTYPE=wireguard\nINTERFACE=nordlynx\nIFINDEX=5\n", false), Some(VpnKind::WireGuard));
+        assert_eq!(VpnKind::detect("wg0", "INTERFACE=wg0\n", false), Some(VpnKind::WireGuard));
+        assert_eq!(VpnKind::detect("tailscale0", "INTERFACE=tailscale0\n", true), Some(VpnKind::Tailscale));
+        assert_eq!(VpnKind::detect("corp", "INTERFACE=corp\n", true), Some(VpnKind::Tun));
+        assert_eq!(VpnKind::detect("eth0", "INTERFACE=eth0\n", false), None);
+
+        let dump = "\
+yAnz5TF+lXXJte14tji3zlMNq+hd2rYUIgJBgB3fBmk=\tHIgo9xNzJMWLKASShiTqIybxZ0U3wGLiUeJ1PKf8ykw=\t51820\toff
+xTIBA5rboUvnH4htodjb6e697QjLERt1NAB4mZqp8Dg=\t(none)\t203.0.113.1:51820\t10.0.0.0/24\t1700000000\t1024\t2048\t25
+TrMvSoP4jYQlY6RIzBgbssQqY3vxI2Pi+y71lOWWXX0=\t(none)\t(none)\t10.0.1.2/32\t0\t0\t0\toff
+";
+        let peers = WireGuardPeer::parse_dump(dump);
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers[0].endpoint.as_deref(), Some("203.0.113.1:51820"));
+        assert_eq!(peers[0].handshake_age(1_700_000_042), Some(42));
+        assert_eq!((peers[0].rx_bytes, peers[0].tx_bytes), (1024, 2048));
+        assert_eq!(peers[1].endpoint, None);
+        assert_eq!(peers[1].latest_handshake, None);
+
+        assert_eq!(human_age(42), "42 s");
+        assert_eq!(human_age(200), "3 min");
+
+        let theme = Theme::default();
+        assert_eq!(VpnState::Down.class(&theme), theme.critical);
+        assert_eq!(VpnState::Stale.class(&theme), theme.warning);
+        assert!(VpnInterface::read("lo").is_none());
+    }
+}
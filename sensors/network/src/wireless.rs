@@ -0,0 +1,262 @@
+//! WiFi link details: SSID, signal strength and bitrate.
+//!
+//! `iw dev <interface> link` reports everything the bar needs from nl80211
+//! without privileges. Drivers still exposing the wireless extensions also
+//! list their signal level in `/proc/net/wireless`, which serves as fallback
+//! when `iw` is not installed.
+
+use waysensor_rs_core::{format, SensorConfig, Theme};
+use std::{
+    fs,
+    path::Path,
+    process::Command,
+    time::{Duration, Instant},
+};
+
+/// How long an `iw` result is reused.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Signal strength below which the link is flagged as weak, in percent.
+pub const DEFAULT_SIGNAL_WARNING: f64 = 40.0;
+
+/// Signal strength below which the link is flagged as unusable, in percent.
+pub const DEFAULT_SIGNAL_CRITICAL: f64 = 20.0;
+
+/// Whether `interface` is a wireless device.
+pub fn is_wireless(interface: &str) -> bool {
+    let device = Path::new("/sys/class/net").join(interface);
+    device.join("wireless").exists() || device.join("phy80211").exists()
+}
+
+/// Signal quality in percent for a signal level in dBm, using the same
+/// linear scale as NetworkManager (-100 dBm = 0%, -50 dBm = 100%).
+pub fn signal_percent(dbm: f64) -> f64 {
+    (2.0 * (dbm + 100.0)).clamp(0.0, 100.0)
+}
+
+/// State of a wireless link.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WifiLink {
+    /// Whether the interface is associated with an access point
+    pub connected: bool,
+    /// Network name
+    pub ssid: Option<String>,
+    /// MAC address of the access point
+    pub bssid: Option<String>,
+    /// Channel frequency in MHz
+    pub frequency_mhz: Option<u32>,
+    /// Signal level in dBm
+    pub signal_dbm: Option<f64>,
+    /// Receive bitrate in Mbit/s
+    pub rx_bitrate: Option<f64>,
+    /// Transmit bitrate in Mbit/s
+    pub tx_bitrate: Option<f64>,
+}
+
+impl WifiLink {
+    /// Parse `iw dev <interface> link` output.
+    pub fn parse_iw(output: &str) -> Self {
+        let mut link = Self::default();
+        for line in output.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("Connected to ") {
+                link.connected = true;
+                link.bssid = rest.split_whitespace().next().map(str::to_owned);
+                continue;
+            }
+            let Some((key, value)) = line.split_once(": ") else { continue };
+            // Values carry units ("-52 dBm", "866.7 MBit/s VHT-MCS 9 ...")
+            let number = || value.split_whitespace().next().and_then(|v| v.parse::<f64>().ok());
+            match key {
+                "SSID" => link.ssid = Some(value.to_owned()),
+                "freq" => link.frequency_mhz = number().map(|mhz| mhz.round() as u32),
+                "signal" => link.signal_dbm = number(),
+                "rx bitrate" => link.rx_bitrate = number(),
+                "tx bitrate" => link.tx_bitrate = number(),
+                _ => {}
+            }
+        }
+        link
+    }
+
+    /// Signal level of `interface` from `/proc/net/wireless`, in dBm.
+    pub fn parse_proc_signal(content: &str, interface: &str) -> Option<f64> {
+        content.lines()
+            .skip(2)
+            .find_map(|line| {
+                let (name, rest) = line.split_once(':')?;
+                if name.trim() != interface {
+                    return None;
+                }
+                // status, link quality, signal level, noise level, ...
+                rest.split_whitespace().nth(2)?.trim_end_matches('.').parse().ok()
+            })
+    }
+
+    /// Query the link of `interface`, with `iw` or else `/proc/net/wireless`.
+    pub fn query(interface: &str) -> Self {
+        if let Ok(output) = Command::new("iw").args(["dev", interface, "link"]).output() {
+            if output.status.success() {
+                return Self::parse_iw(&String::from_utf8_lossy(&output.stdout));
+            }
+        }
+        let signal_dbm = fs::read_to_string("/proc/net/wireless").ok()
+            .and_then(|content| Self::parse_proc_signal(&content, interface));
+        Self {
+            connected: signal_dbm.is_some(),
+            signal_dbm,
+            ..Self::default()
+        }
+    }
+
+    /// Signal quality in percent.
+    pub fn signal_percent(&self) -> Option<f64> {
+        self.signal_dbm.map(signal_percent)
+    }
+
+    /// Frequency band, e.g. `5 GHz`.
+    pub fn band(&self) -> Option<&'static str> {
+        match self.frequency_mhz? {
+            2400..=2500 => Some("2.4 GHz"),
+            4900..=5900 => Some("5 GHz"),
+            5925..=7125 => Some("6 GHz"),
+            _ => None,
+        }
+    }
+
+    /// Tooltip lines: network, signal, band and bitrates.
+    pub fn tooltip_lines(&self, config: &SensorConfig) -> Vec<String> {
+        if !self.connected {
+            return vec![format::key_value("WiFi", "not connected", config)];
+        }
+        let mut lines = vec![format::key_value("SSID", self.ssid.as_deref().unwrap_or("(hidden)"), config)];
+        if let (Some(dbm), Some(percent)) = (self.signal_dbm, self.signal_percent()) {
+            lines.push(format::key_value("Signal", &format!("{:.0}% ({:.0} dBm)", percent, dbm), config));
+        }
+        if let Some(mhz) = self.frequency_mhz {
+            let band = self.band().map(|band| format!(" ({})", band)).unwrap_or_default();
+            lines.push(format::key_value("Frequency", &format!("{} MHz{}", mhz, band), config));
+        }
+        let bitrate = match (self.rx_bitrate, self.tx_bitrate) {
+            (Some(rx), Some(tx)) => Some(format!("↓ {:.0} / ↑ {:.0} Mbit/s", rx, tx)),
+            (Some(rx), None) => Some(format!("↓ {:.0} Mbit/s", rx)),
+            (None, Some(tx)) => Some(format!("↑ {:.0} Mbit/s", tx)),
+            (None, None) => None,
+        };
+        if let Some(bitrate) = bitrate {
+            lines.push(format::key_value("Bitrate", &bitrate, config));
+        }
+        if let Some(bssid) = &self.bssid {
+            lines.push(format::key_value("BSSID", bssid, config));
+        }
+        lines
+    }
+}
+
+/// Cached link state of a wireless interface with signal thresholds.
+#[derive(Debug)]
+pub struct WifiMonitor {
+    link: WifiLink,
+    last_query: Option<Instant>,
+    /// Signal percentage below which the link is a warning
+    pub signal_warning: f64,
+    /// Signal percentage below which the link is critical
+    pub signal_critical: f64,
+}
+
+impl Default for WifiMonitor {
+    fn default() -> Self {
+        Self {
+            link: WifiLink::default(),
+            last_query: None,
+            signal_warning: DEFAULT_SIGNAL_WARNING,
+            signal_critical: DEFAULT_SIGNAL_CRITICAL,
+        }
+    }
+}
+
+impl WifiMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refresh the link of `interface` if the cached result is older than
+    /// [`REFRESH_INTERVAL`].
+    pub fn update(&mut self, interface: &str) -> &WifiLink {
+        let fresh = self.last_query.is_some_and(|at| at.elapsed() < REFRESH_INTERVAL);
+        if !fresh {
+            self.last_query = Some(Instant::now());
+            self.link = WifiLink::query(interface);
+        }
+        &self.link
+    }
+
+    /// Last known link state.
+    pub fn link(&self) -> &WifiLink {
+        &self.link
+    }
+
+    /// CSS class for the signal strength: warning when weak, critical when
+    /// barely usable or disconnected, `None` while the signal is fine.
+    pub fn class<'a>(&self, theme: &'a Theme) -> Option<&'a str> {
+        if !self.link.connected {
+            return Some(theme.critical.as_str());
+        }
+        match self.link.signal_percent()? {
+            percent if percent < self.signal_critical => Some(theme.critical.as_str()),
+            percent if percent < self.signal_warning => Some(theme.warning.as_str()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wifi_link_parsing() {
+        let iw = "\
+Connected to aa:bb:cc:dd:ee:ff (on wlan0)
+\tSSID: Home Network
+\tfreq: 5180.0
+\tRX: 123456 bytes (789 packets)
+\tTX: 12345 bytes (67 packets)
+\tsignal: -52 dBm
+\trx bitrate: 866.7 MBit/s VHT-MCS 9 80MHz short GI VHT-NSS 2
+\ttx bitrate: 650.0 MBit/s VHT-MCS 7 80MHz short GI VHT-NSS 2
+";
+        let link = WifiLink::parse_iw(iw);
+        assert_eq!(link, WifiLink {
+            connected: true,
+            ssid: Some("Home Network".to_string()),
+            bssid: Some("aa:bb:cc:dd:ee:ff".to_string()),
+            frequency_mhz: Some(5180),
+            signal_dbm: Some(-52.0),
+            rx_bitrate: Some(866.7),
+            tx_bitrate: Some(650.0),
+        });
+        assert_eq!(link.signal_percent(), Some(96.0));
+        assert_eq!(link.band(), Some("5 GHz"));
+        assert!(!WifiLink::parse_iw("Not connected.\n").connected);
+
+        let proc = "\
+Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+ wlan0: 0000   40.  -70.  -256        0      0      0      0     12        0
+";
+        assert_eq!(WifiLink::parse_proc_signal(proc, "wlan0"), Some(-70.0));
+        assert_eq!(WifiLink::parse_proc_signal(proc, "wlan1"), None);
+
+        assert_eq!(signal_percent(-30.0), 100.0);
+        assert_eq!(signal_percent(-110.0), 0.0);
+
+        let theme = Theme::default();
+        let mut monitor = WifiMonitor::new();
+        monitor.link = link;
+        assert_eq!(monitor.class(&theme), None);
+        monitor.link.signal_dbm = Some(-85.0);
+        assert_eq!(monitor.class(&theme), Some(theme.warning.as_str()));
+        monitor.link.signal_dbm = Some(-92.0);
+        assert_eq!(monitor.class(&theme), Some(theme.critical.as_str()));
+    }
+}
@@ -1,8 +1,12 @@
 //! NVIDIA GPU monitoring sensor for waysensor-rs.
 //!
-//! This module provides NVIDIA GPU monitoring by parsing nvidia-smi output
-//! and extracting key metrics like temperature, utilization, memory usage, and power.
+//! This module provides NVIDIA GPU monitoring through NVML, or by parsing
+//! nvidia-smi output where the library is missing, and extracts key metrics
+//! like temperature, utilization, memory usage, and power.
 
+pub mod multi;
 pub mod nvidia_gpu;
+pub mod nvml;
 
-pub use nvidia_gpu::NvidiaGpuSensor;
\ No newline at end of file
+pub use multi::MultiNvidiaGpuSensor;
+pub use nvidia_gpu::{NvidiaBackend, NvidiaGpuSensor, ThrottleReasons, THROTTLING_CLASS};
\ No newline at end of file
@@ -1,12 +1,11 @@
 //! waysensor-rs-nvidia-gpu: NVIDIA GPU monitoring binary for Waybar.
 
 use clap::Parser;
-use waysensor_rs_core::{GlobalConfig, IconStyle, Sensor, SensorConfig};
-use waysensor_rs_nvidia_gpu::NvidiaGpuSensor;
+use waysensor_rs_core::{discovery, schedule, session_lock, GlobalConfig, IconStyle, Sensor, SensorConfig, SensorError};
+use waysensor_rs_nvidia_gpu::{MultiNvidiaGpuSensor, NvidiaBackend, NvidiaGpuSensor};
 use std::io::{self, Write};
 use std::process;
 use std::time::Duration;
-use tokio::time;
 
 /// Command-line arguments for the NVIDIA GPU sensor.
 #[derive(Parser)]
@@ -31,6 +30,18 @@ struct Args {
     #[arg(short, long)]
     once: bool,
 
+    /// List available NVIDIA GPUs and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
     /// Icon style (nerdfont, fontawesome, ascii, none)
     #[arg(long)]
     icon_style: Option<IconStyle>,
@@ -51,10 +62,22 @@ struct Args {
     #[arg(long)]
     tooltip_value_color: Option<String>,
 
-    /// GPU ID to monitor (default: first GPU)
+    /// GPU ID to monitor (default: gpu_id from the config, else the first GPU)
     #[arg(long)]
     gpu_id: Option<u32>,
 
+    /// Monitor the GPU with this UUID (GPU-5a1c..., see --list)
+    #[arg(long, conflicts_with = "gpu_id")]
+    uuid: Option<String>,
+
+    /// Monitor all NVIDIA GPUs at once, showing their mean utilization
+    #[arg(long, conflicts_with_all = ["gpu_id", "uuid"])]
+    all: bool,
+
+    /// Where readings come from: auto (NVML, else nvidia-smi), nvml, nvidia-smi
+    #[arg(long, default_value = "auto")]
+    backend: NvidiaBackend,
+
     /// Verify NVIDIA drivers and nvidia-smi are available (tests hardware detection) and exit
     #[arg(long)]
     check: bool,
@@ -96,6 +119,19 @@ fn validate_percentage(s: &str) -> Result<u8, String> {
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
     
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<NvidiaGpuSensor>(args.json) {
+            eprintln!("Error listing NVIDIA GPUs: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
     // Handle config generation
     if args.generate_config {
         if let Some(config_path) = GlobalConfig::default_config_path() {
@@ -117,21 +153,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
     
     // Create the NVIDIA GPU sensor
-    let mut gpu_sensor = if let Some(gpu_id) = args.gpu_id {
-        match NvidiaGpuSensor::new_with_gpu_id(args.warning, args.critical, gpu_id) {
-            Ok(sensor) => sensor,
-            Err(e) => {
-                eprintln!("Failed to create NVIDIA GPU sensor for GPU {}: {}", gpu_id, e);
-                process::exit(1);
-            }
-        }
+    let created: Result<Box<dyn Sensor<Error = SensorError>>, SensorError> = if args.all {
+        MultiNvidiaGpuSensor::new(args.warning, args.critical, args.backend)
+            .map(|sensor| Box::new(sensor) as Box<dyn Sensor<Error = SensorError>>)
+    } else if let Some(gpu_id) = args.gpu_id {
+        NvidiaGpuSensor::new_with_gpu_id(args.warning, args.critical, gpu_id)
+            .map(|sensor| Box::new(sensor.with_backend(args.backend)) as Box<dyn Sensor<Error = SensorError>>)
     } else {
-        match NvidiaGpuSensor::new(args.warning, args.critical) {
-            Ok(sensor) => sensor,
-            Err(e) => {
-                eprintln!("Failed to create NVIDIA GPU sensor: {}", e);
-                process::exit(1);
-            }
+        NvidiaGpuSensor::new(args.warning, args.critical)
+            .map(|sensor| sensor.with_backend(args.backend))
+            .and_then(|sensor| match &args.uuid {
+                Some(uuid) => sensor.select_uuid(uuid),
+                None => Ok(sensor),
+            })
+            .map(|sensor| Box::new(sensor) as Box<dyn Sensor<Error = SensorError>>)
+    };
+    let mut gpu_sensor = match created {
+        Ok(sensor) => sensor,
+        Err(e) => {
+            eprintln!("Failed to create NVIDIA GPU sensor: {}", e);
+            process::exit(1);
         }
     };
     
@@ -151,7 +192,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Load global configuration and apply command line overrides
     let global_config = GlobalConfig::load().unwrap_or_default();
-    let mut config = global_config.to_sensor_config()
+    let mut config = global_config.to_sensor_config_for("nvidia-gpu")
         .with_update_interval(Duration::from_millis(args.interval))
         .apply_color_overrides(
             args.icon_color,
@@ -169,7 +210,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     if args.once {
         // One-shot mode: read once and exit
-        match gpu_sensor.read() {
+        match gpu_sensor.read_styled() {
             Ok(output) => {
                 println!("{}", serde_json::to_string(&output)?);
             }
@@ -180,12 +221,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     } else {
         // Continuous mode: loop and output readings
-        let mut interval = time::interval(Duration::from_millis(args.interval));
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
         
         loop {
             interval.tick().await;
+            if session_lock::wait_async(gpu_sensor.config()).await {
+                interval.reset();
+            }
             
-            match gpu_sensor.read() {
+            match gpu_sensor.read_styled() {
                 Ok(output) => {
                     println!("{}", serde_json::to_string(&output)?);
                     io::stdout().flush()?;
@@ -0,0 +1,125 @@
+//! All NVIDIA GPUs at once, for multi-GPU rigs.
+
+use crate::nvidia_gpu::{NvidiaBackend, NvidiaGpuMetrics, NvidiaGpuSensor};
+use waysensor_rs_core::{format, power::EcoPolling, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+/// Utilization of every NVIDIA GPU, shown as their mean.
+#[derive(Debug)]
+pub struct MultiNvidiaGpuSensor {
+    name: String,
+    /// Queries each GPU by index
+    reader: NvidiaGpuSensor,
+    gpu_count: u32,
+    warning_threshold: f64,
+    critical_threshold: f64,
+    config: SensorConfig,
+    eco: EcoPolling,
+}
+
+impl MultiNvidiaGpuSensor {
+    pub fn new(warning_threshold: u8, critical_threshold: u8, backend: NvidiaBackend) -> Result<Self, SensorError> {
+        let reader = NvidiaGpuSensor::new(warning_threshold, critical_threshold)?.with_backend(backend);
+        let gpu_count = reader.gpu_count()?;
+        if gpu_count == 0 {
+            return Err(SensorError::unavailable("No NVIDIA GPU found"));
+        }
+
+        Ok(Self {
+            name: "nvidia-gpu-all".to_string(),
+            reader,
+            gpu_count,
+            warning_threshold: f64::from(warning_threshold),
+            critical_threshold: f64::from(critical_threshold),
+            config: SensorConfig::default(),
+            eco: EcoPolling::new(EcoPolling::DEFAULT_INTERVAL),
+        })
+    }
+
+    /// Metrics of every GPU that could be read, by index.
+    fn read_all(&self) -> Result<Vec<(u32, NvidiaGpuMetrics)>, SensorError> {
+        let mut readings = Vec::new();
+        for index in 0..self.gpu_count {
+            match self.reader.query_gpu(index) {
+                Ok(metrics) => readings.push((index, metrics)),
+                Err(e) => eprintln!("Warning: Failed to read GPU {}: {}", index, e),
+            }
+        }
+        if readings.is_empty() {
+            return Err(SensorError::temporarily_unavailable("No NVIDIA GPU could be read"));
+        }
+        Ok(readings)
+    }
+
+    /// Totals first, then one line per GPU.
+    fn build_tooltip(&self, readings: &[(u32, NvidiaGpuMetrics)], mean_utilization: f64) -> String {
+        let memory_used: u64 = readings.iter().map(|(_, m)| m.memory_used).sum();
+        let memory_total: u64 = readings.iter().map(|(_, m)| m.memory_total).sum();
+        let mut lines = vec![
+            format::key_only(&format!("NVIDIA GPUs: {}", readings.len()), &self.config),
+            format::key_value("Mean Usage", &format!("{:.1}%", mean_utilization), &self.config),
+            format::key_value("Total Memory", &format!("{} / {} MB", memory_used, memory_total), &self.config),
+        ];
+        let powers: Vec<f64> = readings.iter().filter_map(|(_, m)| m.power_draw).collect();
+        if !powers.is_empty() {
+            lines.push(format::key_value("Total Power", &format!("{:.1}W", powers.iter().sum::<f64>()), &self.config));
+        }
+
+        lines.push(String::new());
+        for (index, m) in readings {
+            let mut value = format!(
+                "{} {:.0}% {:.0}°C {} / {} MB",
+                m.name, m.utilization_gpu, m.temperature, m.memory_used, m.memory_total
+            );
+            if let Some(power) = m.power_draw {
+                value.push_str(&format!(" {:.0}W", power));
+            }
+            if let Some(encoder) = m.encoder_utilization.filter(|&u| u > 0.0) {
+                value.push_str(&format!(" enc {:.0}%", encoder));
+            }
+            if let Some(kind) = m.throttle_reasons.and_then(|reasons| reasons.kind()) {
+                value.push_str(&format!(" throttled ({})", kind));
+            }
+            lines.push(format::key_value(&format!("GPU {}", index), &value, &self.config));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Sensor for MultiNvidiaGpuSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        if let Some(output) = self.eco.before_read(None, &self.config) {
+            return Ok(output);
+        }
+
+        let readings = self.read_all()?;
+        let mean_utilization = readings.iter().map(|(_, m)| m.utilization_gpu).sum::<f64>() / readings.len() as f64;
+
+        let text = format::with_icon_and_colors(&format!("{:3.0}%", mean_utilization), &self.config.icons.gpu, &self.config);
+        let output = format::themed_output(
+            text,
+            Some(self.build_tooltip(&readings, mean_utilization)),
+            Some(mean_utilization.round().clamp(0.0, 100.0) as u8),
+            mean_utilization,
+            self.warning_threshold,
+            self.critical_threshold,
+            &self.config.theme,
+        );
+        Ok(self.eco.after_read(output, &self.config))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.eco = EcoPolling::from_config(&config);
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+}
@@ -1,11 +1,78 @@
-//! NVIDIA GPU monitoring using nvidia-smi parsing.
+//! NVIDIA GPU monitoring using NVML, with nvidia-smi parsing as fallback.
 
+use crate::nvml::{GpuProcess, Nvml};
 use waysensor_rs_core::{
-    format, Sensor, SensorConfig, SensorError, WaybarOutput,
+    diff, format, power::EcoPolling, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput,
 };
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::str;
 
+/// CSS class of a GPU whose clocks are held back by a power or thermal limit.
+pub const THROTTLING_CLASS: &str = "throttling";
+
+/// Decoded `clocks_throttle_reasons` bitmask (`nvmlClocksThrottleReasons`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ThrottleReasons(pub u64);
+
+impl ThrottleReasons {
+    const SW_POWER_CAP: u64 = 0x4;
+    const HW_SLOWDOWN: u64 = 0x8;
+    const SYNC_BOOST: u64 = 0x10;
+    const SW_THERMAL_SLOWDOWN: u64 = 0x20;
+    const HW_THERMAL_SLOWDOWN: u64 = 0x40;
+    const HW_POWER_BRAKE_SLOWDOWN: u64 = 0x80;
+
+    /// Reasons that hold the clocks below what the load asks for, with a
+    /// description. Idle, application clock and display clock settings are
+    /// not throttling.
+    const FLAGS: &'static [(u64, &'static str, &'static str)] = &[
+        (Self::SW_POWER_CAP, "SW power cap", "power draw at the power limit"),
+        (Self::HW_SLOWDOWN, "HW slowdown", "hardware slowdown (overheating or power brake)"),
+        (Self::SYNC_BOOST, "Sync boost", "clocks synced with other GPUs"),
+        (Self::SW_THERMAL_SLOWDOWN, "SW thermal", "temperature above the software limit"),
+        (Self::HW_THERMAL_SLOWDOWN, "HW thermal", "temperature above the hardware limit"),
+        (Self::HW_POWER_BRAKE_SLOWDOWN, "Power brake", "external power brake asserted"),
+    ];
+
+    pub fn is_throttling(&self) -> bool {
+        Self::FLAGS.iter().any(|(mask, _, _)| self.0 & mask != 0)
+    }
+
+    /// Name and description of every active reason.
+    pub fn active(&self) -> Vec<(&'static str, &'static str)> {
+        Self::FLAGS.iter()
+            .filter(|(mask, _, _)| self.0 & mask != 0)
+            .map(|&(_, name, description)| (name, description))
+            .collect()
+    }
+
+    pub fn is_thermal_throttling(&self) -> bool {
+        self.0 & (Self::SW_THERMAL_SLOWDOWN | Self::HW_THERMAL_SLOWDOWN) != 0
+    }
+
+    pub fn is_power_throttling(&self) -> bool {
+        self.0 & (Self::SW_POWER_CAP | Self::HW_POWER_BRAKE_SLOWDOWN) != 0
+    }
+
+    /// Short summary: `thermal`, `power`, `thermal and power` or `other`;
+    /// `None` when not throttling.
+    pub fn kind(&self) -> Option<&'static str> {
+        match (self.is_thermal_throttling(), self.is_power_throttling()) {
+            _ if !self.is_throttling() => None,
+            (true, true) => Some("thermal and power"),
+            (true, false) => Some("thermal"),
+            (false, true) => Some("power"),
+            (false, false) => Some("other"),
+        }
+    }
+
+    /// Parse nvidia-smi's `clocks_throttle_reasons.active`, e.g. `0x0000000000000004`.
+    pub fn parse_hex(value: &str) -> Option<Self> {
+        u64::from_str_radix(value.trim().trim_start_matches("0x"), 16).ok().map(Self)
+    }
+}
+
 /// NVIDIA GPU sensor that monitors GPU utilization, temperature, memory, and power.
 #[derive(Debug)]
 pub struct NvidiaGpuSensor {
@@ -14,9 +81,45 @@ pub struct NvidiaGpuSensor {
     warning_threshold: f64,
     critical_threshold: f64,
     gpu_id: Option<u32>,
+    /// PCI device directory of the monitored GPU, resolved when it is chosen
+    pci_device: Option<PathBuf>,
     utilization_history: Vec<f64>,
     temperature_history: Vec<f64>,
     memory_usage_history: Vec<f64>,
+    eco: EcoPolling,
+    backend: NvidiaBackend,
+    /// Loaded library, `None` if NVML is missing or not wanted
+    nvml: Option<Nvml>,
+    /// Timestamp of the newest per-process utilization sample seen
+    process_samples_since: u64,
+}
+
+/// Where readings come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NvidiaBackend {
+    /// NVML if the library can be loaded, nvidia-smi otherwise
+    #[default]
+    Auto,
+    /// NVML only
+    Nvml,
+    /// nvidia-smi only
+    Smi,
+}
+
+impl std::str::FromStr for NvidiaBackend {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "nvml" => Ok(Self::Nvml),
+            "smi" | "nvidia-smi" => Ok(Self::Smi),
+            _ => Err(SensorError::config_with_value(
+                "Invalid backend. Valid options: auto, nvml, nvidia-smi",
+                s,
+            )),
+        }
+    }
 }
 
 /// NVIDIA GPU metrics parsed from nvidia-smi output.
@@ -32,6 +135,10 @@ pub struct NvidiaGpuMetrics {
     pub memory_total: u64,
     /// Power draw in Watts
     pub power_draw: Option<f64>,
+    /// Power limit in effect in Watts
+    pub power_limit: Option<f64>,
+    /// Power limit the board ships with in Watts
+    pub default_power_limit: Option<f64>,
     /// GPU name/model
     pub name: String,
     /// Driver version
@@ -40,6 +147,16 @@ pub struct NvidiaGpuMetrics {
     pub gpu_clock: Option<u32>,
     /// Memory clock in MHz
     pub memory_clock: Option<u32>,
+    /// Memory controller utilization percentage (NVML only)
+    pub memory_utilization: Option<f64>,
+    /// Performance state, 0 (P0, fastest) to 15 (NVML only)
+    pub pstate: Option<u32>,
+    /// Why the clocks are held back
+    pub throttle_reasons: Option<ThrottleReasons>,
+    /// NVENC utilization percentage
+    pub encoder_utilization: Option<f64>,
+    /// NVDEC utilization percentage
+    pub decoder_utilization: Option<f64>,
 }
 
 impl NvidiaGpuMetrics {
@@ -94,12 +211,81 @@ impl NvidiaGpuSensor {
             warning_threshold: f64::from(warning_threshold),
             critical_threshold: f64::from(critical_threshold),
             gpu_id: None,
+            pci_device: Self::pci_device(0),
             utilization_history: Vec::new(),
             temperature_history: Vec::new(),
             memory_usage_history: Vec::new(),
+            eco: EcoPolling::new(EcoPolling::DEFAULT_INTERVAL),
+            backend: NvidiaBackend::Auto,
+            nvml: Nvml::load().ok(),
+            process_samples_since: 0,
         })
     }
 
+    /// Monitor the GPU with the given UUID (`GPU-5a1c...`, see `--list`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no GPU has this UUID.
+    pub fn select_uuid(mut self, uuid: &str) -> Result<Self, SensorError> {
+        let index = match &self.nvml {
+            Some(nvml) => nvml.index_of_uuid(uuid)?,
+            None => Self::discover()?
+                .into_iter()
+                .find(|gpu| gpu.details.iter().any(|(key, value)| key == "uuid" && value.eq_ignore_ascii_case(uuid)))
+                .and_then(|gpu| gpu.id.parse().ok())
+                .ok_or_else(|| SensorError::config_with_value("No NVIDIA GPU with this UUID", uuid))?,
+        };
+        self.set_gpu_id(index);
+        Ok(self)
+    }
+
+    /// Number of NVIDIA GPUs.
+    pub fn gpu_count(&self) -> Result<u32, SensorError> {
+        match &self.nvml {
+            Some(nvml) => nvml.device_count(),
+            None => Ok(Self::discover()?.len() as u32),
+        }
+    }
+
+    /// Choose where readings come from.
+    #[must_use]
+    pub fn with_backend(mut self, backend: NvidiaBackend) -> Self {
+        if backend == NvidiaBackend::Smi {
+            self.nvml = None;
+        }
+        self.backend = backend;
+        self
+    }
+
+    /// PCI device directory of GPU `gpu_id`.
+    ///
+    /// nvidia-smi numbers GPUs in PCI bus order, which matches the sorted
+    /// device names in sysfs.
+    fn pci_device(gpu_id: u32) -> Option<PathBuf> {
+        Self::pci_device_in(Path::new("/sys/bus/pci/devices"), gpu_id)
+    }
+
+    fn pci_device_in(devices: &Path, gpu_id: u32) -> Option<PathBuf> {
+        let mut devices: Vec<PathBuf> = std::fs::read_dir(devices).ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let read = |name: &str| std::fs::read_to_string(path.join(name)).unwrap_or_default();
+                read("vendor").trim() == "0x10de" && read("class").trim().starts_with("0x03")
+            })
+            .collect();
+        devices.sort();
+        devices.into_iter().nth(gpu_id as usize)
+    }
+
+    /// Monitor GPU `gpu_id`.
+    fn set_gpu_id(&mut self, gpu_id: u32) {
+        self.gpu_id = Some(gpu_id);
+        self.pci_device = Self::pci_device(gpu_id);
+        self.name = format!("nvidia-gpu-{}", gpu_id);
+    }
+
     /// Create a new NVIDIA GPU sensor for a specific GPU ID.
     pub fn new_with_gpu_id(
         warning_threshold: u8,
@@ -107,8 +293,7 @@ impl NvidiaGpuSensor {
         gpu_id: u32,
     ) -> Result<Self, SensorError> {
         let mut sensor = Self::new(warning_threshold, critical_threshold)?;
-        sensor.gpu_id = Some(gpu_id);
-        sensor.name = format!("nvidia-gpu-{}", gpu_id);
+        sensor.set_gpu_id(gpu_id);
         Ok(sensor)
     }
 
@@ -121,14 +306,16 @@ impl NvidiaGpuSensor {
     fn parse_nvidia_smi_output(output: &str) -> Result<NvidiaGpuMetrics, SensorError> {
         // Parse nvidia-smi CSV output
         // Expected format: name, driver_version, temperature.gpu, utilization.gpu,
-        // memory.used, memory.total, power.draw, clocks.current.graphics, clocks.current.memory
+        // memory.used, memory.total, power.draw, clocks.current.graphics, clocks.current.memory,
+        // utilization.encoder, utilization.decoder, power.limit, power.default_limit,
+        // clocks_throttle_reasons.active
         
         let lines: Vec<&str> = output.trim().lines().collect();
-        if lines.len() < 2 {
+        if lines.is_empty() {
             return Err(SensorError::parse("Invalid nvidia-smi output format"));
         }
 
-        let data_line = lines[1]; // Skip header
+        let data_line = lines[0]; // Queried without header
         let fields: Vec<&str> = data_line.split(", ").collect();
 
         if fields.len() < 6 {
@@ -190,30 +377,57 @@ impl NvidiaGpuSensor {
             None
         };
 
+        // "[N/A]" on GPUs without NVENC/NVDEC or power management
+        let optional_number = |index: usize| {
+            fields.get(index)?.split_whitespace().next()?.parse::<f64>().ok()
+        };
+
         Ok(NvidiaGpuMetrics {
             utilization_gpu,
             temperature,
             memory_used,
             memory_total,
             power_draw,
+            power_limit: optional_number(11),
+            default_power_limit: optional_number(12),
             name,
             driver_version,
             gpu_clock,
             memory_clock,
+            memory_utilization: None,
+            pstate: None,
+            throttle_reasons: fields.get(13).and_then(|reasons| ThrottleReasons::parse_hex(reasons)),
+            encoder_utilization: optional_number(9),
+            decoder_utilization: optional_number(10),
         })
     }
 
-    /// Query NVIDIA GPU metrics using nvidia-smi.
+    /// Query NVIDIA GPU metrics from NVML or nvidia-smi, depending on the
+    /// backend.
     fn query_gpu_metrics(&self) -> Result<NvidiaGpuMetrics, SensorError> {
+        self.query_gpu(self.gpu_id.unwrap_or(0))
+    }
+
+    /// Query the metrics of the GPU with the given index.
+    pub(crate) fn query_gpu(&self, index: u32) -> Result<NvidiaGpuMetrics, SensorError> {
+        match (&self.nvml, self.backend) {
+            (Some(nvml), _) => nvml.metrics(index),
+            (None, NvidiaBackend::Nvml) => Err(SensorError::unavailable(
+                "NVML library (libnvidia-ml.so.1) not found",
+            )),
+            (None, _) => Self::query_nvidia_smi(index),
+        }
+    }
+
+    /// Query NVIDIA GPU metrics using nvidia-smi.
+    fn query_nvidia_smi(index: u32) -> Result<NvidiaGpuMetrics, SensorError> {
         let mut cmd = Command::new("nvidia-smi");
         
         // CSV format with specific fields
-        cmd.arg("--query-gpu=name,driver_version,temperature.gpu,utilization.gpu,memory.used,memory.total,power.draw,clocks.current.graphics,clocks.current.memory")
+        cmd.arg("--query-gpu=name,driver_version,temperature.gpu,utilization.gpu,memory.used,memory.total,power.draw,clocks.current.graphics,clocks.current.memory,utilization.encoder,utilization.decoder,power.limit,power.default_limit,clocks_throttle_reasons.active")
            .arg("--format=csv,noheader,nounits");
 
-        if let Some(gpu_id) = self.gpu_id {
-            cmd.arg(format!("--id={}", gpu_id));
-        }
+        cmd.arg(format!("--id={}", index));
 
         let output = cmd.output()
             .map_err(|e| {
@@ -237,6 +451,47 @@ impl NvidiaGpuSensor {
         Self::parse_nvidia_smi_output(stdout)
     }
 
+    /// Processes using the GPU, busiest first; empty without NVML.
+    fn top_processes(&mut self) -> Vec<GpuProcess> {
+        let Some(nvml) = &self.nvml else {
+            return Vec::new();
+        };
+        match nvml.processes(self.gpu_id.unwrap_or(0), self.process_samples_since) {
+            Ok((processes, newest)) => {
+                self.process_samples_since = newest;
+                processes
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Format the top GPU processes with their video memory.
+    fn format_top_processes_section(&self, processes: &[GpuProcess]) -> String {
+        let max_name_length = self.config.visuals.process_name_max_length as usize;
+
+        let top: Vec<(String, f64)> = processes.iter()
+            .take(self.config.visuals.top_processes_count as usize)
+            .map(|process| {
+                let mut name = process.name.clone();
+                if name.chars().count() > max_name_length {
+                    name = name.chars().take(max_name_length.saturating_sub(3)).collect();
+                    name.push_str("...");
+                }
+                if let Some(memory_mb) = process.memory_mb {
+                    name.push_str(&format!(" ({} MB)", memory_mb));
+                }
+                (name, process.utilization)
+            })
+            .collect();
+
+        format::format_top_processes(
+            &top,
+            "Top Processes by GPU",
+            self.config.tooltip_label_color.as_deref(),
+            self.config.tooltip_value_color.as_deref(),
+        )
+    }
+
     /// Update history for sparklines.
     fn update_history(&mut self, metrics: &NvidiaGpuMetrics) {
         let max_len = self.config.visuals.sparkline_length;
@@ -299,9 +554,24 @@ impl NvidiaGpuSensor {
             &self.config,
         ));
 
+        // Hardware video engines, so streamers can see NVENC is really in use
+        for (label, utilization) in [("Encoder", metrics.encoder_utilization), ("Decoder", metrics.decoder_utilization)] {
+            if let Some(utilization) = utilization {
+                let gauge = Self::create_gauge(utilization, 12);
+                let indicator = Self::get_usage_indicator(utilization);
+                lines.push(format::key_value(
+                    label,
+                    &format!("{} {:.0}% {}", gauge, utilization, indicator),
+                    &self.config,
+                ));
+            }
+        }
+
         // Optional metrics with gauges
         if let Some(power) = metrics.power_draw {
-            let power_percentage = ((power / 400.0) * 100.0).min(100.0); // Assume 400W max for NVIDIA GPU
+            // Scaled to the power limit, or 400W where it is unknown
+            let power_max = metrics.power_limit.filter(|&limit| limit > 0.0).unwrap_or(400.0);
+            let power_percentage = ((power / power_max) * 100.0).min(100.0);
             let power_gauge = Self::create_gauge(power_percentage, 12);
             let power_indicator = Self::get_usage_indicator(power_percentage);
             lines.push(format::key_value(
@@ -311,6 +581,21 @@ impl NvidiaGpuSensor {
             ));
         }
 
+        if let Some(limit) = metrics.power_limit {
+            let value = match metrics.default_power_limit {
+                Some(default) if (default - limit).abs() >= 0.5 => format!("{:.0}W (default {:.0}W)", limit, default),
+                _ => format!("{:.0}W", limit),
+            };
+            lines.push(format::key_value("Power Limit", &value, &self.config));
+        }
+
+        if let Some(reasons) = metrics.throttle_reasons {
+            lines.push(format::key_value("Throttling", reasons.kind().unwrap_or("none"), &self.config));
+            for (name, description) in reasons.active() {
+                lines.push(format::key_value(&format!("  {}", name), description, &self.config));
+            }
+        }
+
         if let Some(gpu_clock) = metrics.gpu_clock {
             lines.push(format::key_value(
                 "GPU Clock",
@@ -327,6 +612,22 @@ impl NvidiaGpuSensor {
             ));
         }
 
+        if let Some(memory_utilization) = metrics.memory_utilization {
+            lines.push(format::key_value(
+                "Memory Controller",
+                &format!("{:.0}%", memory_utilization),
+                &self.config,
+            ));
+        }
+
+        if let Some(pstate) = metrics.pstate {
+            lines.push(format::key_value(
+                "Performance State",
+                &format!("P{}", pstate),
+                &self.config,
+            ));
+        }
+
         // Add sparklines if enabled and we have history
         if self.config.visuals.sparklines && self.config.visuals.extended_metadata {
             if self.utilization_history.len() > 1 {
@@ -357,10 +658,54 @@ impl NvidiaGpuSensor {
     }
 }
 
+impl NvidiaGpuSensor {
+    /// Parse `nvidia-smi -L` output, e.g.
+    /// `GPU 0: NVIDIA GeForce RTX 3080 (UUID: GPU-5a1c...)`.
+    fn parse_gpu_list(output: &str) -> Vec<DiscoveredItem> {
+        output.lines()
+            .filter_map(|line| {
+                let (index, rest) = line.strip_prefix("GPU ")?.split_once(": ")?;
+                let (name, uuid) = match rest.rsplit_once(" (UUID: ") {
+                    Some((name, uuid)) => (name, Some(uuid.trim_end_matches(')'))),
+                    None => (rest, None),
+                };
+                let item = DiscoveredItem::new(index.trim(), name.trim());
+                Some(match uuid {
+                    Some(uuid) => item.with_detail("uuid", uuid),
+                    None => item,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Discoverable for NvidiaGpuSensor {
+    const KIND: &'static str = "NVIDIA GPUs";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        // No nvidia-smi means no usable NVIDIA GPU, not a failure to list
+        let Ok(output) = Command::new("nvidia-smi").arg("-L").output() else {
+            return Ok(Vec::new());
+        };
+        if !output.status.success() {
+            return Ok(Vec::new());
+        }
+
+        let stdout = str::from_utf8(&output.stdout)
+            .map_err(|e| SensorError::parse_with_source("Invalid UTF-8 in nvidia-smi output", e))?;
+        Ok(Self::parse_gpu_list(stdout))
+    }
+}
+
 impl Sensor for NvidiaGpuSensor {
     type Error = SensorError;
 
     fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        // On battery: poll less often, and don't let nvidia-smi wake a suspended GPU
+        if let Some(output) = self.eco.before_read(self.pci_device.as_deref(), &self.config) {
+            return Ok(output);
+        }
+        
         let metrics = self.query_gpu_metrics()?;
         
         // Update history for sparklines
@@ -382,6 +727,18 @@ impl Sensor for NvidiaGpuSensor {
         // Add main utilization percentage
         text_parts.push(format!("{:3.0}%", metrics.utilization_gpu));
 
+        // Add encoder/decoder load while they are busy, if enabled
+        let show_codecs = self.config.get_custom("show_encoder")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        if show_codecs {
+            for (label, utilization) in [("enc", metrics.encoder_utilization), ("dec", metrics.decoder_utilization)] {
+                if let Some(utilization) = utilization.filter(|&u| u > 0.0) {
+                    text_parts.push(format!("{} {:.0}%", label, utilization));
+                }
+            }
+        }
+
 
         // Add status indicator if enabled (based on utilization)
         if self.config.visuals.status_indicators {
@@ -399,10 +756,16 @@ impl Sensor for NvidiaGpuSensor {
         let combined_text = text_parts.join(" ");
         let text = format::with_icon_and_colors(&combined_text, icon, &self.config);
 
-        let tooltip = self.create_tooltip(&metrics);
+        let mut tooltip = self.create_tooltip(&metrics);
+
+        // Add top processes by GPU if enabled
+        if self.config.visuals.show_top_processes {
+            let processes = self.top_processes();
+            tooltip.push_str(&self.format_top_processes_section(&processes));
+        }
         let percentage = metrics.utilization_gpu.round().clamp(0.0, 100.0) as u8;
 
-        Ok(format::themed_output(
+        let mut output = format::themed_output(
             text,
             Some(tooltip),
             Some(percentage),
@@ -410,7 +773,14 @@ impl Sensor for NvidiaGpuSensor {
             self.warning_threshold,
             self.critical_threshold,
             &self.config.theme,
-        ))
+        );
+
+        // Mark throttling, unless the output already shows a warning or worse
+        let throttling = metrics.throttle_reasons.is_some_and(|reasons| reasons.is_throttling());
+        if throttling && diff::severity(output.class.as_deref(), &self.config.theme) <= 1 {
+            output.class = Some(THROTTLING_CLASS.to_string());
+        }
+        Ok(self.eco.after_read(output, &self.config))
     }
 
     fn name(&self) -> &str {
@@ -418,6 +788,13 @@ impl Sensor for NvidiaGpuSensor {
     }
 
     fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        // The command line wins over the config file's gpu_id
+        if self.gpu_id.is_none() {
+            if let Some(gpu_id) = config.get_custom("gpu_id").and_then(|v| v.as_u64()) {
+                self.set_gpu_id(gpu_id as u32);
+            }
+        }
+        self.eco = EcoPolling::from_config(&config);
         self.config = config;
         Ok(())
     }
@@ -427,6 +804,10 @@ impl Sensor for NvidiaGpuSensor {
     }
 
     fn check_availability(&self) -> Result<(), Self::Error> {
+        if self.nvml.is_some() || self.backend == NvidiaBackend::Nvml {
+            return self.query_gpu_metrics().map(|_| ());
+        }
+
         // Try to run nvidia-smi to check if it's available
         let output = Command::new("nvidia-smi")
             .arg("--help")
@@ -446,4 +827,126 @@ impl Sensor for NvidiaGpuSensor {
         // Try to query GPU information
         self.query_gpu_metrics().map(|_| ())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_parsing() {
+        assert_eq!("auto".parse::<NvidiaBackend>().unwrap(), NvidiaBackend::Auto);
+        assert_eq!("NVML".parse::<NvidiaBackend>().unwrap(), NvidiaBackend::Nvml);
+        assert_eq!("smi".parse::<NvidiaBackend>().unwrap(), NvidiaBackend::Smi);
+        assert_eq!("nvidia-smi".parse::<NvidiaBackend>().unwrap(), NvidiaBackend::Smi);
+        assert!("cuda".parse::<NvidiaBackend>().is_err());
+    }
+
+    #[test]
+    fn test_smi_parsing() {
+        let metrics = NvidiaGpuSensor::parse_nvidia_smi_output(
+            "NVIDIA GeForce RTX 3080, 550.67, 64, 87, 6144, 10240, 287.50, 1905, 9501\n",
+        ).unwrap();
+        assert_eq!(metrics.name, "NVIDIA GeForce RTX 3080");
+        assert_eq!(metrics.driver_version, "550.67");
+        assert_eq!(metrics.temperature, 64.0);
+        assert_eq!(metrics.utilization_gpu, 87.0);
+        assert_eq!((metrics.memory_used, metrics.memory_total), (6144, 10240));
+        assert_eq!(metrics.memory_usage_percent(), 60.0);
+        assert_eq!(metrics.power_draw, Some(287.5));
+        assert_eq!((metrics.gpu_clock, metrics.memory_clock), (Some(1905), Some(9501)));
+        // Only NVML reports these
+        assert_eq!((metrics.memory_utilization, metrics.pstate), (None, None));
+
+        // Boards without power management
+        let metrics = NvidiaGpuSensor::parse_nvidia_smi_output("Tesla K80, 470.82, 40, 0, 0, 11441, [N/A]").unwrap();
+        assert_eq!(metrics.power_draw, None);
+        assert_eq!(metrics.gpu_clock, None);
+
+        assert!(NvidiaGpuSensor::parse_nvidia_smi_output("").is_err());
+        assert!(NvidiaGpuSensor::parse_nvidia_smi_output("NVIDIA GeForce RTX 3080, 550.67, 64").is_err());
+        assert!(NvidiaGpuSensor::parse_nvidia_smi_output("NVIDIA GeForce RTX 3080, 550.67, hot, 87, 6144, 10240").is_err());
+    }
+
+    #[test]
+    fn test_gpu_list_parsing() {
+        let gpus = NvidiaGpuSensor::parse_gpu_list(
+            "GPU 0: NVIDIA GeForce RTX 3080 (UUID: GPU-5a1c2b3d-0000-1111-2222-333344445555)\n\
+             GPU 1: Tesla T4\n\
+             No devices were found\n",
+        );
+        assert_eq!(gpus.len(), 2);
+        assert_eq!((gpus[0].id.as_str(), gpus[0].label.as_str()), ("0", "NVIDIA GeForce RTX 3080"));
+        assert_eq!(gpus[0].details, vec![("uuid".to_string(), "GPU-5a1c2b3d-0000-1111-2222-333344445555".to_string())]);
+        assert_eq!((gpus[1].id.as_str(), gpus[1].label.as_str()), ("1", "Tesla T4"));
+        assert!(gpus[1].details.is_empty());
+    }
+
+    #[test]
+    fn test_pci_device() {
+        let dir = tempfile::tempdir().unwrap();
+        let device = |slot: &str, vendor: &str, class: &str| {
+            let path = dir.path().join(slot);
+            std::fs::create_dir(&path).unwrap();
+            std::fs::write(path.join("vendor"), vendor).unwrap();
+            std::fs::write(path.join("class"), class).unwrap();
+        };
+        device("0000:00:02.0", "0x8086\n", "0x030000\n");
+        device("0000:41:00.0", "0x10de\n", "0x030000\n");
+        // The HDMI audio function of a card is not a GPU
+        device("0000:01:00.1", "0x10de\n", "0x040300\n");
+        device("0000:01:00.0", "0x10de\n", "0x030200\n");
+
+        // Numbered in PCI bus order, like nvidia-smi
+        assert_eq!(NvidiaGpuSensor::pci_device_in(dir.path(), 0), Some(dir.path().join("0000:01:00.0")));
+        assert_eq!(NvidiaGpuSensor::pci_device_in(dir.path(), 1), Some(dir.path().join("0000:41:00.0")));
+        assert_eq!(NvidiaGpuSensor::pci_device_in(dir.path(), 2), None);
+        assert_eq!(NvidiaGpuSensor::pci_device_in(&dir.path().join("missing"), 0), None);
+    }
+
+    #[test]
+    fn test_smi_codec_utilization() {
+        let line = "NVIDIA GeForce RTX 4070, 550.67, 55, 30, 2048, 12282, 95.20, 2475, 10501, 18, 42";
+        let metrics = NvidiaGpuSensor::parse_nvidia_smi_output(line).unwrap();
+        assert_eq!(metrics.encoder_utilization, Some(18.0));
+        assert_eq!(metrics.decoder_utilization, Some(42.0));
+
+        // GPUs without NVENC/NVDEC
+        let line = "NVIDIA A100-SXM4-40GB, 550.67, 35, 0, 0, 40960, 60.00, 1410, 1215, [N/A], [N/A]";
+        let metrics = NvidiaGpuSensor::parse_nvidia_smi_output(line).unwrap();
+        assert_eq!((metrics.encoder_utilization, metrics.decoder_utilization), (None, None));
+    }
+
+    #[test]
+    fn test_throttle_reasons() {
+        // GPU idle (0x1) and application clocks (0x2) are not throttling
+        let idle = ThrottleReasons::parse_hex("0x0000000000000001").unwrap();
+        assert!(!idle.is_throttling());
+        assert_eq!(idle.kind(), None);
+
+        let power = ThrottleReasons::parse_hex(" 0x0000000000000004\n").unwrap();
+        assert_eq!(power.active(), vec![("SW power cap", "power draw at the power limit")]);
+        assert_eq!(power.kind(), Some("power"));
+
+        assert_eq!(ThrottleReasons(0x20).kind(), Some("thermal"));
+        assert_eq!(ThrottleReasons(0x44).kind(), Some("thermal and power"));
+        assert_eq!(ThrottleReasons(0x8).kind(), Some("other"));
+        assert_eq!(ThrottleReasons(0x8 | 0x10).active().len(), 2);
+
+        assert_eq!(ThrottleReasons::parse_hex("[N/A]"), None);
+    }
+
+    #[test]
+    fn test_smi_power_limits() {
+        let line = "NVIDIA GeForce RTX 3080, 550.67, 78, 99, 9000, 10240, 319.40, 1710, 9501, 0, 0, 320.00, 340.00, 0x0000000000000004";
+        let metrics = NvidiaGpuSensor::parse_nvidia_smi_output(line).unwrap();
+        assert_eq!(metrics.power_limit, Some(320.0));
+        assert_eq!(metrics.default_power_limit, Some(340.0));
+        assert_eq!(metrics.throttle_reasons, Some(ThrottleReasons(0x4)));
+
+        let line = "Tesla K80, 470.82, 40, 0, 0, 11441, [N/A], 562, 2505, [N/A], [N/A], [N/A], [N/A], [N/A]";
+        let metrics = NvidiaGpuSensor::parse_nvidia_smi_output(line).unwrap();
+        assert_eq!((metrics.power_limit, metrics.default_power_limit), (None, None));
+        assert_eq!(metrics.throttle_reasons, None);
+    }
+}
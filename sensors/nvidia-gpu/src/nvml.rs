@@ -0,0 +1,476 @@
+//! Direct NVML queries through `libnvidia-ml.so`.
+//!
+//! The library ships with the driver, so it is loaded at runtime instead of
+//! linked: on machines without it the sensor falls back to nvidia-smi. A
+//! handful of library calls per update is far cheaper than spawning
+//! nvidia-smi, and NVML reports what the CSV output lacks, such as the
+//! performance state and the throttle reason bitmask.
+
+use crate::nvidia_gpu::{NvidiaGpuMetrics, ThrottleReasons};
+use std::collections::HashMap;
+use std::ffi::{c_char, c_int, c_uint, c_void, CStr};
+use waysensor_rs_core::SensorError;
+
+type NvmlReturn = c_uint;
+type Device = *mut c_void;
+
+const SUCCESS: NvmlReturn = 0;
+const ERROR_NOT_FOUND: NvmlReturn = 6;
+const ERROR_INSUFFICIENT_SIZE: NvmlReturn = 7;
+/// `usedGpuMemory` when the driver cannot tell (e.g. on Windows WDDM or
+/// without permission)
+const VALUE_NOT_AVAILABLE: u64 = u64::MAX;
+const LIBRARY: &CStr = c"libnvidia-ml.so.1";
+
+const TEMPERATURE_GPU: c_uint = 0;
+const CLOCK_GRAPHICS: c_uint = 0;
+const CLOCK_MEM: c_uint = 2;
+const PSTATE_UNKNOWN: c_int = 32;
+
+/// Longest name and version strings NVML returns, including the terminator.
+const STRING_BUFFER_SIZE: usize = 96;
+
+#[repr(C)]
+#[derive(Default)]
+struct Utilization {
+    gpu: c_uint,
+    memory: c_uint,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct Memory {
+    total: u64,
+    free: u64,
+    used: u64,
+}
+
+/// `nvmlProcessInfo_t` (v3)
+#[repr(C)]
+#[derive(Default, Clone)]
+struct ProcessInfo {
+    pid: c_uint,
+    used_gpu_memory: u64,
+    gpu_instance_id: c_uint,
+    compute_instance_id: c_uint,
+}
+
+/// `nvmlProcessUtilizationSample_t`
+#[repr(C)]
+#[derive(Default, Clone)]
+struct ProcessUtilizationSample {
+    pid: c_uint,
+    /// CPU timestamp in microseconds
+    time_stamp: u64,
+    sm_util: c_uint,
+    mem_util: c_uint,
+    enc_util: c_uint,
+    dec_util: c_uint,
+}
+
+type ProcessList = unsafe extern "C" fn(Device, *mut c_uint, *mut ProcessInfo) -> NvmlReturn;
+
+/// A process using the GPU.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuProcess {
+    pub pid: u32,
+    pub name: String,
+    /// Video memory in MB, `None` if the driver does not report it
+    pub memory_mb: Option<u64>,
+    /// Share of the streaming multiprocessors' time in percent
+    pub utilization: f64,
+}
+
+/// NVML entry points used by the sensor.
+struct Functions {
+    shutdown: unsafe extern "C" fn() -> NvmlReturn,
+    error_string: unsafe extern "C" fn(NvmlReturn) -> *const c_char,
+    device_count: unsafe extern "C" fn(*mut c_uint) -> NvmlReturn,
+    handle_by_index: unsafe extern "C" fn(c_uint, *mut Device) -> NvmlReturn,
+    handle_by_uuid: unsafe extern "C" fn(*const c_char, *mut Device) -> NvmlReturn,
+    index: unsafe extern "C" fn(Device, *mut c_uint) -> NvmlReturn,
+    driver_version: unsafe extern "C" fn(*mut c_char, c_uint) -> NvmlReturn,
+    name: unsafe extern "C" fn(Device, *mut c_char, c_uint) -> NvmlReturn,
+    temperature: unsafe extern "C" fn(Device, c_uint, *mut c_uint) -> NvmlReturn,
+    utilization: unsafe extern "C" fn(Device, *mut Utilization) -> NvmlReturn,
+    memory: unsafe extern "C" fn(Device, *mut Memory) -> NvmlReturn,
+    /// Milliwatts
+    power_usage: unsafe extern "C" fn(Device, *mut c_uint) -> NvmlReturn,
+    /// Milliwatts, the limit in effect
+    enforced_power_limit: unsafe extern "C" fn(Device, *mut c_uint) -> NvmlReturn,
+    /// Milliwatts, the limit the board ships with
+    default_power_limit: unsafe extern "C" fn(Device, *mut c_uint) -> NvmlReturn,
+    clock: unsafe extern "C" fn(Device, c_uint, *mut c_uint) -> NvmlReturn,
+    performance_state: unsafe extern "C" fn(Device, *mut c_int) -> NvmlReturn,
+    throttle_reasons: unsafe extern "C" fn(Device, *mut u64) -> NvmlReturn,
+    /// Utilization and sampling period in microseconds
+    encoder_utilization: unsafe extern "C" fn(Device, *mut c_uint, *mut c_uint) -> NvmlReturn,
+    decoder_utilization: unsafe extern "C" fn(Device, *mut c_uint, *mut c_uint) -> NvmlReturn,
+    compute_processes: ProcessList,
+    graphics_processes: ProcessList,
+    process_utilization: unsafe extern "C" fn(Device, *mut ProcessUtilizationSample, *mut c_uint, u64) -> NvmlReturn,
+}
+
+/// Look up `name` in the library behind `handle` as a function of type `T`.
+///
+/// # Safety
+///
+/// `handle` must be a live `dlopen` handle and `T` the function pointer type
+/// matching the symbol's C signature.
+unsafe fn symbol<T>(handle: *mut c_void, name: &CStr) -> Result<T, SensorError> {
+    let address = libc::dlsym(handle, name.as_ptr());
+    if address.is_null() {
+        return Err(SensorError::unavailable(format!(
+            "NVML has no {}; the driver is too old",
+            name.to_string_lossy()
+        )));
+    }
+    Ok(std::mem::transmute_copy(&address))
+}
+
+impl Functions {
+    /// # Safety
+    ///
+    /// `handle` must be a live `dlopen` handle of `libnvidia-ml.so`.
+    unsafe fn resolve(handle: *mut c_void) -> Result<Self, SensorError> {
+        Ok(Self {
+            shutdown: symbol(handle, c"nvmlShutdown")?,
+            error_string: symbol(handle, c"nvmlErrorString")?,
+            device_count: symbol(handle, c"nvmlDeviceGetCount_v2")?,
+            handle_by_index: symbol(handle, c"nvmlDeviceGetHandleByIndex_v2")?,
+            handle_by_uuid: symbol(handle, c"nvmlDeviceGetHandleByUUID")?,
+            index: symbol(handle, c"nvmlDeviceGetIndex")?,
+            driver_version: symbol(handle, c"nvmlSystemGetDriverVersion")?,
+            name: symbol(handle, c"nvmlDeviceGetName")?,
+            temperature: symbol(handle, c"nvmlDeviceGetTemperature")?,
+            utilization: symbol(handle, c"nvmlDeviceGetUtilizationRates")?,
+            memory: symbol(handle, c"nvmlDeviceGetMemoryInfo")?,
+            power_usage: symbol(handle, c"nvmlDeviceGetPowerUsage")?,
+            enforced_power_limit: symbol(handle, c"nvmlDeviceGetEnforcedPowerLimit")?,
+            default_power_limit: symbol(handle, c"nvmlDeviceGetPowerManagementDefaultLimit")?,
+            clock: symbol(handle, c"nvmlDeviceGetClockInfo")?,
+            performance_state: symbol(handle, c"nvmlDeviceGetPerformanceState")?,
+            throttle_reasons: symbol(handle, c"nvmlDeviceGetCurrentClocksThrottleReasons")?,
+            encoder_utilization: symbol(handle, c"nvmlDeviceGetEncoderUtilization")?,
+            decoder_utilization: symbol(handle, c"nvmlDeviceGetDecoderUtilization")?,
+            compute_processes: symbol(handle, c"nvmlDeviceGetComputeRunningProcesses_v3")?,
+            graphics_processes: symbol(handle, c"nvmlDeviceGetGraphicsRunningProcesses_v3")?,
+            process_utilization: symbol(handle, c"nvmlDeviceGetProcessUtilization")?,
+        })
+    }
+}
+
+/// An initialized NVML library.
+pub struct Nvml {
+    handle: *mut c_void,
+    functions: Functions,
+}
+
+impl std::fmt::Debug for Nvml {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Nvml").finish_non_exhaustive()
+    }
+}
+
+impl Nvml {
+    /// Load and initialize NVML.
+    ///
+    /// # Errors
+    ///
+    /// Returns an unavailable error if the library is not installed, lacks
+    /// a needed function or fails to initialize (e.g. no driver loaded).
+    pub fn load() -> Result<Self, SensorError> {
+        // SAFETY: LIBRARY is a valid C string; the handle is closed again on
+        // every error path below or when the returned `Nvml` is dropped.
+        let handle = unsafe { libc::dlopen(LIBRARY.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) };
+        if handle.is_null() {
+            return Err(SensorError::unavailable("NVML library (libnvidia-ml.so.1) not found"));
+        }
+
+        // SAFETY: `handle` was just opened and the symbol types match the
+        // NVML headers.
+        let loaded = unsafe { Functions::resolve(handle) }.and_then(|functions| {
+            // SAFETY: as above.
+            let init: unsafe extern "C" fn() -> NvmlReturn = unsafe { symbol(handle, c"nvmlInit_v2")? };
+            // SAFETY: nvmlInit_v2 takes no arguments.
+            let status = unsafe { init() };
+            let nvml = Self { handle, functions };
+            if status != SUCCESS {
+                let error = nvml.error("initialization", status);
+                // Not initialized, so only unload the library
+                std::mem::forget(nvml);
+                return Err(error);
+            }
+            Ok(nvml)
+        });
+        if loaded.is_err() {
+            // SAFETY: nothing refers to the library any more.
+            unsafe { libc::dlclose(handle) };
+        }
+        loaded
+    }
+
+    fn error(&self, what: &str, status: NvmlReturn) -> SensorError {
+        // SAFETY: nvmlErrorString returns a static string for any code.
+        let message = unsafe { CStr::from_ptr((self.functions.error_string)(status)) };
+        SensorError::unavailable(format!("NVML {} failed: {}", what, message.to_string_lossy()))
+    }
+
+    fn check(&self, what: &str, status: NvmlReturn) -> Result<(), SensorError> {
+        if status == SUCCESS {
+            Ok(())
+        } else {
+            Err(self.error(what, status))
+        }
+    }
+
+    /// Read a string NVML writes into a caller-provided buffer.
+    fn string(&self, what: &str, read: impl FnOnce(*mut c_char, c_uint) -> NvmlReturn) -> Result<String, SensorError> {
+        let mut buffer = [0 as c_char; STRING_BUFFER_SIZE];
+        self.check(what, read(buffer.as_mut_ptr(), STRING_BUFFER_SIZE as c_uint))?;
+        // SAFETY: NVML null-terminates the string on success, and the last
+        // byte of the zeroed buffer is never written.
+        Ok(unsafe { CStr::from_ptr(buffer.as_ptr()) }.to_string_lossy().into_owned())
+    }
+
+    /// Number of NVIDIA GPUs.
+    pub fn device_count(&self) -> Result<u32, SensorError> {
+        let mut count = 0;
+        // SAFETY: `count` is a valid out pointer.
+        self.check("device count", unsafe { (self.functions.device_count)(&mut count) })?;
+        Ok(count)
+    }
+
+    fn device(&self, index: u32) -> Result<Device, SensorError> {
+        let mut device = std::ptr::null_mut();
+        // SAFETY: `device` is a valid out pointer.
+        self.check("device lookup", unsafe { (self.functions.handle_by_index)(index, &mut device) })?;
+        Ok(device)
+    }
+
+    /// Fetch a list NVML fills into a caller-provided array: ask for the
+    /// size, then read. `NOT_FOUND` (no samples yet) is an empty list.
+    fn list<T: Default + Clone>(
+        &self,
+        what: &str,
+        mut read: impl FnMut(*mut T, *mut c_uint) -> NvmlReturn,
+    ) -> Result<Vec<T>, SensorError> {
+        let mut count = 0;
+        match read(std::ptr::null_mut(), &mut count) {
+            SUCCESS | ERROR_NOT_FOUND => return Ok(Vec::new()),
+            ERROR_INSUFFICIENT_SIZE => {}
+            status => return Err(self.error(what, status)),
+        }
+        // Room for processes starting between the two calls
+        let mut items = vec![T::default(); count as usize + 8];
+        let mut count = items.len() as c_uint;
+        match read(items.as_mut_ptr(), &mut count) {
+            ERROR_NOT_FOUND => return Ok(Vec::new()),
+            status => self.check(what, status)?,
+        }
+        items.truncate(count as usize);
+        Ok(items)
+    }
+
+    /// Compute and graphics processes on the GPU with the given index, most
+    /// active first, with their load in the samples newer than `since`.
+    ///
+    /// Returns the processes and the timestamp to pass as `since` next time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the GPU does not exist or the process lists
+    /// cannot be read.
+    pub fn processes(&self, index: u32, since: u64) -> Result<(Vec<GpuProcess>, u64), SensorError> {
+        let device = self.device(index)?;
+        let f = &self.functions;
+
+        let mut running = Vec::new();
+        for (what, read) in [("compute processes", f.compute_processes), ("graphics processes", f.graphics_processes)] {
+            // SAFETY: `device` is a handle NVML returned, and `items` holds
+            // `count` entries whenever it is not null.
+            running.extend(self.list(what, |items, count| unsafe { read(device, count, items) })?);
+        }
+
+        // Older drivers and some GPUs have no per-process samples; the list
+        // is still useful for memory
+        // SAFETY: as above.
+        let samples = self.list("process utilization", |samples, count| unsafe {
+            (f.process_utilization)(device, samples, count, since)
+        }).unwrap_or_default();
+        let newest = samples.iter().map(|sample| sample.time_stamp).max().unwrap_or(since);
+        let processes = merge_processes(running, &samples);
+        Ok((processes, newest))
+    }
+
+    /// Index of the GPU with the given UUID (`GPU-5a1c...`).
+    pub fn index_of_uuid(&self, uuid: &str) -> Result<u32, SensorError> {
+        let uuid = std::ffi::CString::new(uuid)
+            .map_err(|_| SensorError::config_with_value("Invalid GPU UUID", uuid))?;
+        let mut device = std::ptr::null_mut();
+        // SAFETY: `uuid` is a valid C string and `device` a valid out pointer.
+        self.check("UUID lookup", unsafe { (self.functions.handle_by_uuid)(uuid.as_ptr(), &mut device) })?;
+        let mut index = 0;
+        // SAFETY: `device` is a handle NVML returned.
+        self.check("device index", unsafe { (self.functions.index)(device, &mut index) })?;
+        Ok(index)
+    }
+
+    /// Metrics of the GPU with the given index (nvidia-smi's numbering).
+    ///
+    /// Temperature, utilization and memory are required; clocks, power and
+    /// the rest are left `None` where the GPU does not support them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the GPU does not exist or a required query fails.
+    pub fn metrics(&self, index: u32) -> Result<NvidiaGpuMetrics, SensorError> {
+        let device = self.device(index)?;
+        let f = &self.functions;
+
+        // SAFETY (all calls below): `device` is a handle NVML returned and
+        // every out pointer refers to a live local of the expected type.
+        let name = self.string("name", |buffer, size| unsafe { (f.name)(device, buffer, size) })?;
+        let driver_version = self.string("driver version", |buffer, size| unsafe { (f.driver_version)(buffer, size) })?;
+
+        let mut temperature = 0;
+        self.check("temperature", unsafe { (f.temperature)(device, TEMPERATURE_GPU, &mut temperature) })?;
+        let mut utilization = Utilization::default();
+        self.check("utilization", unsafe { (f.utilization)(device, &mut utilization) })?;
+        let mut memory = Memory::default();
+        self.check("memory info", unsafe { (f.memory)(device, &mut memory) })?;
+
+        let watts = |read: unsafe extern "C" fn(Device, *mut c_uint) -> NvmlReturn| {
+            let mut milliwatts = 0;
+            (unsafe { read(device, &mut milliwatts) } == SUCCESS).then(|| f64::from(milliwatts) / 1000.0)
+        };
+        let clock = |kind: c_uint| {
+            let mut mhz = 0;
+            (unsafe { (f.clock)(device, kind, &mut mhz) } == SUCCESS).then_some(mhz)
+        };
+        let mut pstate = PSTATE_UNKNOWN;
+        let pstate = (unsafe { (f.performance_state)(device, &mut pstate) } == SUCCESS && pstate != PSTATE_UNKNOWN)
+            .then_some(pstate as u32);
+        let mut reasons = 0;
+        let throttle_reasons = (unsafe { (f.throttle_reasons)(device, &mut reasons) } == SUCCESS).then_some(reasons);
+        let codec = |read: unsafe extern "C" fn(Device, *mut c_uint, *mut c_uint) -> NvmlReturn| {
+            let (mut percent, mut sampling_period) = (0, 0);
+            (unsafe { read(device, &mut percent, &mut sampling_period) } == SUCCESS).then(|| f64::from(percent))
+        };
+
+        const MIB: u64 = 1024 * 1024;
+        Ok(NvidiaGpuMetrics {
+            utilization_gpu: f64::from(utilization.gpu),
+            temperature: f64::from(temperature),
+            memory_used: memory.used / MIB,
+            memory_total: memory.total / MIB,
+            power_draw: watts(f.power_usage),
+            power_limit: watts(f.enforced_power_limit),
+            default_power_limit: watts(f.default_power_limit),
+            name,
+            driver_version,
+            gpu_clock: clock(CLOCK_GRAPHICS),
+            memory_clock: clock(CLOCK_MEM),
+            memory_utilization: Some(f64::from(utilization.memory)),
+            pstate,
+            throttle_reasons: throttle_reasons.map(ThrottleReasons),
+            encoder_utilization: codec(f.encoder_utilization),
+            decoder_utilization: codec(f.decoder_utilization),
+        })
+    }
+}
+
+/// One entry per process from the compute and graphics lists, with the
+/// largest memory and SM load any entry or sample reports, most active first.
+fn merge_processes(running: Vec<ProcessInfo>, samples: &[ProcessUtilizationSample]) -> Vec<GpuProcess> {
+    let mut processes: HashMap<u32, GpuProcess> = HashMap::new();
+    for info in running {
+        let process = processes.entry(info.pid).or_insert_with(|| GpuProcess {
+            pid: info.pid,
+            name: process_name(info.pid),
+            memory_mb: None,
+            utilization: 0.0,
+        });
+        if info.used_gpu_memory != VALUE_NOT_AVAILABLE {
+            let memory_mb = info.used_gpu_memory / (1024 * 1024);
+            process.memory_mb = Some(process.memory_mb.map_or(memory_mb, |mb| mb.max(memory_mb)));
+        }
+    }
+    for sample in samples {
+        if let Some(process) = processes.get_mut(&sample.pid) {
+            process.utilization = process.utilization.max(f64::from(sample.sm_util));
+        }
+    }
+
+    let mut processes: Vec<GpuProcess> = processes.into_values().collect();
+    processes.sort_by(|a, b| {
+        b.utilization.total_cmp(&a.utilization).then(b.memory_mb.cmp(&a.memory_mb))
+    });
+    processes
+}
+
+/// Command name of a process, its pid if `/proc` does not show it (e.g. a
+/// process in another container).
+fn process_name(pid: u32) -> String {
+    std::fs::read_to_string(format!("/proc/{}/comm", pid))
+        .map(|comm| comm.trim().to_string())
+        .unwrap_or_else(|_| pid.to_string())
+}
+
+impl Drop for Nvml {
+    fn drop(&mut self) {
+        // SAFETY: NVML was initialized in `load` and no handles outlive `self`.
+        unsafe {
+            (self.functions.shutdown)();
+            libc::dlclose(self.handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(pid: u32, used_gpu_memory: u64) -> ProcessInfo {
+        ProcessInfo { pid, used_gpu_memory, ..ProcessInfo::default() }
+    }
+
+    fn sample(pid: u32, sm_util: u32) -> ProcessUtilizationSample {
+        ProcessUtilizationSample { pid, sm_util, ..ProcessUtilizationSample::default() }
+    }
+
+    #[test]
+    fn test_merge_processes() {
+        const MIB: u64 = 1024 * 1024;
+        // Pids that cannot exist, so names fall back to the pid
+        let running = vec![
+            info(4_000_001, 512 * MIB),
+            info(4_000_002, VALUE_NOT_AVAILABLE),
+            // Both a compute and a graphics process
+            info(4_000_003, 100 * MIB),
+            info(4_000_003, 300 * MIB),
+        ];
+        // A sample of a process that exited since the lists were read is ignored
+        let samples = [sample(4_000_003, 20), sample(4_000_003, 35), sample(4_000_002, 5), sample(4_000_009, 90)];
+
+        let processes = merge_processes(running, &samples);
+        let summary: Vec<(u32, Option<u64>, f64)> = processes.iter()
+            .map(|p| (p.pid, p.memory_mb, p.utilization))
+            .collect();
+        assert_eq!(summary, vec![
+            (4_000_003, Some(300), 35.0),
+            (4_000_002, None, 5.0),
+            (4_000_001, Some(512), 0.0),
+        ]);
+        assert_eq!(processes[0].name, "4000003");
+
+        assert!(merge_processes(Vec::new(), &samples).is_empty());
+    }
+
+    #[test]
+    fn test_process_name() {
+        let comm = std::fs::read_to_string("/proc/self/comm").unwrap();
+        assert_eq!(process_name(std::process::id()), comm.trim());
+        assert_eq!(process_name(4_000_001), "4000001");
+    }
+}
@@ -0,0 +1,8 @@
+//! systemd sensor for waysensor-rs.
+//!
+//! Counts the failed units of the system and user service managers, read
+//! from systemd over D-Bus, and names them in the tooltip.
+
+pub mod systemd;
+
+pub use systemd::{FailedUnit, SystemdSensor, UnitScope};
@@ -0,0 +1,250 @@
+//! Failed units of the system and user service managers.
+//!
+//! systemd's manager object lists units filtered by state, so one
+//! `ListUnitsFiltered(["failed"])` call per manager, made through `busctl`
+//! like the battery sensor's logind calls, returns exactly the failed ones.
+//! The user manager answers on the session's user bus.
+
+use serde_json::Value;
+use std::process::Command;
+use waysensor_rs_core::{format, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+/// Which service managers to ask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitScope {
+    System,
+    User,
+    /// Both, the system manager first
+    #[default]
+    All,
+}
+
+impl std::str::FromStr for UnitScope {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "system" => Ok(Self::System),
+            "user" => Ok(Self::User),
+            "all" | "both" => Ok(Self::All),
+            _ => Err(SensorError::config_with_value(
+                "Invalid unit scope. Valid options: system, user, all",
+                s,
+            )),
+        }
+    }
+}
+
+impl UnitScope {
+    /// The `busctl` bus flags of the managers in this scope.
+    fn buses(self) -> &'static [&'static str] {
+        match self {
+            Self::System => &["--system"],
+            Self::User => &["--user"],
+            Self::All => &["--system", "--user"],
+        }
+    }
+}
+
+/// Manager name of a `busctl` bus flag.
+fn manager_name(bus: &str) -> &'static str {
+    if bus == "--user" { "user" } else { "system" }
+}
+
+/// A unit in the failed state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedUnit {
+    /// Unit name, e.g. `backup.service`
+    pub name: String,
+    pub description: String,
+    /// `system` or `user`
+    pub manager: &'static str,
+}
+
+/// Parse the `busctl --json` reply of `ListUnitsFiltered`
+/// (`a(ssssssouso)`: name, description, load, active and sub state, ...).
+fn parse_units(json: &str, manager: &'static str) -> Result<Vec<FailedUnit>, SensorError> {
+    let reply: Value = serde_json::from_str(json)
+        .map_err(|e| SensorError::parse_with_source("Invalid ListUnitsFiltered reply", e))?;
+    let units = reply["data"][0].as_array()
+        .ok_or_else(|| SensorError::parse("Unexpected ListUnitsFiltered reply"))?;
+    let mut failed: Vec<FailedUnit> = units
+        .iter()
+        .filter_map(|unit| {
+            Some(FailedUnit {
+                name: unit[0].as_str()?.to_string(),
+                description: unit[1].as_str().unwrap_or_default().to_string(),
+                manager,
+            })
+        })
+        .collect();
+    failed.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(failed)
+}
+
+/// Failed units of the manager on `bus` (`--system` or `--user`).
+fn failed_units(bus: &str) -> Result<Vec<FailedUnit>, SensorError> {
+    let output = Command::new("busctl")
+        .args([
+            bus, "--json=short", "call",
+            "org.freedesktop.systemd1", "/org/freedesktop/systemd1",
+            "org.freedesktop.systemd1.Manager", "ListUnitsFiltered",
+            "as", "1", "failed",
+        ])
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => SensorError::unavailable("busctl not installed"),
+            _ => SensorError::Io(e),
+        })?;
+    if !output.status.success() {
+        return Err(SensorError::unavailable(format!(
+            "The {} manager did not answer: {}",
+            manager_name(bus),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    parse_units(&String::from_utf8_lossy(&output.stdout), manager_name(bus))
+}
+
+/// Sensor counting failed systemd units.
+#[derive(Debug)]
+pub struct SystemdSensor {
+    name: String,
+    config: SensorConfig,
+    scope: UnitScope,
+}
+
+impl SystemdSensor {
+    pub fn new(scope: UnitScope) -> Self {
+        Self {
+            name: "systemd".to_string(),
+            config: SensorConfig::default(),
+            scope,
+        }
+    }
+
+    /// Failed units in scope, leaving out the `ignore` setting's, and the
+    /// managers that did not answer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no manager in scope answers.
+    pub fn failed(&self) -> Result<(Vec<FailedUnit>, Vec<SensorError>), SensorError> {
+        let ignored: Vec<&str> = self.config.get_custom("ignore")
+            .and_then(|v| v.as_array())
+            .map(|names| names.iter().filter_map(|name| name.as_str()).collect())
+            .unwrap_or_default();
+
+        let mut units = Vec::new();
+        let mut errors = Vec::new();
+        for bus in self.scope.buses() {
+            match failed_units(bus) {
+                Ok(failed) => units.extend(failed.into_iter().filter(|unit| !ignored.contains(&unit.name.as_str()))),
+                Err(e) => errors.push(e),
+            }
+        }
+        if errors.len() == self.scope.buses().len() {
+            return Err(errors.remove(0));
+        }
+        Ok((units, errors))
+    }
+
+    fn build_tooltip(&self, units: &[FailedUnit], errors: &[SensorError]) -> String {
+        if units.is_empty() && errors.is_empty() {
+            return format::key_value("Failed Units", "none", &self.config);
+        }
+        let mut lines = Vec::new();
+        for manager in ["system", "user"] {
+            let failed: Vec<&FailedUnit> = units.iter().filter(|unit| unit.manager == manager).collect();
+            if failed.is_empty() {
+                continue;
+            }
+            lines.push(format::key_only(&format!("Failed {} units", manager), &self.config));
+            for unit in failed {
+                lines.push(format::key_value(&unit.name, &unit.description, &self.config));
+            }
+        }
+        for error in errors {
+            lines.push(format::value_only(&error.to_string(), &self.config));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Discoverable for SystemdSensor {
+    const KIND: &'static str = "service managers";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        // A manager that does not answer is left out
+        Ok(UnitScope::All
+            .buses()
+            .iter()
+            .filter_map(|bus| {
+                let failed = failed_units(bus).ok()?;
+                Some(DiscoveredItem::new(manager_name(bus), format!("systemd {} manager", manager_name(bus)))
+                    .with_detail("failed", failed.len().to_string()))
+            })
+            .collect())
+    }
+}
+
+impl Sensor for SystemdSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let (units, errors) = self.failed()?;
+
+        // Nothing to show while every unit is fine, unless configured otherwise
+        let show_zero = self.config.get_custom("show_zero").and_then(|v| v.as_bool()).unwrap_or(false);
+        let text = if units.is_empty() && !show_zero {
+            String::new()
+        } else {
+            format::with_icon_and_colors(&units.len().to_string(), &self.config.icons.failed_units, &self.config)
+        };
+        let class = if units.is_empty() { &self.config.theme.normal } else { &self.config.theme.critical };
+
+        Ok(WaybarOutput {
+            text,
+            tooltip: Some(self.build_tooltip(&units, &errors)),
+            class: Some(class.clone()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        self.failed().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_unit_parsing() {
+        let reply = r#"{"type":"a(ssssssouso)","data":[[
+            ["nfs-mount.service","Mount the NAS","loaded","failed","failed","","/org/freedesktop/systemd1/unit/nfs_2dmount_2eservice",0,"","/"],
+            ["backup.service","Nightly backup","loaded","failed","failed","","/org/freedesktop/systemd1/unit/backup_2eservice",0,"","/"]
+        ]]}"#;
+        let units = parse_units(reply, "system").unwrap();
+        assert_eq!(units, [
+            FailedUnit { name: "backup.service".to_string(), description: "Nightly backup".to_string(), manager: "system" },
+            FailedUnit { name: "nfs-mount.service".to_string(), description: "Mount the NAS".to_string(), manager: "system" },
+        ]);
+        assert!(parse_units(r#"{"type":"a(ssssssouso)","data":[[]]}"#, "user").unwrap().is_empty());
+        assert_eq!("both".parse::<UnitScope>().unwrap(), UnitScope::All);
+    }
+}
@@ -0,0 +1,325 @@
+use waysensor_rs_core::{sysroot, DiscoveredItem, Discoverable, Reading, Sensor, SensorConfig, SensorError, Theme, WaybarOutput, format};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A single fan exposed through the hwmon interface.
+#[derive(Debug, Clone)]
+pub struct FanInfo {
+    /// Human readable label (fanN_label, or "<device> fanN")
+    pub label: String,
+    /// Path to the fanN_input file
+    pub input_path: PathBuf,
+    /// Current speed in RPM
+    pub rpm: u32,
+    /// PWM duty cycle in percent, if the fan has a matching pwmN control
+    pub pwm_percent: Option<f64>,
+    /// Maximum speed reported by the driver (fanN_max), if any
+    pub max_rpm: Option<u32>,
+}
+
+impl FanInfo {
+    /// A fan that is being driven but reports no rotation.
+    pub fn is_stalled(&self) -> bool {
+        self.rpm == 0 && self.pwm_percent.is_some_and(|pwm| pwm > 0.0)
+    }
+}
+
+/// Enumerate all fans under /sys/class/hwmon, sorted by label.
+pub fn list_fans() -> Vec<FanInfo> {
+    list_fans_in(&sysroot::path("/sys/class/hwmon"))
+}
+
+/// Enumerate the fans below an hwmon class directory (useful for testing).
+pub fn list_fans_in(hwmon_dir: &Path) -> Vec<FanInfo> {
+    let mut fans = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(hwmon_dir) {
+        for entry in entries.flatten() {
+            let hwmon_path = entry.path();
+            let device_name = fs::read_to_string(hwmon_path.join("name"))
+                .map(|n| n.trim().to_string())
+                .unwrap_or_else(|_| "hwmon".to_string());
+
+            if let Ok(hwmon_entries) = fs::read_dir(&hwmon_path) {
+                for hwmon_entry in hwmon_entries.flatten() {
+                    let file_name = hwmon_entry.file_name();
+                    let Some(name) = file_name.to_str() else { continue };
+                    if name.starts_with("fan") && name.ends_with("_input") {
+                        fans.extend(read_fan(&hwmon_path, &device_name, name));
+                    }
+                }
+            }
+        }
+    }
+
+    fans.sort_by(|a, b| a.label.cmp(&b.label));
+    fans
+}
+
+fn read_fan(hwmon_path: &Path, device_name: &str, input_name: &str) -> Option<FanInfo> {
+    let index = input_name.strip_prefix("fan")?.strip_suffix("_input")?;
+    let input_path = hwmon_path.join(input_name);
+    let rpm = read_u32(&input_path)?;
+
+    let label = fs::read_to_string(hwmon_path.join(format!("fan{}_label", index)))
+        .map(|l| l.trim().to_string())
+        .unwrap_or_else(|_| format!("{} fan{}", device_name, index));
+
+    // pwmN is 0-255 on every driver that exposes it
+    let pwm_percent = read_u32(&hwmon_path.join(format!("pwm{}", index)))
+        .map(|pwm| (pwm as f64 / 255.0 * 100.0).min(100.0));
+    let max_rpm = read_u32(&hwmon_path.join(format!("fan{}_max", index)))
+        .filter(|&max| max > 0);
+
+    Some(FanInfo {
+        label,
+        input_path,
+        rpm,
+        pwm_percent,
+        max_rpm,
+    })
+}
+
+fn read_u32(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[derive(Debug)]
+pub struct FanSensor {
+    name: String,
+    fan: Option<String>,
+    warning_rpm: u32,
+    critical_rpm: u32,
+    theme: Theme,
+    config: SensorConfig,
+    last_reading: Option<Reading>,
+}
+
+impl FanSensor {
+    /// Create a fan sensor.
+    ///
+    /// `fan` selects a single fan by label or fanN_input path; when `None`
+    /// the fastest fan is shown in the bar and all fans in the tooltip.
+    pub fn new(
+        fan: Option<String>,
+        warning_rpm: u32,
+        critical_rpm: u32,
+    ) -> Result<Self, SensorError> {
+        if warning_rpm >= critical_rpm {
+            return Err(SensorError::config(
+                "Warning RPM must be lower than critical RPM",
+            ));
+        }
+
+        let fans = list_fans();
+        if fans.is_empty() {
+            return Err(SensorError::unavailable("No hwmon fans found"));
+        }
+
+        if let Some(selector) = fan.as_deref().filter(|s| !fans.iter().any(|f| Self::matches(f, s))) {
+            return Err(SensorError::unavailable(format!("Fan not found: {}", selector)));
+        }
+
+        let name = match &fan {
+            Some(selector) => format!("fan-{}", selector),
+            None => "fan".to_string(),
+        };
+
+        Ok(Self {
+            name,
+            fan,
+            warning_rpm,
+            critical_rpm,
+            theme: Theme::default(),
+            config: SensorConfig::default(),
+            last_reading: None,
+        })
+    }
+
+    fn matches(fan: &FanInfo, selector: &str) -> bool {
+        fan.label == selector || fan.input_path == Path::new(selector)
+    }
+
+    fn create_gauge(percentage: f64, width: usize) -> String {
+        let filled = ((percentage / 100.0) * width as f64).round() as usize;
+        let empty = width.saturating_sub(filled);
+        format!("{}{}", "█".repeat(filled), "░".repeat(empty))
+    }
+
+    fn format_fan_line(&self, fan: &FanInfo) -> String {
+        let scale = fan.max_rpm.unwrap_or(self.critical_rpm).max(1);
+        let gauge = Self::create_gauge((fan.rpm as f64 / scale as f64 * 100.0).min(100.0), 10);
+
+        let mut value = format!("{} {} RPM", gauge, fan.rpm);
+        if let Some(pwm) = fan.pwm_percent {
+            value.push_str(&format!(" (PWM {:.0}%)", pwm));
+        }
+        if fan.is_stalled() {
+            value.push_str(" ⚠️ stalled");
+        }
+
+        format::key_value(&fan.label, &value, &self.config)
+    }
+}
+
+impl Discoverable for FanSensor {
+    const KIND: &'static str = "fans";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        Ok(list_fans()
+            .into_iter()
+            .map(|fan| {
+                let mut item = DiscoveredItem::new(fan.input_path.to_string_lossy(), fan.label.clone())
+                    .with_detail("speed", format!("{} RPM", fan.rpm));
+                if let Some(pwm) = fan.pwm_percent {
+                    item = item.with_detail("pwm", format!("{:.0}%", pwm));
+                }
+                if fan.is_stalled() {
+                    item = item.with_detail("state", "stalled");
+                }
+                item
+            })
+            .collect())
+    }
+}
+
+impl Sensor for FanSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let fans = list_fans();
+
+        let displayed = match &self.fan {
+            Some(selector) => fans.iter().find(|f| Self::matches(f, selector)),
+            None => fans.iter().max_by_key(|f| f.rpm),
+        }
+        .ok_or_else(|| SensorError::temporarily_unavailable("Fan no longer present"))?;
+
+        self.last_reading = Some(fans.iter().fold(
+            Reading::new(displayed.rpm as f64, "RPM"),
+            |reading, fan| reading.with_extra(fan.label.clone(), fan.rpm as f64),
+        ));
+
+        let text = format::with_icon_and_colors(
+            &format!("{:4} RPM", displayed.rpm),
+            &self.config.icons.fan,
+            &self.config,
+        );
+
+        let mut tooltip_lines: Vec<String> = fans.iter()
+            .map(|fan| self.format_fan_line(fan))
+            .collect();
+        tooltip_lines.push(format::key_value(
+            "Thresholds",
+            &format!("⚠️ {} RPM / 🔴 {} RPM", self.warning_rpm, self.critical_rpm),
+            &self.config,
+        ));
+
+        let percentage = ((displayed.rpm as f64 / self.critical_rpm as f64) * 100.0).min(100.0) as u8;
+
+        let mut output = format::themed_output(
+            text,
+            Some(tooltip_lines.join("\n")),
+            Some(percentage),
+            displayed.rpm as f64,
+            self.warning_rpm as f64,
+            self.critical_rpm as f64,
+            &self.theme,
+        );
+
+        // A stalled fan is worse than a loud one
+        if fans.iter().any(FanInfo::is_stalled) {
+            output.class = Some(self.theme.critical.clone());
+        }
+
+        Ok(output)
+    }
+
+    fn read_typed(&mut self) -> Result<(WaybarOutput, Reading), Self::Error> {
+        let output = self.read()?;
+        let reading = self.last_reading.clone()
+            .unwrap_or_else(|| Reading::from_output(&output));
+        Ok((output, reading))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.theme = config.theme.clone();
+        self.config = config;
+        Ok(())
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        if list_fans().is_empty() {
+            return Err(SensorError::unavailable("No hwmon fans found"));
+        }
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_fans() {
+        let dir = tempfile::tempdir().unwrap();
+        let nct = dir.path().join("hwmon3");
+        fs::create_dir_all(&nct).unwrap();
+        fs::write(nct.join("name"), "nct6775\n").unwrap();
+        fs::write(nct.join("fan1_input"), "1200\n").unwrap();
+        fs::write(nct.join("fan1_label"), "CPU Fan\n").unwrap();
+        fs::write(nct.join("fan1_max"), "3000\n").unwrap();
+        fs::write(nct.join("pwm1"), "255\n").unwrap();
+        // Driven but not spinning
+        fs::write(nct.join("fan2_input"), "0\n").unwrap();
+        fs::write(nct.join("pwm2"), "102\n").unwrap();
+        // Unreadable input is skipped
+        fs::write(nct.join("fan3_input"), "N/A\n").unwrap();
+
+        // Device without fans
+        let k10 = dir.path().join("hwmon1");
+        fs::create_dir_all(&k10).unwrap();
+        fs::write(k10.join("name"), "k10temp\n").unwrap();
+        fs::write(k10.join("temp1_input"), "45000\n").unwrap();
+
+        let fans = list_fans_in(dir.path());
+        assert_eq!(fans.len(), 2);
+
+        assert_eq!(fans[0].label, "CPU Fan");
+        assert_eq!(fans[0].rpm, 1200);
+        assert_eq!(fans[0].max_rpm, Some(3000));
+        assert_eq!(fans[0].pwm_percent, Some(100.0));
+        assert!(!fans[0].is_stalled());
+
+        assert_eq!(fans[1].label, "nct6775 fan2");
+        assert_eq!(fans[1].input_path, nct.join("fan2_input"));
+        assert_eq!(fans[1].max_rpm, None);
+        assert_eq!(fans[1].pwm_percent, Some(40.0));
+        assert!(fans[1].is_stalled());
+
+        assert!(FanSensor::matches(&fans[0], "CPU Fan"));
+        assert!(FanSensor::matches(&fans[1], &nct.join("fan2_input").to_string_lossy()));
+        assert!(list_fans_in(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_stopped_fan_without_pwm_is_not_stalled() {
+        let fan = FanInfo {
+            label: "case".to_string(),
+            input_path: PathBuf::from("/sys/class/hwmon/hwmon0/fan1_input"),
+            rpm: 0,
+            pwm_percent: None,
+            max_rpm: None,
+        };
+        assert!(!fan.is_stalled());
+        assert!(!FanInfo { pwm_percent: Some(0.0), ..fan }.is_stalled());
+    }
+}
@@ -1,5 +1,7 @@
 pub mod thermal;
+pub mod fan;
 pub mod error;
 
 pub use thermal::ThermalSensor;
-pub use error::ThermalError;
\ No newline at end of file
+pub use fan::{FanInfo, FanSensor};
+pub use error::ThermalError;
@@ -1,10 +1,10 @@
 use clap::Parser;
-use waysensor_rs_core::{GlobalConfig, Sensor, IconStyle};
+use waysensor_rs_core::{discovery, schedule, session_lock, sysroot, GlobalConfig, Sensor, SensorError, IconStyle};
 use std::io::{self, Write};
+use std::path::PathBuf;
 use std::time::Duration;
-use tokio::time;
 
-use waysensor_rs_thermal::ThermalSensor;
+use waysensor_rs_thermal::{FanSensor, ThermalSensor};
 
 #[derive(Parser)]
 #[command(name = "waysensor-rs-thermal")]
@@ -31,9 +31,37 @@ struct Args {
     #[arg(short, long)]
     once: bool,
     
-    /// List available thermal zones
+    /// List available temperature sensors (or fans with --fans) and exit
+    #[arg(short, long, alias = "list-zones")]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Monitor fan speeds instead of temperature
     #[arg(long)]
-    list_zones: bool,
+    fans: bool,
+
+    /// Fan to display in the bar (label or fanN_input path, fastest fan if not specified)
+    #[arg(long)]
+    fan: Option<String>,
+
+    /// Fan warning threshold (RPM)
+    #[arg(long, default_value = "3000")]
+    fan_warning: u32,
+
+    /// Fan critical threshold (RPM)
+    #[arg(long, default_value = "4500")]
+    fan_critical: u32,
+
+    /// List available fans and exit (same as --fans --list)
+    #[arg(long)]
+    list_fans: bool,
 
     /// Icon style (nerdfont, fontawesome, ascii, none)
     #[arg(long)]
@@ -59,6 +87,10 @@ struct Args {
     #[arg(long)]
     check: bool,
 
+    /// Read /proc, /sys and other system paths below this directory (for testing)
+    #[arg(long, value_name = "DIR")]
+    sys_root: Option<PathBuf>,
+
     /// Generate example config file and exit
     #[arg(long)]
     generate_config: bool,
@@ -67,94 +99,29 @@ struct Args {
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    if let Some(root) = &args.sys_root {
+        sysroot::set(root);
+    }
     
-    // Handle list zones mode
-    if args.list_zones {
-        println!("🌡️  Available Thermal Sensors");
-        println!("=============================\n");
-        
-        let mut found_any = false;
-        
-        // Check thermal_zone interface
-        let thermal_dir = "/sys/class/thermal";
-        if let Ok(entries) = std::fs::read_dir(thermal_dir) {
-            for entry in entries.flatten() {
-                if let Some(name) = entry.file_name().to_str() {
-                    if name.starts_with("thermal_zone") {
-                        let type_path = format!("{}/{}/type", thermal_dir, name);
-                        let temp_path = format!("{}/{}/temp", thermal_dir, name);
-                        
-                        if let (Ok(zone_type), Ok(temp)) = (
-                            std::fs::read_to_string(&type_path),
-                            std::fs::read_to_string(&temp_path)
-                        ) {
-                            let zone_type = zone_type.trim();
-                            let temp_millidegrees: i32 = temp.trim().parse().unwrap_or(0);
-                            let temp_celsius = temp_millidegrees as f64 / 1000.0;
-                            
-                            println!("{:<30} {:<25} {:.1}°C", name, zone_type, temp_celsius);
-                            found_any = true;
-                        }
-                    }
-                }
-            }
-        }
-        
-        // Check hwmon interface
-        if let Ok(entries) = std::fs::read_dir("/sys/class/hwmon") {
-            for entry in entries.flatten() {
-                let hwmon_path = entry.path();
-                if let Ok(hwmon_entries) = std::fs::read_dir(&hwmon_path) {
-                    for hwmon_entry in hwmon_entries.flatten() {
-                        let file_name = hwmon_entry.file_name();
-                        if let Some(name) = file_name.to_str() {
-                            if name.starts_with("temp") && name.ends_with("_input") {
-                                let temp_path = hwmon_entry.path();
-                                
-                                // Try to read temperature and label
-                                if let Ok(temp_str) = std::fs::read_to_string(&temp_path) {
-                                    if let Ok(temp_millidegrees) = temp_str.trim().parse::<i32>() {
-                                        let temp_celsius = temp_millidegrees as f64 / 1000.0;
-                                        
-                                        // Try to get a label
-                                        let label_path = temp_path.with_file_name(
-                                            name.replace("_input", "_label")
-                                        );
-                                        let label = if let Ok(label_str) = std::fs::read_to_string(&label_path) {
-                                            label_str.trim().to_string()
-                                        } else {
-                                            // Try to get hwmon device name
-                                            let name_path = hwmon_path.join("name");
-                                            if let Ok(device_name) = std::fs::read_to_string(&name_path) {
-                                                format!("{} {}", device_name.trim(), name.replace("_input", ""))
-                                            } else {
-                                                format!("hwmon {}", name.replace("_input", ""))
-                                            }
-                                        };
-                                        
-                                        let display_path = temp_path.to_string_lossy();
-                                        println!("{:<30} {:<25} {:.1}°C", 
-                                            display_path.chars().rev().take(30).collect::<String>().chars().rev().collect::<String>(),
-                                            label, 
-                                            temp_celsius
-                                        );
-                                        found_any = true;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        
-        if !found_any {
-            println!("No thermal sensors found.");
-        }
-        
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
         return Ok(());
     }
     
+    // Handle list mode
+    if args.list || args.list_fans {
+        let result = if args.fans || args.fan.is_some() || args.list_fans {
+            discovery::print::<FanSensor>(args.json)
+        } else {
+            discovery::print::<ThermalSensor>(args.json)
+        };
+        if let Err(e) = result {
+            eprintln!("Error listing sensors: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     // Handle config generation
     if args.generate_config {
         if let Some(config_path) = GlobalConfig::default_config_path() {
@@ -168,11 +135,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
     
-    let mut thermal_sensor = ThermalSensor::new(
-        args.zone,
-        args.warning,
-        args.critical,
-    )?;
+    let fan_mode = args.fans || args.fan.is_some();
+    let mut thermal_sensor: Box<dyn Sensor<Error = SensorError>> = if fan_mode {
+        Box::new(FanSensor::new(args.fan, args.fan_warning, args.fan_critical)?)
+    } else {
+        Box::new(ThermalSensor::new(
+            args.zone,
+            args.warning,
+            args.critical,
+        )?)
+    };
     
     // Check availability if requested
     if args.check {
@@ -199,7 +171,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             GlobalConfig::default()
         }
     };
-    let mut config = global_config.to_sensor_config()
+    let mut config = global_config.to_sensor_config_for("thermal")
         .with_update_interval(Duration::from_millis(args.interval))
         .apply_color_overrides(
             args.icon_color,
@@ -213,18 +185,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config = config.with_icon_style(icon_style);
     }
     
+    // Load sensor-specific configuration from global config
+    if !fan_mode {
+        if let Some(serde_json::Value::Object(map)) = global_config.sensors.get("thermal") {
+            for (key, value) in map {
+                config = config.with_custom(key.clone(), value.clone());
+            }
+        }
+    }
+    
     thermal_sensor.configure(config)?;
     
     if args.once {
-        let output = thermal_sensor.read()?;
+        let output = thermal_sensor.read_styled()?;
         println!("{}", serde_json::to_string(&output)?);
     } else {
-        let mut interval = time::interval(Duration::from_millis(args.interval));
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
         
         loop {
             interval.tick().await;
+            if session_lock::wait_async(thermal_sensor.config()).await {
+                interval.reset();
+            }
             
-            match thermal_sensor.read() {
+            match thermal_sensor.read_styled() {
                 Ok(output) => {
                     println!("{}", serde_json::to_string(&output)?);
                     io::stdout().flush()?;
@@ -1,15 +1,132 @@
-use waysensor_rs_core::{Sensor, SensorConfig, SensorError, Theme, WaybarOutput, format};
+use waysensor_rs_core::{DiscoveredItem, Discoverable, Reading, Sensor, SensorConfig, SensorError, Theme, WaybarOutput, format, hwmon, procfs, sysroot};
 use std::fs;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Default slope (°C per minute) above which the temperature counts as rising rapidly.
+const DEFAULT_RAPID_RISE_PER_MIN: f64 = 5.0;
+
+/// Default number of recent samples the trend slope is fitted over.
+const DEFAULT_TREND_WINDOW: usize = 6;
+
+/// What a temperature input *is*, independent of the numbered sysfs
+/// directory it currently lives in.
+///
+/// `hwmonN` and `thermal_zoneN` numbers are assigned at probe time and can
+/// change after suspend/resume or a driver reload, so the cached path is
+/// re-resolved from this identity when it disappears.
+#[derive(Debug, Clone, PartialEq)]
+enum SensorIdentity {
+    /// A thermal zone, identified by its `type`
+    ThermalZone { zone_type: String },
+    /// An hwmon input, identified by device `name` and input `label` (or file name)
+    Hwmon { device: String, label: Option<String>, input: String },
+}
+
+impl SensorIdentity {
+    /// Capture the identity of a zone name or hwmon input path.
+    fn capture(zone: &str, temp_path: &Path) -> Option<Self> {
+        if zone.starts_with('/') {
+            let input = temp_path.file_name()?.to_str()?.to_string();
+            let device = read_trimmed(&temp_path.with_file_name("name"))?;
+            let label = read_trimmed(&temp_path.with_file_name(input.replace("_input", "_label")));
+            Some(Self::Hwmon { device, label, input })
+        } else {
+            let zone_type = read_trimmed(&temp_path.with_file_name("type"))?;
+            Some(Self::ThermalZone { zone_type })
+        }
+    }
+
+    /// Find the current `(zone, temp_path)` for this identity under `sys_class`
+    /// (normally `/sys/class`).
+    fn resolve(&self, sys_class: &Path) -> Option<(String, PathBuf)> {
+        match self {
+            Self::ThermalZone { zone_type } => {
+                fs::read_dir(sys_class.join("thermal")).ok()?
+                    .flatten()
+                    .filter(|e| e.file_name().to_string_lossy().starts_with("thermal_zone"))
+                    .find(|e| read_trimmed(&e.path().join("type")).as_deref() == Some(zone_type))
+                    .map(|e| (e.file_name().to_string_lossy().to_string(), e.path().join("temp")))
+            }
+            Self::Hwmon { device, label, input } => {
+                let hwmon = fs::read_dir(sys_class.join("hwmon")).ok()?
+                    .flatten()
+                    .map(|e| e.path())
+                    .find(|p| read_trimmed(&p.join("name")).as_deref() == Some(device))?;
+
+                // Inputs can be renumbered too, so prefer matching on the label
+                let path = match label {
+                    Some(label) => fs::read_dir(&hwmon).ok()?
+                        .flatten()
+                        .map(|e| e.path())
+                        .find(|p| {
+                            let file_name = p.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+                            file_name.starts_with("temp")
+                                && file_name.ends_with("_label")
+                                && read_trimmed(p).as_deref() == Some(label)
+                        })
+                        .map(|p| PathBuf::from(p.to_string_lossy().replace("_label", "_input")))?,
+                    None => hwmon.join(input),
+                };
+
+                path.exists().then(|| (path.to_string_lossy().to_string(), path))
+            }
+        }
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Whether a read error means the sysfs node went away.
+fn is_gone(error: &io::Error) -> bool {
+    // Removed sysfs devices return ENODEV rather than ENOENT on open files
+    const ENODEV: i32 = 19;
+    error.kind() == io::ErrorKind::NotFound || error.raw_os_error() == Some(ENODEV)
+}
 
 #[derive(Debug)]
 pub struct ThermalSensor {
     name: String,
     zone: String,
+    temp_path: PathBuf,
+    identity: Option<SensorIdentity>,
     warning_threshold: f64,  // Celsius
     critical_threshold: f64, // Celsius
     theme: Theme,
     config: SensorConfig,
+    history: Vec<(Instant, f64)>,
+    rapid_rise_per_min: f64,
+    trend_window: usize,
+}
+
+/// Least-squares slope of the temperature history in °C per minute.
+///
+/// Returns `None` until at least two samples spanning a measurable amount of
+/// time are available.
+pub fn temperature_slope(history: &[(Instant, f64)]) -> Option<f64> {
+    let (start, _) = *history.first()?;
+    if history.len() < 2 {
+        return None;
+    }
+
+    let points: Vec<(f64, f64)> = history.iter()
+        .map(|(t, temp)| (t.duration_since(start).as_secs_f64() / 60.0, *temp))
+        .collect();
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance <= f64::EPSILON {
+        return None;
+    }
+
+    let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+    Some(covariance / variance)
 }
 
 impl ThermalSensor {
@@ -57,7 +174,7 @@ impl ThermalSensor {
             zone.clone()
         } else {
             // thermal_zone format
-            format!("/sys/class/thermal/{}/temp", zone)
+            sysroot::path(format!("/sys/class/thermal/{}/temp", zone)).to_string_lossy().into_owned()
         };
         
         if !Path::new(&zone_path).exists() {
@@ -67,6 +184,9 @@ impl ThermalSensor {
             });
         }
         
+        let temp_path = PathBuf::from(zone_path);
+        let identity = SensorIdentity::capture(&zone, &temp_path);
+        
         // Generate a more descriptive name
         let name = if zone.starts_with("/") {
             // Extract a meaningful name from hwmon path
@@ -83,13 +203,44 @@ impl ThermalSensor {
         Ok(Self {
             name,
             zone,
+            temp_path,
+            identity,
             warning_threshold,
             critical_threshold,
             theme: Theme::default(),
             config: SensorConfig::default(),
+            history: Vec::new(),
+            rapid_rise_per_min: DEFAULT_RAPID_RISE_PER_MIN,
+            trend_window: DEFAULT_TREND_WINDOW,
         })
     }
     
+    /// Get a trend arrow for a slope in °C per minute.
+    fn get_trend_arrow(slope: f64, rapid: f64) -> &'static str {
+        match slope {
+            s if s >= rapid => "⇈",
+            s if s >= 0.5 => "↑",
+            s if s <= -0.5 => "↓",
+            _ => "→",
+        }
+    }
+    
+    fn record_sample(&mut self, temperature: f64) {
+        self.history.push((Instant::now(), temperature));
+        let max_len = self.config.visuals.sparkline_length.max(self.trend_window);
+        if self.history.len() > max_len {
+            let excess = self.history.len() - max_len;
+            self.history.drain(..excess);
+        }
+    }
+    
+    /// Slope over the last `trend_window` samples, independent of how much
+    /// history the sparkline keeps.
+    fn trend_slope(&self) -> Option<f64> {
+        let start = self.history.len().saturating_sub(self.trend_window);
+        temperature_slope(&self.history[start..])
+    }
+    
     fn find_best_thermal_zone() -> Result<String, SensorError> {
         // First try thermal_zone interface
         if let Ok(zone) = Self::find_thermal_zone() {
@@ -108,8 +259,8 @@ impl ThermalSensor {
     }
     
     fn find_thermal_zone() -> Result<String, SensorError> {
-        let thermal_dir = "/sys/class/thermal";
-        let entries = fs::read_dir(thermal_dir)
+        let thermal_dir = sysroot::path("/sys/class/thermal");
+        let entries = fs::read_dir(&thermal_dir)
             .map_err(|e| SensorError::Io(e))?;
         
         // Look for CPU thermal zone
@@ -117,7 +268,7 @@ impl ThermalSensor {
             if let Ok(entry) = entry {
                 if let Some(name) = entry.file_name().to_str() {
                     if name.starts_with("thermal_zone") {
-                        let type_path = format!("{}/{}/type", thermal_dir, name);
+                        let type_path = thermal_dir.join(name).join("type");
                         if let Ok(zone_type) = fs::read_to_string(&type_path) {
                             let zone_type = zone_type.trim();
                             // Prefer CPU zones
@@ -133,7 +284,7 @@ impl ThermalSensor {
         }
         
         // If no CPU zone found, use the first available zone
-        let entries = fs::read_dir(thermal_dir)
+        let entries = fs::read_dir(&thermal_dir)
             .map_err(|e| SensorError::Io(e))?;
         for entry in entries {
             if let Ok(entry) = entry {
@@ -155,79 +306,37 @@ impl ThermalSensor {
         // Find hwmon temperature sensors and prefer CPU sensors
         let mut candidates = Vec::new();
         
-        // Search for hwmon temperature sensors
-        if let Ok(entries) = std::fs::read_dir("/sys/class/hwmon") {
-            for entry in entries.flatten() {
-                let hwmon_path = entry.path();
-                if let Some(_hwmon_name) = hwmon_path.file_name().and_then(|n| n.to_str()) {
-                    // Look for temperature inputs in this hwmon device
-                    if let Ok(hwmon_entries) = std::fs::read_dir(&hwmon_path) {
-                        for hwmon_entry in hwmon_entries.flatten() {
-                            let file_name = hwmon_entry.file_name();
-                            if let Some(name) = file_name.to_str() {
-                                if name.starts_with("temp") && name.ends_with("_input") {
-                                    let full_path = hwmon_entry.path();
-                                    
-                                    // Check if this has a label to identify CPU temperature
-                                    let label_path = full_path.with_file_name(
-                                        name.replace("_input", "_label")
-                                    );
-                                    
-                                    let priority = if let Ok(label) = std::fs::read_to_string(&label_path) {
-                                        let label = label.trim().to_lowercase();
-                                        // Check device name for zenpower (most accurate AMD CPU temp)
-                                        let name_path = hwmon_path.join("name");
-                                        let device_name = if let Ok(name) = std::fs::read_to_string(&name_path) {
-                                            name.trim().to_lowercase()
-                                        } else {
-                                            String::new()
-                                        };
-                                        
-                                        if device_name.contains("zenpower") && (label.contains("tdie") || label.contains("tctl")) {
-                                            110 // Highest priority for zenpower Tdie/Tctl (most accurate AMD)
-                                        } else if device_name.contains("k10temp") && (label.contains("tdie") || label.contains("tctl")) {
-                                            105 // Very high priority for k10temp Tdie/Tctl
-                                        } else if label.contains("cpu") || label.contains("core") || 
-                                           label.contains("package") || label.contains("tctl") ||
-                                           label.contains("tdie") || label.contains("cputin") {
-                                            100 // High priority for CPU sensors
-                                        } else {
-                                            50 // Medium priority for labeled sensors
-                                        }
-                                    } else {
-                                        // Check hwmon name for CPU indicators
-                                        let name_path = hwmon_path.join("name");
-                                        if let Ok(hwmon_device_name) = std::fs::read_to_string(&name_path) {
-                                            let device_name = hwmon_device_name.trim().to_lowercase();
-                                            if device_name.contains("zenpower") {
-                                                90 // High priority for zenpower (unlabeled)
-                                            } else if device_name.contains("k10temp") {
-                                                85 // High priority for k10temp
-                                            } else if device_name.contains("cpu") || device_name.contains("core") ||
-                                               device_name.contains("coretemp") {
-                                                80 // High priority for CPU hwmon devices
-                                            } else {
-                                                10 // Low priority for unlabeled, non-CPU sensors
-                                            }
-                                        } else {
-                                            10
-                                        }
-                                    };
-                                    
-                                    // Test if the sensor reads a valid temperature
-                                    if let Ok(temp_content) = std::fs::read_to_string(&full_path) {
-                                        if let Ok(millidegrees) = temp_content.trim().parse::<i32>() {
-                                            let temp_celsius = millidegrees as f64 / 1000.0;
-                                            // Only consider sensors that read reasonable temperatures (5°C to 150°C)
-                                            if temp_celsius >= 5.0 && temp_celsius <= 150.0 {
-                                                candidates.push((priority, full_path.to_string_lossy().to_string()));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+        for input in hwmon::temp_inputs(&sysroot::path(hwmon::HWMON_PATH)) {
+            let device_name = input.device.to_lowercase();
+            let priority = if let Some(label) = &input.label {
+                let label = label.to_lowercase();
+                if device_name.contains("zenpower") && (label.contains("tdie") || label.contains("tctl")) {
+                    110 // Highest priority for zenpower Tdie/Tctl (most accurate AMD)
+                } else if device_name.contains("k10temp") && (label.contains("tdie") || label.contains("tctl")) {
+                    105 // Very high priority for k10temp Tdie/Tctl
+                } else if label.contains("cpu") || label.contains("core") || 
+                   label.contains("package") || label.contains("tctl") ||
+                   label.contains("tdie") || label.contains("cputin") {
+                    100 // High priority for CPU sensors
+                } else {
+                    50 // Medium priority for labeled sensors
+                }
+            } else if device_name.contains("zenpower") {
+                90 // High priority for zenpower (unlabeled)
+            } else if device_name.contains("k10temp") {
+                85 // High priority for k10temp
+            } else if device_name.contains("cpu") || device_name.contains("core") ||
+               device_name.contains("coretemp") {
+                80 // High priority for CPU hwmon devices
+            } else {
+                10 // Low priority for unlabeled, non-CPU sensors
+            };
+            
+            // Only consider sensors that read reasonable temperatures (5°C to 150°C)
+            if let Ok(millidegrees) = procfs::read_value::<i32>(&input.path) {
+                let temp_celsius = millidegrees as f64 / 1000.0;
+                if (5.0..=150.0).contains(&temp_celsius) {
+                    candidates.push((priority, input.path.to_string_lossy().to_string()));
                 }
             }
         }
@@ -245,18 +354,23 @@ impl ThermalSensor {
         }
     }
     
-    fn read_temperature(&self) -> Result<f64, SensorError> {
-        let temp_path = if self.zone.starts_with("/") {
-            // Already a full path (hwmon sensor)
-            self.zone.clone()
-        } else {
-            // thermal_zone format
-            format!("/sys/class/thermal/{}/temp", self.zone)
+    /// Read the cached temperature path, re-resolving it once if the device
+    /// was re-enumerated since the last read.
+    fn read_temperature(&mut self) -> Result<f64, SensorError> {
+        let content = match procfs::read_with(&self.temp_path, str::to_owned) {
+            Err(e) if is_gone(&e) => {
+                let (zone, temp_path) = self.identity.as_ref()
+                    .and_then(|identity| identity.resolve(&sysroot::path("/sys/class")))
+                    .ok_or_else(|| SensorError::temporarily_unavailable(
+                        format!("Thermal sensor disappeared: {}", self.temp_path.display())
+                    ))?;
+                self.zone = zone;
+                self.temp_path = temp_path;
+                fs::read_to_string(&self.temp_path)?
+            }
+            result => result?,
         };
         
-        let content = fs::read_to_string(&temp_path)
-            .map_err(|e| SensorError::Io(e))?;
-        
         let millidegrees = content.trim().parse::<i32>()
             .map_err(|e| SensorError::Parse {
                 message: format!("Failed to parse temperature: {}", e),
@@ -268,11 +382,50 @@ impl ThermalSensor {
     }
 }
 
+impl Discoverable for ThermalSensor {
+    const KIND: &'static str = "temperature sensors";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        let mut items = Vec::new();
+        let format_temp = |path: &Path| {
+            read_trimmed(path)
+                .and_then(|t| t.parse::<i32>().ok())
+                .map(|millidegrees| format!("{:.1}°C", millidegrees as f64 / 1000.0))
+        };
+
+        let mut zones: Vec<_> = fs::read_dir(sysroot::path("/sys/class/thermal"))
+            .map(|entries| entries.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default();
+        zones.retain(|p| p.file_name().is_some_and(|n| n.to_string_lossy().starts_with("thermal_zone")));
+        zones.sort();
+
+        for zone in zones {
+            let Some(temperature) = format_temp(&zone.join("temp")) else { continue };
+            let name = zone.file_name().unwrap_or_default().to_string_lossy().to_string();
+            let zone_type = read_trimmed(&zone.join("type")).unwrap_or_else(|| "unknown".to_string());
+            items.push(DiscoveredItem::new(name, zone_type).with_detail("temperature", temperature));
+        }
+
+        for input in hwmon::temp_inputs(&sysroot::path(hwmon::HWMON_PATH)) {
+            let Some(temperature) = format_temp(&input.path) else { continue };
+            let label = match &input.label {
+                Some(label) => format!("{} {}", input.device, label),
+                None => format!("{} temp{}", input.device, input.index),
+            };
+            items.push(DiscoveredItem::new(input.path.to_string_lossy(), label).with_detail("temperature", temperature));
+        }
+
+        Ok(items)
+    }
+}
+
 impl Sensor for ThermalSensor {
     type Error = SensorError;
     
     fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
         let temperature = self.read_temperature()?;
+        self.record_sample(temperature);
+        let slope = self.trend_slope();
         
         // Get appropriate thermal icon based on temperature
         let icon = if temperature < 50.0 {
@@ -299,12 +452,42 @@ impl Sensor for ThermalSensor {
         let thresholds_line = format::key_value("Thresholds", &format!("⚠️ {:.0}°C / 🔴 {:.0}°C", 
             self.warning_threshold, self.critical_threshold), &self.config);
         
-        let tooltip = format!("{}\n{}\n{}", zone_line, temp_line, thresholds_line);
+        let mut tooltip = format!("{}\n{}\n{}", zone_line, temp_line, thresholds_line);
+        
+        if let Some(slope) = slope {
+            let arrow = Self::get_trend_arrow(slope, self.rapid_rise_per_min);
+            let trend_line = format::key_value("Trend", &format!("{} {:+.1}°C/min", arrow, slope), &self.config);
+            tooltip.push_str(&format!("\n{}", trend_line));
+            
+            // Only meaningful while heating up and still below critical
+            if slope > 0.0 && temperature < self.critical_threshold {
+                let minutes = (self.critical_threshold - temperature) / slope;
+                let estimate = if minutes < 1.0 {
+                    format!("{:.0}s", minutes * 60.0)
+                } else {
+                    format!("{:.0} min", minutes)
+                };
+                if minutes < 60.0 {
+                    let critical_line = format::key_value("Critical in", &format!("~{} at current slope", estimate), &self.config);
+                    tooltip.push_str(&format!("\n{}", critical_line));
+                }
+            }
+        }
+        
+        if self.config.visuals.sparklines && self.history.len() > 1 {
+            let values: Vec<f64> = self.history.iter().map(|(_, t)| *t).collect();
+            let sparkline = format::create_sparkline(&values, self.config.visuals.sparkline_style);
+            if !sparkline.is_empty() {
+                let colored_sparkline = format::colored_sparkline(&sparkline, self.config.sparkline_color.as_deref());
+                let sparkline_line = format::key_value("History", &colored_sparkline, &self.config);
+                tooltip.push_str(&format!("\n{}", sparkline_line));
+            }
+        }
         
         // Calculate percentage (0°C = 0%, critical = 100%)
         let percentage = ((temperature / self.critical_threshold) * 100.0).min(100.0) as u8;
         
-        Ok(format::themed_output(
+        let mut output = format::themed_output(
             text,
             Some(tooltip),
             Some(percentage),
@@ -312,7 +495,28 @@ impl Sensor for ThermalSensor {
             self.warning_threshold,
             self.critical_threshold,
             &self.theme,
-        ))
+        );
+        
+        // Flag rapid heating, unless already critical
+        if slope.is_some_and(|s| s >= self.rapid_rise_per_min) && temperature < self.critical_threshold {
+            output.class = Some(self.theme.rising.clone());
+        }
+        
+        Ok(output)
+    }
+    
+    fn read_typed(&mut self) -> Result<(WaybarOutput, Reading), Self::Error> {
+        let output = self.read()?;
+        let (_, temperature) = *self.history.last()
+            .ok_or_else(|| SensorError::invalid_data("No temperature sample recorded"))?;
+        
+        let mut reading = Reading::new(temperature, "°C")
+            .with_extra("warning", self.warning_threshold)
+            .with_extra("critical", self.critical_threshold);
+        if let Some(slope) = self.trend_slope() {
+            reading = reading.with_extra("slope_per_min", slope);
+        }
+        Ok((output, reading))
     }
     
     fn name(&self) -> &str {
@@ -320,8 +524,105 @@ impl Sensor for ThermalSensor {
     }
     
     fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        if let Some(rapid) = config.get_custom("rapid_rise_per_min").and_then(|v| v.as_f64()) {
+            self.rapid_rise_per_min = rapid;
+        }
+        if let Some(window) = config.get_custom("trend_window").and_then(|v| v.as_u64()) {
+            self.trend_window = (window as usize).max(2);
+        }
         self.theme = config.theme.clone();
         self.config = config;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_temperature_slope() {
+        let start = Instant::now();
+        let history: Vec<(Instant, f64)> = (0..5)
+            .map(|i| (start + Duration::from_secs(30 * i), 50.0 + i as f64))
+            .collect();
+
+        // +1°C every 30s
+        let slope = temperature_slope(&history).unwrap();
+        assert!((slope - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_identity_resolves_after_renumbering() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let old = root.join("hwmon").join("hwmon2");
+        fs::create_dir_all(&old).unwrap();
+        fs::write(old.join("name"), "k10temp\n").unwrap();
+        fs::write(old.join("temp1_input"), "45000\n").unwrap();
+        fs::write(old.join("temp1_label"), "Tctl\n").unwrap();
+
+        let old_input = old.join("temp1_input");
+        let identity = SensorIdentity::capture(&old_input.to_string_lossy(), &old_input).unwrap();
+        assert_eq!(identity, SensorIdentity::Hwmon {
+            device: "k10temp".to_string(),
+            label: Some("Tctl".to_string()),
+            input: "temp1_input".to_string(),
+        });
+
+        // Driver reload: new hwmon number and the label moved to another input
+        fs::remove_dir_all(&old).unwrap();
+        let new = root.join("hwmon").join("hwmon5");
+        fs::create_dir_all(&new).unwrap();
+        fs::write(new.join("name"), "k10temp\n").unwrap();
+        fs::write(new.join("temp3_input"), "46000\n").unwrap();
+        fs::write(new.join("temp3_label"), "Tctl\n").unwrap();
+
+        let (_, path) = identity.resolve(root).unwrap();
+        assert_eq!(path, new.join("temp3_input"));
+
+        let zone = root.join("thermal").join("thermal_zone7");
+        fs::create_dir_all(&zone).unwrap();
+        fs::write(zone.join("type"), "x86_pkg_temp\n").unwrap();
+        fs::write(zone.join("temp"), "50000\n").unwrap();
+        let identity = SensorIdentity::ThermalZone { zone_type: "x86_pkg_temp".to_string() };
+        assert_eq!(identity.resolve(root).unwrap().0, "thermal_zone7");
+
+    }
+
+    #[test]
+    fn test_temperature_slope_needs_elapsed_time() {
+        let now = Instant::now();
+        assert!(temperature_slope(&[]).is_none());
+        assert!(temperature_slope(&[(now, 40.0)]).is_none());
+        assert!(temperature_slope(&[(now, 40.0), (now, 45.0)]).is_none());
+    }
+
+    #[test]
+    fn test_rising_class_uses_theme_and_trend_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("temp1_input");
+        fs::write(&input, "75000\n").unwrap();
+
+        let mut sensor = ThermalSensor::new(Some(input.to_string_lossy().into_owned()), 90.0, 100.0).unwrap();
+        let config = SensorConfig::default()
+            .with_theme(Theme::default().with_rising("heating"))
+            .with_custom("trend_window", serde_json::json!(3));
+        sensor.configure(config).unwrap();
+        assert_eq!(sensor.trend_window, 3);
+
+        // Cooling for a long time, then climbing fast over the last samples
+        let now = Instant::now();
+        sensor.history = [80.0, 70.0, 60.0, 50.0, 40.0, 55.0, 70.0]
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (now - Duration::from_secs(60 * (7 - i as u64)), *t))
+            .collect();
+        assert!(temperature_slope(&sensor.history).unwrap() < 0.0);
+        assert!(sensor.trend_slope().unwrap() > 5.0);
+
+        let output = sensor.read().unwrap();
+        assert_eq!(output.class.as_deref(), Some("heating"));
+    }
+}
@@ -0,0 +1,9 @@
+//! Package update sensor for waysensor-rs.
+//!
+//! Counts the updates pending from the system package manager (pacman, apt
+//! or dnf) and Flatpak. Checks are slow and may hit the network, so their
+//! results are cached on disk and refreshed at a configurable interval.
+
+pub mod updates;
+
+pub use updates::{UpdateBackend, UpdatesSensor};
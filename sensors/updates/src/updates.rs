@@ -0,0 +1,447 @@
+//! Pending package updates.
+//!
+//! Each backend lists the packages it would upgrade without changing the
+//! system or needing root: pacman through `checkupdates` (pacman-contrib),
+//! which syncs a temporary copy of the databases; apt from its existing
+//! lists, refreshed by the system's apt timer; dnf with `check-update`;
+//! Flatpak with `remote-ls --updates`. These can take tens of seconds, so
+//! checks run on a worker thread while the last result stays on the bar.
+//! Results are written to `~/.cache/waysensor-rs/updates.json` so several
+//! bars, and restarts, share one check per interval.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::mpsc::{self, RecvTimeoutError, TryRecvError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use waysensor_rs_core::{format, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+/// Default time between two checks in seconds.
+pub const DEFAULT_CHECK_INTERVAL: u64 = 3600;
+
+/// Seconds before a failed check (often no network yet) is tried again.
+const RETRY_INTERVAL: u64 = 300;
+
+/// How long the first read waits for a check when there is no earlier result.
+const FIRST_CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Packages listed per backend in the tooltip.
+const TOOLTIP_PACKAGES: usize = 10;
+
+/// Where pending updates come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UpdateBackend {
+    Pacman,
+    Apt,
+    Dnf,
+    Flatpak,
+}
+
+impl std::str::FromStr for UpdateBackend {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "pacman" => Ok(Self::Pacman),
+            "apt" => Ok(Self::Apt),
+            "dnf" => Ok(Self::Dnf),
+            "flatpak" => Ok(Self::Flatpak),
+            _ => Err(SensorError::config_with_value(
+                "Invalid update backend. Valid options: pacman, apt, dnf, flatpak",
+                s,
+            )),
+        }
+    }
+}
+
+impl UpdateBackend {
+    pub const ALL: [Self; 4] = [Self::Pacman, Self::Apt, Self::Dnf, Self::Flatpak];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pacman => "pacman",
+            Self::Apt => "apt",
+            Self::Dnf => "dnf",
+            Self::Flatpak => "flatpak",
+        }
+    }
+
+    /// Backends whose package manager is installed.
+    pub fn detect() -> Vec<Self> {
+        Self::ALL.into_iter().filter(|backend| installed(backend.name())).collect()
+    }
+
+    /// Command listing the pending updates, and the exit codes that mean
+    /// success: `checkupdates` exits 2 and `dnf check-update` 0 when there
+    /// is nothing to update, `dnf` 100 when there is.
+    fn command(self) -> (&'static str, &'static [&'static str], &'static [i32]) {
+        match self {
+            Self::Pacman => ("checkupdates", &[], &[0, 2]),
+            Self::Apt => ("apt", &["list", "--upgradable"], &[0]),
+            Self::Dnf => ("dnf", &["-q", "check-update"], &[0, 100]),
+            Self::Flatpak => ("flatpak", &["remote-ls", "--updates", "--columns=application"], &[0]),
+        }
+    }
+
+    /// Names of the packages with an update pending.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tool is missing or the check fails, e.g.
+    /// without network.
+    pub fn check(self) -> Result<Vec<String>, SensorError> {
+        let (program, args, success) = self.command();
+        let output = Command::new(program)
+            .args(args)
+            .env("LC_ALL", "C")
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => SensorError::unavailable(format!("{} not installed", program)),
+                _ => SensorError::Io(e),
+            })?;
+        if !output.status.code().is_some_and(|code| success.contains(&code)) {
+            return Err(SensorError::unavailable(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(self.parse(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn parse(self, output: &str) -> Vec<String> {
+        let lines = output.lines().map(str::trim).filter(|line| !line.is_empty());
+        match self {
+            // linux 6.9.1.arch1-1 -> 6.9.2.arch1-1
+            Self::Pacman => lines.filter_map(|line| line.split_whitespace().next()).map(str::to_string).collect(),
+            // curl/stable-security 7.88.1-10+deb12u6 amd64 [upgradable from: 7.88.1-10+deb12u5]
+            Self::Apt => lines
+                .filter(|line| line.contains("[upgradable from"))
+                .filter_map(|line| line.split('/').next())
+                .map(str::to_string)
+                .collect(),
+            // kernel.x86_64    6.9.2-200.fc40    updates
+            // Obsoleted packages follow a heading and are counted already
+            Self::Dnf => lines
+                .take_while(|line| !line.starts_with("Obsoleting"))
+                .filter_map(|line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+                    [package, _version, _repo] => Some(package.rsplit_once('.').map_or(package, |(name, _)| name)),
+                    _ => None,
+                })
+                .map(str::to_string)
+                .collect(),
+            // org.mozilla.firefox
+            Self::Flatpak => lines.map(str::to_string).collect(),
+        }
+    }
+}
+
+/// Whether `program` is on the `PATH`.
+fn installed(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Result of one check of every backend.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct UpdateCache {
+    /// Unix time of the check
+    checked_at: u64,
+    /// Pending packages by backend name, as of the last successful check
+    updates: BTreeMap<String, Vec<String>>,
+    /// Backends whose latest check failed, with the error
+    failed: BTreeMap<String, String>,
+}
+
+impl UpdateCache {
+    fn total(&self) -> usize {
+        self.updates.values().map(Vec::len).sum()
+    }
+
+    /// Check every backend now. A backend whose check fails keeps its
+    /// packages from `previous`.
+    fn check(backends: &[UpdateBackend], mut previous: Self) -> Self {
+        let mut cache = Self { checked_at: now(), ..Self::default() };
+        for backend in backends {
+            let name = backend.name().to_string();
+            match backend.check() {
+                Ok(packages) => {
+                    cache.updates.insert(name, packages);
+                }
+                Err(e) => {
+                    if let Some(packages) = previous.updates.remove(&name) {
+                        cache.updates.insert(name.clone(), packages);
+                    }
+                    cache.failed.insert(name, e.to_string());
+                }
+            }
+        }
+        cache
+    }
+
+    /// Whether the cache holds a result for exactly these backends.
+    fn covers(&self, backends: &[UpdateBackend]) -> bool {
+        let names: Vec<&str> = backends.iter().map(|backend| backend.name()).collect();
+        names.iter().all(|name| self.updates.contains_key(*name) || self.failed.contains_key(*name))
+            && self.updates.keys().chain(self.failed.keys()).all(|name| names.contains(&name.as_str()))
+    }
+}
+
+/// Cache file, e.g. `~/.cache/waysensor-rs/updates.json`.
+fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("waysensor-rs").join("updates.json"))
+}
+
+/// Sensor counting pending updates over several backends.
+#[derive(Debug)]
+pub struct UpdatesSensor {
+    name: String,
+    config: SensorConfig,
+    backends: Vec<UpdateBackend>,
+    /// Seconds between two checks
+    check_interval: u64,
+    /// Share results through the cache file
+    persist: bool,
+    cache: UpdateCache,
+    /// Check running on a worker thread
+    pending: Option<mpsc::Receiver<UpdateCache>>,
+}
+
+impl UpdatesSensor {
+    /// Create a sensor for `backends`, or for every installed package
+    /// manager when empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no supported package manager is installed.
+    pub fn new(backends: Vec<UpdateBackend>) -> Result<Self, SensorError> {
+        let mut backends = if backends.is_empty() { UpdateBackend::detect() } else { backends };
+        backends.sort();
+        backends.dedup();
+        if backends.is_empty() {
+            return Err(SensorError::unavailable("No pacman, apt, dnf or flatpak found"));
+        }
+        Ok(Self {
+            name: "updates".to_string(),
+            config: SensorConfig::default(),
+            backends,
+            check_interval: DEFAULT_CHECK_INTERVAL,
+            persist: true,
+            cache: UpdateCache::default(),
+            pending: None,
+        })
+    }
+
+    /// Check every backend now and wait for the result, whatever the age
+    /// of the last check. A backend whose check fails keeps its previous
+    /// packages.
+    pub fn refresh(&mut self) {
+        let cache = UpdateCache::check(&self.backends, self.cache.clone());
+        self.finish(cache);
+    }
+
+    fn start_check(&mut self) {
+        let (tx, rx) = mpsc::channel();
+        let (backends, previous) = (self.backends.clone(), self.cache.clone());
+        let spawned = std::thread::Builder::new()
+            .name("updates-check".to_string())
+            .spawn(move || {
+                // The receiver is gone if the sensor was reconfigured
+                let _ = tx.send(UpdateCache::check(&backends, previous));
+            });
+        if spawned.is_ok() {
+            self.pending = Some(rx);
+        }
+    }
+
+    /// Take the running check's result if it is done, or once it is (up to
+    /// a timeout) when `wait` is set.
+    fn collect(&mut self, wait: bool) {
+        let Some(rx) = self.pending.take() else {
+            return;
+        };
+        let received = if wait {
+            rx.recv_timeout(FIRST_CHECK_TIMEOUT).map_err(|e| e == RecvTimeoutError::Disconnected)
+        } else {
+            rx.try_recv().map_err(|e| e == TryRecvError::Disconnected)
+        };
+        match received {
+            Ok(cache) => self.finish(cache),
+            Err(false) => self.pending = Some(rx),
+            // The worker died; the next read starts over
+            Err(true) => {}
+        }
+    }
+
+    fn finish(&mut self, cache: UpdateCache) {
+        if self.persist {
+            // A cache that cannot be written only costs extra checks
+            if let Some(path) = cache_path() {
+                let _ = fs::create_dir_all(path.parent().unwrap_or(&path))
+                    .and_then(|_| fs::write(&path, serde_json::to_string(&cache).unwrap_or_default()));
+            }
+        }
+        self.cache = cache;
+    }
+
+    /// The latest result, starting a check once it is older than the check
+    /// interval, or a few minutes after a failure. Another instance's newer
+    /// result is picked up from the cache file. Only the very first read,
+    /// with nothing to show yet, waits for the check.
+    fn updates(&mut self) -> &UpdateCache {
+        self.collect(false);
+        if self.persist {
+            let stored = cache_path()
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|json| serde_json::from_str::<UpdateCache>(&json).ok())
+                .filter(|stored| stored.covers(&self.backends) && stored.checked_at > self.cache.checked_at);
+            if let Some(stored) = stored {
+                self.cache = stored;
+            }
+        }
+        let interval = if self.cache.failed.is_empty() {
+            self.check_interval
+        } else {
+            self.check_interval.min(RETRY_INTERVAL)
+        };
+        let covered = self.cache.covers(&self.backends);
+        if self.pending.is_none() && (!covered || now().saturating_sub(self.cache.checked_at) >= interval) {
+            self.start_check();
+        }
+        if !covered {
+            self.collect(true);
+        }
+        &self.cache
+    }
+
+    fn build_tooltip(&self, cache: &UpdateCache) -> String {
+        let mut lines = Vec::new();
+        for (backend, packages) in &cache.updates {
+            lines.push(format::key_value(backend, &packages.len().to_string(), &self.config));
+            for package in packages.iter().take(TOOLTIP_PACKAGES) {
+                lines.push(format::value_only(&format!("  {}", package), &self.config));
+            }
+            if packages.len() > TOOLTIP_PACKAGES {
+                lines.push(format::value_only(
+                    &format!("  and {} more", packages.len() - TOOLTIP_PACKAGES),
+                    &self.config,
+                ));
+            }
+        }
+        for (backend, error) in &cache.failed {
+            lines.push(format::key_value(backend, &format!("check failed ({})", error), &self.config));
+        }
+        let age = now().saturating_sub(cache.checked_at) / 60;
+        lines.push(format::key_value("Checked", &format!("{} min ago", age), &self.config));
+        lines.join("\n")
+    }
+}
+
+impl Discoverable for UpdatesSensor {
+    const KIND: &'static str = "update backends";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        Ok(UpdateBackend::detect()
+            .into_iter()
+            .map(|backend| {
+                let (program, args, _) = backend.command();
+                DiscoveredItem::new(backend.name(), format!("{} {}", program, args.join(" ")).trim_end().to_string())
+            })
+            .collect())
+    }
+}
+
+impl Sensor for UpdatesSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let cache = self.updates().clone();
+        if !cache.covers(&self.backends) {
+            return Err(SensorError::temporarily_unavailable("Still checking for updates"));
+        }
+        if cache.updates.is_empty() {
+            let (backend, error) = cache.failed.iter().next()
+                .map_or(("updates", "no backend"), |(backend, error)| (backend.as_str(), error.as_str()));
+            return Err(SensorError::unavailable(format!("{} check failed: {}", backend, error)));
+        }
+
+        let total = cache.total();
+        let threshold = self.config.get_custom("warning_threshold").and_then(|v| v.as_u64()).unwrap_or(25) as usize;
+        let class = if total >= threshold { &self.config.theme.warning } else { &self.config.theme.normal };
+        // Nothing to show when up to date, unless configured otherwise
+        let show_zero = self.config.get_custom("show_zero").and_then(|v| v.as_bool()).unwrap_or(false);
+        let text = if total == 0 && !show_zero {
+            String::new()
+        } else {
+            format::with_icon_and_colors(&total.to_string(), &self.config.icons.updates, &self.config)
+        };
+
+        Ok(WaybarOutput {
+            text,
+            tooltip: Some(self.build_tooltip(&cache)),
+            class: Some(class.clone()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        if let Some(interval) = config.get_custom("check_interval").and_then(|v| v.as_u64()) {
+            // At least a minute: checks sync package databases
+            self.check_interval = interval.max(60);
+        }
+        if let Some(cache) = config.get_custom("cache").and_then(|v| v.as_bool()) {
+            self.persist = cache;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        // A failed check may be the network; a missing tool is not
+        match self.backends.iter().map(|backend| backend.command().0).find(|program| !installed(program)) {
+            Some(program) => Err(SensorError::unavailable(format!("{} not installed", program))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_list_parsing() {
+        let pacman = "linux 6.9.1.arch1-1 -> 6.9.2.arch1-1\nmesa 1:24.1.0-1 -> 1:24.1.1-1\n";
+        assert_eq!(UpdateBackend::Pacman.parse(pacman), ["linux", "mesa"]);
+
+        let apt = "Listing...\ncurl/stable-security 7.88.1-10+deb12u6 amd64 [upgradable from: 7.88.1-10+deb12u5]\n";
+        assert_eq!(UpdateBackend::Apt.parse(apt), ["curl"]);
+
+        let dnf = "\nkernel.x86_64    6.9.2-200.fc40    updates\nmesa-dri-drivers.i686    24.1.1-1.fc40    updates\n\
+                   Obsoleting Packages\ngrub2-tools.x86_64    1:2.06-121.fc40    updates\n";
+        assert_eq!(UpdateBackend::Dnf.parse(dnf), ["kernel", "mesa-dri-drivers"]);
+
+        assert_eq!(UpdateBackend::Flatpak.parse("org.mozilla.firefox\n\n"), ["org.mozilla.firefox"]);
+
+        let mut cache = UpdateCache::default();
+        cache.updates.insert("pacman".to_string(), vec!["linux".to_string()]);
+        cache.failed.insert("flatpak".to_string(), "offline".to_string());
+        assert!(cache.covers(&[UpdateBackend::Pacman, UpdateBackend::Flatpak]));
+        assert!(!cache.covers(&[UpdateBackend::Pacman]));
+        assert_eq!(cache.total(), 1);
+    }
+}
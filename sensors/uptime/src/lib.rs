@@ -0,0 +1,8 @@
+//! Uptime sensor for waysensor-rs.
+//!
+//! Reports how long the system has been up and which kernel it runs, and
+//! flags when a reboot is needed to use a newer installed kernel.
+
+pub mod uptime;
+
+pub use uptime::{RebootReason, UptimeSensor};
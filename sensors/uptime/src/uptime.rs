@@ -0,0 +1,270 @@
+//! Uptime, running kernel and pending reboots.
+//!
+//! A kernel upgrade only takes effect on reboot. Distributions say so in
+//! different ways: Debian and Ubuntu create `/run/reboot-required`, while
+//! on Arch the running kernel's modules directory disappears as soon as
+//! the package is upgraded. Elsewhere, a modules directory of the same
+//! kernel flavor with a higher version than the running one means a newer
+//! kernel waits to be booted.
+
+use chrono::{DateTime, Local};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use waysensor_rs_core::{format, sysroot, DiscoveredItem, Discoverable, Sensor, SensorConfig, SensorError, WaybarOutput};
+
+const MODULES_PATH: &str = "/lib/modules";
+const REBOOT_REQUIRED_PATH: &str = "/run/reboot-required";
+
+/// Why a reboot is needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RebootReason {
+    /// A kernel newer than the running one is installed
+    NewerKernel(String),
+    /// The running kernel's modules were removed by an upgrade
+    KernelRemoved,
+    /// The package manager asked for one (`/run/reboot-required`)
+    Requested,
+}
+
+impl std::fmt::Display for RebootReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NewerKernel(release) => write!(f, "kernel {} installed", release),
+            Self::KernelRemoved => write!(f, "running kernel was upgraded"),
+            Self::Requested => write!(f, "requested by package upgrades"),
+        }
+    }
+}
+
+/// Compact uptime for the bar: `3d 4h`, `4h 12m` or `12m`.
+fn format_short(uptime: Duration) -> String {
+    let minutes = uptime.as_secs() / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {}m", hours, minutes),
+        _ => format!("{}d {}h", days, hours),
+    }
+}
+
+/// Full uptime for the tooltip: `3 days, 4 hours, 12 minutes`.
+fn format_long(uptime: Duration) -> String {
+    let minutes = uptime.as_secs() / 60;
+    let parts = [(minutes / 1440, "day"), (minutes / 60 % 24, "hour"), (minutes % 60, "minute")];
+    let text: Vec<String> = parts
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{} {}{}", value, unit, if *value == 1 { "" } else { "s" }))
+        .collect();
+    if text.is_empty() { "less than a minute".to_string() } else { text.join(", ") }
+}
+
+/// Numbers of a kernel release in order: 6.9.2-arch1-1 gives 6, 9, 2, 1, 1.
+fn version_numbers(release: &str) -> Vec<u64> {
+    release.split(|c: char| !c.is_ascii_digit()).filter_map(|part| part.parse().ok()).collect()
+}
+
+/// Release with its numbers removed, identifying the kernel flavor: the
+/// same for every `linux` (Arch) or `-amd64` (Debian) build, different for
+/// `linux-lts` or `-rt-amd64`.
+fn flavor(release: &str) -> String {
+    release.chars().filter(|c| !c.is_ascii_digit()).collect()
+}
+
+fn compare_releases(a: &str, b: &str) -> Ordering {
+    version_numbers(a).cmp(&version_numbers(b))
+}
+
+/// Kernel releases with modules installed under `modules`, oldest first.
+/// Directories left behind without `modules.dep` are not installed kernels.
+fn installed_kernels_in(modules: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(modules) else {
+        return Vec::new();
+    };
+    let mut kernels: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().join("modules.dep").exists())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    kernels.sort_by(|a, b| compare_releases(a, b));
+    kernels
+}
+
+/// Why `running` needs a reboot, judging from the kernels in `modules`.
+fn kernel_reboot_reason(running: &str, installed: &[String]) -> Option<RebootReason> {
+    // No modules at all: a container, or a kernel built without modules
+    if installed.is_empty() {
+        return None;
+    }
+    let newest = installed.iter()
+        .filter(|release| flavor(release) == flavor(running))
+        .max_by(|a, b| compare_releases(a, b));
+    match newest {
+        Some(newest) if compare_releases(newest, running) == Ordering::Greater => {
+            Some(RebootReason::NewerKernel(newest.clone()))
+        }
+        _ if !installed.iter().any(|release| release == running) => Some(RebootReason::KernelRemoved),
+        _ => None,
+    }
+}
+
+fn read_uptime() -> Result<Duration, SensorError> {
+    parse_uptime(&fs::read_to_string(sysroot::path("/proc/uptime"))?)
+}
+
+/// Parse the contents of `/proc/uptime`.
+fn parse_uptime(content: &str) -> Result<Duration, SensorError> {
+    content.split_whitespace().next()
+        .and_then(|seconds| seconds.parse().ok())
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| SensorError::parse(format!("Invalid /proc/uptime: {}", content.trim())))
+}
+
+fn running_kernel() -> Result<String, SensorError> {
+    Ok(fs::read_to_string(sysroot::path("/proc/sys/kernel/osrelease"))?.trim().to_string())
+}
+
+/// Sensor for uptime and pending reboots.
+#[derive(Debug)]
+pub struct UptimeSensor {
+    name: String,
+    config: SensorConfig,
+}
+
+impl Default for UptimeSensor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UptimeSensor {
+    pub fn new() -> Self {
+        Self {
+            name: "uptime".to_string(),
+            config: SensorConfig::default(),
+        }
+    }
+
+    /// Why a reboot is needed, if it is.
+    pub fn reboot_reason(&self) -> Result<Option<RebootReason>, SensorError> {
+        let running = running_kernel()?;
+        let reason = kernel_reboot_reason(&running, &installed_kernels_in(&sysroot::path(MODULES_PATH)));
+        Ok(reason.or_else(|| sysroot::path(REBOOT_REQUIRED_PATH).exists().then_some(RebootReason::Requested)))
+    }
+
+    fn build_tooltip(&self, uptime: Duration, kernel: &str, reboot: Option<&RebootReason>) -> String {
+        let mut lines = vec![format::key_value("Uptime", &format_long(uptime), &self.config)];
+        if let Ok(uptime) = chrono::Duration::from_std(uptime) {
+            let booted: DateTime<Local> = Local::now() - uptime;
+            lines.push(format::key_value("Booted", &booted.format("%Y-%m-%d %H:%M").to_string(), &self.config));
+        }
+        lines.push(format::key_value("Kernel", kernel, &self.config));
+        if let Some(reason) = reboot {
+            lines.push(format::key_value("Reboot Required", &reason.to_string(), &self.config));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Discoverable for UptimeSensor {
+    const KIND: &'static str = "installed kernels";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        let running = running_kernel()?;
+        Ok(installed_kernels_in(&sysroot::path(MODULES_PATH))
+            .into_iter()
+            .map(|release| {
+                let is_running = release == running;
+                let item = DiscoveredItem::new(release.clone(), format!("Linux {}", release));
+                if is_running { item.with_detail("running", "yes") } else { item }
+            })
+            .collect())
+    }
+}
+
+impl Sensor for UptimeSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let uptime = read_uptime()?;
+        let kernel = running_kernel()?;
+        let reboot = self.reboot_reason()?;
+
+        let mut status = format_short(uptime);
+        if self.config.get_custom("show_kernel").and_then(|v| v.as_bool()).unwrap_or(false) {
+            status = format!("{} {}", status, kernel);
+        }
+        let (icon, class) = match reboot {
+            Some(_) => (&self.config.icons.reboot_required, &self.config.theme.warning),
+            None => (&self.config.icons.uptime, &self.config.theme.normal),
+        };
+
+        Ok(WaybarOutput {
+            text: format::with_icon_and_colors(&status, icon, &self.config),
+            tooltip: Some(self.build_tooltip(uptime, &kernel, reboot.as_ref())),
+            class: Some(class.clone()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        read_uptime().map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uptime_parsing() {
+        assert_eq!(parse_uptime("350735.47 234388.90\n").unwrap(), Duration::from_secs_f64(350735.47));
+        for content in ["", "abc 1.0", "inf 1.0", "NaN 1.0", "-5.0 1.0"] {
+            assert!(parse_uptime(content).is_err(), "{:?}", content);
+        }
+    }
+
+    #[test]
+    fn test_uptime_and_reboot_detection() {
+        assert_eq!(format_short(Duration::from_secs(45 * 60)), "45m");
+        assert_eq!(format_short(Duration::from_secs(4 * 3600 + 12 * 60)), "4h 12m");
+        assert_eq!(format_short(Duration::from_secs(3 * 86400 + 4 * 3600 + 59)), "3d 4h");
+        assert_eq!(format_long(Duration::from_secs(86400 + 2 * 3600 + 60)), "1 day, 2 hours, 1 minute");
+
+        let dir = tempfile::tempdir().unwrap();
+        let modules = dir.path();
+        for release in ["6.9.2-arch1-1", "6.10.1-arch1-1", "6.6.30-1-lts", "6.11.0-arch1-1"] {
+            fs::create_dir_all(modules.join(release)).unwrap();
+        }
+        // The last one is a leftover without modules
+        for release in ["6.9.2-arch1-1", "6.10.1-arch1-1", "6.6.30-1-lts"] {
+            fs::write(modules.join(release).join("modules.dep"), "").unwrap();
+        }
+        let installed = installed_kernels_in(modules);
+        assert_eq!(installed, ["6.6.30-1-lts", "6.9.2-arch1-1", "6.10.1-arch1-1"]);
+
+        assert_eq!(
+            kernel_reboot_reason("6.9.2-arch1-1", &installed),
+            Some(RebootReason::NewerKernel("6.10.1-arch1-1".to_string()))
+        );
+        assert_eq!(kernel_reboot_reason("6.10.1-arch1-1", &installed), None);
+        assert_eq!(kernel_reboot_reason("6.6.30-1-lts", &installed), None);
+        assert_eq!(kernel_reboot_reason("6.8.9-arch1-2", &installed[..1]), Some(RebootReason::KernelRemoved));
+        assert_eq!(kernel_reboot_reason("6.18.44-fc-v130", &[]), None);
+    }
+}
@@ -0,0 +1,8 @@
+//! Weather sensor for waysensor-rs.
+//!
+//! Shows the current conditions from Open-Meteo or wttr.in, fetched in the
+//! background and cached so the bar keeps its last reading while offline.
+
+pub mod weather;
+
+pub use weather::{Condition, Conditions, Location, WeatherProvider, WeatherSensor};
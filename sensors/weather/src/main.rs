@@ -0,0 +1,236 @@
+//! waysensor-rs-weather: current weather binary for Waybar.
+//!
+//! The output interval only re-reads the last result; the weather is
+//! fetched at the "refresh_interval" setting. Bind a fetch to the module's
+//! click event with `"on-click": "waysensor-rs-weather --refresh"`.
+
+use clap::Parser;
+use waysensor_rs_core::{discovery, schedule, session_lock, GlobalConfig, IconStyle, UnitSystem, Sensor, SensorConfig};
+use waysensor_rs_weather::{Location, WeatherProvider, WeatherSensor};
+use std::io::{self, Write};
+use std::process;
+use std::time::Duration;
+
+/// Command-line arguments for the weather sensor.
+#[derive(Parser)]
+#[command(name = "waysensor-rs-weather")]
+#[command(about = "Weather sensor for waysensor-rs")]
+#[command(version)]
+#[command(author)]
+struct Args {
+    /// Update interval in milliseconds (minimum 100ms)
+    #[arg(short, long, default_value = "60000", value_parser = validate_interval)]
+    interval: u64,
+
+    /// Weather service: open-meteo or wttr.in (default: the "provider" setting, else open-meteo)
+    #[arg(short, long)]
+    provider: Option<WeatherProvider>,
+
+    /// Place name to report the weather for (default: the "location" setting, or its coordinates)
+    #[arg(long)]
+    location: Option<String>,
+
+    /// Units: metric or imperial (default: the global "units" setting)
+    #[arg(short, long)]
+    units: Option<UnitSystem>,
+
+    /// Fetch the weather now, whatever the age of the last result, and exit
+    #[arg(long)]
+    refresh: bool,
+
+    /// One-shot mode (output once and exit)
+    #[arg(short, long)]
+    once: bool,
+
+    /// List the weather providers and exit
+    #[arg(short, long)]
+    list: bool,
+
+    /// Print build information (git hash, target, core version) as JSON and exit
+    #[arg(long)]
+    build_info: bool,
+
+    /// Print the --list output as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Icon style (nerdfont, fontawesome, ascii, none)
+    #[arg(long)]
+    icon_style: Option<IconStyle>,
+
+    /// Icon color (hex format like "#7aa2f7")
+    #[arg(long)]
+    icon_color: Option<String>,
+
+    /// Text color (hex format like "#c0caf5")
+    #[arg(long)]
+    text_color: Option<String>,
+
+    /// Tooltip label color (hex format like "#bb9af7")
+    #[arg(long)]
+    tooltip_label_color: Option<String>,
+
+    /// Tooltip value color (hex format like "#9ece6a")
+    #[arg(long)]
+    tooltip_value_color: Option<String>,
+
+    /// Verify curl is installed and exit
+    #[arg(long)]
+    check: bool,
+
+    /// Generate example config file and exit
+    #[arg(long)]
+    generate_config: bool,
+}
+
+/// Validate that the interval is at least 100ms.
+fn validate_interval(s: &str) -> Result<u64, String> {
+    let interval = s.parse::<u64>()
+        .map_err(|_| "Interval must be a positive integer".to_owned())?;
+    
+    if interval < SensorConfig::MIN_UPDATE_INTERVAL {
+        return Err(format!(
+            "Interval must be at least {}ms", 
+            SensorConfig::MIN_UPDATE_INTERVAL
+        ));
+    }
+    
+    Ok(interval)
+}
+
+/// Main entry point for the weather sensor.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    
+    if args.build_info {
+        println!("{}", waysensor_rs_core::build_info!().to_json());
+        return Ok(());
+    }
+    
+    if args.list {
+        if let Err(e) = discovery::print::<WeatherSensor>(args.json) {
+            eprintln!("Error listing weather providers: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Handle config generation
+    if args.generate_config {
+        if let Some(config_path) = GlobalConfig::default_config_path() {
+            GlobalConfig::save_example_config_to_file(&config_path)?;
+            println!("Generated example config at: {}", config_path.display());
+            println!("\nYou can now edit this file to customize your default colors and settings.");
+        } else {
+            eprintln!("Could not determine config directory");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Load global configuration and apply command line overrides
+    let global_config = GlobalConfig::load().unwrap_or_default();
+    let mut config = global_config.to_sensor_config_for("weather")
+        .with_update_interval(Duration::from_millis(args.interval))
+        .apply_color_overrides(
+            args.icon_color,
+            args.text_color,
+            args.tooltip_label_color,
+            args.tooltip_value_color,
+        );
+    
+    // Override icon style only if explicitly provided
+    if let Some(icon_style) = args.icon_style {
+        config = config.with_icon_style(icon_style);
+    }
+    if let Some(units) = args.units {
+        config = config.with_units(units);
+    }
+    
+    // The provider and location from the command line, else from the config
+    let provider = match args.provider {
+        Some(provider) => provider,
+        None => match config.get_custom("provider").and_then(|v| v.as_str()) {
+            Some(name) => name.parse()?,
+            None => WeatherProvider::default(),
+        },
+    };
+    let coordinates = config.get_custom("latitude").and_then(|v| v.as_f64())
+        .zip(config.get_custom("longitude").and_then(|v| v.as_f64()));
+    let location = match (args.location, coordinates) {
+        (Some(place), _) => Location::Place(place),
+        (None, Some((latitude, longitude))) => Location::Coordinates { latitude, longitude },
+        (None, None) => config.get_custom("location").and_then(|v| v.as_str())
+            .map_or(Location::Auto, |place| Location::Place(place.to_string())),
+    };
+    let mut weather_sensor = match WeatherSensor::new(provider, location) {
+        Ok(sensor) => sensor,
+        Err(e) => {
+            eprintln!("Failed to create weather sensor: {}", e);
+            process::exit(1);
+        }
+    };
+    
+    // Fetch now from the click binding; running instances pick the result up
+    if args.refresh {
+        weather_sensor.configure(config)?;
+        if let Err(e) = weather_sensor.refresh() {
+            eprintln!("Error fetching weather: {}", e);
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // Check availability if requested
+    if args.check {
+        match weather_sensor.check_availability() {
+            Ok(()) => {
+                println!("Weather sensor is available");
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("Weather sensor is not available: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+    
+    weather_sensor.configure(config)?;
+    
+    if args.once {
+        // One-shot mode: read once and exit
+        match weather_sensor.read_styled() {
+            Ok(output) => {
+                println!("{}", serde_json::to_string(&output)?);
+            }
+            Err(e) => {
+                eprintln!("Error reading weather: {}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        // Continuous mode: loop and output readings
+        let mut interval = schedule::interval(Duration::from_millis(args.interval));
+        
+        loop {
+            interval.tick().await;
+            if session_lock::wait_async(weather_sensor.config()).await {
+                interval.reset();
+            }
+            
+            match weather_sensor.read_styled() {
+                Ok(output) => {
+                    println!("{}", serde_json::to_string(&output)?);
+                    io::stdout().flush()?;
+                }
+                Err(e) => {
+                    eprintln!("Error reading weather: {}", e);
+                    // Continue running on errors, just log them
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,698 @@
+//! Current weather from Open-Meteo or wttr.in.
+//!
+//! Both services answer plain HTTPS requests without an API key, fetched
+//! with `curl` like the network sensor's connectivity probe. A fetch runs on
+//! a worker thread so reads never wait on the network, except for the very
+//! first one. Results are cached in `~/.cache/waysensor-rs/weather.json`,
+//! and the last one stays on the bar, marked stale, while offline.
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, RecvTimeoutError, TryRecvError};
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use waysensor_rs_core::{
+    format, DiscoveredItem, Discoverable, IconConfig, Sensor, SensorConfig, SensorError, UnitSystem, WaybarOutput,
+};
+
+/// Seconds between two fetches by default.
+const DEFAULT_REFRESH_INTERVAL: u64 = 900;
+/// Services ask for a few minutes between requests.
+const MIN_REFRESH_INTERVAL: u64 = 300;
+/// Seconds before a failed fetch is retried.
+const RETRY_INTERVAL: u64 = 60;
+/// Seconds curl gets for one request.
+const HTTP_TIMEOUT_SECS: u64 = 10;
+
+const OPEN_METEO_URL: &str = "https://api.open-meteo.com/v1/forecast";
+const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+const WTTR_URL: &str = "https://wttr.in";
+
+/// Weather service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WeatherProvider {
+    #[default]
+    OpenMeteo,
+    Wttr,
+}
+
+impl std::str::FromStr for WeatherProvider {
+    type Err = SensorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "open-meteo" | "openmeteo" => Ok(Self::OpenMeteo),
+            "wttr.in" | "wttr" => Ok(Self::Wttr),
+            _ => Err(SensorError::config_with_value(
+                "Invalid weather provider. Valid options: open-meteo, wttr.in",
+                s,
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for WeatherProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OpenMeteo => write!(f, "open-meteo"),
+            Self::Wttr => write!(f, "wttr.in"),
+        }
+    }
+}
+
+/// Where to report the weather for.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Location {
+    Coordinates { latitude: f64, longitude: f64 },
+    /// Place name, looked up by the provider
+    Place(String),
+    /// wttr.in's guess from the public IP address
+    #[default]
+    Auto,
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Coordinates { latitude, longitude } => write!(f, "{:.4},{:.4}", latitude, longitude),
+            Self::Place(name) => write!(f, "{}", name),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+/// Weather condition, as far as the icon goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Condition {
+    Clear,
+    PartlyCloudy,
+    Cloudy,
+    Fog,
+    Drizzle,
+    Rain,
+    Snow,
+    Thunderstorm,
+}
+
+impl Condition {
+    /// Condition of a WMO weather interpretation code, as used by Open-Meteo.
+    pub fn from_wmo(code: u64) -> Self {
+        match code {
+            0 | 1 => Self::Clear,
+            2 => Self::PartlyCloudy,
+            45 | 48 => Self::Fog,
+            51..=57 => Self::Drizzle,
+            61..=67 | 80..=82 => Self::Rain,
+            71..=77 | 85 | 86 => Self::Snow,
+            95..=99 => Self::Thunderstorm,
+            _ => Self::Cloudy,
+        }
+    }
+
+    /// Condition of a WorldWeatherOnline code, as used by wttr.in.
+    pub fn from_wwo(code: u64) -> Self {
+        match code {
+            113 => Self::Clear,
+            116 => Self::PartlyCloudy,
+            143 | 248 | 260 => Self::Fog,
+            263 | 266 | 281 | 284 => Self::Drizzle,
+            176 | 293..=308 | 353..=359 => Self::Rain,
+            179 | 182 | 185 | 227 | 230 | 311..=338 | 350 | 362..=377 => Self::Snow,
+            200 | 386..=395 => Self::Thunderstorm,
+            _ => Self::Cloudy,
+        }
+    }
+
+    /// Icon for this condition, the night sky for a clear night.
+    pub fn icon(self, is_day: bool, icons: &IconConfig) -> &str {
+        match self {
+            Self::Clear if !is_day => &icons.weather_night,
+            Self::Clear => &icons.weather_clear,
+            Self::PartlyCloudy => &icons.weather_partly_cloudy,
+            Self::Cloudy => &icons.weather_cloudy,
+            Self::Fog => &icons.weather_fog,
+            Self::Drizzle | Self::Rain => &icons.weather_rain,
+            Self::Snow => &icons.weather_snow,
+            Self::Thunderstorm => &icons.weather_thunderstorm,
+        }
+    }
+}
+
+/// Description of a WMO weather interpretation code.
+fn wmo_description(code: u64) -> &'static str {
+    match code {
+        0 => "Clear sky",
+        1 => "Mainly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 => "Fog",
+        48 => "Depositing rime fog",
+        51..=55 => "Drizzle",
+        56 | 57 => "Freezing drizzle",
+        61 | 63 => "Rain",
+        65 => "Heavy rain",
+        66 | 67 => "Freezing rain",
+        71..=75 => "Snow",
+        77 => "Snow grains",
+        80..=82 => "Rain showers",
+        85 | 86 => "Snow showers",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => "Unknown",
+    }
+}
+
+/// Current conditions, in the units they were asked for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conditions {
+    /// Place name the provider reported, if any
+    pub place: Option<String>,
+    pub condition: Condition,
+    pub description: String,
+    pub is_day: bool,
+    /// °C or °F
+    pub temperature: f64,
+    pub feels_like: Option<f64>,
+    /// Relative humidity in percent
+    pub humidity: Option<f64>,
+    /// km/h or mph
+    pub wind_speed: Option<f64>,
+    pub units: UnitSystem,
+}
+
+impl Conditions {
+    fn temperature_unit(&self) -> &'static str {
+        match self.units {
+            UnitSystem::Metric => "°C",
+            UnitSystem::Imperial => "°F",
+        }
+    }
+
+    fn speed_unit(&self) -> &'static str {
+        match self.units {
+            UnitSystem::Metric => "km/h",
+            UnitSystem::Imperial => "mph",
+        }
+    }
+
+    /// Temperature rounded to whole degrees, e.g. `14°C`.
+    pub fn format_temperature(&self, temperature: f64) -> String {
+        // Adding zero turns a rounded -0 into 0
+        format!("{:.0}{}", temperature.round() + 0.0, self.temperature_unit())
+    }
+}
+
+/// Parse an Open-Meteo forecast reply with `current` conditions.
+fn parse_open_meteo(json: &str, units: UnitSystem) -> Result<Conditions, SensorError> {
+    let reply: Value = serde_json::from_str(json)
+        .map_err(|e| SensorError::parse_with_source("Invalid Open-Meteo reply", e))?;
+    let current = &reply["current"];
+    let temperature = current["temperature_2m"].as_f64()
+        .ok_or_else(|| SensorError::parse("Open-Meteo reply without current temperature"))?;
+    let code = current["weather_code"].as_u64().unwrap_or_default();
+    Ok(Conditions {
+        place: None,
+        condition: Condition::from_wmo(code),
+        description: wmo_description(code).to_string(),
+        is_day: current["is_day"].as_u64().is_none_or(|is_day| is_day == 1),
+        temperature,
+        feels_like: current["apparent_temperature"].as_f64(),
+        humidity: current["relative_humidity_2m"].as_f64(),
+        wind_speed: current["wind_speed_10m"].as_f64(),
+        units,
+    })
+}
+
+/// Parse the first result of an Open-Meteo geocoding search: the place
+/// name and its coordinates.
+fn parse_geocoding(json: &str) -> Result<Option<(String, f64, f64)>, SensorError> {
+    let reply: Value = serde_json::from_str(json)
+        .map_err(|e| SensorError::parse_with_source("Invalid geocoding reply", e))?;
+    let place = &reply["results"][0];
+    Ok(place["name"].as_str().zip(place["latitude"].as_f64()).zip(place["longitude"].as_f64())
+        .map(|((name, latitude), longitude)| (name.to_string(), latitude, longitude)))
+}
+
+/// Resolved place name, latitude and longitude.
+type Place = (String, f64, f64);
+
+/// Place names resolved in this process. Places don't move, so each name
+/// is only looked up once.
+static GEOCODED: LazyLock<Mutex<HashMap<String, Place>>> = LazyLock::new(Default::default);
+
+/// Resolved name and coordinates of the place called `name`.
+fn geocode(name: &str) -> Result<Place, SensorError> {
+    let cached = GEOCODED.lock().unwrap_or_else(PoisonError::into_inner).get(name).cloned();
+    if let Some(place) = cached {
+        return Ok(place);
+    }
+    let url = format!("{}?name={}&count=1&format=json", GEOCODING_URL, encode(name));
+    let place = parse_geocoding(&http_get(&url)?)?
+        .ok_or_else(|| SensorError::config_with_value("Unknown location", name))?;
+    GEOCODED.lock().unwrap_or_else(PoisonError::into_inner).insert(name.to_string(), place.clone());
+    Ok(place)
+}
+
+/// Parse a wttr.in clock time such as `08:41 PM`.
+fn parse_clock(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%I:%M %p").ok()
+}
+
+/// Parse a wttr.in `format=j1` reply. Its numbers are strings, and it
+/// reports every unit at once.
+fn parse_wttr(json: &str, units: UnitSystem) -> Result<Conditions, SensorError> {
+    let reply: Value = serde_json::from_str(json)
+        .map_err(|e| SensorError::parse_with_source("Invalid wttr.in reply", e))?;
+    let current = &reply["current_condition"][0];
+    let number = |key: &str| current[key].as_str().and_then(|value| value.trim().parse::<f64>().ok());
+    let (temperature, feels_like, wind_speed) = match units {
+        UnitSystem::Metric => ("temp_C", "FeelsLikeC", "windspeedKmph"),
+        UnitSystem::Imperial => ("temp_F", "FeelsLikeF", "windspeedMiles"),
+    };
+    let temperature = number(temperature)
+        .ok_or_else(|| SensorError::parse("wttr.in reply without current temperature"))?;
+    let code = number("weatherCode").map_or(0, |code| code as u64);
+
+    // Day or night from the observation time between sunrise and sunset
+    let astronomy = &reply["weather"][0]["astronomy"][0];
+    let observed = current["localObsDateTime"].as_str()
+        .and_then(|stamp| stamp.split_once(' '))
+        .and_then(|(_, time)| parse_clock(time));
+    let clock = |value: &Value| value.as_str().and_then(parse_clock);
+    let is_day = match (observed, clock(&astronomy["sunrise"]), clock(&astronomy["sunset"])) {
+        (Some(observed), Some(sunrise), Some(sunset)) => sunrise <= observed && observed < sunset,
+        _ => true,
+    };
+
+    let area = &reply["nearest_area"][0];
+    Ok(Conditions {
+        place: area["areaName"][0]["value"].as_str().filter(|name| !name.is_empty()).map(str::to_string),
+        condition: Condition::from_wwo(code),
+        description: current["weatherDesc"][0]["value"].as_str().unwrap_or("Unknown").trim().to_string(),
+        is_day,
+        temperature,
+        feels_like: number(feels_like),
+        humidity: number("humidity"),
+        wind_speed: number(wind_speed),
+        units,
+    })
+}
+
+/// Percent-encode `text` for a URL path or query.
+fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Body of `url`.
+fn http_get(url: &str) -> Result<String, SensorError> {
+    let output = Command::new("curl")
+        .args(["-sfL", "--max-time", &HTTP_TIMEOUT_SECS.to_string(), url])
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => SensorError::unavailable("curl not installed"),
+            _ => SensorError::Io(e),
+        })?;
+    if !output.status.success() {
+        // Offline, timed out or refused; worth another try later
+        let host = url.split('/').nth(2).unwrap_or(url);
+        return Err(SensorError::temporarily_unavailable(format!("No answer from {}", host)));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Fetch the current conditions at `location` from `provider`.
+///
+/// # Errors
+///
+/// Returns an error if the service does not answer, Open-Meteo is asked
+/// without a location, or a place name is not found.
+pub fn fetch(provider: WeatherProvider, location: &Location, units: UnitSystem) -> Result<Conditions, SensorError> {
+    match provider {
+        WeatherProvider::OpenMeteo => {
+            let (place, latitude, longitude) = match location {
+                Location::Coordinates { latitude, longitude } => (None, *latitude, *longitude),
+                Location::Place(name) => {
+                    let (name, latitude, longitude) = geocode(name)?;
+                    (Some(name), latitude, longitude)
+                }
+                Location::Auto => {
+                    return Err(SensorError::config(
+                        "Open-Meteo needs a location or coordinates; wttr.in can locate you by IP",
+                    ))
+                }
+            };
+            let mut url = format!(
+                "{}?latitude={}&longitude={}&current=temperature_2m,apparent_temperature,\
+                 relative_humidity_2m,weather_code,wind_speed_10m,is_day",
+                OPEN_METEO_URL, latitude, longitude
+            );
+            if units == UnitSystem::Imperial {
+                url.push_str("&temperature_unit=fahrenheit&wind_speed_unit=mph");
+            }
+            let mut conditions = parse_open_meteo(&http_get(&url)?, units)?;
+            conditions.place = place;
+            Ok(conditions)
+        }
+        WeatherProvider::Wttr => {
+            let place = match location {
+                Location::Coordinates { latitude, longitude } => format!("{},{}", latitude, longitude),
+                Location::Place(name) => encode(name),
+                Location::Auto => String::new(),
+            };
+            parse_wttr(&http_get(&format!("{}/{}?format=j1", WTTR_URL, place))?, units)
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Result of the last successful fetch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Report {
+    /// Unix time of the fetch
+    fetched_at: u64,
+    /// Provider, location and units asked, so other settings start over
+    source: String,
+    conditions: Conditions,
+}
+
+/// Cache file, e.g. `~/.cache/waysensor-rs/weather.json`.
+fn cache_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("waysensor-rs").join("weather.json"))
+}
+
+/// Error text for the tooltip, without the prefix of unavailability.
+fn describe(error: &SensorError) -> String {
+    match error {
+        SensorError::Unavailable { reason, .. } => reason.clone(),
+        other => other.to_string(),
+    }
+}
+
+type FetchResult = Result<Conditions, SensorError>;
+
+/// Sensor for the current weather.
+#[derive(Debug)]
+pub struct WeatherSensor {
+    name: String,
+    config: SensorConfig,
+    provider: WeatherProvider,
+    location: Location,
+    /// Seconds between two fetches
+    refresh_interval: u64,
+    /// Share results through the cache file
+    persist: bool,
+    report: Option<Report>,
+    /// Fetch running on a worker thread
+    pending: Option<mpsc::Receiver<FetchResult>>,
+    /// Unix time and error of the last failed fetch, cleared on success
+    failure: Option<(u64, String)>,
+}
+
+impl WeatherSensor {
+    /// Create a sensor for `location`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a configuration error for Open-Meteo without a location.
+    pub fn new(provider: WeatherProvider, location: Location) -> Result<Self, SensorError> {
+        if provider == WeatherProvider::OpenMeteo && location == Location::Auto {
+            return Err(SensorError::config(
+                "Open-Meteo needs a location (--location or the \"location\" setting); use wttr.in to locate by IP",
+            ));
+        }
+        Ok(Self {
+            name: "weather".to_string(),
+            config: SensorConfig::default(),
+            provider,
+            location,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            persist: true,
+            report: None,
+            pending: None,
+            failure: None,
+        })
+    }
+
+    fn source(&self) -> String {
+        format!("{} {} {}", self.provider, self.location, self.config.units)
+    }
+
+    /// Fetch now and wait for the result, whatever the age of the last one.
+    ///
+    /// # Errors
+    ///
+    /// Returns the fetch error; the last result is kept.
+    pub fn refresh(&mut self) -> Result<(), SensorError> {
+        match fetch(self.provider, &self.location, self.config.units) {
+            Ok(conditions) => {
+                self.finish(Ok(conditions));
+                Ok(())
+            }
+            Err(e) => {
+                self.failure = Some((now(), describe(&e)));
+                Err(e)
+            }
+        }
+    }
+
+    fn start_fetch(&mut self) {
+        let (tx, rx) = mpsc::channel();
+        let (provider, location, units) = (self.provider, self.location.clone(), self.config.units);
+        let spawned = std::thread::Builder::new()
+            .name("weather-fetch".to_string())
+            .spawn(move || {
+                // The receiver is gone if the sensor was reconfigured
+                let _ = tx.send(fetch(provider, &location, units));
+            });
+        match spawned {
+            Ok(_) => self.pending = Some(rx),
+            Err(e) => self.failure = Some((now(), e.to_string())),
+        }
+    }
+
+    /// Take the running fetch's result if it is done, or once it is when
+    /// `wait` is set.
+    fn collect(&mut self, wait: bool) {
+        let Some(rx) = self.pending.take() else {
+            return;
+        };
+        let received = if wait {
+            rx.recv_timeout(Duration::from_secs(3 * HTTP_TIMEOUT_SECS))
+                .map_err(|e| e == RecvTimeoutError::Disconnected)
+        } else {
+            rx.try_recv().map_err(|e| e == TryRecvError::Disconnected)
+        };
+        match received {
+            Ok(result) => self.finish(result),
+            Err(false) => self.pending = Some(rx),
+            Err(true) => self.finish(Err(SensorError::temporarily_unavailable("Weather fetch stopped"))),
+        }
+    }
+
+    fn finish(&mut self, result: FetchResult) {
+        match result {
+            Ok(conditions) => {
+                let report = Report { fetched_at: now(), source: self.source(), conditions };
+                if self.persist {
+                    // A cache that cannot be written only costs extra fetches
+                    if let Some(path) = cache_path() {
+                        let _ = fs::create_dir_all(path.parent().unwrap_or(&path))
+                            .and_then(|_| fs::write(&path, serde_json::to_string(&report).unwrap_or_default()));
+                    }
+                }
+                self.report = Some(report);
+                self.failure = None;
+            }
+            Err(e) => self.failure = Some((now(), describe(&e))),
+        }
+    }
+
+    /// The latest conditions, starting a fetch once they are older than the
+    /// refresh interval. Another instance's newer result is picked up from
+    /// the cache file.
+    fn conditions(&mut self) -> Option<&Report> {
+        self.collect(false);
+        if self.persist {
+            let source = self.source();
+            let fetched_at = self.report.as_ref().map_or(0, |report| report.fetched_at);
+            let stored = cache_path()
+                .and_then(|path| fs::read_to_string(path).ok())
+                .and_then(|json| serde_json::from_str::<Report>(&json).ok())
+                .filter(|stored| stored.source == source && stored.fetched_at > fetched_at);
+            if let Some(stored) = stored {
+                self.report = Some(stored);
+            }
+        }
+
+        let now = now();
+        let due = self.report.as_ref()
+            .is_none_or(|report| now.saturating_sub(report.fetched_at) >= self.refresh_interval);
+        let retry = self.failure.as_ref().is_none_or(|(failed_at, _)| now.saturating_sub(*failed_at) >= RETRY_INTERVAL);
+        if due && retry && self.pending.is_none() {
+            self.start_fetch();
+        }
+        // Nothing to show yet: wait for the first fetch
+        if self.report.is_none() {
+            self.collect(true);
+        }
+        self.report.as_ref()
+    }
+
+    fn build_tooltip(&self, report: &Report, stale: bool) -> String {
+        let conditions = &report.conditions;
+        let mut lines = Vec::new();
+        if let Some(place) = &conditions.place {
+            lines.push(format::key_value("Location", place, &self.config));
+        }
+        lines.push(format::key_value("Conditions", &conditions.description, &self.config));
+        lines.push(format::key_value("Temperature", &conditions.format_temperature(conditions.temperature), &self.config));
+        if let Some(feels_like) = conditions.feels_like {
+            lines.push(format::key_value("Feels Like", &conditions.format_temperature(feels_like), &self.config));
+        }
+        if let Some(humidity) = conditions.humidity {
+            lines.push(format::key_value("Humidity", &format!("{:.0}%", humidity), &self.config));
+        }
+        if let Some(wind_speed) = conditions.wind_speed {
+            lines.push(format::key_value("Wind", &format!("{:.0} {}", wind_speed, conditions.speed_unit()), &self.config));
+        }
+        let age = now().saturating_sub(report.fetched_at) / 60;
+        let updated = if age == 0 { "just now".to_string() } else { format!("{} min ago", age) };
+        lines.push(format::key_value("Updated", &format!("{} from {}", updated, self.provider), &self.config));
+        if let (true, Some((_, error))) = (stale, &self.failure) {
+            lines.push(format::value_only(&format!("Offline: {}", error), &self.config));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Discoverable for WeatherSensor {
+    const KIND: &'static str = "weather providers";
+
+    fn discover() -> Result<Vec<DiscoveredItem>, SensorError> {
+        Ok(vec![
+            DiscoveredItem::new(WeatherProvider::OpenMeteo.to_string(), "Open-Meteo")
+                .with_detail("location", "required"),
+            DiscoveredItem::new(WeatherProvider::Wttr.to_string(), "wttr.in")
+                .with_detail("location", "optional (IP geolocation)"),
+        ])
+    }
+}
+
+impl Sensor for WeatherSensor {
+    type Error = SensorError;
+
+    fn read(&mut self) -> Result<WaybarOutput, Self::Error> {
+        let Some(report) = self.conditions().cloned() else {
+            let reason = self.failure.as_ref().map_or("No weather data yet", |(_, error)| error.as_str());
+            return Err(SensorError::temporarily_unavailable(reason));
+        };
+        let conditions = &report.conditions;
+
+        // Missed a refresh: the last result is shown, but marked
+        let stale = now().saturating_sub(report.fetched_at) >= 2 * self.refresh_interval;
+        let icon = conditions.condition.icon(conditions.is_day, &self.config.icons);
+        let class = if stale { &self.config.theme.unknown } else { &self.config.theme.normal };
+
+        Ok(WaybarOutput {
+            text: format::with_icon_and_colors(&conditions.format_temperature(conditions.temperature), icon, &self.config),
+            tooltip: Some(self.build_tooltip(&report, stale)),
+            class: Some(class.clone()),
+            percentage: None,
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn configure(&mut self, config: SensorConfig) -> Result<(), Self::Error> {
+        if let Some(interval) = config.get_custom("refresh_interval").and_then(|v| v.as_u64()) {
+            self.refresh_interval = interval.max(MIN_REFRESH_INTERVAL);
+        }
+        if let Some(cache) = config.get_custom("cache").and_then(|v| v.as_bool()) {
+            self.persist = cache;
+        }
+        // Results in other units do not apply any more
+        if config.units != self.config.units {
+            self.report = None;
+            self.pending = None;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    fn config(&self) -> &SensorConfig {
+        &self.config
+    }
+
+    fn check_availability(&self) -> Result<(), Self::Error> {
+        match Command::new("curl").arg("--version").output() {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(SensorError::unavailable("curl not installed")),
+            Err(e) => Err(SensorError::Io(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weather_parsing() {
+        let open_meteo = r#"{"latitude":52.52,"longitude":13.42,"current":{"time":"2024-05-01T21:30",
+            "temperature_2m":-0.4,"apparent_temperature":-3.1,"relative_humidity_2m":81,
+            "weather_code":0,"wind_speed_10m":11.2,"is_day":0}}"#;
+        let conditions = parse_open_meteo(open_meteo, UnitSystem::Metric).unwrap();
+        assert_eq!(conditions.condition, Condition::Clear);
+        assert_eq!(conditions.description, "Clear sky");
+        assert!(!conditions.is_day);
+        assert_eq!(conditions.format_temperature(conditions.temperature), "0°C");
+        assert_eq!(conditions.humidity, Some(81.0));
+        let icons = IconConfig::default();
+        assert_eq!(conditions.condition.icon(conditions.is_day, &icons), icons.weather_night);
+
+        let wttr = r#"{"current_condition":[{"temp_C":"14","temp_F":"57","FeelsLikeC":"13","FeelsLikeF":"55",
+            "humidity":"62","weatherCode":"296","weatherDesc":[{"value":"Light rain"}],
+            "windspeedKmph":"15","windspeedMiles":"9","localObsDateTime":"2024-05-01 02:10 PM"}],
+            "nearest_area":[{"areaName":[{"value":"Hamburg"}]}],
+            "weather":[{"astronomy":[{"sunrise":"05:38 AM","sunset":"08:41 PM"}]}]}"#;
+        let conditions = parse_wttr(wttr, UnitSystem::Imperial).unwrap();
+        assert_eq!(conditions.place.as_deref(), Some("Hamburg"));
+        assert_eq!(conditions.condition, Condition::Rain);
+        assert_eq!(conditions.description, "Light rain");
+        assert!(conditions.is_day);
+        assert_eq!(conditions.format_temperature(conditions.temperature), "57°F");
+        assert_eq!(conditions.wind_speed, Some(9.0));
+
+        assert_eq!(Condition::from_wmo(95), Condition::Thunderstorm);
+        assert_eq!(Condition::from_wwo(338), Condition::Snow);
+        assert_eq!(
+            parse_geocoding(r#"{"results":[{"name":"Berlin","latitude":52.52,"longitude":13.41}]}"#).unwrap(),
+            Some(("Berlin".to_string(), 52.52, 13.41))
+        );
+        assert_eq!(encode("São Paulo"), "S%C3%A3o%20Paulo");
+        assert_eq!("wttr".parse::<WeatherProvider>().unwrap(), WeatherProvider::Wttr);
+    }
+
+    #[test]
+    fn test_geocoding_is_cached() {
+        // A cached name never reaches the network
+        GEOCODED.lock().unwrap().insert("Testville".to_string(), ("Testville".to_string(), 1.5, -2.5));
+        assert_eq!(geocode("Testville").unwrap(), ("Testville".to_string(), 1.5, -2.5));
+    }
+}
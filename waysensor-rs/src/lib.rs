@@ -0,0 +1,155 @@
+//! waysensor-rs sensors as a library.
+//!
+//! Every sensor binary in the suite is a thin wrapper around a type
+//! implementing [`Sensor`]. This crate re-exports those types together with
+//! the core traits, so custom bars, TUIs and other Rust programs can read
+//! sensors in-process instead of spawning the binaries and parsing their JSON.
+//!
+//! Each sensor crate sits behind a feature of the same name (`cpu`, `memory`,
+//! `disk`, `network`, `battery`, `thermal`, `amd-gpu`, `intel-gpu`,
+//! `nvidia-gpu`, `gpu`, `audio`, `backlight`, `bluetooth`, `media`, `updates`,
+//! `systemd`, `uptime`, `weather`, `keyboard`, `compositor`, `containers`),
+//! all enabled by default. The full crate is available as a module
+//! (`waysensor_rs::cpu`) and its main types at the top level.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use waysensor_rs::{CpuSensor, GlobalConfig, MemorySensor, Sensor};
+//!
+//! let config = GlobalConfig::default();
+//!
+//! let mut cpu = CpuSensor::new(70, 90)?;
+//! cpu.configure(config.to_sensor_config_for("cpu"))?;
+//!
+//! let mut memory = MemorySensor::new(70, 90, false, false)?;
+//! memory.configure(config.to_sensor_config_for("memory"))?;
+//!
+//! for sensor in [&mut cpu as &mut dyn Sensor<Error = _>, &mut memory] {
+//!     let output = sensor.read_styled()?;
+//!     println!("{}: {}", sensor.name(), output.text);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub use waysensor_rs_core as core;
+pub use waysensor_rs_core::build_info::BuildInfo;
+pub use waysensor_rs_core::{
+    Discoverable, DiscoveredItem, GlobalConfig, Reading, Sensor, SensorConfig, SensorError, Theme,
+    WaybarOutput,
+};
+
+#[cfg(feature = "cpu")]
+pub use waysensor_rs_cpu as cpu;
+#[cfg(feature = "cpu")]
+pub use waysensor_rs_cpu::{CpuSensor, KernelHealthSensor};
+
+#[cfg(feature = "memory")]
+pub use waysensor_rs_memory as memory;
+#[cfg(feature = "memory")]
+pub use waysensor_rs_memory::MemorySensor;
+
+#[cfg(feature = "disk")]
+pub use waysensor_rs_disk as disk;
+#[cfg(feature = "disk")]
+pub use waysensor_rs_disk::{
+    DirWatchSensor, DiskSensor, DiskSensorBuilder, FsErrorSensor, IoSensor, MultiDiskSensor,
+};
+
+#[cfg(feature = "network")]
+pub use waysensor_rs_network as network;
+#[cfg(feature = "network")]
+pub use waysensor_rs_network::NetworkSensor;
+
+#[cfg(feature = "battery")]
+pub use waysensor_rs_battery as battery;
+#[cfg(feature = "battery")]
+pub use waysensor_rs_battery::{BatterySensor, SleepSensor};
+
+#[cfg(feature = "thermal")]
+pub use waysensor_rs_thermal as thermal;
+#[cfg(feature = "thermal")]
+pub use waysensor_rs_thermal::{FanSensor, ThermalSensor};
+
+#[cfg(feature = "amd-gpu")]
+pub use waysensor_rs_amd_gpu as amd_gpu;
+#[cfg(feature = "amd-gpu")]
+pub use waysensor_rs_amd_gpu::AmdgpuSensor;
+
+#[cfg(feature = "intel-gpu")]
+pub use waysensor_rs_intel_gpu as intel_gpu;
+#[cfg(feature = "intel-gpu")]
+pub use waysensor_rs_intel_gpu::IntelGpuSensor;
+
+#[cfg(feature = "nvidia-gpu")]
+pub use waysensor_rs_nvidia_gpu as nvidia_gpu;
+#[cfg(feature = "nvidia-gpu")]
+pub use waysensor_rs_nvidia_gpu::NvidiaGpuSensor;
+
+#[cfg(feature = "gpu")]
+pub use waysensor_rs_gpu as gpu;
+#[cfg(feature = "gpu")]
+pub use waysensor_rs_gpu::{GpuSensor, GpuVendor};
+
+#[cfg(feature = "audio")]
+pub use waysensor_rs_audio as audio;
+#[cfg(feature = "audio")]
+pub use waysensor_rs_audio::{AudioSensor, MicSensor};
+
+#[cfg(feature = "backlight")]
+pub use waysensor_rs_backlight as backlight;
+#[cfg(feature = "backlight")]
+pub use waysensor_rs_backlight::BacklightSensor;
+
+#[cfg(feature = "bluetooth")]
+pub use waysensor_rs_bluetooth as bluetooth;
+#[cfg(feature = "bluetooth")]
+pub use waysensor_rs_bluetooth::BluetoothSensor;
+
+#[cfg(feature = "media")]
+pub use waysensor_rs_media as media;
+#[cfg(feature = "media")]
+pub use waysensor_rs_media::MediaSensor;
+
+#[cfg(feature = "updates")]
+pub use waysensor_rs_updates as updates;
+#[cfg(feature = "updates")]
+pub use waysensor_rs_updates::UpdatesSensor;
+
+#[cfg(feature = "systemd")]
+pub use waysensor_rs_systemd as systemd;
+#[cfg(feature = "systemd")]
+pub use waysensor_rs_systemd::SystemdSensor;
+
+#[cfg(feature = "uptime")]
+pub use waysensor_rs_uptime as uptime;
+#[cfg(feature = "uptime")]
+pub use waysensor_rs_uptime::UptimeSensor;
+
+#[cfg(feature = "weather")]
+pub use waysensor_rs_weather as weather;
+#[cfg(feature = "weather")]
+pub use waysensor_rs_weather::WeatherSensor;
+
+#[cfg(feature = "keyboard")]
+pub use waysensor_rs_keyboard as keyboard;
+#[cfg(feature = "keyboard")]
+pub use waysensor_rs_keyboard::KeyboardSensor;
+
+#[cfg(feature = "compositor")]
+pub use waysensor_rs_compositor as compositor;
+#[cfg(feature = "compositor")]
+pub use waysensor_rs_compositor::CompositorSensor;
+#[cfg(feature = "containers")]
+pub use waysensor_rs_containers as containers;
+#[cfg(feature = "containers")]
+pub use waysensor_rs_containers::ContainersSensor;
+
+/// Build information of this crate, listing the enabled sensor features.
+pub fn build_info() -> BuildInfo {
+    waysensor_rs_core::build_info!(
+        "cpu", "memory", "disk", "network", "battery", "thermal", "amd-gpu", "intel-gpu", "nvidia-gpu", "gpu",
+        "audio", "backlight", "bluetooth", "media", "updates", "systemd", "uptime", "weather",
+        "keyboard", "compositor", "containers",
+    )
+}
@@ -0,0 +1,421 @@
+//! Workspace automation tasks.
+//!
+//! ```text
+//! cargo xtask integration [--release] [--no-build]
+//! ```
+//!
+//! The `integration` task builds every sensor binary and runs it the way a
+//! user or Waybar would: `--help`, `--list`, the availability check and
+//! `--once`. The emitted JSON is validated against the Waybar output schema,
+//! so CLI-level regressions (clap conflicts, panics in `main`, malformed
+//! output, wrong exit codes) are caught even though every unit test still
+//! passes.
+//!
+//! Sensors that read only `/proc` and `/sys` are also run with `--sys-root`
+//! against the fixture trees in `xtask/fixtures/<sensor>`, where the
+//! hardware is always present, and against an empty root, where it never
+//! is. That pins down both outcomes of the availability check regardless of
+//! the machine the harness runs on.
+
+use serde_json::Value;
+use std::env;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a single sensor invocation may run before it is killed.
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Exit code of a Rust process that panicked in `main`.
+const PANIC_EXIT_CODE: i32 = 101;
+
+/// Keys Waybar understands in a custom module's JSON output.
+const WAYBAR_KEYS: &[&str] = &["text", "alt", "tooltip", "class", "percentage"];
+
+/// A sensor binary and the flag it uses to test hardware availability.
+struct SensorBin {
+    name: &'static str,
+    check_flag: &'static str,
+    /// Directory under `xtask/fixtures` to pass as `--sys-root`
+    fixture: Option<&'static str>,
+}
+
+const SENSORS: &[SensorBin] = &[
+    SensorBin { name: "waysensor-rs-cpu", check_flag: "--check", fixture: Some("cpu") },
+    SensorBin { name: "waysensor-rs-memory", check_flag: "--check", fixture: Some("memory") },
+    SensorBin { name: "waysensor-rs-disk", check_flag: "--test", fixture: None },
+    SensorBin { name: "waysensor-rs-network", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-battery", check_flag: "--check", fixture: Some("battery") },
+    SensorBin { name: "waysensor-rs-thermal", check_flag: "--check", fixture: Some("thermal") },
+    SensorBin { name: "waysensor-rs-amd-gpu", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-intel-gpu", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-nvidia-gpu", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-gpu", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-audio", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-backlight", check_flag: "--check", fixture: Some("backlight") },
+    SensorBin { name: "waysensor-rs-bluetooth", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-media", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-updates", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-systemd", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-uptime", check_flag: "--check", fixture: Some("uptime") },
+    SensorBin { name: "waysensor-rs-weather", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-keyboard", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-compositor", check_flag: "--check", fixture: None },
+    SensorBin { name: "waysensor-rs-containers", check_flag: "--check", fixture: None },
+];
+
+/// Captured result of running a sensor binary.
+struct Run {
+    status: Option<ExitStatus>,
+    stdout: String,
+    stderr: String,
+}
+
+impl Run {
+    fn code(&self) -> Option<i32> {
+        self.status.and_then(|s| s.code())
+    }
+
+    fn success(&self) -> bool {
+        self.status.is_some_and(|s| s.success())
+    }
+
+    fn timed_out(&self) -> bool {
+        self.status.is_none()
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("integration") => {
+            let release = args.iter().any(|a| a == "--release");
+            let build = !args.iter().any(|a| a == "--no-build");
+            process::exit(integration(release, build));
+        }
+        _ => {
+            eprintln!("Usage: cargo xtask integration [--release] [--no-build]");
+            process::exit(2);
+        }
+    }
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask lives inside the workspace")
+        .to_path_buf()
+}
+
+fn integration(release: bool, build: bool) -> i32 {
+    let root = workspace_root();
+
+    if build {
+        let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let mut cmd = Command::new(cargo);
+        cmd.current_dir(&root).args(["build", "--bins"]);
+        if release {
+            cmd.arg("--release");
+        }
+        match cmd.status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => {
+                eprintln!("cargo build failed: {}", status);
+                return 1;
+            }
+            Err(e) => {
+                eprintln!("Failed to run cargo: {}", e);
+                return 1;
+            }
+        }
+    }
+
+    let target_dir = env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| root.join("target"))
+        .join(if release { "release" } else { "debug" });
+
+    // Keep the user's config.ron out of the picture
+    let config_home = env::temp_dir().join(format!("waysensor-xtask-{}", process::id()));
+    if let Err(e) = std::fs::create_dir_all(&config_home) {
+        eprintln!("Failed to create {}: {}", config_home.display(), e);
+        return 1;
+    }
+
+    // A root without any hardware for the fixture runs
+    let empty_root = config_home.join("empty-root");
+    if let Err(e) = std::fs::create_dir_all(&empty_root) {
+        eprintln!("Failed to create {}: {}", empty_root.display(), e);
+        return 1;
+    }
+
+    let mut failures = 0;
+    for sensor in SENSORS {
+        let binary = target_dir.join(sensor.name);
+        let problems = if binary.is_file() {
+            let mut problems = check_sensor(&binary, sensor, &config_home);
+            if let Some(fixture) = sensor.fixture {
+                let fixture = root.join("xtask").join("fixtures").join(fixture);
+                problems.extend(check_fixture(&binary, sensor, &fixture, &empty_root, &config_home));
+            }
+            problems
+        } else {
+            vec![format!("binary not found at {}", binary.display())]
+        };
+
+        if problems.is_empty() {
+            println!("ok    {}", sensor.name);
+        } else {
+            failures += 1;
+            println!("FAIL  {}", sensor.name);
+            for problem in problems {
+                println!("        {}", problem);
+            }
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(&config_home);
+
+    println!();
+    println!("{} sensors, {} failed", SENSORS.len(), failures);
+    if failures == 0 { 0 } else { 1 }
+}
+
+/// Run one sensor binary through its CLI surface and collect every problem found.
+fn check_sensor(binary: &Path, sensor: &SensorBin, config_home: &Path) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    // --help exercises clap's debug assertions (duplicate short flags etc.)
+    let help = run(binary, &["--help"], config_home);
+    if !help.success() {
+        problems.push(describe("--help", &help));
+    }
+
+    // Discovery must work (and print valid JSON) whether or not hardware is present
+    let list = run(binary, &["--list", "--json"], config_home);
+    if !list.success() {
+        problems.push(describe("--list --json", &list));
+    } else if !serde_json::from_str::<Value>(list.stdout.trim()).is_ok_and(|v| v.is_array()) {
+        problems.push(format!("--list --json did not print a JSON array: {}", list.stdout.trim()));
+    }
+
+    let check = run(binary, &[sensor.check_flag], config_home);
+    let available = match check.code() {
+        Some(0) => true,
+        Some(1) => {
+            if check.stdout.trim().is_empty() && check.stderr.trim().is_empty() {
+                problems.push(format!("{} failed without explaining why", sensor.check_flag));
+            }
+            false
+        }
+        _ => {
+            problems.push(describe(sensor.check_flag, &check));
+            false
+        }
+    };
+
+    let once = run(binary, &["--once"], config_home);
+    if once.timed_out() || once.code() == Some(PANIC_EXIT_CODE) {
+        problems.push(describe("--once", &once));
+        return problems;
+    }
+
+    let last_line = once.stdout.lines().rev().find(|l| !l.trim().is_empty());
+    match (available, last_line) {
+        (true, Some(line)) => {
+            if !once.success() {
+                problems.push(describe("--once", &once));
+            }
+            if let Err(e) = validate_output(line) {
+                problems.push(format!("--once emitted invalid output: {} ({})", e, line));
+            }
+        }
+        (true, None) => problems.push("--once produced no output on available hardware".to_string()),
+        // Absent hardware may fail cleanly or emit an error output, but
+        // anything it prints on stdout must still be valid for Waybar.
+        (false, Some(line)) if once.success() => {
+            if let Err(e) = validate_output(line) {
+                problems.push(format!("--once emitted invalid output: {} ({})", e, line));
+            }
+        }
+        (false, _) => {}
+    }
+
+    problems
+}
+
+/// Run a sensor against its fixture tree, where the hardware must be found
+/// and read, and against an empty root, where it must be reported missing.
+fn check_fixture(binary: &Path, sensor: &SensorBin, fixture: &Path, empty_root: &Path, config_home: &Path) -> Vec<String> {
+    if !fixture.is_dir() {
+        return vec![format!("fixture not found at {}", fixture.display())];
+    }
+    let mut problems = Vec::new();
+    let fixture = fixture.to_string_lossy();
+    let empty_root = empty_root.to_string_lossy();
+
+    let check = run(binary, &[sensor.check_flag, "--sys-root", &fixture], config_home);
+    if !check.success() {
+        problems.push(describe(&format!("{} on the fixture", sensor.check_flag), &check));
+    }
+
+    let once = run(binary, &["--once", "--sys-root", &fixture], config_home);
+    match once.stdout.lines().rev().find(|l| !l.trim().is_empty()) {
+        _ if !once.success() => problems.push(describe("--once on the fixture", &once)),
+        Some(line) => {
+            if let Err(e) = validate_output(line) {
+                problems.push(format!("--once on the fixture emitted invalid output: {} ({})", e, line));
+            }
+        }
+        None => problems.push("--once on the fixture produced no output".to_string()),
+    }
+
+    let list = run(binary, &["--list", "--json", "--sys-root", &fixture], config_home);
+    if !list.success() {
+        problems.push(describe("--list --json on the fixture", &list));
+    } else if !serde_json::from_str::<Value>(list.stdout.trim())
+        .is_ok_and(|v| v.as_array().is_some_and(|a| !a.is_empty()))
+    {
+        problems.push(format!("--list --json did not list the fixture's devices: {}", list.stdout.trim()));
+    }
+
+    // Absent hardware is exit 1 with an explanation, not a crash
+    let absent = run(binary, &[sensor.check_flag, "--sys-root", &empty_root], config_home);
+    match absent.code() {
+        Some(1) if absent.stdout.trim().is_empty() && absent.stderr.trim().is_empty() => {
+            problems.push(format!("{} on an empty root exited 1 without explaining why", sensor.check_flag));
+        }
+        Some(1) => {}
+        Some(0) => problems.push(format!("{} found hardware in an empty root", sensor.check_flag)),
+        _ => problems.push(describe(&format!("{} on an empty root", sensor.check_flag), &absent)),
+    }
+
+    problems
+}
+
+fn describe(what: &str, run: &Run) -> String {
+    let outcome = match run.status {
+        None => format!("timed out after {}s", TIMEOUT.as_secs()),
+        Some(status) => match status.code() {
+            Some(PANIC_EXIT_CODE) => "panicked".to_string(),
+            Some(code) => format!("exited with {}", code),
+            None => "killed by a signal".to_string(),
+        },
+    };
+    let stderr = run.stderr.lines().next().unwrap_or_default();
+    format!("{} {}: {}", what, outcome, stderr)
+}
+
+/// Run a binary with a timeout, capturing stdout and stderr.
+fn run(binary: &Path, args: &[&str], config_home: &Path) -> Run {
+    let child = Command::new(binary)
+        .args(args)
+        .env("XDG_CONFIG_HOME", config_home)
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            return Run { status: None, stdout: String::new(), stderr: e.to_string() };
+        }
+    };
+
+    // Drain the pipes on separate threads so a chatty sensor can't block on a full pipe
+    let mut stdout_pipe = child.stdout.take().expect("stdout is piped");
+    let mut stderr_pipe = child.stderr.take().expect("stderr is piped");
+    let stdout_reader = thread::spawn(move || {
+        let mut buf = String::new();
+        let _ = stdout_pipe.read_to_string(&mut buf);
+        buf
+    });
+    let stderr_reader = thread::spawn(move || {
+        let mut buf = String::new();
+        let _ = stderr_pipe.read_to_string(&mut buf);
+        buf
+    });
+
+    let deadline = Instant::now() + TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(20)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                break None;
+            }
+        }
+    };
+
+    Run {
+        status,
+        stdout: stdout_reader.join().unwrap_or_default(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+    }
+}
+
+/// Validate a line of sensor output against Waybar's custom module JSON protocol.
+fn validate_output(line: &str) -> Result<(), String> {
+    let value: Value = serde_json::from_str(line).map_err(|e| format!("not JSON: {}", e))?;
+    let object = value.as_object().ok_or("not a JSON object")?;
+
+    match object.get("text") {
+        Some(Value::String(_)) => {}
+        Some(_) => return Err("\"text\" is not a string".to_string()),
+        None => return Err("missing \"text\"".to_string()),
+    }
+
+    for key in ["alt", "tooltip", "class"] {
+        if let Some(value) = object.get(key).filter(|v| !v.is_string()) {
+            return Err(format!("\"{}\" is not a string: {}", key, value));
+        }
+    }
+
+    if let Some(percentage) = object.get("percentage") {
+        match percentage.as_u64() {
+            Some(p) if p <= 100 => {}
+            _ => return Err(format!("\"percentage\" is not an integer in 0-100: {}", percentage)),
+        }
+    }
+
+    if let Some(key) = object.keys().find(|k| !WAYBAR_KEYS.contains(&k.as_str())) {
+        return Err(format!("unknown key \"{}\"", key));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_output() {
+        assert!(validate_output(r#"{"text":"42%"}"#).is_ok());
+        assert!(validate_output(r#"{"text":" 5%","tooltip":"CPU","class":"normal","percentage":5}"#).is_ok());
+
+        assert!(validate_output("CPU 42%").is_err());
+        assert!(validate_output(r#"["42%"]"#).is_err());
+        assert!(validate_output(r#"{"tooltip":"no text"}"#).is_err());
+        assert!(validate_output(r#"{"text":42}"#).is_err());
+        assert!(validate_output(r#"{"text":"x","percentage":101}"#).is_err());
+        assert!(validate_output(r#"{"text":"x","percentage":-1}"#).is_err());
+        assert!(validate_output(r#"{"text":"x","class":["a"]}"#).is_err());
+        assert!(validate_output(r#"{"text":"x","colour":"red"}"#).is_err());
+    }
+
+    #[test]
+    fn test_fixtures_exist() {
+        let fixtures = workspace_root().join("xtask").join("fixtures");
+        for fixture in SENSORS.iter().filter_map(|s| s.fixture) {
+            assert!(fixtures.join(fixture).is_dir(), "missing fixture {}", fixture);
+        }
+    }
+}